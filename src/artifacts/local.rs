@@ -0,0 +1,228 @@
+//! Local-filesystem artifact store.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures::future::BoxFuture;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::retention::{select_for_deletion, StoredFile};
+use super::{content_type_for, new_artifact_id, parse_artifact_id, ArtifactStore, RetentionPolicy};
+use crate::models::{Artifact, ArtifactKind, GcReport};
+
+/// Stores artifacts under `<root>/<kind>/<session>/<uuid>.<ext>`.
+pub struct LocalArtifactStore {
+    root: PathBuf,
+}
+
+impl LocalArtifactStore {
+    /// Create a store rooted at `root` (created if missing).
+    pub fn new(root: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&root)
+            .with_context(|| format!("Failed to create artifact directory {}", root.display()))?;
+        Ok(Self { root })
+    }
+
+    fn path_for(&self, id: &str) -> PathBuf {
+        self.root.join(id)
+    }
+}
+
+/// List every artifact file under `root` (blocking).
+fn scan(root: &Path) -> Result<Vec<StoredFile>> {
+    let mut files = Vec::new();
+
+    for kind_dir in std::fs::read_dir(root)?.flatten() {
+        let Ok(sessions) = std::fs::read_dir(kind_dir.path()) else {
+            continue;
+        };
+        for session_dir in sessions.flatten() {
+            let Ok(entries) = std::fs::read_dir(session_dir.path()) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(meta) = entry.metadata() else { continue };
+                if !meta.is_file() {
+                    continue;
+                }
+                files.push(StoredFile {
+                    id: format!(
+                        "{}/{}/{}",
+                        kind_dir.file_name().to_string_lossy(),
+                        session_dir.file_name().to_string_lossy(),
+                        entry.file_name().to_string_lossy()
+                    ),
+                    session: session_dir.file_name().to_string_lossy().into_owned(),
+                    size: meta.len(),
+                    modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+impl ArtifactStore for LocalArtifactStore {
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+
+    fn put(
+        &self,
+        kind: ArtifactKind,
+        session: &str,
+        extension: &str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'_, Result<Artifact>> {
+        let id = new_artifact_id(kind, session, extension);
+        Box::pin(async move {
+            let path = self.path_for(&id);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, &data)
+                .await
+                .with_context(|| format!("Failed to write artifact {}", path.display()))?;
+
+            Ok(Artifact {
+                content_type: content_type_for(&id).to_string(),
+                kind,
+                size: data.len() as u64,
+                url: format!("file://{}", path.display()),
+                created_at: Utc::now().to_rfc3339(),
+                id,
+            })
+        })
+    }
+
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            parse_artifact_id(id)?;
+            tokio::fs::read(self.path_for(id))
+                .await
+                .with_context(|| format!("Artifact not found: {}", id))
+        })
+    }
+
+    fn collect_garbage<'a>(
+        &'a self,
+        policy: &'a RetentionPolicy,
+    ) -> BoxFuture<'a, Result<GcReport>> {
+        let root = self.root.clone();
+        let policy = policy.clone();
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || -> Result<GcReport> {
+                let files = scan(&root)?;
+                let doomed = select_for_deletion(&files, &policy, SystemTime::now());
+
+                let mut report = GcReport::default();
+                for &i in &doomed {
+                    match std::fs::remove_file(root.join(&files[i].id)) {
+                        Ok(()) => {
+                            report.deleted += 1;
+                            report.freed_bytes += files[i].size;
+                        }
+                        Err(e) => tracing::debug!("Failed to delete {}: {}", files[i].id, e),
+                    }
+                }
+
+                let total: u64 = files.iter().map(|f| f.size).sum();
+                report.remaining = files.len() - report.deleted;
+                report.remaining_bytes = total - report.freed_bytes;
+                Ok(report)
+            })
+            .await
+            .context("Artifact GC task failed")?
+        })
+    }
+
+    fn free_bytes(&self) -> Option<u64> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(self.root.as_os_str().as_bytes()).ok()?;
+        // SAFETY: statvfs only writes into `stat`; `path` is NUL-terminated
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        // Field widths differ between Linux and macOS
+        #[allow(clippy::unnecessary_cast)]
+        let free = stat.f_bavail as u64 * stat.f_frsize as u64;
+        Some(free)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_store() -> (LocalArtifactStore, PathBuf) {
+        let root =
+            std::env::temp_dir().join(format!("fgp-artifacts-test-{}", uuid::Uuid::new_v4()));
+        (LocalArtifactStore::new(root.clone()).unwrap(), root)
+    }
+
+    #[tokio::test]
+    async fn test_put_then_get() {
+        let (store, root) = temp_store();
+
+        let artifact = store
+            .put(
+                ArtifactKind::Screenshot,
+                "default",
+                "png",
+                b"png-bytes".to_vec(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(artifact.size, 9);
+        assert_eq!(artifact.content_type, "image/png");
+        assert!(artifact.url.starts_with("file://"));
+
+        assert_eq!(store.get(&artifact.id).await.unwrap(), b"png-bytes");
+        assert!(store.get("screenshot/default/../secret").await.is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage_applies_session_cap() {
+        let (store, root) = temp_store();
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let artifact = store
+                .put(ArtifactKind::Screenshot, "crawl", "png", vec![0; 10])
+                .await
+                .unwrap();
+            ids.push(artifact.id);
+            // Distinct mtimes so "newest" is well defined
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_total_bytes: None,
+            max_per_session: Some(2),
+            interval: Duration::from_secs(60),
+        };
+        let report = store.collect_garbage(&policy).await.unwrap();
+
+        assert_eq!(report.deleted, 1);
+        assert_eq!(report.freed_bytes, 10);
+        assert_eq!(report.remaining, 2);
+        assert!(store.get(&ids[0]).await.is_err(), "oldest should be gone");
+        assert!(store.get(&ids[2]).await.is_ok());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_free_bytes_reports_disk() {
+        let (store, root) = temp_store();
+        assert!(store.free_bytes().is_some());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,163 @@
+//! Artifact storage.
+//!
+//! Screenshots, PDFs, HARs, recordings, and downloads are handed to an
+//! [`ArtifactStore`], which returns an [`Artifact`] with a stable ID and a
+//! backend URL instead of a raw host path. IDs have the form
+//! `<kind>/<session>/<uuid>.<ext>` and resolve through `browser.artifact.get`
+//! whatever the backend.
+//!
+//! The local-filesystem store is the default; an S3-compatible store (AWS S3,
+//! MinIO, R2, ...) is selected with `start --artifact-store s3`.
+
+mod local;
+mod retention;
+mod s3;
+
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+
+use crate::models::{Artifact, ArtifactKind, GcReport};
+
+pub use local::LocalArtifactStore;
+pub use retention::{run_gc_loop, RetentionPolicy};
+pub use s3::{S3ArtifactStore, S3Config};
+
+/// Storage backend for artifacts.
+pub trait ArtifactStore: Send + Sync {
+    /// Backend name, reported by `health`.
+    fn backend(&self) -> &'static str;
+
+    /// Store `data` as a new artifact of `session` with the given file extension.
+    fn put(
+        &self,
+        kind: ArtifactKind,
+        session: &str,
+        extension: &str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'_, Result<Artifact>>;
+
+    /// Read an artifact's bytes by ID.
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Vec<u8>>>;
+
+    /// Delete artifacts that fall outside `policy`.
+    ///
+    /// Backends with their own expiry (e.g. S3 lifecycle rules) may leave this
+    /// as a no-op.
+    fn collect_garbage<'a>(
+        &'a self,
+        _policy: &'a RetentionPolicy,
+    ) -> BoxFuture<'a, Result<GcReport>> {
+        Box::pin(async { Ok(GcReport::default()) })
+    }
+
+    /// Free space available to the store, for the `health` self-test.
+    ///
+    /// None when the backend has no meaningful local limit.
+    fn free_bytes(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Allocate a fresh artifact ID.
+pub fn new_artifact_id(kind: ArtifactKind, session: &str, extension: &str) -> String {
+    format!(
+        "{}/{}/{}.{}",
+        kind.as_str(),
+        sanitize_session(session),
+        uuid::Uuid::new_v4(),
+        extension
+    )
+}
+
+/// Session segment of an artifact ID (safe as a path component).
+fn sanitize_session(session: &str) -> String {
+    let cleaned: String = session
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if cleaned.is_empty() {
+        "default".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Validate an artifact ID and return its kind and session.
+///
+/// Rejects anything that could address a file outside the store's root.
+pub fn parse_artifact_id(id: &str) -> Result<(ArtifactKind, &str)> {
+    let invalid = || format!("Invalid artifact ID: {}", id);
+
+    let mut parts = id.split('/');
+    let (Some(prefix), Some(session), Some(name), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        anyhow::bail!(invalid());
+    };
+
+    let kind = ArtifactKind::from_prefix(prefix)
+        .with_context(|| format!("Unknown artifact kind in ID: {}", id))?;
+
+    if session != sanitize_session(session)
+        || name.is_empty()
+        || name.starts_with('.')
+        || name.contains('\\')
+    {
+        anyhow::bail!(invalid());
+    }
+
+    Ok((kind, session))
+}
+
+/// MIME type for an artifact file extension.
+pub fn content_type_for(id: &str) -> &'static str {
+    match id.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("pdf") => "application/pdf",
+        Some("har") | Some("json") => "application/json",
+        Some("webm") => "video/webm",
+        Some("mp4") => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_id_roundtrip() {
+        let id = new_artifact_id(ArtifactKind::Screenshot, "gmail", "png");
+        assert!(id.starts_with("screenshot/gmail/"));
+        assert_eq!(
+            parse_artifact_id(&id).unwrap(),
+            (ArtifactKind::Screenshot, "gmail")
+        );
+        assert_eq!(content_type_for(&id), "image/png");
+    }
+
+    #[test]
+    fn test_session_is_sanitized() {
+        let id = new_artifact_id(ArtifactKind::Pdf, "../evil session", "pdf");
+        assert!(id.starts_with("pdf/___evil_session/"));
+        assert!(parse_artifact_id(&id).is_ok());
+    }
+
+    #[test]
+    fn test_parse_artifact_id_rejects_escapes() {
+        assert!(parse_artifact_id("screenshot/default/../../etc/passwd").is_err());
+        assert!(parse_artifact_id("screenshot/../x.png").is_err());
+        assert!(parse_artifact_id("screenshot/default/..").is_err());
+        assert!(parse_artifact_id("screenshot/default/").is_err());
+        assert!(parse_artifact_id("secrets/default/key.pem").is_err());
+        assert!(parse_artifact_id("screenshot/x.png").is_err());
+    }
+}
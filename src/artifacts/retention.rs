@@ -0,0 +1,194 @@
+//! Artifact retention policies and background garbage collection.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use super::ArtifactStore;
+use crate::models::GcReport;
+
+/// Limits applied to stored artifacts. `None` disables a limit.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// Delete artifacts older than this
+    pub max_age: Option<Duration>,
+    /// Delete the oldest artifacts while the total exceeds this many bytes
+    pub max_total_bytes: Option<u64>,
+    /// Keep at most this many artifacts per session (newest first)
+    pub max_per_session: Option<usize>,
+    /// How often the background GC runs
+    pub interval: Duration,
+}
+
+/// Keeps everything: deleting artifacts is opt-in.
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age: None,
+            max_total_bytes: None,
+            max_per_session: None,
+            interval: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Whether any limit is set.
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_total_bytes.is_some() || self.max_per_session.is_some()
+    }
+}
+
+/// A stored artifact as seen by the GC.
+#[derive(Debug, Clone)]
+pub(super) struct StoredFile {
+    pub id: String,
+    pub session: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// Indices of `files` that fall outside `policy` at time `now`.
+///
+/// Age is applied first, then the per-session cap (keeping each session's
+/// newest), then the total size cap (evicting the oldest overall).
+pub(super) fn select_for_deletion(
+    files: &[StoredFile],
+    policy: &RetentionPolicy,
+    now: SystemTime,
+) -> Vec<usize> {
+    let mut doomed = vec![false; files.len()];
+
+    if let Some(max_age) = policy.max_age {
+        for (i, file) in files.iter().enumerate() {
+            let age = now.duration_since(file.modified).unwrap_or_default();
+            doomed[i] = age > max_age;
+        }
+    }
+
+    // Newest first, so caps keep the most recent artifacts
+    let mut by_recency: Vec<usize> = (0..files.len()).collect();
+    by_recency.sort_by(|&a, &b| files[b].modified.cmp(&files[a].modified));
+
+    if let Some(cap) = policy.max_per_session {
+        let mut kept: HashMap<&str, usize> = HashMap::new();
+        for &i in &by_recency {
+            if doomed[i] {
+                continue;
+            }
+            let count = kept.entry(files[i].session.as_str()).or_default();
+            if *count >= cap {
+                doomed[i] = true;
+            } else {
+                *count += 1;
+            }
+        }
+    }
+
+    if let Some(max_bytes) = policy.max_total_bytes {
+        let mut total = 0u64;
+        let mut full = false;
+        for &i in &by_recency {
+            if doomed[i] {
+                continue;
+            }
+            // Once the budget is spent, everything older goes too
+            full = full || total + files[i].size > max_bytes;
+            if full {
+                doomed[i] = true;
+            } else {
+                total += files[i].size;
+            }
+        }
+    }
+
+    doomed
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &d)| d.then_some(i))
+        .collect()
+}
+
+/// Run garbage collection on `store` every `policy.interval`, forever.
+pub async fn run_gc_loop(store: Arc<dyn ArtifactStore>, policy: RetentionPolicy) {
+    let mut ticker = tokio::time::interval(policy.interval);
+    loop {
+        ticker.tick().await;
+        match store.collect_garbage(&policy).await {
+            Ok(report) if report.deleted > 0 => tracing::info!(
+                "Artifact GC deleted {} ({} bytes), {} remaining ({} bytes)",
+                report.deleted,
+                report.freed_bytes,
+                report.remaining,
+                report.remaining_bytes
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Artifact GC failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(session: &str, size: u64, age_secs: u64, now: SystemTime) -> StoredFile {
+        StoredFile {
+            id: format!("screenshot/{}/{}.png", session, age_secs),
+            session: session.to_string(),
+            size,
+            modified: now - Duration::from_secs(age_secs),
+        }
+    }
+
+    fn unlimited() -> RetentionPolicy {
+        RetentionPolicy {
+            max_age: None,
+            max_total_bytes: None,
+            max_per_session: None,
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_max_age() {
+        let now = SystemTime::now();
+        let files = vec![file("a", 1, 10, now), file("a", 1, 1000, now)];
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(100)),
+            ..unlimited()
+        };
+        assert_eq!(select_for_deletion(&files, &policy, now), vec![1]);
+    }
+
+    #[test]
+    fn test_per_session_cap_keeps_newest() {
+        let now = SystemTime::now();
+        let files = vec![
+            file("a", 1, 30, now),
+            file("a", 1, 10, now),
+            file("a", 1, 20, now),
+            file("b", 1, 40, now),
+        ];
+        let policy = RetentionPolicy {
+            max_per_session: Some(2),
+            ..unlimited()
+        };
+        assert_eq!(select_for_deletion(&files, &policy, now), vec![0]);
+    }
+
+    #[test]
+    fn test_total_size_evicts_oldest() {
+        let now = SystemTime::now();
+        let files = vec![
+            file("a", 40, 30, now),
+            file("b", 40, 10, now),
+            file("a", 40, 20, now),
+        ];
+        let policy = RetentionPolicy {
+            max_total_bytes: Some(100),
+            ..unlimited()
+        };
+        assert_eq!(select_for_deletion(&files, &policy, now), vec![0]);
+    }
+}
@@ -3,96 +3,298 @@
 //! Extracts interactive elements and injects `data-fgp-ref` attributes onto
 //! the DOM so that `@eN` refs returned in snapshots can be used by interaction
 //! methods (click, fill, etc.) via `resolve_selector`.
+//!
+//! Refs are stable: a DOM node keeps its `@eN` across snapshots of the same
+//! document (keyed on its backend node ID, see [`RefRegistry`]), and numbers
+//! are never reused, so a ref to a node that has since been removed fails
+//! to resolve instead of hitting whatever element took its place.
+//!
+//! Open shadow roots are part of the snapshot: Chrome's accessibility tree
+//! already spans them, and the ref bookkeeping and DOM fallback search them
+//! with the `shadow` helpers, so web components don't come back empty.
+//! Child frames are snapshotted one by one, those in other processes
+//! through their own sessions, with frame-qualified refs (see `frames`).
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 use anyhow::{Context, Result};
 use chromiumoxide::cdp::browser_protocol::accessibility::{
-    AxNode as CdpAxNode, AxProperty, AxPropertyName, GetFullAxTreeParams,
+    AxNode as CdpAxNode, AxPropertyName, GetFullAxTreeParams,
+};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    BackendNodeId, GetDocumentParams, PushNodesByBackendIdsToFrontendParams,
+    SetAttributeValueParams,
 };
-use chromiumoxide::cdp::browser_protocol::dom::{DescribeNodeParams, SetAttributeValueParams};
+use chromiumoxide::cdp::browser_protocol::page::{FrameId, GetLayoutMetricsParams};
 use chromiumoxide::page::Page;
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
 
-use crate::models::AriaNode;
+use super::frames::{self, ChildFrame};
+use super::oopif::{Connection, Remote};
+use super::own_mutations;
+use super::shadow;
+use crate::models::{AriaNode, ElementRect};
 
-/// Clear stale `data-fgp-ref` attributes from previous snapshots.
-async fn clear_old_refs(page: &Page) {
-    let _ = page
-        .evaluate(
-            "document.querySelectorAll('[data-fgp-ref]').forEach(el => el.removeAttribute('data-fgp-ref'))",
-        )
-        .await;
+/// Bounding boxes of rendered elements carrying a ref, keyed by ref
+/// (without `@`).
+const RECTS_SCRIPT: &str = r#"(() => {
+    const rects = {};
+    for (const el of deepQueryAll('[data-fgp-ref]')) {
+        const r = el.getBoundingClientRect();
+        if (r.width === 0 && r.height === 0) continue;
+        rects[el.getAttribute('data-fgp-ref')] = { x: r.x, y: r.y, width: r.width, height: r.height };
+    }
+    return rects;
+})"#;
+
+/// Ref numbers handed out on one document, so a node keeps its `@eN` for as
+/// long as it stays in the DOM. Reset when the page loads a new document.
+#[derive(Debug, Default, Clone)]
+pub struct RefRegistry {
+    /// `performance.timeOrigin` of the document the numbers belong to
+    document: Option<String>,
+    /// Ref number by backend node ID
+    numbers: HashMap<i64, usize>,
+    /// Highest number handed out so far
+    last: usize,
+    /// Frames numbered for frame-qualified refs; frame `n` is at `n - 1`
+    frames: Vec<FrameId>,
 }
 
-/// Inject `data-fgp-ref` attributes onto DOM elements for CDP-extracted nodes.
-///
-/// Uses `backend_dom_node_id` from each CDP AxNode to resolve the DOM element
-/// via CDP, then sets `data-fgp-ref="eN"` so that `resolve_selector("@eN")`
-/// can find it later.
-async fn inject_refs_for_cdp_nodes(page: &Page, cdp_nodes: &[&CdpAxNode], aria_nodes: &[AriaNode]) {
-    for (cdp_node, aria_node) in cdp_nodes.iter().zip(aria_nodes.iter()) {
-        let backend_id = match cdp_node.backend_dom_node_id {
-            Some(id) => id,
-            None => continue,
+impl RefRegistry {
+    /// Forget every ref if `document` isn't the one they were handed out on.
+    fn start_document(&mut self, document: String) {
+        if self.document.as_ref() != Some(&document) {
+            *self = Self {
+                document: Some(document),
+                ..Self::default()
+            };
+        }
+    }
+
+    /// The number already given to `backend_id`, or the next unused one.
+    fn number_for(&mut self, backend_id: Option<BackendNodeId>) -> usize {
+        let last = &mut self.last;
+        let mut next = || {
+            *last += 1;
+            *last
         };
+        match backend_id {
+            Some(id) => *self.numbers.entry(*id.inner()).or_insert_with(next),
+            None => next(),
+        }
+    }
+
+    /// The number already given to `frame`, or the next unused one.
+    fn frame_number_for(&mut self, frame: &FrameId) -> usize {
+        match self.frames.iter().position(|f| f == frame) {
+            Some(index) => index + 1,
+            None => {
+                self.frames.push(frame.clone());
+                self.frames.len()
+            }
+        }
+    }
 
-        // Strip the "@" prefix: "@e5" -> "e5"
-        let ref_value = &aria_node.ref_id[1..];
+    /// The frame numbered `number` on the current document.
+    pub fn frame(&self, number: usize) -> Option<FrameId> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.frames.get(index))
+            .cloned()
+    }
+}
+
+fn lock(refs: &Mutex<RefRegistry>) -> std::sync::MutexGuard<'_, RefRegistry> {
+    refs.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Identity of the page's current document.
+async fn document_id(page: &Page) -> Result<String> {
+    page.evaluate("String(performance.timeOrigin)")
+        .await
+        .context("Failed to identify the document")?
+        .into_value()
+        .context("Failed to parse the document identity")
+}
+
+/// Clear stale `data-fgp-ref` attributes from previous snapshots in `frame`
+/// (default: the page).
+async fn clear_old_refs(page: &Page, frame: Option<&ChildFrame>) {
+    let _ = frames::evaluate::<JsonValue>(
+        page,
+        frame,
+        shadow::call(
+            "(() => deepQueryAll('[data-fgp-ref]').forEach(el => el.removeAttribute('data-fgp-ref')))",
+            "",
+        ),
+    )
+    .await;
+}
 
-        // Resolve BackendNodeId -> NodeId via DOM.describeNode
-        let describe_result = page
-            .execute(
-                DescribeNodeParams::builder()
-                    .backend_node_id(backend_id)
-                    .build(),
+/// Inject `data-fgp-ref` attributes onto DOM elements for CDP-extracted nodes,
+/// in `remote`'s process (default: the page's).
+///
+/// All backend node IDs are pushed to the frontend in a single
+/// `DOM.pushNodesByBackendIdsToFrontend` call, then the attribute writes are
+/// pipelined concurrently instead of doing two sequential round-trips per node.
+async fn inject_refs(page: &Page, remote: Option<&Remote>, targets: &[(BackendNodeId, String)]) {
+    if targets.is_empty() {
+        return;
+    }
+
+    // The DOM agent only tracks nodes once the document has been requested
+    if frames::execute(page, remote, GetDocumentParams::default())
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    let backend_ids: Vec<BackendNodeId> = targets.iter().map(|(id, _)| *id).collect();
+    let node_ids = match frames::execute(
+        page,
+        remote,
+        PushNodesByBackendIdsToFrontendParams::new(backend_ids),
+    )
+    .await
+    {
+        Ok(result) => result.node_ids,
+        Err(e) => {
+            tracing::debug!("Failed to push backend nodes to frontend: {}", e);
+            return;
+        }
+    };
+
+    let writes = node_ids
+        .into_iter()
+        .zip(targets.iter())
+        // NodeId 0 means the backend node could not be resolved
+        .filter(|(node_id, _)| *node_id.inner() != 0)
+        .map(|(node_id, (_, ref_value))| {
+            frames::execute(
+                page,
+                remote,
+                SetAttributeValueParams::new(node_id, "data-fgp-ref", ref_value.as_str()),
             )
-            .await;
+        });
 
-        let node_id = match describe_result {
-            Ok(result) => result.node.node_id,
-            Err(_) => continue,
+    futures::future::join_all(writes).await;
+}
+
+/// Give the element `backend_id` in `frame` (default: the page) a ref, the
+/// one it already has if a snapshot numbered it, and return the ref.
+pub async fn assign_ref(
+    page: &Page,
+    refs: &Mutex<RefRegistry>,
+    frame: Option<&ChildFrame>,
+    backend_id: BackendNodeId,
+) -> Result<String> {
+    let document = document_id(page).await?;
+    let ref_value = {
+        let mut registry = lock(refs);
+        registry.start_document(document);
+        let prefix = match frame {
+            Some(frame) => format!("f{}", registry.frame_number_for(&frame.id)),
+            None => String::new(),
         };
+        format!("{}e{}", prefix, registry.number_for(Some(backend_id)))
+    };
+    let remote = frame.and_then(|frame| frame.remote.as_ref());
+    inject_refs(page, remote, &[(backend_id, ref_value.clone())]).await;
+    Ok(format!("@{}", ref_value))
+}
 
-        // Set the data-fgp-ref attribute on the DOM element
-        let _ = page
-            .execute(SetAttributeValueParams::new(
-                node_id,
-                "data-fgp-ref",
-                ref_value,
-            ))
-            .await;
+/// Build flattened `AriaNode`s from `Accessibility.getFullAXTree` in one pass.
+///
+/// The CDP response is already a flat list, so we walk it once, skip ignored
+/// nodes, and collect the backend node IDs that need a ref attribute.
+/// Returns the nodes plus `(backend_id, "eN")` pairs for ref injection.
+/// Refs of a child frame's nodes start with its `frame` prefix (`f2`).
+fn build_flat_nodes(
+    cdp_nodes: &[CdpAxNode],
+    refs: &mut RefRegistry,
+    frame: &str,
+) -> (Vec<AriaNode>, Vec<(BackendNodeId, String)>) {
+    let capacity = cdp_nodes.len() / 4; // Most nodes filtered out
+    let mut nodes = Vec::with_capacity(capacity);
+    let mut ref_targets = Vec::with_capacity(capacity);
+    // A DOM node behind several AX nodes keeps its ref on the first only
+    let mut seen = HashSet::new();
+
+    for node in cdp_nodes {
+        if node.ignored {
+            continue;
+        }
+        if !(is_interactive_node(node) || has_role_or_name(node)) {
+            continue;
+        }
+
+        let backend_id = node
+            .backend_dom_node_id
+            .filter(|id| seen.insert(*id.inner()));
+        let aria_node = convert_node_ref(node, frame, refs.number_for(backend_id));
+        if let Some(backend_id) = backend_id {
+            // Strip the "@" prefix: "@e5" -> "e5"
+            ref_targets.push((backend_id, aria_node.ref_id[1..].to_string()));
+        }
+        nodes.push(aria_node);
     }
+
+    (nodes, ref_targets)
+}
+
+/// Extract ARIA accessibility tree from page, numbering refs with `refs`.
+/// Frames in other processes are included when `connection` reaches them.
+pub async fn extract_aria_tree(
+    page: &Page,
+    refs: &Mutex<RefRegistry>,
+    connection: Option<&Connection>,
+) -> Result<Vec<AriaNode>> {
+    extract(page, refs, connection, true).await
 }
 
-/// Extract ARIA accessibility tree from page.
-pub async fn extract_aria_tree(page: &Page) -> Result<Vec<AriaNode>> {
-    let mut counter = 0;
+/// Like `extract_aria_tree`, but leaves the page and `refs` as they are:
+/// refs are numbered on a copy of the registry and not injected, so only
+/// nodes an earlier snapshot tagged get geometry.
+pub async fn read_aria_tree(
+    page: &Page,
+    refs: &Mutex<RefRegistry>,
+    connection: Option<&Connection>,
+) -> Result<Vec<AriaNode>> {
+    let scratch = Mutex::new(lock(refs).clone());
+    extract(page, &scratch, connection, false).await
+}
 
-    // Clear stale refs from previous snapshots
-    clear_old_refs(page).await;
+async fn extract(
+    page: &Page,
+    refs: &Mutex<RefRegistry>,
+    connection: Option<&Connection>,
+    inject: bool,
+) -> Result<Vec<AriaNode>> {
+    let document = document_id(page).await?;
+    lock(refs).start_document(document);
 
     // Try CDP accessibility tree first
     if let Ok(response) = page.execute(GetFullAxTreeParams::default()).await {
-        // Single-pass extraction - no clones, references only
-        let capacity = response.nodes.len() / 4; // Most nodes filtered out
-        let mut included_cdp_nodes: Vec<&CdpAxNode> = Vec::with_capacity(capacity);
-        let mut nodes = Vec::with_capacity(capacity);
-
-        for node in &response.nodes {
-            if is_interactive_node(node) || has_role_or_name(node) {
-                included_cdp_nodes.push(node);
-                nodes.push(convert_node_ref(node, &mut counter));
-            }
-        }
+        let (mut nodes, ref_targets) = build_flat_nodes(&response.nodes, &mut lock(refs), "");
 
         if !nodes.is_empty() {
-            // Inject data-fgp-ref attributes onto the actual DOM elements
+            // Move data-fgp-ref attributes onto the actual DOM elements
             // so that resolve_selector("@eN") can find them later
-            inject_refs_for_cdp_nodes(page, &included_cdp_nodes, &nodes).await;
+            if inject {
+                clear_old_refs(page, None).await;
+                inject_refs(page, None, &ref_targets).await;
+            }
+            measure(page, None, &mut nodes).await;
+            add_frames(page, refs, connection, ref_targets, inject, &mut nodes).await;
 
             tracing::debug!(
-                "Extracted {} nodes from CDP accessibility tree",
-                nodes.len()
+                "Extracted {} of {} nodes from CDP accessibility tree",
+                nodes.len(),
+                response.nodes.len()
             );
             return Ok(nodes);
         }
@@ -101,11 +303,229 @@ pub async fn extract_aria_tree(page: &Page) -> Result<Vec<AriaNode>> {
     // Fallback to DOM traversal - more reliable on macOS
     // This path injects data-fgp-ref attributes directly in the JS
     tracing::debug!("CDP accessibility tree empty, falling back to DOM traversal");
-    let nodes = extract_dom_interactives(page, &mut counter).await?;
+    let last = lock(refs).last;
+    let (mut nodes, last) = extract_dom_interactives(page, last, inject).await?;
+    let mut registry = lock(refs);
+    registry.last = registry.last.max(last);
+    drop(registry);
+    measure(page, None, &mut nodes).await;
 
     Ok(nodes)
 }
 
+/// Snapshot each child frame and put its nodes under the frame's `Iframe`
+/// node (at the end, if that isn't in the tree). `page_targets` are the
+/// page's ref targets. A frame Chrome can't give a tree for (gone since it
+/// was listed, or out of process without a `connection`) is left out.
+async fn add_frames(
+    page: &Page,
+    refs: &Mutex<RefRegistry>,
+    connection: Option<&Connection>,
+    page_targets: Vec<(BackendNodeId, String)>,
+    inject: bool,
+    nodes: &mut Vec<AriaNode>,
+) {
+    // Backend node IDs are per process: keep each target's session with it
+    let mut owners: Vec<(Option<Remote>, BackendNodeId, String)> = page_targets
+        .into_iter()
+        .map(|(id, ref_value)| (None, id, ref_value))
+        .collect();
+    let child_frames = match frames::child_frames(page, connection).await {
+        Ok(child_frames) => child_frames,
+        Err(e) => {
+            tracing::debug!("Failed to list frames: {}", e);
+            return;
+        }
+    };
+
+    for frame in child_frames {
+        let params = GetFullAxTreeParams::builder()
+            .frame_id(frame.id.clone())
+            .build();
+        let response = match frames::execute(page, frame.remote.as_ref(), params).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::debug!("Skipping frame {}: {}", frame.id.inner(), e);
+                continue;
+            }
+        };
+        let prefix = format!("f{}", lock(refs).frame_number_for(&frame.id));
+        let (mut frame_nodes, targets) =
+            build_flat_nodes(&response.nodes, &mut lock(refs), &prefix);
+        if frame_nodes.is_empty() {
+            continue;
+        }
+        if inject {
+            clear_old_refs(page, Some(&frame)).await;
+            inject_refs(page, frame.remote.as_ref(), &targets).await;
+        }
+        measure(page, Some(&frame), &mut frame_nodes).await;
+
+        let owner = frames::owner(page, &frame).await.ok().and_then(|owner| {
+            owners
+                .iter()
+                .find(|(remote, id, _)| {
+                    id.inner() == owner.inner() && Remote::same(remote.as_ref(), frame.holder())
+                })
+                .map(|(_, _, ref_value)| format!("@{}", ref_value))
+        });
+        owners.extend(
+            targets
+                .into_iter()
+                .map(|(id, ref_value)| (frame.remote.clone(), id, ref_value)),
+        );
+        match owner.and_then(|ref_id| node_mut(nodes, &ref_id)) {
+            Some(node) => node.children.extend(frame_nodes),
+            None => nodes.extend(frame_nodes),
+        }
+    }
+}
+
+/// The node with `ref_id`, at any depth.
+fn node_mut<'a>(nodes: &'a mut [AriaNode], ref_id: &str) -> Option<&'a mut AriaNode> {
+    for node in nodes {
+        if node.ref_id == ref_id {
+            return Some(node);
+        }
+        if let Some(found) = node_mut(&mut node.children, ref_id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Fill in each node's bounding box and viewport visibility from the
+/// elements its ref was injected on in `frame` (default: the page).
+/// Geometry is best effort: if the page can't be measured the nodes are
+/// left without it.
+async fn measure(page: &Page, frame: Option<&ChildFrame>, nodes: &mut [AriaNode]) {
+    let metrics = match page.execute(GetLayoutMetricsParams::default()).await {
+        Ok(metrics) => metrics.result,
+        Err(e) => {
+            tracing::debug!("Failed to read layout metrics: {}", e);
+            return;
+        }
+    };
+    let mut rects: HashMap<String, ElementRect> =
+        match frames::evaluate(page, frame, shadow::call(RECTS_SCRIPT, "")).await {
+            Ok(rects) => rects,
+            Err(e) => {
+                tracing::debug!("Failed to measure snapshot elements: {}", e);
+                return;
+            }
+        };
+    // A frame's boxes are relative to its own viewport
+    if let Some(frame) = frame {
+        match frames::origin(page, frame).await {
+            Ok(origin) => {
+                for rect in rects.values_mut() {
+                    rect.x += origin.x;
+                    rect.y += origin.y;
+                }
+            }
+            Err(e) => {
+                tracing::debug!("Failed to place frame {}: {}", frame.id.inner(), e);
+                return;
+            }
+        }
+    }
+
+    let viewport = &metrics.css_layout_viewport;
+    place(
+        nodes,
+        &rects,
+        viewport.client_width as f64,
+        viewport.client_height as f64,
+    );
+}
+
+fn place(nodes: &mut [AriaNode], rects: &HashMap<String, ElementRect>, width: f64, height: f64) {
+    for node in nodes {
+        node.rect = rects.get(node.ref_id.trim_start_matches('@')).copied();
+        node.in_viewport = node
+            .rect
+            .is_some_and(|rect| in_viewport(&rect, width, height));
+        place(&mut node.children, rects, width, height);
+    }
+}
+
+/// Whether any of `rect` overlaps a `width` x `height` viewport.
+fn in_viewport(rect: &ElementRect, width: f64, height: f64) -> bool {
+    rect.x < width && rect.y < height && rect.x + rect.width > 0.0 && rect.y + rect.height > 0.0
+}
+
+/// Page-side marker used to decide whether a cached snapshot is still valid.
+///
+/// `token` identifies the tracker instance (a new document loses it), and
+/// `version` is bumped on every DOM mutation, input/change, focus change,
+/// scroll or resize (snapshots carry element geometry).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MutationMarker {
+    pub token: String,
+    pub version: u64,
+    pub url: String,
+}
+
+/// Install a fresh mutation tracker on the page and return its initial marker.
+///
+/// The gateway's own mutations (see `own_mutations`) are ignored, so ref
+/// injection by the snapshot itself, subtree filters, XPath and chained
+/// selector resolution and debugging overlays drawn by the gateway don't
+/// invalidate the cache.
+pub async fn track_mutations(page: &Page) -> Result<MutationMarker> {
+    let token = uuid::Uuid::new_v4().to_string();
+    let script = format!(
+        r#"((token) => {{
+            const prev = window.__fgpSnapshotTracker;
+            if (prev && prev.observer) prev.observer.disconnect();
+            const state = {{ token, version: 0, observer: null }};
+            {}
+            state.observer = new MutationObserver((records) => {{
+                if (!records.every(ignored)) state.version++;
+            }});
+            state.observer.observe(document, {{
+                subtree: true, childList: true, attributes: true, characterData: true
+            }});
+            if (!prev) {{
+                const bump = () => {{
+                    const s = window.__fgpSnapshotTracker;
+                    if (s) s.version++;
+                }};
+                for (const type of ['input', 'change', 'focusin', 'focusout', 'scroll']) {{
+                    document.addEventListener(type, bump, true);
+                }}
+                window.addEventListener('resize', bump);
+            }}
+            window.__fgpSnapshotTracker = state;
+            return {{ token: state.token, version: state.version, url: location.href }};
+        }})({})"#,
+        own_mutations::ignore_script(),
+        serde_json::to_string(&token)?
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to install mutation tracker")?
+        .into_value()
+        .context("Failed to parse mutation marker")
+}
+
+/// Read the current mutation marker (empty token if no tracker is installed).
+pub async fn read_mutation_marker(page: &Page) -> Result<MutationMarker> {
+    page.evaluate(
+        r#"(() => {
+            const s = window.__fgpSnapshotTracker;
+            return s
+                ? { token: s.token, version: s.version, url: location.href }
+                : { token: '', version: 0, url: location.href };
+        })()"#,
+    )
+    .await
+    .context("Failed to read mutation marker")?
+    .into_value()
+    .context("Failed to parse mutation marker")
+}
+
 /// Check if a node is interactive and should be included.
 fn is_interactive_node(node: &CdpAxNode) -> bool {
     let role_match = node
@@ -152,19 +572,29 @@ fn has_role_or_name(node: &CdpAxNode) -> bool {
 }
 
 fn is_focusable(node: &CdpAxNode) -> bool {
-    node.properties
-        .as_ref()
-        .map(|props: &Vec<AxProperty>| {
-            props.iter().any(|p| {
-                matches!(p.name, AxPropertyName::Focusable)
-                    && p.value
-                        .value
-                        .as_ref()
-                        .and_then(json_as_bool)
-                        .unwrap_or(false)
-            })
-        })
-        .unwrap_or(false)
+    focus_flags(node).0
+}
+
+/// Read `(focusable, focused)` from the node properties in a single scan.
+fn focus_flags(node: &CdpAxNode) -> (bool, bool) {
+    let mut focusable = false;
+    let mut focused = false;
+
+    for prop in node.properties.iter().flatten() {
+        let flag = prop
+            .value
+            .value
+            .as_ref()
+            .and_then(json_as_bool)
+            .unwrap_or(false);
+        match prop.name {
+            AxPropertyName::Focusable => focusable |= flag,
+            AxPropertyName::Focused => focused |= flag,
+            _ => {}
+        }
+    }
+
+    (focusable, focused)
 }
 
 /// Helper to extract string from JSON value.
@@ -177,8 +607,95 @@ fn json_as_bool(v: &JsonValue) -> Option<bool> {
     v.as_bool()
 }
 
+/// Discovers interactive elements (in open shadow roots too), collects ARIA
+/// data, AND (with `write`) injects data-fgp-ref attributes in the same pass,
+/// removing them from elements no longer in the tree.
+const DOM_SNAPSHOT_SCRIPT: &str = r#"((startCounter, write) => {
+    const roleFor = (el) => {
+        const explicit = el.getAttribute && el.getAttribute('role');
+        if (explicit) return explicit;
+        const tag = el.tagName ? el.tagName.toLowerCase() : '';
+        if (tag === 'a') return 'link';
+        if (tag === 'button') return 'button';
+        if (tag === 'img') return 'img';
+        if (tag === 'nav') return 'navigation';
+        if (tag === 'main') return 'main';
+        if (tag === 'article') return 'article';
+        if (tag === 'section') return 'section';
+        if (tag === 'option') return 'option';
+        if (tag === 'select') return 'combobox';
+        if (tag === 'textarea') return 'textbox';
+        if (tag === 'input') {
+            const t = (el.getAttribute('type') || 'text').toLowerCase();
+            if (t === 'checkbox') return 'checkbox';
+            if (t === 'radio') return 'radio';
+            if (t === 'range') return 'slider';
+            if (t === 'search') return 'searchbox';
+            if (t === 'number') return 'spinbutton';
+            return 'textbox';
+        }
+        if (tag && tag.startsWith('h')) return 'heading';
+        if (el.isContentEditable) return 'textbox';
+        return null;
+    };
+    const nameFor = (el) => {
+        const label = el.getAttribute && el.getAttribute('aria-label');
+        if (label) return label;
+        const alt = el.getAttribute && el.getAttribute('alt');
+        if (alt) return alt;
+        const title = el.getAttribute && el.getAttribute('title');
+        if (title) return title;
+        const text = (el.textContent || '').trim();
+        return text.length ? text : null;
+    };
+    const selector = [
+        'a', 'button', 'input', 'select', 'textarea', 'option',
+        '[role]', 'img', 'nav', 'main', 'article', 'section',
+        'h1', 'h2', 'h3', 'h4', 'h5', 'h6', '[contenteditable]'
+    ].join(',');
+    const nodes = [];
+    const seen = new Set();
+    const refs = new Set();
+    const tagged = new Set();
+    let refCounter = startCounter;
+    for (const el of deepQueryAll(selector)) {
+        if (seen.has(el)) continue;
+        seen.add(el);
+        const role = roleFor(el);
+        if (!role) continue;
+        // A cloned element carries a copy of its original's ref
+        let ref = el.getAttribute('data-fgp-ref');
+        if (!ref || refs.has(ref)) {
+            refCounter++;
+            ref = 'e' + refCounter;
+            if (write) el.setAttribute('data-fgp-ref', ref);
+        }
+        refs.add(ref);
+        tagged.add(el);
+        const name = nameFor(el);
+        const value = 'value' in el ? el.value : null;
+        nodes.push({
+            ref,
+            role,
+            name,
+            value,
+            focusable: el.tabIndex >= 0,
+            focused: deepActiveElement() === el,
+        });
+    }
+    if (write) {
+        for (const el of deepQueryAll('[data-fgp-ref]')) {
+            if (!tagged.has(el)) el.removeAttribute('data-fgp-ref');
+        }
+    }
+    return { nodes, last: refCounter };
+})"#;
+
 #[derive(Debug, Deserialize)]
 struct DomSnapshotNode {
+    /// Ref without the `@`
+    #[serde(default, rename = "ref")]
+    ref_id: String,
     role: String,
     #[serde(default)]
     name: Option<String>,
@@ -190,92 +707,36 @@ struct DomSnapshotNode {
     focused: bool,
 }
 
-async fn extract_dom_interactives(page: &Page, counter: &mut usize) -> Result<Vec<AriaNode>> {
-    // The JS discovers interactive elements, collects ARIA data, AND injects
-    // data-fgp-ref attributes in the same pass. The ref counter in JS starts
-    // at counter+1 to stay in sync with the Rust counter that increments below.
-    let script = format!(
-        r#"((startCounter) => {{
-        const roleFor = (el) => {{
-            const explicit = el.getAttribute && el.getAttribute('role');
-            if (explicit) return explicit;
-            const tag = el.tagName ? el.tagName.toLowerCase() : '';
-            if (tag === 'a') return 'link';
-            if (tag === 'button') return 'button';
-            if (tag === 'img') return 'img';
-            if (tag === 'nav') return 'navigation';
-            if (tag === 'main') return 'main';
-            if (tag === 'article') return 'article';
-            if (tag === 'section') return 'section';
-            if (tag === 'option') return 'option';
-            if (tag === 'select') return 'combobox';
-            if (tag === 'textarea') return 'textbox';
-            if (tag === 'input') {{
-                const t = (el.getAttribute('type') || 'text').toLowerCase();
-                if (t === 'checkbox') return 'checkbox';
-                if (t === 'radio') return 'radio';
-                if (t === 'range') return 'slider';
-                if (t === 'search') return 'searchbox';
-                if (t === 'number') return 'spinbutton';
-                return 'textbox';
-            }}
-            if (tag && tag.startsWith('h')) return 'heading';
-            if (el.isContentEditable) return 'textbox';
-            return null;
-        }};
-        const nameFor = (el) => {{
-            const label = el.getAttribute && el.getAttribute('aria-label');
-            if (label) return label;
-            const alt = el.getAttribute && el.getAttribute('alt');
-            if (alt) return alt;
-            const title = el.getAttribute && el.getAttribute('title');
-            if (title) return title;
-            const text = (el.textContent || '').trim();
-            return text.length ? text : null;
-        }};
-        const selector = [
-            'a', 'button', 'input', 'select', 'textarea', 'option',
-            '[role]', 'img', 'nav', 'main', 'article', 'section',
-            'h1', 'h2', 'h3', 'h4', 'h5', 'h6', '[contenteditable]'
-        ].join(',');
-        const nodes = [];
-        const seen = new Set();
-        let refCounter = startCounter;
-        for (const el of document.querySelectorAll(selector)) {{
-            if (seen.has(el)) continue;
-            seen.add(el);
-            const role = roleFor(el);
-            if (!role) continue;
-            refCounter++;
-            el.setAttribute('data-fgp-ref', 'e' + refCounter);
-            const name = nameFor(el);
-            const value = 'value' in el ? el.value : null;
-            nodes.push({{
-                role,
-                name,
-                value,
-                focusable: el.tabIndex >= 0,
-                focused: document.activeElement === el,
-            }});
-        }}
-        return nodes;
-    }})({})"#,
-        *counter
-    );
+#[derive(Debug, Deserialize)]
+struct DomSnapshot {
+    nodes: Vec<DomSnapshotNode>,
+    last: usize,
+}
+
+/// Walk the DOM for nodes and refs, returning them with the highest ref
+/// number now in use. Elements keep the ref an earlier snapshot gave them;
+/// new ones are numbered after `last`. Refs are only written to the page
+/// with `inject`.
+async fn extract_dom_interactives(
+    page: &Page,
+    last: usize,
+    inject: bool,
+) -> Result<(Vec<AriaNode>, usize)> {
+    let script = shadow::call(DOM_SNAPSHOT_SCRIPT, &format!("{}, {}", last, inject));
 
-    let dom_nodes: Vec<DomSnapshotNode> = page
+    let snapshot: DomSnapshot = page
         .evaluate(script)
         .await
         .context("Failed to evaluate DOM fallback for ARIA snapshot")?
         .into_value()
         .context("Failed to parse DOM fallback for ARIA snapshot")?;
 
-    let nodes = dom_nodes
+    let nodes = snapshot
+        .nodes
         .into_iter()
         .filter(|n| !n.role.is_empty())
         .map(|n| {
-            *counter += 1;
-            let ref_id = format!("@e{}", counter);
+            let ref_id = format!("@{}", n.ref_id);
             let name = n.name.and_then(|s| {
                 let trimmed = s.trim().to_string();
                 if trimmed.is_empty() {
@@ -299,18 +760,19 @@ async fn extract_dom_interactives(page: &Page, counter: &mut usize) -> Result<Ve
                 value,
                 focusable: n.focusable,
                 focused: n.focused,
+                rect: None,
+                in_viewport: false,
                 children: vec![],
             }
         })
         .collect();
 
-    Ok(nodes)
+    Ok((nodes, snapshot.last))
 }
 
 /// Convert CDP AxNode reference to our AriaNode format - zero-copy extraction.
-fn convert_node_ref(node: &CdpAxNode, counter: &mut usize) -> AriaNode {
-    *counter += 1;
-    let ref_id = format!("@e{}", counter);
+fn convert_node_ref(node: &CdpAxNode, frame: &str, number: usize) -> AriaNode {
+    let ref_id = format!("@{}e{}", frame, number);
 
     let role = node
         .role
@@ -331,35 +793,7 @@ fn convert_node_ref(node: &CdpAxNode, counter: &mut usize) -> AriaNode {
         .and_then(|v| v.value.as_ref())
         .and_then(|v: &JsonValue| v.as_str().map(|s| s.to_string()));
 
-    let focusable = node
-        .properties
-        .as_ref()
-        .map(|props: &Vec<AxProperty>| {
-            props.iter().any(|p| {
-                matches!(p.name, AxPropertyName::Focusable)
-                    && p.value
-                        .value
-                        .as_ref()
-                        .and_then(json_as_bool)
-                        .unwrap_or(false)
-            })
-        })
-        .unwrap_or(false);
-
-    let focused = node
-        .properties
-        .as_ref()
-        .map(|props: &Vec<AxProperty>| {
-            props.iter().any(|p| {
-                matches!(p.name, AxPropertyName::Focused)
-                    && p.value
-                        .value
-                        .as_ref()
-                        .and_then(json_as_bool)
-                        .unwrap_or(false)
-            })
-        })
-        .unwrap_or(false);
+    let (focusable, focused) = focus_flags(node);
 
     AriaNode {
         ref_id,
@@ -368,14 +802,149 @@ fn convert_node_ref(node: &CdpAxNode, counter: &mut usize) -> AriaNode {
         value,
         focusable,
         focused,
+        rect: None,
+        in_viewport: false,
         children: vec![], // Flatten for LLM consumption
     }
 }
 
+/// Ref of the focused node, if the focused element is in the tree.
+pub fn focused_ref(nodes: &[AriaNode]) -> Option<String> {
+    nodes.iter().find_map(|node| {
+        if node.focused {
+            Some(node.ref_id.clone())
+        } else {
+            focused_ref(&node.children)
+        }
+    })
+}
+
+/// Render nodes as indented `- role "name" [@eN]` lines, in the spirit of
+/// Playwright's ARIA snapshots; a fraction of the size of the JSON tree.
+///
+/// Focus is flagged as `[focused]` and a value follows a colon.
+pub fn render_text(nodes: &[AriaNode]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        write_text_node(&mut out, node, 0);
+    }
+    out
+}
+
+fn write_text_node(out: &mut String, node: &AriaNode, depth: usize) {
+    let one_line = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    out.push_str(&"  ".repeat(depth));
+    out.push_str("- ");
+    out.push_str(&node.role);
+    if let Some(name) = node.name.as_deref().map(one_line) {
+        if !name.is_empty() {
+            out.push_str(" \"");
+            out.push_str(&name.replace('\\', "\\\\").replace('"', "\\\""));
+            out.push('"');
+        }
+    }
+    out.push_str(" [");
+    out.push_str(&node.ref_id);
+    out.push(']');
+    if node.focused {
+        out.push_str(" [focused]");
+    }
+    if let Some(value) = node.value.as_deref().map(one_line) {
+        if !value.is_empty() {
+            out.push_str(": ");
+            out.push_str(&value);
+        }
+    }
+    out.push('\n');
+
+    for child in &node.children {
+        write_text_node(out, child, depth + 1);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn node(ref_id: &str, role: &str, name: Option<&str>, children: Vec<AriaNode>) -> AriaNode {
+        AriaNode {
+            ref_id: ref_id.to_string(),
+            role: role.to_string(),
+            name: name.map(str::to_string),
+            value: None,
+            focusable: false,
+            focused: false,
+            rect: None,
+            in_viewport: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_place_marks_viewport_overlap() {
+        let rect = |x, y| ElementRect {
+            x,
+            y,
+            width: 100.0,
+            height: 40.0,
+        };
+        let rects: HashMap<String, ElementRect> = [
+            ("e1".to_string(), rect(10.0, 10.0)),
+            ("e2".to_string(), rect(10.0, 780.0)),
+            ("e3".to_string(), rect(10.0, 1200.0)),
+            ("e4".to_string(), rect(-100.0, 10.0)),
+        ]
+        .into();
+        let mut nodes = vec![
+            node(
+                "@e1",
+                "button",
+                None,
+                vec![node("@e2", "link", None, vec![])],
+            ),
+            node("@e3", "link", None, vec![]),
+            node("@e4", "link", None, vec![]),
+            node("@e5", "link", None, vec![]),
+        ];
+
+        place(&mut nodes, &rects, 1280.0, 800.0);
+
+        assert_eq!(nodes[0].rect, Some(rect(10.0, 10.0)));
+        assert!(nodes[0].in_viewport);
+        assert!(nodes[0].children[0].in_viewport);
+        assert!(!nodes[1].in_viewport);
+        assert!(!nodes[2].in_viewport);
+        assert_eq!(nodes[3].rect, None);
+        assert!(!nodes[3].in_viewport);
+    }
+
+    #[test]
+    fn test_render_text() {
+        let mut email = node("@e3", "textbox", Some("Email"), vec![]);
+        email.value = Some("ada@example.com".to_string());
+        email.focused = true;
+        let nodes = vec![
+            node("@e1", "heading", Some("Sign  in\n"), vec![]),
+            node(
+                "@e2",
+                "form",
+                None,
+                vec![email, node("@e4", "button", Some("Say \"hi\""), vec![])],
+            ),
+        ];
+
+        assert_eq!(
+            render_text(&nodes),
+            "- heading \"Sign in\" [@e1]\n\
+             - form [@e2]\n\
+             \x20 - textbox \"Email\" [@e3] [focused]: ada@example.com\n\
+             \x20 - button \"Say \\\"hi\\\"\" [@e4]\n"
+        );
+        assert_eq!(focused_ref(&nodes).as_deref(), Some("@e3"));
+        assert_eq!(focused_ref(&nodes[..1]), None);
+    }
+
     #[test]
     fn test_json_as_str() {
         assert_eq!(
@@ -524,6 +1093,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_flat_nodes_skips_ignored_and_collects_refs() {
+        let cdp_nodes: Vec<CdpAxNode> = serde_json::from_value(serde_json::json!([
+            {
+                "nodeId": "1",
+                "ignored": true,
+                "role": {"type": "role", "value": "generic"}
+            },
+            {
+                "nodeId": "2",
+                "ignored": false,
+                "role": {"type": "role", "value": "button"},
+                "name": {"type": "computedString", "value": "Submit"},
+                "backendDOMNodeId": 42
+            },
+            {
+                "nodeId": "3",
+                "ignored": false,
+                "role": {"type": "role", "value": "link"}
+            }
+        ]))
+        .unwrap();
+
+        let mut registry = RefRegistry::default();
+        let (nodes, refs) = build_flat_nodes(&cdp_nodes, &mut registry, "");
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].ref_id, "@e1");
+        assert_eq!(nodes[0].role, "button");
+        assert_eq!(nodes[0].name, Some("Submit".to_string()));
+        assert_eq!(nodes[1].ref_id, "@e2");
+        assert_eq!(registry.last, 2);
+
+        // Only nodes backed by a DOM node get a ref attribute
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].1, "e1");
+    }
+
+    #[test]
+    fn test_refs_stay_stable_across_snapshots() {
+        let ax = |id: &str, role: &str, backend: i64| {
+            serde_json::json!({
+                "nodeId": id,
+                "ignored": false,
+                "role": {"type": "role", "value": role},
+                "backendDOMNodeId": backend
+            })
+        };
+        let tree = |nodes: Vec<serde_json::Value>| -> Vec<CdpAxNode> {
+            serde_json::from_value(serde_json::Value::Array(nodes)).unwrap()
+        };
+        let ref_ids = |nodes: &[AriaNode]| -> Vec<String> {
+            nodes.iter().map(|n| n.ref_id.clone()).collect()
+        };
+
+        let mut registry = RefRegistry::default();
+        registry.start_document("1700000000000.5".to_string());
+        let (first, _) = build_flat_nodes(
+            &tree(vec![
+                ax("1", "button", 42),
+                ax("2", "link", 43),
+                ax("3", "textbox", 44),
+            ]),
+            &mut registry,
+            "",
+        );
+        assert_eq!(ref_ids(&first), vec!["@e1", "@e2", "@e3"]);
+
+        // A banner is inserted above and the link removed: the others keep
+        // their refs, and the link's number isn't handed out again
+        registry.start_document("1700000000000.5".to_string());
+        let (second, injected) = build_flat_nodes(
+            &tree(vec![
+                ax("4", "alert", 50),
+                ax("1", "button", 42),
+                ax("3", "textbox", 44),
+            ]),
+            &mut registry,
+            "",
+        );
+        assert_eq!(ref_ids(&second), vec!["@e4", "@e1", "@e3"]);
+        assert_eq!(injected[0].1, "e4");
+
+        // Two AX nodes for one DOM node: only the first gets its ref
+        let (shared, _) = build_flat_nodes(
+            &tree(vec![ax("1", "button", 42), ax("5", "img", 42)]),
+            &mut registry,
+            "",
+        );
+        assert_eq!(ref_ids(&shared), vec!["@e1", "@e5"]);
+
+        // A new document starts over
+        registry.start_document("1700000009999.1".to_string());
+        let (reloaded, _) = build_flat_nodes(&tree(vec![ax("1", "button", 42)]), &mut registry, "");
+        assert_eq!(ref_ids(&reloaded), vec!["@e1"]);
+        assert_eq!(registry.last, 1);
+    }
+
+    #[test]
+    fn test_frame_refs() {
+        let cdp_nodes: Vec<CdpAxNode> = serde_json::from_value(serde_json::json!([
+            {
+                "nodeId": "1",
+                "ignored": false,
+                "role": {"type": "role", "value": "textbox"},
+                "name": {"type": "computedString", "value": "Card number"},
+                "backendDOMNodeId": 90
+            }
+        ]))
+        .unwrap();
+        let checkout = FrameId::new("C0FFEE");
+        let chat = FrameId::new("BEEF");
+
+        let mut registry = RefRegistry::default();
+        registry.start_document("1700000000000.5".to_string());
+        registry.number_for(None);
+        assert_eq!(registry.frame_number_for(&checkout), 1);
+        assert_eq!(registry.frame_number_for(&chat), 2);
+        assert_eq!(registry.frame_number_for(&checkout), 1);
+        assert_eq!(registry.frame(2), Some(chat));
+        assert_eq!(registry.frame(0), None);
+        assert_eq!(registry.frame(3), None);
+
+        let (nodes, refs) = build_flat_nodes(&cdp_nodes, &mut registry, "f1");
+        assert_eq!(nodes[0].ref_id, "@f1e2");
+        assert_eq!(refs[0].1, "f1e2");
+
+        let mut tree = vec![node(
+            "@e7",
+            "main",
+            None,
+            vec![node("@e1", "Iframe", None, vec![])],
+        )];
+        node_mut(&mut tree, "@e1").unwrap().children.extend(nodes);
+        assert_eq!(tree[0].children[0].children[0].ref_id, "@f1e2");
+        assert!(node_mut(&mut tree, "@e9").is_none());
+
+        registry.start_document("1700000009999.1".to_string());
+        assert_eq!(registry.frame(1), None);
+    }
+
     #[test]
     fn test_ref_id_generation() {
         // Test that counter increments properly for ref_id generation
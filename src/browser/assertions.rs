@@ -0,0 +1,236 @@
+//! Assertions with evidence.
+//!
+//! Each check reads the page once and reports the observed value alongside
+//! the verdict, so a failure explains itself without a follow-up snapshot.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::client::resolve_selector;
+use crate::models::{Assertion, AssertionEvidence, AssertionResult};
+
+/// Longest text kept as evidence; page-level text can be huge.
+const MAX_EVIDENCE_CHARS: usize = 2000;
+
+/// What the page reports about an element (or the whole page).
+#[derive(Debug, Default, Deserialize)]
+struct Probe {
+    found: bool,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    visible: bool,
+    #[serde(default)]
+    count: usize,
+}
+
+/// Probe `css_selector`, or the page body when None.
+async fn probe(page: &Page, css_selector: Option<&str>) -> Result<Probe> {
+    let script = format!(
+        r#"((sel) => {{
+            const count = sel === null ? 1 : document.querySelectorAll(sel).length;
+            const el = sel === null ? document.body : document.querySelector(sel);
+            if (!el) return {{ found: false, count }};
+            const r = el.getBoundingClientRect();
+            const visible = r.width > 0 && r.height > 0 &&
+                el.checkVisibility({{ opacityProperty: true, visibilityProperty: true }});
+            return {{ found: true, text: el.innerText ?? el.textContent ?? '', visible, count }};
+        }})({})"#,
+        serde_json::to_string(&css_selector)?
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to evaluate assertion")?
+        .into_value()
+        .context("Failed to parse assertion probe")
+}
+
+/// Check `assertion` against the page.
+///
+/// A failed assertion is a normal result (`passed: false`); errors are
+/// reserved for the page being unreachable or the selector being invalid.
+pub async fn check(page: &Page, assertion: &Assertion) -> Result<AssertionResult> {
+    let url = page.url().await?.unwrap_or_default();
+
+    let (passed, actual, message) = match assertion {
+        Assertion::Text {
+            selector,
+            expected,
+            exact,
+        } => {
+            let css_selector = selector.as_deref().map(resolve_selector);
+            let probe = probe(page, css_selector.as_deref()).await?;
+            let target = selector.as_deref().unwrap_or("page");
+            if !probe.found {
+                (false, Value::Null, format!("Element not found: {}", target))
+            } else {
+                let text = collapse_whitespace(&probe.text);
+                let passed = text_matches(&text, expected, *exact);
+                let relation = if *exact { "equal" } else { "contain" };
+                let message = if passed {
+                    format!("Text of {} does {} {:?}", target, relation, expected)
+                } else {
+                    format!("Expected text of {} to {} {:?}", target, relation, expected)
+                };
+                (passed, json!(truncate(&text)), message)
+            }
+        }
+        Assertion::Visible { selector } => {
+            let probe = probe(page, Some(&resolve_selector(selector))).await?;
+            let message = match (probe.found, probe.visible) {
+                (false, _) => format!("Element not found: {}", selector),
+                (true, false) => format!("Expected {} to be visible", selector),
+                (true, true) => format!("{} is visible", selector),
+            };
+            (probe.visible, json!(probe.visible), message)
+        }
+        Assertion::Url { pattern } => {
+            let passed = glob_match(pattern, &url);
+            let message = if passed {
+                format!("URL matches {}", pattern)
+            } else {
+                format!("Expected URL to match {}, got {}", pattern, url)
+            };
+            (passed, json!(url), message)
+        }
+        Assertion::Count { selector, expected } => {
+            let probe = probe(page, Some(&resolve_selector(selector))).await?;
+            let passed = probe.count == *expected;
+            let message = if passed {
+                format!("{} matches {} element(s)", selector, expected)
+            } else {
+                format!(
+                    "Expected {} to match {} element(s), found {}",
+                    selector, expected, probe.count
+                )
+            };
+            (passed, json!(probe.count), message)
+        }
+    };
+
+    Ok(AssertionResult {
+        passed,
+        assertion: assertion.clone(),
+        message,
+        evidence: AssertionEvidence {
+            actual,
+            url,
+            screenshot: None,
+        },
+    })
+}
+
+/// Check every assertion, in order, without stopping at the first failure.
+///
+/// An assertion that can't be evaluated (e.g. an invalid selector) is
+/// reported as failed with the error as its message.
+pub async fn check_all(page: &Page, assertions: &[Assertion]) -> Vec<AssertionResult> {
+    let mut results = Vec::with_capacity(assertions.len());
+    for assertion in assertions {
+        let result = match check(page, assertion).await {
+            Ok(result) => result,
+            Err(e) => AssertionResult {
+                passed: false,
+                assertion: assertion.clone(),
+                message: format!("{:#}", e),
+                evidence: AssertionEvidence {
+                    actual: Value::Null,
+                    url: page.url().await.ok().flatten().unwrap_or_default(),
+                    screenshot: None,
+                },
+            },
+        };
+        results.push(result);
+    }
+    results
+}
+
+/// Collapse runs of whitespace to single spaces and trim.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Compare already-collapsed `actual` text against `expected`.
+fn text_matches(actual: &str, expected: &str, exact: bool) -> bool {
+    let expected = collapse_whitespace(expected);
+    if exact {
+        actual == expected
+    } else {
+        actual.contains(&expected)
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_EVIDENCE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters.
+/// A pattern without `*` must match exactly.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+
+    let Some(rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let Some(mut rest) = rest.strip_suffix(last) else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("https://example.com/", "https://example.com/"));
+        assert!(!glob_match("https://example.com", "https://example.com/"));
+        assert!(glob_match(
+            "*/checkout*",
+            "https://shop.test/checkout?step=2"
+        ));
+        assert!(glob_match(
+            "https://*.test/*/done",
+            "https://shop.test/order/42/done"
+        ));
+        assert!(!glob_match(
+            "https://*.test/*/done",
+            "https://shop.test/order/42"
+        ));
+        assert!(!glob_match("a*a", "a"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_text_matches_collapses_whitespace() {
+        let actual = collapse_whitespace("  Welcome back,\n\n  Ada  ");
+        assert_eq!(actual, "Welcome back, Ada");
+        assert!(text_matches(&actual, "back,  Ada", false));
+        assert!(!text_matches(&actual, "back, Ada", true));
+        assert!(text_matches(&actual, "Welcome back, Ada", true));
+    }
+
+    #[test]
+    fn test_truncate_keeps_short_text() {
+        assert_eq!(truncate("short"), "short");
+        let long = "é".repeat(MAX_EVIDENCE_CHARS + 5);
+        assert_eq!(truncate(&long).chars().count(), MAX_EVIDENCE_CHARS + 1);
+    }
+}
@@ -0,0 +1,372 @@
+//! Filling a form from a flat key/value map.
+//!
+//! Keys are matched to the fields `describe_form` lists, in order of
+//! confidence: a field's `name` (or `id`), then a token of its `autocomplete`
+//! attribute, then its label, then a label starting with the key when only one
+//! field's does (so `email` finds "Email address", but `name` doesn't guess
+//! between "First name" and "Last name"). Keys are compared without case or
+//! punctuation, so `first_name`, `firstName` and "First name" all match. Each
+//! field takes at most one key.
+//!
+//! How a value is applied depends on the field: checkboxes take a boolean (or
+//! yes/no), radio groups and selects take an option's value or label, date
+//! inputs go through `set_date`, file inputs take paths, and everything else is
+//! typed like `fill`.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::models::FormField;
+
+/// What to do to a matched field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Type into the field, replacing its text
+    Fill(String),
+    /// Set a checkbox's state
+    Check(bool),
+    /// Check the radio at this selector
+    Choose { selector: String, value: String },
+    /// Select these option values
+    Select(Vec<String>),
+    /// Set a date input (ISO date)
+    Date(String),
+    /// Set a file input's files
+    Upload(Vec<String>),
+}
+
+impl Action {
+    /// The value set, for the report.
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Fill(text) | Action::Date(text) => text.clone(),
+            Action::Check(checked) => checked.to_string(),
+            Action::Choose { value, .. } => value.clone(),
+            Action::Select(values) | Action::Upload(values) => values.join(", "),
+        }
+    }
+}
+
+/// One data key matched to a field.
+#[derive(Debug)]
+pub struct Assignment {
+    pub key: String,
+    /// Index into the form's fields
+    pub field: usize,
+    /// The action, or why the value can't be applied to the field
+    pub action: Result<Action>,
+}
+
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// In the fields' document order
+    pub assignments: Vec<Assignment>,
+    /// Keys no field matched, in data order
+    pub unmatched: Vec<String>,
+    /// Required fields no key matched that are still empty, by label (or
+    /// name, or selector)
+    pub unfilled_required: Vec<String>,
+}
+
+/// Lowercase letters and digits only.
+fn squash(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How well `field` matches `key` (already squashed), 0 (best) to 3, if at all.
+fn tier(field: &FormField, key: &str) -> Option<u8> {
+    if field
+        .name
+        .as_deref()
+        .is_some_and(|name| squash(name) == key)
+    {
+        return Some(0);
+    }
+    if field
+        .autocomplete
+        .as_deref()
+        .is_some_and(|ac| ac.split_whitespace().any(|token| squash(token) == key))
+    {
+        return Some(1);
+    }
+    let label = squash(field.label.as_deref().unwrap_or_default());
+    if label.is_empty() {
+        None
+    } else if label == key {
+        Some(2)
+    } else if label.starts_with(key) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Match `data` against `fields` and work out what to do to each match.
+pub fn plan(fields: &[FormField], data: &[(String, Value)]) -> Plan {
+    let keys: Vec<String> = data.iter().map(|(key, _)| squash(key)).collect();
+    let mut field_of: Vec<Option<usize>> = vec![None; data.len()];
+    let mut taken = vec![false; fields.len()];
+
+    for level in 0..=3 {
+        for (k, key) in keys.iter().enumerate() {
+            if field_of[k].is_some() || key.is_empty() {
+                continue;
+            }
+            let candidates: Vec<usize> = (0..fields.len())
+                .filter(|&f| !taken[f] && tier(&fields[f], key) == Some(level))
+                .collect();
+            // A label only starting with the key is too weak to pick between fields
+            let pick = match (level, candidates.as_slice()) {
+                (3, [only]) => Some(*only),
+                (3, _) => None,
+                (_, candidates) => candidates.first().copied(),
+            };
+            if let Some(f) = pick {
+                taken[f] = true;
+                field_of[k] = Some(f);
+            }
+        }
+    }
+
+    let mut plan = Plan::default();
+    for ((key, value), field) in data.iter().zip(field_of) {
+        match field {
+            Some(f) => plan.assignments.push(Assignment {
+                key: key.clone(),
+                field: f,
+                action: action(&fields[f], value),
+            }),
+            None => plan.unmatched.push(key.clone()),
+        }
+    }
+    plan.assignments.sort_by_key(|a| a.field);
+    plan.unfilled_required = fields
+        .iter()
+        .zip(&taken)
+        .filter(|(field, taken)| {
+            field.required && !**taken && field.value.as_deref().unwrap_or_default().is_empty()
+        })
+        .map(|(field, _)| {
+            field
+                .label
+                .clone()
+                .or_else(|| field.name.clone())
+                .unwrap_or_else(|| field.selector.clone())
+        })
+        .collect();
+    plan
+}
+
+/// The value as text, if it's a string, number or boolean.
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Text of a value or of each item of a list.
+fn texts(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::Array(items) => items.iter().map(text).collect(),
+        other => text(other).map(|t| vec![t]),
+    }
+}
+
+fn action(field: &FormField, value: &Value) -> Result<Action> {
+    if field.disabled {
+        bail!("Field is disabled");
+    }
+    let Some(values) = texts(value) else {
+        bail!("Value must be a string, number, boolean or a list of them");
+    };
+    let single = || match values.as_slice() {
+        [one] => Ok(one.clone()),
+        _ => bail!("Field takes a single value"),
+    };
+    // An option by value, or by label ignoring case and punctuation
+    let option = |wanted: &str| {
+        field
+            .options
+            .iter()
+            .find(|o| o.value == wanted)
+            .or_else(|| {
+                let wanted = squash(wanted);
+                field.options.iter().find(|o| squash(&o.label) == wanted)
+            })
+            .ok_or_else(|| {
+                let known: Vec<&str> = field.options.iter().map(|o| o.label.as_str()).collect();
+                anyhow::anyhow!("No option '{}' (options: {})", wanted, known.join(", "))
+            })
+    };
+
+    match field.kind.as_str() {
+        "checkbox" => match value {
+            Value::Bool(checked) => Ok(Action::Check(*checked)),
+            _ => {
+                let raw = single()?;
+                match squash(&raw).as_str() {
+                    "true" | "yes" | "on" | "1" | "checked" => Ok(Action::Check(true)),
+                    "false" | "no" | "off" | "0" | "" | "unchecked" => Ok(Action::Check(false)),
+                    _ => bail!("Checkbox value must be true or false, got '{}'", raw),
+                }
+            }
+        },
+        "radio" => {
+            let chosen = option(&single()?)?;
+            match &chosen.selector {
+                Some(selector) => Ok(Action::Choose {
+                    selector: selector.clone(),
+                    value: chosen.value.clone(),
+                }),
+                None => bail!("Radio '{}' has no selector", chosen.value),
+            }
+        }
+        "select" => Ok(Action::Select(vec![option(&single()?)?.value.clone()])),
+        "select-multiple" => Ok(Action::Select(
+            values
+                .iter()
+                .map(|v| option(v).map(|o| o.value.clone()))
+                .collect::<Result<_>>()?,
+        )),
+        "file" => Ok(Action::Upload(values)),
+        _ if field.readonly => bail!("Field is read-only"),
+        "date" => Ok(Action::Date(single()?)),
+        _ => Ok(Action::Fill(single()?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FormOption;
+    use serde_json::json;
+
+    fn field(name: &str, kind: &str, label: &str, autocomplete: Option<&str>) -> FormField {
+        FormField {
+            name: Some(name.to_string()),
+            kind: kind.to_string(),
+            label: Some(label.to_string()),
+            selector: format!("#{}", name),
+            required: false,
+            disabled: false,
+            readonly: false,
+            value: None,
+            checked: None,
+            placeholder: None,
+            autocomplete: autocomplete.map(str::to_string),
+            constraints: Default::default(),
+            options: vec![],
+            valid: true,
+            validation_message: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_matches_keys_to_fields() {
+        let mut country = field("ctry", "select", "Country", None);
+        country.options = ["us", "gb"]
+            .iter()
+            .zip(["United States", "United Kingdom"])
+            .map(|(value, label)| FormOption {
+                value: value.to_string(),
+                label: label.to_string(),
+                selected: false,
+                disabled: false,
+                selector: None,
+            })
+            .collect();
+        let mut dob = field("dob", "date", "Date of birth", Some("bday"));
+        dob.required = true;
+        let fields = vec![
+            field("fname", "text", "First name", Some("given-name")),
+            field("lname", "text", "Last name", None),
+            field(
+                "user_email",
+                "email",
+                "Email address",
+                Some("section-1 email"),
+            ),
+            field("terms", "checkbox", "I accept the terms", None),
+            country,
+            dob,
+        ];
+        let data = vec![
+            ("email".to_string(), json!("ada@example.com")),
+            ("given_name".to_string(), json!("Ada")),
+            ("name".to_string(), json!("Ada Lovelace")),
+            ("last".to_string(), json!("Lovelace")),
+            ("terms".to_string(), json!("yes")),
+            ("country".to_string(), json!("united kingdom")),
+            ("phone".to_string(), json!(5551234)),
+        ];
+
+        let plan = plan(&fields, &data);
+
+        let matched: Vec<(&str, &str, Action)> = plan
+            .assignments
+            .iter()
+            .map(|a| {
+                (
+                    a.key.as_str(),
+                    fields[a.field].name.as_deref().unwrap(),
+                    a.action.as_ref().unwrap().clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            matched,
+            vec![
+                ("given_name", "fname", Action::Fill("Ada".to_string())),
+                ("last", "lname", Action::Fill("Lovelace".to_string())),
+                (
+                    "email",
+                    "user_email",
+                    Action::Fill("ada@example.com".to_string())
+                ),
+                ("terms", "terms", Action::Check(true)),
+                ("country", "ctry", Action::Select(vec!["gb".to_string()])),
+            ]
+        );
+        assert_eq!(plan.unmatched, vec!["name", "phone"]);
+        assert_eq!(plan.unfilled_required, vec!["Date of birth"]);
+    }
+
+    #[test]
+    fn test_plan_reports_unusable_values() {
+        let mut fields = vec![field("size", "select", "Size", None)];
+        fields[0].options = vec![FormOption {
+            value: "m".to_string(),
+            label: "Medium".to_string(),
+            selected: false,
+            disabled: false,
+            selector: None,
+        }];
+        let mut locked = field("code", "text", "Code", None);
+        locked.readonly = true;
+        fields.push(locked);
+
+        let plan = plan(
+            &fields,
+            &[
+                ("size".to_string(), json!("XL")),
+                ("code".to_string(), json!("ABC")),
+            ],
+        );
+
+        let errors: Vec<String> = plan
+            .assignments
+            .iter()
+            .map(|a| a.action.as_ref().unwrap_err().to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec!["No option 'XL' (options: Medium)", "Field is read-only"]
+        );
+    }
+}
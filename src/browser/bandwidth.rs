@@ -0,0 +1,226 @@
+//! Per-session network traffic accounting.
+//!
+//! Downloads are the `encodedDataLength` Chrome reports when a request
+//! finishes: bytes actually received, headers and compression included, so
+//! cache hits count as requests but not bytes. Uploads are request bodies as
+//! Chrome reports them in `postDataEntries`; file parts of multipart bodies
+//! aren't included there, so file uploads are under-counted. Requests that
+//! fail or are still in flight aren't counted.
+//!
+//! Requests made since the last main-frame navigation are also kept one by
+//! one (up to a bound), for per-page reports like the third-party breakdown.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::network::{
+    EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent, RequestId, ResourceType,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use super::events::cdp_enum_name;
+use crate::models::BandwidthUsage;
+
+/// Requests kept for the current page; later ones only count in the totals.
+const MAX_PAGE_REQUESTS: usize = 5_000;
+
+/// Traffic of a session's page, shared with the task that records it.
+#[derive(Debug, Clone, Default)]
+pub struct Meter(Arc<Mutex<Tally>>);
+
+/// A finished request of the current page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest {
+    pub url: String,
+    pub resource_type: String,
+    pub downloaded_bytes: u64,
+}
+
+#[derive(Debug)]
+struct Pending {
+    url: String,
+    resource_type: String,
+    uploaded: u64,
+}
+
+#[derive(Debug, Default)]
+struct Tally {
+    /// Requests still loading
+    inflight: HashMap<RequestId, Pending>,
+    usage: BandwidthUsage,
+    /// Requests finished since the last main-frame navigation
+    page_requests: Vec<PageRequest>,
+}
+
+impl Tally {
+    fn finish(&mut self, request_id: &RequestId, downloaded: u64) {
+        let Some(pending) = self.inflight.remove(request_id) else {
+            return;
+        };
+        let by_type = self
+            .usage
+            .by_type
+            .entry(pending.resource_type.clone())
+            .or_default();
+        for counts in [&mut self.usage.total, by_type] {
+            counts.requests += 1;
+            counts.downloaded_bytes += downloaded;
+            counts.uploaded_bytes += pending.uploaded;
+        }
+        if self.page_requests.len() < MAX_PAGE_REQUESTS {
+            self.page_requests.push(PageRequest {
+                url: pending.url,
+                resource_type: pending.resource_type,
+                downloaded_bytes: downloaded,
+            });
+        }
+    }
+}
+
+impl Meter {
+    /// Traffic so far.
+    pub fn usage(&self) -> BandwidthUsage {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .usage
+            .clone()
+    }
+
+    /// Requests of the current page that have finished, in order.
+    pub fn page_requests(&self) -> Vec<PageRequest> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .page_requests
+            .clone()
+    }
+}
+
+/// Length of the data a base64 string decodes to.
+fn decoded_len(base64: &str) -> u64 {
+    let base64 = base64.trim_end_matches('=');
+    (base64.len() as u64 * 3) / 4
+}
+
+/// Count the traffic of `page` into `meter` until the page goes away or the
+/// returned task is aborted.
+pub async fn track(page: &Page, meter: &Meter) -> Result<JoinHandle<()>> {
+    let mut requests = page
+        .event_listener::<EventRequestWillBeSent>()
+        .await
+        .context("Failed to listen for network requests")?;
+    let mut finished = page
+        .event_listener::<EventLoadingFinished>()
+        .await
+        .context("Failed to listen for network requests")?;
+    let mut failed = page
+        .event_listener::<EventLoadingFailed>()
+        .await
+        .context("Failed to listen for network requests")?;
+    let main_frame = page.mainframe().await.ok().flatten();
+
+    // One task, so a request is always seen before it finishes
+    let meter = meter.clone();
+    Ok(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(event) = requests.next() => {
+                    let resource_type = event
+                        .r#type
+                        .as_ref()
+                        .map(cdp_enum_name)
+                        .unwrap_or_else(|| "other".to_string())
+                        .to_lowercase();
+                    let uploaded = event
+                        .request
+                        .post_data_entries
+                        .iter()
+                        .flatten()
+                        .filter_map(|entry| entry.bytes.as_ref())
+                        .map(|bytes| decoded_len(bytes.as_ref()))
+                        .sum();
+                    let mut tally = meter.0.lock().unwrap_or_else(|e| e.into_inner());
+                    let navigation = event.r#type == Some(ResourceType::Document)
+                        && main_frame.is_some()
+                        && event.frame_id == main_frame;
+                    if navigation && event.redirect_response.is_none() {
+                        tally.page_requests.clear();
+                    }
+                    // Redirects reuse the request ID; only the last hop counts
+                    tally.inflight.insert(
+                        event.request_id.clone(),
+                        Pending {
+                            url: event.request.url.clone(),
+                            resource_type,
+                            uploaded,
+                        },
+                    );
+                }
+                Some(event) = finished.next() => {
+                    let downloaded = event.encoded_data_length.max(0.0) as u64;
+                    let mut tally = meter.0.lock().unwrap_or_else(|e| e.into_inner());
+                    tally.finish(&event.request_id, downloaded);
+                }
+                Some(event) = failed.next() => {
+                    let mut tally = meter.0.lock().unwrap_or_else(|e| e.into_inner());
+                    tally.inflight.remove(&event.request_id);
+                }
+                else => break,
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ResourceBandwidth;
+
+    #[test]
+    fn test_decoded_len() {
+        assert_eq!(decoded_len(""), 0);
+        assert_eq!(decoded_len("YQ=="), 1);
+        assert_eq!(decoded_len("YWI="), 2);
+        assert_eq!(decoded_len("YWJj"), 3);
+        assert_eq!(decoded_len("e30xMjM0NQ=="), 7);
+    }
+
+    #[test]
+    fn test_tally_by_type() {
+        let mut tally = Tally::default();
+        let request = |id: &str| RequestId::new(id.to_string());
+        let pending = |resource_type: &str, uploaded| Pending {
+            url: format!("https://example.com/{}", resource_type),
+            resource_type: resource_type.to_string(),
+            uploaded,
+        };
+        tally.inflight.insert(request("1"), pending("document", 0));
+        tally.inflight.insert(request("2"), pending("xhr", 120));
+        tally.inflight.insert(request("3"), pending("xhr", 0));
+        tally.finish(&request("1"), 5_000);
+        tally.finish(&request("2"), 300);
+        tally.finish(&request("3"), 200);
+        // Unknown (or already finished) requests are ignored
+        tally.finish(&request("1"), 5_000);
+
+        assert_eq!(tally.page_requests.len(), 3);
+        assert_eq!(tally.page_requests[0].url, "https://example.com/document");
+        let usage = tally.usage;
+        assert_eq!(usage.total.requests, 3);
+        assert_eq!(usage.total.downloaded_bytes, 5_500);
+        assert_eq!(usage.total.uploaded_bytes, 120);
+        assert_eq!(
+            usage.by_type["xhr"],
+            ResourceBandwidth {
+                requests: 2,
+                downloaded_bytes: 500,
+                uploaded_bytes: 120,
+            }
+        );
+        assert_eq!(usage.by_type["document"].downloaded_bytes, 5_000);
+    }
+}
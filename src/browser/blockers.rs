@@ -0,0 +1,345 @@
+//! Guessing what stands between an agent and a page's content.
+//!
+//! The page reports a handful of signals (visible password fields, CAPTCHA
+//! widgets, paywall markup, a viewport-covering overlay, the start of its
+//! text), and each kind of blocker is scored from the ones that point to it.
+//! Scores are summed weights capped at 1; a kind counts as detected from
+//! `THRESHOLD` up. This is a heuristic: a site's own sign-in page is a
+//! login wall by these rules, and a paywall that only renders server-side
+//! with no markers shows as nothing.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::captcha;
+use super::events::cdp_enum_name;
+use crate::models::{Blocker, BlockerKind, BlockerReport, CaptchaChallenge};
+
+/// Score from which a blocker kind counts as detected.
+const THRESHOLD: f64 = 0.5;
+
+/// Characters of page text scanned for phrases.
+const TEXT_SAMPLE_CHARS: usize = 20_000;
+
+const CAPTCHA_PHRASES: &[&str] = &[
+    "verify you are human",
+    "verify that you are human",
+    "i'm not a robot",
+    "i am not a robot",
+    "are you a robot",
+    "complete the captcha",
+    "solve the captcha",
+    "unusual traffic from your computer",
+    "press and hold",
+];
+
+const INTERSTITIAL_PHRASES: &[&str] = &[
+    "checking your browser",
+    "checking if the site connection is secure",
+    "just a moment",
+    "attention required",
+    "enable javascript and cookies to continue",
+    "ddos protection by",
+    "you are now leaving",
+    "you are leaving",
+    "are you 18 or older",
+    "are you over 18",
+    "confirm your age",
+    "age verification",
+    "your connection is not private",
+];
+
+const LOGIN_PHRASES: &[&str] = &[
+    "sign in to continue",
+    "log in to continue",
+    "login to continue",
+    "sign in to view",
+    "log in to view",
+    "log in to see",
+    "sign in to see",
+    "please log in",
+    "please sign in",
+    "you must be logged in",
+    "you need to sign in",
+    "create an account to continue",
+    "sign up to continue",
+];
+
+const PAYWALL_PHRASES: &[&str] = &[
+    "subscribe to continue reading",
+    "subscribe to read",
+    "to continue reading",
+    "subscribers only",
+    "for subscribers",
+    "already a subscriber",
+    "you've reached your limit",
+    "you have reached your limit",
+    "free articles",
+    "become a member to read",
+    "unlock this article",
+];
+
+const LOGIN_URL_PARTS: &[&str] = &[
+    "/login",
+    "/signin",
+    "/sign-in",
+    "/sign_in",
+    "/auth",
+    "/sso",
+    "/oauth",
+    "accounts.",
+    "login.",
+];
+
+const CAPTCHA_URL_PARTS: &[&str] = &["captcha", "/sorry/", "/challenge"];
+
+const INTERSTITIAL_URL_PARTS: &[&str] = &["/cdn-cgi/", "interstitial", "consent.", "age-gate"];
+
+/// Collect the page-side signals; `limit` caps the text returned.
+const SIGNALS_SCRIPT: &str = r#"((limit) => {
+    const visible = (el) => {
+        const r = el.getBoundingClientRect();
+        const s = getComputedStyle(el);
+        return r.width > 0 && r.height > 0 && s.visibility !== 'hidden' && s.display !== 'none';
+    };
+    const paywall = [];
+    for (const el of document.querySelectorAll(
+        '[class*="paywall" i], [id*="paywall" i], [class*="regwall" i], [class*="metered" i], .tp-modal, [class*="subscribe-wall" i]'
+    )) {
+        if (visible(el)) paywall.push((el.id ? '#' + el.id : '.' + String(el.className).split(' ')[0]));
+    }
+    let notFree = false;
+    for (const s of document.querySelectorAll('script[type="application/ld+json"]')) {
+        if (/"isAccessibleForFree"\s*:\s*(false|"false")/i.test(s.textContent || '')) notFree = true;
+    }
+    const vw = innerWidth, vh = innerHeight;
+    let overlay = false;
+    for (const el of document.querySelectorAll('body *')) {
+        const s = getComputedStyle(el);
+        if (s.position !== 'fixed' && s.position !== 'sticky') continue;
+        if (!visible(el)) continue;
+        const r = el.getBoundingClientRect();
+        const w = Math.min(r.right, vw) - Math.max(r.left, 0);
+        const h = Math.min(r.bottom, vh) - Math.max(r.top, 0);
+        if (w > 0 && h > 0 && w * h >= vw * vh * 0.5) { overlay = true; break; }
+    }
+    const locked = [document.documentElement, document.body].some(
+        (el) => el && ['hidden', 'clip'].includes(getComputedStyle(el).overflowY)
+    );
+    const dialogs = [...document.querySelectorAll('[role="dialog"], [role="alertdialog"], dialog[open]')]
+        .filter(visible);
+    const text = (document.body ? document.body.innerText : '').slice(0, limit);
+    return {
+        url: location.href,
+        title: document.title,
+        text,
+        password_fields: [...document.querySelectorAll('input[type="password"]')].filter(visible).length,
+        paywall,
+        not_free: notFree,
+        overlay,
+        scroll_locked: locked,
+        dialog_text: dialogs.map((d) => d.innerText || '').join('\n').slice(0, limit),
+    };
+})"#;
+
+#[derive(Debug, Default, Deserialize)]
+struct Signals {
+    url: String,
+    title: String,
+    text: String,
+    password_fields: usize,
+    /// Filled in from `captcha::detect`
+    #[serde(default)]
+    captcha: Vec<CaptchaChallenge>,
+    paywall: Vec<String>,
+    not_free: bool,
+    overlay: bool,
+    scroll_locked: bool,
+    dialog_text: String,
+}
+
+/// Running score of one blocker kind.
+struct Score {
+    kind: BlockerKind,
+    total: f64,
+    evidence: Vec<String>,
+}
+
+impl Score {
+    fn new(kind: BlockerKind) -> Self {
+        Self {
+            kind,
+            total: 0.0,
+            evidence: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, weight: f64, evidence: String) {
+        self.total += weight;
+        self.evidence.push(evidence);
+    }
+
+    /// Add `weight` per phrase of `phrases` found in `text`, up to two.
+    fn phrases(&mut self, weight: f64, text: &str, phrases: &[&str]) {
+        for phrase in phrases.iter().filter(|p| text.contains(*p)).take(2) {
+            self.add(weight, format!("text: \"{}\"", phrase));
+        }
+    }
+
+    /// Add `weight` for the first of `parts` found in `url`.
+    fn url(&mut self, weight: f64, url: &str, parts: &[&str]) {
+        if let Some(part) = parts.iter().find(|p| url.contains(*p)) {
+            self.add(weight, format!("url contains \"{}\"", part));
+        }
+    }
+}
+
+/// Score each blocker kind from the page's signals.
+fn classify(signals: &Signals) -> BlockerReport {
+    let text = format!(
+        "{}\n{}\n{}",
+        signals.title, signals.dialog_text, signals.text
+    )
+    .to_lowercase();
+    let url = signals.url.to_lowercase();
+    let covered = signals.overlay || signals.scroll_locked;
+
+    let mut captcha = Score::new(BlockerKind::Captcha);
+    if !signals.captcha.is_empty() {
+        let widget = &signals.captcha[0];
+        captcha.add(
+            0.6,
+            format!("captcha widget: {}", cdp_enum_name(&widget.provider)),
+        );
+    }
+    captcha.phrases(0.3, &text, CAPTCHA_PHRASES);
+    captcha.url(0.3, &url, CAPTCHA_URL_PARTS);
+
+    let mut interstitial = Score::new(BlockerKind::Interstitial);
+    interstitial.phrases(0.3, &text, INTERSTITIAL_PHRASES);
+    interstitial.url(0.3, &url, INTERSTITIAL_URL_PARTS);
+    if url.starts_with("chrome-error://") {
+        interstitial.add(1.0, "browser error page".to_string());
+    }
+    // Challenge pages are mostly the phrase and little else
+    if interstitial.total > 0.0 && signals.text.trim().chars().count() < 500 {
+        interstitial.add(0.2, "little other text on the page".to_string());
+    }
+
+    let mut login = Score::new(BlockerKind::LoginWall);
+    if signals.password_fields > 0 {
+        login.add(
+            0.4,
+            format!("{} password field(s)", signals.password_fields),
+        );
+        if covered {
+            login.add(0.2, "password field over covered content".to_string());
+        }
+    }
+    login.phrases(0.3, &text, LOGIN_PHRASES);
+    login.url(0.3, &url, LOGIN_URL_PARTS);
+
+    let mut paywall = Score::new(BlockerKind::Paywall);
+    if signals.not_free {
+        paywall.add(
+            0.5,
+            "structured data: isAccessibleForFree false".to_string(),
+        );
+    }
+    if !signals.paywall.is_empty() {
+        paywall.add(0.4, format!("paywall markup: {}", signals.paywall[0]));
+    }
+    paywall.phrases(0.3, &text, PAYWALL_PHRASES);
+    if paywall.total > 0.0 && covered {
+        paywall.add(0.1, "content covered or scrolling locked".to_string());
+    }
+
+    let mut blockers: Vec<Blocker> = [captcha, interstitial, login, paywall]
+        .into_iter()
+        .filter(|score| score.total >= THRESHOLD)
+        .map(|score| Blocker {
+            kind: score.kind,
+            confidence: score.total.min(1.0),
+            evidence: score.evidence,
+        })
+        .collect();
+    blockers.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    BlockerReport {
+        url: signals.url.clone(),
+        title: signals.title.clone(),
+        blocked: !blockers.is_empty(),
+        primary: blockers.first().map(|b| b.kind),
+        blockers,
+    }
+}
+
+/// Classify whatever is blocking the current page, if anything.
+pub async fn detect(page: &Page) -> Result<BlockerReport> {
+    let mut signals: Signals = page
+        .evaluate(format!("{}({})", SIGNALS_SCRIPT, TEXT_SAMPLE_CHARS))
+        .await
+        .context("Failed to read page signals")?
+        .into_value()
+        .context("Failed to parse page signals")?;
+    signals.captcha = captcha::detect(page).await?;
+
+    Ok(classify(&signals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CaptchaProvider;
+
+    #[test]
+    fn test_classify_blockers() {
+        let login = Signals {
+            url: "https://example.com/login?next=/feed".to_string(),
+            title: "Example".to_string(),
+            text: "Sign in to continue\nEmail\nPassword".to_string(),
+            password_fields: 1,
+            ..Signals::default()
+        };
+        let report = classify(&login);
+        assert!(report.blocked);
+        assert_eq!(report.primary, Some(BlockerKind::LoginWall));
+        assert_eq!(report.blockers[0].confidence, 1.0);
+
+        let paywall = Signals {
+            url: "https://news.example.com/2026/10/story".to_string(),
+            text: "Lead paragraph. Subscribe to continue reading.".to_string(),
+            not_free: true,
+            overlay: true,
+            ..Signals::default()
+        };
+        assert_eq!(classify(&paywall).primary, Some(BlockerKind::Paywall));
+
+        let challenge = Signals {
+            url: "https://shop.example.com/".to_string(),
+            title: "Just a moment...".to_string(),
+            text: "Checking if the site connection is secure".to_string(),
+            captcha: vec![CaptchaChallenge {
+                provider: CaptchaProvider::Turnstile,
+                frame_url:
+                    "https://challenges.cloudflare.com/cdn-cgi/challenge-platform/h/g/turnstile/"
+                        .to_string(),
+            }],
+            ..Signals::default()
+        };
+        let report = classify(&challenge);
+        let kinds: Vec<BlockerKind> = report.blockers.iter().map(|b| b.kind).collect();
+        assert!(kinds.contains(&BlockerKind::Captcha));
+        assert!(kinds.contains(&BlockerKind::Interstitial));
+
+        let article = Signals {
+            url: "https://blog.example.com/post".to_string(),
+            text: "A long free article that mentions signing in once.".repeat(20),
+            ..Signals::default()
+        };
+        let report = classify(&article);
+        assert!(!report.blocked);
+        assert_eq!(report.primary, None);
+    }
+}
@@ -0,0 +1,340 @@
+//! Canvas drawing and capture.
+//!
+//! Signature pads and whiteboards only see pointer input, so strokes are
+//! replayed as real mouse (or pen) events through CDP: press at the first
+//! point, move through the rest with their timing, release at the last.
+//! Points are relative to the canvas's top-left corner, in CSS pixels or as
+//! fractions of its size. The canvas is scrolled into view first.
+//!
+//! Capture reads the bitmap with `toDataURL`, so it is the canvas's own
+//! resolution rather than a screenshot. Canvases tainted by cross-origin
+//! images can't be read; WebGL canvases without `preserveDrawingBuffer` may
+//! come back blank.
+//!
+//! When `selector` matches a wrapper rather than a canvas, the first canvas
+//! inside it is used.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchMouseEventParams, DispatchMouseEventPointerType, DispatchMouseEventType, MouseButton,
+};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{CanvasDrawOptions, CanvasPoint, ElementRect, PointerKind};
+
+/// Pen pressure for points without one.
+const DEFAULT_PRESSURE: f64 = 0.5;
+
+const FIND_JS: &str = r#"const canvas = (sel) => {
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    return el.tagName === 'CANVAS' ? el : el.querySelector('canvas');
+};
+"#;
+
+const RECT_SCRIPT: &str = r#"((sel) => {
+    const el = canvas(sel);
+    if (!el) return { found: !!document.querySelector(sel), canvas: false };
+    el.scrollIntoView({ block: 'center', inline: 'center' });
+    const r = el.getBoundingClientRect();
+    return { found: true, canvas: true, rect: { x: r.left, y: r.top, width: r.width, height: r.height } };
+})"#;
+
+const CAPTURE_SCRIPT: &str = r#"((sel) => {
+    const el = canvas(sel);
+    if (!el) return { found: !!document.querySelector(sel), canvas: false };
+    const r = el.getBoundingClientRect();
+    let data = null;
+    try {
+        data = el.toDataURL('image/png').split(',')[1];
+    } catch (e) {
+        return { found: true, canvas: true, tainted: true };
+    }
+    return {
+        found: true, canvas: true, data, width: el.width, height: el.height,
+        rect: { x: r.left + scrollX, y: r.top + scrollY, width: r.width, height: r.height },
+    };
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Located {
+    found: bool,
+    canvas: bool,
+    #[serde(default)]
+    rect: Option<ElementRect>,
+    #[serde(default)]
+    tainted: bool,
+    #[serde(default)]
+    data: Option<String>,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+}
+
+/// A canvas bitmap, before it is saved.
+pub struct CanvasCapture {
+    /// Base64 PNG
+    pub data: String,
+    /// Bitmap size (not the CSS size)
+    pub width: u32,
+    pub height: u32,
+    /// Bounds in document CSS pixels
+    pub rect: ElementRect,
+}
+
+/// One pointer position of a planned stroke.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Step {
+    /// Viewport CSS pixels
+    x: f64,
+    y: f64,
+    /// Milliseconds since the stroke started (as given for the first point)
+    at: f64,
+    pressure: f64,
+}
+
+/// Turn strokes into viewport positions with absolute timing.
+fn plan(
+    strokes: &[Vec<CanvasPoint>],
+    rect: &ElementRect,
+    options: &CanvasDrawOptions,
+) -> Result<Vec<Vec<Step>>> {
+    let (scale_x, scale_y) = if options.normalized {
+        (rect.width, rect.height)
+    } else {
+        (1.0, 1.0)
+    };
+
+    strokes
+        .iter()
+        .enumerate()
+        .map(|(index, stroke)| {
+            if stroke.is_empty() {
+                anyhow::bail!("Stroke {} has no points", index);
+            }
+            let mut last = 0.0;
+            stroke
+                .iter()
+                .enumerate()
+                .map(|(i, point)| {
+                    let at = point.t.unwrap_or_else(|| {
+                        if i == 0 {
+                            0.0
+                        } else {
+                            last + options.step_ms as f64
+                        }
+                    });
+                    if at < last {
+                        anyhow::bail!("Stroke {} goes back in time at point {}", index, i);
+                    }
+                    last = at;
+                    Ok(Step {
+                        x: rect.x + point.x * scale_x,
+                        y: rect.y + point.y * scale_y,
+                        at,
+                        pressure: point.pressure.unwrap_or(DEFAULT_PRESSURE).clamp(0.0, 1.0),
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+async fn locate(page: &Page, selector: &str, script: &str) -> Result<Located> {
+    let call = format!(
+        "(() => {{ {}return {}({}); }})()",
+        FIND_JS,
+        script,
+        serde_json::to_string(&resolve_selector(selector))?
+    );
+    let located: Located = page
+        .evaluate(call)
+        .await
+        .context("Failed to find canvas")?
+        .into_value()
+        .context("Failed to parse canvas")?;
+    if !located.found {
+        anyhow::bail!("Element not found: {}", selector);
+    }
+    if !located.canvas {
+        anyhow::bail!("No <canvas> at or inside {}", selector);
+    }
+    Ok(located)
+}
+
+async fn pointer(
+    page: &Page,
+    kind: DispatchMouseEventType,
+    step: &Step,
+    buttons: i64,
+    options: &CanvasDrawOptions,
+) -> Result<()> {
+    let mut event = DispatchMouseEventParams::builder()
+        .r#type(kind.clone())
+        .x(step.x)
+        .y(step.y)
+        .buttons(buttons);
+    if kind != DispatchMouseEventType::MouseMoved {
+        event = event.button(MouseButton::Left).click_count(1);
+    }
+    if options.pointer == PointerKind::Pen {
+        event = event
+            .pointer_type(DispatchMouseEventPointerType::Pen)
+            // Hovering and releasing pens press with no force
+            .force(if buttons == 0 { 0.0 } else { step.pressure });
+    }
+    page.execute(event.build().map_err(anyhow::Error::msg)?)
+        .await
+        .context("Failed to dispatch pointer event")?;
+    Ok(())
+}
+
+/// Replay `strokes` over the canvas at `selector`; returns the canvas rect
+/// and how long it took.
+pub async fn draw(
+    page: &Page,
+    selector: &str,
+    strokes: &[Vec<CanvasPoint>],
+    options: &CanvasDrawOptions,
+) -> Result<(ElementRect, Duration)> {
+    let located = locate(page, selector, RECT_SCRIPT).await?;
+    let rect = located.rect.context("Canvas has no layout")?;
+    let plan = plan(strokes, &rect, options)?;
+
+    let started = Instant::now();
+    for (index, stroke) in plan.iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(Duration::from_millis(options.step_ms)).await;
+        }
+        let first = &stroke[0];
+        pointer(page, DispatchMouseEventType::MouseMoved, first, 0, options).await?;
+        pointer(
+            page,
+            DispatchMouseEventType::MousePressed,
+            first,
+            1,
+            options,
+        )
+        .await?;
+
+        let stroke_start = Instant::now();
+        for step in &stroke[1..] {
+            let due = Duration::from_secs_f64((step.at - first.at) / 1000.0);
+            if let Some(wait) = due.checked_sub(stroke_start.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+            pointer(page, DispatchMouseEventType::MouseMoved, step, 1, options).await?;
+        }
+
+        let last = stroke.last().unwrap_or(first);
+        pointer(
+            page,
+            DispatchMouseEventType::MouseReleased,
+            last,
+            0,
+            options,
+        )
+        .await?;
+    }
+
+    Ok((rect, started.elapsed()))
+}
+
+/// Read the bitmap of the canvas at `selector` as a PNG.
+pub async fn capture(page: &Page, selector: &str) -> Result<CanvasCapture> {
+    let located = locate(page, selector, CAPTURE_SCRIPT).await?;
+    if located.tainted {
+        anyhow::bail!("Canvas is tainted by cross-origin content and can't be read; use screenshot with a selector");
+    }
+
+    Ok(CanvasCapture {
+        data: located.data.context("Canvas returned no image")?,
+        width: located.width,
+        height: located.height,
+        rect: located.rect.context("Canvas has no layout")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64, t: Option<f64>) -> CanvasPoint {
+        CanvasPoint {
+            x,
+            y,
+            t,
+            pressure: None,
+        }
+    }
+
+    fn options(normalized: bool) -> CanvasDrawOptions {
+        CanvasDrawOptions {
+            normalized,
+            step_ms: 10,
+            pointer: PointerKind::Mouse,
+        }
+    }
+
+    fn rect() -> ElementRect {
+        ElementRect {
+            x: 100.0,
+            y: 50.0,
+            width: 400.0,
+            height: 200.0,
+        }
+    }
+
+    #[test]
+    fn test_plan_offsets_and_spaces_points() {
+        let strokes = vec![vec![
+            point(0.0, 0.0, None),
+            point(10.0, 5.0, None),
+            point(20.0, 5.0, Some(100.0)),
+            point(30.0, 5.0, None),
+        ]];
+
+        let plan = plan(&strokes, &rect(), &options(false)).unwrap();
+
+        let positions: Vec<_> = plan[0].iter().map(|s| (s.x, s.y, s.at)).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (100.0, 50.0, 0.0),
+                (110.0, 55.0, 10.0),
+                (120.0, 55.0, 100.0),
+                (130.0, 55.0, 110.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_normalized() {
+        let strokes = vec![vec![point(0.5, 0.5, None), point(1.0, 1.0, None)]];
+
+        let plan = plan(&strokes, &rect(), &options(true)).unwrap();
+
+        assert_eq!((plan[0][0].x, plan[0][0].y), (300.0, 150.0));
+        assert_eq!((plan[0][1].x, plan[0][1].y), (500.0, 250.0));
+        assert_eq!(plan[0][0].pressure, DEFAULT_PRESSURE);
+    }
+
+    #[test]
+    fn test_plan_rejects_bad_strokes() {
+        assert!(plan(&[vec![]], &rect(), &options(false)).is_err());
+        assert!(plan(
+            &[vec![
+                point(0.0, 0.0, Some(50.0)),
+                point(1.0, 1.0, Some(20.0))
+            ]],
+            &rect(),
+            &options(false)
+        )
+        .is_err());
+    }
+}
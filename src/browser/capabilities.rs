@@ -0,0 +1,119 @@
+//! What a launched browser can do in its headless mode.
+//!
+//! Legacy headless starts fastest but is a separate, stripped-down browser
+//! without extensions or a PDF viewer. New headless is the full browser
+//! without a window, and headed mode adds the window but can't print to
+//! PDF. Extension support follows from the mode; the PDF viewer, PDF
+//! printing and screencast frames are probed on a scratch page, so the
+//! report describes the Chrome build actually running. A capability the
+//! mode should have but the browser lacks (say, `new` on a
+//! chrome-headless-shell build, which only runs old headless) is listed as
+//! missing.
+
+use std::time::Duration;
+
+use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+use chromiumoxide::page::Page;
+
+use super::screencast::{ScreencastOptions, SharedScreencast};
+use crate::models::{BrowserCapabilities, HeadlessMode};
+
+/// How long the screencast probe waits for a frame.
+const FRAME_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Capabilities each mode is expected to have.
+fn expected(mode: HeadlessMode) -> &'static [&'static str] {
+    match mode {
+        HeadlessMode::Old => &["print_to_pdf", "screencast"],
+        HeadlessMode::New => &["extensions", "pdf_viewer", "print_to_pdf", "screencast"],
+        HeadlessMode::Headed => &["extensions", "pdf_viewer", "screencast"],
+    }
+}
+
+/// Names of the capabilities in `expected(mode)` that `found` lacks.
+fn missing(mode: HeadlessMode, found: &BrowserCapabilities) -> Vec<String> {
+    expected(mode)
+        .iter()
+        .filter(|name| match **name {
+            "extensions" => !found.extensions,
+            "pdf_viewer" => !found.pdf_viewer,
+            "print_to_pdf" => !found.print_to_pdf,
+            "screencast" => !found.screencast,
+            _ => false,
+        })
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Whether a screencast of `page` delivers a frame.
+async fn screencast_frame(page: &Page) -> bool {
+    let shared = SharedScreencast::default();
+    let Ok(screencast) = shared.join(page, ScreencastOptions::default(), false).await else {
+        return false;
+    };
+    let (latest, mut frames) = screencast.subscribe();
+    // Repaint, so a frame is due even on a static page
+    let _ = page
+        .evaluate("document.documentElement.style.background = '#fefefe'")
+        .await;
+    let delivered = latest.is_some()
+        || matches!(
+            tokio::time::timeout(FRAME_TIMEOUT, frames.recv()).await,
+            Ok(Ok(_))
+        );
+    screencast.stop().await;
+    delivered
+}
+
+/// Probe what the browser can do on `page`, a page nothing else uses.
+pub async fn probe(page: &Page, mode: HeadlessMode) -> BrowserCapabilities {
+    let pdf_viewer = match page.evaluate("navigator.pdfViewerEnabled === true").await {
+        Ok(result) => result.into_value::<bool>().unwrap_or(false),
+        Err(_) => false,
+    };
+    let print_to_pdf = page.execute(PrintToPdfParams::default()).await.is_ok();
+    let screencast = screencast_frame(page).await;
+
+    let mut capabilities = BrowserCapabilities {
+        mode,
+        display: None,
+        extensions: mode != HeadlessMode::Old,
+        pdf_viewer,
+        print_to_pdf,
+        screencast,
+        missing: Vec::new(),
+    };
+    capabilities.missing = missing(mode, &capabilities);
+    capabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(mode: HeadlessMode, pdf_viewer: bool, print_to_pdf: bool) -> BrowserCapabilities {
+        BrowserCapabilities {
+            mode,
+            display: None,
+            extensions: mode != HeadlessMode::Old,
+            pdf_viewer,
+            print_to_pdf,
+            screencast: true,
+            missing: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_missing_capabilities() {
+        let old = found(HeadlessMode::Old, false, true);
+        assert!(missing(HeadlessMode::Old, &old).is_empty());
+
+        // Asked for new headless, got a build that only runs old
+        let shell = found(HeadlessMode::New, false, true);
+        assert_eq!(missing(HeadlessMode::New, &shell), vec!["pdf_viewer"]);
+
+        // Headed can't print, and isn't expected to
+        let headed = found(HeadlessMode::Headed, true, false);
+        assert!(missing(HeadlessMode::Headed, &headed).is_empty());
+    }
+}
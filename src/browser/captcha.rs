@@ -0,0 +1,138 @@
+//! CAPTCHA widget detection.
+//!
+//! Nothing here solves a CAPTCHA; the point is to say that one is in the
+//! way, so an orchestrator can hand the session to a human instead of
+//! retrying a click that can't work. reCAPTCHA, hCaptcha and Cloudflare
+//! Turnstile all render their widget in an iframe from their own host, so a
+//! widget is recognized by its frame URL: in the page's visible iframes for
+//! command results, and as the frame's document request for `captcha`
+//! session events. Frames that never ask anything of a user (invisible
+//! reCAPTCHA, hCaptcha's challenge popup before it opens) aren't counted.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::network::{EventRequestWillBeSent, ResourceType};
+use chromiumoxide::page::Page;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::events::forward_events;
+use crate::models::{CaptchaChallenge, CaptchaProvider, SessionEvent};
+
+/// `src` of the page's visible iframes.
+const FRAMES_SCRIPT: &str = r#"(() => [...document.querySelectorAll('iframe[src]')]
+    .filter((f) => {
+        const r = f.getBoundingClientRect();
+        const s = getComputedStyle(f);
+        return r.width > 0 && r.height > 0 && s.visibility !== 'hidden';
+    })
+    .map((f) => f.src))()"#;
+
+/// The CAPTCHA widget loaded from `url`, if it is one.
+pub fn challenge(url: &str) -> Option<CaptchaChallenge> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let path = parsed.path();
+
+    let recaptcha_host =
+        host == "google.com" || host == "www.google.com" || host.ends_with("recaptcha.net");
+    let hcaptcha_host = host == "hcaptcha.com" || host.ends_with(".hcaptcha.com");
+    let provider = if recaptcha_host && path.contains("/recaptcha/") && path.ends_with("/anchor") {
+        let invisible = parsed
+            .query_pairs()
+            .any(|(key, value)| key == "size" && value == "invisible");
+        (!invisible).then_some(CaptchaProvider::Recaptcha)?
+    } else if hcaptcha_host && path.ends_with("/hcaptcha.html") {
+        let popup = parsed
+            .fragment()
+            .unwrap_or_default()
+            .contains("frame=challenge");
+        (!popup).then_some(CaptchaProvider::Hcaptcha)?
+    } else if host == "challenges.cloudflare.com"
+        && path.starts_with("/cdn-cgi/challenge-platform/")
+    {
+        CaptchaProvider::Turnstile
+    } else {
+        return None;
+    };
+
+    Some(CaptchaChallenge {
+        provider,
+        frame_url: url.to_string(),
+    })
+}
+
+/// CAPTCHA widgets visible on the page, one per provider.
+pub async fn detect(page: &Page) -> Result<Vec<CaptchaChallenge>> {
+    let frames: Vec<String> = page
+        .evaluate(FRAMES_SCRIPT)
+        .await
+        .context("Failed to list frames")?
+        .into_value()
+        .context("Failed to parse frames")?;
+
+    let mut found: Vec<CaptchaChallenge> = Vec::new();
+    for widget in frames.iter().filter_map(|url| challenge(url)) {
+        if !found.iter().any(|c| c.provider == widget.provider) {
+            found.push(widget);
+        }
+    }
+    Ok(found)
+}
+
+/// Emit a `captcha` session event whenever a widget frame loads in `page`,
+/// from the returned task.
+pub async fn watch(
+    page: &Page,
+    sender: &broadcast::Sender<SessionEvent>,
+) -> Result<JoinHandle<()>> {
+    let requests = page
+        .event_listener::<EventRequestWillBeSent>()
+        .await
+        .context("Failed to listen for frame loads")?;
+    Ok(forward_events(requests, sender.clone(), |event| {
+        if event.r#type != Some(ResourceType::Document) {
+            return None;
+        }
+        challenge(&event.request.url).map(SessionEvent::Captcha)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_from_frame_url() {
+        let provider = |url| challenge(url).map(|c| c.provider);
+        assert_eq!(
+            provider("https://www.google.com/recaptcha/api2/anchor?ar=1&k=6Le&size=normal"),
+            Some(CaptchaProvider::Recaptcha)
+        );
+        assert_eq!(
+            provider("https://www.recaptcha.net/recaptcha/enterprise/anchor?k=6Le&size=compact"),
+            Some(CaptchaProvider::Recaptcha)
+        );
+        assert_eq!(
+            provider("https://www.google.com/recaptcha/api2/anchor?k=6Le&size=invisible"),
+            None
+        );
+        assert_eq!(
+            provider("https://www.google.com/recaptcha/api2/bframe?k=6Le"),
+            None
+        );
+        assert_eq!(
+            provider("https://newassets.hcaptcha.com/captcha/v1/abc/static/hcaptcha.html#frame=checkbox&id=0"),
+            Some(CaptchaProvider::Hcaptcha)
+        );
+        assert_eq!(
+            provider("https://newassets.hcaptcha.com/captcha/v1/abc/static/hcaptcha.html#frame=challenge&id=0"),
+            None
+        );
+        assert_eq!(
+            provider("https://challenges.cloudflare.com/cdn-cgi/challenge-platform/h/g/turnstile/if/ov2/av0/rcv/abc/0x4AAA/auto/fbE/new/normal/auto/"),
+            Some(CaptchaProvider::Turnstile)
+        );
+        assert_eq!(provider("https://www.google.com/search?q=recaptcha"), None);
+        assert_eq!(provider("not a url"), None);
+    }
+}
@@ -0,0 +1,147 @@
+//! Raw Chrome DevTools Protocol calls.
+//!
+//! An escape hatch for protocol features without a typed command: the
+//! method and params go to the session's page target as given and the
+//! result comes back untouched. Raw calls can do anything the protocol can
+//! (read cookies, run code in any frame, close targets), so the daemon only
+//! forwards them for domains it was started with.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use chromiumoxide::types::{Command, Method, MethodId};
+use serde::ser::{Serialize, Serializer};
+use serde_json::Value;
+
+/// A CDP call by name; serializes as its params.
+#[derive(Debug, Clone)]
+struct RawCommand {
+    method: String,
+    params: Value,
+}
+
+impl Serialize for RawCommand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.params.serialize(serializer)
+    }
+}
+
+impl Method for RawCommand {
+    fn identifier(&self) -> MethodId {
+        Cow::Owned(self.method.clone())
+    }
+}
+
+impl Command for RawCommand {
+    type Response = Value;
+}
+
+/// Domains whose methods may be called raw.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CdpDomains {
+    All,
+    Only(Vec<String>),
+}
+
+impl CdpDomains {
+    /// Parse `--allow-cdp-domains` values; `*` allows every domain.
+    pub fn new(domains: &[String]) -> Result<Self> {
+        let mut allowed = Vec::new();
+        for domain in domains.iter().map(|d| d.trim()).filter(|d| !d.is_empty()) {
+            if domain == "*" {
+                return Ok(Self::All);
+            }
+            if !is_identifier(domain) {
+                anyhow::bail!("Invalid CDP domain '{}'", domain);
+            }
+            allowed.push(domain.to_string());
+        }
+        if allowed.is_empty() {
+            anyhow::bail!("No CDP domains given (use '*' to allow all)");
+        }
+        Ok(Self::Only(allowed))
+    }
+
+    /// Whether `domain` may be called.
+    pub fn allows(&self, domain: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(domains) => domains.iter().any(|d| d == domain),
+        }
+    }
+}
+
+impl fmt::Display for CdpDomains {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => write!(f, "all domains"),
+            Self::Only(domains) => write!(f, "{}", domains.join(", ")),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// The domain of a `Domain.method` name.
+pub fn domain(method: &str) -> Result<&str> {
+    match method.split_once('.') {
+        Some((domain, name)) if is_identifier(domain) && is_identifier(name) => Ok(domain),
+        _ => anyhow::bail!(
+            "Invalid CDP method '{}' (expected Domain.method, e.g. Page.getLayoutMetrics)",
+            method
+        ),
+    }
+}
+
+/// Send `method` with `params` to the page's target; returns the raw result.
+pub async fn call(page: &Page, method: &str, params: Value) -> Result<Value> {
+    let params = match params {
+        Value::Null => Value::Object(Default::default()),
+        Value::Object(params) => Value::Object(params),
+        _ => anyhow::bail!("'params' must be an object"),
+    };
+    let response = page
+        .execute(RawCommand {
+            method: method.to_string(),
+            params,
+        })
+        .await
+        .with_context(|| format!("CDP call {} failed", method))?;
+    Ok(response.result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_allowlist() {
+        let only = CdpDomains::new(&["Page".to_string(), " Network ".to_string()]).unwrap();
+        assert!(only.allows("Page"));
+        assert!(only.allows("Network"));
+        assert!(!only.allows("Runtime"));
+        assert_eq!(only.to_string(), "Page, Network");
+
+        let all = CdpDomains::new(&["Page".to_string(), "*".to_string()]).unwrap();
+        assert_eq!(all, CdpDomains::All);
+        assert!(all.allows("Storage"));
+
+        assert!(CdpDomains::new(&[]).is_err());
+        assert!(CdpDomains::new(&["Page.navigate".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_method_domain() {
+        assert_eq!(domain("Page.getLayoutMetrics").unwrap(), "Page");
+        assert_eq!(domain("DOM.describeNode").unwrap(), "DOM");
+        assert!(domain("navigate").is_err());
+        assert!(domain("Page.").is_err());
+        assert!(domain(".navigate").is_err());
+        assert!(domain("Page.navigate.now").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Chained selectors that cross shadow roots and frames.
+//!
+//! `iframe#pay >>> my-widget >>> button.submit` is a list of CSS selectors,
+//! each matched inside what the one before it found: inside a frame's
+//! document when that was an `<iframe>`, inside its open shadow root (then
+//! its light DOM) when it hosts one, and among its descendants otherwise.
+//! Each step takes the first match, looking through nested open shadow
+//! roots as well. The element a chain ends on is given a ref (the one a
+//! snapshot gave it, if any, frame-qualified in a child frame), and the
+//! action runs on that ref, so it reaches elements in frames like a
+//! snapshot ref does.
+
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::dom::{BackendNodeId, DescribeNodeParams};
+use chromiumoxide::cdp::js_protocol::runtime::ReleaseObjectParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::aria::{self, RefRegistry};
+use super::frames::{self, ChildFrame};
+use super::oopif::{Connection, Remote};
+use super::shadow;
+
+/// Separates the steps of a chained selector.
+const SEPARATOR: &str = ">>>";
+
+/// Follows `steps` from the document until one matches nothing or an
+/// iframe is reached with steps left, and marks the element it stopped on.
+const WALK_SCRIPT: &str = r#"((steps) => {
+    document.querySelectorAll('[data-fgp-chain]').forEach((el) => el.removeAttribute('data-fgp-chain'));
+    const first = (root, sel) => {
+        const hit = root.querySelector(sel);
+        if (hit) return hit;
+        for (const r of shadowRoots(root)) {
+            const found = r.querySelector(sel);
+            if (found) return found;
+        }
+        return null;
+    };
+    let el = null;
+    for (let i = 0; i < steps.length; i++) {
+        let next;
+        try {
+            next = el === null ? first(document, steps[i])
+                : (el.shadowRoot && first(el.shadowRoot, steps[i])) || first(el, steps[i]);
+        } catch (e) {
+            return { stopped: i, found: false, error: e.message };
+        }
+        if (!next) return { stopped: i, found: false, error: null };
+        el = next;
+        if (i + 1 < steps.length && (el.tagName === 'IFRAME' || el.tagName === 'FRAME')) {
+            el.setAttribute('data-fgp-chain', '');
+            return { stopped: i + 1, found: true, error: null };
+        }
+    }
+    el.setAttribute('data-fgp-chain', '');
+    return { stopped: steps.length, found: true, error: null };
+})"#;
+
+/// Unmarks and returns the element the walk stopped on.
+const TAKE_SCRIPT: &str = r#"(() => {
+    const el = deepQuery('[data-fgp-chain]');
+    if (el) el.removeAttribute('data-fgp-chain');
+    return el;
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Walk {
+    /// Steps followed before stopping
+    stopped: usize,
+    found: bool,
+    error: Option<String>,
+}
+
+/// Whether `selector` chains several steps with `>>>`.
+pub fn is_chained(selector: &str) -> bool {
+    selector.contains(SEPARATOR)
+}
+
+/// The steps of a chained selector, trimmed.
+fn steps(selector: &str) -> Result<Vec<&str>> {
+    let steps: Vec<&str> = selector.split(SEPARATOR).map(str::trim).collect();
+    if steps.iter().any(|step| step.is_empty()) {
+        anyhow::bail!("Empty step in chained selector: {}", selector);
+    }
+    Ok(steps)
+}
+
+/// Backend node of the element the last walk in `frame` stopped on.
+async fn take(page: &Page, frame: Option<&ChildFrame>) -> Result<BackendNodeId> {
+    let object_id = frames::element(page, frame, shadow::call(TAKE_SCRIPT, ""))
+        .await?
+        .context("Chained selector target disappeared")?;
+    let remote = frame.and_then(|frame| frame.remote.as_ref());
+    let described = frames::execute(
+        page,
+        remote,
+        DescribeNodeParams::builder()
+            .object_id(object_id.clone())
+            .build(),
+    )
+    .await
+    .context("Failed to describe chained selector target");
+    let _ = frames::execute(page, remote, ReleaseObjectParams::new(object_id)).await;
+    Ok(described?.node.backend_node_id)
+}
+
+/// The frame held by the `<iframe>` element `owner` of `parent` (default:
+/// the page).
+async fn frame_of(
+    page: &Page,
+    connection: Option<&Connection>,
+    parent: Option<&ChildFrame>,
+    owner: BackendNodeId,
+) -> Result<ChildFrame> {
+    let session = parent.and_then(|parent| parent.remote.as_ref());
+    for frame in frames::child_frames(page, connection).await? {
+        // Backend node IDs only mean something in their own process
+        if !Remote::same(frame.holder(), session) {
+            continue;
+        }
+        if let Ok(id) = frames::owner(page, &frame).await {
+            if id == owner {
+                return Ok(frame);
+            }
+        }
+    }
+    anyhow::bail!("Frame has no document to search")
+}
+
+/// Resolve a chained `selector` to a ref for the element it ends on.
+/// Frames in other processes are followed when `connection` reaches them.
+pub async fn resolve(
+    page: &Page,
+    refs: &Mutex<RefRegistry>,
+    connection: Option<&Connection>,
+    selector: &str,
+) -> Result<String> {
+    let steps = steps(selector)?;
+    let mut frame: Option<ChildFrame> = None;
+    let mut done = 0;
+
+    loop {
+        let walk: Walk = frames::evaluate(
+            page,
+            frame.as_ref(),
+            shadow::call(WALK_SCRIPT, &serde_json::to_string(&steps[done..])?),
+        )
+        .await
+        .context("Failed to follow chained selector")?;
+        let reached = done + walk.stopped;
+        if let Some(error) = walk.error {
+            anyhow::bail!(
+                "Invalid selector {} in {}: {}",
+                steps[reached],
+                selector,
+                error
+            );
+        }
+        if !walk.found {
+            anyhow::bail!(
+                "Element not found: nothing matches {} in {}",
+                steps[reached],
+                selector
+            );
+        }
+
+        let element = take(page, frame.as_ref()).await?;
+        if reached == steps.len() {
+            return aria::assign_ref(page, refs, frame.as_ref(), element).await;
+        }
+        frame = Some(frame_of(page, connection, frame.as_ref(), element).await?);
+        done = reached;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps() {
+        assert!(is_chained("iframe#pay >>> my-widget >>> button.submit"));
+        assert!(!is_chained("form > button"));
+        assert_eq!(
+            steps("iframe#pay >>> my-widget>>>button.submit").unwrap(),
+            vec!["iframe#pay", "my-widget", "button.submit"]
+        );
+        assert!(steps("iframe#pay >>> >>> button").is_err());
+        assert!(steps(">>> button").is_err());
+    }
+}
@@ -13,30 +13,269 @@
 
 use anyhow::{Context, Result};
 use chromiumoxide::browser::{Browser, BrowserConfig};
-use chromiumoxide::cdp::browser_protocol::browser::BrowserContextId;
-use chromiumoxide::cdp::browser_protocol::input::{DispatchKeyEventParams, DispatchKeyEventType};
+use chromiumoxide::cdp::browser_protocol::browser::{
+    BrowserContextId, GrantPermissionsParams, PermissionType,
+};
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams, DispatchMouseEventType,
+    MouseButton,
+};
 use chromiumoxide::cdp::browser_protocol::network::{
-    CookieParam, SetCookiesParams, TimeSinceEpoch,
+    ClearBrowserCacheParams, CookieParam, SetCookiesParams, TimeSinceEpoch,
 };
-use chromiumoxide::cdp::browser_protocol::target::CreateBrowserContextParams;
+use chromiumoxide::cdp::browser_protocol::page::ScriptIdentifier;
+use chromiumoxide::cdp::browser_protocol::target::{
+    CreateBrowserContextParams, GetTargetsParams, TargetId,
+};
+use chromiumoxide::cdp::js_protocol::heap_profiler::CollectGarbageParams;
 use chromiumoxide::page::Page;
-use futures::StreamExt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
 
-use super::aria::extract_aria_tree;
+use super::aria::{
+    extract_aria_tree, focused_ref, read_aria_tree, read_mutation_marker, render_text,
+    track_mutations, MutationMarker, RefRegistry,
+};
+use super::autofill::{self, Action};
+use super::bandwidth::{self, Meter};
+use super::field::{self, Placement};
+use super::frames::ChildFrame;
+use super::keyboard::{self, KeyboardLayout};
+use super::links::{self, PageLinks};
+use super::maintenance::{MaintenancePolicy, MAX_SCROLL_POSITIONS};
+use super::notifications::{self, Inbox};
+use super::oopif::Connection;
+use super::paging::{self, SnapshotCursor};
+use super::popups::{self, Opener, Popups};
+use super::replay::Replay;
+use super::screencast::{Screencast, ScreencastOptions, SharedScreencast};
+use super::shadow::{self, Target};
+use super::timeouts::{Timeouts, DEFAULT_TIMEOUT};
+use super::xvfb::{self, VirtualDisplay};
+use super::zoom::{self, Zoom};
+use super::{
+    assertions, blockers, canvas, capabilities, captcha, cdp, chain, clock, csp, date, drag,
+    dry_run, environment, events, find, focus, form, frames, gate, media, navigation, overlay,
+    print, ranking, recovery, refs, results, rich_text, screenshot, scroll, select, selection,
+    snapshot_diff, snapshot_filter, spatial, stability, table, third_party, upload, variants,
+    xpath,
+};
 use crate::models::{
-    AriaSnapshot, ClickResult, FillResult, LocalStorageState, NavigationResult, ScreenshotResult,
-    SerializableCookie,
+    AriaNode, AriaSnapshot, AriaSnapshotDiff, Assertion, AssertionResult, AutofillFailure,
+    AutofillResult, AutofilledField, BandwidthUsage, BlockerReport, BrowserCapabilities,
+    CanvasDrawOptions, CanvasDrawResult, CanvasPoint, ClearResult, ClickButton, ClickOptions,
+    ClickResult, ClipboardCopy, ClockTickMode, CspMode, CspTrialResult, DateStrategy, DragResult,
+    DryRunAction, DryRunReport, ElementFingerprint, ElementQuery, ElementRanking, ElementRect,
+    EmulationState, EnvironmentSummary, FakeClockResult, FastForwardResult, FillResult,
+    FocusResult, FormDescription, FoundElements, HeadlessMode, HighlightResult, HoverResult,
+    ImageFormat, LocalStorageState, LocaleVariant, LocaleVariantSnapshot, MaintenanceReport,
+    MediaAction, MediaControlResult, MediaList, MediaMuteResult, NavigationResult,
+    NotificationList, ObservedEvent, OptionQuery, PdfOptions, PdfResult, PopupPolicy, PrintOptions,
+    RankedMatch, RecoveryStep, RefExport, RichTextFormat, RichTextResult, ScreenshotOptions,
+    ScreenshotResult, ScrollBlock, ScrollRestoreResult, ScrollResult, ScrollState, SearchResults,
+    SelectResult, SerializableCookie, SessionEvent, SessionEvents, SessionInfo, SetDateResult,
+    SnapshotFilter, SpatialAnchor, SpatialDirection, SpatialFilter, SpatialResult, StabilityResult,
+    TableCell, TableQuery, TextRange, TextSelection, ThirdPartyReport, TypeTextResult,
+    UploadResult, ValidationErrors, VerifyReport, WaitUntil, ZoomResult,
 };
+use crate::robots::RobotsPolicy;
+use crate::throttle::OriginThrottle;
 
 /// A browser session with isolated context.
 pub struct BrowserSession {
     pub id: String,
     pub context_id: Option<BrowserContextId>, // None = default context
     pub page: Page,
+    /// When `page` was created, for recycling
+    page_created: Instant,
+    /// Last snapshot plus the page-side marker it was taken under
+    snapshot_cache: Option<CachedSnapshot>,
+    /// Nodes of the last two snapshots served (previous, latest), for diffs
+    snapshot_nodes: (Option<Vec<AriaNode>>, Option<Vec<AriaNode>>),
+    /// Last snapshot served to an observer and its ID, for its cursors
+    observed: Option<(String, AriaSnapshot)>,
+    /// Ref numbers handed out on the current document
+    refs: Arc<std::sync::Mutex<RefRegistry>>,
+    /// Last scroll position observed per URL, for `restore_scroll`
+    scroll_positions: HashMap<String, ScrollState>,
+    /// Draw a synthetic cursor and click ripples at interaction points
+    show_cursor: bool,
+    /// Layout used to generate key events for `fill` and `press`
+    keyboard_layout: KeyboardLayout,
+    /// Bounded fan-out of CDP events for this session's page
+    events: broadcast::Sender<SessionEvent>,
+    /// Tasks feeding `events`, `notifications` and `bandwidth` from `page`
+    watchers: Vec<JoinHandle<()>>,
+    /// Recent events, for clients that ask after the fact
+    replay: Replay,
+    /// Web Notifications the page has shown
+    notifications: Inbox,
+    /// Init script and mode of the fake clock, when one is set
+    fake_clock: Option<(ScriptIdentifier, ClockTickMode)>,
+    /// Network traffic since the session was created
+    bandwidth: Meter,
+    /// Page zoom, when not 100%
+    zoom: Option<Zoom>,
+    /// What happens to tabs and windows the page opens, and the page script
+    /// enforcing it
+    popup_policy: (PopupPolicy, Option<ScriptIdentifier>),
+    /// Session whose page opened this one, for adopted popups
+    opener: Option<String>,
+    /// Time limits set for this session, instead of the daemon's
+    timeouts: Option<Timeouts>,
+    /// Screencast of `page`, shared by recordings, live view and frame streams
+    screencast: SharedScreencast,
+    /// robots.txt the page's documents are held to, if enforced
+    robots: Option<Arc<RobotsPolicy>>,
+}
+
+impl BrowserSession {
+    /// Create a session and start forwarding its page events.
+    async fn new(
+        id: &str,
+        context_id: Option<BrowserContextId>,
+        page: Page,
+        robots: Option<Arc<RobotsPolicy>>,
+    ) -> Self {
+        let (events, _) = broadcast::channel(events::EVENT_CHANNEL_CAPACITY);
+        let replay = Replay::record(&events);
+
+        let mut session = Self {
+            id: id.to_string(),
+            context_id,
+            page,
+            page_created: Instant::now(),
+            snapshot_cache: None,
+            snapshot_nodes: (None, None),
+            observed: None,
+            refs: Arc::default(),
+            scroll_positions: HashMap::new(),
+            show_cursor: false,
+            keyboard_layout: KeyboardLayout::default(),
+            events,
+            watchers: Vec::new(),
+            replay,
+            notifications: Inbox::default(),
+            fake_clock: None,
+            bandwidth: Meter::default(),
+            zoom: None,
+            popup_policy: (PopupPolicy::Allow, None),
+            opener: None,
+            timeouts: None,
+            screencast: SharedScreencast::default(),
+            robots,
+        };
+        session.watch_page().await;
+        session
+    }
+
+    /// Forward the page's events, notifications, CAPTCHAs and traffic to
+    /// the session.
+    async fn watch_page(&mut self) {
+        let watchers = self.feeds().watch(&self.page).await;
+        self.watchers.extend(watchers);
+    }
+
+    /// What a page's watchers feed, to attach them without the session.
+    fn feeds(&self) -> Feeds {
+        Feeds {
+            id: self.id.clone(),
+            events: self.events.clone(),
+            notifications: self.notifications.clone(),
+            bandwidth: self.bandwidth.clone(),
+            robots: self.robots.clone(),
+        }
+    }
+
+    /// Stop the tasks started by `watch_page`, before the page is closed or
+    /// swapped out.
+    fn unwatch_page(&mut self) {
+        for task in self.watchers.drain(..) {
+            task.abort();
+        }
+    }
+
+    /// Whether the session holds state tied to its page that a fresh page
+    /// would lose.
+    fn page_bound(&self) -> bool {
+        self.fake_clock.is_some()
+            || self.zoom.is_some()
+            || self.popup_policy.0 != PopupPolicy::Allow
+            || self.opener.is_some()
+            || self.screencast.is_running()
+    }
+}
+
+/// A session's event channel, notification inbox and traffic meter.
+struct Feeds {
+    id: String,
+    events: broadcast::Sender<SessionEvent>,
+    notifications: Inbox,
+    bandwidth: Meter,
+    robots: Option<Arc<RobotsPolicy>>,
+}
+
+impl Feeds {
+    /// Start the tasks feeding these from `page`.
+    async fn watch(&self, page: &Page) -> Vec<JoinHandle<()>> {
+        let id = &self.id;
+        let mut watchers = Vec::new();
+        match events::attach_page_events(page, &self.events).await {
+            Ok(tasks) => watchers.extend(tasks),
+            Err(e) => tracing::warn!("Session {} will not emit events: {}", id, e),
+        }
+        match notifications::capture(page, &self.events, &self.notifications).await {
+            Ok(task) => watchers.push(task),
+            Err(e) => tracing::warn!("Session {} will not capture notifications: {}", id, e),
+        }
+        match captcha::watch(page, &self.events).await {
+            Ok(task) => watchers.push(task),
+            Err(e) => tracing::warn!("Session {} will not report CAPTCHAs: {}", id, e),
+        }
+        match bandwidth::track(page, &self.bandwidth).await {
+            Ok(task) => watchers.push(task),
+            Err(e) => tracing::warn!("Session {} will not count bandwidth: {}", id, e),
+        }
+        watchers.extend(self.guard(page).await);
+        watchers
+    }
+
+    /// Hold `page`'s documents to the session's robots.txt, if enforced.
+    async fn guard(&self, page: &Page) -> Option<JoinHandle<()>> {
+        let robots = Arc::clone(self.robots.as_ref()?);
+        match gate::guard(page, robots).await {
+            Ok(task) => Some(task),
+            Err(e) => {
+                tracing::warn!(
+                    "Session {} will not hold pages to robots.txt: {}",
+                    self.id,
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+/// A snapshot that stays valid while the page's mutation marker is unchanged.
+///
+/// The cache also backs pagination: cursors name the snapshot `id` they were
+/// cut from, so later pages come from the same tree even after it goes stale.
+struct CachedSnapshot {
+    id: String,
+    /// None when mutation tracking failed - never reused, only paged
+    marker: Option<MutationMarker>,
+    snapshot: AriaSnapshot,
+    /// Cursor id and nodes of the last filtered view of `snapshot`
+    filtered: Option<(String, AriaSnapshot)>,
+    /// When `snapshot` was taken
+    taken: Instant,
+    /// Whether a page of it (or of `filtered`) was served with a cursor
+    paged: bool,
 }
 
 /// Chrome browser client with multi-session support for parallel requests.
@@ -46,16 +285,30 @@ pub struct BrowserClient {
     default_session_id: String,
     #[allow(dead_code)]
     user_data_dir: PathBuf,
+    /// Popup policies of the sessions, and popups waiting to become sessions
+    popups: Popups,
+    /// Attached to a running Chrome rather than launched
+    connected: bool,
+    /// How the browser runs (always headed when connected)
+    mode: HeadlessMode,
+    /// Xvfb server a headed browser runs on, if one was started
+    display: Option<VirtualDisplay>,
+    /// Pages replaced by recycling, closed on the next maintenance pass
+    retired: std::sync::Mutex<Vec<Page>>,
+    /// Second DevTools connection, reaching frames in other processes
+    frames: Option<Connection>,
+    /// robots.txt every session's documents are held to, once enforced
+    robots: std::sync::OnceLock<Arc<RobotsPolicy>>,
 }
 
 impl BrowserClient {
     /// Create a new browser client with a default session.
-    pub async fn new(user_data_dir: PathBuf, headless: bool) -> Result<Self> {
+    pub async fn new(user_data_dir: PathBuf, mode: HeadlessMode) -> Result<Self> {
         // Ensure user data directory exists
         tokio::fs::create_dir_all(&user_data_dir).await?;
 
         // Find Chrome executable - check common paths
-        let chrome_path = Self::find_chrome_executable()?;
+        let chrome_path = Self::find_chrome_executable(mode)?;
 
         let mut builder = BrowserConfig::builder()
             .chrome_executable(chrome_path)
@@ -63,7 +316,6 @@ impl BrowserClient {
             .viewport(None)
             .no_sandbox()
             // Performance flags (matching agent-browser/Playwright)
-            .arg("--headless=old")
             .arg("--disable-gpu")
             .arg("--disable-dev-shm-usage")
             .arg("--disable-back-forward-cache")
@@ -71,7 +323,6 @@ impl BrowserClient {
             .arg("--disable-breakpad")
             .arg("--disable-component-extensions-with-background-pages")
             .arg("--disable-default-apps")
-            .arg("--disable-extensions")
             .arg("--disable-hang-monitor")
             .arg("--disable-ipc-flooding-protection")
             .arg("--disable-popup-blocking")
@@ -85,20 +336,33 @@ impl BrowserClient {
             .arg("--password-store=basic")
             .arg("--disable-features=MediaRouter,OptimizationHints,Translate,ThirdPartyStoragePartitioning");
 
-        if !headless {
-            builder = builder.with_head();
+        let mut display = None;
+        match mode {
+            // Old headless can't run extensions anyway
+            HeadlessMode::Old => {
+                builder = builder.arg("--headless=old").arg("--disable-extensions");
+            }
+            HeadlessMode::New => builder = builder.arg("--headless=new"),
+            HeadlessMode::Headed => {
+                builder = builder.with_head();
+                if xvfb::needed() {
+                    let virtual_display = VirtualDisplay::start().await?;
+                    builder = builder.arg(format!("--display={}", virtual_display.display()));
+                    display = Some(virtual_display);
+                }
+            }
         }
 
         let config = builder
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build browser config: {}", e))?;
 
-        let (browser, mut handler) = Browser::launch(config)
+        let (browser, handler) = Browser::launch(config)
             .await
             .context("Failed to launch browser")?;
 
-        // Spawn handler task - just drain events, no logging overhead
-        tokio::spawn(async move { while handler.next().await.is_some() {} });
+        // Handler only routes messages; event consumers live on their own tasks
+        events::spawn_handler(handler);
 
         // Create default session with pre-warmed page
         let default_page = browser
@@ -107,20 +371,30 @@ impl BrowserClient {
             .context("Failed to create initial page")?;
 
         let default_session_id = "default".to_string();
-        let default_session = BrowserSession {
-            id: default_session_id.clone(),
-            context_id: None, // Uses browser's default context
-            page: default_page,
-        };
+        // Uses browser's default context
+        let default_session =
+            BrowserSession::new(&default_session_id, None, default_page, None).await;
 
         let mut sessions = HashMap::new();
         sessions.insert(default_session_id.clone(), default_session);
 
+        let popups = Popups::default();
+        if let Err(e) = popups::track(&browser, &popups).await {
+            tracing::warn!("Popup policies will not apply: {}", e);
+        }
+
         Ok(Self {
             browser,
             sessions: Arc::new(RwLock::new(sessions)),
             default_session_id,
             user_data_dir,
+            popups,
+            connected: false,
+            mode,
+            display,
+            retired: Default::default(),
+            frames: connect_frames(&browser).await,
+            robots: Default::default(),
         })
     }
 
@@ -143,7 +417,7 @@ impl BrowserClient {
     pub async fn connect(debug_url: &str) -> Result<Self> {
         tracing::info!("Connecting to existing Chrome at: {}", debug_url);
 
-        let (browser, mut handler) = Browser::connect(debug_url).await.with_context(|| {
+        let (browser, handler) = Browser::connect(debug_url).await.with_context(|| {
             format!(
                 "Failed to connect to Chrome at {}. \
                  Make sure Chrome is running with --remote-debugging-port=9222",
@@ -151,8 +425,8 @@ impl BrowserClient {
             )
         })?;
 
-        // Spawn handler task - just drain events, no logging overhead
-        tokio::spawn(async move { while handler.next().await.is_some() {} });
+        // Handler only routes messages; event consumers live on their own tasks
+        events::spawn_handler(handler);
 
         // Get existing pages or create a new one
         let pages = browser.pages().await.context("Failed to list pages")?;
@@ -170,15 +444,18 @@ impl BrowserClient {
         };
 
         let default_session_id = "default".to_string();
-        let default_session = BrowserSession {
-            id: default_session_id.clone(),
-            context_id: None, // Uses browser's default context (user's real context!)
-            page: default_page,
-        };
+        // Uses browser's default context (user's real context!)
+        let default_session =
+            BrowserSession::new(&default_session_id, None, default_page, None).await;
 
         let mut sessions = HashMap::new();
         sessions.insert(default_session_id.clone(), default_session);
 
+        let popups = Popups::default();
+        if let Err(e) = popups::track(&browser, &popups).await {
+            tracing::warn!("Popup policies will not apply: {}", e);
+        }
+
         // Use a placeholder for user_data_dir since we're connecting to existing browser
         let user_data_dir = PathBuf::from("/connected-browser");
 
@@ -189,6 +466,13 @@ impl BrowserClient {
             sessions: Arc::new(RwLock::new(sessions)),
             default_session_id,
             user_data_dir,
+            popups,
+            connected: true,
+            mode: HeadlessMode::Headed,
+            display: None,
+            retired: Default::default(),
+            frames: connect_frames(&browser).await,
+            robots: Default::default(),
         })
     }
 
@@ -220,11 +504,13 @@ impl BrowserClient {
             .await
             .context("Failed to create page in context")?;
 
-        let session = BrowserSession {
-            id: session_id.to_string(),
-            context_id: Some(context_id),
+        let session = BrowserSession::new(
+            session_id,
+            Some(context_id),
             page,
-        };
+            self.robots.get().cloned(),
+        )
+        .await;
 
         sessions.insert(session_id.to_string(), session);
         tracing::info!("Created new session: {}", session_id);
@@ -232,29 +518,247 @@ impl BrowserClient {
         Ok(session_id.to_string())
     }
 
-    /// Close and dispose a session.
-    pub async fn close_session(&self, session_id: &str) -> Result<()> {
+    /// Close and dispose a session, returning its network traffic (None if
+    /// there was no such session).
+    pub async fn close_session(&self, session_id: &str) -> Result<Option<BandwidthUsage>> {
         if session_id == self.default_session_id {
             anyhow::bail!("Cannot close default session");
         }
 
         let mut sessions = self.sessions.write().await;
 
-        if let Some(session) = sessions.remove(session_id) {
-            if let Some(context_id) = session.context_id {
-                self.browser
-                    .dispose_browser_context(context_id)
-                    .await
-                    .context("Failed to dispose browser context")?;
+        let Some(mut session) = sessions.remove(session_id) else {
+            return Ok(None);
+        };
+        // Read before disposing, so the last responses are counted
+        let bandwidth = session.bandwidth.usage();
+        session.unwatch_page();
+        self.popups.unwatch(session.page.target_id());
+        match session.context_id {
+            Some(context_id) => self
+                .browser
+                .dispose_browser_context(context_id)
+                .await
+                .context("Failed to dispose browser context")?,
+            // Adopted popups share their opener's context
+            None => {
+                let _ = session.page.close().await;
+            }
+        }
+        tracing::info!(
+            "Closed session: {} ({} requests, {} bytes down, {} bytes up)",
+            session_id,
+            bandwidth.total.requests,
+            bandwidth.total.downloaded_bytes,
+            bandwidth.total.uploaded_bytes
+        );
+
+        Ok(Some(bandwidth))
+    }
+
+    /// Clear the sessions' caches and buffers, remove those whose page has
+    /// closed, and recycle those due under `policy` unless they are `busy`
+    /// (see `maintenance`).
+    pub async fn maintain(
+        &self,
+        policy: &MaintenancePolicy,
+        busy: &HashSet<String>,
+        robots: Option<&RobotsPolicy>,
+        throttle: &OriginThrottle,
+    ) -> Result<MaintenanceReport> {
+        self.adopt_popups().await;
+        let mut report = MaintenanceReport::default();
+
+        let retired = std::mem::take(&mut *self.retired.lock().unwrap_or_else(|e| e.into_inner()));
+        for page in retired {
+            let _ = page.close().await;
+        }
+
+        let live: HashSet<TargetId> = self
+            .browser
+            .execute(GetTargetsParams::default())
+            .await
+            .context("Failed to list targets")?
+            .result
+            .target_infos
+            .into_iter()
+            .map(|target| target.target_id)
+            .collect();
+        let closed: Vec<String> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| s.id != self.default_session_id && !live.contains(s.page.target_id()))
+            .map(|s| s.id.clone())
+            .collect();
+        for id in closed {
+            if let Err(e) = self.close_session(&id).await {
+                tracing::warn!("Failed to dispose closed session {}: {}", id, e);
+            }
+            report.pruned.push(id);
+        }
+
+        let mut pages = Vec::new();
+        let mut scrolled = Vec::new();
+        let mut due = Vec::new();
+        {
+            let mut sessions = self.sessions.write().await;
+            for session in sessions.values_mut() {
+                if session
+                    .snapshot_cache
+                    .as_ref()
+                    .is_some_and(|cache| !policy.keeps_snapshot(cache.taken.elapsed(), cache.paged))
+                {
+                    session.snapshot_cache = None;
+                }
+                if session.scroll_positions.len() > MAX_SCROLL_POSITIONS {
+                    scrolled.push((session.id.clone(), session.page.clone()));
+                }
+                report.trimmed_events += session.replay.trim(policy.keep_events);
+                pages.push(session.page.clone());
+
+                if !self.connected
+                    && !busy.contains(&session.id)
+                    && !session.page_bound()
+                    && policy.due_for_recycle(session.page_created.elapsed())
+                {
+                    due.push(session.id.clone());
+                }
+            }
+            report.sessions = sessions.len();
+        }
+
+        // Outside the lock: a hung renderer shouldn't hold up every session
+        for (id, page) in scrolled {
+            let url = page.url().await.ok().flatten();
+            if let Some(session) = self.sessions.write().await.get_mut(&id) {
+                session
+                    .scroll_positions
+                    .retain(|scrolled, _| Some(scrolled) == url.as_ref());
+            }
+        }
+        for page in pages {
+            let _ = page.execute(CollectGarbageParams::default()).await;
+            let _ = page.execute(ClearBrowserCacheParams::default()).await;
+        }
+
+        for id in due {
+            match self.recycle(&id, robots, throttle).await {
+                Ok(()) => report.recycled.push(id),
+                Err(e) => tracing::warn!("Failed to recycle session {}: {}", id, e),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Move a session to a fresh page at the same URL, in a new renderer
+    /// process. The old page is retired rather than closed, so actions
+    /// already holding it can finish. The reload honours `robots` and
+    /// `throttle` like any navigation; a session whose URL robots.txt now
+    /// disallows keeps its page.
+    async fn recycle(
+        &self,
+        session_id: &str,
+        robots: Option<&RobotsPolicy>,
+        throttle: &OriginThrottle,
+    ) -> Result<()> {
+        let (context_id, old, feeds) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(session_id)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+            (
+                session.context_id.clone(),
+                session.page.clone(),
+                session.feeds(),
+            )
+        };
+        let url = old
+            .url()
+            .await
+            .ok()
+            .flatten()
+            .filter(|url| url.starts_with("http"));
+        if let (Some(robots), Some(url)) = (robots, url.as_deref()) {
+            robots.check(url).await?;
+        }
+
+        let mut target =
+            chromiumoxide::cdp::browser_protocol::target::CreateTargetParams::builder()
+                .url("about:blank");
+        if let Some(context_id) = context_id {
+            target = target.browser_context_id(context_id);
+        }
+        let page = self
+            .browser
+            .new_page(
+                target
+                    .build()
+                    .map_err(|e| anyhow::anyhow!("Failed to build target params: {:?}", e))?,
+            )
+            .await
+            .context("Failed to create page")?;
+        if let Some(url) = url {
+            let _permit = throttle.acquire(&url).await;
+            if let Err(e) =
+                navigation::navigate(&page, &url, WaitUntil::Load, DEFAULT_TIMEOUT).await
+            {
+                tracing::warn!(
+                    "Recycled session {} did not reload {}: {}",
+                    session_id,
+                    url,
+                    e
+                );
             }
-            tracing::info!("Closed session: {}", session_id);
         }
+        // Attached before the lock, which every call on every session takes
+        let watchers = feeds.watch(&page).await;
+
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            drop(sessions);
+            for task in watchers {
+                task.abort();
+            }
+            let _ = page.close().await;
+            anyhow::bail!("Session {} closed while recycling", session_id);
+        };
+        session.unwatch_page();
+        session.watchers = watchers;
+        let old = std::mem::replace(&mut session.page, page);
+        session.page_created = Instant::now();
+        session.snapshot_cache = None;
+        session.snapshot_nodes = (None, None);
+        session.observed = None;
+        session.refs = Arc::default();
+        drop(sessions);
+        self.retired
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(old);
+        tracing::info!("Recycled session {} onto a fresh page", session_id);
 
         Ok(())
     }
 
+    /// Hold the documents every session's page loads to `robots`, whatever
+    /// navigates there (see `gate`). Only the first policy given applies.
+    pub async fn enforce_robots(&self, robots: Arc<RobotsPolicy>) {
+        if self.robots.set(robots).is_err() {
+            return;
+        }
+        for session in self.sessions.write().await.values_mut() {
+            session.robots = self.robots.get().cloned();
+            let guard = session.feeds().guard(&session.page).await;
+            session.watchers.extend(guard);
+        }
+    }
+
     /// List all active sessions.
     pub async fn list_sessions(&self) -> Vec<String> {
+        self.adopt_popups().await;
         let sessions = self.sessions.read().await;
         sessions
             .values()
@@ -262,8 +766,201 @@ impl BrowserClient {
             .collect()
     }
 
+    /// URL of the session's page, for calls that reload it by loading it
+    /// again.
+    pub async fn reload_url(&self, session_id: Option<&str>) -> Result<String> {
+        let page = self.get_page(session_id).await?;
+        page.url()
+            .await
+            .context("Failed to read page URL")?
+            .filter(|url| url.starts_with("http"))
+            .context("No page to reload; pass a url")
+    }
+
+    /// Load `url` with a trial Content-Security-Policy and report what it
+    /// blocks or would block.
+    pub async fn csp_trial(
+        &self,
+        url: &str,
+        policy: &str,
+        mode: CspMode,
+        settle: Duration,
+        limit: Duration,
+        session_id: Option<&str>,
+    ) -> Result<CspTrialResult> {
+        let page = self.get_page(session_id).await?;
+        let guarded = self.robots.get().is_some();
+        csp::trial(&page, url, policy, mode, settle, limit, guarded).await
+    }
+
+    /// Requests of a session's current page, grouped by registrable domain.
+    pub async fn third_party_report(&self, session_id: Option<&str>) -> Result<ThirdPartyReport> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (page, requests) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.page.clone(), session.bandwidth.page_requests())
+        };
+        third_party::report(&page, &requests).await
+    }
+
+    /// Classify whatever blocks the current page: a login wall, paywall,
+    /// CAPTCHA or interstitial.
+    pub async fn detect_blockers(&self, session_id: Option<&str>) -> Result<BlockerReport> {
+        let page = self.get_page(session_id).await?;
+        blockers::detect(&page).await
+    }
+
+    /// Same-origin links on the current page, for `check_links`.
+    pub async fn page_links(&self, session_id: Option<&str>) -> Result<PageLinks> {
+        let page = self.get_page(session_id).await?;
+        links::collect(&page).await
+    }
+
+    /// Load `url` in a throwaway context emulating `variant`, and capture it
+    /// as a text snapshot once it has settled for `settle`.
+    pub async fn locale_variant(
+        &self,
+        url: &str,
+        variant: LocaleVariant,
+        settle: Duration,
+    ) -> Result<LocaleVariantSnapshot> {
+        let context_id = self
+            .browser
+            .create_browser_context(CreateBrowserContextParams::default())
+            .await
+            .context("Failed to create browser context")?;
+
+        let captured = async {
+            let page = self
+                .browser
+                .new_page(
+                    chromiumoxide::cdp::browser_protocol::target::CreateTargetParams::builder()
+                        .url("about:blank")
+                        .browser_context_id(context_id.clone())
+                        .build()
+                        .map_err(|e| anyhow::anyhow!("Failed to build target params: {:?}", e))?,
+                )
+                .await
+                .context("Failed to create page in context")?;
+            if variant.geolocation.is_some() {
+                self.browser
+                    .execute(
+                        GrantPermissionsParams::builder()
+                            .permissions(vec![PermissionType::Geolocation])
+                            .browser_context_id(context_id.clone())
+                            .build()
+                            .map_err(anyhow::Error::msg)?,
+                    )
+                    .await
+                    .context("Failed to grant geolocation")?;
+            }
+            variants::emulate(&page, &variant).await?;
+            let guard = match self.robots.get() {
+                Some(robots) => Some(gate::guard(&page, Arc::clone(robots)).await?),
+                None => None,
+            };
+            let captured =
+                variants::capture(&page, self.frames.as_ref(), url, variant, settle).await;
+            if let Some(guard) = guard {
+                guard.abort();
+            }
+            captured
+        }
+        .await;
+
+        if let Err(e) = self.browser.dispose_browser_context(context_id).await {
+            tracing::warn!("Failed to dispose locale variant context: {}", e);
+        }
+        captured
+    }
+
+    /// URL and network traffic of every active session.
+    pub async fn session_infos(&self) -> Vec<SessionInfo> {
+        self.adopt_popups().await;
+        let sessions = self.sessions.read().await;
+        let mut infos = Vec::with_capacity(sessions.len());
+        for session in sessions.values() {
+            infos.push(SessionInfo {
+                id: session.id.clone(),
+                url: session.page.url().await.ok().flatten(),
+                active: session.id == self.default_session_id,
+                bandwidth: session.bandwidth.usage(),
+                opener: session.opener.clone(),
+            });
+        }
+        infos.sort_by(|a, b| a.id.cmp(&b.id));
+        infos
+    }
+
+    /// Subscribe to a session's event stream, along with the events it has
+    /// recorded so far when `replay` is set.
+    pub async fn subscribe_events(
+        &self,
+        replay: bool,
+        session_id: Option<&str>,
+    ) -> Result<(broadcast::Receiver<SessionEvent>, Vec<ObservedEvent>)> {
+        let sessions = self.sessions.read().await;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let session = sessions
+            .get(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+
+        Ok(if replay {
+            session.replay.subscribe(&session.events)
+        } else {
+            (session.events.subscribe(), Vec::new())
+        })
+    }
+
+    /// Recent events of a session numbered `since` or later, of the given
+    /// `types` if any.
+    pub async fn session_events(
+        &self,
+        since: u64,
+        types: &[String],
+        session_id: Option<&str>,
+    ) -> Result<SessionEvents> {
+        let sessions = self.sessions.read().await;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+
+        sessions
+            .get(sid)
+            .map(|s| s.replay.events(sid, since, types))
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))
+    }
+
+    /// Web Notifications shown in a session, emptying its inbox when `clear`.
+    pub async fn get_notifications(
+        &self,
+        clear: bool,
+        session_id: Option<&str>,
+    ) -> Result<NotificationList> {
+        let sessions = self.sessions.read().await;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+
+        sessions
+            .get(sid)
+            .map(|s| s.notifications.list(clear))
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))
+    }
+
+    /// Map the current refs (or only `refs`) to CDP node handles, for other
+    /// CDP clients attached to the same browser.
+    pub async fn export_refs(
+        &self,
+        refs: Option<&[String]>,
+        session_id: Option<&str>,
+    ) -> Result<RefExport> {
+        let page = self.get_page(session_id).await?;
+        refs::export(&page, refs).await
+    }
+
     /// Get page for a session (or default).
     async fn get_page(&self, session_id: Option<&str>) -> Result<Page> {
+        self.adopt_popups().await;
         let sessions = self.sessions.read().await;
         let sid = session_id.unwrap_or(&self.default_session_id);
 
@@ -273,72 +970,526 @@ impl BrowserClient {
             .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))
     }
 
-    /// Navigate to a URL.
-    pub async fn navigate(&self, url: &str, session_id: Option<&str>) -> Result<NavigationResult> {
+    /// Navigate to a URL, for at most `limit`.
+    pub async fn navigate(
+        &self,
+        url: &str,
+        wait_until: WaitUntil,
+        limit: Duration,
+        session_id: Option<&str>,
+    ) -> Result<NavigationResult> {
+        let page = self.get_page(session_id).await?;
+        let waited = navigation::navigate(&page, url, wait_until, limit).await?;
+        navigation_result(&page, waited).await
+    }
+
+    /// Go back one page in the session's history.
+    pub async fn go_back(
+        &self,
+        wait_until: WaitUntil,
+        limit: Duration,
+        session_id: Option<&str>,
+    ) -> Result<NavigationResult> {
+        let page = self.get_page(session_id).await?;
+        let waited = navigation::go(&page, -1, wait_until, limit).await?;
+        navigation_result(&page, waited).await
+    }
+
+    /// Go forward one page in the session's history.
+    pub async fn go_forward(
+        &self,
+        wait_until: WaitUntil,
+        limit: Duration,
+        session_id: Option<&str>,
+    ) -> Result<NavigationResult> {
         let page = self.get_page(session_id).await?;
+        let waited = navigation::go(&page, 1, wait_until, limit).await?;
+        navigation_result(&page, waited).await
+    }
 
-        page.goto(url).await.context("Navigation failed")?;
-        page.wait_for_navigation().await.ok();
+    /// Reload the session's page, bypassing the cache with `ignore_cache`.
+    pub async fn reload(
+        &self,
+        ignore_cache: bool,
+        wait_until: WaitUntil,
+        limit: Duration,
+        session_id: Option<&str>,
+    ) -> Result<NavigationResult> {
+        let page = self.get_page(session_id).await?;
+        let waited = navigation::reload(&page, ignore_cache, wait_until, limit).await?;
+        navigation_result(&page, waited).await
+    }
 
-        let current_url = page.url().await?.unwrap_or_default();
-        let title = page.get_title().await?.unwrap_or_default();
+    /// URL and title of the session's current page.
+    pub async fn page_info(&self, session_id: Option<&str>) -> Result<NavigationResult> {
+        let page = self.get_page(session_id).await?;
+        let (url, title) = page_url_and_title(&page).await?;
 
         Ok(NavigationResult {
-            url: current_url.to_string(),
+            url,
             title,
             status: None,
+            captchas: Vec::new(),
+            wait_until: None,
+            load_time_ms: None,
         })
     }
 
-    /// Get ARIA accessibility tree snapshot.
-    pub async fn snapshot(&self, session_id: Option<&str>) -> Result<AriaSnapshot> {
+    /// Send a raw CDP call to the session's page; returns the raw result.
+    pub async fn cdp(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        session_id: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let page = self.get_page(session_id).await?;
+        cdp::call(&page, method, params).await
+    }
+
+    /// Browser build, platform, viewport, overrides, proxy, extensions and
+    /// feature flags of a session, in one report.
+    pub async fn environment(&self, session_id: Option<&str>) -> Result<EnvironmentSummary> {
         let page = self.get_page(session_id).await?;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (zoom, keyboard_layout, fake_clock, popup_policy, show_cursor) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (
+                session.zoom.map(|zoom| zoom.factor),
+                session.keyboard_layout,
+                session.fake_clock.as_ref().map(|(_, mode)| *mode),
+                session.popup_policy.0,
+                session.show_cursor,
+            )
+        };
+
+        let (version, switches) = environment::browser(&self.browser, self.connected).await?;
+        let extensions = environment::extensions(&self.browser).await?;
+        let seen = environment::page(&page).await?;
+
+        Ok(EnvironmentSummary {
+            session_id: sid.to_string(),
+            browser: version,
+            platform: seen.platform(),
+            viewport: seen.viewport(),
+            emulation: EmulationState {
+                zoom,
+                keyboard_layout: events::cdp_enum_name(&keyboard_layout),
+                fake_clock,
+                popup_policy,
+                show_cursor,
+                user_agent: seen.user_agent,
+                locale: seen.locale,
+                timezone: seen.timezone,
+            },
+            proxy: switches.proxy,
+            extensions,
+            features: switches.features,
+        })
+    }
+
+    /// Get ARIA accessibility tree snapshot.
+    ///
+    /// With `use_cache`, a snapshot is reused as long as the page reports no
+    /// DOM mutations, input/focus changes, scrolling, resizing, or URL
+    /// changes since it was taken.
+    pub async fn snapshot(
+        &self,
+        session_id: Option<&str>,
+        use_cache: bool,
+    ) -> Result<AriaSnapshot> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (page, refs) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.page.clone(), Arc::clone(&session.refs))
+        };
 
-        let url = page.url().await?.unwrap_or_default().to_string();
-        let title = page.get_title().await?.unwrap_or_default();
+        if use_cache {
+            if let Some(mut snapshot) = self.cached_snapshot(sid, &page).await {
+                // Scrolls inside shadow roots don't reach the tracker
+                snapshot.scroll = scroll::capture(&page).await.ok();
+                self.record_scroll(sid, &snapshot.url, snapshot.scroll.as_ref())
+                    .await;
+                return Ok(snapshot);
+            }
+        }
 
-        let nodes = extract_aria_tree(&page).await?;
+        // Start tracking before extraction so changes made while the tree is
+        // being walked invalidate the cache on the next call.
+        let marker = track_mutations(&page).await.ok();
+
+        // URL, title, scroll, and the AX tree are independent - pipeline them
+        // on the page's CDP session instead of paying serial round-trips.
+        let ((url, title), nodes, scroll) = futures::try_join!(
+            page_url_and_title(&page),
+            extract_aria_tree(&page, &refs, self.frames.as_ref()),
+            async { Ok::<_, anyhow::Error>(scroll::capture(&page).await.ok()) },
+        )?;
         let element_count = count_nodes(&nodes);
+        let focused = focused_ref(&nodes);
+        // The tracker only watches the page's own document, so a snapshot
+        // with frame content is paged but never reused
+        let marker = marker.filter(|_| !has_frame_nodes(&nodes));
+        self.record_scroll(sid, &url, scroll.as_ref()).await;
 
-        Ok(AriaSnapshot {
+        let snapshot = AriaSnapshot {
             url,
             title,
             nodes,
             element_count,
-        })
-    }
+            cached: false,
+            offset: 0,
+            next_cursor: None,
+            truncated: false,
+            scroll,
+            focused,
+        };
 
-    /// Export cookies for a session.
-    pub async fn get_cookies(&self, session_id: Option<&str>) -> Result<Vec<SerializableCookie>> {
-        let page = self.get_page(session_id).await?;
-        let cookies = page.get_cookies().await?;
+        let mut sessions = self.sessions.write().await;
+        if let Some(session) = sessions.get_mut(sid) {
+            session.snapshot_cache = Some(CachedSnapshot {
+                id: uuid::Uuid::new_v4().to_string(),
+                marker,
+                snapshot: snapshot.clone(),
+                filtered: None,
+                taken: Instant::now(),
+                paged: false,
+            });
+        }
 
-        Ok(cookies
-            .into_iter()
-            .map(|cookie| SerializableCookie {
-                name: cookie.name,
-                value: cookie.value,
-                domain: cookie.domain,
-                path: cookie.path,
-                expires: if cookie.session {
-                    None
-                } else {
-                    Some(cookie.expires)
-                },
-                secure: cookie.secure,
-                http_only: cookie.http_only,
-                same_site: cookie.same_site,
-            })
-            .collect())
+        Ok(snapshot)
     }
 
-    /// Restore cookies for a session.
-    pub async fn set_cookies(
+    /// Get one page of the ARIA snapshot.
+    ///
+    /// Without a cursor this takes (or reuses) a snapshot, narrows it to the
+    /// nodes matching `filter`, and returns its first `limit` nodes. With a
+    /// cursor, the page is cut from the snapshot (or filtered view) the cursor
+    /// was issued for, so ordering and refs stay stable across pages.
+    /// Pages are also cut at roughly `max_bytes` of JSON (see `paging::paginate`).
+    pub async fn snapshot_page(
         &self,
-        cookies: &[SerializableCookie],
         session_id: Option<&str>,
-    ) -> Result<()> {
-        if cookies.is_empty() {
+        use_cache: bool,
+        filter: &SnapshotFilter,
+        limit: usize,
+        max_bytes: usize,
+        cursor: Option<&str>,
+    ) -> Result<AriaSnapshot> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+
+        let Some(token) = cursor else {
+            let page = self.get_page(session_id).await?;
+            snapshot_filter::mark_root(&page, filter).await?;
+            let snapshot = self.snapshot(session_id, use_cache).await?;
+            let view = if filter.is_empty() {
+                None
+            } else {
+                Some(snapshot_filter::apply(&page, snapshot.clone(), filter).await?)
+            };
+            let mut sessions = self.sessions.write().await;
+            let snapshot_id = match sessions.get_mut(sid) {
+                Some(session) => {
+                    // Diffs compare whole snapshots, whatever was filtered
+                    let (_, latest) = std::mem::take(&mut session.snapshot_nodes);
+                    session.snapshot_nodes = (latest, Some(snapshot.nodes.clone()));
+                    match (session.snapshot_cache.as_mut(), &view) {
+                        (Some(cache), Some(view)) => {
+                            let id = uuid::Uuid::new_v4().to_string();
+                            cache.filtered = Some((id.clone(), view.clone()));
+                            id
+                        }
+                        (Some(cache), None) => cache.id.clone(),
+                        (None, _) => String::new(),
+                    }
+                }
+                None => String::new(),
+            };
+            let first =
+                paging::paginate(view.unwrap_or(snapshot), &snapshot_id, 0, limit, max_bytes);
+            // Maintenance keeps a snapshot whose cursors may still be used
+            if first.next_cursor.is_some() {
+                if let Some(cache) = sessions
+                    .get_mut(sid)
+                    .and_then(|session| session.snapshot_cache.as_mut())
+                {
+                    cache.paged = true;
+                }
+            }
+            return Ok(first);
+        };
+
+        self.resume_snapshot_page(sid, token, limit, max_bytes)
+            .await
+    }
+
+    /// Get one page of the ARIA snapshot for an observer of the session.
+    ///
+    /// Unlike `snapshot_page`, this leaves the session's snapshot state to
+    /// its owner: the cached snapshot is served while the page is unchanged,
+    /// otherwise a tree is read without injecting refs or installing a
+    /// mutation tracker. The cache, diff baseline and the owner's cursors
+    /// stay as they were; the observer's view is kept apart for its cursors.
+    /// `filter` can't have a root, which would be marked in the page.
+    pub async fn observe_snapshot_page(
+        &self,
+        session_id: Option<&str>,
+        use_cache: bool,
+        filter: &SnapshotFilter,
+        limit: usize,
+        max_bytes: usize,
+        cursor: Option<&str>,
+    ) -> Result<AriaSnapshot> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        if let Some(token) = cursor {
+            return self
+                .resume_snapshot_page(sid, token, limit, max_bytes)
+                .await;
+        }
+        anyhow::ensure!(
+            filter.root.is_none(),
+            "Observers can't narrow a snapshot to a root element"
+        );
+
+        let (page, refs) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.page.clone(), Arc::clone(&session.refs))
+        };
+
+        let cached = if use_cache {
+            self.cached_snapshot(sid, &page).await
+        } else {
+            None
+        };
+        let mut snapshot = match cached {
+            Some(snapshot) => snapshot,
+            None => {
+                let ((url, title), nodes) = futures::try_join!(
+                    page_url_and_title(&page),
+                    read_aria_tree(&page, &refs, self.frames.as_ref()),
+                )?;
+                AriaSnapshot {
+                    url,
+                    title,
+                    element_count: count_nodes(&nodes),
+                    focused: focused_ref(&nodes),
+                    nodes,
+                    cached: false,
+                    offset: 0,
+                    next_cursor: None,
+                    truncated: false,
+                    scroll: None,
+                }
+            }
+        };
+        snapshot.scroll = scroll::capture(&page).await.ok();
+        let view = if filter.is_empty() {
+            snapshot
+        } else {
+            snapshot_filter::apply(&page, snapshot, filter).await?
+        };
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut sessions = self.sessions.write().await;
+        if let Some(session) = sessions.get_mut(sid) {
+            session.observed = Some((id.clone(), view.clone()));
+        }
+        drop(sessions);
+        Ok(paging::paginate(view, &id, 0, limit, max_bytes))
+    }
+
+    /// Page of the snapshot (or view) a cursor was issued for.
+    async fn resume_snapshot_page(
+        &self,
+        sid: &str,
+        token: &str,
+        limit: usize,
+        max_bytes: usize,
+    ) -> Result<AriaSnapshot> {
+        let cursor = SnapshotCursor::decode(token)?;
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+        let cached = session.snapshot_cache.iter().flat_map(|cache| {
+            let view = cache
+                .filtered
+                .as_ref()
+                .map(|(id, view)| (id.as_str(), view));
+            std::iter::once((cache.id.as_str(), &cache.snapshot)).chain(view)
+        });
+        let observed = session
+            .observed
+            .as_ref()
+            .map(|(id, view)| (id.as_str(), view));
+        let (id, snapshot) = paging::resume(&cursor, cached.chain(observed))?;
+
+        let mut snapshot = snapshot.clone();
+        snapshot.cached = true;
+        Ok(paging::paginate(
+            snapshot,
+            id,
+            cursor.offset,
+            limit,
+            max_bytes,
+        ))
+    }
+
+    /// Unified diff between the last two snapshots served in a session, as
+    /// text snapshots; None before the second one.
+    pub async fn snapshot_diff(&self, session_id: Option<&str>) -> Result<Option<String>> {
+        let sessions = self.sessions.read().await;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let session = sessions
+            .get(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+
+        Ok(match &session.snapshot_nodes {
+            (Some(previous), Some(latest)) => Some(snapshot_diff::unified(
+                &render_text(previous),
+                &render_text(latest),
+            )),
+            _ => None,
+        })
+    }
+
+    /// Node-level diff between the last two snapshots served in a session,
+    /// for the page at `url`; None before the second one.
+    pub async fn snapshot_changes(
+        &self,
+        url: &str,
+        session_id: Option<&str>,
+    ) -> Result<Option<AriaSnapshotDiff>> {
+        let sessions = self.sessions.read().await;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let session = sessions
+            .get(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+
+        Ok(match &session.snapshot_nodes {
+            (Some(previous), Some(latest)) => Some(snapshot_diff::nodes(url, previous, latest)),
+            _ => None,
+        })
+    }
+
+    /// Remember the scroll position observed for a URL in a session.
+    async fn record_scroll(&self, session_id: &str, url: &str, state: Option<&ScrollState>) {
+        let Some(state) = state else { return };
+        let mut sessions = self.sessions.write().await;
+        if let Some(session) = sessions.get_mut(session_id) {
+            session
+                .scroll_positions
+                .insert(url.to_string(), state.clone());
+        }
+    }
+
+    /// Restore a scroll position.
+    ///
+    /// Without an explicit `state`, restores the position last observed (by a
+    /// snapshot) for the page's current URL, so after a reload or
+    /// back-navigation the page lands where the agent left off.
+    pub async fn restore_scroll(
+        &self,
+        state: Option<ScrollState>,
+        session_id: Option<&str>,
+    ) -> Result<ScrollRestoreResult> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let page = self.get_page(session_id).await?;
+        let url = page.url().await?.unwrap_or_default();
+
+        let state = match state {
+            Some(state) => state,
+            None => {
+                let sessions = self.sessions.read().await;
+                sessions
+                    .get(sid)
+                    .and_then(|s| s.scroll_positions.get(&url))
+                    .cloned()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "No scroll position recorded for {} - take a snapshot first",
+                            url
+                        )
+                    })?
+            }
+        };
+
+        let result = scroll::restore(&page, &state).await?;
+        self.record_scroll(sid, &url, Some(&result.scroll)).await;
+
+        Ok(result)
+    }
+
+    /// Wait until the page has settled: no network requests in flight, no
+    /// layout shifts and no DOM mutations for `quiet`.
+    ///
+    /// Returns `stable: false` with the still-busy signals after `timeout`
+    /// rather than failing, so callers can decide whether to carry on.
+    pub async fn wait_for_stable(
+        &self,
+        quiet: Duration,
+        timeout: Duration,
+        session_id: Option<&str>,
+    ) -> Result<StabilityResult> {
+        let page = self.get_page(session_id).await?;
+        stability::wait_for_stable(&page, quiet, timeout).await
+    }
+
+    /// Return the cached snapshot for a session if the page is unchanged.
+    async fn cached_snapshot(&self, session_id: &str, page: &Page) -> Option<AriaSnapshot> {
+        let current = read_mutation_marker(page).await.ok()?;
+
+        let sessions = self.sessions.read().await;
+        let cache = sessions.get(session_id)?.snapshot_cache.as_ref()?;
+        if cache.marker.as_ref() != Some(&current) {
+            return None;
+        }
+
+        tracing::debug!("Serving cached snapshot for session {}", session_id);
+        let mut snapshot = cache.snapshot.clone();
+        snapshot.cached = true;
+        Some(snapshot)
+    }
+
+    /// Export cookies for a session.
+    pub async fn get_cookies(&self, session_id: Option<&str>) -> Result<Vec<SerializableCookie>> {
+        let page = self.get_page(session_id).await?;
+        let cookies = page.get_cookies().await?;
+
+        Ok(cookies
+            .into_iter()
+            .map(|cookie| SerializableCookie {
+                name: cookie.name,
+                value: cookie.value,
+                domain: cookie.domain,
+                path: cookie.path,
+                expires: if cookie.session {
+                    None
+                } else {
+                    Some(cookie.expires)
+                },
+                secure: cookie.secure,
+                http_only: cookie.http_only,
+                same_site: cookie.same_site,
+            })
+            .collect())
+    }
+
+    /// Restore cookies for a session.
+    pub async fn set_cookies(
+        &self,
+        cookies: &[SerializableCookie],
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        if cookies.is_empty() {
             return Ok(());
         }
 
@@ -364,16 +1515,17 @@ impl BrowserClient {
     /// Capture localStorage for a session.
     pub async fn get_local_storage(&self, session_id: Option<&str>) -> Result<LocalStorageState> {
         let page = self.get_page(session_id).await?;
-        let origin: String = page
-            .evaluate("location.origin")
-            .await
+        let (origin_result, entries_result) = futures::join!(
+            page.evaluate("location.origin"),
+            page.evaluate("(() => { try { return Object.entries(localStorage); } catch (_) { return []; } })()")
+        );
+
+        let origin: String = origin_result
             .context("Failed to evaluate location.origin")?
             .into_value()
             .context("Failed to parse location.origin")?;
 
-        let entries: Vec<(String, String)> = page
-            .evaluate("(() => { try { return Object.entries(localStorage); } catch (_) { return []; } })()")
-            .await
+        let entries: Vec<(String, String)> = entries_result
             .context("Failed to read localStorage entries")?
             .into_value()
             .context("Failed to parse localStorage entries")?;
@@ -407,112 +1559,1065 @@ impl BrowserClient {
     }
 
     /// Take a screenshot.
+    ///
+    /// With a `path`, the base64 payload from Chrome is decoded straight to
+    /// disk; without one it is returned as-is instead of being decoded and
+    /// re-encoded. A `scale` renders the capture at that device scale factor
+    /// without leaving the emulation in place. An `element` crops the capture
+    /// to that element plus padding, optionally outlined for the capture;
+    /// otherwise it covers the viewport, or the whole page with `full_page`.
     pub async fn screenshot(
         &self,
         path: Option<&str>,
+        options: &ScreenshotOptions,
         session_id: Option<&str>,
     ) -> Result<ScreenshotResult> {
         let page = self.get_page(session_id).await?;
+        let zoom = self.zoom(session_id).await?;
+        let image = screenshot::Image {
+            format: options.format,
+            quality: options.quality,
+        };
 
-        let screenshot_data = page
-            .screenshot(
-                chromiumoxide::page::ScreenshotParams::builder()
-                    .full_page(true)
-                    .build(),
-            )
-            .await?;
+        let (encoded, clip, page_size) = match (&options.element, options.clip) {
+            (None, Some(requested)) => {
+                let clip = screenshot::clip_region(&page, requested, options.clip_origin)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Clip lies outside the page"))?;
+                let encoded = screenshot::capture_base64(
+                    &page,
+                    screenshot::Region::Clip(clip),
+                    image,
+                    options.scale,
+                    zoom.as_ref(),
+                )
+                .await?;
+                (encoded, Some(clip), None)
+            }
+            (None, None) => {
+                let region = if options.full_page {
+                    screenshot::Region::FullPage
+                } else {
+                    screenshot::Region::Viewport
+                };
+                let encoded =
+                    screenshot::capture_base64(&page, region, image, options.scale, zoom.as_ref())
+                        .await?;
+                (encoded, None, Some(screenshot::page_size(&page).await?))
+            }
+            (Some(element), _) => {
+                let css_selector = resolve_selector(&element.selector);
+                let frame = self.frame_for(session_id, &element.selector).await?;
+                let region = screenshot::element_region(
+                    &page,
+                    frame.as_ref(),
+                    &css_selector,
+                    element.padding,
+                )
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Element not found: {}", element.selector))?;
 
-        let (width, height) = (1920, 1080);
+                if element.marker {
+                    overlay::draw_marker(&page, &region.element, overlay::DEFAULT_HIGHLIGHT_COLOR)
+                        .await?;
+                }
+                let captured = screenshot::capture_base64(
+                    &page,
+                    screenshot::Region::Clip(region.clip),
+                    image,
+                    options.scale,
+                    zoom.as_ref(),
+                )
+                .await;
+                if element.marker {
+                    overlay::remove_marker(&page).await?;
+                }
 
-        if let Some(file_path) = path {
-            tokio::fs::write(file_path, &screenshot_data).await?;
-            Ok(ScreenshotResult {
-                data: None,
-                path: Some(file_path.to_string()),
-                width,
-                height,
-            })
-        } else {
-            let encoded = base64::Engine::encode(
-                &base64::engine::general_purpose::STANDARD,
-                &screenshot_data,
-            );
-            Ok(ScreenshotResult {
-                data: Some(encoded),
-                path: None,
-                width,
-                height,
-            })
+                (captured?, Some(region.clip), None)
+            }
+        };
+
+        let (width, height) =
+            screenshot::image_dimensions(&encoded, options.format).unwrap_or((1920, 1080));
+        let (page_width, page_height) = page_size.unzip();
+
+        let data = match path {
+            Some(file_path) => {
+                screenshot::write_base64_to_file(encoded, PathBuf::from(file_path)).await?;
+                None
+            }
+            None => Some(encoded),
+        };
+        Ok(ScreenshotResult {
+            data,
+            path: path.map(str::to_string),
+            artifact: None,
+            format: options.format,
+            width,
+            height,
+            clip,
+            page_width,
+            page_height,
+        })
+    }
+
+    /// Take a screenshot once `frames` new frames have been painted, so
+    /// changes made just before (e.g. by a click) are in it.
+    pub async fn screenshot_after_paint(
+        &self,
+        path: Option<&str>,
+        options: &ScreenshotOptions,
+        frames: u32,
+        session_id: Option<&str>,
+    ) -> Result<ScreenshotResult> {
+        let page = self.get_page(session_id).await?;
+        if !screenshot::wait_for_paint(&page, frames).await? {
+            tracing::debug!("No frames painted before screenshot; capturing anyway");
         }
+
+        self.screenshot(path, options, session_id).await
     }
 
-    /// Click an element.
-    pub async fn click(&self, selector: &str, session_id: Option<&str>) -> Result<ClickResult> {
+    /// Render the page with print media, one image per page.
+    pub async fn print_preview(
+        &self,
+        options: &PrintOptions,
+        session_id: Option<&str>,
+    ) -> Result<print::PrintCapture> {
         let page = self.get_page(session_id).await?;
+        let zoom = self.zoom(session_id).await?;
+        print::capture_pages(&page, options, zoom.as_ref()).await
+    }
 
-        let css_selector = resolve_selector(selector);
+    /// Print the page to a PDF, written to `path` if given and returned
+    /// base64'd otherwise.
+    pub async fn pdf(
+        &self,
+        path: Option<&str>,
+        options: &PdfOptions,
+        session_id: Option<&str>,
+    ) -> Result<PdfResult> {
+        let page = self.get_page(session_id).await?;
+        let encoded = print::print_pdf(&page, options).await?;
+        let size = print::decoded_len(&encoded);
+
+        let data = match path {
+            Some(file_path) => {
+                screenshot::write_base64_to_file(encoded, PathBuf::from(file_path)).await?;
+                None
+            }
+            None => Some(encoded),
+        };
+        Ok(PdfResult {
+            data,
+            path: path.map(str::to_string),
+            artifact: None,
+            paper: options.paper,
+            landscape: options.landscape,
+            margins: options.margins,
+            size,
+        })
+    }
+
+    /// Draw `strokes` on a canvas with pointer events.
+    pub async fn canvas_draw(
+        &self,
+        selector: &str,
+        strokes: &[Vec<CanvasPoint>],
+        options: &CanvasDrawOptions,
+        session_id: Option<&str>,
+    ) -> Result<CanvasDrawResult> {
+        let page = self.get_page(session_id).await?;
+        let (rect, elapsed) = canvas::draw(&page, selector, strokes, options).await?;
+
+        Ok(CanvasDrawResult {
+            success: true,
+            strokes: strokes.len(),
+            points: strokes.iter().map(Vec::len).sum(),
+            rect,
+            duration_ms: elapsed.as_millis() as u64,
+        })
+    }
+
+    /// Drag `source` onto `target` (refs or CSS) with `steps` pointer moves.
+    pub async fn drag_and_drop(
+        &self,
+        source: &str,
+        target: &str,
+        steps: u32,
+        session_id: Option<&str>,
+    ) -> Result<DragResult> {
+        let page = self.get_page(session_id).await?;
+        let (native, elapsed) = drag::drag_and_drop(&page, source, target, steps).await?;
+
+        Ok(DragResult {
+            success: true,
+            source: source.to_string(),
+            target: target.to_string(),
+            steps,
+            native,
+            duration_ms: elapsed.as_millis() as u64,
+        })
+    }
+
+    /// Read a canvas bitmap as PNG, to a file if `path` is given.
+    pub async fn canvas_capture(
+        &self,
+        selector: &str,
+        path: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<ScreenshotResult> {
+        let page = self.get_page(session_id).await?;
+        let capture = canvas::capture(&page, selector).await?;
+
+        let data = match path {
+            Some(file_path) => {
+                screenshot::write_base64_to_file(capture.data, PathBuf::from(file_path)).await?;
+                None
+            }
+            None => Some(capture.data),
+        };
+        Ok(ScreenshotResult {
+            data,
+            path: path.map(str::to_string),
+            artifact: None,
+            format: ImageFormat::Png,
+            width: capture.width,
+            height: capture.height,
+            clip: Some(capture.rect),
+            page_width: None,
+            page_height: None,
+        })
+    }
+
+    /// Subscribe to the JPEG screencast of the session's page, starting it
+    /// with `options` if it isn't running. A running one is shared as it
+    /// is, unless `exact` asks for `options` and they differ
+    /// (`ScreencastConflict`).
+    pub async fn join_screencast(
+        &self,
+        options: ScreencastOptions,
+        exact: bool,
+        session_id: Option<&str>,
+    ) -> Result<Screencast> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (page, screencast) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.page.clone(), session.screencast.clone())
+        };
+        screencast.join(&page, options, exact).await
+    }
+
+    /// Elements from the last snapshot nearest to `anchor`.
+    pub async fn nearest(
+        &self,
+        anchor: &SpatialAnchor,
+        direction: SpatialDirection,
+        filter: &SpatialFilter,
+        session_id: Option<&str>,
+    ) -> Result<SpatialResult> {
+        let page = self.get_page(session_id).await?;
+        let nodes = self.last_snapshot_nodes(session_id).await;
+
+        let (anchor_rect, candidates) = spatial::measure(&page, Some(anchor), &nodes).await?;
+        let anchor_rect = anchor_rect.context("Anchor has no layout")?;
+
+        Ok(SpatialResult {
+            anchor: Some(anchor_rect),
+            matches: spatial::nearest(&anchor_rect, candidates, direction, filter),
+        })
+    }
+
+    /// Elements from the last snapshot inside `area` (document CSS pixels).
+    pub async fn within(
+        &self,
+        area: &ElementRect,
+        partial: bool,
+        filter: &SpatialFilter,
+        session_id: Option<&str>,
+    ) -> Result<SpatialResult> {
+        let page = self.get_page(session_id).await?;
+        let nodes = self.last_snapshot_nodes(session_id).await;
+
+        let (_, candidates) = spatial::measure(&page, None, &nodes).await?;
+
+        Ok(SpatialResult {
+            anchor: None,
+            matches: spatial::within(area, candidates, partial, filter),
+        })
+    }
+
+    /// Find snapshot elements by role, accessible name and text.
+    ///
+    /// Uses the cached snapshot when the page hasn't changed, so the returned
+    /// refs are the ones a full snapshot would give.
+    pub async fn find_elements(
+        &self,
+        query: &ElementQuery,
+        session_id: Option<&str>,
+    ) -> Result<FoundElements> {
+        let snapshot = self.snapshot(session_id, true).await?;
+        let page = self.get_page(session_id).await?;
+        let mut matches = find::find(&page, &snapshot.nodes, query).await?;
+        let total = matches.len();
+        matches.truncate(query.limit);
+        Ok(FoundElements {
+            url: snapshot.url,
+            matches,
+            total,
+        })
+    }
+
+    /// Title, URL and snippet of each result card on a search-result page.
+    ///
+    /// Takes a snapshot first (reused while the page is unchanged) so the
+    /// titles carry refs.
+    pub async fn summarize_results(
+        &self,
+        container: Option<&str>,
+        limit: usize,
+        session_id: Option<&str>,
+    ) -> Result<SearchResults> {
+        let snapshot = self.snapshot(session_id, true).await?;
+        let page = self.get_page(session_id).await?;
+        results::collect(&page, snapshot.url, container, limit).await
+    }
+
+    /// Fill the form at `selector` (default: the first form on the page)
+    /// from a flat key/value map, matching keys to fields by name,
+    /// `autocomplete` and label (see `autofill`).
+    ///
+    /// A value that can't be applied is reported with its key instead of
+    /// stopping the rest.
+    pub async fn autofill_form(
+        &self,
+        selector: Option<&str>,
+        data: &[(String, serde_json::Value)],
+        session_id: Option<&str>,
+    ) -> Result<AutofillResult> {
+        let page = self.get_page(session_id).await?;
+        let form = form::describe(&page, selector).await?;
+        let plan = autofill::plan(&form.fields, data);
+
+        let mut filled = Vec::new();
+        let mut failed = Vec::new();
+        for assignment in plan.assignments {
+            let field = &form.fields[assignment.field];
+            let applied = match assignment.action {
+                Ok(action) => self
+                    .apply_autofill(&field.selector, &action, session_id)
+                    .await
+                    .map(|()| action.describe()),
+                Err(e) => Err(e),
+            };
+            match applied {
+                Ok(value) => filled.push(AutofilledField {
+                    key: assignment.key,
+                    selector: field.selector.clone(),
+                    name: field.name.clone(),
+                    label: field.label.clone(),
+                    value,
+                }),
+                Err(e) => failed.push(AutofillFailure {
+                    key: assignment.key,
+                    selector: field.selector.clone(),
+                    error: format!("{:#}", e),
+                }),
+            }
+        }
+
+        Ok(AutofillResult {
+            selector: form.selector,
+            filled,
+            failed,
+            unmatched: plan.unmatched,
+            unfilled_required: plan.unfilled_required,
+        })
+    }
+
+    async fn apply_autofill(
+        &self,
+        selector: &str,
+        action: &Action,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        match action {
+            Action::Fill(text) => {
+                self.fill(selector, text, false, session_id).await?;
+            }
+            Action::Check(checked) => self.check(selector, *checked, session_id).await?,
+            Action::Choose { selector, .. } => self.check(selector, true, session_id).await?,
+            Action::Select(values) => {
+                let query = OptionQuery::Value(values.clone());
+                self.select_option(selector, &query, session_id).await?;
+            }
+            Action::Date(date) => {
+                self.set_date(selector, date, None, session_id).await?;
+            }
+            Action::Upload(paths) => {
+                self.upload_files(selector, paths, session_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Describe the fields of the form at `selector` (default: the first
+    /// form on the page).
+    pub async fn describe_form(
+        &self,
+        selector: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<FormDescription> {
+        let page = self.get_page(session_id).await?;
+        form::describe(&page, selector).await
+    }
+
+    /// Collect the validation errors showing on the form at `selector`
+    /// (default: the first form on the page), by field.
+    pub async fn validation_errors(
+        &self,
+        selector: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<ValidationErrors> {
+        let page = self.get_page(session_id).await?;
+        form::validation_errors(&page, selector).await
+    }
+
+    /// Locate a table cell by row and column.
+    pub async fn table_cell(
+        &self,
+        query: &TableQuery,
+        session_id: Option<&str>,
+    ) -> Result<TableCell> {
+        let page = self.get_page(session_id).await?;
+        table::locate(
+            &page,
+            query.table.as_deref(),
+            &query.row,
+            &query.column,
+            query.target.as_deref(),
+        )
+        .await
+    }
+
+    /// Click the control in a table cell (or the cell itself if it has none).
+    pub async fn table_click(
+        &self,
+        query: &TableQuery,
+        session_id: Option<&str>,
+    ) -> Result<TableCell> {
+        let mut cell = self.table_cell(query, session_id).await?;
+        let selector = cell
+            .target
+            .as_ref()
+            .map_or(cell.selector.clone(), |t| t.selector.clone());
+        self.click(&selector, session_id).await?;
+        cell.clicked = true;
+        Ok(cell)
+    }
+
+    /// Fingerprint of `ref_id` in the session's last snapshot, to find the
+    /// element again after a reload.
+    pub async fn fingerprint(
+        &self,
+        ref_id: &str,
+        session_id: Option<&str>,
+    ) -> Option<ElementFingerprint> {
+        let nodes = self.last_snapshot_nodes(session_id).await;
+        recovery::fingerprint(&nodes, ref_id)
+    }
+
+    /// Reload the session's page to recover a failed element action.
+    pub async fn reload_for_recovery(&self, session_id: Option<&str>) -> Result<RecoveryStep> {
+        let page = self.get_page(session_id).await?;
+        recovery::reload(&page).await
+    }
 
-        let element = page
-            .find_element(&css_selector)
+    /// Ref of the element matching `fingerprint` in a fresh snapshot.
+    pub async fn refind(
+        &self,
+        fingerprint: &ElementFingerprint,
+        session_id: Option<&str>,
+    ) -> Result<Option<String>> {
+        let snapshot = self.snapshot(session_id, false).await?;
+        Ok(recovery::refind(&snapshot.nodes, fingerprint))
+    }
+
+    /// Nodes of the session's last snapshot, stale or not; the refs in the
+    /// DOM always come from it.
+    async fn last_snapshot_nodes(&self, session_id: Option<&str>) -> Vec<AriaNode> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        self.sessions
+            .read()
             .await
-            .context("Element not found")?;
+            .get(sid)
+            .and_then(|s| s.snapshot_cache.as_ref())
+            .map(|cache| cache.snapshot.nodes.clone())
+            .unwrap_or_default()
+    }
 
-        element.click().await?;
+    /// Report what an interaction would do, without dispatching it.
+    ///
+    /// `value` is the fill text, option value, or upload path; `checked` the
+    /// desired state for check.
+    pub async fn dry_run(
+        &self,
+        action: DryRunAction,
+        selector: &str,
+        value: Option<&str>,
+        checked: bool,
+        session_id: Option<&str>,
+    ) -> Result<DryRunReport> {
+        let page = self.get_page(session_id).await?;
+        let mut report = dry_run::rehearse(&page, action, selector, value, checked).await?;
+
+        if action == DryRunAction::Upload {
+            if let Some(path) = value.filter(|p| !std::path::Path::new(p).exists()) {
+                report.problems.push(format!("File not found: {}", path));
+                report.actionable = false;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Mark which of the elements `selector` matches `strategy` picks, for
+    /// the next interaction to address as [`super::PICKED_SELECTOR`].
+    pub async fn rank_matches(
+        &self,
+        selector: &str,
+        strategy: ElementRanking,
+        session_id: Option<&str>,
+    ) -> Result<RankedMatch> {
+        let page = self.get_page(session_id).await?;
+        ranking::pick(&page, selector, strategy).await
+    }
+
+    /// Resolve XPath selectors to CSS selectors of the elements they match.
+    pub async fn mark_xpaths(
+        &self,
+        selectors: &[&str],
+        session_id: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let page = self.get_page(session_id).await?;
+        xpath::mark(&page, selectors).await
+    }
+
+    /// Resolve chained (`>>>`) selectors to refs of the elements they end on.
+    pub async fn resolve_chains(
+        &self,
+        selectors: &[&str],
+        session_id: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (page, refs) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.page.clone(), Arc::clone(&session.refs))
+        };
+
+        let mut resolved = Vec::with_capacity(selectors.len());
+        for selector in selectors {
+            resolved.push(chain::resolve(&page, &refs, self.frames.as_ref(), selector).await?);
+        }
+        Ok(resolved)
+    }
+
+    /// Click an element.
+    pub async fn click(&self, selector: &str, session_id: Option<&str>) -> Result<ClickResult> {
+        self.click_with(selector, &ClickOptions::default(), session_id)
+            .await
+    }
+
+    /// Click an element with another button, several times, or holding
+    /// modifier keys (right-click, double-click, shift-click).
+    pub async fn click_with(
+        &self,
+        selector: &str,
+        options: &ClickOptions,
+        session_id: Option<&str>,
+    ) -> Result<ClickResult> {
+        let page = self.get_page(session_id).await?;
+        let (modifiers, modifier_keys) = keyboard::modifier_mask(&options.modifiers)?;
+        let frame = self.frame_for(session_id, selector).await?;
+
+        let target = Target::find(&page, frame.as_ref(), selector).await?;
+
+        if *options == ClickOptions::default() {
+            target.click(&page).await?;
+        } else {
+            target.scroll_into_view().await?;
+            let point = target.clickable_point().await?;
+            let (button, buttons) = match options.button {
+                ClickButton::Left => (MouseButton::Left, 1),
+                ClickButton::Right => (MouseButton::Right, 2),
+                ClickButton::Middle => (MouseButton::Middle, 4),
+            };
+            let event = |kind: DispatchMouseEventType, count: u32, buttons: i64| {
+                let button = match kind {
+                    DispatchMouseEventType::MouseMoved => MouseButton::None,
+                    _ => button.clone(),
+                };
+                DispatchMouseEventParams::builder()
+                    .r#type(kind)
+                    .x(point.x)
+                    .y(point.y)
+                    .modifiers(modifiers)
+                    .button(button)
+                    .buttons(buttons)
+                    .click_count(count as i64)
+                    .build()
+                    .map_err(anyhow::Error::msg)
+            };
+
+            page.execute(event(DispatchMouseEventType::MouseMoved, 0, 0)?)
+                .await?;
+            // Each press reports its place in the sequence, as a real
+            // double-click does (detail 1, then 2)
+            for count in 1..=options.click_count {
+                page.execute(event(DispatchMouseEventType::MousePressed, count, buttons)?)
+                    .await?;
+                page.execute(event(DispatchMouseEventType::MouseReleased, count, 0)?)
+                    .await?;
+            }
+        }
+        self.trace_pointer(session_id, &page, &target, true).await;
 
         Ok(ClickResult {
             success: true,
             element: Some(selector.to_string()),
+            button: options.button,
+            click_count: options.click_count,
+            modifiers: modifier_keys,
         })
     }
 
-    /// Fill an input field.
+    /// Fill an input field, typing with the session's keyboard layout.
     pub async fn fill(
         &self,
         selector: &str,
         value: &str,
+        append: bool,
         session_id: Option<&str>,
     ) -> Result<FillResult> {
         let page = self.get_page(session_id).await?;
+        let frame = self.frame_for(session_id, selector).await?;
 
-        let css_selector = resolve_selector(selector);
-
-        let element = page
-            .find_element(&css_selector)
-            .await
-            .context("Element not found")?;
+        let target = Target::find(&page, frame.as_ref(), selector).await?;
 
-        element.click().await?;
-        element.type_str(value).await?;
+        target.click(&page).await?;
+        self.trace_pointer(session_id, &page, &target, true).await;
+        let layout = self.keyboard_layout(session_id).await?;
+        let placement = if append {
+            Placement::Append
+        } else {
+            Placement::Clear
+        };
+        let previous_value =
+            field::prepare(&page, frame.as_ref(), layout, selector, placement).await?;
+        keyboard::type_text(&page, layout, value).await?;
 
         Ok(FillResult {
             success: true,
             value: value.to_string(),
+            appended: append,
+            previous_value,
+            field_value: field::value(&page, frame.as_ref(), selector).await?,
+        })
+    }
+
+    /// Empty the field at `selector` with a select-all and Backspace.
+    pub async fn clear(&self, selector: &str, session_id: Option<&str>) -> Result<ClearResult> {
+        let page = self.get_page(session_id).await?;
+        let frame = self.frame_for(session_id, selector).await?;
+        let layout = self.keyboard_layout(session_id).await?;
+
+        let previous_value =
+            field::prepare(&page, frame.as_ref(), layout, selector, Placement::Clear).await?;
+
+        Ok(ClearResult {
+            success: field::value(&page, frame.as_ref(), selector)
+                .await?
+                .is_empty(),
+            selector: selector.to_string(),
+            previous_value,
+        })
+    }
+
+    /// Type `text` one keystroke at a time, pausing `delay_ms` (give or take
+    /// up to `jitter_ms`) between keys, into the element at `selector` (after
+    /// clicking it) or the focused element.
+    pub async fn type_text(
+        &self,
+        selector: Option<&str>,
+        text: &str,
+        delay_ms: u64,
+        jitter_ms: u64,
+        session_id: Option<&str>,
+    ) -> Result<TypeTextResult> {
+        let page = self.get_page(session_id).await?;
+
+        if let Some(selector) = selector {
+            let frame = self.frame_for(session_id, selector).await?;
+            let target = Target::find(&page, frame.as_ref(), selector).await?;
+            target.click(&page).await?;
+            self.trace_pointer(session_id, &page, &target, true).await;
+        }
+
+        let layout = self.keyboard_layout(session_id).await?;
+        let started = std::time::Instant::now();
+        keyboard::type_text_paced(&page, layout, text, delay_ms, jitter_ms).await?;
+
+        Ok(TypeTextResult {
+            success: true,
+            typed: text.chars().count(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
         })
     }
 
-    /// Press a key.
-    pub async fn press(&self, key: &str, session_id: Option<&str>) -> Result<()> {
+    /// Select `range` in the element at `selector`, or in the whole page.
+    pub async fn select_text(
+        &self,
+        selector: Option<&str>,
+        range: &TextRange,
+        session_id: Option<&str>,
+    ) -> Result<TextSelection> {
+        let page = self.get_page(session_id).await?;
+        selection::select(&page, selector, range).await
+    }
+
+    /// The text currently selected.
+    pub async fn get_selection(&self, session_id: Option<&str>) -> Result<TextSelection> {
+        let page = self.get_page(session_id).await?;
+        selection::current(&page).await
+    }
+
+    /// Copy the current selection to the clipboard.
+    pub async fn copy_selection(&self, session_id: Option<&str>) -> Result<ClipboardCopy> {
+        let page = self.get_page(session_id).await?;
+        selection::copy(&page).await
+    }
+
+    /// Fill a rich text editor (contenteditable), replacing its content
+    /// unless `append`.
+    pub async fn fill_rich_text(
+        &self,
+        selector: &str,
+        content: &str,
+        format: RichTextFormat,
+        append: bool,
+        session_id: Option<&str>,
+    ) -> Result<RichTextResult> {
+        let page = self.get_page(session_id).await?;
+        rich_text::fill(&page, selector, content, format, append).await
+    }
+
+    /// Press a key or shortcut (`Enter`, `Control+A`, `Meta+Shift+P`).
+    ///
+    /// Named keys and single characters are sent with the `code`/`keyCode`
+    /// the session's keyboard layout would produce.
+    pub async fn press(&self, key: &str, session_id: Option<&str>) -> Result<()> {
+        let page = self.get_page(session_id).await?;
+        let layout = self.keyboard_layout(session_id).await?;
+
+        match keyboard::parse_combo(layout, key)? {
+            Some(combo) => keyboard::dispatch_combo(&page, &combo).await?,
+            None => {
+                // Unknown key name - pass it through and let Chrome interpret it
+                page.execute(
+                    DispatchKeyEventParams::builder()
+                        .r#type(DispatchKeyEventType::KeyDown)
+                        .key(key)
+                        .build()
+                        .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
+                )
+                .await?;
+
+                page.execute(
+                    DispatchKeyEventParams::builder()
+                        .r#type(DispatchKeyEventType::KeyUp)
+                        .key(key)
+                        .build()
+                        .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the keyboard layout used to generate key events for a session.
+    pub async fn set_keyboard_layout(
+        &self,
+        layout: KeyboardLayout,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+        session.keyboard_layout = layout;
+        Ok(())
+    }
+
+    /// Time limits set for a session, if any.
+    pub async fn timeouts(&self, session_id: Option<&str>) -> Result<Option<Timeouts>> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+        Ok(session.timeouts)
+    }
+
+    /// Set a session's time limits; `None` goes back to the daemon's.
+    pub async fn set_timeouts(
+        &self,
+        timeouts: Option<Timeouts>,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(sid)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+        session.timeouts = timeouts;
+        Ok(())
+    }
+
+    /// Set what happens to tabs and windows the session's page opens.
+    pub async fn set_popup_policy(
+        &self,
+        policy: PopupPolicy,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        let page = self.get_page(session_id).await?;
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (previous, events) = {
+            let mut sessions = self.sessions.write().await;
+            let session = sessions
+                .get_mut(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.popup_policy.1.take(), session.events.clone())
+        };
+
+        let script = popups::apply(&page, policy, previous).await?;
+        if let Some(session) = self.sessions.write().await.get_mut(sid) {
+            session.popup_policy = (policy, script);
+        }
+        self.popups.watch(
+            page.target_id().clone(),
+            Opener {
+                session_id: sid.to_string(),
+                policy,
+                page,
+                events,
+            },
+        );
+        Ok(())
+    }
+
+    /// Make sessions of the popups adopted under `attach` since the last
+    /// call. Popups open more popups the same way.
+    async fn adopt_popups(&self) {
+        for popup in self.popups.take_adopted() {
+            let page = match self.browser.get_page(popup.target_id).await {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::warn!("Failed to adopt popup {}: {}", popup.session_id, e);
+                    continue;
+                }
+            };
+            let mut session = BrowserSession::new(
+                &popup.session_id,
+                None,
+                page.clone(),
+                self.robots.get().cloned(),
+            )
+            .await;
+            session.popup_policy = (PopupPolicy::Attach, None);
+            session.opener = Some(popup.opener);
+            self.popups.watch(
+                page.target_id().clone(),
+                Opener {
+                    session_id: popup.session_id.clone(),
+                    policy: PopupPolicy::Attach,
+                    page,
+                    events: session.events.clone(),
+                },
+            );
+            tracing::info!("Adopted popup as session: {}", popup.session_id);
+            self.sessions
+                .write()
+                .await
+                .insert(popup.session_id, session);
+        }
+    }
+
+    /// Zoom the session's page to `factor` (1 for 100%).
+    pub async fn set_zoom(&self, factor: f64, session_id: Option<&str>) -> Result<ZoomResult> {
+        let page = self.get_page(session_id).await?;
+        let zoom = zoom::apply(&page, factor).await?;
+
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        if let Some(session) = self.sessions.write().await.get_mut(sid) {
+            session.zoom = zoom;
+        }
+
+        let (viewport_width, viewport_height) = zoom::viewport(&page).await?;
+        Ok(ZoomResult {
+            factor,
+            viewport_width,
+            viewport_height,
+        })
+    }
+
+    /// Page zoom of a session, when not 100%.
+    async fn zoom(&self, session_id: Option<&str>) -> Result<Option<Zoom>> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        self.sessions
+            .read()
+            .await
+            .get(sid)
+            .map(|s| s.zoom)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))
+    }
+
+    /// Keyboard layout of a session.
+    async fn keyboard_layout(&self, session_id: Option<&str>) -> Result<KeyboardLayout> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        self.sessions
+            .read()
+            .await
+            .get(sid)
+            .map(|s| s.keyboard_layout)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))
+    }
+
+    /// Frame a frame-qualified ref (`@f2e15`) belongs to, with the session
+    /// of its process if that isn't the page's; `None` for selectors on the
+    /// page itself.
+    async fn frame_for(
+        &self,
+        session_id: Option<&str>,
+        selector: &str,
+    ) -> Result<Option<ChildFrame>> {
+        let Some(number) = frames::frame_number(selector) else {
+            return Ok(None);
+        };
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let (page, refs) = {
+            let sessions = self.sessions.read().await;
+            let session = sessions
+                .get(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            (session.page.clone(), Arc::clone(&session.refs))
+        };
+        let gone = || format!("Frame of {} is gone; take a new snapshot", selector);
+        let id = refs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .frame(number)
+            .with_context(gone)?;
+        frames::child_frames(&page, self.frames.as_ref())
+            .await?
+            .into_iter()
+            .find(|frame| frame.id == id)
+            .map(Some)
+            .with_context(gone)
+    }
+
+    /// Run the session's clock from `start_time` in `tick_mode`, on the
+    /// current and every later document.
+    pub async fn set_fake_clock(
+        &self,
+        start_time: &str,
+        tick_mode: ClockTickMode,
+        session_id: Option<&str>,
+    ) -> Result<FakeClockResult> {
+        let page = self.get_page(session_id).await?;
+        let previous = self.take_fake_clock(session_id).await?;
+
+        let (installed, result) = clock::install(
+            &page,
+            start_time,
+            tick_mode,
+            previous.map(|(script, _)| script),
+        )
+        .await?;
+
+        self.store_fake_clock(session_id, installed, tick_mode)
+            .await;
+        Ok(result)
+    }
+
+    /// Move the session's fake clock ahead, firing the page's timers that
+    /// fall due on the way.
+    pub async fn fast_forward(
+        &self,
+        ms: u64,
+        max_timers: usize,
+        session_id: Option<&str>,
+    ) -> Result<FastForwardResult> {
+        let page = self.get_page(session_id).await?;
+        let Some((script, tick_mode)) = self.take_fake_clock(session_id).await? else {
+            anyhow::bail!("No fake clock in this session; call set_fake_clock first");
+        };
+
+        let forwarded = match clock::fast_forward(&page, ms, max_timers, tick_mode).await {
+            Ok(forwarded) => forwarded,
+            Err(e) => {
+                self.store_fake_clock(session_id, script, tick_mode).await;
+                return Err(e);
+            }
+        };
+        // Later documents start from where this one ended up
+        let start = (forwarded.clock.epoch_ms.round() as i64).to_string();
+        let (installed, _) = clock::install(&page, &start, tick_mode, Some(script)).await?;
+        self.store_fake_clock(session_id, installed, tick_mode)
+            .await;
+
+        Ok(forwarded)
+    }
+
+    /// Put the real clock back in a session.
+    pub async fn clear_fake_clock(&self, session_id: Option<&str>) -> Result<FakeClockResult> {
         let page = self.get_page(session_id).await?;
+        let installed = self.take_fake_clock(session_id).await?;
+        clock::remove(&page, installed.map(|(script, _)| script)).await
+    }
 
-        page.execute(
-            DispatchKeyEventParams::builder()
-                .r#type(DispatchKeyEventType::KeyDown)
-                .key(key)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
-        )
-        .await?;
-
-        page.execute(
-            DispatchKeyEventParams::builder()
-                .r#type(DispatchKeyEventType::KeyUp)
-                .key(key)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
-        )
-        .await?;
+    /// Fake clock of a session, which the caller replaces or removes.
+    async fn take_fake_clock(
+        &self,
+        session_id: Option<&str>,
+    ) -> Result<Option<(ScriptIdentifier, ClockTickMode)>> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        self.sessions
+            .write()
+            .await
+            .get_mut(sid)
+            .map(|s| s.fake_clock.take())
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))
+    }
 
-        Ok(())
+    async fn store_fake_clock(
+        &self,
+        session_id: Option<&str>,
+        script: ScriptIdentifier,
+        tick_mode: ClockTickMode,
+    ) {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        if let Some(session) = self.sessions.write().await.get_mut(sid) {
+            session.fake_clock = Some((script, tick_mode));
+        }
     }
 
     // =========================================================================
@@ -527,32 +2632,43 @@ impl BrowserClient {
         session_id: Option<&str>,
     ) -> Result<()> {
         let page = self.get_page(session_id).await?;
+        let frame = self.frame_for(session_id, selector).await?;
         let css_selector = resolve_selector(selector);
 
         // Use JSON encoding for safe string escaping
         let selector_json = serde_json::to_string(&css_selector)?;
         let value_json = serde_json::to_string(value)?;
 
-        let script = format!(
-            r#"(() => {{
-                const sel = {};
-                const val = {};
-                const el = document.querySelector(sel);
+        let script = shadow::call(
+            r#"((sel, val) => {
+                const el = deepQuery(sel);
                 if (!el) throw new Error('Element not found: ' + sel);
                 el.value = val;
-                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                el.dispatchEvent(new Event('change', { bubbles: true }));
                 return true;
-            }})()"#,
-            selector_json, value_json
+            })"#,
+            &format!("{}, {}", selector_json, value_json),
         );
 
-        page.evaluate(script)
+        frames::evaluate::<bool>(&page, frame.as_ref(), script)
             .await
             .context("Failed to select option")?;
 
         Ok(())
     }
 
+    /// Select options of a `<select>` by value, label, or index.
+    pub async fn select_option(
+        &self,
+        selector: &str,
+        query: &OptionQuery,
+        session_id: Option<&str>,
+    ) -> Result<SelectResult> {
+        let page = self.get_page(session_id).await?;
+        let frame = self.frame_for(session_id, selector).await?;
+        select::select_options(&page, frame.as_ref(), selector, query).await
+    }
+
     /// Set checkbox/radio state.
     pub async fn check(
         &self,
@@ -561,204 +2677,470 @@ impl BrowserClient {
         session_id: Option<&str>,
     ) -> Result<()> {
         let page = self.get_page(session_id).await?;
+        let frame = self.frame_for(session_id, selector).await?;
         let css_selector = resolve_selector(selector);
 
         let selector_json = serde_json::to_string(&css_selector)?;
 
-        let script = format!(
-            r#"(() => {{
-                const sel = {};
-                const el = document.querySelector(sel);
+        let script = shadow::call(
+            r#"((sel, checked) => {
+                const el = deepQuery(sel);
                 if (!el) throw new Error('Element not found: ' + sel);
-                if (el.checked !== {}) {{
+                if (el.checked !== checked) {
                     el.click();
-                }}
+                }
                 return el.checked;
-            }})()"#,
-            selector_json, checked
+            })"#,
+            &format!("{}, {}", selector_json, checked),
         );
 
-        page.evaluate(script)
+        frames::evaluate::<bool>(&page, frame.as_ref(), script)
             .await
             .context("Failed to set checkbox state")?;
 
         Ok(())
     }
 
-    /// Hover over an element.
-    pub async fn hover(&self, selector: &str, session_id: Option<&str>) -> Result<()> {
+    /// Hover over an element, revealing hover-only menus and tooltips.
+    pub async fn hover(&self, selector: &str, session_id: Option<&str>) -> Result<HoverResult> {
         let page = self.get_page(session_id).await?;
-        let css_selector = resolve_selector(selector);
+        let frame = self.frame_for(session_id, selector).await?;
 
-        let element = page
-            .find_element(&css_selector)
-            .await
-            .context("Element not found")?;
+        let target = Target::find(&page, frame.as_ref(), selector).await?;
 
-        element.hover().await?;
+        target.hover(&page).await?;
+        self.trace_pointer(session_id, &page, &target, false).await;
 
-        Ok(())
+        Ok(HoverResult {
+            success: true,
+            selector: selector.to_string(),
+        })
     }
 
-    /// Scroll to element or by amount.
-    pub async fn scroll(
+    /// Focus the element at `selector` without clicking it.
+    pub async fn focus(&self, selector: &str, session_id: Option<&str>) -> Result<FocusResult> {
+        let page = self.get_page(session_id).await?;
+        let frame = self.frame_for(session_id, selector).await?;
+        focus::apply(&page, frame.as_ref(), Some(selector), true).await
+    }
+
+    /// Take focus away from the element at `selector`, or from whatever has
+    /// focus, firing its `blur` handlers.
+    pub async fn blur(
         &self,
         selector: Option<&str>,
-        x: i32,
-        y: i32,
         session_id: Option<&str>,
-    ) -> Result<()> {
+    ) -> Result<FocusResult> {
         let page = self.get_page(session_id).await?;
+        let frame = match selector {
+            Some(selector) => self.frame_for(session_id, selector).await?,
+            None => None,
+        };
+        focus::apply(&page, frame.as_ref(), selector, false).await
+    }
 
-        let script = if let Some(sel) = selector {
-            let css_selector = resolve_selector(sel);
-            let selector_json = serde_json::to_string(&css_selector)?;
-            format!(
-                r#"(() => {{
-                    const sel = {};
-                    const el = document.querySelector(sel);
-                    if (el) el.scrollIntoView({{ behavior: 'instant', block: 'center' }});
-                }})()"#,
-                selector_json
-            )
-        } else {
-            format!("window.scrollBy({}, {})", x, y)
+    /// Set a date field: native date inputs directly, JS date pickers by
+    /// typing in their format (`format`, or what the field suggests) and
+    /// falling back to their calendar popup.
+    pub async fn set_date(
+        &self,
+        selector: &str,
+        iso_date: &str,
+        format: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<SetDateResult> {
+        let (day, time) = date::parse_date(iso_date)?;
+        let page = self.get_page(session_id).await?;
+        let css_selector = resolve_selector(selector);
+
+        let field = date::probe(&page, &css_selector).await?;
+        if !field.found {
+            anyhow::bail!("Element not found: {}", selector);
+        }
+        if field.disabled {
+            anyhow::bail!("Date field is disabled: {}", selector);
+        }
+        let result = |strategy, format, value| SetDateResult {
+            success: true,
+            date: iso_date.to_string(),
+            strategy,
+            format,
+            value,
         };
 
-        page.evaluate(script).await?;
+        if let Some(value) = date::native_value(&field.input_type, day, time, &field.value) {
+            let set = date::set_value(&page, &css_selector, &value).await?;
+            if set != value {
+                anyhow::bail!("Date field rejected {} (outside min/max?)", value);
+            }
+            return Ok(result(DateStrategy::Native, None, set));
+        }
 
-        Ok(())
+        // Typing first: it's what a user would do, and skips paging through months
+        let mut typed = None;
+        if !field.read_only {
+            let format = date::typing_format(format, &field.hints)?;
+            let text = day
+                .and_time(time.unwrap_or_default())
+                .format(&format)
+                .to_string();
+            date::focus(&page, &css_selector).await?;
+            let layout = self.keyboard_layout(session_id).await?;
+            keyboard::type_text(&page, layout, &text).await?;
+            tokio::time::sleep(date::SETTLE).await;
+            date::commit(&page, &css_selector).await?;
+            tokio::time::sleep(date::SETTLE).await;
+
+            let value = date::value(&page, &css_selector).await?;
+            if date::typed_matches(&value, &format, day) {
+                return Ok(result(DateStrategy::Typed, Some(format), value));
+            }
+            typed = Some(text);
+        }
+
+        self.click(selector, session_id).await?;
+        tokio::time::sleep(date::SETTLE).await;
+        let mark = format!("[{}]", date::PICK_MARK);
+        for _ in 0..date::MAX_CALENDAR_STEPS {
+            match date::calendar_step(&page, day).await? {
+                date::CalendarStep::Pick => {
+                    self.click(&mark, session_id).await?;
+                    tokio::time::sleep(date::SETTLE).await;
+                    let value = date::value(&page, &css_selector).await?;
+                    return Ok(result(DateStrategy::Calendar, None, value));
+                }
+                date::CalendarStep::Page { .. } => {
+                    self.click(&mark, session_id).await?;
+                    tokio::time::sleep(date::SETTLE).await;
+                }
+                date::CalendarStep::Paged => tokio::time::sleep(date::SETTLE).await,
+                step => {
+                    let reason = match step {
+                        date::CalendarStep::NoPopup => "no calendar popup opened",
+                        date::CalendarStep::UnknownMonth => {
+                            "can't tell which month the calendar shows"
+                        }
+                        date::CalendarStep::NoNav => {
+                            "no next/previous month control in the calendar"
+                        }
+                        _ => "the day can't be picked in the calendar (disabled?)",
+                    };
+                    match typed {
+                        Some(text) => anyhow::bail!(
+                            "Typing '{}' wasn't accepted and {}; pass 'format' to type another format",
+                            text,
+                            reason
+                        ),
+                        None => anyhow::bail!("Date field is read-only and {}", reason),
+                    }
+                }
+            }
+        }
+        anyhow::bail!(
+            "Calendar didn't reach {} after {} pages",
+            day,
+            date::MAX_CALENDAR_STEPS
+        )
     }
 
-    /// Press a key with modifiers (Ctrl, Shift, Alt, Meta).
-    pub async fn press_combo(
+    /// State of a media element, or of all media on the page without a
+    /// selector; `wait_ms` waits for them to become playable.
+    pub async fn media_state(
         &self,
-        modifiers: &[&str],
-        key: &str,
+        selector: Option<&str>,
+        wait_ms: u64,
         session_id: Option<&str>,
-    ) -> Result<()> {
+    ) -> Result<MediaList> {
+        let page = self.get_page(session_id).await?;
+        Ok(MediaList {
+            media: media::states(&page, selector, wait_ms).await?,
+        })
+    }
+
+    /// Play, pause, or seek a media element (the first on the page without
+    /// a selector).
+    pub async fn media_control(
+        &self,
+        selector: Option<&str>,
+        action: MediaAction,
+        session_id: Option<&str>,
+    ) -> Result<MediaControlResult> {
         let page = self.get_page(session_id).await?;
+        let (state, problem) = media::control(&page, selector, action).await?;
 
-        // Calculate modifier flags
-        let modifier_flags: i64 = modifiers.iter().fold(0, |acc, m| {
-            acc | match m.to_lowercase().as_str() {
-                "ctrl" | "control" => 1,
-                "shift" => 2,
-                "alt" => 4,
-                "meta" | "cmd" | "command" => 8,
-                _ => 0,
-            }
-        });
+        Ok(MediaControlResult {
+            success: problem.is_none(),
+            problem,
+            state,
+        })
+    }
 
-        // Send keyDown with modifiers
-        page.execute(
-            DispatchKeyEventParams::builder()
-                .r#type(DispatchKeyEventType::KeyDown)
-                .key(key)
-                .modifiers(modifier_flags)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
-        )
-        .await?;
+    /// Mute or unmute all media on the page.
+    pub async fn mute_media(
+        &self,
+        muted: bool,
+        session_id: Option<&str>,
+    ) -> Result<MediaMuteResult> {
+        let page = self.get_page(session_id).await?;
+        let elements = media::mute(&page, muted).await?;
+        Ok(MediaMuteResult { muted, elements })
+    }
 
-        // Send keyUp
-        page.execute(
-            DispatchKeyEventParams::builder()
-                .r#type(DispatchKeyEventType::KeyUp)
-                .key(key)
-                .modifiers(modifier_flags)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
+    /// Outline an element with an optional label for humans watching the run.
+    ///
+    /// Intended for headful sessions and recordings; `duration_ms` of 0 keeps
+    /// the outline until the next highlight.
+    pub async fn highlight(
+        &self,
+        selector: &str,
+        label: Option<&str>,
+        duration_ms: u64,
+        color: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<HighlightResult> {
+        let page = self.get_page(session_id).await?;
+        let css_selector = resolve_selector(selector);
+
+        let rect = overlay::highlight(
+            &page,
+            &css_selector,
+            label,
+            duration_ms,
+            color.unwrap_or(overlay::DEFAULT_HIGHLIGHT_COLOR),
         )
         .await?;
 
-        Ok(())
+        Ok(HighlightResult {
+            success: rect.is_some(),
+            element: css_selector,
+            rect,
+        })
     }
 
-    /// Upload a file to an input element.
-    pub async fn upload(
+    /// Check an assertion against the session's current page.
+    pub async fn assert(
         &self,
-        selector: &str,
-        file_path: &str,
+        assertion: &Assertion,
         session_id: Option<&str>,
-    ) -> Result<()> {
+    ) -> Result<AssertionResult> {
         let page = self.get_page(session_id).await?;
-        let css_selector = resolve_selector(selector);
+        assertions::check(&page, assertion).await
+    }
 
-        // Resolve to absolute path
-        let path = std::path::Path::new(file_path);
-        let absolute_path = if path.is_absolute() {
-            path.to_path_buf()
-        } else {
-            std::env::current_dir()?.join(path)
+    /// Check a batch of assertions as soft asserts, collecting every result.
+    pub async fn verify(
+        &self,
+        checks: &[Assertion],
+        session_id: Option<&str>,
+    ) -> Result<VerifyReport> {
+        let page = self.get_page(session_id).await?;
+        Ok(VerifyReport::new(
+            assertions::check_all(&page, checks).await,
+        ))
+    }
+
+    /// Show or hide the synthetic cursor for a session.
+    ///
+    /// While shown, clicks and hovers move an on-page cursor to the point the
+    /// mouse event was dispatched at (with a ripple on click), so headful runs
+    /// and recordings show where interactions happened.
+    pub async fn set_cursor_overlay(&self, enabled: bool, session_id: Option<&str>) -> Result<()> {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let page = {
+            let mut sessions = self.sessions.write().await;
+            let session = sessions
+                .get_mut(sid)
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", sid))?;
+            session.show_cursor = enabled;
+            session.page.clone()
         };
 
-        // Verify file exists
-        if !absolute_path.exists() {
-            anyhow::bail!("File not found: {}", absolute_path.display());
+        if !enabled {
+            overlay::hide_cursor(&page).await?;
         }
 
-        // Validate element exists and is a file input
-        let selector_json = serde_json::to_string(&css_selector)?;
-
-        // Get the DOM node ID for the element
-        let script = format!(
-            r#"(() => {{
-                const sel = {};
-                const el = document.querySelector(sel);
-                if (!el) throw new Error('Element not found: ' + sel);
-                if (el.tagName !== 'INPUT' || el.type !== 'file') {{
-                    throw new Error('Element is not a file input: ' + sel);
-                }}
-                return true;
-            }})()"#,
-            selector_json
-        );
+        Ok(())
+    }
 
-        page.evaluate(script)
+    /// Draw the synthetic cursor at an element's click point, if enabled.
+    ///
+    /// Best-effort: a failure here never fails the interaction itself.
+    async fn trace_pointer(
+        &self,
+        session_id: Option<&str>,
+        page: &Page,
+        target: &Target,
+        click: bool,
+    ) {
+        let sid = session_id.unwrap_or(&self.default_session_id);
+        let enabled = self
+            .sessions
+            .read()
             .await
-            .context("Element validation failed")?;
-
-        // Use CDP DOM.setFileInputFiles via element
-        // chromiumoxide Element doesn't expose this directly, so we use Page.evaluate with file input
-        // Instead, we'll focus the element and use CDP Input domain
+            .get(sid)
+            .is_some_and(|s| s.show_cursor);
+        if !enabled {
+            return;
+        }
 
-        // Get the element's node id and use DOM.setFileInputFiles
-        use chromiumoxide::cdp::browser_protocol::dom::{
-            GetDocumentParams, QuerySelectorParams, SetFileInputFilesParams,
+        let drawn = match target.clickable_point().await {
+            Ok(point) => overlay::draw_cursor(page, point.x, point.y, click).await,
+            Err(e) => Err(e),
         };
+        if let Err(e) = drawn {
+            tracing::debug!("Failed to draw cursor overlay: {}", e);
+        }
+    }
+
+    /// Scroll to element or by amount.
+    pub async fn scroll(
+        &self,
+        selector: Option<&str>,
+        x: i32,
+        y: i32,
+        session_id: Option<&str>,
+    ) -> Result<ScrollResult> {
+        match selector {
+            Some(sel) => {
+                self.scroll_to_element(sel, ScrollBlock::Center, session_id)
+                    .await
+            }
+            None => self.scroll_by(None, x as f64, y as f64, session_id).await,
+        }
+    }
 
-        // Get document root
-        let doc = page.execute(GetDocumentParams::default()).await?;
-        let root_node_id = doc.root.node_id;
+    /// Scroll an element into view.
+    pub async fn scroll_to_element(
+        &self,
+        selector: &str,
+        block: ScrollBlock,
+        session_id: Option<&str>,
+    ) -> Result<ScrollResult> {
+        let page = self.get_page(session_id).await?;
+        scroll::to_element(&page, selector, block).await
+    }
 
-        // Query for the element
-        let query_result = page
-            .execute(QuerySelectorParams::new(root_node_id, &css_selector))
-            .await?;
-        let node_id = query_result.node_id;
-
-        // Set the file
-        page.execute(
-            SetFileInputFilesParams::builder()
-                .files(vec![absolute_path.to_string_lossy().to_string()])
-                .node_id(node_id)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build SetFileInputFilesParams: {:?}", e))?,
-        )
-        .await?;
+    /// Scroll the window, or the scroll container at `container`, by pixels.
+    pub async fn scroll_by(
+        &self,
+        container: Option<&str>,
+        x: f64,
+        y: f64,
+        session_id: Option<&str>,
+    ) -> Result<ScrollResult> {
+        let page = self.get_page(session_id).await?;
+        scroll::by(&page, container, x, y).await
+    }
+
+    /// Scroll the window (or `container`) to the bottom until lazy-loaded
+    /// content stops appearing, waiting `settle` after each round.
+    pub async fn scroll_to_bottom(
+        &self,
+        container: Option<&str>,
+        max_rounds: u32,
+        settle: Duration,
+        session_id: Option<&str>,
+    ) -> Result<ScrollResult> {
+        let page = self.get_page(session_id).await?;
+        scroll::to_bottom(&page, container, max_rounds, settle).await
+    }
 
-        tracing::debug!("Uploaded file {} to {}", file_path, selector);
+    /// Press a key with modifiers (Ctrl, Shift, Alt, Meta).
+    pub async fn press_combo(
+        &self,
+        modifiers: &[&str],
+        key: &str,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        let mut combo = modifiers.join("+");
+        if !combo.is_empty() {
+            combo.push('+');
+        }
+        combo.push_str(key);
+        self.press(&combo, session_id).await
+    }
 
+    /// Upload a file to an input element.
+    pub async fn upload(
+        &self,
+        selector: &str,
+        file_path: &str,
+        session_id: Option<&str>,
+    ) -> Result<()> {
+        self.upload_files(selector, &[file_path.to_string()], session_id)
+            .await?;
         Ok(())
     }
 
+    /// Set the files of a file input, replacing any chosen before.
+    pub async fn upload_files(
+        &self,
+        selector: &str,
+        paths: &[String],
+        session_id: Option<&str>,
+    ) -> Result<UploadResult> {
+        let page = self.get_page(session_id).await?;
+        upload::set_files(&page, selector, paths).await
+    }
+
     /// Health check - verify browser is responsive.
     pub async fn health_check(&self) -> Result<bool> {
         let _version = self.browser.version().await?;
         Ok(true)
     }
 
+    /// Open a throwaway `about:blank` page and time a CDP round-trip on it.
+    ///
+    /// Returns `(navigate_ms, cdp_latency_ms)`. The page is closed afterwards;
+    /// no session is touched.
+    pub async fn self_test(&self) -> Result<(f64, f64)> {
+        let started = std::time::Instant::now();
+        let page = self
+            .new_detached_page()
+            .await
+            .context("Failed to open about:blank")?;
+        let navigate_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let probe = std::time::Instant::now();
+        let round_trip = page.evaluate("1 + 1").await;
+        let latency_ms = probe.elapsed().as_secs_f64() * 1000.0;
+
+        let _ = page.close().await;
+        round_trip.context("CDP round-trip failed")?;
+
+        Ok((navigate_ms, latency_ms))
+    }
+
+    /// What the browser can do in its mode, probed on a scratch page.
+    pub async fn capabilities(&self) -> Result<BrowserCapabilities> {
+        let page = self.new_detached_page().await?;
+        let mut found = capabilities::probe(&page, self.mode).await;
+        let _ = page.close().await;
+        found.display = self
+            .display
+            .as_ref()
+            .map(|display| display.display().to_string());
+        Ok(found)
+    }
+
+    /// Open a page outside every session, for background work like crawls.
+    /// Its documents are held to robots.txt like the sessions' until it
+    /// closes.
+    ///
+    /// The caller owns the page and should close it when done.
+    pub async fn new_detached_page(&self) -> Result<Page> {
+        let page = self
+            .browser
+            .new_page("about:blank")
+            .await
+            .context("Failed to open page")?;
+        if let Some(robots) = self.robots.get() {
+            // The guard's events end with the page
+            drop(gate::guard(&page, Arc::clone(robots)).await?);
+        }
+        Ok(page)
+    }
+
     /// Close the browser.
     #[allow(dead_code)]
     pub async fn close(mut self) -> Result<()> {
@@ -781,7 +3163,9 @@ impl BrowserClient {
     }
 
     /// Find Chrome executable on the system.
-    fn find_chrome_executable() -> Result<PathBuf> {
+    ///
+    /// chrome-headless-shell only runs old headless, so other modes skip it.
+    fn find_chrome_executable(mode: HeadlessMode) -> Result<PathBuf> {
         let (subdir_name, alt_subdir) = if cfg!(target_os = "macos") {
             if cfg!(target_arch = "aarch64") {
                 (
@@ -798,7 +3182,7 @@ impl BrowserClient {
             ("chrome-headless-shell-linux", "chrome-headless-shell-linux")
         };
 
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = dirs::home_dir().filter(|_| mode == HeadlessMode::Old) {
             let playwright_cache = home.join("Library/Caches/ms-playwright");
             if playwright_cache.exists() {
                 if let Ok(entries) = std::fs::read_dir(&playwright_cache) {
@@ -854,9 +3238,48 @@ impl BrowserClient {
     }
 }
 
-/// Resolve @eN selector to CSS selector.
-fn resolve_selector(selector: &str) -> String {
-    if selector.starts_with("@e") {
+/// Open the connection out-of-process frames are reached through; without
+/// it they are left out of snapshots.
+async fn connect_frames(browser: &Browser) -> Option<Connection> {
+    match Connection::open(browser.websocket_address()).await {
+        Ok(connection) => Some(connection),
+        Err(e) => {
+            tracing::warn!("Cross-site frames will be left out of snapshots: {}", e);
+            None
+        }
+    }
+}
+
+/// Fetch the page URL and title concurrently on the page's CDP session.
+async fn page_url_and_title(page: &Page) -> Result<(String, String)> {
+    let (url, title) = futures::try_join!(page.url(), page.get_title())?;
+    Ok((url.unwrap_or_default(), title.unwrap_or_default()))
+}
+
+/// Result of a navigation that reached `waited.0` after `waited.1`.
+async fn navigation_result(
+    page: &Page,
+    (reached, load_time): (WaitUntil, Duration),
+) -> Result<NavigationResult> {
+    let (url, title) = page_url_and_title(page).await?;
+    let captchas = captcha::detect(page).await.unwrap_or_else(|e| {
+        tracing::debug!("Could not check {} for CAPTCHAs: {}", url, e);
+        Vec::new()
+    });
+
+    Ok(NavigationResult {
+        url,
+        title,
+        status: None,
+        captchas,
+        wait_until: Some(reached),
+        load_time_ms: Some(load_time.as_millis() as u64),
+    })
+}
+
+/// Resolve @eN (or frame-qualified @fNeM) selector to CSS selector.
+pub(super) fn resolve_selector(selector: &str) -> String {
+    if selector.starts_with("@e") || frames::frame_number(selector).is_some() {
         format!("[data-fgp-ref='{}']", &selector[1..])
     } else {
         selector.to_string()
@@ -868,6 +3291,13 @@ fn count_nodes(nodes: &[crate::models::AriaNode]) -> usize {
     nodes.iter().map(|n| 1 + count_nodes(&n.children)).sum()
 }
 
+/// Whether any node in the tree comes from a child frame.
+fn has_frame_nodes(nodes: &[crate::models::AriaNode]) -> bool {
+    nodes
+        .iter()
+        .any(|n| frames::frame_number(&n.ref_id).is_some() || has_frame_nodes(&n.children))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -880,7 +3310,7 @@ mod tests {
     #[ignore]
     async fn snapshot_refs_are_findable() {
         let tmp = std::env::temp_dir().join("fgp-test-snapshot-refs");
-        let client = BrowserClient::new(tmp, true)
+        let client = BrowserClient::new(tmp, HeadlessMode::Old)
             .await
             .expect("Failed to launch Chrome");
 
@@ -890,10 +3320,13 @@ mod tests {
             <input id="input1" type="text" placeholder="Type here" />
             <a id="link1" href="#">A Link</a>
         </body></html>"##;
-        client.navigate(html, None).await.unwrap();
+        client
+            .navigate(html, WaitUntil::Load, DEFAULT_TIMEOUT, None)
+            .await
+            .unwrap();
 
         // Take snapshot — this should inject data-fgp-ref attributes
-        let snapshot = client.snapshot(None).await.unwrap();
+        let snapshot = client.snapshot(None, false).await.unwrap();
         assert!(!snapshot.nodes.is_empty(), "Snapshot should have nodes");
 
         // Every node with an @eN ref should be findable via resolve_selector
@@ -924,7 +3357,7 @@ mod tests {
     #[ignore]
     async fn snapshot_refs_refresh_on_second_call() {
         let tmp = std::env::temp_dir().join("fgp-test-snapshot-refresh");
-        let client = BrowserClient::new(tmp, true)
+        let client = BrowserClient::new(tmp, HeadlessMode::Old)
             .await
             .expect("Failed to launch Chrome");
 
@@ -932,14 +3365,17 @@ mod tests {
             <button id="btn1">Click Me</button>
             <a id="link1" href="#">A Link</a>
         </body></html>"##;
-        client.navigate(html, None).await.unwrap();
+        client
+            .navigate(html, WaitUntil::Load, DEFAULT_TIMEOUT, None)
+            .await
+            .unwrap();
 
         // First snapshot
-        let snap1 = client.snapshot(None).await.unwrap();
+        let snap1 = client.snapshot(None, false).await.unwrap();
         assert!(!snap1.nodes.is_empty());
 
         // Second snapshot on the same page — refs should be refreshed
-        let snap2 = client.snapshot(None).await.unwrap();
+        let snap2 = client.snapshot(None, false).await.unwrap();
         assert!(!snap2.nodes.is_empty());
 
         // Refs from second snapshot should all be findable
@@ -954,4 +3390,93 @@ mod tests {
             );
         }
     }
+
+    /// Verify that an observer's snapshot leaves the owner's diff baseline
+    /// and paging cursor valid.
+    ///
+    /// Requires Chrome — run with: cargo test -- --ignored observer_snapshot
+    #[tokio::test]
+    #[ignore]
+    async fn observer_snapshot_keeps_owner_state() {
+        let tmp = std::env::temp_dir().join("fgp-test-observer-snapshot");
+        let client = BrowserClient::new(tmp, HeadlessMode::Old)
+            .await
+            .expect("Failed to launch Chrome");
+
+        let html = r##"data:text/html,<html><body>
+            <button id="btn1">Click Me</button>
+            <a id="link1" href="#">A Link</a>
+            <input id="input1" type="text" />
+        </body></html>"##;
+        client
+            .navigate(html, WaitUntil::Load, DEFAULT_TIMEOUT, None)
+            .await
+            .unwrap();
+
+        let all = SnapshotFilter::default();
+        client
+            .snapshot_page(None, false, &all, 1, usize::MAX, None)
+            .await
+            .unwrap();
+        let first = client
+            .snapshot_page(None, false, &all, 1, usize::MAX, None)
+            .await
+            .unwrap();
+        let cursor = first
+            .next_cursor
+            .expect("Owner's page should have a cursor");
+        let diff = client.snapshot_diff(None).await.unwrap();
+        assert!(diff.is_some());
+
+        // Uncached and filtered: the calls that used to replace the cache
+        let interactive = SnapshotFilter {
+            interactive: true,
+            ..SnapshotFilter::default()
+        };
+        let observed = client
+            .observe_snapshot_page(None, false, &interactive, 1, usize::MAX, None)
+            .await
+            .unwrap();
+        assert!(!observed.nodes.is_empty());
+
+        assert_eq!(client.snapshot_diff(None).await.unwrap(), diff);
+        let next = client
+            .snapshot_page(None, true, &all, 1, usize::MAX, Some(&cursor))
+            .await
+            .unwrap();
+        assert_eq!(next.offset, 1);
+    }
+
+    /// Verify that a snapshot records scroll and restore_scroll returns to it
+    /// after a reload.
+    ///
+    /// Requires Chrome — run with: cargo test -- --ignored scroll_restores
+    #[tokio::test]
+    #[ignore]
+    async fn scroll_restores_after_reload() {
+        let tmp = std::env::temp_dir().join("fgp-test-scroll-restore");
+        let client = BrowserClient::new(tmp, HeadlessMode::Old)
+            .await
+            .expect("Failed to launch Chrome");
+
+        let html = r##"data:text/html,<html><body>
+            <div style="height: 5000px">Tall page</div>
+        </body></html>"##;
+        client
+            .navigate(html, WaitUntil::Load, DEFAULT_TIMEOUT, None)
+            .await
+            .unwrap();
+        client.scroll(None, 0, 1200, None).await.unwrap();
+
+        let snapshot = client.snapshot(None, false).await.unwrap();
+        assert_eq!(snapshot.scroll.as_ref().map(|s| s.y), Some(1200.0));
+
+        client
+            .navigate(html, WaitUntil::Load, DEFAULT_TIMEOUT, None)
+            .await
+            .unwrap();
+        let restored = client.restore_scroll(None, None).await.unwrap();
+        assert!(restored.success);
+        assert_eq!(restored.scroll.y, 1200.0);
+    }
 }
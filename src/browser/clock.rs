@@ -0,0 +1,339 @@
+//! Fake clock for time-dependent pages.
+//!
+//! Every document in the session gets a `Date` replacement whose `now()` and
+//! argument-less constructor report the fake time, either running from the
+//! start time at normal speed (`realtime`) or standing still (`frozen`).
+//! Dates built from arguments, and everything rendered in local time, still
+//! go through the real `Date`, so the page's timezone (including an emulated
+//! one) applies as usual; a start time without an offset is read in that
+//! timezone too.
+//!
+//! Timers (`setTimeout`, `setInterval`) keep running on real time, which is
+//! what countdowns and expiry banners poll with, but are tracked, so
+//! `fast_forward` can move the clock ahead and fire the ones that fall due on
+//! the way, in order, each seeing its own due time. Only timers set after the
+//! clock was installed are tracked; `requestAnimationFrame`, CSS animations,
+//! and `performance.now()` stay on real time.
+//!
+//! `Emulation.setVirtualTimePolicy` isn't used: once a budget runs out the
+//! page stays paused, or skips every idle period from then on, and there is
+//! no going back to real time.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, RemoveScriptToEvaluateOnNewDocumentParams,
+    ScriptIdentifier,
+};
+use chromiumoxide::page::Page;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::models::{ClockTickMode, FakeClockResult, FastForwardResult};
+
+/// Installs the `Date` and timer replacements once per document and
+/// (re)configures them.
+const CLOCK_SCRIPT: &str = r#"((config) => {
+    let clock = window.__fgpClock;
+    if (!clock) {
+        const RealDate = Date;
+        const real = {
+            setTimeout: window.setTimeout, clearTimeout: window.clearTimeout,
+            setInterval: window.setInterval, clearInterval: window.clearInterval,
+        };
+        clock = { RealDate, real, config };
+        const now = () => clock.config.frozen
+            ? clock.config.start
+            : clock.config.start + (RealDate.now() - clock.config.anchor);
+        const FakeDate = function Date(...args) {
+            if (!new.target) return new RealDate(now()).toString();
+            return Reflect.construct(RealDate, args.length ? args : [now()], new.target);
+        };
+        FakeDate.prototype = RealDate.prototype;
+        FakeDate.now = now;
+        FakeDate.parse = RealDate.parse;
+        FakeDate.UTC = RealDate.UTC;
+
+        const timers = new Map();
+        let lastId = 0;
+        const arm = (id, t, wait) => {
+            t.handle = real.setTimeout.call(window, () => fire(id), wait);
+        };
+        const fire = (id) => {
+            const t = timers.get(id);
+            if (!t) return;
+            if (t.repeat) {
+                t.due += t.delay;
+                arm(id, t, t.delay);
+            } else {
+                timers.delete(id);
+            }
+            try {
+                if (typeof t.fn === 'function') t.fn.apply(window, t.args);
+                else (0, eval)(String(t.fn));
+            } catch (e) {
+                real.setTimeout.call(window, () => { throw e; });
+            }
+        };
+        const add = (repeat) => function (fn, delay, ...args) {
+            const id = ++lastId;
+            // Browsers run zero-delay intervals about every millisecond
+            const wait = Math.max(repeat ? 1 : 0, Number(delay) || 0);
+            const t = { fn, args, delay: wait, due: now() + wait, repeat };
+            timers.set(id, t);
+            arm(id, t, wait);
+            return id;
+        };
+        const clear = function (id) {
+            const t = timers.get(id);
+            if (!t) return;
+            real.clearTimeout.call(window, t.handle);
+            timers.delete(id);
+        };
+
+        clock.fastForward = (ms, maxTimers) => {
+            const target = now() + ms;
+            let fired = 0;
+            while (fired < maxTimers) {
+                let nextId = null;
+                for (const [id, t] of timers) {
+                    if (t.due <= target && (nextId === null || t.due < timers.get(nextId).due)) nextId = id;
+                }
+                if (nextId === null) break;
+                const t = timers.get(nextId);
+                clock.config.start += Math.max(0, t.due - now());
+                real.clearTimeout.call(window, t.handle);
+                fire(nextId);
+                fired++;
+            }
+            const capped = fired >= maxTimers;
+            if (!capped) clock.config.start += Math.max(0, target - now());
+            for (const [id, t] of timers) {
+                real.clearTimeout.call(window, t.handle);
+                arm(id, t, Math.max(0, t.due - now()));
+            }
+            return { fired, pending: timers.size, capped };
+        };
+
+        Object.defineProperty(window, '__fgpClock', { value: clock, configurable: true });
+        window.Date = FakeDate;
+        window.setTimeout = add(false);
+        window.setInterval = add(true);
+        window.clearTimeout = clear;
+        window.clearInterval = clear;
+    }
+    clock.config = config;
+})"#;
+
+/// Reads the start time with the page's real `Date`, in its timezone.
+const ANCHOR_SCRIPT: &str = r#"((start) => {
+    const RealDate = window.__fgpClock ? window.__fgpClock.RealDate : Date;
+    return { start: RealDate.parse(start), anchor: RealDate.now() };
+})"#;
+
+const NOW_SCRIPT: &str = r#"(() => {
+    const now = new Date();
+    return { epoch_ms: now.getTime(), iso: now.toISOString(), local: now.toString() };
+})()"#;
+
+/// Tracked timers keep firing; only new ones go back to the real functions.
+const RESTORE_SCRIPT: &str = r#"(() => {
+    if (!window.__fgpClock) return;
+    window.Date = window.__fgpClock.RealDate;
+    Object.assign(window, window.__fgpClock.real);
+    delete window.__fgpClock;
+})()"#;
+
+const FAST_FORWARD_SCRIPT: &str = r#"((ms, maxTimers) => window.__fgpClock
+    ? window.__fgpClock.fastForward(ms, maxTimers)
+    : null)"#;
+
+#[derive(Debug, Deserialize)]
+struct Anchor {
+    start: Option<f64>,
+    anchor: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Skipped {
+    fired: usize,
+    pending: usize,
+    capped: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Now {
+    epoch_ms: f64,
+    iso: String,
+    local: String,
+}
+
+/// `start_time` as a string the page's `Date.parse` reads the same way: an
+/// instant for RFC 3339 times and epoch milliseconds, local wall-clock time
+/// for dates and times without an offset (JS would read a bare date as UTC).
+fn normalize_start(start_time: &str) -> Result<String> {
+    let start_time = start_time.trim();
+    if let Ok(ms) = start_time.parse::<i64>() {
+        let instant = Utc
+            .timestamp_millis_opt(ms)
+            .single()
+            .with_context(|| format!("Start time out of range: {}", ms))?;
+        return Ok(instant.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+    }
+    if let Ok(instant) = DateTime::parse_from_rfc3339(start_time) {
+        return Ok(instant
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+    ] {
+        if let Ok(local) = NaiveDateTime::parse_from_str(start_time, format) {
+            return Ok(local.format("%Y-%m-%dT%H:%M:%S%.3f").to_string());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(start_time, "%Y-%m-%d") {
+        return Ok(format!("{}T00:00:00.000", date));
+    }
+    anyhow::bail!(
+        "Unrecognized start time '{}'; use ISO 8601 (2024-03-15T09:30:00, optionally with an offset) or epoch milliseconds",
+        start_time
+    )
+}
+
+async fn now(page: &Page, tick_mode: Option<ClockTickMode>) -> Result<FakeClockResult> {
+    let now: Now = page
+        .evaluate(NOW_SCRIPT)
+        .await
+        .context("Failed to read page clock")?
+        .into_value()
+        .context("Failed to parse page clock")?;
+    Ok(FakeClockResult {
+        success: true,
+        tick_mode,
+        epoch_ms: now.epoch_ms,
+        iso: now.iso,
+        local: now.local,
+    })
+}
+
+/// Run the page's clock from `start_time` (in `tick_mode`) on this and every
+/// later document, replacing `previous`; returns the new script to replace
+/// next time, and the time the page reads.
+pub async fn install(
+    page: &Page,
+    start_time: &str,
+    tick_mode: ClockTickMode,
+    previous: Option<ScriptIdentifier>,
+) -> Result<(ScriptIdentifier, FakeClockResult)> {
+    let start = normalize_start(start_time)?;
+    let anchor: Anchor = page
+        .evaluate(format!(
+            "{}({})",
+            ANCHOR_SCRIPT,
+            serde_json::to_string(&start)?
+        ))
+        .await
+        .context("Failed to read page clock")?
+        .into_value()
+        .context("Failed to parse page clock")?;
+    let start_ms = anchor
+        .start
+        .with_context(|| format!("The page can't read start time '{}'", start_time))?;
+
+    let config = serde_json::json!({
+        "start": start_ms,
+        "anchor": anchor.anchor,
+        "frozen": tick_mode == ClockTickMode::Frozen,
+    });
+    let script = format!("{}({})", CLOCK_SCRIPT, config);
+
+    if let Some(previous) = previous {
+        let _ = page
+            .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(previous))
+            .await;
+    }
+    let identifier = page
+        .execute(AddScriptToEvaluateOnNewDocumentParams::new(script.clone()))
+        .await
+        .context("Failed to install fake clock")?
+        .result
+        .identifier;
+    // The current document predates the script
+    page.evaluate(script)
+        .await
+        .context("Failed to install fake clock")?;
+
+    Ok((identifier, now(page, Some(tick_mode)).await?))
+}
+
+/// Move the page's fake clock `ms` ahead, firing the timers that fall due on
+/// the way (at most `max_timers`, so intervals can't spin forever). Returns
+/// the page time afterwards, which later documents should start from.
+pub async fn fast_forward(
+    page: &Page,
+    ms: u64,
+    max_timers: usize,
+    tick_mode: ClockTickMode,
+) -> Result<FastForwardResult> {
+    let skipped: Option<Skipped> = page
+        .evaluate(format!("{}({}, {})", FAST_FORWARD_SCRIPT, ms, max_timers))
+        .await
+        .context("Failed to fast-forward")?
+        .into_value()
+        .context("Failed to parse fast-forward result")?;
+    let skipped = skipped.context(
+        "The page has no fake clock; call set_fake_clock before the page sets its timers",
+    )?;
+    let clock = now(page, Some(tick_mode)).await?;
+
+    Ok(FastForwardResult {
+        success: !skipped.capped,
+        advanced_ms: ms,
+        timers_fired: skipped.fired,
+        timers_pending: skipped.pending,
+        clock,
+    })
+}
+
+/// Put the real clock back on this and later documents.
+pub async fn remove(page: &Page, installed: Option<ScriptIdentifier>) -> Result<FakeClockResult> {
+    if let Some(installed) = installed {
+        page.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(installed))
+            .await
+            .context("Failed to remove fake clock")?;
+    }
+    page.evaluate(RESTORE_SCRIPT)
+        .await
+        .context("Failed to remove fake clock")?;
+
+    now(page, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_start() {
+        assert_eq!(
+            normalize_start("2024-03-15T09:30:00+02:00").unwrap(),
+            "2024-03-15T07:30:00.000Z"
+        );
+        assert_eq!(
+            normalize_start("1710495000000").unwrap(),
+            "2024-03-15T09:30:00.000Z"
+        );
+        // No offset: local time in the page's timezone
+        assert_eq!(
+            normalize_start("2024-03-15T09:30").unwrap(),
+            "2024-03-15T09:30:00.000"
+        );
+        assert_eq!(
+            normalize_start(" 2024-03-15 ").unwrap(),
+            "2024-03-15T00:00:00.000"
+        );
+        assert!(normalize_start("next tuesday").is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! Content-Security-Policy trials.
+//!
+//! The page is (re)loaded with a policy added to its main document's
+//! response through `Fetch` interception, and the `securitypolicyviolation`
+//! events that policy raises are collected by an init script. By default the
+//! policy goes in as `Content-Security-Policy-Report-Only`, so the page loads
+//! as usual and every resource the policy would block is reported; enforcing
+//! replaces the page's own CSP headers instead, to see what breaks (resources
+//! that never load because of an earlier block aren't reported then).
+//! Policies the page sets in `<meta>` tags still apply either way.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    ContinueRequestParams, DisableParams, EnableParams, EventRequestPaused, FulfillRequestParams,
+    GetResponseBodyParams, HeaderEntry, RequestPattern, RequestStage,
+};
+use chromiumoxide::cdp::browser_protocol::network::ResourceType;
+use chromiumoxide::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, FrameId, RemoveScriptToEvaluateOnNewDocumentParams,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use serde::Deserialize;
+
+use super::{gate, navigation};
+use crate::models::{CspMode, CspTrialResult, CspViolation, WaitUntil};
+
+const ENFORCE_HEADER: &str = "Content-Security-Policy";
+const REPORT_ONLY_HEADER: &str = "Content-Security-Policy-Report-Only";
+
+/// Records violations of the trial policy (the argument) only, not of the
+/// page's own policies.
+const COLLECTOR_SCRIPT: &str = r#"((policy) => {
+    if (window.__fgpCsp) return;
+    const seen = [];
+    Object.defineProperty(window, '__fgpCsp', { value: seen });
+    document.addEventListener('securitypolicyviolation', (e) => {
+        if (e.originalPolicy.trim() !== policy) return;
+        seen.push({
+            directive: e.effectiveDirective || e.violatedDirective,
+            blocked_uri: e.blockedURI || '',
+            source_file: e.sourceFile || null,
+            line: e.lineNumber || null,
+            sample: e.sample || null,
+        });
+    }, true);
+})"#;
+
+const READ_SCRIPT: &str = "(() => window.__fgpCsp || [])()";
+
+#[derive(Debug, Deserialize)]
+struct Violation {
+    directive: String,
+    blocked_uri: String,
+    source_file: Option<String>,
+    line: Option<u64>,
+    sample: Option<String>,
+}
+
+/// What the interceptor did to the main document.
+#[derive(Debug, Default)]
+struct Injection {
+    applied: bool,
+    existing_policy: Option<String>,
+}
+
+/// `headers` with `policy` added in `mode`; also returns the page's own
+/// enforced policy, if any.
+fn with_policy(
+    headers: &[(String, String)],
+    policy: &str,
+    mode: CspMode,
+) -> (Vec<(String, String)>, Option<String>) {
+    let own: Vec<&str> = headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case(ENFORCE_HEADER))
+        .map(|(_, value)| value.as_str())
+        .collect();
+    let existing_policy = (!own.is_empty()).then(|| own.join(", "));
+
+    let (name, keep_own) = match mode {
+        CspMode::ReportOnly => (REPORT_ONLY_HEADER, true),
+        CspMode::Enforce => (ENFORCE_HEADER, false),
+    };
+    let mut headers: Vec<(String, String)> = headers
+        .iter()
+        .filter(|(n, _)| keep_own || !n.eq_ignore_ascii_case(ENFORCE_HEADER))
+        .cloned()
+        .collect();
+    headers.push((name.to_string(), policy.to_string()));
+
+    (headers, existing_policy)
+}
+
+/// Collapse repeats of the same blocked resource, keeping the first report.
+fn summarize(violations: Vec<Violation>) -> Vec<CspViolation> {
+    let mut summary: Vec<CspViolation> = Vec::new();
+    for v in violations {
+        if let Some(seen) = summary
+            .iter_mut()
+            .find(|s| s.directive == v.directive && s.blocked_uri == v.blocked_uri)
+        {
+            seen.count += 1;
+            continue;
+        }
+        summary.push(CspViolation {
+            directive: v.directive,
+            blocked_uri: v.blocked_uri,
+            count: 1,
+            source_file: v.source_file,
+            line: v.line,
+            sample: v.sample.filter(|s| !s.is_empty()),
+        });
+    }
+    summary
+}
+
+/// Answer one paused request: the main document gets the policy, anything
+/// else (redirects, iframes) goes through untouched.
+async fn handle_paused(
+    page: &Page,
+    event: &EventRequestPaused,
+    main_frame: &Option<FrameId>,
+    policy: &str,
+    mode: CspMode,
+    injection: &Mutex<Injection>,
+) -> Result<()> {
+    // Documents paused before their request are the robots.txt gate's
+    if gate::at_request(event) {
+        return Ok(());
+    }
+    let status = event.response_status_code.unwrap_or(0);
+    let is_main = main_frame.as_ref() == Some(&event.frame_id);
+    if !is_main || (300..400).contains(&status) || event.response_error_reason.is_some() {
+        page.execute(ContinueRequestParams::new(event.request_id.clone()))
+            .await?;
+        return Ok(());
+    }
+
+    let body = page
+        .execute(GetResponseBodyParams::new(event.request_id.clone()))
+        .await?
+        .result;
+    let body = if body.base64_encoded {
+        body.body
+    } else {
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, body.body)
+    };
+    let headers: Vec<(String, String)> = event
+        .response_headers
+        .iter()
+        .flatten()
+        .map(|h| (h.name.clone(), h.value.clone()))
+        .collect();
+    let (headers, existing_policy) = with_policy(&headers, policy, mode);
+
+    page.execute(
+        FulfillRequestParams::builder()
+            .request_id(event.request_id.clone())
+            .response_code(status)
+            .response_headers(
+                headers
+                    .into_iter()
+                    .map(|(name, value)| HeaderEntry::new(name, value))
+                    .collect::<Vec<_>>(),
+            )
+            .body(body)
+            .build()
+            .map_err(anyhow::Error::msg)?,
+    )
+    .await?;
+
+    let mut injection = injection.lock().unwrap_or_else(|e| e.into_inner());
+    injection.applied = true;
+    injection.existing_policy = existing_policy;
+    Ok(())
+}
+
+/// Load `url` with `policy` added in `mode` (failing with `TimedOut` if it
+/// doesn't load within `limit`), wait `settle` for late resources, and
+/// report what the policy blocks (or would block). A `guarded` page keeps
+/// its documents held to robots.txt (see `gate`) throughout.
+pub async fn trial(
+    page: &Page,
+    url: &str,
+    policy: &str,
+    mode: CspMode,
+    settle: Duration,
+    limit: Duration,
+    guarded: bool,
+) -> Result<CspTrialResult> {
+    let policy = policy.trim().to_string();
+    if policy.is_empty() {
+        anyhow::bail!("Policy is empty");
+    }
+    let main_frame = page
+        .mainframe()
+        .await
+        .context("Failed to find main frame")?;
+
+    let collector = page
+        .execute(AddScriptToEvaluateOnNewDocumentParams::new(format!(
+            "{}({})",
+            COLLECTOR_SCRIPT,
+            serde_json::to_string(&policy)?
+        )))
+        .await
+        .context("Failed to install violation collector")?
+        .result
+        .identifier;
+
+    let mut paused = page
+        .event_listener::<EventRequestPaused>()
+        .await
+        .context("Failed to listen for intercepted requests")?;
+    // Enabling replaces the patterns in force, so the gate's go along
+    let mut patterns = vec![RequestPattern::builder()
+        .url_pattern("*")
+        .resource_type(ResourceType::Document)
+        .request_stage(RequestStage::Response)
+        .build()];
+    patterns.extend(guarded.then(gate::pattern));
+    page.execute(EnableParams::builder().patterns(patterns).build())
+        .await
+        .context("Failed to intercept the document")?;
+
+    let injection = Arc::new(Mutex::new(Injection::default()));
+    let interceptor = {
+        let page = page.clone();
+        let policy = policy.clone();
+        let injection = injection.clone();
+        tokio::spawn(async move {
+            while let Some(event) = paused.next().await {
+                if let Err(e) =
+                    handle_paused(&page, &event, &main_frame, &policy, mode, &injection).await
+                {
+                    tracing::debug!("CSP trial could not answer intercepted request: {}", e);
+                }
+            }
+        })
+    };
+
+    let loaded = async {
+        navigation::navigate(page, url, WaitUntil::Load, limit).await?;
+        tokio::time::sleep(settle).await;
+        let violations: Vec<Violation> = page
+            .evaluate(READ_SCRIPT)
+            .await
+            .context("Failed to read violations")?
+            .into_value()
+            .context("Failed to parse violations")?;
+        let url = page.url().await.ok().flatten().unwrap_or_default();
+        Ok::<_, anyhow::Error>((url, violations))
+    }
+    .await;
+
+    interceptor.abort();
+    if guarded {
+        let _ = page
+            .execute(EnableParams::builder().pattern(gate::pattern()).build())
+            .await;
+    } else {
+        let _ = page.execute(DisableParams::default()).await;
+    }
+    let _ = page
+        .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(collector))
+        .await;
+
+    let (url, violations) = loaded?;
+    let violations = summarize(violations);
+    let injection = injection.lock().unwrap_or_else(|e| e.into_inner());
+
+    Ok(CspTrialResult {
+        success: injection.applied,
+        url,
+        policy,
+        mode,
+        existing_policy: injection.existing_policy.clone(),
+        blocked: violations.len(),
+        violations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_with_policy() {
+        let headers = vec![
+            header("content-type", "text/html"),
+            header("content-security-policy", "frame-ancestors 'none'"),
+        ];
+
+        let (report_only, existing) =
+            with_policy(&headers, "script-src 'self'", CspMode::ReportOnly);
+        assert_eq!(existing.as_deref(), Some("frame-ancestors 'none'"));
+        assert_eq!(report_only.len(), 3);
+        assert_eq!(
+            report_only[2],
+            header(REPORT_ONLY_HEADER, "script-src 'self'")
+        );
+
+        let (enforced, _) = with_policy(&headers, "script-src 'self'", CspMode::Enforce);
+        assert_eq!(
+            enforced,
+            vec![
+                header("content-type", "text/html"),
+                header(ENFORCE_HEADER, "script-src 'self'"),
+            ]
+        );
+
+        let (_, existing) = with_policy(&headers[..1], "default-src 'self'", CspMode::Enforce);
+        assert!(existing.is_none());
+    }
+
+    #[test]
+    fn test_summarize_violations() {
+        let violation = |directive: &str, uri: &str| Violation {
+            directive: directive.to_string(),
+            blocked_uri: uri.to_string(),
+            source_file: None,
+            line: None,
+            sample: Some(String::new()),
+        };
+        let summary = summarize(vec![
+            violation("script-src-elem", "https://cdn.example.net/a.js"),
+            violation("img-src", "https://pixel.example.org/p.gif"),
+            violation("script-src-elem", "https://cdn.example.net/a.js"),
+        ]);
+
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].count, 2);
+        assert_eq!(summary[1].directive, "img-src");
+        assert!(summary[1].sample.is_none());
+    }
+}
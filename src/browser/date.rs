@@ -0,0 +1,435 @@
+//! Date field entry.
+//!
+//! Native `<input type=date|datetime-local|month|week>` take their value
+//! directly (with the events frameworks listen for). JS date pickers are
+//! tried by typing into their text field, in the format its placeholder or
+//! `data-date-format` suggests (ISO if neither does), and otherwise through
+//! their calendar popup: the day is found by `data-date`/`data-value`
+//! attributes, an accessible label ("March 15th, 2024"), or its number once
+//! the popup shows the right month, paging with the next/previous buttons or
+//! month/year dropdowns to get there.
+//!
+//! When `selector` matches a wrapper rather than an input, the first input
+//! inside it is used.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Deserialize;
+
+/// Time for a widget to react to a keystroke or click before it's read.
+pub const SETTLE: Duration = Duration::from_millis(150);
+
+/// Calendar pages turned before giving up (ten years of months).
+pub const MAX_CALENDAR_STEPS: usize = 120;
+
+/// Finds the input behind a selector; prefixed to every script here.
+const FIELD_JS: &str = r#"const field = (sel) => {
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    return el.matches('input, textarea') ? el : el.querySelector('input');
+};
+"#;
+
+const PROBE_SCRIPT: &str = r#"((sel) => {
+    const el = field(sel);
+    if (!el) return { found: false };
+    return {
+        found: true,
+        input_type: (el.getAttribute('type') || 'text').toLowerCase(),
+        read_only: el.readOnly,
+        disabled: el.disabled,
+        value: el.value,
+        hints: [el.getAttribute('placeholder'), el.getAttribute('data-date-format'),
+            el.getAttribute('data-format'), el.getAttribute('aria-placeholder')].filter((h) => h),
+    };
+})"#;
+
+/// Sets the value the way a user edit would, so React and friends notice.
+const SET_VALUE_SCRIPT: &str = r#"((sel, value) => {
+    const el = field(sel);
+    const setter = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'value').set;
+    setter.call(el, value);
+    el.dispatchEvent(new Event('input', { bubbles: true }));
+    el.dispatchEvent(new Event('change', { bubbles: true }));
+    return el.value;
+})"#;
+
+const FOCUS_SCRIPT: &str = r#"((sel) => {
+    const el = field(sel);
+    el.focus();
+    el.select();
+    return true;
+})"#;
+
+const COMMIT_SCRIPT: &str = r#"((sel) => {
+    const el = field(sel);
+    el.dispatchEvent(new Event('change', { bubbles: true }));
+    el.blur();
+    return true;
+})"#;
+
+const VALUE_SCRIPT: &str = r#"((sel) => field(sel).value)"#;
+
+/// Attribute marking the element `calendar_step` wants clicked.
+pub const PICK_MARK: &str = "data-fgp-date-pick";
+
+/// One step towards the target day in the open calendar popup: mark the day
+/// to click, or the control that pages towards it.
+const CALENDAR_STEP_SCRIPT: &str = r#"((year, month, day) => {
+    const MARK = 'data-fgp-date-pick';
+    document.querySelectorAll('[' + MARK + ']').forEach((el) => el.removeAttribute(MARK));
+    const shown = (el) => {
+        const r = el.getBoundingClientRect();
+        return r.width > 0 && r.height > 0 && el.checkVisibility({ visibilityProperty: true });
+    };
+    // Known widgets first; a dialog may just be the modal holding the field
+    const lastShown = (q) => [...document.querySelectorAll(q)].filter(shown).pop();
+    const popup = lastShown([
+        '.flatpickr-calendar.open', '.react-datepicker', '.datepicker-dropdown', '.ui-datepicker',
+        '.MuiPickersPopper-root', '.mat-datepicker-content', '.air-datepicker', '.pika-single',
+        '.daterangepicker',
+    ].join(', ')) || lastShown('[role=dialog]');
+    if (!popup) return { status: 'no_popup' };
+
+    const names = ['january', 'february', 'march', 'april', 'may', 'june', 'july',
+        'august', 'september', 'october', 'november', 'december'];
+    const pad = (n) => String(n).padStart(2, '0');
+    const iso = year + '-' + pad(month) + '-' + pad(day);
+    const disabled = (el) => el.matches('[disabled], [aria-disabled=true]') ||
+        /\b(disabled|is-disabled|flatpickr-disabled)\b/.test(el.className);
+    const pick = (el) => { el.setAttribute(MARK, ''); return { status: 'pick' }; };
+
+    const cells = [...popup.querySelectorAll(
+        'td, button, a, span, div, [role=gridcell], [role=option]')].filter(shown);
+    const byAttr = cells.find((el) => ['data-date', 'data-value', 'data-day', 'datetime']
+        .some((a) => (el.getAttribute(a) || '').startsWith(iso)));
+    if (byAttr) return disabled(byAttr) ? { status: 'disabled' } : pick(byAttr);
+
+    const dayRe = new RegExp('(^|\\D)0?' + day + '(st|nd|rd|th)?(\\D|$)');
+    const byLabel = cells.find((el) => {
+        const label = (el.getAttribute('aria-label') || el.getAttribute('title') || '').toLowerCase();
+        return label.includes(names[month - 1]) && label.includes(String(year)) &&
+            dayRe.test(label.replace(String(year), ''));
+    });
+    if (byLabel) return disabled(byLabel) ? { status: 'disabled' } : pick(byLabel);
+
+    // Which month is on screen: dropdowns, else the header text
+    const selects = [...popup.querySelectorAll('select')];
+    const monthSelect = selects.find((s) => [...s.options].some(
+        (o) => names.includes(o.text.trim().toLowerCase())));
+    const yearSelect = selects.find((s) => s !== monthSelect &&
+        [...s.options].some((o) => /^\d{4}$/.test(o.text.trim())));
+    const text = (popup.textContent || '').toLowerCase();
+    const shortNames = names.map((n) => n.slice(0, 3));
+    let shownMonth = -1;
+    if (monthSelect) {
+        shownMonth = names.indexOf(monthSelect.options[monthSelect.selectedIndex].text.trim().toLowerCase());
+    } else {
+        const found = text.match(new RegExp('\\b(' + names.join('|') + '|' + shortNames.join('|') + ')\\b'));
+        if (found) shownMonth = shortNames.indexOf(found[1].slice(0, 3));
+    }
+    let shownYear = NaN;
+    if (yearSelect) {
+        shownYear = parseInt(yearSelect.value, 10);
+    } else {
+        const found = text.match(/\b(1[89]|2[01])\d\d\b/);
+        const input = popup.querySelector('input.cur-year, input[type=number]');
+        shownYear = found ? parseInt(found[0], 10) : input ? parseInt(input.value, 10) : NaN;
+    }
+    if (shownMonth < 0 || isNaN(shownYear)) return { status: 'unknown_month' };
+
+    const diff = (year * 12 + month - 1) - (shownYear * 12 + shownMonth);
+    if (diff === 0) {
+        const byText = cells.find((el) => el.children.length === 0 &&
+            el.textContent.trim() === String(day) && !disabled(el) &&
+            !/\b(outside|other-month|prevMonthDay|nextMonthDay|old|new)\b/.test(
+                el.className + ' ' + (el.parentElement ? el.parentElement.className : '')));
+        return byText ? pick(byText) : { status: 'disabled' };
+    }
+
+    if (monthSelect && yearSelect && [...yearSelect.options].some((o) => o.value == year)) {
+        yearSelect.value = String(year);
+        yearSelect.dispatchEvent(new Event('change', { bubbles: true }));
+        monthSelect.selectedIndex = [...monthSelect.options].findIndex(
+            (o) => o.text.trim().toLowerCase() === names[month - 1]);
+        monthSelect.dispatchEvent(new Event('change', { bubbles: true }));
+        return { status: 'paged' };
+    }
+
+    // Labelled controls first; class names also match days like `prevMonthDay`
+    const word = diff > 0 ? 'next' : 'prev';
+    const controls = (q) => [...popup.querySelectorAll(q)].filter(
+        (el) => shown(el) && !disabled(el) && !/day/i.test(el.className));
+    const nav = controls('[aria-label*=' + word + ' i], [title*=' + word + ' i]')[0] ||
+        controls('[class*=' + word + ' i]')[0];
+    if (!nav) return { status: 'no_nav' };
+    nav.setAttribute(MARK, '');
+    return { status: 'page', months: Math.abs(diff) };
+})"#;
+
+/// A date field as the page reports it.
+#[derive(Debug, Deserialize)]
+pub struct DateField {
+    pub found: bool,
+    #[serde(default)]
+    pub input_type: String,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub value: String,
+    /// Placeholder and format attributes, in that order
+    #[serde(default)]
+    pub hints: Vec<String>,
+}
+
+/// Outcome of one calendar step.
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CalendarStep {
+    /// The day is marked with `PICK_MARK`
+    Pick,
+    /// The next/previous control is marked; the target is `months` away
+    Page {
+        months: u32,
+    },
+    /// Dropdowns were switched to the target month
+    Paged,
+    NoPopup,
+    UnknownMonth,
+    NoNav,
+    /// The day is on screen but can't be picked
+    Disabled,
+}
+
+/// Parse `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM[:SS]`.
+pub fn parse_date(input: &str) -> Result<(NaiveDate, Option<NaiveTime>)> {
+    let input = input.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok((date, None));
+    }
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(input, f).ok())
+        .map(|dt| (dt.date(), Some(dt.time())))
+        .with_context(|| format!("Invalid date '{}' (expected YYYY-MM-DD)", input))
+}
+
+/// Value for a native date/time input, or None if `input_type` isn't one.
+///
+/// A `datetime-local` keeps its current time unless one is given.
+pub fn native_value(
+    input_type: &str,
+    date: NaiveDate,
+    time: Option<NaiveTime>,
+    current: &str,
+) -> Option<String> {
+    match input_type {
+        "date" => Some(date.format("%Y-%m-%d").to_string()),
+        "month" => Some(date.format("%Y-%m").to_string()),
+        "week" => {
+            let week = date.iso_week();
+            Some(format!("{}-W{:02}", week.year(), week.week()))
+        }
+        "datetime-local" => {
+            let time = match time {
+                Some(time) => time.format("%H:%M").to_string(),
+                None => current
+                    .split_once('T')
+                    .map_or("00:00".to_string(), |(_, t)| t.to_string()),
+            };
+            Some(format!("{}T{}", date.format("%Y-%m-%d"), time))
+        }
+        _ => None,
+    }
+}
+
+/// strftime format for a date pattern like `MM/DD/YYYY` or `dd.mm.yy`, or
+/// None if `pattern` isn't one (e.g. "Pick a date").
+fn pattern_format(pattern: &str) -> Option<String> {
+    let chars: Vec<char> = pattern.trim().chars().collect();
+    let mut format = String::new();
+    let mut seen = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].to_ascii_lowercase();
+        let run = chars[i..]
+            .iter()
+            .take_while(|x| x.to_ascii_lowercase() == c)
+            .count();
+        let spec = match (c, run) {
+            ('y', 4) => "%Y",
+            ('y', 2) => "%y",
+            ('m', 1) => "%-m",
+            ('m', 2) => "%m",
+            ('m', 3) => "%b",
+            ('m', 4) => "%B",
+            ('d', 1) => "%-d",
+            ('d', 2) => "%d",
+            _ if chars[i].is_alphanumeric() => return None,
+            _ => "",
+        };
+        if spec.is_empty() {
+            format.push_str(&chars[i..i + run].iter().collect::<String>());
+        } else if seen.contains(c) {
+            return None;
+        } else {
+            seen.push(c);
+            format.push_str(spec);
+        }
+        i += run;
+    }
+    (seen.len() == 3).then_some(format)
+}
+
+/// Format to type the date in: `explicit` (strftime, or a pattern like
+/// `DD/MM/YYYY`), else the first hint that reads as a pattern, else ISO.
+pub fn typing_format(explicit: Option<&str>, hints: &[String]) -> Result<String> {
+    if let Some(format) = explicit {
+        if format.contains('%') {
+            return Ok(format.to_string());
+        }
+        return pattern_format(format)
+            .with_context(|| format!("Unrecognized date format '{}'", format));
+    }
+    Ok(hints
+        .iter()
+        .find_map(|h| pattern_format(h))
+        .unwrap_or_else(|| "%Y-%m-%d".to_string()))
+}
+
+/// Whether a typed field ended up holding `date` in `format`.
+pub fn typed_matches(value: &str, format: &str, date: NaiveDate) -> bool {
+    let value = value.trim();
+    !value.is_empty()
+        && (NaiveDate::parse_from_str(value, format).ok() == Some(date)
+            || NaiveDateTime::parse_from_str(value, format)
+                .ok()
+                .map(|d| d.date())
+                == Some(date))
+}
+
+fn script(body: &str, args: &[String]) -> String {
+    format!(
+        "(() => {{ {}return {}({}); }})()",
+        FIELD_JS,
+        body,
+        args.join(", ")
+    )
+}
+
+async fn run<T: serde::de::DeserializeOwned>(page: &Page, script: String, what: &str) -> Result<T> {
+    page.evaluate(script)
+        .await
+        .with_context(|| format!("Failed to {}", what))?
+        .into_value()
+        .with_context(|| format!("Failed to parse result of {}", what))
+}
+
+pub async fn probe(page: &Page, sel: &str) -> Result<DateField> {
+    let args = [serde_json::to_string(sel)?];
+    run(page, script(PROBE_SCRIPT, &args), "inspect date field").await
+}
+
+/// Set a native input's value; returns the value it holds afterwards.
+pub async fn set_value(page: &Page, sel: &str, value: &str) -> Result<String> {
+    let args = [serde_json::to_string(sel)?, serde_json::to_string(value)?];
+    run(page, script(SET_VALUE_SCRIPT, &args), "set date value").await
+}
+
+/// Focus the field with its text selected, so typing replaces it.
+pub async fn focus(page: &Page, sel: &str) -> Result<()> {
+    let args = [serde_json::to_string(sel)?];
+    run::<bool>(page, script(FOCUS_SCRIPT, &args), "focus date field").await?;
+    Ok(())
+}
+
+/// Fire `change` and blur the field, as leaving it would.
+pub async fn commit(page: &Page, sel: &str) -> Result<()> {
+    let args = [serde_json::to_string(sel)?];
+    run::<bool>(page, script(COMMIT_SCRIPT, &args), "commit date field").await?;
+    Ok(())
+}
+
+pub async fn value(page: &Page, sel: &str) -> Result<String> {
+    let args = [serde_json::to_string(sel)?];
+    run(page, script(VALUE_SCRIPT, &args), "read date field").await
+}
+
+/// Look at the open calendar popup and mark the next thing to click.
+pub async fn calendar_step(page: &Page, date: NaiveDate) -> Result<CalendarStep> {
+    let args = [
+        date.year().to_string(),
+        date.month().to_string(),
+        date.day().to_string(),
+    ];
+    run(page, script(CALENDAR_STEP_SCRIPT, &args), "read calendar").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn march_15() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2024-03-15").unwrap(), (march_15(), None));
+        assert_eq!(
+            parse_date("2024-03-15T09:30").unwrap(),
+            (march_15(), NaiveTime::from_hms_opt(9, 30, 0))
+        );
+        assert!(parse_date("15/03/2024").is_err());
+    }
+
+    #[test]
+    fn test_native_value() {
+        assert_eq!(
+            native_value("date", march_15(), None, "").as_deref(),
+            Some("2024-03-15")
+        );
+        assert_eq!(
+            native_value("week", march_15(), None, "").as_deref(),
+            Some("2024-W11")
+        );
+        assert_eq!(
+            native_value("datetime-local", march_15(), None, "2020-01-01T08:15").as_deref(),
+            Some("2024-03-15T08:15")
+        );
+        assert_eq!(native_value("text", march_15(), None, ""), None);
+    }
+
+    #[test]
+    fn test_typing_format_from_hints() {
+        let hints = |h: &[&str]| h.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            typing_format(None, &hints(&["MM/DD/YYYY"])).unwrap(),
+            "%m/%d/%Y"
+        );
+        assert_eq!(
+            typing_format(None, &hints(&["Pick a date", "d.m.yy"])).unwrap(),
+            "%-d.%-m.%y"
+        );
+        assert_eq!(
+            typing_format(None, &hints(&["Select"])).unwrap(),
+            "%Y-%m-%d"
+        );
+        assert_eq!(typing_format(Some("DD MMM YYYY"), &[]).unwrap(), "%d %b %Y");
+        assert_eq!(typing_format(Some("%d/%m/%Y"), &[]).unwrap(), "%d/%m/%Y");
+        assert!(typing_format(Some("MM/MM/YYYY"), &[]).is_err());
+    }
+
+    #[test]
+    fn test_typed_matches() {
+        assert!(typed_matches("03/15/2024", "%m/%d/%Y", march_15()));
+        assert!(!typed_matches("03/16/2024", "%m/%d/%Y", march_15()));
+        assert!(!typed_matches("", "%m/%d/%Y", march_15()));
+    }
+}
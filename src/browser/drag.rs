@@ -0,0 +1,223 @@
+//! Drag and drop between two elements.
+//!
+//! The pointer is pressed at the source's center, moved to the target's
+//! center in even steps, and released, all as CDP mouse events, which is
+//! what pointer-driven libraries (dnd-kit, react-beautiful-dnd, SortableJS
+//! fallback mode) listen for. Native HTML5 drag and drop (`draggable`)
+//! never sees synthetic mouse moves as a drag, so drags Chrome starts are
+//! intercepted and finished with CDP drag events (enter, over, drop)
+//! instead.
+//!
+//! Both elements have to fit in the viewport together; the source is
+//! scrolled to the center and the target into view.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchDragEventParams, DispatchDragEventType, DispatchMouseEventParams,
+    DispatchMouseEventType, DragData, EventDragIntercepted, MouseButton, SetInterceptDragsParams,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+
+/// Pointer moves between source and target when not given.
+pub const DEFAULT_DRAG_STEPS: u32 = 10;
+
+/// Most pointer moves a drag may take.
+pub const MAX_DRAG_STEPS: u32 = 100;
+
+/// Pause between pointer moves, about a frame, so drag libraries see
+/// separate events.
+const STEP_DELAY: Duration = Duration::from_millis(16);
+
+/// How long to wait after the first move for Chrome to start a native drag.
+const NATIVE_DRAG_WAIT: Duration = Duration::from_millis(100);
+
+const LOCATE_SCRIPT: &str = r#"((src, dst) => {
+    const a = document.querySelector(src);
+    if (!a) return { missing: 'source' };
+    const b = document.querySelector(dst);
+    if (!b) return { missing: 'target' };
+    const inView = (r) => r.top >= 0 && r.left >= 0 && r.bottom <= innerHeight && r.right <= innerWidth;
+    a.scrollIntoView({ block: 'center', inline: 'center' });
+    if (!inView(b.getBoundingClientRect())) b.scrollIntoView({ block: 'nearest', inline: 'nearest' });
+    const ra = a.getBoundingClientRect();
+    const rb = b.getBoundingClientRect();
+    const center = (r) => ({ x: r.left + r.width / 2, y: r.top + r.height / 2 });
+    return { from: center(ra), to: center(rb), together: inView(ra) && inView(rb) };
+})"#;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Located {
+    #[serde(default)]
+    missing: Option<String>,
+    #[serde(default)]
+    from: Option<Point>,
+    #[serde(default)]
+    to: Option<Point>,
+    #[serde(default)]
+    together: bool,
+}
+
+/// Pointer positions after the press, ending on `to`.
+fn path(from: Point, to: Point, steps: u32) -> Vec<Point> {
+    let steps = steps.max(1);
+    (1..=steps)
+        .map(|i| {
+            let t = i as f64 / steps as f64;
+            Point {
+                x: from.x + (to.x - from.x) * t,
+                y: from.y + (to.y - from.y) * t,
+            }
+        })
+        .collect()
+}
+
+async fn mouse(page: &Page, kind: DispatchMouseEventType, at: Point, buttons: i64) -> Result<()> {
+    let mut event = DispatchMouseEventParams::builder()
+        .r#type(kind.clone())
+        .x(at.x)
+        .y(at.y)
+        .buttons(buttons);
+    if kind != DispatchMouseEventType::MouseMoved {
+        event = event.button(MouseButton::Left).click_count(1);
+    }
+    page.execute(event.build().map_err(anyhow::Error::msg)?)
+        .await
+        .context("Failed to dispatch mouse event")?;
+    Ok(())
+}
+
+async fn drag_event(
+    page: &Page,
+    kind: DispatchDragEventType,
+    at: Point,
+    data: &DragData,
+) -> Result<()> {
+    page.execute(DispatchDragEventParams::new(kind, at.x, at.y, data.clone()))
+        .await
+        .context("Failed to dispatch drag event")?;
+    Ok(())
+}
+
+/// Press, move, and release; returns whether it became a native drag.
+async fn perform(page: &Page, from: Point, to: Point, steps: u32) -> Result<bool> {
+    let mut intercepted = page
+        .event_listener::<EventDragIntercepted>()
+        .await
+        .context("Failed to listen for native drags")?;
+    let mut moves = path(from, to, steps).into_iter();
+
+    mouse(page, DispatchMouseEventType::MouseMoved, from, 0).await?;
+    mouse(page, DispatchMouseEventType::MousePressed, from, 1).await?;
+    if let Some(first) = moves.next() {
+        mouse(page, DispatchMouseEventType::MouseMoved, first, 1).await?;
+    }
+
+    let native = match tokio::time::timeout(NATIVE_DRAG_WAIT, intercepted.next()).await {
+        Ok(Some(event)) => Some(event.data.clone()),
+        _ => None,
+    };
+
+    match &native {
+        Some(data) => {
+            let mut kind = DispatchDragEventType::DragEnter;
+            for at in moves {
+                tokio::time::sleep(STEP_DELAY).await;
+                drag_event(page, kind, at, data).await?;
+                kind = DispatchDragEventType::DragOver;
+            }
+            // Enters the target if the drag took a single step
+            drag_event(page, kind, to, data).await?;
+            drag_event(page, DispatchDragEventType::Drop, to, data).await?;
+        }
+        None => {
+            for at in moves {
+                tokio::time::sleep(STEP_DELAY).await;
+                mouse(page, DispatchMouseEventType::MouseMoved, at, 1).await?;
+            }
+        }
+    }
+    mouse(page, DispatchMouseEventType::MouseReleased, to, 0).await?;
+
+    Ok(native.is_some())
+}
+
+/// Drag `source` onto `target` in `steps` pointer moves; returns whether
+/// native HTML5 drag and drop was used, and how long it took.
+pub async fn drag_and_drop(
+    page: &Page,
+    source: &str,
+    target: &str,
+    steps: u32,
+) -> Result<(bool, Duration)> {
+    let call = format!(
+        "{}({}, {})",
+        LOCATE_SCRIPT,
+        serde_json::to_string(&resolve_selector(source))?,
+        serde_json::to_string(&resolve_selector(target))?
+    );
+    let located: Located = page
+        .evaluate(call)
+        .await
+        .context("Failed to find drag elements")?
+        .into_value()
+        .context("Failed to parse drag elements")?;
+    match located.missing.as_deref() {
+        Some("source") => anyhow::bail!("Element not found: {}", source),
+        Some(_) => anyhow::bail!("Element not found: {}", target),
+        None => {}
+    }
+    if !located.together {
+        anyhow::bail!(
+            "{} and {} don't fit in the viewport together; resize the viewport or scroll first",
+            source,
+            target
+        );
+    }
+    let from = located.from.context("Drag source has no layout")?;
+    let to = located.to.context("Drag target has no layout")?;
+
+    page.execute(SetInterceptDragsParams::new(true))
+        .await
+        .context("Failed to intercept native drags")?;
+    let started = Instant::now();
+    let performed = perform(page, from, to, steps).await;
+    let _ = page.execute(SetInterceptDragsParams::new(false)).await;
+
+    Ok((performed?, started.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_ends_on_target() {
+        let from = Point { x: 100.0, y: 50.0 };
+        let to = Point { x: 300.0, y: 150.0 };
+
+        let points: Vec<_> = path(from, to, 4).iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(
+            points,
+            vec![
+                (150.0, 75.0),
+                (200.0, 100.0),
+                (250.0, 125.0),
+                (300.0, 150.0)
+            ]
+        );
+        let points: Vec<_> = path(from, to, 0).iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(points, vec![(300.0, 150.0)]);
+    }
+}
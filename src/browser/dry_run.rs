@@ -0,0 +1,306 @@
+//! Dry runs of interaction commands.
+//!
+//! A dry run resolves the target the real command would use, checks that it
+//! could act on it (visible, enabled, not covered, right kind of element)
+//! and predicts the outcome (navigation, state change) without dispatching
+//! any event. Agents can plan with it and recorded scripts can be validated
+//! against a live page.
+//!
+//! Predictions only cover the default behaviour: a click on a link is
+//! reported as navigating even if a script would call `preventDefault()`.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{DryRunAction, DryRunReport, DryRunTarget, NavigationIntent};
+
+const PROBE_SCRIPT: &str = r#"((sel, action, value, checked) => {
+    const all = document.querySelectorAll(sel);
+    const el = all[0];
+    if (!el) return { matches: 0 };
+
+    const describe = (node) => {
+        let text = node.tagName.toLowerCase();
+        if (node.id) text += '#' + node.id;
+        else if (typeof node.className === 'string' && node.className.trim())
+            text += '.' + node.className.trim().split(/\s+/)[0];
+        return text;
+    };
+
+    const r = el.getBoundingClientRect();
+    const visible = r.width > 0 && r.height > 0 &&
+        el.checkVisibility({ visibilityProperty: true });
+    const enabled = !el.matches(':disabled') &&
+        !el.closest('[aria-disabled="true"]') && !el.closest('[inert]');
+    const in_viewport = r.bottom > 0 && r.right > 0 && r.top < innerHeight && r.left < innerWidth;
+
+    let obscured_by = null;
+    if (visible && in_viewport) {
+        const x = Math.min(Math.max(r.left + r.width / 2, 0), innerWidth - 1);
+        const y = Math.min(Math.max(r.top + r.height / 2, 0), innerHeight - 1);
+        const hit = document.elementFromPoint(x, y);
+        if (hit && hit !== el && !el.contains(hit) && !(hit.control && hit.control === el)) {
+            obscured_by = describe(hit);
+        }
+    }
+
+    let would_navigate = null;
+    if (action === 'click') {
+        const link = el.closest('a[href], area[href]');
+        const button = el.closest('button, input[type=submit], input[type=image]');
+        if (link && !link.href.startsWith('javascript:') && !link.hasAttribute('download')) {
+            const url = new URL(link.href);
+            const samePage = url.origin === location.origin && url.pathname === location.pathname &&
+                url.search === location.search && url.hash !== '';
+            if (!samePage) {
+                const target = link.target;
+                would_navigate = { url: link.href, method: 'GET', new_tab: !!target && target !== '_self' };
+            }
+        } else if (button && button.form && (button.tagName === 'INPUT' || button.type === 'submit')) {
+            const form = button.form;
+            const method = (button.getAttribute('formmethod') || form.method || 'get').toUpperCase();
+            if (method !== 'DIALOG') {
+                const target = button.getAttribute('formtarget') || form.target;
+                would_navigate = {
+                    url: button.hasAttribute('formaction') ? button.formAction : form.action,
+                    method,
+                    new_tab: !!target && target !== '_self',
+                };
+            }
+        }
+    }
+
+    const tag = el.tagName.toLowerCase();
+    const inputType = tag === 'input' ? el.type : null;
+    const textInputs = ['checkbox', 'radio', 'button', 'submit', 'reset', 'file', 'image',
+        'range', 'color', 'hidden'];
+    let kind_ok = true;
+    let option_found = null;
+    let would_change = null;
+    if (action === 'fill') {
+        kind_ok = el.isContentEditable || ((tag === 'textarea' ||
+            (tag === 'input' && !textInputs.includes(inputType))) && !el.readOnly);
+    } else if (action === 'select') {
+        kind_ok = tag === 'select';
+        if (kind_ok) {
+            option_found = [...el.options].some((o) => o.value === value);
+            would_change = option_found && el.value !== value;
+        }
+    } else if (action === 'check') {
+        kind_ok = inputType === 'checkbox' || inputType === 'radio';
+        if (kind_ok) would_change = el.checked !== checked;
+    } else if (action === 'upload') {
+        kind_ok = inputType === 'file';
+    }
+
+    const label = (el.getAttribute('aria-label') || el.innerText || el.value ||
+        el.getAttribute('placeholder') || '').trim().slice(0, 80);
+
+    return {
+        matches: all.length,
+        target: {
+            tag,
+            role: el.getAttribute('role') || inputType,
+            label: label || null,
+            rect: { x: r.left, y: r.top, width: r.width, height: r.height },
+        },
+        visible, enabled, in_viewport, obscured_by, would_navigate,
+        kind_ok, option_found, would_change,
+    };
+})"#;
+
+/// What the page reports about the target.
+#[derive(Debug, Default, Deserialize)]
+struct Probe {
+    matches: usize,
+    #[serde(default)]
+    target: Option<DryRunTarget>,
+    #[serde(default)]
+    visible: bool,
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    in_viewport: bool,
+    #[serde(default)]
+    obscured_by: Option<String>,
+    #[serde(default)]
+    would_navigate: Option<NavigationIntent>,
+    #[serde(default)]
+    kind_ok: bool,
+    #[serde(default)]
+    option_found: Option<bool>,
+    #[serde(default)]
+    would_change: Option<bool>,
+}
+
+/// Why `action` would fail or misbehave on the probed element.
+fn problems(action: DryRunAction, probe: &Probe, value: Option<&str>) -> Vec<String> {
+    if probe.matches == 0 {
+        return vec!["No element matches the selector".to_string()];
+    }
+
+    let mut problems = Vec::new();
+    // select/check/upload set state through the DOM and work on hidden inputs
+    if action.uses_pointer() {
+        if !probe.visible {
+            problems.push("Element is not visible".to_string());
+        }
+        if let Some(cover) = &probe.obscured_by {
+            problems.push(format!("Element is covered by {}", cover));
+        }
+    }
+    if action != DryRunAction::Hover && !probe.enabled {
+        problems.push("Element is disabled".to_string());
+    }
+    let wrong_kind = match action {
+        DryRunAction::Fill => Some("Element is not editable"),
+        DryRunAction::Select => Some("Element is not a <select>"),
+        DryRunAction::Check => Some("Element is not a checkbox or radio button"),
+        DryRunAction::Upload => Some("Element is not a file input"),
+        DryRunAction::Click | DryRunAction::Hover => None,
+    };
+    if let (false, Some(problem)) = (probe.kind_ok, wrong_kind) {
+        problems.push(problem.to_string());
+    }
+    if probe.option_found == Some(false) {
+        problems.push(format!(
+            "No option with value '{}'",
+            value.unwrap_or_default()
+        ));
+    }
+    problems
+}
+
+/// Rehearse `action` on `selector`.
+///
+/// `value` is the fill text, option value, or upload path; `checked` the
+/// desired state for check.
+pub async fn rehearse(
+    page: &Page,
+    action: DryRunAction,
+    selector: &str,
+    value: Option<&str>,
+    checked: bool,
+) -> Result<DryRunReport> {
+    let action_name = serde_json::to_value(action)?;
+    let script = format!(
+        "{}({}, {}, {}, {})",
+        PROBE_SCRIPT,
+        serde_json::to_string(&resolve_selector(selector))?,
+        action_name,
+        serde_json::to_string(&value)?,
+        checked
+    );
+
+    let probe: Probe = page
+        .evaluate(script)
+        .await
+        .context("Failed to probe dry-run target")?
+        .into_value()
+        .context("Failed to parse dry-run probe")?;
+
+    let problems = problems(action, &probe, value);
+    Ok(DryRunReport {
+        dry_run: true,
+        action,
+        selector: selector.to_string(),
+        matches: probe.matches,
+        target: probe.target,
+        visible: probe.visible,
+        enabled: probe.enabled,
+        in_viewport: probe.in_viewport,
+        obscured_by: probe.obscured_by,
+        would_navigate: probe.would_navigate,
+        would_change: probe.would_change,
+        actionable: problems.is_empty(),
+        problems,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ElementRect;
+
+    fn actionable() -> Probe {
+        Probe {
+            matches: 1,
+            target: Some(DryRunTarget {
+                tag: "button".to_string(),
+                role: None,
+                label: Some("Save".to_string()),
+                rect: ElementRect {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 80.0,
+                    height: 30.0,
+                },
+            }),
+            visible: true,
+            enabled: true,
+            in_viewport: true,
+            kind_ok: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_missing_element() {
+        let probe = Probe::default();
+        assert_eq!(
+            problems(DryRunAction::Click, &probe, None),
+            vec!["No element matches the selector"]
+        );
+    }
+
+    #[test]
+    fn test_pointer_checks_only_apply_to_pointer_actions() {
+        let probe = Probe {
+            visible: false,
+            obscured_by: Some("div#cookie-banner".to_string()),
+            ..actionable()
+        };
+
+        assert_eq!(
+            problems(DryRunAction::Click, &probe, None),
+            vec![
+                "Element is not visible",
+                "Element is covered by div#cookie-banner"
+            ]
+        );
+        // Hidden custom-styled checkboxes are still set through the DOM
+        assert!(problems(DryRunAction::Check, &probe, None).is_empty());
+    }
+
+    #[test]
+    fn test_disabled_and_wrong_kind() {
+        let probe = Probe {
+            enabled: false,
+            kind_ok: false,
+            ..actionable()
+        };
+
+        assert_eq!(
+            problems(DryRunAction::Fill, &probe, Some("x")),
+            vec!["Element is disabled", "Element is not editable"]
+        );
+        // Hovering a disabled element is fine
+        assert!(problems(DryRunAction::Hover, &probe, None).is_empty());
+    }
+
+    #[test]
+    fn test_missing_option() {
+        let probe = Probe {
+            option_found: Some(false),
+            would_change: Some(false),
+            ..actionable()
+        };
+
+        assert_eq!(
+            problems(DryRunAction::Select, &probe, Some("ca")),
+            vec!["No option with value 'ca'"]
+        );
+    }
+}
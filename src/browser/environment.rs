@@ -0,0 +1,214 @@
+//! Environment summary of a session.
+//!
+//! Chrome's build comes from `Browser.getVersion` and its switches from
+//! `Browser.getBrowserCommandLine`, which Chrome only answers when it runs
+//! with `--enable-automation` (always the case for launched browsers; an
+//! attached one may not say). Extensions are the ones with a running
+//! background page or service worker. Viewport, platform, locale and
+//! timezone are read in the page, so they reflect any emulation.
+
+use anyhow::{Context, Result};
+use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::browser::GetBrowserCommandLineParams;
+use chromiumoxide::cdp::browser_protocol::target::GetTargetsParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use crate::models::{BrowserVersion, ExtensionInfo, FeatureFlags, PlatformInfo, ViewportInfo};
+
+/// What the page reads about where it runs.
+const PAGE_SCRIPT: &str = r#"({
+    width: innerWidth,
+    height: innerHeight,
+    device_pixel_ratio: devicePixelRatio,
+    screen_width: screen.width,
+    screen_height: screen.height,
+    platform: navigator.userAgentData?.platform || navigator.platform,
+    user_agent: navigator.userAgent,
+    locale: navigator.language,
+    timezone: Intl.DateTimeFormat().resolvedOptions().timeZone,
+})"#;
+
+/// The page's view of its environment.
+#[derive(Debug, Deserialize)]
+pub struct PageEnvironment {
+    width: f64,
+    height: f64,
+    device_pixel_ratio: f64,
+    screen_width: f64,
+    screen_height: f64,
+    platform: String,
+    pub user_agent: String,
+    pub locale: String,
+    pub timezone: String,
+}
+
+impl PageEnvironment {
+    pub fn viewport(&self) -> ViewportInfo {
+        ViewportInfo {
+            width: self.width.round() as u32,
+            height: self.height.round() as u32,
+            device_pixel_ratio: self.device_pixel_ratio,
+            screen_width: self.screen_width.round() as u32,
+            screen_height: self.screen_height.round() as u32,
+        }
+    }
+
+    pub fn platform(&self) -> PlatformInfo {
+        PlatformInfo {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            navigator_platform: self.platform.clone(),
+        }
+    }
+}
+
+/// Switches Chrome was started with, as far as they matter here.
+#[derive(Debug, Default, PartialEq)]
+pub struct Switches {
+    pub headless: Option<bool>,
+    pub proxy: Option<String>,
+    pub features: FeatureFlags,
+}
+
+/// Read `--headless`, `--proxy-server` and the feature lists from Chrome's
+/// command line; `None` when it wasn't available.
+pub fn parse_switches(args: Option<Vec<String>>) -> Switches {
+    let Some(args) = args else {
+        return Switches::default();
+    };
+    let value = |name: &str| {
+        args.iter().rev().find_map(|arg| {
+            arg.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        })
+    };
+    // Chrome merges repeated feature switches
+    let features = |name: &str| -> Vec<String> {
+        args.iter()
+            .filter_map(|arg| arg.strip_prefix(name)?.strip_prefix('='))
+            .flat_map(|list| list.split(','))
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    Switches {
+        headless: Some(
+            args.iter()
+                .any(|arg| arg == "--headless" || arg.starts_with("--headless=")),
+        ),
+        proxy: value("--proxy-server"),
+        features: FeatureFlags {
+            enabled: features("--enable-features"),
+            disabled: features("--disable-features"),
+            switches: Some(args.clone()),
+        },
+    }
+}
+
+/// Chrome's build and switches.
+pub async fn browser(browser: &Browser, connected: bool) -> Result<(BrowserVersion, Switches)> {
+    let version = browser
+        .version()
+        .await
+        .context("Failed to read browser version")?;
+    let args = browser
+        .execute(GetBrowserCommandLineParams::default())
+        .await
+        .ok()
+        .map(|response| response.result.arguments);
+    let switches = parse_switches(args);
+
+    Ok((
+        BrowserVersion {
+            product: version.product,
+            revision: version.revision,
+            protocol_version: version.protocol_version,
+            js_version: version.js_version,
+            user_agent: version.user_agent,
+            headless: switches.headless,
+            mode: if connected { "connect" } else { "launch" }.to_string(),
+        },
+        switches,
+    ))
+}
+
+/// Extensions with a running background page or service worker.
+pub async fn extensions(browser: &Browser) -> Result<Vec<ExtensionInfo>> {
+    let targets = browser
+        .execute(GetTargetsParams::default())
+        .await
+        .context("Failed to list targets")?
+        .result
+        .target_infos;
+
+    let mut extensions: Vec<ExtensionInfo> = Vec::new();
+    for target in targets {
+        let Some(id) = target
+            .url
+            .strip_prefix("chrome-extension://")
+            .and_then(|rest| rest.split('/').next())
+        else {
+            continue;
+        };
+        if !matches!(target.r#type.as_str(), "background_page" | "service_worker")
+            || extensions.iter().any(|extension| extension.id == id)
+        {
+            continue;
+        }
+        extensions.push(ExtensionInfo {
+            id: id.to_string(),
+            title: target.title,
+            kind: target.r#type,
+        });
+    }
+    extensions.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(extensions)
+}
+
+/// What `page` reads about where it runs.
+pub async fn page(page: &Page) -> Result<PageEnvironment> {
+    page.evaluate(PAGE_SCRIPT)
+        .await
+        .context("Failed to read page environment")?
+        .into_value()
+        .context("Failed to parse page environment")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_switches() {
+        let args = [
+            "/opt/chrome/chrome",
+            "--headless=new",
+            "--proxy-server=http://proxy.internal:3128",
+            "--disable-features=Translate,MediaRouter",
+            "--enable-features=NetworkService",
+            "--disable-features=site-per-process",
+        ]
+        .map(str::to_string)
+        .to_vec();
+
+        let switches = parse_switches(Some(args));
+        assert_eq!(switches.headless, Some(true));
+        assert_eq!(
+            switches.proxy.as_deref(),
+            Some("http://proxy.internal:3128")
+        );
+        assert_eq!(switches.features.enabled, vec!["NetworkService"]);
+        assert_eq!(
+            switches.features.disabled,
+            vec!["Translate", "MediaRouter", "site-per-process"]
+        );
+
+        assert_eq!(parse_switches(None), Switches::default());
+        let headed = parse_switches(Some(vec!["--no-first-run".to_string()]));
+        assert_eq!(headed.headless, Some(false));
+        assert_eq!(headed.proxy, None);
+    }
+}
@@ -0,0 +1,171 @@
+//! CDP handler and event dispatch.
+//!
+//! The chromiumoxide handler runs on its own task and only routes messages.
+//! Each CDP event stream a session cares about is drained by a dedicated
+//! forwarding task into a bounded broadcast channel, so a slow subscriber
+//! lags (and loses the oldest events) instead of back-pressuring the handler
+//! and stalling command responses for every other session.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::network::EventResponseReceived;
+use chromiumoxide::cdp::browser_protocol::page::EventFrameNavigated;
+use chromiumoxide::cdp::js_protocol::runtime::EventConsoleApiCalled;
+use chromiumoxide::handler::Handler;
+use chromiumoxide::page::Page;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::models::SessionEvent;
+
+/// Per-session event channel capacity before slow subscribers start lagging.
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Drive the chromiumoxide handler on a dedicated task.
+pub fn spawn_handler(mut handler: Handler) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(result) = handler.next().await {
+            if let Err(e) = result {
+                tracing::debug!("CDP handler error: {}", e);
+            }
+        }
+        tracing::info!("CDP handler loop ended");
+    })
+}
+
+/// Forward a CDP event stream into a bounded broadcast channel on its own task.
+///
+/// `map` converts raw events and can drop ones the session doesn't care about.
+pub fn forward_events<S, T, F>(
+    stream: S,
+    sender: broadcast::Sender<SessionEvent>,
+    mut map: F,
+) -> JoinHandle<()>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Send,
+    F: FnMut(T) -> Option<SessionEvent> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut stream = Box::pin(stream);
+        while let Some(event) = stream.next().await {
+            if let Some(event) = map(event) {
+                // An error only means nobody is subscribed right now
+                let _ = sender.send(event);
+            }
+        }
+    })
+}
+
+/// Subscribe a session's page to the CDP events exposed as `SessionEvent`s,
+/// returning the forwarding tasks so the session can stop them.
+pub async fn attach_page_events(
+    page: &Page,
+    sender: &broadcast::Sender<SessionEvent>,
+) -> Result<Vec<JoinHandle<()>>> {
+    // Listen to everything before spawning, so a failure leaves no task behind
+    let navigations = page
+        .event_listener::<EventFrameNavigated>()
+        .await
+        .context("Failed to listen for navigation events")?;
+    let console = page
+        .event_listener::<EventConsoleApiCalled>()
+        .await
+        .context("Failed to listen for console events")?;
+    let responses = page
+        .event_listener::<EventResponseReceived>()
+        .await
+        .context("Failed to listen for response events")?;
+
+    let navigations = forward_events(navigations, sender.clone(), |event| {
+        // Only main-frame navigations are interesting at the session level
+        if event.frame.parent_id.is_some() {
+            return None;
+        }
+        Some(SessionEvent::Navigation {
+            url: event.frame.url.clone(),
+        })
+    });
+
+    let console = forward_events(console, sender.clone(), |event| {
+        let text = event
+            .args
+            .iter()
+            .map(|arg| match (&arg.value, &arg.description) {
+                (Some(serde_json::Value::String(s)), _) => s.clone(),
+                (Some(value), _) => value.to_string(),
+                (None, Some(description)) => description.clone(),
+                (None, None) => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some(SessionEvent::Console {
+            level: cdp_enum_name(&event.r#type),
+            text,
+        })
+    });
+
+    let responses = forward_events(responses, sender.clone(), |event| {
+        Some(SessionEvent::Response {
+            url: event.response.url.clone(),
+            status: event.response.status,
+            resource_type: cdp_enum_name(&event.r#type).to_lowercase(),
+        })
+    });
+
+    Ok(vec![navigations, console, responses])
+}
+
+/// Wire name of a CDP enum value (e.g. `ConsoleApiCalledType::Warning` -> "warning").
+pub fn cdp_enum_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chromiumoxide::cdp::js_protocol::runtime::ConsoleApiCalledType;
+
+    #[test]
+    fn test_cdp_enum_name() {
+        assert_eq!(cdp_enum_name(&ConsoleApiCalledType::Warning), "warning");
+        assert_eq!(cdp_enum_name(&ConsoleApiCalledType::Log), "log");
+    }
+
+    #[tokio::test]
+    async fn test_aborted_forwarding_releases_channel() {
+        let (sender, mut receiver) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let task = forward_events(futures::stream::pending::<()>(), sender, |_| None);
+
+        task.abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert!(matches!(
+            receiver.recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forward_events_maps_and_filters() {
+        let (sender, mut receiver) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let stream = futures::stream::iter(vec!["https://a.test", "", "https://b.test"]);
+
+        forward_events(stream, sender, |url: &str| {
+            (!url.is_empty()).then(|| SessionEvent::Navigation {
+                url: url.to_string(),
+            })
+        })
+        .await
+        .unwrap();
+
+        let mut urls = Vec::new();
+        while let Ok(SessionEvent::Navigation { url }) = receiver.try_recv() {
+            urls.push(url);
+        }
+        assert_eq!(urls, vec!["https://a.test", "https://b.test"]);
+    }
+}
@@ -0,0 +1,121 @@
+//! Caret placement and clearing for text fields.
+//!
+//! `fill` replaces a field's text by default: the text is selected and
+//! deleted with a real Backspace, so the page sees the same `beforeinput` and
+//! `input` events as when a user empties it, before the new value is typed.
+//! In append mode the caret goes to the end instead. Inputs that don't
+//! support selection ranges (`email`, `number`) are selected with `select()`,
+//! or sent an End key to reach the end.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use super::frames::{self, ChildFrame};
+use super::keyboard::{self, KeyboardLayout};
+use super::shadow;
+
+/// Focus the field and select its text (`select`) or put the caret at the
+/// end (`end`); returns its text before.
+const PREPARE_SCRIPT: &str = r#"((sel, mode) => {
+    const el = deepQuery(sel);
+    if (!el) return null;
+    const field = el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement;
+    if (!field && !el.isContentEditable) return { editable: false, value: '', caret: false };
+    if (el.disabled || el.readOnly) return { editable: false, value: '', caret: false };
+    el.focus();
+    const value = field ? el.value : el.innerText;
+    if (!field) {
+        const range = document.createRange();
+        range.selectNodeContents(el);
+        if (mode === 'end') range.collapse(false);
+        const selection = getSelection();
+        selection.removeAllRanges();
+        selection.addRange(range);
+        return { editable: true, value, caret: true };
+    }
+    try {
+        const end = el.value.length;
+        el.setSelectionRange(mode === 'end' ? end : 0, end);
+        return { editable: true, value, caret: true };
+    } catch (e) {
+        if (mode === 'end') return { editable: true, value, caret: false };
+        el.select();
+        return { editable: true, value, caret: true };
+    }
+})"#;
+
+const VALUE_SCRIPT: &str = r#"((sel) => {
+    const el = deepQuery(sel);
+    if (!el) return null;
+    return el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement
+        ? el.value
+        : el.innerText;
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Prepared {
+    editable: bool,
+    value: String,
+    /// Whether the script could place the caret itself
+    caret: bool,
+}
+
+/// How to leave a field's existing text before typing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Delete it
+    Clear,
+    /// Keep it, with the caret at its end
+    Append,
+}
+
+/// Focus the field at `selector` in `frame` (default: the page) and clear it
+/// or move the caret to its end; returns its text before.
+pub async fn prepare(
+    page: &Page,
+    frame: Option<&ChildFrame>,
+    layout: KeyboardLayout,
+    selector: &str,
+    placement: Placement,
+) -> Result<String> {
+    let css = serde_json::to_string(&resolve_selector(selector))?;
+    let mode = match placement {
+        Placement::Clear => "select",
+        Placement::Append => "end",
+    };
+    let prepared: Option<Prepared> = frames::evaluate(
+        page,
+        frame,
+        shadow::call(PREPARE_SCRIPT, &format!("{}, '{}'", css, mode)),
+    )
+    .await
+    .context("Failed to focus field")?;
+    let prepared = prepared.with_context(|| format!("Element not found: {}", selector))?;
+    if !prepared.editable {
+        anyhow::bail!("Element is not an editable field: {}", selector);
+    }
+
+    let key = match placement {
+        Placement::Clear if !prepared.value.is_empty() => Some("Backspace"),
+        Placement::Append if !prepared.caret => Some("End"),
+        _ => None,
+    };
+    if let Some(key) = key {
+        let stroke = keyboard::stroke_for_key(layout, key)
+            .with_context(|| format!("No {} key on the keyboard layout", key))?;
+        keyboard::dispatch(page, &stroke).await?;
+    }
+
+    Ok(prepared.value)
+}
+
+/// Text of the field at `selector` in `frame` (default: the page).
+pub async fn value(page: &Page, frame: Option<&ChildFrame>, selector: &str) -> Result<String> {
+    let css = serde_json::to_string(&resolve_selector(selector))?;
+    let value: Option<String> = frames::evaluate(page, frame, shadow::call(VALUE_SCRIPT, &css))
+        .await
+        .context("Failed to read field")?;
+    value.with_context(|| format!("Element not found: {}", selector))
+}
@@ -0,0 +1,144 @@
+//! Finding snapshot elements by role, accessible name and text.
+//!
+//! Queries run against the session's snapshot (reused while the page is
+//! unchanged), so the refs they return are the snapshot's own, but only the
+//! matches go back to the caller. Role and name are matched in the tree;
+//! text is matched on the page against each ref'd element's rendered text,
+//! keeping the innermost element that contains it, so a button with an
+//! `aria-label` is still found by the words on it, and the section around it
+//! isn't. Matching ignores case and runs of whitespace.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+
+use super::shadow;
+use crate::models::{AriaNode, ElementQuery};
+
+/// Refs (without `@`) of the innermost elements whose rendered text
+/// contains (or, `exact`, is) `text`, already lowercased and collapsed.
+const TEXT_SCRIPT: &str = r#"((text, exact) => {
+    const norm = (s) => (s || '').replace(/\s+/g, ' ').trim().toLowerCase();
+    const hits = deepQueryAll('[data-fgp-ref]').filter((el) => {
+        const t = norm(el.innerText || el.value);
+        return exact ? t === text : t.includes(text);
+    });
+    return hits
+        .filter((el) => !hits.some((other) => other !== el && el.contains(other)))
+        .map((el) => el.getAttribute('data-fgp-ref'));
+})"#;
+
+/// Lowercased, with runs of whitespace collapsed.
+fn normalize(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn matches(node: &AriaNode, query: &ElementQuery) -> bool {
+    if let Some(role) = &query.role {
+        if !node.role.eq_ignore_ascii_case(role) {
+            return false;
+        }
+    }
+    match &query.name {
+        Some(wanted) => node.name.as_deref().is_some_and(|name| {
+            let (name, wanted) = (normalize(name), normalize(wanted));
+            if query.exact {
+                name == wanted
+            } else {
+                name.contains(&wanted)
+            }
+        }),
+        None => true,
+    }
+}
+
+/// Nodes in document order, without their children.
+fn flatten(nodes: &[AriaNode], out: &mut Vec<AriaNode>) {
+    for node in nodes {
+        out.push(AriaNode {
+            children: Vec::new(),
+            ..node.clone()
+        });
+        flatten(&node.children, out);
+    }
+}
+
+/// Every node of `nodes` matching `query`, in document order.
+pub async fn find(page: &Page, nodes: &[AriaNode], query: &ElementQuery) -> Result<Vec<AriaNode>> {
+    let with_text: Option<HashSet<String>> = match &query.text {
+        Some(text) => {
+            let refs: Vec<String> = page
+                .evaluate(shadow::call(
+                    TEXT_SCRIPT,
+                    &format!(
+                        "{}, {}",
+                        serde_json::to_string(&normalize(text))?,
+                        query.exact
+                    ),
+                ))
+                .await
+                .context("Failed to match text")?
+                .into_value()
+                .context("Failed to parse text matches")?;
+            Some(refs.into_iter().collect())
+        }
+        None => None,
+    };
+
+    let mut flat = Vec::new();
+    flatten(nodes, &mut flat);
+    flat.retain(|node| {
+        matches(node, query)
+            && match &with_text {
+                Some(refs) => refs.contains(node.ref_id.trim_start_matches('@')),
+                None => true,
+            }
+    });
+    Ok(flat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(ref_id: &str, role: &str, name: Option<&str>) -> AriaNode {
+        AriaNode {
+            ref_id: ref_id.to_string(),
+            role: role.to_string(),
+            name: name.map(str::to_string),
+            value: None,
+            focusable: false,
+            focused: false,
+            rect: None,
+            in_viewport: false,
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn test_matches_role_and_name() {
+        let login = node("@e1", "button", Some("Log  in"));
+        let link = node("@e2", "link", Some("Forgot your login?"));
+        let query = |role: Option<&str>, name: Option<&str>, exact| ElementQuery {
+            role: role.map(str::to_string),
+            name: name.map(str::to_string),
+            exact,
+            ..ElementQuery::default()
+        };
+
+        assert!(matches(&login, &query(Some("Button"), None, false)));
+        assert!(!matches(&link, &query(Some("button"), None, false)));
+        assert!(matches(&login, &query(None, Some("log in"), false)));
+        assert!(matches(&link, &query(None, Some("LOGIN"), false)));
+        assert!(matches(&login, &query(None, Some("log in"), true)));
+        assert!(!matches(&link, &query(None, Some("login"), true)));
+        assert!(!matches(
+            &node("@e3", "button", None),
+            &query(None, Some("log in"), false)
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+//! Moving keyboard focus without clicking.
+//!
+//! Validation and autosave often run on `blur`/`focusout` only, which clicks
+//! elsewhere trigger unreliably. `focus` and `blur` call the element's own
+//! methods, firing the same events a user's tabbing would. A headless page
+//! isn't focused itself, so focus emulation is turned on first; otherwise
+//! Chrome would move `activeElement` without firing any focus events.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::SetFocusEmulationEnabledParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use super::frames::{self, ChildFrame};
+use super::shadow;
+use crate::models::FocusResult;
+
+/// Focus or blur the element (or, for blur without one, whatever has focus)
+/// and describe the element that has focus afterwards.
+const FOCUS_SCRIPT: &str = r#"((sel, action) => {
+    const describe = (el) => {
+        if (!el || el === document.body) return null;
+        let text = el.tagName.toLowerCase();
+        if (el.id) text += '#' + el.id;
+        else if (el.name) text += '[name="' + el.name + '"]';
+        return text;
+    };
+    const el = sel === null ? deepActiveElement() : deepQuery(sel);
+    if (sel !== null && !el) return null;
+    if (action === 'focus') el.focus();
+    else if (el && el.blur) el.blur();
+    const active = deepActiveElement();
+    return {
+        has_focus: !!el && el !== document.body && active === el,
+        active_element: describe(active),
+    };
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Focused {
+    has_focus: bool,
+    active_element: Option<String>,
+}
+
+/// Move focus to the element at `selector` in `frame` (default: the page)
+/// (`focus`), or away from it or the focused element (`blur`).
+pub async fn apply(
+    page: &Page,
+    frame: Option<&ChildFrame>,
+    selector: Option<&str>,
+    focus: bool,
+) -> Result<FocusResult> {
+    page.execute(SetFocusEmulationEnabledParams::new(true))
+        .await
+        .context("Failed to enable focus emulation")?;
+
+    let css = match selector {
+        Some(selector) => serde_json::to_string(&resolve_selector(selector))?,
+        None => "null".to_string(),
+    };
+    let action = if focus { "focus" } else { "blur" };
+    let focused: Option<Focused> = frames::evaluate(
+        page,
+        frame,
+        shadow::call(FOCUS_SCRIPT, &format!("{}, '{}'", css, action)),
+    )
+    .await
+    .with_context(|| format!("Failed to {}", action))?;
+    let focused =
+        focused.with_context(|| format!("Element not found: {}", selector.unwrap_or_default()))?;
+
+    Ok(FocusResult {
+        // An element that can't take focus (no tabindex, disabled) stays put
+        success: focused.has_focus == focus,
+        selector: selector.map(str::to_string),
+        active_element: focused.active_element,
+    })
+}
@@ -0,0 +1,473 @@
+//! Form introspection.
+//!
+//! `describe_form` lists what a form asks for, so an agent can fill one it
+//! has never seen in a single pass: each field's name, type, label, current
+//! value, constraints, and the options of selects and radio groups. Labels
+//! come from `aria-labelledby`, `<label>`s, `aria-label` or `title`, in that
+//! order; a radio group is labelled by its fieldset's legend (or its
+//! `radiogroup`'s label) and each radio becomes one of its options. Every
+//! field and option comes with a selector to act on it: its snapshot ref when
+//! it has one, a CSS path otherwise. Hidden inputs are left out.
+//!
+//! `validation_errors` reports what a rejected submit complained about. A
+//! field is in error when it has `aria-invalid="true"`, matches `:invalid`,
+//! or has a visible error message: its `aria-errormessage`, an
+//! `aria-describedby` target that looks like an error, or error-looking text
+//! (`role=alert`, `.error`, `.invalid-feedback` and similar) whose nearest
+//! container holds that field and no other. Error text around several
+//! fields is reported for the form as a whole.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{FieldError, FormButton, FormDescription, FormField, ValidationErrors};
+
+const FORM_SCRIPT: &str = r#"((sel) => {
+    const form = sel !== null ? document.querySelector(sel) : document.querySelector('form');
+    if (!form) return null;
+
+    const text = (el) => (el.textContent || '').replace(/\s+/g, ' ').trim();
+    const pathOf = (el) => {
+        const parts = [];
+        while (el && el !== document.documentElement) {
+            if (el.id && document.querySelectorAll('#' + CSS.escape(el.id)).length === 1) {
+                parts.unshift('#' + CSS.escape(el.id));
+                return parts.join(' > ');
+            }
+            const parent = el.parentElement;
+            parts.unshift(el.tagName.toLowerCase() + ':nth-child(' +
+                ([...parent.children].indexOf(el) + 1) + ')');
+            el = parent;
+        }
+        parts.unshift('html');
+        return parts.join(' > ');
+    };
+    const selectorOf = (el) => el.hasAttribute('data-fgp-ref')
+        ? '@' + el.getAttribute('data-fgp-ref') : pathOf(el);
+    const labelOf = (el) => {
+        const by = el.getAttribute('aria-labelledby');
+        if (by) {
+            const t = by.split(/\s+/).map((id) => document.getElementById(id))
+                .filter(Boolean).map(text).join(' ');
+            if (t) return t;
+        }
+        if (el.labels && el.labels.length) {
+            const t = [...el.labels].map(text).join(' ');
+            if (t) return t;
+        }
+        const named = el.getAttribute('aria-label') || el.getAttribute('title');
+        return named ? named.trim() : null;
+    };
+    const groupLabelOf = (el) => {
+        const group = el.closest('[role=radiogroup]');
+        if (group) return labelOf(group) || null;
+        const legend = el.closest('fieldset') && el.closest('fieldset').querySelector('legend');
+        return legend ? text(legend) || null : null;
+    };
+    const attr = (el, name) => el.hasAttribute(name) ? el.getAttribute(name) : null;
+
+    const native = form.tagName === 'FORM';
+    const controls = native ? [...form.elements]
+        : [...form.querySelectorAll('input, select, textarea, button')];
+    const fields = [];
+    const submit = [];
+    for (const el of controls) {
+        const tag = el.tagName.toLowerCase();
+        if (!['input', 'select', 'textarea', 'button'].includes(tag)) continue;
+        const type = tag === 'select' ? (el.multiple ? 'select-multiple' : 'select')
+            : tag === 'textarea' ? 'textarea' : (el.type || 'text').toLowerCase();
+        if (tag === 'button' || ['submit', 'image', 'reset', 'button'].includes(type)) {
+            if (type === 'submit' || type === 'image') {
+                submit.push({ label: text(el) || el.value || labelOf(el) || el.alt || null,
+                    selector: selectorOf(el) });
+            }
+            continue;
+        }
+        if (type === 'hidden') continue;
+
+        const toggle = type === 'checkbox' || type === 'radio';
+        const constraints = {};
+        for (const name of ['pattern', 'min', 'max', 'minlength', 'maxlength', 'step', 'accept']) {
+            const value = attr(el, name);
+            if (value !== null) constraints[name] = value;
+        }
+        const field = {
+            name: el.name || el.id || null,
+            type,
+            label: type === 'radio' ? groupLabelOf(el) : labelOf(el),
+            selector: selectorOf(el),
+            required: el.required,
+            disabled: el.disabled,
+            readonly: !!el.readOnly,
+            value: toggle ? (el.checked ? el.value : null) : type === 'file'
+                ? [...el.files].map((f) => f.name).join(', ') || null : el.value,
+            checked: toggle ? el.checked : null,
+            placeholder: attr(el, 'placeholder'),
+            autocomplete: attr(el, 'autocomplete'),
+            constraints,
+            options: [],
+            valid: el.validity ? el.validity.valid : true,
+            validation_message: el.validationMessage || null,
+        };
+        if (tag === 'select') {
+            field.options = [...el.options].map((o) => ({
+                value: o.value, label: o.label.trim(), selected: o.selected, disabled: o.disabled,
+            }));
+        } else if (type === 'radio') {
+            field.options = [{
+                value: el.value, label: labelOf(el) || el.value, selected: el.checked,
+                disabled: el.disabled, selector: field.selector,
+            }];
+        }
+        fields.push(field);
+    }
+
+    return {
+        selector: selectorOf(form),
+        action: native ? new URL(form.getAttribute('action') || '', location.href).href : null,
+        method: native ? (form.getAttribute('method') || 'get').toLowerCase() : null,
+        fields,
+        submit,
+    };
+})"#;
+
+/// One finding per reason a field (or, without `selector`, the form) is in
+/// error, in document order.
+const ERRORS_SCRIPT: &str = r#"((sel) => {
+    const form = sel !== null ? document.querySelector(sel) : document.querySelector('form');
+    if (!form) return null;
+
+    const text = (el) => (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim();
+    const pathOf = (el) => {
+        const parts = [];
+        while (el && el !== document.documentElement) {
+            if (el.id && document.querySelectorAll('#' + CSS.escape(el.id)).length === 1) {
+                parts.unshift('#' + CSS.escape(el.id));
+                return parts.join(' > ');
+            }
+            const parent = el.parentElement;
+            parts.unshift(el.tagName.toLowerCase() + ':nth-child(' +
+                ([...parent.children].indexOf(el) + 1) + ')');
+            el = parent;
+        }
+        parts.unshift('html');
+        return parts.join(' > ');
+    };
+    const selectorOf = (el) => el.hasAttribute('data-fgp-ref')
+        ? '@' + el.getAttribute('data-fgp-ref') : pathOf(el);
+    const labelOf = (el) => {
+        const by = el.getAttribute('aria-labelledby');
+        if (by) {
+            const t = by.split(/\s+/).map((id) => document.getElementById(id))
+                .filter(Boolean).map(text).join(' ');
+            if (t) return t;
+        }
+        if (el.labels && el.labels.length) {
+            const t = [...el.labels].map(text).join(' ');
+            if (t) return t;
+        }
+        const named = el.getAttribute('aria-label') || el.getAttribute('title');
+        return named ? named.trim() : null;
+    };
+    const visible = (el) => {
+        const style = getComputedStyle(el);
+        if (style.display === 'none' || style.visibility === 'hidden') return false;
+        const r = el.getBoundingClientRect();
+        return r.width > 0 && r.height > 0;
+    };
+    const ERROR_TEXT = '[role=alert], [aria-live=assertive], .error, .errors, .error-message, ' +
+        '.invalid-feedback, .field-error, .form-error, .text-danger, .help-block.error, ' +
+        '[class*="error" i], [class*="invalid" i]';
+
+    const controls = [...form.querySelectorAll(
+        'input, select, textarea, [contenteditable]:not([contenteditable=false]), ' +
+        '[role=textbox], [role=combobox], [role=checkbox], [role=radio], [role=switch]'
+    )].filter((el) => el.type !== 'hidden' && !el.disabled);
+    const findings = [];
+    const note = (el, source, message) => findings.push({
+        selector: el ? selectorOf(el) : null,
+        name: el ? (el.name || el.id || null) : null,
+        label: el ? labelOf(el) : null,
+        source,
+        message: message || null,
+    });
+    const claimed = new Set();
+    const radios = new Set();
+
+    for (const el of controls) {
+        if (el.type === 'radio' && el.name) {
+            if (radios.has(el.name)) continue;
+            radios.add(el.name);
+        }
+        if (el.getAttribute('aria-invalid') === 'true') note(el, 'aria-invalid', null);
+        if (el.matches(':invalid')) note(el, 'invalid', el.validationMessage);
+        for (const attr of ['aria-errormessage', 'aria-describedby']) {
+            for (const id of (el.getAttribute(attr) || '').split(/\s+/).filter(Boolean)) {
+                const target = document.getElementById(id);
+                if (!target || claimed.has(target) || !visible(target) || !text(target)) continue;
+                if (attr === 'aria-describedby' && !target.matches(ERROR_TEXT)) continue;
+                claimed.add(target);
+                note(el, attr === 'aria-errormessage' ? 'aria-errormessage' : 'error-text', text(target));
+            }
+        }
+    }
+
+    const texts = [...form.querySelectorAll(ERROR_TEXT)].filter((el) =>
+        !controls.some((c) => el === c || el.contains(c)) && visible(el) && text(el));
+    for (const el of texts) {
+        if (claimed.has(el) || texts.some((other) => other !== el && other.contains(el))) continue;
+        if ([...claimed].some((other) => other.contains(el) || el.contains(other))) continue;
+        let owner = null;
+        for (let box = el.parentElement; box && !owner; box = box.parentElement) {
+            const inside = controls.filter((c) => box.contains(c));
+            const names = new Set(inside.map((c) => c.type === 'radio' && c.name ? c.name : c));
+            if (names.size > 1) break;
+            if (names.size === 1) owner = inside[0];
+            if (box === form) break;
+        }
+        note(owner, 'error-text', text(el));
+    }
+
+    return { selector: selectorOf(form), findings };
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Finding {
+    selector: Option<String>,
+    name: Option<String>,
+    label: Option<String>,
+    source: String,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawErrors {
+    selector: String,
+    findings: Vec<Finding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawForm {
+    selector: String,
+    action: Option<String>,
+    method: Option<String>,
+    fields: Vec<FormField>,
+    submit: Vec<FormButton>,
+}
+
+/// Fold radios sharing a name into one field, with each radio an option.
+fn group_radios(fields: Vec<FormField>) -> Vec<FormField> {
+    let mut grouped: Vec<FormField> = Vec::with_capacity(fields.len());
+    for field in fields {
+        let group = match (&field.kind[..], &field.name) {
+            ("radio", Some(name)) => grouped
+                .iter_mut()
+                .find(|f| f.kind == "radio" && f.name.as_ref() == Some(name)),
+            _ => None,
+        };
+        let Some(group) = group else {
+            grouped.push(field);
+            continue;
+        };
+        group.required |= field.required;
+        group.valid &= field.valid;
+        if field.checked == Some(true) {
+            group.value = field.value;
+            group.checked = Some(true);
+        }
+        if group.label.is_none() {
+            group.label = field.label;
+        }
+        group.options.extend(field.options);
+    }
+    grouped
+}
+
+/// Describe the form at `selector` (default: the first `<form>` on the page).
+///
+/// Any element works as the form, so forms built without a `<form>` can be
+/// described from their container; `action` and `method` are then absent.
+pub async fn describe(page: &Page, selector: Option<&str>) -> Result<FormDescription> {
+    let sel = selector.map(resolve_selector);
+    let raw: Option<RawForm> = page
+        .evaluate(format!("{}({})", FORM_SCRIPT, serde_json::to_string(&sel)?))
+        .await
+        .context("Failed to read form")?
+        .into_value()
+        .context("Failed to parse form")?;
+    let raw = raw.with_context(|| match selector {
+        Some(selector) => format!("Form not found: {}", selector),
+        None => "No form on the page".to_string(),
+    })?;
+
+    Ok(FormDescription {
+        selector: raw.selector,
+        action: raw.action,
+        method: raw.method,
+        fields: group_radios(raw.fields),
+        submit: raw.submit,
+    })
+}
+
+/// Fold findings into one entry per field, in the order fields were first
+/// flagged, and the messages not tied to a field. Repeated messages are
+/// kept once.
+fn collect_errors(findings: Vec<Finding>) -> (Vec<FieldError>, Vec<String>) {
+    let mut fields: Vec<FieldError> = Vec::new();
+    let mut form_errors: Vec<String> = Vec::new();
+    for finding in findings {
+        let Some(selector) = finding.selector else {
+            if let Some(message) = finding.message {
+                if !form_errors.contains(&message) {
+                    form_errors.push(message);
+                }
+            }
+            continue;
+        };
+        let index = match fields.iter().position(|f| f.selector == selector) {
+            Some(index) => index,
+            None => {
+                fields.push(FieldError {
+                    selector,
+                    name: finding.name,
+                    label: finding.label,
+                    messages: Vec::new(),
+                    sources: Vec::new(),
+                });
+                fields.len() - 1
+            }
+        };
+        let field = &mut fields[index];
+        if !field.sources.contains(&finding.source) {
+            field.sources.push(finding.source);
+        }
+        if let Some(message) = finding.message {
+            if !field.messages.contains(&message) {
+                field.messages.push(message);
+            }
+        }
+    }
+    (fields, form_errors)
+}
+
+/// Validation errors showing on the form at `selector` (default: the first
+/// `<form>` on the page).
+pub async fn validation_errors(page: &Page, selector: Option<&str>) -> Result<ValidationErrors> {
+    let sel = selector.map(resolve_selector);
+    let raw: Option<RawErrors> = page
+        .evaluate(format!(
+            "{}({})",
+            ERRORS_SCRIPT,
+            serde_json::to_string(&sel)?
+        ))
+        .await
+        .context("Failed to read validation errors")?
+        .into_value()
+        .context("Failed to parse validation errors")?;
+    let raw = raw.with_context(|| match selector {
+        Some(selector) => format!("Form not found: {}", selector),
+        None => "No form on the page".to_string(),
+    })?;
+
+    let (fields, form_errors) = collect_errors(raw.findings);
+    Ok(ValidationErrors {
+        selector: raw.selector,
+        valid: fields.is_empty() && form_errors.is_empty(),
+        fields,
+        form_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FormOption;
+
+    fn radio(name: &str, value: &str, checked: bool) -> FormField {
+        FormField {
+            name: Some(name.to_string()),
+            kind: "radio".to_string(),
+            label: Some("Shipping".to_string()),
+            selector: format!("#{}", value),
+            required: false,
+            disabled: false,
+            readonly: false,
+            value: checked.then(|| value.to_string()),
+            checked: Some(checked),
+            placeholder: None,
+            autocomplete: None,
+            constraints: Default::default(),
+            options: vec![FormOption {
+                value: value.to_string(),
+                label: value.to_string(),
+                selected: checked,
+                disabled: false,
+                selector: Some(format!("#{}", value)),
+            }],
+            valid: true,
+            validation_message: None,
+        }
+    }
+
+    #[test]
+    fn test_group_radios() {
+        let mut email = radio("email", "", false);
+        email.kind = "email".to_string();
+        email.options.clear();
+        let mut express = radio("shipping", "express", true);
+        express.required = true;
+        let fields = vec![
+            radio("shipping", "standard", false),
+            email,
+            express,
+            radio("gift", "yes", false),
+        ];
+
+        let grouped = group_radios(fields);
+
+        assert_eq!(grouped.len(), 3);
+        let shipping = &grouped[0];
+        assert_eq!(shipping.value.as_deref(), Some("express"));
+        assert_eq!(shipping.checked, Some(true));
+        assert!(shipping.required);
+        let values: Vec<&str> = shipping.options.iter().map(|o| o.value.as_str()).collect();
+        assert_eq!(values, vec!["standard", "express"]);
+        assert_eq!(grouped[1].kind, "email");
+        assert_eq!(grouped[2].name.as_deref(), Some("gift"));
+        assert_eq!(grouped[2].checked, Some(false));
+    }
+
+    fn finding(selector: Option<&str>, source: &str, message: Option<&str>) -> Finding {
+        Finding {
+            selector: selector.map(str::to_string),
+            name: selector.map(|s| s.trim_start_matches('@').to_string()),
+            label: None,
+            source: source.to_string(),
+            message: message.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        let findings = vec![
+            finding(Some("@e4"), "aria-invalid", None),
+            finding(Some("@e7"), "invalid", Some("Please fill out this field.")),
+            finding(Some("@e4"), "error-text", Some("Enter a valid email")),
+            finding(None, "error-text", Some("Please fix the errors below")),
+            finding(Some("@e4"), "error-text", Some("Enter a valid email")),
+            finding(None, "error-text", Some("Please fix the errors below")),
+        ];
+
+        let (fields, form_errors) = collect_errors(findings);
+
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].selector, "@e4");
+        assert_eq!(fields[0].sources, vec!["aria-invalid", "error-text"]);
+        assert_eq!(fields[0].messages, vec!["Enter a valid email"]);
+        assert_eq!(fields[1].selector, "@e7");
+        assert_eq!(fields[1].messages, vec!["Please fill out this field."]);
+        assert_eq!(form_errors, vec!["Please fix the errors below"]);
+    }
+}
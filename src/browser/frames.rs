@@ -0,0 +1,309 @@
+//! Iframe content in snapshots and actions.
+//!
+//! Each child frame's accessibility tree is read with
+//! `Accessibility.getFullAXTree` for that frame, and its nodes get
+//! frame-qualified refs: `@f2e15` is element 15 in the page's second frame.
+//! Frame numbers, like ref numbers, hold for as long as the document does
+//! (see `RefRegistry`), and the frame's nodes sit under its `Iframe` node.
+//!
+//! Scripts acting on a frame ref run in an isolated world created in the
+//! owning frame: it shares the frame's DOM but not the page's globals, and
+//! works for cross-origin frames the page itself can't script. Pointer
+//! actions land on the element's content quad, placed in the top-level
+//! viewport.
+//!
+//! A cross-site frame Chrome renders in another process is reached through
+//! that process's session (see `oopif`): its commands go there, and the
+//! boxes it reports, relative to its own viewport, are offset by where its
+//! `<iframe>` sits.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    BackendNodeId, GetBoxModelParams, GetContentQuadsParams, GetFrameOwnerParams,
+    ScrollIntoViewIfNeededParams,
+};
+use chromiumoxide::cdp::browser_protocol::page::{
+    CreateIsolatedWorldParams, FrameId, FrameTree, GetFrameTreeParams,
+};
+use chromiumoxide::cdp::js_protocol::runtime::{
+    EvaluateParams, ExecutionContextId, ReleaseObjectParams, RemoteObjectId,
+};
+use chromiumoxide::page::Page;
+use chromiumoxide::types::Command;
+use serde::de::DeserializeOwned;
+
+use super::oopif::{Connection, Remote};
+use super::shadow::{self, Point};
+
+/// Name of the isolated world frame scripts run in.
+const WORLD: &str = "fgp-frame";
+
+/// Scrolls the element into view within its frame and returns it.
+const ELEMENT_SCRIPT: &str = r#"((sel) => {
+    const el = deepQuery(sel);
+    if (el) el.scrollIntoView({ block: 'center', inline: 'center' });
+    return el;
+})"#;
+
+/// Number of the frame a frame-qualified ref (`@f2e15`) belongs to; `None`
+/// for anything else.
+pub fn frame_number(selector: &str) -> Option<usize> {
+    let rest = selector.strip_prefix("@f")?;
+    let (frame, element) = rest.split_once('e')?;
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(frame) || !digits(element) {
+        return None;
+    }
+    frame.parse().ok()
+}
+
+/// A frame below the main one.
+#[derive(Clone)]
+pub struct ChildFrame {
+    pub id: FrameId,
+    /// Session of the process rendering it, if not the page's
+    pub remote: Option<Remote>,
+}
+
+impl ChildFrame {
+    /// The frame a remote session renders.
+    fn root(remote: &Remote) -> Self {
+        Self {
+            id: remote.root.clone(),
+            remote: Some(remote.clone()),
+        }
+    }
+
+    /// Session of the process the frame's `<iframe>` element is in.
+    pub fn holder(&self) -> Option<&Remote> {
+        match &self.remote {
+            Some(remote) if remote.root == self.id => remote.holder.as_deref(),
+            remote => remote.as_ref(),
+        }
+    }
+}
+
+/// Send `cmd` to `remote`'s session, or the page's without one.
+pub async fn execute<C: Command>(
+    page: &Page,
+    remote: Option<&Remote>,
+    cmd: C,
+) -> Result<C::Response> {
+    match remote {
+        Some(remote) => remote.execute(cmd).await,
+        None => Ok(page.execute(cmd).await?.result),
+    }
+}
+
+/// Every frame below the main one, parents before their children. Frames
+/// in other processes are only listed with a `connection` to reach them.
+pub async fn child_frames(page: &Page, connection: Option<&Connection>) -> Result<Vec<ChildFrame>> {
+    let tree = page
+        .execute(GetFrameTreeParams::default())
+        .await
+        .context("Failed to list frames")?
+        .result
+        .frame_tree;
+    let mut frames = Vec::new();
+    collect(&tree, None, &mut frames);
+    let Some(connection) = connection else {
+        return Ok(frames);
+    };
+
+    let remotes = match connection.frames(page.target_id()).await {
+        Ok(remotes) => remotes,
+        Err(e) => {
+            tracing::debug!("Failed to list out-of-process frames: {}", e);
+            return Ok(frames);
+        }
+    };
+    for remote in remotes {
+        let tree = match remote.execute(GetFrameTreeParams::default()).await {
+            Ok(tree) => tree.frame_tree,
+            Err(e) => {
+                tracing::debug!("Skipping frame {}: {}", remote.root.inner(), e);
+                continue;
+            }
+        };
+        let mut rendered = vec![ChildFrame::root(&remote)];
+        collect(&tree, Some(&remote), &mut rendered);
+        for frame in rendered {
+            match frames.iter_mut().find(|listed| listed.id == frame.id) {
+                Some(listed) => *listed = frame,
+                None => frames.push(frame),
+            }
+        }
+    }
+    Ok(frames)
+}
+
+fn collect(tree: &FrameTree, remote: Option<&Remote>, out: &mut Vec<ChildFrame>) {
+    for child in tree.child_frames.iter().flatten() {
+        out.push(ChildFrame {
+            id: child.frame.id.clone(),
+            remote: remote.cloned(),
+        });
+        collect(child, remote, out);
+    }
+}
+
+/// Backend node of the `<iframe>` (or `<frame>`) element holding `frame`,
+/// in the holder's session.
+pub async fn owner(page: &Page, frame: &ChildFrame) -> Result<BackendNodeId> {
+    Ok(execute(
+        page,
+        frame.holder(),
+        GetFrameOwnerParams::new(frame.id.clone()),
+    )
+    .await
+    .context("Failed to find the frame's element")?
+    .backend_node_id)
+}
+
+/// Top-left corner of `frame`'s viewport in the top-level viewport.
+pub async fn origin(page: &Page, frame: &ChildFrame) -> Result<Point> {
+    let mut frame = frame.clone();
+    let mut origin = Point { x: 0.0, y: 0.0 };
+    loop {
+        let holder = frame.holder().cloned();
+        let params = GetBoxModelParams::builder()
+            .backend_node_id(owner(page, &frame).await?)
+            .build();
+        let model = execute(page, holder.as_ref(), params)
+            .await
+            .context("Failed to measure the frame")?
+            .model;
+        let content = model.content.inner();
+        origin.x += content[0];
+        origin.y += content[1];
+        // Another process measures from the viewport of the frame it renders
+        match holder {
+            Some(remote) => frame = ChildFrame::root(&remote),
+            None => return Ok(origin),
+        }
+    }
+}
+
+/// A fresh execution context in `frame` sharing its DOM.
+async fn world(page: &Page, frame: &ChildFrame) -> Result<ExecutionContextId> {
+    let params = CreateIsolatedWorldParams::builder()
+        .frame_id(frame.id.clone())
+        .world_name(WORLD)
+        .build()
+        .map_err(anyhow::Error::msg)?;
+    Ok(execute(page, frame.remote.as_ref(), params)
+        .await
+        .context("Failed to enter the frame")?
+        .execution_context_id)
+}
+
+/// Evaluate `expression` in `frame`, or in the page's main world without
+/// one, and parse its value.
+pub async fn evaluate<T: DeserializeOwned>(
+    page: &Page,
+    frame: Option<&ChildFrame>,
+    expression: String,
+) -> Result<T> {
+    let mut params = EvaluateParams::builder()
+        .expression(expression)
+        .await_promise(true)
+        .return_by_value(true);
+    if let Some(frame) = frame {
+        params = params.context_id(world(page, frame).await?);
+    }
+    let params = params.build().map_err(anyhow::Error::msg)?;
+    let Some(remote) = frame.and_then(|frame| frame.remote.as_ref()) else {
+        return Ok(page.evaluate_expression(params).await?.into_value()?);
+    };
+    let evaluated = remote.execute(params).await?;
+    if let Some(exception) = evaluated.exception_details {
+        anyhow::bail!("Script failed in the frame: {}", exception.text);
+    }
+    Ok(serde_json::from_value(
+        evaluated.result.value.unwrap_or_default(),
+    )?)
+}
+
+/// Evaluate `expression` in `frame` (default: the page's main world) for
+/// an element, returning a handle to it; `None` if it evaluates to null.
+/// The caller releases the handle, in the frame's session.
+pub async fn element(
+    page: &Page,
+    frame: Option<&ChildFrame>,
+    expression: String,
+) -> Result<Option<RemoteObjectId>> {
+    let mut params = EvaluateParams::builder().expression(expression);
+    if let Some(frame) = frame {
+        params = params.context_id(world(page, frame).await?);
+    }
+    let params = params.build().map_err(anyhow::Error::msg)?;
+    let evaluated = execute(page, frame.and_then(|frame| frame.remote.as_ref()), params)
+        .await
+        .context("Failed to search the frame")?;
+    if let Some(exception) = evaluated.exception_details {
+        anyhow::bail!("Failed to search the frame: {}", exception.text);
+    }
+    Ok(evaluated.result.object_id)
+}
+
+/// Scroll the element at CSS `selector` in `frame` into view and return its
+/// center in the top-level viewport, or `None` if the frame has no such
+/// element.
+pub async fn locate(page: &Page, frame: &ChildFrame, selector: &str) -> Result<Option<Point>> {
+    let expression = shadow::call(ELEMENT_SCRIPT, &serde_json::to_string(selector)?);
+    let Some(object_id) = element(page, Some(frame), expression).await? else {
+        return Ok(None);
+    };
+    let remote = frame.remote.as_ref();
+
+    let center = async {
+        execute(
+            page,
+            remote,
+            ScrollIntoViewIfNeededParams::builder()
+                .object_id(object_id.clone())
+                .build(),
+        )
+        .await?;
+        let quads = execute(
+            page,
+            remote,
+            GetContentQuadsParams::builder()
+                .object_id(object_id.clone())
+                .build(),
+        )
+        .await?
+        .quads;
+        let quad = quads.first().context("Element is not rendered")?.inner();
+        let mut center = Point {
+            x: (quad[0] + quad[2] + quad[4] + quad[6]) / 4.0,
+            y: (quad[1] + quad[3] + quad[5] + quad[7]) / 4.0,
+        };
+        if let Some(remote) = remote {
+            let origin = origin(page, &ChildFrame::root(remote)).await?;
+            center.x += origin.x;
+            center.y += origin.y;
+        }
+        Ok::<_, anyhow::Error>(center)
+    }
+    .await;
+    let _ = execute(page, remote, ReleaseObjectParams::new(object_id)).await;
+    center.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_number() {
+        assert_eq!(frame_number("@f2e15"), Some(2));
+        assert_eq!(frame_number("@f10e3"), Some(10));
+        assert_eq!(frame_number("@e15"), None);
+        assert_eq!(frame_number("@f2"), None);
+        assert_eq!(frame_number("@fe15"), None);
+        assert_eq!(frame_number("@f2e"), None);
+        assert_eq!(frame_number("@foo"), None);
+        assert_eq!(frame_number("#f2e15"), None);
+    }
+}
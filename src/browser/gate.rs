@@ -0,0 +1,80 @@
+//! robots.txt for every document a page loads.
+//!
+//! The service checks the URL of each navigation it starts, but a page also
+//! navigates by itself: links clicked, forms submitted, redirects, history,
+//! script setting `location`. With a robots policy, each session's page
+//! pauses its main frame's document requests through `Fetch` interception
+//! and fails the ones robots.txt disallows (`net::ERR_BLOCKED_BY_CLIENT`),
+//! whatever started them. Subframe documents aren't held: checking every
+//! embed's origin would stall page loads on robots.txt fetches.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    ContinueRequestParams, EnableParams, EventRequestPaused, FailRequestParams, RequestPattern,
+    RequestStage,
+};
+use chromiumoxide::cdp::browser_protocol::network::{ErrorReason, ResourceType};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use crate::robots::{RobotsDisallowed, RobotsPolicy};
+
+/// Documents, paused before they are requested.
+pub fn pattern() -> RequestPattern {
+    RequestPattern::builder()
+        .url_pattern("*")
+        .resource_type(ResourceType::Document)
+        .request_stage(RequestStage::Request)
+        .build()
+}
+
+/// Whether a paused request is at the request stage (no response yet).
+pub fn at_request(event: &EventRequestPaused) -> bool {
+    event.response_status_code.is_none() && event.response_error_reason.is_none()
+}
+
+/// Hold `page`'s documents to `robots` until the returned task is aborted.
+pub async fn guard(page: &Page, robots: Arc<RobotsPolicy>) -> Result<JoinHandle<()>> {
+    let mut paused = page
+        .event_listener::<EventRequestPaused>()
+        .await
+        .context("Failed to listen for intercepted requests")?;
+    page.execute(EnableParams::builder().pattern(pattern()).build())
+        .await
+        .context("Failed to intercept documents")?;
+
+    let page = page.clone();
+    Ok(tokio::spawn(async move {
+        while let Some(event) = paused.next().await {
+            // Responses paused by someone else's patterns (a CSP trial)
+            if !at_request(&event) {
+                continue;
+            }
+            let main = page.mainframe().await.ok().flatten();
+            let disallowed = main.as_ref() == Some(&event.frame_id)
+                && robots
+                    .check(&event.request.url)
+                    .await
+                    .is_err_and(|e| e.downcast_ref::<RobotsDisallowed>().is_some());
+            let answered = if disallowed {
+                tracing::info!("robots.txt disallows {}; blocked", event.request.url);
+                page.execute(FailRequestParams::new(
+                    event.request_id.clone(),
+                    ErrorReason::BlockedByClient,
+                ))
+                .await
+                .map(drop)
+            } else {
+                page.execute(ContinueRequestParams::new(event.request_id.clone()))
+                    .await
+                    .map(drop)
+            };
+            if let Err(e) = answered {
+                tracing::debug!("Could not answer intercepted document: {}", e);
+            }
+        }
+    }))
+}
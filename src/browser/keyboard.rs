@@ -0,0 +1,668 @@
+//! Layout-aware key event generation.
+//!
+//! Sites that validate raw key events check `code` (the physical key) and
+//! `keyCode` alongside `key`, so typing `@` on a German layout has to look
+//! like AltGr+Q rather than Shift+2. Each session carries a layout; characters
+//! are mapped to the physical key and modifiers that produce them on that
+//! layout. Characters a layout can't produce fall back to `Input.insertText`.
+//!
+//! Shortcuts like `Control+A` or `Meta+Shift+P` press each modifier key,
+//! then the key with the modifier bits set, then release the modifiers.
+//!
+//! Text can also be typed at a human pace, with a randomized pause between
+//! keystrokes, for autocomplete widgets that debounce input or drop
+//! keystrokes that arrive faster than they render.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, InsertTextParams,
+};
+use chromiumoxide::page::Page;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// CDP modifier bits.
+const ALT: i64 = 1;
+const CTRL: i64 = 2;
+const META: i64 = 4;
+const SHIFT: i64 = 8;
+/// AltGr is reported as Ctrl+Alt, as on Windows.
+const ALT_GR: i64 = CTRL | ALT;
+
+/// Keyboard layout used to generate key events for a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardLayout {
+    /// US QWERTY
+    #[default]
+    Us,
+    /// UK QWERTY
+    Uk,
+    /// German QWERTZ
+    De,
+    /// French AZERTY
+    Fr,
+}
+
+impl KeyboardLayout {
+    /// Parse a layout name (`us`, `uk`/`gb`, `de`, `fr`).
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "us" | "en-us" => Ok(Self::Us),
+            "uk" | "gb" | "en-gb" => Ok(Self::Uk),
+            "de" | "de-de" => Ok(Self::De),
+            "fr" | "fr-fr" => Ok(Self::Fr),
+            other => anyhow::bail!(
+                "Unsupported keyboard layout '{}' (expected us, uk, de, or fr)",
+                other
+            ),
+        }
+    }
+
+    fn rows(self) -> &'static [Row] {
+        match self {
+            Self::Us => US_ROWS,
+            Self::Uk => UK_ROWS,
+            Self::De => DE_ROWS,
+            Self::Fr => FR_ROWS,
+        }
+    }
+
+    fn alt_gr(self) -> &'static [(char, &'static str)] {
+        match self {
+            Self::Us => &[],
+            Self::Uk => UK_ALT_GR,
+            Self::De => DE_ALT_GR,
+            Self::Fr => FR_ALT_GR,
+        }
+    }
+}
+
+/// A fully described key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStroke {
+    pub key: String,
+    pub code: String,
+    pub key_code: i64,
+    pub modifiers: i64,
+    /// Text inserted by the key (None for non-printing keys)
+    pub text: Option<String>,
+}
+
+/// Physical keys of one row and the characters they produce.
+struct Row {
+    codes: &'static [&'static str],
+    base: &'static str,
+    shift: &'static str,
+}
+
+const fn row(codes: &'static [&'static str], base: &'static str, shift: &'static str) -> Row {
+    Row { codes, base, shift }
+}
+
+const NUMBER_ROW: &[&str] = &[
+    "Backquote",
+    "Digit1",
+    "Digit2",
+    "Digit3",
+    "Digit4",
+    "Digit5",
+    "Digit6",
+    "Digit7",
+    "Digit8",
+    "Digit9",
+    "Digit0",
+    "Minus",
+    "Equal",
+];
+const TOP_ROW: &[&str] = &[
+    "KeyQ",
+    "KeyW",
+    "KeyE",
+    "KeyR",
+    "KeyT",
+    "KeyY",
+    "KeyU",
+    "KeyI",
+    "KeyO",
+    "KeyP",
+    "BracketLeft",
+    "BracketRight",
+];
+const HOME_ROW: &[&str] = &[
+    "KeyA",
+    "KeyS",
+    "KeyD",
+    "KeyF",
+    "KeyG",
+    "KeyH",
+    "KeyJ",
+    "KeyK",
+    "KeyL",
+    "Semicolon",
+    "Quote",
+];
+const BOTTOM_ROW: &[&str] = &[
+    "KeyZ", "KeyX", "KeyC", "KeyV", "KeyB", "KeyN", "KeyM", "Comma", "Period", "Slash",
+];
+
+const US_ROWS: &[Row] = &[
+    row(NUMBER_ROW, "`1234567890-=", "~!@#$%^&*()_+"),
+    row(TOP_ROW, "qwertyuiop[]", "QWERTYUIOP{}"),
+    row(&["Backslash"], "\\", "|"),
+    row(HOME_ROW, "asdfghjkl;'", "ASDFGHJKL:\""),
+    row(BOTTOM_ROW, "zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+const UK_ROWS: &[Row] = &[
+    row(NUMBER_ROW, "`1234567890-=", "¬!\"£$%^&*()_+"),
+    row(TOP_ROW, "qwertyuiop[]", "QWERTYUIOP{}"),
+    row(HOME_ROW, "asdfghjkl;'", "ASDFGHJKL:@"),
+    row(&["Backslash"], "#", "~"),
+    row(&["IntlBackslash"], "\\", "|"),
+    row(BOTTOM_ROW, "zxcvbnm,./", "ZXCVBNM<>?"),
+];
+const UK_ALT_GR: &[(char, &str)] = &[('€', "Digit4")];
+
+const DE_ROWS: &[Row] = &[
+    row(NUMBER_ROW, "^1234567890ß´", "°!\"§$%&/()=?`"),
+    row(TOP_ROW, "qwertzuiopü+", "QWERTZUIOPÜ*"),
+    row(HOME_ROW, "asdfghjklöä", "ASDFGHJKLÖÄ"),
+    row(&["Backslash"], "#", "'"),
+    row(&["IntlBackslash"], "<", ">"),
+    row(BOTTOM_ROW, "yxcvbnm,.-", "YXCVBNM;:_"),
+];
+const DE_ALT_GR: &[(char, &str)] = &[
+    ('@', "KeyQ"),
+    ('€', "KeyE"),
+    ('²', "Digit2"),
+    ('³', "Digit3"),
+    ('{', "Digit7"),
+    ('[', "Digit8"),
+    (']', "Digit9"),
+    ('}', "Digit0"),
+    ('\\', "Minus"),
+    ('~', "BracketRight"),
+    ('|', "IntlBackslash"),
+    ('µ', "KeyM"),
+];
+
+const FR_ROWS: &[Row] = &[
+    row(NUMBER_ROW, "²&é\"'(-è_çà)=", "²1234567890°+"),
+    row(TOP_ROW, "azertyuiop^$", "AZERTYUIOP¨£"),
+    row(HOME_ROW, "qsdfghjklmù", "QSDFGHJKLM%"),
+    row(&["Backslash"], "*", "µ"),
+    row(&["IntlBackslash"], "<", ">"),
+    row(BOTTOM_ROW, "wxcvbn,;:!", "WXCVBN?./§"),
+];
+const FR_ALT_GR: &[(char, &str)] = &[
+    ('~', "Digit2"),
+    ('#', "Digit3"),
+    ('{', "Digit4"),
+    ('[', "Digit5"),
+    ('|', "Digit6"),
+    ('`', "Digit7"),
+    ('\\', "Digit8"),
+    ('@', "Digit0"),
+    (']', "Minus"),
+    ('}', "Equal"),
+    ('€', "KeyE"),
+    ('¤', "BracketRight"),
+];
+
+/// Non-printing keys (`key` and `code` match): (key, keyCode).
+const NAMED_KEYS: &[(&str, i64)] = &[
+    ("Backspace", 8),
+    ("Tab", 9),
+    ("Enter", 13),
+    ("Escape", 27),
+    ("PageUp", 33),
+    ("PageDown", 34),
+    ("End", 35),
+    ("Home", 36),
+    ("ArrowLeft", 37),
+    ("ArrowUp", 38),
+    ("ArrowRight", 39),
+    ("ArrowDown", 40),
+    ("Insert", 45),
+    ("Delete", 46),
+    ("F1", 112),
+    ("F2", 113),
+    ("F3", 114),
+    ("F4", 115),
+    ("F5", 116),
+    ("F6", 117),
+    ("F7", 118),
+    ("F8", 119),
+    ("F9", 120),
+    ("F10", 121),
+    ("F11", 122),
+    ("F12", 123),
+];
+
+/// A modifier key: (names, key, code, keyCode, bit).
+type ModifierKey = (
+    &'static [&'static str],
+    &'static str,
+    &'static str,
+    i64,
+    i64,
+);
+
+/// Modifier keys a shortcut can hold.
+const MODIFIER_KEYS: &[ModifierKey] = &[
+    (&["control", "ctrl"], "Control", "ControlLeft", 17, CTRL),
+    (&["shift"], "Shift", "ShiftLeft", 16, SHIFT),
+    (&["alt", "option"], "Alt", "AltLeft", 18, ALT),
+    (
+        &["meta", "cmd", "command", "super"],
+        "Meta",
+        "MetaLeft",
+        91,
+        META,
+    ),
+];
+
+/// Windows virtual key code for a physical key producing `ch`.
+fn virtual_key_code(code: &str, ch: char) -> i64 {
+    if ch.is_ascii_alphabetic() {
+        return ch.to_ascii_uppercase() as i64;
+    }
+    if let Some(digit) = code.strip_prefix("Digit") {
+        return digit.as_bytes()[0] as i64;
+    }
+    if let Some(letter) = code.strip_prefix("Key") {
+        return letter.as_bytes()[0] as i64;
+    }
+    match code {
+        "Space" => 32,
+        "Semicolon" => 186,
+        "Equal" => 187,
+        "Comma" => 188,
+        "Minus" => 189,
+        "Period" => 190,
+        "Slash" => 191,
+        "Backquote" => 192,
+        "BracketLeft" => 219,
+        "Backslash" => 220,
+        "BracketRight" => 221,
+        "Quote" => 222,
+        "IntlBackslash" => 226,
+        _ => 0,
+    }
+}
+
+/// The key stroke that types `ch` on `layout`, if the layout has one.
+pub fn stroke_for_char(layout: KeyboardLayout, ch: char) -> Option<KeyStroke> {
+    let stroke = |code: &str, modifiers: i64| KeyStroke {
+        key: ch.to_string(),
+        code: code.to_string(),
+        key_code: virtual_key_code(code, ch),
+        modifiers,
+        text: Some(ch.to_string()),
+    };
+
+    match ch {
+        ' ' => return Some(stroke("Space", 0)),
+        '\n' | '\r' => return stroke_for_key(layout, "Enter"),
+        '\t' => return stroke_for_key(layout, "Tab"),
+        _ => {}
+    }
+
+    for row in layout.rows() {
+        if let Some(i) = row.base.chars().position(|c| c == ch) {
+            return Some(stroke(row.codes[i], 0));
+        }
+    }
+    for row in layout.rows() {
+        if let Some(i) = row.shift.chars().position(|c| c == ch) {
+            return Some(stroke(row.codes[i], SHIFT));
+        }
+    }
+    layout
+        .alt_gr()
+        .iter()
+        .find(|(c, _)| *c == ch)
+        .map(|(_, code)| stroke(code, ALT_GR))
+}
+
+/// The key stroke for a `press` argument: a named key or a single character.
+pub fn stroke_for_key(layout: KeyboardLayout, key: &str) -> Option<KeyStroke> {
+    let mut chars = key.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return stroke_for_char(layout, ch);
+    }
+
+    NAMED_KEYS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(name, key_code)| KeyStroke {
+            key: name.to_string(),
+            code: name.to_string(),
+            key_code: *key_code,
+            modifiers: 0,
+            text: (*name == "Enter").then(|| "\r".to_string()),
+        })
+}
+
+/// A key pressed while holding modifier keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    /// Modifier keys, pressed in order and released in reverse
+    pub held: Vec<KeyStroke>,
+    pub stroke: KeyStroke,
+}
+
+fn modifier_key(name: &str) -> Option<&'static ModifierKey> {
+    let name = name.trim().to_ascii_lowercase();
+    MODIFIER_KEYS
+        .iter()
+        .find(|(names, ..)| names.contains(&name.as_str()))
+}
+
+/// CDP modifier bits for modifier names (`Shift`, `ctrl`, `Cmd`), with the
+/// names as Chrome reports the keys (`Shift`, `Control`, `Meta`).
+pub fn modifier_mask<S: AsRef<str>>(names: &[S]) -> Result<(i64, Vec<String>)> {
+    let mut bits = 0;
+    let mut keys = Vec::new();
+    for name in names {
+        let (_, key, _, _, bit) = modifier_key(name.as_ref())
+            .with_context(|| format!("Unknown modifier '{}'", name.as_ref()))?;
+        if bits & bit == 0 {
+            keys.push(key.to_string());
+        }
+        bits |= bit;
+    }
+    Ok((bits, keys))
+}
+
+/// `Control+Shift+P` -> (["Control", "Shift"], "P"); `Control++` presses `+`.
+fn split_combo(input: &str) -> (Vec<&str>, &str) {
+    let (modifiers, key) = match input.strip_suffix("++") {
+        Some(modifiers) if !modifiers.is_empty() => (modifiers, "+"),
+        _ => match input.rsplit_once('+') {
+            Some((modifiers, key)) if !modifiers.is_empty() && !key.is_empty() => (modifiers, key),
+            _ => return (Vec::new(), input),
+        },
+    };
+    (modifiers.split('+').map(str::trim).collect(), key.trim())
+}
+
+/// The keys for a `press` argument: a key (`Enter`, `a`) or modifiers and
+/// a key joined by `+` (`Control+A`, `Meta+Shift+P`). `None` for a lone key
+/// the layout doesn't know.
+pub fn parse_combo(layout: KeyboardLayout, input: &str) -> Result<Option<KeyCombo>> {
+    let (modifiers, key) = split_combo(input);
+
+    let mut held = Vec::new();
+    let mut bits = 0;
+    for name in modifiers {
+        let (_, key, code, key_code, bit) = modifier_key(name)
+            .with_context(|| format!("Unknown modifier '{}' in '{}'", name, input))?;
+        bits |= bit;
+        held.push(KeyStroke {
+            key: key.to_string(),
+            code: code.to_string(),
+            key_code: *key_code,
+            modifiers: bits,
+            text: None,
+        });
+    }
+
+    let mut key = if key.eq_ignore_ascii_case("space") {
+        " ".to_string()
+    } else {
+        key.to_string()
+    };
+    // In shortcuts a letter's case comes from Shift, not from how it's written
+    if !held.is_empty() && key.len() == 1 && key.chars().all(|c| c.is_ascii_alphabetic()) {
+        key = if bits & SHIFT != 0 {
+            key.to_ascii_uppercase()
+        } else {
+            key.to_ascii_lowercase()
+        };
+    }
+
+    let Some(mut stroke) = stroke_for_key(layout, &key) else {
+        if held.is_empty() {
+            return Ok(None);
+        }
+        anyhow::bail!("Unknown key '{}' in '{}'", key, input);
+    };
+    stroke.modifiers |= bits;
+    if bits & (CTRL | ALT | META) != 0 {
+        // Shortcuts don't type their key
+        stroke.text = None;
+    }
+
+    Ok(Some(KeyCombo { held, stroke }))
+}
+
+async fn key_event(
+    page: &Page,
+    kind: DispatchKeyEventType,
+    stroke: &KeyStroke,
+    modifiers: i64,
+) -> Result<()> {
+    let mut event = DispatchKeyEventParams::builder()
+        .r#type(kind.clone())
+        .key(stroke.key.clone())
+        .code(stroke.code.clone())
+        .windows_virtual_key_code(stroke.key_code)
+        .modifiers(modifiers);
+    if let (DispatchKeyEventType::KeyDown, Some(text)) = (kind, &stroke.text) {
+        event = event.text(text.clone()).unmodified_text(text.clone());
+    }
+    page.execute(
+        event
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build key event: {:?}", e))?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Dispatch keyDown/keyUp for a stroke.
+pub async fn dispatch(page: &Page, stroke: &KeyStroke) -> Result<()> {
+    key_event(
+        page,
+        DispatchKeyEventType::KeyDown,
+        stroke,
+        stroke.modifiers,
+    )
+    .await?;
+    key_event(page, DispatchKeyEventType::KeyUp, stroke, stroke.modifiers).await
+}
+
+/// Press the modifiers of `combo`, then its key, then release the modifiers.
+pub async fn dispatch_combo(page: &Page, combo: &KeyCombo) -> Result<()> {
+    for held in &combo.held {
+        key_event(page, DispatchKeyEventType::KeyDown, held, held.modifiers).await?;
+    }
+    dispatch(page, &combo.stroke).await?;
+    for (i, held) in combo.held.iter().enumerate().rev() {
+        let still_held = i
+            .checked_sub(1)
+            .map_or(0, |prev| combo.held[prev].modifiers);
+        key_event(page, DispatchKeyEventType::KeyUp, held, still_held).await?;
+    }
+    Ok(())
+}
+
+/// Pause before the next keystroke: `delay_ms` give or take up to
+/// `jitter_ms`, picked by `random`.
+fn keystroke_pause(delay_ms: u64, jitter_ms: u64, random: u64) -> Duration {
+    let offset = random % (2 * jitter_ms + 1);
+    Duration::from_millis((delay_ms + offset).saturating_sub(jitter_ms))
+}
+
+/// Type text into the focused element using `layout`.
+pub async fn type_text(page: &Page, layout: KeyboardLayout, text: &str) -> Result<()> {
+    type_text_paced(page, layout, text, 0, 0).await
+}
+
+/// Type text into the focused element using `layout`, pausing `delay_ms`
+/// (give or take up to `jitter_ms`) between keystrokes.
+pub async fn type_text_paced(
+    page: &Page,
+    layout: KeyboardLayout,
+    text: &str,
+    delay_ms: u64,
+    jitter_ms: u64,
+) -> Result<()> {
+    for (i, ch) in text.chars().enumerate() {
+        if i > 0 && delay_ms + jitter_ms > 0 {
+            let random = uuid::Uuid::new_v4().as_u128() as u64;
+            tokio::time::sleep(keystroke_pause(delay_ms, jitter_ms, random)).await;
+        }
+        match stroke_for_char(layout, ch) {
+            Some(stroke) => dispatch(page, &stroke).await?,
+            None => {
+                page.execute(InsertTextParams::new(ch.to_string()))
+                    .await
+                    .context("Failed to insert text")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_rows_are_aligned() {
+        for layout in [
+            KeyboardLayout::Us,
+            KeyboardLayout::Uk,
+            KeyboardLayout::De,
+            KeyboardLayout::Fr,
+        ] {
+            for row in layout.rows() {
+                assert_eq!(row.base.chars().count(), row.codes.len(), "{:?}", layout);
+                assert_eq!(row.shift.chars().count(), row.codes.len(), "{:?}", layout);
+            }
+        }
+    }
+
+    #[test]
+    fn test_at_sign_per_layout() {
+        let us = stroke_for_char(KeyboardLayout::Us, '@').unwrap();
+        assert_eq!((us.code.as_str(), us.modifiers), ("Digit2", SHIFT));
+
+        let uk = stroke_for_char(KeyboardLayout::Uk, '@').unwrap();
+        assert_eq!((uk.code.as_str(), uk.modifiers), ("Quote", SHIFT));
+
+        let de = stroke_for_char(KeyboardLayout::De, '@').unwrap();
+        assert_eq!((de.code.as_str(), de.modifiers), ("KeyQ", ALT_GR));
+
+        let fr = stroke_for_char(KeyboardLayout::Fr, '@').unwrap();
+        assert_eq!((fr.code.as_str(), fr.modifiers), ("Digit0", ALT_GR));
+    }
+
+    #[test]
+    fn test_layout_specific_characters() {
+        let sz = stroke_for_char(KeyboardLayout::De, 'ß').unwrap();
+        assert_eq!(sz.code, "Minus");
+        assert_eq!(sz.key_code, 189);
+        assert!(stroke_for_char(KeyboardLayout::Us, 'ß').is_none());
+
+        // AZERTY: 'a' sits on the physical Q key but reports keyCode 65
+        let a = stroke_for_char(KeyboardLayout::Fr, 'a').unwrap();
+        assert_eq!((a.code.as_str(), a.key_code), ("KeyQ", 65));
+
+        let z = stroke_for_char(KeyboardLayout::De, 'Z').unwrap();
+        assert_eq!(
+            (z.code.as_str(), z.modifiers, z.key_code),
+            ("KeyY", SHIFT, 90)
+        );
+    }
+
+    #[test]
+    fn test_parse_combo() {
+        let select_all = parse_combo(KeyboardLayout::Us, "Control+A")
+            .unwrap()
+            .unwrap();
+        assert_eq!(select_all.held.len(), 1);
+        assert_eq!(select_all.held[0].code, "ControlLeft");
+        assert_eq!(
+            (
+                select_all.stroke.key.as_str(),
+                select_all.stroke.code.as_str(),
+                select_all.stroke.modifiers
+            ),
+            ("a", "KeyA", CTRL)
+        );
+        assert_eq!(select_all.stroke.text, None);
+
+        let palette = parse_combo(KeyboardLayout::Us, "Meta+Shift+p")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (palette.stroke.key.as_str(), palette.stroke.modifiers),
+            ("P", META | SHIFT)
+        );
+        assert_eq!(palette.held[1].modifiers, META | SHIFT);
+
+        let zoom = parse_combo(KeyboardLayout::Us, "Control++")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (zoom.stroke.code.as_str(), zoom.stroke.modifiers),
+            ("Equal", CTRL | SHIFT)
+        );
+
+        let enter = parse_combo(KeyboardLayout::Us, "Enter").unwrap().unwrap();
+        assert!(enter.held.is_empty());
+        assert!(parse_combo(KeyboardLayout::Us, "MediaPlayPause")
+            .unwrap()
+            .is_none());
+        assert!(parse_combo(KeyboardLayout::Us, "Hyper+A").is_err());
+        assert!(parse_combo(KeyboardLayout::Us, "Control+Nope").is_err());
+    }
+
+    #[test]
+    fn test_modifier_mask() {
+        let (bits, keys) = modifier_mask(&["shift", "Cmd", "SHIFT"]).unwrap();
+        assert_eq!(bits, SHIFT | META);
+        assert_eq!(keys, vec!["Shift", "Meta"]);
+        assert_eq!(modifier_mask::<&str>(&[]).unwrap(), (0, vec![]));
+        assert!(modifier_mask(&["hyper"]).is_err());
+    }
+
+    #[test]
+    fn test_named_keys() {
+        let enter = stroke_for_key(KeyboardLayout::Fr, "Enter").unwrap();
+        assert_eq!(enter.key_code, 13);
+        assert_eq!(enter.text.as_deref(), Some("\r"));
+
+        let arrow = stroke_for_key(KeyboardLayout::Us, "arrowdown").unwrap();
+        assert_eq!((arrow.key.as_str(), arrow.key_code), ("ArrowDown", 40));
+        assert!(arrow.text.is_none());
+
+        assert!(stroke_for_key(KeyboardLayout::Us, "NotAKey").is_none());
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(KeyboardLayout::from_name("GB").unwrap(), KeyboardLayout::Uk);
+        assert_eq!(KeyboardLayout::from_name("de").unwrap(), KeyboardLayout::De);
+        assert!(KeyboardLayout::from_name("dvorak").is_err());
+    }
+
+    #[test]
+    fn test_keystroke_pause() {
+        assert_eq!(keystroke_pause(80, 0, 12345), Duration::from_millis(80));
+        assert_eq!(keystroke_pause(80, 30, 0), Duration::from_millis(50));
+        assert_eq!(keystroke_pause(80, 30, 60), Duration::from_millis(110));
+        // Never negative
+        assert_eq!(keystroke_pause(10, 30, 0), Duration::ZERO);
+        for random in 0..200 {
+            let pause = keystroke_pause(80, 30, random).as_millis();
+            assert!((50..=110).contains(&pause));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Collecting a page's links for `browser.check_links`.
+//!
+//! Only same-origin http(s) links are kept, without fragments and without
+//! duplicates, in document order. The page's user agent and cookies travel
+//! with them, so the checker requests each link as the session would.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+const LINKS_SCRIPT: &str = r#"(() => ({
+    url: location.href,
+    user_agent: navigator.userAgent,
+    hrefs: Array.from(document.links, (a) => a.href),
+}))()"#;
+
+#[derive(Debug, Deserialize)]
+struct RawLinks {
+    url: String,
+    user_agent: String,
+    hrefs: Vec<String>,
+}
+
+/// A page's checkable links and what to send when requesting them.
+#[derive(Debug, Clone)]
+pub struct PageLinks {
+    pub url: String,
+    pub user_agent: String,
+    /// `Cookie` header value for the page, if it has cookies
+    pub cookie: Option<String>,
+    pub links: Vec<String>,
+}
+
+/// `hrefs` on `page_url`'s origin, normalized and deduplicated.
+fn same_origin_links(page_url: &str, hrefs: Vec<String>) -> Vec<String> {
+    let Ok(page) = url::Url::parse(page_url) else {
+        return Vec::new();
+    };
+    let origin = page.origin();
+
+    let mut seen = HashSet::new();
+    hrefs
+        .into_iter()
+        .filter_map(|href| {
+            let mut url = url::Url::parse(&href).ok()?;
+            if !matches!(url.scheme(), "http" | "https") || url.origin() != origin {
+                return None;
+            }
+            url.set_fragment(None);
+            Some(String::from(url))
+        })
+        .filter(|url| seen.insert(url.clone()))
+        .collect()
+}
+
+/// Same-origin links on `page`, with its user agent and cookies.
+pub async fn collect(page: &Page) -> Result<PageLinks> {
+    let raw: RawLinks = page
+        .evaluate(LINKS_SCRIPT)
+        .await
+        .context("Failed to collect links")?
+        .into_value()
+        .context("Failed to parse links")?;
+    let cookies = page.get_cookies().await.context("Failed to read cookies")?;
+
+    let cookie = (!cookies.is_empty()).then(|| {
+        cookies
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    });
+    Ok(PageLinks {
+        links: same_origin_links(&raw.url, raw.hrefs),
+        url: raw.url,
+        user_agent: raw.user_agent,
+        cookie,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_origin_links() {
+        let hrefs = [
+            "https://shop.example.com/cart",
+            "https://shop.example.com/cart#summary",
+            "https://shop.example.com/help?topic=returns",
+            "http://shop.example.com/insecure",
+            "https://cdn.example.com/logo.png",
+            "mailto:help@example.com",
+            "javascript:void(0)",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        assert_eq!(
+            same_origin_links("https://shop.example.com/", hrefs),
+            vec![
+                "https://shop.example.com/cart",
+                "https://shop.example.com/help?topic=returns",
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Upkeep for sessions that run for hours.
+//!
+//! An always-on session piles up state nobody asks for again: cached
+//! snapshot trees (but for those still being paged through), scroll positions of pages long left, hundreds of buffered
+//! events, garbage the renderer hasn't collected, the in-memory HTTP cache
+//! of its context, and sessions whose page was closed from the inside
+//! (`window.close()`, an adopted popup finishing). A maintenance pass clears
+//! all of these.
+//!
+//! Renderer processes still creep up however much is collected, so with
+//! `recycle_after` a session whose page is older than that is moved to a
+//! fresh page at the same URL, in a new process, loaded under the same
+//! robots.txt and throttle rules as any navigation. Cookies and localStorage
+//! live in the session's context and survive; sessionStorage and in-page
+//! script state don't. The old page stays open until the next pass, so an
+//! action already holding it can finish. Sessions holding state tied to
+//! their page (a recording or frame stream, a fake clock, zoom, a popup
+//! policy), adopted popups, and sessions of an attached Chrome are never
+//! recycled.
+
+use std::time::Duration;
+
+/// Scroll positions a session keeps before those of other pages are dropped.
+pub const MAX_SCROLL_POSITIONS: usize = 100;
+
+/// What a maintenance pass does, and how often.
+#[derive(Debug, Clone)]
+pub struct MaintenancePolicy {
+    /// How often the background pass runs
+    pub interval: Duration,
+    /// Recorded events kept per session and observer
+    pub keep_events: usize,
+    /// Give a session a fresh page once its page is this old
+    pub recycle_after: Option<Duration>,
+}
+
+impl Default for MaintenancePolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10 * 60),
+            keep_events: 100,
+            recycle_after: None,
+        }
+    }
+}
+
+impl MaintenancePolicy {
+    /// Whether a page created `age` ago is due for recycling.
+    pub fn due_for_recycle(&self, age: Duration) -> bool {
+        self.recycle_after.is_some_and(|after| age >= after)
+    }
+
+    /// Whether a cached snapshot taken `age` ago survives a pass: only one
+    /// a cursor was issued for (`paged`), until it is an interval old.
+    pub fn keeps_snapshot(&self, age: Duration, paged: bool) -> bool {
+        paged && age < self.interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recycling_is_opt_in() {
+        let policy = MaintenancePolicy::default();
+        assert!(!policy.due_for_recycle(Duration::from_secs(24 * 60 * 60)));
+
+        let policy = MaintenancePolicy {
+            recycle_after: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        assert!(!policy.due_for_recycle(Duration::from_secs(3599)));
+        assert!(policy.due_for_recycle(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_paged_snapshots_outlive_one_pass() {
+        let policy = MaintenancePolicy::default();
+        assert!(!policy.keeps_snapshot(Duration::ZERO, false));
+        assert!(policy.keeps_snapshot(Duration::from_secs(60), true));
+        assert!(!policy.keeps_snapshot(policy.interval, true));
+    }
+}
@@ -0,0 +1,299 @@
+//! `<audio>` and `<video>` control.
+//!
+//! Media elements are driven through their DOM API (`play()`, `pause()`,
+//! `currentTime`), with scripts evaluated as a user gesture so autoplay
+//! policy doesn't reject `play()`. Commands wait (briefly) for the element
+//! to react, and report its state afterwards so a test can tell whether the
+//! media actually loads and plays.
+//!
+//! Without a selector the first media element on the page is used; a
+//! selector matching a wrapper (a player's container) uses the first media
+//! element inside it. Elements in iframes and shadow roots aren't reached.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{MediaAction, MediaState};
+
+/// How long play/seek wait for the element to react, in milliseconds.
+const REACT_TIMEOUT_MS: u64 = 5_000;
+
+/// Seconds a seek may land from its target (players snap to keyframes).
+const SEEK_TOLERANCE: f64 = 0.5;
+
+const MEDIA_JS: &str = r#"const media = (sel) => {
+    if (sel === null) return document.querySelector('video, audio');
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    return el instanceof HTMLMediaElement ? el : el.querySelector('video, audio');
+};
+const errors = { 1: 'aborted', 2: 'network error', 3: 'decode error', 4: 'source not supported' };
+const state = (el) => {
+    const buffered = el.buffered.length ? el.buffered.end(el.buffered.length - 1) : 0;
+    const video = el.tagName === 'VIDEO';
+    return {
+        ref_id: el.hasAttribute('data-fgp-ref') ? '@' + el.getAttribute('data-fgp-ref') : null,
+        tag: el.tagName.toLowerCase(),
+        src: el.currentSrc || el.getAttribute('src') || null,
+        duration: isFinite(el.duration) ? el.duration : null,
+        live: el.duration === Infinity,
+        current_time: el.currentTime,
+        paused: el.paused,
+        ended: el.ended,
+        muted: el.muted,
+        volume: el.volume,
+        playback_rate: el.playbackRate,
+        ready_state: el.readyState,
+        playable: el.readyState >= 3,
+        buffered_until: buffered,
+        width: video && el.videoWidth ? el.videoWidth : null,
+        height: video && el.videoHeight ? el.videoHeight : null,
+        error: el.error ? (errors[el.error.code] || 'error') +
+            (el.error.message ? ': ' + el.error.message : '') : null,
+    };
+};
+const settle = (promise, ms) => Promise.race([
+    promise, new Promise((resolve) => setTimeout(resolve, ms))]);
+"#;
+
+const STATE_SCRIPT: &str = r#"(async (sel, waitMs) => {
+    const list = sel === null ? [...document.querySelectorAll('video, audio')] : [media(sel)];
+    if (list[0] === null) return { found: false };
+    const deadline = Date.now() + waitMs;
+    while (Date.now() < deadline && list.some((el) => el.readyState < 3 && !el.error)) {
+        await new Promise((resolve) => setTimeout(resolve, 50));
+    }
+    return { found: true, media: list.map(state) };
+})"#;
+
+const CONTROL_SCRIPT: &str = r#"(async (sel, action, time, timeoutMs) => {
+    const el = media(sel);
+    if (!el) return { found: false };
+    let error = null;
+    if (action === 'play') {
+        try {
+            await settle(el.play(), timeoutMs);
+        } catch (e) {
+            error = e.name + ': ' + e.message;
+        }
+    } else if (action === 'pause') {
+        el.pause();
+    } else {
+        const seeked = new Promise((resolve) => el.addEventListener('seeked', resolve, { once: true }));
+        el.currentTime = time;
+        await settle(seeked, timeoutMs);
+    }
+    return { found: true, media: [state(el)], error };
+})"#;
+
+/// Mutes every media element, and any that start playing later, until the
+/// page navigates.
+const MUTE_SCRIPT: &str = r#"((muted) => {
+    window.__fgpMuted = muted;
+    if (!window.__fgpMuteListener) {
+        window.__fgpMuteListener = true;
+        document.addEventListener('play', (e) => {
+            if (e.target instanceof HTMLMediaElement) e.target.muted = window.__fgpMuted;
+        }, true);
+    }
+    const all = document.querySelectorAll('video, audio');
+    all.forEach((el) => { el.muted = muted; });
+    return all.length;
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Probe {
+    found: bool,
+    #[serde(default)]
+    media: Vec<MediaState>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+async fn run<T: serde::de::DeserializeOwned>(page: &Page, script: String) -> Result<T> {
+    let params = EvaluateParams::builder()
+        .expression(format!("(() => {{ {}return {}; }})()", MEDIA_JS, script))
+        .user_gesture(true)
+        .await_promise(true)
+        .return_by_value(true)
+        .build()
+        .map_err(anyhow::Error::msg)?;
+    page.evaluate_expression(params)
+        .await
+        .context("Failed to reach media element")?
+        .into_value()
+        .context("Failed to parse media state")
+}
+
+fn not_found(selector: Option<&str>) -> anyhow::Error {
+    match selector {
+        Some(selector) => anyhow::anyhow!("No <video> or <audio> at or inside {}", selector),
+        None => anyhow::anyhow!("No <video> or <audio> on the page"),
+    }
+}
+
+/// Why `action` didn't take, judging by the element's state afterwards.
+fn problem(action: MediaAction, state: &MediaState, error: Option<String>) -> Option<String> {
+    if let Some(error) = error {
+        return Some(error);
+    }
+    if let Some(error) = &state.error {
+        return Some(format!("Media failed to load: {}", error));
+    }
+    match action {
+        MediaAction::Play if state.paused => Some("Still paused after play()".to_string()),
+        MediaAction::Play if !state.playable => {
+            Some("Playing but not enough data yet (buffering)".to_string())
+        }
+        MediaAction::Pause if !state.paused => Some("Still playing after pause()".to_string()),
+        MediaAction::Seek(target) => {
+            // Seeks past the end land on the end
+            let expected = state.duration.map_or(target, |d| target.min(d));
+            ((state.current_time - expected).abs() > SEEK_TOLERANCE).then(|| {
+                format!(
+                    "Seek to {}s landed at {}s (not seekable yet?)",
+                    target, state.current_time
+                )
+            })
+        }
+        _ => None,
+    }
+}
+
+/// State of the media element at `selector`, or of all media on the page.
+///
+/// With `wait_ms`, waits up to that long for them to become playable (or
+/// fail).
+pub async fn states(page: &Page, selector: Option<&str>, wait_ms: u64) -> Result<Vec<MediaState>> {
+    let sel = selector.map(resolve_selector);
+    let probe: Probe = run(
+        page,
+        format!(
+            "{}({}, {})",
+            STATE_SCRIPT,
+            serde_json::to_string(&sel)?,
+            wait_ms
+        ),
+    )
+    .await?;
+    if !probe.found {
+        return Err(not_found(selector));
+    }
+    Ok(probe.media)
+}
+
+/// Play, pause or seek; returns the state afterwards and what went wrong.
+pub async fn control(
+    page: &Page,
+    selector: Option<&str>,
+    action: MediaAction,
+) -> Result<(MediaState, Option<String>)> {
+    let sel = selector.map(resolve_selector);
+    let (name, time) = match action {
+        MediaAction::Play => ("play", 0.0),
+        MediaAction::Pause => ("pause", 0.0),
+        MediaAction::Seek(time) => ("seek", time),
+    };
+    let probe: Probe = run(
+        page,
+        format!(
+            "{}({}, {}, {}, {})",
+            CONTROL_SCRIPT,
+            serde_json::to_string(&sel)?,
+            serde_json::to_string(name)?,
+            time,
+            REACT_TIMEOUT_MS
+        ),
+    )
+    .await?;
+    if !probe.found {
+        return Err(not_found(selector));
+    }
+    let state = probe
+        .media
+        .into_iter()
+        .next()
+        .context("Media element returned no state")?;
+
+    let problem = problem(action, &state, probe.error);
+    Ok((state, problem))
+}
+
+/// Mute (or unmute) all media on the page; returns how many elements there
+/// are.
+pub async fn mute(page: &Page, muted: bool) -> Result<usize> {
+    run(page, format!("{}({})", MUTE_SCRIPT, muted)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing() -> MediaState {
+        MediaState {
+            ref_id: None,
+            tag: "video".to_string(),
+            src: Some("https://example.com/clip.mp4".to_string()),
+            duration: Some(60.0),
+            live: false,
+            current_time: 12.0,
+            paused: false,
+            ended: false,
+            muted: false,
+            volume: 1.0,
+            playback_rate: 1.0,
+            ready_state: 4,
+            playable: true,
+            buffered_until: 30.0,
+            width: Some(1280),
+            height: Some(720),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_play_problems() {
+        assert_eq!(problem(MediaAction::Play, &playing(), None), None);
+
+        let blocked = MediaState {
+            paused: true,
+            ..playing()
+        };
+        assert_eq!(
+            problem(
+                MediaAction::Play,
+                &blocked,
+                Some("NotAllowedError: play() failed".to_string())
+            )
+            .as_deref(),
+            Some("NotAllowedError: play() failed")
+        );
+
+        let broken = MediaState {
+            paused: true,
+            ready_state: 0,
+            playable: false,
+            error: Some("source not supported".to_string()),
+            ..playing()
+        };
+        assert_eq!(
+            problem(MediaAction::Play, &broken, None).as_deref(),
+            Some("Media failed to load: source not supported")
+        );
+    }
+
+    #[test]
+    fn test_seek_problems() {
+        assert_eq!(problem(MediaAction::Seek(12.2), &playing(), None), None);
+        // Past the end lands on the end
+        let at_end = MediaState {
+            current_time: 60.0,
+            ..playing()
+        };
+        assert_eq!(problem(MediaAction::Seek(90.0), &at_end, None), None);
+        assert!(problem(MediaAction::Seek(40.0), &playing(), None).is_some());
+    }
+}
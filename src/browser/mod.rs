@@ -1,6 +1,82 @@
 //! Browser automation module.
 
 mod aria;
+mod assertions;
+mod autofill;
+mod bandwidth;
+mod blockers;
+mod canvas;
+mod capabilities;
+mod captcha;
+mod cdp;
+mod chain;
 mod client;
+mod clock;
+mod csp;
+mod date;
+mod drag;
+mod dry_run;
+mod environment;
+mod events;
+mod field;
+mod find;
+mod focus;
+mod form;
+mod frames;
+mod gate;
+mod keyboard;
+mod links;
+mod maintenance;
+mod media;
+mod navigation;
+mod notifications;
+mod oopif;
+mod overlay;
+mod own_mutations;
+mod paging;
+mod popups;
+mod print;
+mod ranking;
+mod recovery;
+mod refs;
+mod replay;
+mod results;
+mod rich_text;
+mod screencast;
+mod screenshot;
+mod scroll;
+mod select;
+mod selection;
+mod shadow;
+mod snapshot_diff;
+mod snapshot_filter;
+mod spatial;
+mod stability;
+mod table;
+mod third_party;
+mod timeouts;
+mod upload;
+mod variants;
+mod xpath;
+mod xvfb;
+mod zoom;
 
+pub use aria::render_text as render_aria_text;
+pub use cdp::{domain as cdp_domain, CdpDomains};
+pub use chain::is_chained;
 pub use client::BrowserClient;
+pub use drag::{DEFAULT_DRAG_STEPS, MAX_DRAG_STEPS};
+pub use keyboard::KeyboardLayout;
+pub use links::PageLinks;
+pub use maintenance::MaintenancePolicy;
+pub use paging::DEFAULT_PAGE_SIZE;
+pub use ranking::PICKED_SELECTOR;
+pub use recovery::{is_recoverable, RECOVERY_RETRIES, RETRY_DELAY_MS};
+pub use screencast::{Frame, Screencast, ScreencastConflict, ScreencastOptions};
+pub use screenshot::{
+    DEFAULT_PAINT_FRAMES, MAX_PAINT_FRAMES, MAX_QUALITY as MAX_SCREENSHOT_QUALITY,
+    MAX_SCALE as MAX_SCREENSHOT_SCALE,
+};
+pub use timeouts::{limit_from_ms, TimedOut, TimeoutKind, Timeouts, MAX_TIMEOUT};
+pub use xpath::is_xpath;
+pub use zoom::{MAX_ZOOM, MIN_ZOOM};
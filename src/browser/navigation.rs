@@ -0,0 +1,258 @@
+//! Navigation with a choice of when it counts as done.
+//!
+//! Chrome reports a new document's progress as lifecycle events: commit,
+//! `DOMContentLoaded`, `load`, and `networkIdle` once no requests have been
+//! in flight for 500ms. Single-page apps often fetch and render their
+//! content well after `load`, so a caller about to snapshot one can wait
+//! for `networkidle` instead; a caller that only needs the URL can stop at
+//! `commit`. Waiting stops at the requested condition or when the
+//! navigation's time limit runs out, and the result reports the last
+//! condition reached, so a page that never goes idle still returns rather
+//! than failing. A navigation that hasn't committed by then fails with
+//! `TimedOut`.
+//!
+//! Reloads and history moves wait the same way. They don't say which
+//! document they load, so it is taken from the main frame's next
+//! navigation; a history entry made by `pushState` stays in the same
+//! document and counts as loaded straight away.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::network::LoaderId;
+use chromiumoxide::cdp::browser_protocol::page::{
+    EventFrameNavigated, EventLifecycleEvent, EventNavigatedWithinDocument,
+    GetNavigationHistoryParams, NavigateParams, NavigateToHistoryEntryParams, ReloadParams,
+    SetLifecycleEventsEnabledParams,
+};
+use chromiumoxide::error::CdpError;
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+
+use super::timeouts::{TimedOut, TimeoutKind};
+use crate::models::WaitUntil;
+
+/// What the command starting a navigation says about it.
+enum Started {
+    /// A new document, already committed
+    Document(LoaderId),
+    /// A fragment or `pushState` entry of the current document
+    SameDocument,
+    /// Known once the main frame navigates
+    Pending,
+}
+
+/// Condition a lifecycle event satisfies.
+fn condition(event: &str) -> Option<WaitUntil> {
+    match event {
+        "commit" => Some(WaitUntil::Commit),
+        "DOMContentLoaded" => Some(WaitUntil::DomContentLoaded),
+        "load" => Some(WaitUntil::Load),
+        "networkIdle" => Some(WaitUntil::NetworkIdle),
+        _ => None,
+    }
+}
+
+/// Run `start` and wait for `wait_until` on the document it loads, for at
+/// most `limit`; returns the condition reached and how long it took.
+async fn run<F>(
+    page: &Page,
+    wait_until: WaitUntil,
+    limit: Duration,
+    start: F,
+) -> Result<(WaitUntil, Duration)>
+where
+    F: Future<Output = Result<Started>>,
+{
+    page.execute(SetLifecycleEventsEnabledParams::new(true))
+        .await
+        .context("Failed to enable lifecycle events")?;
+    let mut lifecycle = page
+        .event_listener::<EventLifecycleEvent>()
+        .await
+        .context("Failed to listen for lifecycle events")?;
+    let mut navigated = page
+        .event_listener::<EventFrameNavigated>()
+        .await
+        .context("Failed to listen for navigations")?;
+    let mut within_document = page
+        .event_listener::<EventNavigatedWithinDocument>()
+        .await
+        .context("Failed to listen for navigations")?;
+    let main_frame = page.mainframe().await.ok().flatten();
+
+    let timed_out = || TimedOut {
+        kind: TimeoutKind::Navigation,
+        limit,
+    };
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + limit;
+    let started_as = tokio::time::timeout_at(deadline, start)
+        .await
+        .map_err(|_| timed_out())??;
+    let mut loader = match started_as {
+        Started::Document(loader) => Some(loader),
+        // The document was already loaded
+        Started::SameDocument => {
+            return Ok((wait_until.min(WaitUntil::Load), started.elapsed()));
+        }
+        Started::Pending => None,
+    };
+    // Committed means at least `commit`
+    let mut reached = loader.as_ref().map(|_| WaitUntil::Commit);
+    // Lifecycle events that came in before the document was known
+    let mut early: Vec<(LoaderId, WaitUntil)> = Vec::new();
+
+    while reached < Some(wait_until) {
+        tokio::select! {
+            Some(event) = lifecycle.next() => {
+                if let Some(condition) = condition(&event.name) {
+                    match &loader {
+                        Some(loader) if *loader == event.loader_id => {
+                            reached = reached.max(Some(condition));
+                        }
+                        Some(_) => {}
+                        None => early.push((event.loader_id.clone(), condition)),
+                    }
+                }
+            }
+            Some(event) = navigated.next(), if loader.is_none() => {
+                if event.frame.parent_id.is_none() {
+                    let document = event.frame.loader_id.clone();
+                    reached = early
+                        .iter()
+                        .filter(|(id, _)| *id == document)
+                        .map(|(_, condition)| *condition)
+                        .chain([WaitUntil::Commit])
+                        .max();
+                    loader = Some(document);
+                }
+            }
+            Some(event) = within_document.next(), if loader.is_none() => {
+                if main_frame.as_ref() == Some(&event.frame_id) {
+                    return Ok((wait_until.min(WaitUntil::Load), started.elapsed()));
+                }
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                tracing::debug!(
+                    "Navigation reached {:?} but not {:?} within {}ms",
+                    reached,
+                    wait_until,
+                    limit.as_millis()
+                );
+                break;
+            }
+        }
+    }
+
+    let reached = reached.ok_or_else(timed_out)?;
+    Ok((reached, started.elapsed()))
+}
+
+/// Navigate `page` to `url` and wait for `wait_until`, for at most `limit`.
+pub async fn navigate(
+    page: &Page,
+    url: &str,
+    wait_until: WaitUntil,
+    limit: Duration,
+) -> Result<(WaitUntil, Duration)> {
+    run(page, wait_until, limit, async {
+        let navigated = match page.execute(NavigateParams::new(url)).await {
+            Ok(response) => response.result,
+            // A server slow to answer outlasts chromiumoxide's per-command
+            // timeout; the commit still shows up as a frame navigation
+            Err(CdpError::Timeout) => return Ok(Started::Pending),
+            Err(e) => return Err(e).context("Navigation failed"),
+        };
+        if let Some(error) = navigated.error_text.filter(|e| !e.is_empty()) {
+            anyhow::bail!("Navigation failed: {}", error);
+        }
+        // Page.navigate returns once the new document is committed
+        Ok(match navigated.loader_id {
+            Some(loader) => Started::Document(loader),
+            None => Started::SameDocument,
+        })
+    })
+    .await
+}
+
+/// Reload `page`, bypassing the cache with `ignore_cache`, and wait for
+/// `wait_until`, for at most `limit`.
+pub async fn reload(
+    page: &Page,
+    ignore_cache: bool,
+    wait_until: WaitUntil,
+    limit: Duration,
+) -> Result<(WaitUntil, Duration)> {
+    run(page, wait_until, limit, async {
+        page.execute(ReloadParams::builder().ignore_cache(ignore_cache).build())
+            .await
+            .context("Reload failed")?;
+        Ok(Started::Pending)
+    })
+    .await
+}
+
+/// Move `delta` entries through `page`'s history (-1 is back) and wait for
+/// `wait_until`, for at most `limit`.
+pub async fn go(
+    page: &Page,
+    delta: i64,
+    wait_until: WaitUntil,
+    limit: Duration,
+) -> Result<(WaitUntil, Duration)> {
+    let history = page
+        .execute(GetNavigationHistoryParams::default())
+        .await
+        .context("Failed to read history")?
+        .result;
+    let entry = usize::try_from(history.current_index + delta)
+        .ok()
+        .and_then(|index| history.entries.get(index))
+        .with_context(|| {
+            if delta < 0 {
+                "No page to go back to"
+            } else {
+                "No page to go forward to"
+            }
+        })?;
+
+    run(page, wait_until, limit, async {
+        page.execute(NavigateToHistoryEntryParams::new(entry.id))
+            .await
+            .context("History navigation failed")?;
+        Ok(Started::Pending)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle_conditions_in_order() {
+        let order: Vec<_> = [
+            "commit",
+            "DOMContentLoaded",
+            "firstPaint",
+            "load",
+            "networkIdle",
+        ]
+        .into_iter()
+        .filter_map(condition)
+        .collect();
+        assert_eq!(
+            order,
+            vec![
+                WaitUntil::Commit,
+                WaitUntil::DomContentLoaded,
+                WaitUntil::Load,
+                WaitUntil::NetworkIdle
+            ]
+        );
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(condition("networkAlmostIdle"), None);
+    }
+}
@@ -0,0 +1,210 @@
+//! Web Notification capture.
+//!
+//! Headless Chrome never shows notifications, so flows that end in "you
+//! should see a notification" can't be checked by looking. Every document
+//! gets a `Notification` replacement (and a `showNotification` wrapper on
+//! service worker registrations) that reports through a CDP binding instead
+//! of showing anything. Permission reads as granted, so apps that ask first
+//! go ahead.
+//!
+//! Only secure contexts get the shim, as only they have the real API.
+//! Notifications shown from inside a service worker (e.g. on push) aren't
+//! seen; only calls made from the page are.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::page::AddScriptToEvaluateOnNewDocumentParams;
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled};
+use chromiumoxide::page::Page;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::events::forward_events;
+use crate::models::{NotificationList, SessionEvent, WebNotification};
+
+/// Notifications kept per session; older ones are dropped.
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// Page-side function the shim reports through.
+const BINDING: &str = "__fgpNotify";
+
+const SHIM_SCRIPT: &str = r#"(() => {
+    if (window.__fgpNotifications || !window.isSecureContext || typeof __fgpNotify !== 'function') return;
+    window.__fgpNotifications = true;
+    const send = __fgpNotify;
+    const record = (title, options, source) => {
+        options = options || {};
+        send(JSON.stringify({
+            title: String(title),
+            body: options.body ? String(options.body) : null,
+            tag: options.tag ? String(options.tag) : null,
+            icon: options.icon ? String(options.icon) : null,
+            origin: location.origin,
+            source,
+            timestamp: new Date().toISOString(),
+        }));
+    };
+
+    class Notification extends EventTarget {
+        constructor(title, options = {}) {
+            if (arguments.length === 0) {
+                throw new TypeError("Failed to construct 'Notification': 1 argument required, but only 0 present.");
+            }
+            super();
+            this.title = String(title);
+            this.body = options.body || '';
+            this.tag = options.tag || '';
+            this.icon = options.icon || '';
+            this.data = options.data === undefined ? null : options.data;
+            this.onshow = this.onclick = this.onclose = this.onerror = null;
+            record(title, options, 'page');
+            setTimeout(() => this.#fire('show'));
+        }
+        #fire(type) {
+            const event = new Event(type);
+            if (typeof this['on' + type] === 'function') this['on' + type](event);
+            this.dispatchEvent(event);
+        }
+        close() {
+            this.#fire('close');
+        }
+        static get permission() {
+            return 'granted';
+        }
+        static get maxActions() {
+            return 2;
+        }
+        static requestPermission(callback) {
+            if (typeof callback === 'function') callback('granted');
+            return Promise.resolve('granted');
+        }
+    }
+    window.Notification = Notification;
+
+    if (window.ServiceWorkerRegistration) {
+        ServiceWorkerRegistration.prototype.showNotification = function (title, options) {
+            record(title, options, 'service_worker');
+            return Promise.resolve();
+        };
+    }
+    if (navigator.permissions) {
+        const query = navigator.permissions.query.bind(navigator.permissions);
+        navigator.permissions.query = (descriptor) => descriptor && descriptor.name === 'notifications'
+            ? Promise.resolve({ name: 'notifications', state: 'granted', onchange: null,
+                addEventListener() {}, removeEventListener() {} })
+            : query(descriptor);
+    }
+})()"#;
+
+/// Notifications a session's pages have shown, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct Inbox(Arc<Mutex<Shown>>);
+
+#[derive(Debug, Default)]
+struct Shown {
+    notifications: VecDeque<WebNotification>,
+    dropped: u64,
+}
+
+impl Inbox {
+    fn push(&self, notification: WebNotification) {
+        let mut shown = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if shown.notifications.len() == MAX_NOTIFICATIONS {
+            shown.notifications.pop_front();
+            shown.dropped += 1;
+        }
+        shown.notifications.push_back(notification);
+    }
+
+    /// Captured notifications, emptying the inbox when `clear`.
+    pub fn list(&self, clear: bool) -> NotificationList {
+        let mut shown = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let list = NotificationList {
+            notifications: shown.notifications.iter().cloned().collect(),
+            dropped: shown.dropped,
+        };
+        if clear {
+            *shown = Shown::default();
+        }
+        list
+    }
+}
+
+/// Install the shim in `page` and its future documents, recording what it
+/// reports in `inbox` and as session events from the returned task.
+pub async fn capture(
+    page: &Page,
+    sender: &broadcast::Sender<SessionEvent>,
+    inbox: &Inbox,
+) -> Result<JoinHandle<()>> {
+    // Listening first buffers reports made while the shim is installed
+    let calls = page
+        .event_listener::<EventBindingCalled>()
+        .await
+        .context("Failed to listen for notifications")?;
+
+    page.execute(AddBindingParams::new(BINDING))
+        .await
+        .context("Failed to add notification binding")?;
+    page.execute(AddScriptToEvaluateOnNewDocumentParams::new(SHIM_SCRIPT))
+        .await
+        .context("Failed to install notification shim")?;
+    // The current document predates the script
+    page.evaluate(SHIM_SCRIPT)
+        .await
+        .context("Failed to install notification shim")?;
+
+    let recorded = inbox.clone();
+    Ok(forward_events(calls, sender.clone(), move |call| {
+        if call.name != BINDING {
+            return None;
+        }
+        match serde_json::from_str::<WebNotification>(&call.payload) {
+            Ok(notification) => {
+                recorded.push(notification.clone());
+                Some(SessionEvent::Notification(notification))
+            }
+            Err(e) => {
+                tracing::debug!("Unreadable notification from page: {}", e);
+                None
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NotificationSource;
+
+    fn notification(title: &str) -> WebNotification {
+        WebNotification {
+            title: title.to_string(),
+            body: None,
+            tag: None,
+            icon: None,
+            origin: "https://example.com".to_string(),
+            source: NotificationSource::Page,
+            timestamp: "2024-03-15T10:00:00.000Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_inbox_drops_oldest_and_clears() {
+        let inbox = Inbox::default();
+        for i in 0..MAX_NOTIFICATIONS + 2 {
+            inbox.push(notification(&format!("#{}", i)));
+        }
+
+        let list = inbox.list(true);
+        assert_eq!(list.notifications.len(), MAX_NOTIFICATIONS);
+        assert_eq!(list.notifications[0].title, "#2");
+        assert_eq!(list.dropped, 2);
+
+        let list = inbox.list(false);
+        assert!(list.notifications.is_empty());
+        assert_eq!(list.dropped, 0);
+    }
+}
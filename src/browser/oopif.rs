@@ -0,0 +1,344 @@
+//! Out-of-process iframes.
+//!
+//! With site isolation, a cross-site iframe renders in a process of its own
+//! and is a CDP target of its own: the page's session only sees its
+//! `<iframe>` element. chromiumoxide only drives page targets, so the daemon
+//! keeps a second DevTools connection for these frames. A page is attached
+//! on it the first time its frames are listed, with `Target.setAutoAttach`
+//! (flattened) so that every iframe target below it gets a session on the
+//! same socket, and commands for a frame in another process go to its
+//! session.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::page::FrameId;
+use chromiumoxide::cdp::browser_protocol::target::TargetId;
+use chromiumoxide::types::Command;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+type Reply = oneshot::Sender<Result<Value, String>>;
+
+/// A DevTools connection reaching pages' out-of-process frames.
+#[derive(Clone)]
+pub struct Connection {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    outgoing: mpsc::UnboundedSender<String>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    /// Calls waiting for their response, by message ID
+    pending: HashMap<u64, Reply>,
+    /// Session of each page attached so far, by target ID
+    pages: HashMap<String, String>,
+    /// Out-of-process frames, in the order they were attached
+    frames: Vec<Attached>,
+}
+
+struct Attached {
+    session: String,
+    /// Session it was attached from: its page's or another frame's
+    parent: String,
+    /// The frame the target renders (its target ID)
+    frame: FrameId,
+}
+
+impl State {
+    /// Drop `session` and every frame attached from it.
+    fn forget(&mut self, session: &str) {
+        self.pages.retain(|_, page| page != session);
+        let mut gone = vec![session.to_string()];
+        while let Some(session) = gone.pop() {
+            self.frames.retain(|frame| {
+                if frame.parent == session {
+                    gone.push(frame.session.clone());
+                }
+                frame.session != session && frame.parent != session
+            });
+        }
+    }
+}
+
+fn lock(state: &Mutex<State>) -> std::sync::MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Params attaching to every iframe target below a session, running.
+fn auto_attach() -> Value {
+    json!({
+        "autoAttach": true,
+        "waitForDebuggerOnStart": false,
+        "flatten": true,
+        "filter": [{ "type": "iframe" }],
+    })
+}
+
+impl Shared {
+    /// Send a call; its result arrives on the returned channel.
+    fn send(
+        &self,
+        session: Option<&str>,
+        method: &str,
+        params: Value,
+    ) -> oneshot::Receiver<Result<Value, String>> {
+        let (reply, response) = oneshot::channel();
+        let mut state = lock(&self.state);
+        state.next_id += 1;
+        let id = state.next_id;
+        let mut message = json!({ "id": id, "method": method, "params": params });
+        if let Some(session) = session {
+            message["sessionId"] = session.into();
+        }
+        // A closed connection drops `reply`, failing the call
+        if self.outgoing.send(message.to_string()).is_ok() {
+            state.pending.insert(id, reply);
+        }
+        response
+    }
+
+    fn receive(&self, message: Value) {
+        if let Some(id) = message["id"].as_u64() {
+            let reply = lock(&self.state).pending.remove(&id);
+            if let Some(reply) = reply {
+                let result = match message.get("error") {
+                    Some(error) => Err(error["message"]
+                        .as_str()
+                        .unwrap_or("unknown error")
+                        .to_string()),
+                    None => Ok(message["result"].clone()),
+                };
+                let _ = reply.send(result);
+            }
+            return;
+        }
+
+        let params = &message["params"];
+        match message["method"].as_str() {
+            Some("Target.attachedToTarget") if params["targetInfo"]["type"] == "iframe" => {
+                let (Some(session), Some(parent), Some(target)) = (
+                    params["sessionId"].as_str(),
+                    message["sessionId"].as_str(),
+                    params["targetInfo"]["targetId"].as_str(),
+                ) else {
+                    return;
+                };
+                lock(&self.state).frames.push(Attached {
+                    session: session.to_string(),
+                    parent: parent.to_string(),
+                    frame: FrameId::new(target),
+                });
+                // Frames in other processes below this one attach to it
+                drop(self.send(Some(session), "Target.setAutoAttach", auto_attach()));
+            }
+            Some("Target.detachedFromTarget") => {
+                if let Some(session) = params["sessionId"].as_str() {
+                    lock(&self.state).forget(session);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Connection {
+    /// Open a connection to the browser's DevTools endpoint `url`.
+    pub async fn open(url: &str) -> Result<Self> {
+        let (socket, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .context("Failed to open a DevTools connection for frames")?;
+        let (mut sink, mut stream) = socket.split();
+        let (outgoing, mut queue) = mpsc::unbounded_channel::<String>();
+        let shared = Arc::new(Shared {
+            outgoing,
+            state: Default::default(),
+        });
+
+        tokio::spawn(async move {
+            while let Some(text) = queue.recv().await {
+                if sink.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let reader = Arc::downgrade(&shared);
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = stream.next().await {
+                let Some(shared) = reader.upgrade() else {
+                    break;
+                };
+                if let Message::Text(text) = message {
+                    if let Ok(message) = serde_json::from_str(&text) {
+                        shared.receive(message);
+                    }
+                }
+            }
+            if let Some(shared) = reader.upgrade() {
+                tracing::warn!("DevTools connection for frames closed");
+                let mut state = lock(&shared.state);
+                state.pending.clear();
+                state.pages.clear();
+                state.frames.clear();
+            }
+        });
+
+        Ok(Self { shared })
+    }
+
+    async fn call(&self, session: Option<&str>, method: &str, params: Value) -> Result<Value> {
+        match self.shared.send(session, method, params).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(message)) => anyhow::bail!("{} failed: {}", method, message),
+            Err(_) => anyhow::bail!("{} failed: DevTools connection closed", method),
+        }
+    }
+
+    /// Session of `page`, attached with its frames on first use.
+    async fn page_session(&self, page: &TargetId) -> Result<String> {
+        if let Some(session) = lock(&self.shared.state).pages.get(page.inner()) {
+            return Ok(session.clone());
+        }
+
+        let attached = self
+            .call(
+                None,
+                "Target.attachToTarget",
+                json!({ "targetId": page.inner(), "flatten": true }),
+            )
+            .await?;
+        let session = attached["sessionId"]
+            .as_str()
+            .context("Target.attachToTarget returned no session")?
+            .to_string();
+        lock(&self.shared.state)
+            .pages
+            .insert(page.inner().clone(), session.clone());
+        self.call(Some(&session), "Target.setAutoAttach", auto_attach())
+            .await?;
+
+        // Frames already there attach before the call returns; have the
+        // ones among them attach theirs before listing anything
+        let mut done = HashSet::new();
+        loop {
+            let frames: Vec<String> = self
+                .remotes(&session)
+                .into_iter()
+                .map(|remote| remote.session)
+                .filter(|frame| !done.contains(frame))
+                .collect();
+            if frames.is_empty() {
+                return Ok(session);
+            }
+            for frame in frames {
+                let _ = self
+                    .call(Some(&frame), "Target.setAutoAttach", auto_attach())
+                    .await;
+                done.insert(frame);
+            }
+        }
+    }
+
+    /// Out-of-process frames of `page`, parents before their children.
+    pub async fn frames(&self, page: &TargetId) -> Result<Vec<Remote>> {
+        let session = self.page_session(page).await?;
+        Ok(self.remotes(&session))
+    }
+
+    fn remotes(&self, page: &str) -> Vec<Remote> {
+        let state = lock(&self.shared.state);
+        let mut remotes = Vec::new();
+        self.collect(&state.frames, page, None, &mut remotes);
+        remotes
+    }
+
+    fn collect(
+        &self,
+        frames: &[Attached],
+        parent: &str,
+        holder: Option<&Remote>,
+        out: &mut Vec<Remote>,
+    ) {
+        for frame in frames.iter().filter(|frame| frame.parent == parent) {
+            let remote = Remote {
+                connection: self.clone(),
+                session: frame.session.clone(),
+                root: frame.frame.clone(),
+                holder: holder.cloned().map(Box::new),
+            };
+            out.push(remote.clone());
+            self.collect(frames, &frame.session, Some(&remote), out);
+        }
+    }
+}
+
+/// An out-of-process frame's session.
+#[derive(Clone)]
+pub struct Remote {
+    connection: Connection,
+    session: String,
+    /// The frame the process renders, held by an `<iframe>` outside it
+    pub root: FrameId,
+    /// Frame process that `<iframe>` is in; `None` for the page's
+    pub holder: Option<Box<Remote>>,
+}
+
+impl Remote {
+    /// Send `cmd` to the frame's process.
+    pub async fn execute<C: Command>(&self, cmd: C) -> Result<C::Response> {
+        let method = cmd.identifier();
+        let params = serde_json::to_value(&cmd)?;
+        let result = self
+            .connection
+            .call(Some(&self.session), &method, params)
+            .await?;
+        serde_json::from_value(result).with_context(|| format!("Unexpected {} result", method))
+    }
+
+    /// Whether both are the same session (`None`: the page's).
+    pub fn same(a: Option<&Remote>, b: Option<&Remote>) -> bool {
+        a.map(|remote| &remote.session) == b.map(|remote| &remote.session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attached(session: &str, parent: &str) -> Attached {
+        Attached {
+            session: session.to_string(),
+            parent: parent.to_string(),
+            frame: FrameId::new(session.to_uppercase()),
+        }
+    }
+
+    #[test]
+    fn test_forget_drops_nested_frames() {
+        let mut state = State::default();
+        state.pages.insert("PAGE".to_string(), "page".to_string());
+        state.frames = vec![
+            attached("ads", "page"),
+            attached("pay", "page"),
+            attached("card", "pay"),
+            attached("3ds", "card"),
+        ];
+
+        state.forget("pay");
+        let left: Vec<&str> = state.frames.iter().map(|f| f.session.as_str()).collect();
+        assert_eq!(left, vec!["ads"]);
+        assert_eq!(state.pages.len(), 1);
+
+        state.forget("page");
+        assert!(state.frames.is_empty());
+        assert!(state.pages.is_empty());
+    }
+}
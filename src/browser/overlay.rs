@@ -0,0 +1,219 @@
+//! Debugging overlays drawn into the page.
+//!
+//! Overlays are plain elements tagged `data-fgp-overlay`, marked
+//! `aria-hidden` and `pointer-events: none`, so they show up in headful
+//! windows, screenshots, and screencasts without entering the ARIA snapshot,
+//! intercepting clicks, or invalidating the snapshot cache.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use crate::models::ElementRect;
+
+/// Default outline color for highlights.
+pub const DEFAULT_HIGHLIGHT_COLOR: &str = "#ff3366";
+
+/// Outline an element with an optional label.
+///
+/// The element is scrolled into view first if it's outside the viewport. Any
+/// previous highlight is replaced; a `duration_ms` of 0 keeps the highlight
+/// until the next one. Returns the element's viewport rect, or None if the
+/// selector matched nothing.
+pub async fn highlight(
+    page: &Page,
+    css_selector: &str,
+    label: Option<&str>,
+    duration_ms: u64,
+    color: &str,
+) -> Result<Option<ElementRect>> {
+    let script = format!(
+        r#"((sel, label, duration, color) => {{
+            const el = document.querySelector(sel);
+            if (!el) return {{ found: false }};
+
+            document.querySelectorAll('[data-fgp-overlay="highlight"]').forEach(n => n.remove());
+
+            let r = el.getBoundingClientRect();
+            if (r.bottom < 0 || r.top > innerHeight || r.right < 0 || r.left > innerWidth) {{
+                el.scrollIntoView({{ behavior: 'instant', block: 'center' }});
+                r = el.getBoundingClientRect();
+            }}
+
+            const box = document.createElement('div');
+            box.setAttribute('data-fgp-overlay', 'highlight');
+            box.setAttribute('aria-hidden', 'true');
+            Object.assign(box.style, {{
+                position: 'absolute',
+                left: (r.left + scrollX) + 'px',
+                top: (r.top + scrollY) + 'px',
+                width: r.width + 'px',
+                height: r.height + 'px',
+                outline: '3px solid ' + color,
+                outlineOffset: '1px',
+                pointerEvents: 'none',
+                zIndex: '2147483647',
+                boxSizing: 'border-box',
+            }});
+
+            if (label) {{
+                const tag = document.createElement('div');
+                tag.textContent = label;
+                Object.assign(tag.style, {{
+                    position: 'absolute',
+                    left: '-3px',
+                    bottom: '100%',
+                    marginBottom: '4px',
+                    padding: '1px 6px',
+                    background: color,
+                    color: '#fff',
+                    font: '12px/1.4 monospace',
+                    whiteSpace: 'nowrap',
+                    borderRadius: '2px',
+                }});
+                box.appendChild(tag);
+            }}
+
+            document.documentElement.appendChild(box);
+            if (duration > 0) setTimeout(() => box.remove(), duration);
+
+            return {{ found: true, rect: {{ x: r.left, y: r.top, width: r.width, height: r.height }} }};
+        }})({}, {}, {}, {})"#,
+        serde_json::to_string(css_selector)?,
+        serde_json::to_string(&label)?,
+        duration_ms,
+        serde_json::to_string(color)?,
+    );
+
+    #[derive(Deserialize)]
+    struct Drawn {
+        rect: Option<ElementRect>,
+    }
+
+    let drawn: Drawn = page
+        .evaluate(script)
+        .await
+        .context("Failed to draw highlight")?
+        .into_value()
+        .context("Failed to parse highlight result")?;
+
+    Ok(drawn.rect)
+}
+
+/// Move the synthetic cursor to viewport point `(x, y)`, with a click ripple.
+///
+/// The cursor persists between calls (until the document changes or it's
+/// hidden), so recordings show the pointer travelling between interactions.
+pub async fn draw_cursor(page: &Page, x: f64, y: f64, click: bool) -> Result<()> {
+    let script = format!(
+        r##"((x, y, click) => {{
+            let cursor = document.querySelector('[data-fgp-overlay="cursor"]');
+            if (!cursor) {{
+                cursor = document.createElement('div');
+                cursor.setAttribute('data-fgp-overlay', 'cursor');
+                cursor.setAttribute('aria-hidden', 'true');
+                cursor.innerHTML =
+                    '<svg width="20" height="24" viewBox="0 0 20 24">' +
+                    '<path d="M1 1 L1 19 L6 14 L9.5 22 L12.5 20.6 L9 13 L16 13 Z" ' +
+                    'fill="#000" stroke="#fff" stroke-width="1.5" stroke-linejoin="round"/></svg>';
+                Object.assign(cursor.style, {{
+                    position: 'fixed',
+                    width: '20px',
+                    height: '24px',
+                    pointerEvents: 'none',
+                    zIndex: '2147483647',
+                    transition: 'left 120ms ease-out, top 120ms ease-out',
+                }});
+                document.documentElement.appendChild(cursor);
+            }}
+            cursor.style.left = (x - 1) + 'px';
+            cursor.style.top = (y - 1) + 'px';
+
+            if (click) {{
+                const ripple = document.createElement('div');
+                ripple.setAttribute('data-fgp-overlay', 'ripple');
+                ripple.setAttribute('aria-hidden', 'true');
+                Object.assign(ripple.style, {{
+                    position: 'fixed',
+                    left: (x - 20) + 'px',
+                    top: (y - 20) + 'px',
+                    width: '40px',
+                    height: '40px',
+                    borderRadius: '50%',
+                    border: '3px solid #ff3366',
+                    boxSizing: 'border-box',
+                    pointerEvents: 'none',
+                    zIndex: '2147483646',
+                }});
+                document.documentElement.appendChild(ripple);
+                ripple.animate(
+                    [{{ transform: 'scale(0.3)', opacity: 1 }}, {{ transform: 'scale(1.4)', opacity: 0 }}],
+                    {{ duration: 600, easing: 'ease-out' }}
+                ).onfinish = () => ripple.remove();
+            }}
+        }})({}, {}, {})"##,
+        x, y, click
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to draw cursor")?;
+    Ok(())
+}
+
+/// Remove the synthetic cursor and any in-flight click ripples.
+pub async fn hide_cursor(page: &Page) -> Result<()> {
+    page.evaluate(
+        r#"document.querySelectorAll('[data-fgp-overlay="cursor"], [data-fgp-overlay="ripple"]')
+            .forEach(n => n.remove())"#,
+    )
+    .await
+    .context("Failed to hide cursor")?;
+    Ok(())
+}
+
+/// Outline `rect` (document CSS pixels) for an annotated screenshot.
+///
+/// Unlike highlights, the marker is positioned from a rect measured by the
+/// caller so it lines up exactly with the capture clip, and it leaves any
+/// existing highlight alone.
+pub async fn draw_marker(page: &Page, rect: &ElementRect, color: &str) -> Result<()> {
+    let script = format!(
+        r#"((r, color) => {{
+            document.querySelectorAll('[data-fgp-overlay="marker"]').forEach(n => n.remove());
+            const box = document.createElement('div');
+            box.setAttribute('data-fgp-overlay', 'marker');
+            box.setAttribute('aria-hidden', 'true');
+            Object.assign(box.style, {{
+                position: 'absolute',
+                left: r.x + 'px',
+                top: r.y + 'px',
+                width: r.width + 'px',
+                height: r.height + 'px',
+                outline: '3px solid ' + color,
+                outlineOffset: '1px',
+                pointerEvents: 'none',
+                zIndex: '2147483647',
+                boxSizing: 'border-box',
+            }});
+            document.documentElement.appendChild(box);
+        }})({}, {})"#,
+        serde_json::to_string(rect)?,
+        serde_json::to_string(color)?,
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to draw marker")?;
+    Ok(())
+}
+
+/// Remove the screenshot marker.
+pub async fn remove_marker(page: &Page) -> Result<()> {
+    page.evaluate(
+        r#"document.querySelectorAll('[data-fgp-overlay="marker"]').forEach(n => n.remove())"#,
+    )
+    .await
+    .context("Failed to remove marker")?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! The gateway's own changes to a page's DOM.
+//!
+//! Snapshots tag elements with refs, subtree filters and XPath and chained
+//! selector resolution mark their targets, and overlays draw highlights and
+//! the synthetic cursor. None of that is the page changing, so both the
+//! snapshot cache's mutation tracker and the stability monitor leave it out
+//! with the same rule.
+
+use super::snapshot_filter::ROOT_ATTRIBUTE;
+
+/// Attributes the gateway writes on page elements.
+pub const FGP_ATTRIBUTES: &[&str] = &[
+    "data-fgp-ref",
+    ROOT_ATTRIBUTE,
+    "data-fgp-xpath",
+    "data-fgp-chain",
+];
+
+/// JS statements defining `ignored(record)`: whether a `MutationObserver`
+/// record only touches gateway attributes or `[data-fgp-overlay]` elements.
+pub fn ignore_script() -> String {
+    let attributes: Vec<String> = FGP_ATTRIBUTES
+        .iter()
+        .map(|name| format!("'{}'", name))
+        .collect();
+    format!(
+        r#"const FGP_ATTRIBUTES = [{}];
+        const isOverlay = (n) => {{
+            const el = n && (n.nodeType === 1 ? n : n.parentElement);
+            return !!(el && el.closest('[data-fgp-overlay]'));
+        }};
+        const ignored = (r) =>
+            (r.type === 'attributes' && FGP_ATTRIBUTES.includes(r.attributeName)) ||
+            isOverlay(r.target) ||
+            (r.type === 'childList' &&
+                [...r.addedNodes, ...r.removedNodes].every(isOverlay));"#,
+        attributes.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_gateway_attribute_is_ignored() {
+        let script = ignore_script();
+        assert!(script.contains(
+            "const FGP_ATTRIBUTES = ['data-fgp-ref', 'data-fgp-root', 'data-fgp-xpath', 'data-fgp-chain'];"
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+//! Cursor-based pagination of ARIA snapshots.
+//!
+//! A page is a slice of the snapshot's nodes in document order. The cursor
+//! names the snapshot it was cut from, so continuation pages are served from
+//! the same snapshot (and the same `@eN` refs) even if the page changes.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+
+use crate::models::{AriaNode, AriaSnapshot};
+
+/// Default nodes per page when a cursor is given without an explicit limit.
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+
+/// Appended to node text shortened to fit the size limit.
+pub const TRUNCATION_MARKER: &str = "…[truncated]";
+
+/// Bytes reserved for `next_cursor`, `truncated`, and separators.
+const ENVELOPE_ALLOWANCE: usize = 128;
+
+/// Least text kept per field when shrinking a node that can't fit whole.
+const MIN_FIELD_BYTES: usize = 64;
+
+/// Position within a specific snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotCursor {
+    pub snapshot_id: String,
+    pub offset: usize,
+}
+
+impl SnapshotCursor {
+    /// Encode as an opaque URL-safe token.
+    pub fn encode(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(format!("{}:{}", self.snapshot_id, self.offset))
+    }
+
+    /// Decode a token produced by [`SnapshotCursor::encode`].
+    pub fn decode(token: &str) -> Result<Self> {
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token)
+            .context("Invalid snapshot cursor")?;
+        let raw = String::from_utf8(raw).context("Invalid snapshot cursor")?;
+        let (snapshot_id, offset) = raw.rsplit_once(':').context("Invalid snapshot cursor")?;
+
+        Ok(Self {
+            snapshot_id: snapshot_id.to_string(),
+            offset: offset.parse().context("Invalid snapshot cursor offset")?,
+        })
+    }
+}
+
+/// A cursor cut from a snapshot that is no longer cached.
+#[derive(Debug, Clone)]
+pub struct SnapshotCursorExpired {
+    pub snapshot_id: String,
+}
+
+impl std::fmt::Display for SnapshotCursorExpired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SNAPSHOT_CURSOR_EXPIRED: snapshot {} is no longer cached - take a new snapshot without a cursor",
+            self.snapshot_id
+        )
+    }
+}
+
+impl std::error::Error for SnapshotCursorExpired {}
+
+/// Find the snapshot `cursor` was cut from among the cached `(id, snapshot)`
+/// pairs.
+pub fn resume<'a>(
+    cursor: &SnapshotCursor,
+    cached: impl IntoIterator<Item = (&'a str, &'a AriaSnapshot)>,
+) -> Result<(&'a str, &'a AriaSnapshot), SnapshotCursorExpired> {
+    cached
+        .into_iter()
+        .find(|(id, _)| *id == cursor.snapshot_id)
+        .ok_or_else(|| SnapshotCursorExpired {
+            snapshot_id: cursor.snapshot_id.clone(),
+        })
+}
+
+/// Cut one page out of a snapshot.
+///
+/// The page holds at most `limit` nodes and, approximately, at most
+/// `max_bytes` of JSON. When the byte budget ends the page early, `truncated`
+/// is set and `next_cursor` continues from the first node left out; a single
+/// node too large for the budget on its own has its text shortened instead.
+/// `element_count` keeps the total so callers know how much remains.
+pub fn paginate(
+    mut snapshot: AriaSnapshot,
+    snapshot_id: &str,
+    offset: usize,
+    limit: usize,
+    max_bytes: usize,
+) -> AriaSnapshot {
+    let limit = limit.max(1);
+    let total = snapshot.nodes.len();
+    let start = offset.min(total);
+    let mut end = start.saturating_add(limit).min(total);
+
+    let nodes = std::mem::take(&mut snapshot.nodes);
+    let mut used = json_len(&snapshot) + ENVELOPE_ALLOWANCE;
+    snapshot.nodes = nodes;
+
+    for i in start..end {
+        let size = json_len(&snapshot.nodes[i]) + 1;
+        if used + size <= max_bytes {
+            used += size;
+            continue;
+        }
+
+        snapshot.truncated = true;
+        if i == start {
+            // Always make progress, even if it means shortening the node
+            shrink_node(&mut snapshot.nodes[i], max_bytes.saturating_sub(used));
+            end = i + 1;
+        } else {
+            end = i;
+        }
+        break;
+    }
+
+    snapshot.nodes = snapshot.nodes.drain(start..end).collect();
+    snapshot.offset = start;
+    snapshot.next_cursor = (end < total).then(|| {
+        SnapshotCursor {
+            snapshot_id: snapshot_id.to_string(),
+            offset: end,
+        }
+        .encode()
+    });
+
+    snapshot
+}
+
+fn json_len<T: serde::Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
+}
+
+/// Shorten a node's name and value so it fits (roughly) in `budget` bytes.
+fn shrink_node(node: &mut AriaNode, budget: usize) {
+    let (name, value) = (node.name.take(), node.value.take());
+    let overhead = json_len(node);
+    let per_field = budget.saturating_sub(overhead) / 2;
+
+    node.name = name.map(|text| truncate_text(text, per_field));
+    node.value = value.map(|text| truncate_text(text, per_field));
+}
+
+/// Cut `text` to about `max_bytes` (but never below [`MIN_FIELD_BYTES`]),
+/// marking the cut.
+fn truncate_text(text: String, max_bytes: usize) -> String {
+    let mut end = max_bytes
+        .saturating_sub(TRUNCATION_MARKER.len())
+        .max(MIN_FIELD_BYTES);
+    if end >= text.len() {
+        return text;
+    }
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &text[..end], TRUNCATION_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AriaNode;
+
+    fn snapshot_with(count: usize) -> AriaSnapshot {
+        AriaSnapshot {
+            url: "https://example.com".to_string(),
+            title: "Example".to_string(),
+            nodes: (1..=count)
+                .map(|i| AriaNode {
+                    ref_id: format!("@e{}", i),
+                    role: "button".to_string(),
+                    name: None,
+                    value: None,
+                    focusable: true,
+                    focused: false,
+                    rect: None,
+                    in_viewport: false,
+                    children: vec![],
+                })
+                .collect(),
+            element_count: count,
+            cached: false,
+            offset: 0,
+            next_cursor: None,
+            truncated: false,
+            scroll: None,
+            focused: None,
+        }
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = SnapshotCursor {
+            snapshot_id: "3f2a-uuid".to_string(),
+            offset: 1500,
+        };
+        assert_eq!(SnapshotCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(SnapshotCursor::decode("not a cursor!").is_err());
+    }
+
+    #[test]
+    fn test_resume_expired_cursor() {
+        let (latest, view) = (snapshot_with(3), snapshot_with(1));
+        let cached = [("snap", &latest), ("view", &view)];
+        let cursor = |id: &str| SnapshotCursor {
+            snapshot_id: id.to_string(),
+            offset: 1,
+        };
+
+        let (id, snapshot) = resume(&cursor("view"), cached).unwrap();
+        assert_eq!((id, snapshot.element_count), ("view", 1));
+
+        let error = anyhow::Error::new(resume(&cursor("older"), cached).unwrap_err());
+        assert!(error.to_string().starts_with("SNAPSHOT_CURSOR_EXPIRED: "));
+        assert!(error.downcast_ref::<SnapshotCursorExpired>().is_some());
+        assert!(resume(&cursor("snap"), []).is_err());
+    }
+
+    #[test]
+    fn test_paginate_walks_all_nodes_in_order() {
+        let mut offset = 0;
+        let mut seen = Vec::new();
+
+        loop {
+            let page = paginate(snapshot_with(5), "snap", offset, 2, usize::MAX);
+            assert_eq!(page.element_count, 5);
+            assert_eq!(page.offset, offset);
+            seen.extend(page.nodes.iter().map(|n| n.ref_id.clone()));
+
+            match page.next_cursor {
+                Some(token) => {
+                    let cursor = SnapshotCursor::decode(&token).unwrap();
+                    assert_eq!(cursor.snapshot_id, "snap");
+                    offset = cursor.offset;
+                }
+                None => break,
+            }
+        }
+
+        assert_eq!(seen, vec!["@e1", "@e2", "@e3", "@e4", "@e5"]);
+    }
+
+    #[test]
+    fn test_paginate_past_end_is_empty() {
+        let page = paginate(snapshot_with(3), "snap", 10, 2, usize::MAX);
+        assert!(page.nodes.is_empty());
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_paginate_cuts_at_byte_budget() {
+        let full = paginate(snapshot_with(50), "snap", 0, usize::MAX, usize::MAX);
+        let budget = serde_json::to_vec(&full).unwrap().len() / 2;
+
+        let page = paginate(snapshot_with(50), "snap", 0, usize::MAX, budget);
+        assert!(page.truncated);
+        assert!(page.nodes.len() < 50);
+        assert!(serde_json::to_vec(&page).unwrap().len() <= budget);
+
+        let cursor = SnapshotCursor::decode(page.next_cursor.as_deref().unwrap()).unwrap();
+        assert_eq!(cursor.offset, page.nodes.len());
+
+        // Fits whole: untouched, no flag
+        let page = paginate(snapshot_with(3), "snap", 0, usize::MAX, 1 << 20);
+        assert!(!page.truncated);
+        assert_eq!(page.nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_paginate_shrinks_oversized_node() {
+        let mut snapshot = snapshot_with(2);
+        snapshot.nodes[0].name = Some("x".repeat(100_000));
+
+        let page = paginate(snapshot, "snap", 0, usize::MAX, 4096);
+        assert!(page.truncated);
+        assert_eq!(page.nodes.len(), 1);
+        let name = page.nodes[0].name.as_deref().unwrap();
+        assert!(name.ends_with(TRUNCATION_MARKER));
+        assert!(name.len() < 4096);
+        assert!(page.next_cursor.is_some());
+    }
+
+    #[test]
+    fn test_truncate_text_respects_char_boundaries() {
+        let text = "é".repeat(100);
+        let cut = truncate_text(text, 81);
+        assert!(cut.ends_with(TRUNCATION_MARKER));
+        assert!(cut.len() <= 81 + TRUNCATION_MARKER.len());
+        assert_eq!(truncate_text("short".to_string(), 10), "short");
+    }
+}
@@ -0,0 +1,285 @@
+//! What happens when a page opens a new tab or window.
+//!
+//! By default popups open as Chrome opens them, in tabs no session drives.
+//! A session can instead keep them in its own tab (`same_tab`: `target=_blank`
+//! links and forms are retargeted to `_self`, and `window.open(url)`
+//! navigates the page), adopt each one as a new session (`attach`), or
+//! refuse them (`block`: `window.open` returns `null` and new-tab links do
+//! nothing).
+//!
+//! The page-side hooks cover ordinary clicks and `window.open`. Popups they
+//! miss (Ctrl+click, middle click, `noopener` windows) are caught when Chrome
+//! reports the new target, and closed, after navigating the opener to the
+//! popup's URL under `same_tab`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, RemoveScriptToEvaluateOnNewDocumentParams,
+    ScriptIdentifier,
+};
+use chromiumoxide::cdp::browser_protocol::target::{
+    CloseTargetParams, EventTargetCreated, TargetId,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+
+use crate::models::{PopupPolicy, SessionEvent};
+
+/// Installs the `window.open` and new-tab link hooks once per document and
+/// sets the mode they follow.
+const POLICY_SCRIPT: &str = r#"((mode) => {
+    const key = Symbol.for('fgp.popups');
+    if (window[key]) {
+        window[key].mode = mode;
+        return;
+    }
+    const state = { mode };
+    Object.defineProperty(window, key, { value: state });
+
+    const newTab = (target) => {
+        target = (target || document.querySelector('base[target]')?.target || '').toLowerCase();
+        return target !== '' && !['_self', '_parent', '_top'].includes(target);
+    };
+    const open = window.open;
+    window.open = function (url, target, features) {
+        if (state.mode === 'block' && newTab(target || '_blank')) return null;
+        if (state.mode === 'same_tab' && newTab(target || '_blank')) {
+            if (url !== undefined && String(url) !== '') {
+                location.assign(new URL(String(url), location.href).href);
+            }
+            return window;
+        }
+        return open.apply(this, arguments);
+    };
+
+    const retarget = (event, element) => {
+        if (!element || !newTab(element.getAttribute('target'))) return;
+        if (state.mode === 'block') event.preventDefault();
+        else element.setAttribute('target', '_self');
+    };
+    document.addEventListener('click', (event) => {
+        if (state.mode !== 'same_tab' && state.mode !== 'block') return;
+        retarget(event, event.composedPath().find(
+            (node) => node instanceof Element && node.matches('a[href], area[href]')));
+    }, true);
+    document.addEventListener('submit', (event) => {
+        if (state.mode !== 'same_tab' && state.mode !== 'block') return;
+        retarget(event, event.target);
+    }, true);
+})"#;
+
+/// Stands down hooks already in the document.
+const ALLOW_SCRIPT: &str = r#"(() => {
+    const state = window[Symbol.for('fgp.popups')];
+    if (state) state.mode = 'allow';
+})()"#;
+
+/// A session whose page has a popup policy other than `allow`.
+pub struct Opener {
+    pub session_id: String,
+    pub policy: PopupPolicy,
+    pub page: Page,
+    pub events: broadcast::Sender<SessionEvent>,
+}
+
+/// A popup adopted under `attach`, waiting to be made a session.
+pub struct Popup {
+    pub session_id: String,
+    pub target_id: TargetId,
+    /// Session whose page opened it
+    pub opener: String,
+}
+
+#[derive(Default)]
+struct Tracker {
+    /// Openers by their page's target
+    openers: HashMap<TargetId, Opener>,
+    /// Popups to adopt, oldest first
+    adopted: Vec<Popup>,
+    /// Popups adopted so far, for session IDs
+    count: u64,
+}
+
+/// Popup policies of a browser's sessions, shared with the task watching
+/// for new targets.
+#[derive(Clone, Default)]
+pub struct Popups(Arc<Mutex<Tracker>>);
+
+/// What to do about a new target.
+enum Action {
+    Attach(String),
+    Close(Page, Option<String>),
+}
+
+impl Popups {
+    /// Apply `opener.policy` to popups from the page at `target`; `allow`
+    /// stops watching it.
+    pub fn watch(&self, target: TargetId, opener: Opener) {
+        let mut tracker = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if opener.policy == PopupPolicy::Allow {
+            tracker.openers.remove(&target);
+        } else {
+            tracker.openers.insert(target, opener);
+        }
+    }
+
+    /// Stop watching the page at `target`, e.g. when its session closes.
+    pub fn unwatch(&self, target: &TargetId) {
+        let mut tracker = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        tracker.openers.remove(target);
+    }
+
+    /// Popups adopted since the last call.
+    pub fn take_adopted(&self) -> Vec<Popup> {
+        let mut tracker = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut tracker.adopted)
+    }
+
+    /// Decide on a page `target` opened by `opener`, reporting it to the
+    /// opener's session.
+    fn opened(&self, target: &TargetId, opener: &TargetId, url: &str) -> Option<Action> {
+        let mut tracker = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let (session_id, policy, page, events) = {
+            let opener = tracker.openers.get(opener)?;
+            (
+                opener.session_id.clone(),
+                opener.policy,
+                opener.page.clone(),
+                opener.events.clone(),
+            )
+        };
+
+        let (action, popup_session) = match policy {
+            PopupPolicy::Allow => return None,
+            PopupPolicy::Attach => {
+                tracker.count += 1;
+                let id = format!("{}-popup-{}", session_id, tracker.count);
+                tracker.adopted.push(Popup {
+                    session_id: id.clone(),
+                    target_id: target.clone(),
+                    opener: session_id,
+                });
+                (Action::Attach(id.clone()), Some(id))
+            }
+            PopupPolicy::SameTab => {
+                let follow = (!url.is_empty() && url != "about:blank").then(|| url.to_string());
+                (Action::Close(page, follow), None)
+            }
+            PopupPolicy::Block => (Action::Close(page, None), None),
+        };
+        let _ = events.send(SessionEvent::Popup {
+            url: url.to_string(),
+            policy,
+            session_id: popup_session,
+        });
+        Some(action)
+    }
+}
+
+/// The page script for `policy`; `None` when the page keeps its own
+/// `window.open` and links.
+fn script(policy: PopupPolicy) -> Option<String> {
+    let mode = match policy {
+        PopupPolicy::SameTab => "same_tab",
+        PopupPolicy::Block => "block",
+        PopupPolicy::Allow | PopupPolicy::Attach => return None,
+    };
+    Some(format!("{}({:?})", POLICY_SCRIPT, mode))
+}
+
+/// Set the page hooks for `policy` on this and later documents, replacing
+/// `previous`; returns the script to replace next time.
+pub async fn apply(
+    page: &Page,
+    policy: PopupPolicy,
+    previous: Option<ScriptIdentifier>,
+) -> Result<Option<ScriptIdentifier>> {
+    if let Some(previous) = previous {
+        let _ = page
+            .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(previous))
+            .await;
+    }
+
+    let Some(script) = script(policy) else {
+        page.evaluate(ALLOW_SCRIPT)
+            .await
+            .context("Failed to set popup policy")?;
+        return Ok(None);
+    };
+    let identifier = page
+        .execute(AddScriptToEvaluateOnNewDocumentParams::new(script.clone()))
+        .await
+        .context("Failed to set popup policy")?
+        .result
+        .identifier;
+    // The current document predates the script
+    page.evaluate(script)
+        .await
+        .context("Failed to set popup policy")?;
+    Ok(Some(identifier))
+}
+
+/// Watch `browser` for pages opened by watched sessions and apply their
+/// policies, until the browser goes away.
+pub async fn track(browser: &Browser, popups: &Popups) -> Result<()> {
+    let mut created = browser
+        .event_listener::<EventTargetCreated>()
+        .await
+        .context("Failed to listen for new targets")?;
+
+    let popups = popups.clone();
+    tokio::spawn(async move {
+        while let Some(event) = created.next().await {
+            let info = &event.target_info;
+            let Some(opener) = info.opener_id.as_ref() else {
+                continue;
+            };
+            if info.r#type != "page" {
+                continue;
+            }
+            match popups.opened(&info.target_id, opener, &info.url) {
+                None => {}
+                Some(Action::Attach(session_id)) => {
+                    tracing::info!("Popup {} adopted as session {}", info.url, session_id);
+                }
+                Some(Action::Close(page, follow)) => {
+                    if let Err(e) = page
+                        .execute(CloseTargetParams::new(info.target_id.clone()))
+                        .await
+                    {
+                        tracing::warn!("Failed to close popup {}: {}", info.url, e);
+                    }
+                    if let Some(url) = follow {
+                        if let Err(e) = page.goto(url.as_str()).await {
+                            tracing::warn!("Failed to open popup {} in its opener: {}", url, e);
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_only_for_page_side_policies() {
+        assert!(script(PopupPolicy::SameTab)
+            .unwrap()
+            .ends_with(r#"})("same_tab")"#));
+        assert!(script(PopupPolicy::Block)
+            .unwrap()
+            .ends_with(r#"})("block")"#));
+        assert!(script(PopupPolicy::Allow).is_none());
+        assert!(script(PopupPolicy::Attach).is_none());
+    }
+}
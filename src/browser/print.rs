@@ -0,0 +1,276 @@
+//! Print previews: the page rendered with `media: print`, one image per page.
+//!
+//! The viewport is resized to the printable area of the paper (96 CSS pixels
+//! per inch, minus margins) and print media is emulated, so `@media print`
+//! rules and print-only letterheads apply as they would on paper. The
+//! document is then cut into page-height slices, breaking early wherever an
+//! element asks for a page break (`break-before`/`break-after: page`). This
+//! approximates Chrome's paginator; `@page` rules and fragmentation inside
+//! elements aren't reproduced. An optional PDF from `Page.printToPDF` gives
+//! the exact printed output; [`print_pdf`] also exports one on its own, with
+//! per-side margins and header and footer templates.
+//!
+//! Emulation is undone afterwards, even if a capture fails.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    SetDeviceMetricsOverrideParams, SetEmulatedMediaParams,
+};
+use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::screenshot::{self, Image, Region};
+use super::zoom::{self, Zoom};
+use crate::models::{ElementRect, Margins, PdfOptions, PrintOptions};
+
+/// CSS pixels per inch.
+const CSS_DPI: f64 = 96.0;
+
+/// Most pages captured in one preview.
+const MAX_PAGES: usize = 50;
+
+/// Breaks closer than this to the previous one are ignored (no blank pages).
+const MIN_PAGE_HEIGHT: f64 = 1.0;
+
+/// Stands in for a missing header or footer, which Chrome would otherwise
+/// fill with its own (date and title, or URL and page numbers).
+const EMPTY_TEMPLATE: &str = "<span></span>";
+
+const LAYOUT_SCRIPT: &str = r#"(() => {
+    const doc = document.documentElement;
+    const breaks = [];
+    const forced = (v) => ['page', 'always', 'left', 'right', 'recto', 'verso'].includes(v);
+    for (const el of document.body ? document.body.querySelectorAll('*') : []) {
+        const style = getComputedStyle(el);
+        if (style.display === 'none') continue;
+        if (forced(style.breakBefore) || forced(style.breakAfter)) {
+            const r = el.getBoundingClientRect();
+            if (forced(style.breakBefore)) breaks.push(r.top + scrollY);
+            if (forced(style.breakAfter)) breaks.push(r.bottom + scrollY);
+        }
+    }
+    return { height: Math.max(doc.scrollHeight, doc.clientHeight), breaks };
+})()"#;
+
+/// Rendered print preview, before the pages are stored.
+pub struct PrintCapture {
+    /// Printable area of a page in CSS pixels
+    pub page_width: f64,
+    pub page_height: f64,
+    pub total_pages: usize,
+    /// Clip and base64 PNG of each captured page
+    pub pages: Vec<(ElementRect, String)>,
+    /// Base64 PDF, if requested
+    pub pdf: Option<String>,
+}
+
+/// Printable area of a page in CSS pixels.
+fn printable_size(options: &PrintOptions) -> (f64, f64) {
+    let (width, height) = options.paper.inches();
+    let (width, height) = if options.landscape {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    (
+        ((width - 2.0 * options.margin) * CSS_DPI).max(MIN_PAGE_HEIGHT),
+        ((height - 2.0 * options.margin) * CSS_DPI).max(MIN_PAGE_HEIGHT),
+    )
+}
+
+/// Split a document into `(top, height)` page slices no taller than
+/// `page_height`, starting a new page at each forced break.
+fn paginate(doc_height: f64, page_height: f64, forced: &[f64]) -> Vec<(f64, f64)> {
+    let mut forced: Vec<f64> = forced
+        .iter()
+        .copied()
+        .filter(|y| y.is_finite() && *y > 0.0 && *y < doc_height)
+        .collect();
+    forced.sort_by(|a, b| a.total_cmp(b));
+
+    let mut pages = Vec::new();
+    let mut top = 0.0;
+    while top < doc_height || pages.is_empty() {
+        let full = top + page_height;
+        let bottom = forced
+            .iter()
+            .copied()
+            .find(|y| *y >= top + MIN_PAGE_HEIGHT && *y < full)
+            .unwrap_or(full);
+        pages.push((top, bottom - top));
+        top = bottom;
+    }
+    pages
+}
+
+/// Render `page` with print media and capture up to `MAX_PAGES` page images,
+/// putting back `zoom` afterwards.
+pub async fn capture_pages(
+    page: &Page,
+    options: &PrintOptions,
+    zoom: Option<&Zoom>,
+) -> Result<PrintCapture> {
+    let (page_width, page_height) = printable_size(options);
+
+    page.execute(SetEmulatedMediaParams::builder().media("print").build())
+        .await
+        .context("Failed to emulate print media")?;
+    let result = async {
+        let metrics = SetDeviceMetricsOverrideParams::builder()
+            .width(page_width.round() as i64)
+            .height(page_height.round() as i64)
+            .device_scale_factor(options.scale.unwrap_or(1.0))
+            .mobile(false)
+            .build()
+            .map_err(anyhow::Error::msg)?;
+        page.execute(metrics)
+            .await
+            .context("Failed to resize viewport to paper")?;
+        capture_emulated(page, page_width, page_height).await
+    }
+    .await;
+
+    zoom::restore(page, zoom).await?;
+    page.execute(SetEmulatedMediaParams::builder().media("").build())
+        .await
+        .context("Failed to restore screen media")?;
+
+    let mut capture = result?;
+    if options.pdf {
+        let pdf = PdfOptions {
+            paper: options.paper,
+            landscape: options.landscape,
+            margins: Margins::uniform(options.margin),
+            header: None,
+            footer: None,
+            background: true,
+        };
+        capture.pdf = Some(print_pdf(page, &pdf).await?);
+    }
+    Ok(capture)
+}
+
+async fn capture_emulated(page: &Page, page_width: f64, page_height: f64) -> Result<PrintCapture> {
+    #[derive(Deserialize)]
+    struct Layout {
+        height: f64,
+        #[serde(default)]
+        breaks: Vec<f64>,
+    }
+
+    let layout: Layout = page
+        .evaluate(LAYOUT_SCRIPT)
+        .await
+        .context("Failed to measure print layout")?
+        .into_value()
+        .context("Failed to parse print layout")?;
+
+    let slices = paginate(layout.height, page_height, &layout.breaks);
+    let mut pages = Vec::new();
+    for (top, height) in slices.iter().take(MAX_PAGES) {
+        let clip = ElementRect {
+            x: 0.0,
+            y: *top,
+            width: page_width,
+            height: *height,
+        };
+        pages.push((
+            clip,
+            screenshot::capture(page, Region::Clip(clip), Image::default()).await?,
+        ));
+    }
+
+    Ok(PrintCapture {
+        page_width,
+        page_height,
+        total_pages: slices.len(),
+        pages,
+        pdf: None,
+    })
+}
+
+/// Print `page` to a PDF with Chrome's print pipeline; returns it base64'd.
+pub async fn print_pdf(page: &Page, options: &PdfOptions) -> Result<String> {
+    let (width, height) = options.paper.inches();
+    let mut params = PrintToPdfParams::builder()
+        .paper_width(width)
+        .paper_height(height)
+        .landscape(options.landscape)
+        .margin_top(options.margins.top)
+        .margin_bottom(options.margins.bottom)
+        .margin_left(options.margins.left)
+        .margin_right(options.margins.right)
+        .print_background(options.background);
+    if options.header.is_some() || options.footer.is_some() {
+        params = params
+            .display_header_footer(true)
+            .header_template(options.header.as_deref().unwrap_or(EMPTY_TEMPLATE))
+            .footer_template(options.footer.as_deref().unwrap_or(EMPTY_TEMPLATE));
+    }
+    let response = page
+        .execute(params.build())
+        .await
+        .context("Failed to print PDF (only supported in headless mode)")?;
+    Ok(response.result.data.into())
+}
+
+/// Size in bytes of the data in a base64 string.
+pub fn decoded_len(encoded: &str) -> usize {
+    let padding = encoded.bytes().rev().take_while(|b| *b == b'=').count();
+    (encoded.len() / 4 * 3).saturating_sub(padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PaperSize;
+
+    fn options(paper: PaperSize, landscape: bool, margin: f64) -> PrintOptions {
+        PrintOptions {
+            paper,
+            landscape,
+            margin,
+            scale: None,
+            pdf: false,
+        }
+    }
+
+    #[test]
+    fn test_printable_size() {
+        assert_eq!(
+            printable_size(&options(PaperSize::Letter, false, 0.5)),
+            (720.0, 960.0)
+        );
+        assert_eq!(
+            printable_size(&options(PaperSize::Letter, true, 0.5)),
+            (960.0, 720.0)
+        );
+    }
+
+    #[test]
+    fn test_decoded_len() {
+        assert_eq!(decoded_len(""), 0);
+        assert_eq!(decoded_len("JVBERi0="), 5);
+        assert_eq!(decoded_len("JVBERg=="), 4);
+        assert_eq!(decoded_len("JVBERi0x"), 6);
+    }
+
+    #[test]
+    fn test_paginate_fills_pages() {
+        assert_eq!(
+            paginate(2500.0, 1000.0, &[]),
+            vec![(0.0, 1000.0), (1000.0, 1000.0), (2000.0, 1000.0)]
+        );
+        // An empty document is still one page
+        assert_eq!(paginate(0.0, 1000.0, &[]), vec![(0.0, 1000.0)]);
+    }
+
+    #[test]
+    fn test_paginate_honors_forced_breaks() {
+        assert_eq!(
+            paginate(1500.0, 1000.0, &[400.0, 400.5, 0.0, 9000.0]),
+            vec![(0.0, 400.0), (400.0, 1000.0), (1400.0, 1000.0)]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Choosing one of several elements a selector matches.
+//!
+//! Interactions act on the first match in document order, which for
+//! selectors like `button.submit` is often a hidden template or an
+//! off-screen duplicate. A ranking strategy picks the match a user would
+//! act on instead. The chosen element is marked with `data-fgp-pick` and
+//! addressed by that attribute, so every interaction (and its dry run)
+//! resolves the same element.
+//!
+//! Strategies other than `document` prefer visible matches, and fall back
+//! to the first match when none qualifies. `topmost` hit-tests each match's
+//! center, so it only tells matches inside the viewport apart.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{ElementRanking, ElementRect, RankedMatch};
+
+/// Selector of the element picked by the last ranking.
+pub const PICKED_SELECTOR: &str = "[data-fgp-pick]";
+
+/// Matches whose tops are this close (CSS pixels) are on the same line for
+/// `reading_order`.
+const ROW_TOLERANCE: f64 = 8.0;
+
+const CANDIDATES_SCRIPT: &str = r#"((sel) => {
+    document.querySelectorAll('[data-fgp-pick]').forEach((el) => el.removeAttribute('data-fgp-pick'));
+    return [...document.querySelectorAll(sel)].map((el) => {
+        const r = el.getBoundingClientRect();
+        const visible = r.width > 0 && r.height > 0 &&
+            el.checkVisibility({ visibilityProperty: true });
+        const in_viewport = r.bottom > 0 && r.right > 0 && r.top < innerHeight && r.left < innerWidth;
+        let on_top = false;
+        if (visible && in_viewport) {
+            const x = Math.min(Math.max(r.left + r.width / 2, 0), innerWidth - 1);
+            const y = Math.min(Math.max(r.top + r.height / 2, 0), innerHeight - 1);
+            const hit = document.elementFromPoint(x, y);
+            on_top = !!hit && (hit === el || el.contains(hit) || (hit.control && hit.control === el));
+        }
+        return {
+            rect: { x: r.left, y: r.top, width: r.width, height: r.height },
+            visible, in_viewport, on_top,
+        };
+    });
+})"#;
+
+const MARK_SCRIPT: &str = r#"((sel, index) => {
+    const el = document.querySelectorAll(sel)[index];
+    if (!el) return false;
+    el.setAttribute('data-fgp-pick', '');
+    return true;
+})"#;
+
+/// A match as the page lays it out.
+#[derive(Debug, Clone, Deserialize)]
+struct Candidate {
+    /// Viewport CSS pixels
+    rect: ElementRect,
+    visible: bool,
+    in_viewport: bool,
+    on_top: bool,
+}
+
+/// Index of the match `strategy` picks, if there are any.
+fn rank(strategy: ElementRanking, candidates: &[Candidate]) -> Option<usize> {
+    if candidates.is_empty() {
+        return None;
+    }
+    let visible: Vec<usize> = (0..candidates.len())
+        .filter(|&i| candidates[i].visible)
+        .collect();
+    let first_where = |keep: fn(&Candidate) -> bool| {
+        visible
+            .iter()
+            .copied()
+            .find(|&i| keep(&candidates[i]))
+            .or(visible.first().copied())
+    };
+
+    let picked = match strategy {
+        ElementRanking::Document => Some(0),
+        ElementRanking::InViewport => first_where(|c| c.in_viewport),
+        ElementRanking::Topmost => first_where(|c| c.on_top),
+        ElementRanking::Largest => visible.iter().copied().fold(None, |best, i| {
+            let area = |i: usize| candidates[i].rect.width * candidates[i].rect.height;
+            match best {
+                Some(b) if area(b) >= area(i) => Some(b),
+                _ => Some(i),
+            }
+        }),
+        ElementRanking::ReadingOrder => {
+            let top = visible
+                .iter()
+                .map(|&i| candidates[i].rect.y)
+                .fold(f64::INFINITY, f64::min);
+            visible
+                .iter()
+                .copied()
+                .filter(|&i| candidates[i].rect.y - top <= ROW_TOLERANCE)
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(b) if candidates[b].rect.x <= candidates[i].rect.x => Some(b),
+                    _ => Some(i),
+                })
+        }
+    };
+    picked.or(Some(0))
+}
+
+/// Mark the match of `selector` that `strategy` picks; interactions then
+/// address it with [`PICKED_SELECTOR`].
+pub async fn pick(page: &Page, selector: &str, strategy: ElementRanking) -> Result<RankedMatch> {
+    let css = serde_json::to_string(&resolve_selector(selector))?;
+    let candidates: Vec<Candidate> = page
+        .evaluate(format!("{}({})", CANDIDATES_SCRIPT, css))
+        .await
+        .context("Failed to rank matches")?
+        .into_value()
+        .context("Failed to parse matches")?;
+    let index =
+        rank(strategy, &candidates).with_context(|| format!("Element not found: {}", selector))?;
+
+    let marked: bool = page
+        .evaluate(format!("{}({}, {})", MARK_SCRIPT, css, index))
+        .await
+        .context("Failed to mark ranked element")?
+        .into_value()
+        .context("Failed to mark ranked element")?;
+    if !marked {
+        anyhow::bail!("Element changed while ranking: {}", selector);
+    }
+
+    Ok(RankedMatch {
+        strategy,
+        matches: candidates.len(),
+        index,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(x: f64, y: f64, size: f64, visible: bool) -> Candidate {
+        Candidate {
+            rect: ElementRect {
+                x,
+                y,
+                width: size,
+                height: size,
+            },
+            visible,
+            in_viewport: visible && y < 800.0,
+            on_top: false,
+        }
+    }
+
+    #[test]
+    fn test_rank_strategies() {
+        let candidates = vec![
+            // Hidden template
+            candidate(0.0, 0.0, 0.0, false),
+            // Below the fold
+            candidate(10.0, 1200.0, 80.0, true),
+            candidate(300.0, 104.0, 40.0, true),
+            Candidate {
+                on_top: true,
+                ..candidate(20.0, 100.0, 40.0, true)
+            },
+        ];
+
+        assert_eq!(rank(ElementRanking::Document, &candidates), Some(0));
+        assert_eq!(rank(ElementRanking::InViewport, &candidates), Some(2));
+        assert_eq!(rank(ElementRanking::Largest, &candidates), Some(1));
+        assert_eq!(rank(ElementRanking::Topmost, &candidates), Some(3));
+        // Same line as #2 within the tolerance, further left
+        assert_eq!(rank(ElementRanking::ReadingOrder, &candidates), Some(3));
+    }
+
+    #[test]
+    fn test_rank_falls_back_to_first_match() {
+        let hidden = vec![
+            candidate(0.0, 0.0, 0.0, false),
+            candidate(0.0, 0.0, 0.0, false),
+        ];
+        for strategy in [
+            ElementRanking::InViewport,
+            ElementRanking::Largest,
+            ElementRanking::Topmost,
+            ElementRanking::ReadingOrder,
+        ] {
+            assert_eq!(rank(strategy, &hidden), Some(0));
+        }
+        assert_eq!(rank(ElementRanking::Largest, &[]), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Reload-and-retry recovery for element actions.
+//!
+//! With recovery on, an element action that fails because its target is
+//! missing or stale is retried a few times; if it still fails, the page is
+//! reloaded (or, when that fails, the URL it was on is loaded again) and the
+//! action gets one more try. Refs don't survive a reload, so a ref target is
+//! fingerprinted from the session's snapshot before the first attempt (role,
+//! accessible name, and which of the nodes sharing both it is) and looked up
+//! again in a fresh snapshot afterwards. CSS selectors are simply re-run.
+//!
+//! Only failures that a reload can plausibly fix are recovered; a disabled
+//! field or a bad parameter fails straight away.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+
+use crate::models::{AriaNode, ElementFingerprint, RecoveryStep};
+
+/// Retries before the page is reloaded.
+pub const RECOVERY_RETRIES: u32 = 2;
+
+/// Pause before the first retry; doubled for each one after.
+pub const RETRY_DELAY_MS: u64 = 250;
+
+/// Lowercased error fragments of a missing, stale or moving target.
+const RECOVERABLE: &[&str] = &[
+    "element not found",
+    "element changed",
+    "no node with given id",
+    "node is detached",
+    "could not compute box model",
+    "not visible",
+    "not interactable",
+    "execution context was destroyed",
+    "cannot find context",
+];
+
+/// Whether reloading the page could make `error` go away.
+pub fn is_recoverable(error: &anyhow::Error) -> bool {
+    let message = format!("{:#}", error).to_lowercase();
+    RECOVERABLE
+        .iter()
+        .any(|fragment| message.contains(fragment))
+}
+
+/// Nodes in document order.
+fn flatten<'a>(nodes: &'a [AriaNode], out: &mut Vec<&'a AriaNode>) {
+    for node in nodes {
+        out.push(node);
+        flatten(&node.children, out);
+    }
+}
+
+fn same_element(node: &AriaNode, role: &str, name: Option<&str>) -> bool {
+    node.role == role && node.name.as_deref() == name
+}
+
+/// Fingerprint of the node with `ref_id`, if the snapshot has it.
+pub fn fingerprint(nodes: &[AriaNode], ref_id: &str) -> Option<ElementFingerprint> {
+    let mut flat = Vec::new();
+    flatten(nodes, &mut flat);
+    let node = flat.iter().find(|node| node.ref_id == ref_id)?;
+    let occurrence = flat
+        .iter()
+        .take_while(|other| other.ref_id != ref_id)
+        .filter(|other| same_element(other, &node.role, node.name.as_deref()))
+        .count();
+    Some(ElementFingerprint {
+        role: node.role.clone(),
+        name: node.name.clone(),
+        occurrence,
+    })
+}
+
+/// Ref of the node matching `fingerprint`.
+///
+/// Falls back to the only node with that role and name when the page now
+/// has fewer of them.
+pub fn refind(nodes: &[AriaNode], fingerprint: &ElementFingerprint) -> Option<String> {
+    let mut flat = Vec::new();
+    flatten(nodes, &mut flat);
+    let candidates: Vec<&AriaNode> = flat
+        .into_iter()
+        .filter(|node| same_element(node, &fingerprint.role, fingerprint.name.as_deref()))
+        .collect();
+    match candidates.get(fingerprint.occurrence) {
+        Some(node) => Some(node.ref_id.clone()),
+        None if candidates.len() == 1 => Some(candidates[0].ref_id.clone()),
+        None => None,
+    }
+}
+
+/// Reload `page`, or load the URL it was on if reloading fails.
+pub async fn reload(page: &Page) -> Result<RecoveryStep> {
+    let url = page.url().await.ok().flatten();
+    match page.reload().await {
+        Ok(_) => Ok(RecoveryStep::Reload { url }),
+        Err(e) => {
+            let url = url
+                .filter(|url| url.starts_with("http"))
+                .with_context(|| format!("Reload failed ({}) and there is no URL to load", e))?;
+            page.goto(url.as_str())
+                .await
+                .with_context(|| format!("Failed to load {} again", url))?;
+            page.wait_for_navigation().await.ok();
+            Ok(RecoveryStep::Navigate { url })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(ref_id: &str, role: &str, name: Option<&str>, children: Vec<AriaNode>) -> AriaNode {
+        AriaNode {
+            ref_id: ref_id.to_string(),
+            role: role.to_string(),
+            name: name.map(str::to_string),
+            value: None,
+            focusable: true,
+            focused: false,
+            rect: None,
+            in_viewport: true,
+            children,
+        }
+    }
+
+    fn page(first_ref: usize) -> Vec<AriaNode> {
+        let r = |n: usize| format!("@e{}", first_ref + n);
+        vec![node(
+            &r(0),
+            "main",
+            None,
+            vec![
+                node(&r(1), "button", Some("Add to cart"), vec![]),
+                node(&r(2), "link", Some("Details"), vec![]),
+                node(&r(3), "button", Some("Add to cart"), vec![]),
+            ],
+        )]
+    }
+
+    #[test]
+    fn test_refind_after_reload() {
+        let before = page(10);
+        let second = fingerprint(&before, "@e13").unwrap();
+        assert_eq!(second.role, "button");
+        assert_eq!(second.occurrence, 1);
+        assert!(fingerprint(&before, "@e99").is_none());
+
+        // Refs start over after the reload
+        let after = page(1);
+        assert_eq!(refind(&after, &second).as_deref(), Some("@e4"));
+        let details = fingerprint(&before, "@e12").unwrap();
+        assert_eq!(refind(&after, &details).as_deref(), Some("@e3"));
+
+        // One button left: it's the one
+        let fewer = vec![node("@e1", "button", Some("Add to cart"), vec![])];
+        assert_eq!(refind(&fewer, &second).as_deref(), Some("@e1"));
+        assert_eq!(refind(&[], &second), None);
+    }
+
+    #[test]
+    fn test_recoverable_errors() {
+        assert!(is_recoverable(&anyhow::anyhow!("Element not found: @e4")));
+        assert!(is_recoverable(
+            &anyhow::anyhow!("Could not compute box model.").context("Click failed")
+        ));
+        assert!(!is_recoverable(&anyhow::anyhow!(
+            "Element is disabled: #email"
+        )));
+        assert!(!is_recoverable(&anyhow::anyhow!(
+            "Missing 'selector' parameter"
+        )));
+    }
+}
@@ -0,0 +1,187 @@
+//! Export of snapshot refs as CDP handles.
+//!
+//! Refs live on the page as `data-fgp-ref` attributes, so other CDP clients
+//! attached to the same browser can't use `@eN` directly. This resolves each
+//! ref to its `backendNodeId` (stable for the node's lifetime and usable
+//! from any CDP session, via `DOM.resolveNode` or `DOM.pushNodesByBackendIdsToFrontend`),
+//! a Runtime `objectId` (valid on this daemon's CDP session only), and
+//! selectors that find the element without the daemon.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    DescribeNodeParams, GetDocumentParams, QuerySelectorAllParams, ResolveNodeParams,
+};
+use chromiumoxide::cdp::js_protocol::runtime::ReleaseObjectGroupParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use crate::models::{RefBinding, RefExport};
+
+/// Runtime object group the exported `objectId`s belong to; released (and
+/// the old IDs invalidated) on the next export.
+const OBJECT_GROUP: &str = "fgp-ref-export";
+
+/// Structural CSS path of every ref'd element, by ref.
+const SELECTORS_SCRIPT: &str = r#"(() => {
+    const pathOf = (el) => {
+        const parts = [];
+        while (el && el !== document.documentElement) {
+            if (el.id && document.querySelectorAll('#' + CSS.escape(el.id)).length === 1) {
+                parts.unshift('#' + CSS.escape(el.id));
+                return parts.join(' > ');
+            }
+            const parent = el.parentElement;
+            if (!parent) break;
+            parts.unshift(el.tagName.toLowerCase() + ':nth-child(' +
+                ([...parent.children].indexOf(el) + 1) + ')');
+            el = parent;
+        }
+        parts.unshift('html');
+        return parts.join(' > ');
+    };
+    return [...document.querySelectorAll('[data-fgp-ref]')].map((el) => ({
+        ref: el.getAttribute('data-fgp-ref'),
+        tag: el.tagName.toLowerCase(),
+        selector: pathOf(el),
+    }));
+})()"#;
+
+#[derive(Debug, Deserialize)]
+struct Located {
+    #[serde(rename = "ref")]
+    ref_value: String,
+    tag: String,
+    selector: String,
+}
+
+/// `@e5`, `e5` -> `e5`.
+fn bare_ref(ref_id: &str) -> &str {
+    ref_id.trim().trim_start_matches('@')
+}
+
+/// Value of `name` in CDP's flat `[name, value, name, value, ...]` list.
+fn attribute<'a>(attributes: &'a [String], name: &str) -> Option<&'a str> {
+    attributes
+        .chunks_exact(2)
+        .find(|pair| pair[0] == name)
+        .map(|pair| pair[1].as_str())
+}
+
+/// Resolve the refs of the last snapshot (or only `wanted`) to CDP handles.
+pub async fn export(page: &Page, wanted: Option<&[String]>) -> Result<RefExport> {
+    let wanted: Option<Vec<&str>> = wanted.map(|refs| refs.iter().map(|r| bare_ref(r)).collect());
+
+    let located: Vec<Located> = page
+        .evaluate(SELECTORS_SCRIPT)
+        .await
+        .context("Failed to find ref'd elements")?
+        .into_value()
+        .context("Failed to parse ref'd elements")?;
+    if located.is_empty() {
+        anyhow::bail!("No refs on the page; take a snapshot first");
+    }
+    let mut located: HashMap<String, Located> = located
+        .into_iter()
+        .map(|l| (l.ref_value.clone(), l))
+        .collect();
+    if let Some(missing) = wanted.iter().flatten().find(|r| !located.contains_key(**r)) {
+        anyhow::bail!("Ref @{} is not on the page; take a new snapshot", missing);
+    }
+
+    // Old object IDs are dropped so repeated exports don't pin nodes
+    let _ = page
+        .execute(ReleaseObjectGroupParams::new(OBJECT_GROUP))
+        .await;
+
+    let root = page
+        .execute(GetDocumentParams::default())
+        .await
+        .context("Failed to read document")?
+        .result
+        .root
+        .node_id;
+    let node_ids = page
+        .execute(QuerySelectorAllParams::new(root, "[data-fgp-ref]"))
+        .await
+        .context("Failed to query ref'd elements")?
+        .result
+        .node_ids;
+
+    let lookups = node_ids.into_iter().map(|node_id| async move {
+        let described = page
+            .execute(DescribeNodeParams::builder().node_id(node_id).build())
+            .await?;
+        let resolved = page
+            .execute(
+                ResolveNodeParams::builder()
+                    .node_id(node_id)
+                    .object_group(OBJECT_GROUP)
+                    .build(),
+            )
+            .await?;
+        Ok::<_, anyhow::Error>((described.result.node, resolved.result.object.object_id))
+    });
+
+    let mut refs = Vec::new();
+    for lookup in futures::future::join_all(lookups).await {
+        let (node, object_id) = match lookup {
+            Ok(found) => found,
+            // Removed from the page since the query
+            Err(e) => {
+                tracing::debug!("Skipping ref'd element: {}", e);
+                continue;
+            }
+        };
+        let Some(ref_value) = node
+            .attributes
+            .as_deref()
+            .and_then(|attributes| attribute(attributes, "data-fgp-ref"))
+        else {
+            continue;
+        };
+        if wanted
+            .as_ref()
+            .is_some_and(|wanted| !wanted.contains(&ref_value))
+        {
+            continue;
+        }
+        let Some(located) = located.remove(ref_value) else {
+            continue;
+        };
+
+        refs.push(RefBinding {
+            ref_id: format!("@{}", ref_value),
+            backend_node_id: *node.backend_node_id.inner(),
+            object_id: object_id.map(|id| id.inner().clone()),
+            tag: located.tag,
+            selector: located.selector,
+            ref_selector: format!("[data-fgp-ref=\"{}\"]", ref_value),
+        });
+    }
+
+    Ok(RefExport {
+        target_id: page.target_id().inner().clone(),
+        url: page.url().await?.unwrap_or_default(),
+        refs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ref_attribute_lookup() {
+        let attributes: Vec<String> = ["class", "btn", "data-fgp-ref", "e12", "type", "submit"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(attribute(&attributes, "data-fgp-ref"), Some("e12"));
+        assert_eq!(attribute(&attributes, "id"), None);
+        assert_eq!(bare_ref(" @e12"), "e12");
+        assert_eq!(bare_ref("e12"), "e12");
+    }
+}
@@ -0,0 +1,177 @@
+//! Recent events of a session, for clients that ask after the fact.
+//!
+//! Every session records its last `REPLAY_CAPACITY` events (navigations,
+//! console messages, responses, notifications...) from the moment it is
+//! created, numbered in order. A client can act first and then read what
+//! happened since a sequence number it noted, instead of having to subscribe
+//! before acting; an observer can start from the recorded events too.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use tokio::sync::broadcast;
+
+use crate::models::{ObservedEvent, SessionEvent, SessionEvents};
+
+/// Events kept per session before the oldest are evicted.
+pub const REPLAY_CAPACITY: usize = 500;
+
+#[derive(Debug, Default)]
+struct Log {
+    events: VecDeque<ObservedEvent>,
+    next_seq: u64,
+    /// Events evicted to stay within `REPLAY_CAPACITY`
+    dropped: u64,
+    /// Events the broadcast channel dropped before we read them
+    missed: u64,
+}
+
+impl Log {
+    fn push(&mut self, event: SessionEvent) {
+        self.events.push_back(ObservedEvent {
+            seq: self.next_seq,
+            at: Utc::now().to_rfc3339(),
+            event,
+        });
+        self.next_seq += 1;
+        while self.events.len() > REPLAY_CAPACITY {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+    }
+}
+
+/// Recorded events of a session, shared with the task recording them.
+#[derive(Debug, Clone, Default)]
+pub struct Replay(Arc<Mutex<Log>>);
+
+impl Replay {
+    /// Record what `sender` broadcasts from now on, until every sender is
+    /// dropped.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn record(sender: &broadcast::Sender<SessionEvent>) -> Self {
+        let replay = Self::default();
+        let mut events = sender.subscribe();
+        let log = Arc::clone(&replay.0);
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => log.lock().unwrap_or_else(|e| e.into_inner()).push(event),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        log.lock().unwrap_or_else(|e| e.into_inner()).missed += n;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        replay
+    }
+
+    /// Recorded events numbered `since` or later, of the given `types` if
+    /// any.
+    pub fn events(&self, session_id: &str, since: u64, types: &[String]) -> SessionEvents {
+        let log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        SessionEvents {
+            session_id: session_id.to_string(),
+            events: log
+                .events
+                .iter()
+                .filter(|e| e.seq >= since)
+                .filter(|e| types.is_empty() || types.iter().any(|t| t == e.event.kind()))
+                .cloned()
+                .collect(),
+            next: log.next_seq,
+            dropped: log.dropped,
+            missed: log.missed,
+        }
+    }
+
+    /// Drop all but the newest `keep` events; returns how many went.
+    pub fn trim(&self, keep: usize) -> u64 {
+        let mut log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let excess = log.events.len().saturating_sub(keep);
+        log.events.drain(..excess);
+        log.dropped += excess as u64;
+        excess as u64
+    }
+
+    /// Subscribe to `sender` and return the events recorded so far with
+    /// the receiver.
+    ///
+    /// The log is held while subscribing, so no event comes both ways; one
+    /// sent just before that the recorder hasn't read yet comes neither way.
+    pub fn subscribe(
+        &self,
+        sender: &broadcast::Sender<SessionEvent>,
+    ) -> (broadcast::Receiver<SessionEvent>, Vec<ObservedEvent>) {
+        let log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        (sender.subscribe(), log.events.iter().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn navigation(url: &str) -> SessionEvent {
+        SessionEvent::Navigation {
+            url: url.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_are_kept_for_late_readers() {
+        let (sender, _) = broadcast::channel(16);
+        let replay = Replay::record(&sender);
+        sender.send(navigation("https://example.com/")).unwrap();
+        sender
+            .send(SessionEvent::Console {
+                level: "log".to_string(),
+                text: "ready".to_string(),
+            })
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        let all = replay.events("default", 0, &[]);
+        assert_eq!(all.events.len(), 2);
+        assert_eq!(all.next, 2);
+        assert_eq!(replay.events("default", 1, &[]).events.len(), 1);
+
+        let consoles = replay.events("default", 0, &["console".to_string()]);
+        assert_eq!(consoles.events.len(), 1);
+        assert_eq!(consoles.events[0].seq, 1);
+    }
+
+    #[test]
+    fn test_oldest_events_are_evicted() {
+        let mut log = Log::default();
+        for i in 0..REPLAY_CAPACITY + 3 {
+            log.push(navigation(&format!("https://example.com/{}", i)));
+        }
+        assert_eq!(log.events.len(), REPLAY_CAPACITY);
+        assert_eq!(log.dropped, 3);
+        assert_eq!(log.events.front().map(|e| e.seq), Some(3));
+    }
+
+    #[test]
+    fn test_trim_keeps_newest_events() {
+        let replay = Replay::default();
+        for i in 0..10 {
+            replay
+                .0
+                .lock()
+                .unwrap()
+                .push(navigation(&format!("https://example.com/{}", i)));
+        }
+        assert_eq!(replay.trim(4), 6);
+        assert_eq!(replay.trim(4), 0);
+
+        let kept = replay.events("default", 0, &[]);
+        assert_eq!(kept.events.len(), 4);
+        assert_eq!(kept.events[0].seq, 6);
+        assert_eq!(kept.dropped, 6);
+        assert_eq!(kept.next, 10);
+    }
+}
@@ -0,0 +1,251 @@
+//! Result cards of search-result pages.
+//!
+//! Search and click is the most common agent loop, and a results page is
+//! mostly chrome around a list of repeated cards. A card is found from its
+//! title link (a link in a heading, or a heading in a link, as search
+//! engines and most site searches mark them up), widened to the largest
+//! ancestor holding no other title; its snippet is the longest text in the
+//! card besides the title and the displayed URL. When no headings are used
+//! the page's repeated `li`/`article` items with a link stand in.
+//!
+//! Titles carry the ref from the session's snapshot, so the next step can
+//! click one directly. Tracking redirects (`/url?q=`, `/l/?uddg=`) are
+//! unwrapped to the destination URL.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+use url::Url;
+
+use super::client::resolve_selector;
+use crate::models::{SearchResult, SearchResults};
+
+/// Snippets are cut to this many characters.
+const MAX_SNIPPET_CHARS: usize = 300;
+
+/// Query parameters search engines put the destination URL in.
+const REDIRECT_PARAMS: &[&str] = &["q", "url", "uddg", "u"];
+
+/// Cards in document order as `{title, href, snippet, ref}`, and the query
+/// in the page's search box.
+const RESULTS_SCRIPT: &str = r#"((sel) => {
+    const root = sel !== null ? document.querySelector(sel) : document.body;
+    if (!root) return null;
+
+    const text = (el) => (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim();
+    const visible = (el) => {
+        const r = el.getBoundingClientRect();
+        return r.width > 0 && r.height > 0;
+    };
+    const chrome = 'nav, header, footer, aside, [role=navigation], [role=banner], ' +
+        '[role=contentinfo], [role=search], form';
+    const usable = (a) => /^https?:/.test(a.href) && visible(a) && !a.closest(chrome) &&
+        text(a).length > 0;
+
+    let titles = [...root.querySelectorAll(
+        'h1 a[href], h2 a[href], h3 a[href], h4 a[href], [role=heading] a[href], a[href]:has(h1, h2, h3, h4, [role=heading])')]
+        .filter(usable);
+    // Without title headings, take the first link of repeated list items
+    if (titles.length < 2) {
+        titles = [...root.querySelectorAll('li, article, [role=listitem], [role=article]')]
+            .filter((item) => !item.parentElement.closest('li, article, [role=listitem], [role=article]'))
+            .map((item) => [...item.querySelectorAll('a[href]')].find(usable))
+            .filter((a) => a && text(a).length >= 10);
+    }
+    titles = titles.filter((a, i) => titles.indexOf(a) === i);
+
+    const cardOf = (a) => {
+        let card = a;
+        while (card.parentElement && card.parentElement !== root &&
+            titles.filter((t) => card.parentElement.contains(t)).length === 1) {
+            card = card.parentElement;
+        }
+        return card;
+    };
+    const refOf = (a) => {
+        const el = a.hasAttribute('data-fgp-ref') ? a : a.querySelector('[data-fgp-ref]');
+        return el ? '@' + el.getAttribute('data-fgp-ref') : null;
+    };
+
+    const cards = titles.map((a) => {
+        const card = cardOf(a);
+        const title = text(a.querySelector('h1, h2, h3, h4, [role=heading]') || a);
+        const host = new URL(a.href).hostname.replace(/^www\./, '');
+        const lines = (card.innerText || '').split('\n').map((l) => l.replace(/\s+/g, ' ').trim())
+            .filter((l) => l && l !== title && !title.includes(l) && !l.includes(host) &&
+                !/^https?:\/\//.test(l) && !l.includes('›'));
+        const snippet = lines.reduce((a, b) => (b.length > a.length ? b : a), '');
+        return { title, href: a.href, snippet, ref: refOf(a) };
+    });
+
+    const box = document.querySelector(
+        'input[type=search], [role=searchbox], input[name=q], input[name=query], input[name=search]');
+    return { cards, query: box && box.value ? box.value : null };
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Card {
+    title: String,
+    href: String,
+    snippet: String,
+    #[serde(rename = "ref")]
+    ref_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Scan {
+    cards: Vec<Card>,
+    query: Option<String>,
+}
+
+/// The destination of a search engine's tracking redirect, or `href` itself.
+fn unwrap_redirect(href: &str) -> String {
+    let Ok(url) = Url::parse(href) else {
+        return href.to_string();
+    };
+    let path = url.path();
+    if path == "/url" || path == "/l/" || path == "/link" || path.ends_with("/redirect") {
+        for (key, value) in url.query_pairs() {
+            if REDIRECT_PARAMS.contains(&key.as_ref())
+                && (value.starts_with("http://") || value.starts_with("https://"))
+            {
+                return value.into_owned();
+            }
+        }
+    }
+    href.to_string()
+}
+
+/// Cut `text` to `MAX_SNIPPET_CHARS` on a character boundary.
+fn truncate_snippet(text: &str) -> String {
+    match text.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+/// Number the cards, dropping repeats of a destination already listed.
+fn summarize(cards: Vec<Card>) -> Vec<SearchResult> {
+    let mut seen = HashSet::new();
+    cards
+        .into_iter()
+        .map(|card| (unwrap_redirect(&card.href), card))
+        .filter(|(url, card)| !card.title.is_empty() && seen.insert(url.clone()))
+        .enumerate()
+        .map(|(i, (url, card))| SearchResult {
+            position: i + 1,
+            title: card.title,
+            url,
+            snippet: (!card.snippet.is_empty()).then(|| truncate_snippet(&card.snippet)),
+            ref_id: card.ref_id,
+        })
+        .collect()
+}
+
+/// Result cards of the page (or of `container`), at most `limit`.
+pub async fn collect(
+    page: &Page,
+    page_url: String,
+    container: Option<&str>,
+    limit: usize,
+) -> Result<SearchResults> {
+    let sel = container.map(resolve_selector);
+    let scan: Option<Scan> = page
+        .evaluate(format!(
+            "{}({})",
+            RESULTS_SCRIPT,
+            serde_json::to_string(&sel)?
+        ))
+        .await
+        .context("Failed to read search results")?
+        .into_value()
+        .context("Failed to parse search results")?;
+    let scan = scan.with_context(|| {
+        format!(
+            "Results container not found: {}",
+            container.unwrap_or_default()
+        )
+    })?;
+
+    let mut results = summarize(scan.cards);
+    let total = results.len();
+    results.truncate(limit);
+    Ok(SearchResults {
+        url: page_url,
+        query: scan.query,
+        results,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(title: &str, href: &str, snippet: &str) -> Card {
+        Card {
+            title: title.to_string(),
+            href: href.to_string(),
+            snippet: snippet.to_string(),
+            ref_id: Some("@e4".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_unwrap_redirect() {
+        assert_eq!(
+            unwrap_redirect("https://www.google.com/url?q=https://example.com/a%3Fb%3D1&sa=U"),
+            "https://example.com/a?b=1"
+        );
+        assert_eq!(
+            unwrap_redirect("https://duckduckgo.com/l/?uddg=https%3A%2F%2Fexample.org%2F&rut=x"),
+            "https://example.org/"
+        );
+        // Only redirect paths are unwrapped
+        assert_eq!(
+            unwrap_redirect("https://shop.example.com/search?q=https://x.test"),
+            "https://shop.example.com/search?q=https://x.test"
+        );
+        assert_eq!(unwrap_redirect("not a url"), "not a url");
+    }
+
+    #[test]
+    fn test_summarize_numbers_and_dedupes() {
+        let results = summarize(vec![
+            card(
+                "Rust",
+                "https://www.rust-lang.org/",
+                "A language empowering everyone",
+            ),
+            card("", "https://example.com/empty", ""),
+            card(
+                "Rust (again)",
+                "https://www.google.com/url?q=https://www.rust-lang.org/",
+                "",
+            ),
+            card("The Book", "https://doc.rust-lang.org/book/", ""),
+        ]);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].position, 1);
+        assert_eq!(
+            results[0].snippet.as_deref(),
+            Some("A language empowering everyone")
+        );
+        assert_eq!(results[1].position, 2);
+        assert_eq!(results[1].title, "The Book");
+        assert_eq!(results[1].snippet, None);
+        assert_eq!(results[1].ref_id.as_deref(), Some("@e4"));
+    }
+
+    #[test]
+    fn test_truncate_snippet() {
+        let long = "é".repeat(MAX_SNIPPET_CHARS + 5);
+        let cut = truncate_snippet(&long);
+        assert_eq!(cut.chars().count(), MAX_SNIPPET_CHARS + 1);
+        assert!(cut.ends_with('…'));
+        assert_eq!(truncate_snippet("short"), "short");
+    }
+}
@@ -0,0 +1,290 @@
+//! Rich text editors (contenteditable).
+//!
+//! ProseMirror, Quill, Draft.js, Lexical and friends keep their own document
+//! model and ignore `value` or DOM edits made behind their back; they do
+//! handle paste. Content is pasted as a synthetic `paste` event carrying
+//! `text/html` and `text/plain`, and inserted with `execCommand` when the
+//! editor leaves the paste to the browser or the text doesn't show up.
+//! Markdown is converted to HTML here first, so formatting survives.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use pulldown_cmark::{html, Options, Parser};
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{RichTextFormat, RichTextMethod, RichTextResult};
+
+/// Time for the editor to apply a paste before the result is read.
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// Characters of the content looked for in the editor afterwards.
+const CHECK_CHARS: usize = 40;
+
+/// Characters of editor text returned.
+const MAX_TEXT: usize = 500;
+
+/// Finds the editable root behind a selector; prefixed to every script here.
+const EDITOR_JS: &str = r#"const editor = (sel) => {
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    if (el.isContentEditable) return el.closest('[contenteditable]:not([contenteditable=false])') || el;
+    return el.querySelector('[contenteditable]:not([contenteditable=false])');
+};
+"#;
+
+/// Focus the editor and select what the content replaces (everything, or
+/// the caret at the end when appending).
+const PREPARE_SCRIPT: &str = r#"((sel, append) => {
+    const el = editor(sel);
+    if (!el) return { found: !!document.querySelector(sel), editable: false };
+    el.focus();
+    const range = document.createRange();
+    range.selectNodeContents(el);
+    if (append) range.collapse(false);
+    const selection = getSelection();
+    selection.removeAllRanges();
+    selection.addRange(range);
+    const kind = el.classList.contains('ProseMirror') ? 'prosemirror'
+        : el.classList.contains('ql-editor') ? 'quill'
+        : el.closest('.DraftEditor-root') ? 'draft'
+        : el.hasAttribute('data-lexical-editor') ? 'lexical'
+        : el.closest('.ck-editor') ? 'ckeditor'
+        : 'contenteditable';
+    return { found: true, editable: true, editor: kind };
+})"#;
+
+const PASTE_SCRIPT: &str = r#"((sel, html, plain) => {
+    const el = editor(sel);
+    const data = new DataTransfer();
+    if (html !== null) data.setData('text/html', html);
+    data.setData('text/plain', plain);
+    const event = new ClipboardEvent('paste', { clipboardData: data, bubbles: true, cancelable: true });
+    el.dispatchEvent(event);
+    return event.defaultPrevented;
+})"#;
+
+const INSERT_SCRIPT: &str = r#"((sel, html, plain) => {
+    editor(sel).focus();
+    return html !== null ? document.execCommand('insertHTML', false, html)
+        : document.execCommand('insertText', false, plain);
+})"#;
+
+const TEXT_SCRIPT: &str = r#"((sel) => editor(sel).innerText)"#;
+
+#[derive(Debug, Deserialize)]
+struct Prepared {
+    found: bool,
+    editable: bool,
+    #[serde(default)]
+    editor: String,
+}
+
+/// `content` as (HTML, plain text) clipboard flavors.
+fn flavors(content: &str, format: RichTextFormat) -> (Option<String>, String) {
+    match format {
+        RichTextFormat::Markdown => {
+            let mut out = String::new();
+            html::push_html(
+                &mut out,
+                Parser::new_ext(
+                    content,
+                    Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES,
+                ),
+            );
+            // Markdown source is what a user would paste as plain text
+            (Some(out.trim_end().to_string()), content.to_string())
+        }
+        RichTextFormat::Html => (Some(content.to_string()), strip_tags(content)),
+        RichTextFormat::Text => (None, content.to_string()),
+    }
+}
+
+/// Text of an HTML fragment, roughly: tags dropped, common entities decoded.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Words of `text`, for comparing regardless of how the editor laid it out.
+fn words(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether the editor shows the start of the expected text, comparing only
+/// letters and digits (editors add bullets, drop spacing, etc.).
+fn shows(editor_text: &str, expected: &str) -> bool {
+    let letters = |s: &str| -> String {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let wanted: String = letters(expected).chars().take(CHECK_CHARS).collect();
+    letters(editor_text).contains(&wanted)
+}
+
+fn script(body: &str, args: &[String]) -> String {
+    format!(
+        "(() => {{ {}return {}({}); }})()",
+        EDITOR_JS,
+        body,
+        args.join(", ")
+    )
+}
+
+async fn run<T: serde::de::DeserializeOwned>(page: &Page, script: String, what: &str) -> Result<T> {
+    page.evaluate(script)
+        .await
+        .with_context(|| format!("Failed to {}", what))?
+        .into_value()
+        .with_context(|| format!("Failed to parse result of {}", what))
+}
+
+/// Put `content` into the rich text editor at `selector`, replacing what is
+/// there unless `append`.
+pub async fn fill(
+    page: &Page,
+    selector: &str,
+    content: &str,
+    format: RichTextFormat,
+    append: bool,
+) -> Result<RichTextResult> {
+    let sel = serde_json::to_string(&resolve_selector(selector))?;
+    let (html, plain) = flavors(content, format);
+    let expected = html.as_deref().map_or(plain.clone(), strip_tags);
+    let args = [
+        sel.clone(),
+        serde_json::to_string(&html)?,
+        serde_json::to_string(&plain)?,
+    ];
+
+    let prepare = script(PREPARE_SCRIPT, &[sel.clone(), append.to_string()]);
+    let prepared: Prepared = run(page, prepare.clone(), "focus editor").await?;
+    if !prepared.found {
+        anyhow::bail!("Element not found: {}", selector);
+    }
+    if !prepared.editable {
+        anyhow::bail!(
+            "Not a rich text editor (no contenteditable): {}; use fill for inputs",
+            selector
+        );
+    }
+    let before: String = run(page, script(TEXT_SCRIPT, &[sel.clone()]), "read editor").await?;
+
+    let handled: bool = run(page, script(PASTE_SCRIPT, &args), "paste into editor").await?;
+    let mut method = RichTextMethod::Paste;
+    if !handled {
+        // Nobody took the paste; let the browser insert it
+        run::<bool>(page, script(INSERT_SCRIPT, &args), "insert into editor").await?;
+        method = insert_method(&html);
+    }
+    tokio::time::sleep(SETTLE).await;
+    let mut text: String = run(page, script(TEXT_SCRIPT, &[sel.clone()]), "read editor").await?;
+
+    if handled && !shows(&text, &expected) {
+        // The editor swallowed the paste without applying it (some filter
+        // synthetic clipboard data); insert at the same spot instead
+        run::<Prepared>(page, prepare, "focus editor").await?;
+        run::<bool>(page, script(INSERT_SCRIPT, &args), "insert into editor").await?;
+        method = insert_method(&html);
+        tokio::time::sleep(SETTLE).await;
+        text = run(page, script(TEXT_SCRIPT, &[sel]), "read editor").await?;
+    }
+    if !shows(&text, &expected) {
+        anyhow::bail!(
+            "Editor ({}) didn't take the content; it shows: {}",
+            prepared.editor,
+            truncate(&words(&text), 120)
+        );
+    }
+
+    Ok(RichTextResult {
+        success: true,
+        editor: prepared.editor,
+        method,
+        changed: words(&text) != words(&before),
+        text: truncate(&text, MAX_TEXT),
+    })
+}
+
+fn insert_method(html: &Option<String>) -> RichTextMethod {
+    if html.is_some() {
+        RichTextMethod::InsertHtml
+    } else {
+        RichTextMethod::InsertText
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_flavors() {
+        let (html, plain) = flavors(
+            "# Notes\n\nShip **today**, see [docs](https://example.com).\n\n- one\n- ~~two~~",
+            RichTextFormat::Markdown,
+        );
+
+        assert_eq!(
+            html.as_deref(),
+            Some(
+                "<h1>Notes</h1>\n<p>Ship <strong>today</strong>, see \
+                 <a href=\"https://example.com\">docs</a>.</p>\n<ul>\n<li>one</li>\n\
+                 <li><del>two</del></li>\n</ul>"
+            )
+        );
+        assert!(plain.starts_with("# Notes"));
+    }
+
+    #[test]
+    fn test_html_and_text_flavors() {
+        let (html, plain) = flavors("<p>Fish &amp; chips</p><p>today</p>", RichTextFormat::Html);
+        assert_eq!(html.as_deref(), Some("<p>Fish &amp; chips</p><p>today</p>"));
+        assert_eq!(words(&plain), "Fish & chips today");
+
+        let (html, plain) = flavors("<b>literal</b>", RichTextFormat::Text);
+        assert_eq!(html, None);
+        assert_eq!(plain, "<b>literal</b>");
+    }
+
+    #[test]
+    fn test_shows_ignores_markup_and_layout() {
+        let (html, _) = flavors(
+            "# Notes\n\nShip **today**, see [docs](https://example.com).",
+            RichTextFormat::Markdown,
+        );
+        assert!(shows(
+            "Notes\nShip today, see docs.\n• one",
+            &strip_tags(&html.unwrap())
+        ));
+        assert!(!shows("Notes", "# Other notes"));
+    }
+}
@@ -0,0 +1,265 @@
+//! CDP screencast of a session's page.
+//!
+//! Chrome pushes a JPEG frame whenever the page repaints and waits for an ack
+//! before sending the next one, so a slow consumer throttles the stream
+//! instead of queueing frames. Decoded frames fan out over a broadcast
+//! channel; the most recent one is kept so a new viewer gets a picture
+//! immediately rather than on the next repaint.
+//!
+//! Chrome runs one screencast per page, so recordings, the live view and
+//! frame streams of a session share one: the first subscriber starts it
+//! with its settings, later ones join it, and it stops when the last one
+//! leaves. A subscriber that needs other settings is refused rather than
+//! changing them under the others.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::page::{
+    EventScreencastFrame, ScreencastFrameAckParams, StartScreencastFormat, StartScreencastParams,
+    StopScreencastParams,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Frames buffered per viewer before it starts skipping.
+const FRAME_CHANNEL_CAPACITY: usize = 4;
+
+/// A JPEG frame shared between viewers.
+pub type Frame = Arc<Vec<u8>>;
+
+/// Screencast settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreencastOptions {
+    /// JPEG quality, 0-100
+    pub quality: u8,
+    /// Frames are scaled down to fit these bounds (CSS pixels)
+    pub max_width: u32,
+    pub max_height: u32,
+}
+
+impl Default for ScreencastOptions {
+    fn default() -> Self {
+        Self {
+            quality: 60,
+            max_width: 1280,
+            max_height: 800,
+        }
+    }
+}
+
+/// Settings asked for that differ from those the session's screencast
+/// already runs with.
+#[derive(Debug)]
+pub struct ScreencastConflict {
+    pub running: ScreencastOptions,
+}
+
+impl std::fmt::Display for ScreencastConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SCREENCAST_CONFLICT: the session's screencast already runs at quality {} \
+             within {}x{}; omit quality and size to share it",
+            self.running.quality, self.running.max_width, self.running.max_height
+        )
+    }
+}
+
+impl std::error::Error for ScreencastConflict {}
+
+/// The screencast of a session's page, started by its first subscriber and
+/// stopped after its last.
+#[derive(Clone, Default)]
+pub struct SharedScreencast {
+    running: Arc<tokio::sync::Mutex<Option<Running>>>,
+}
+
+struct Running {
+    page: Page,
+    options: ScreencastOptions,
+    subscribers: usize,
+    /// Only the task sends, so subscribers see the channel close when it ends
+    frames: broadcast::Receiver<Frame>,
+    /// Also tells runs apart: each has its own
+    latest: Arc<Mutex<Option<Frame>>>,
+    task: JoinHandle<()>,
+}
+
+impl Running {
+    async fn start(page: &Page, options: ScreencastOptions) -> Result<Self> {
+        let mut events = page
+            .event_listener::<EventScreencastFrame>()
+            .await
+            .context("Failed to listen for screencast frames")?;
+
+        let (sender, frames) = broadcast::channel(FRAME_CHANNEL_CAPACITY);
+        let latest = Arc::new(Mutex::new(None));
+
+        let task = {
+            let page = page.clone();
+            let latest = Arc::clone(&latest);
+            tokio::spawn(async move {
+                while let Some(event) = events.next().await {
+                    // Ack first so Chrome can render the next frame meanwhile
+                    let ack = ScreencastFrameAckParams::new(event.session_id);
+                    if let Err(e) = page.execute(ack).await {
+                        tracing::debug!("Screencast ack failed: {}", e);
+                        break;
+                    }
+
+                    let data: String = event.data.clone().into();
+                    let jpeg = match base64::engine::general_purpose::STANDARD.decode(data) {
+                        Ok(jpeg) => Arc::new(jpeg),
+                        Err(e) => {
+                            tracing::debug!("Bad screencast frame: {}", e);
+                            continue;
+                        }
+                    };
+                    *latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&jpeg));
+                    // An error only means nobody is watching right now
+                    let _ = sender.send(jpeg);
+                }
+            })
+        };
+
+        let params = StartScreencastParams::builder()
+            .format(StartScreencastFormat::Jpeg)
+            .quality(options.quality as i64)
+            .max_width(options.max_width as i64)
+            .max_height(options.max_height as i64)
+            .build();
+        if let Err(e) = page.execute(params).await {
+            task.abort();
+            return Err(e).context("Failed to start screencast");
+        }
+
+        Ok(Self {
+            page: page.clone(),
+            options,
+            subscribers: 0,
+            frames,
+            latest,
+            task,
+        })
+    }
+
+    async fn stop(self) {
+        self.task.abort();
+        if let Err(e) = self.page.execute(StopScreencastParams::default()).await {
+            tracing::debug!("Failed to stop screencast: {}", e);
+        }
+    }
+}
+
+impl SharedScreencast {
+    /// Subscribe to the screencast, starting it on `page` with `options` if
+    /// it isn't running. A running one is joined with the settings it has,
+    /// unless `exact` asks for `options` and they differ.
+    pub async fn join(
+        &self,
+        page: &Page,
+        options: ScreencastOptions,
+        exact: bool,
+    ) -> Result<Screencast> {
+        let mut running = self.running.lock().await;
+        // One whose page went away is started afresh
+        if running.as_ref().is_some_and(|run| run.task.is_finished()) {
+            *running = None;
+        }
+        let run = match running.as_mut() {
+            Some(run) if exact && run.options != options => {
+                return Err(ScreencastConflict {
+                    running: run.options,
+                }
+                .into());
+            }
+            Some(run) => run,
+            None => running.insert(Running::start(page, options).await?),
+        };
+        run.subscribers += 1;
+
+        Ok(Screencast {
+            shared: self.clone(),
+            options: run.options,
+            frames: run.frames.resubscribe(),
+            latest: Arc::clone(&run.latest),
+            left: false,
+        })
+    }
+
+    /// Whether anyone is subscribed (or subscribing) to the screencast.
+    pub fn is_running(&self) -> bool {
+        self.running
+            .try_lock()
+            .map_or(true, |running| running.is_some())
+    }
+
+    /// Unsubscribe from the run `latest` belongs to, stopping it after the
+    /// last subscriber.
+    async fn leave(&self, latest: &Arc<Mutex<Option<Frame>>>) {
+        let mut running = self.running.lock().await;
+        let last = match running.as_mut() {
+            Some(run) if Arc::ptr_eq(&run.latest, latest) => {
+                run.subscribers -= 1;
+                run.subscribers == 0
+            }
+            _ => false,
+        };
+        if last {
+            if let Some(run) = running.take() {
+                run.stop().await;
+            }
+        }
+    }
+}
+
+/// A subscription to a session's screencast.
+pub struct Screencast {
+    shared: SharedScreencast,
+    options: ScreencastOptions,
+    frames: broadcast::Receiver<Frame>,
+    latest: Arc<Mutex<Option<Frame>>>,
+    left: bool,
+}
+
+impl Screencast {
+    /// Settings the screencast runs with, which may be another
+    /// subscriber's.
+    pub fn options(&self) -> ScreencastOptions {
+        self.options
+    }
+
+    /// Receive frames from now on, plus the most recent frame if any.
+    pub fn subscribe(&self) -> (Option<Frame>, broadcast::Receiver<Frame>) {
+        let receiver = self.frames.resubscribe();
+        let latest = self
+            .latest
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        (latest, receiver)
+    }
+
+    /// Unsubscribe, stopping the screencast if nobody else watches it.
+    pub async fn stop(mut self) {
+        self.left = true;
+        self.shared.leave(&self.latest).await;
+    }
+}
+
+impl Drop for Screencast {
+    fn drop(&mut self) {
+        if self.left {
+            return;
+        }
+        // Dropped without `stop` (e.g. on an error path): leave anyway
+        let (shared, latest) = (self.shared.clone(), Arc::clone(&self.latest));
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move { shared.leave(&latest).await });
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! Screenshot capture helpers.
+//!
+//! Captures go through `Page.captureScreenshot` directly so the base64 payload
+//! returned by Chrome can either be handed back as-is or decoded straight into
+//! a file, without materialising an intermediate decoded buffer.
+//!
+//! Captures are PNG by default, or JPEG or WebP at a chosen quality, which
+//! are many times smaller for photos and full-page captures sent to vision
+//! models.
+//!
+//! A capture can be rendered at a different device scale factor than the live
+//! viewport (e.g. 2x for documentation images). The override only lasts for
+//! the capture and is cleared afterwards, even if the capture fails.
+//!
+//! A page capture covers the viewport, or with full-page mode the whole
+//! scrollable document, rendered in one pass with `captureBeyondViewport`
+//! rather than stitched from scrolled viewports, so fixed headers appear once.
+//!
+//! Element captures are clipped to the element's box plus optional padding
+//! of surrounding context, measured in document coordinates so elements
+//! outside the viewport are captured without scrolling the page. Elements
+//! in open shadow roots and in frames (by frame ref) are measured too.
+//!
+//! An after-paint capture first waits for the compositor to draw new frames,
+//! so a screenshot taken right after a click shows what the click changed
+//! rather than the frame before it.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::SetDeviceMetricsOverrideParams;
+use chromiumoxide::cdp::browser_protocol::page::{
+    CaptureScreenshotFormat, CaptureScreenshotParams, GetLayoutMetricsParams, Viewport,
+};
+use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::frames::{self, ChildFrame};
+use super::shadow;
+use super::zoom::{self, Zoom};
+use crate::models::{ClipOrigin, ElementRect, ImageFormat};
+
+/// Largest device scale factor a capture may be rendered at.
+pub const MAX_SCALE: f64 = 4.0;
+
+/// Frames an after-paint capture waits for unless told otherwise.
+pub const DEFAULT_PAINT_FRAMES: u32 = 1;
+
+/// Most frames an after-paint capture may wait for.
+pub const MAX_PAINT_FRAMES: u32 = 10;
+
+/// How long to wait for frames before capturing anyway.
+const PAINT_TIMEOUT_MS: u64 = 1_000;
+
+/// Largest JPEG or WebP quality.
+pub const MAX_QUALITY: u8 = 100;
+
+/// Base64 characters of a JPEG searched for its frame header.
+const JPEG_HEADER_CHARS: usize = 64 * 1024;
+
+/// Encoding of a capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Image {
+    pub format: ImageFormat,
+    /// JPEG or WebP quality; ignored for PNG
+    pub quality: Option<u8>,
+}
+
+/// Part of the page a screenshot covers.
+#[derive(Debug, Clone, Copy)]
+pub enum Region {
+    /// The visible viewport
+    Viewport,
+    /// The whole scrollable page
+    FullPage,
+    /// A rect in document CSS pixels
+    Clip(ElementRect),
+}
+
+/// Box of an element in document CSS pixels, plus the clip covering it and
+/// `padding` pixels of context (kept within the document).
+#[derive(Debug, Clone, Copy)]
+pub struct ElementRegion {
+    pub element: ElementRect,
+    pub clip: ElementRect,
+}
+
+/// Viewport rect of the element at `sel` (if given and found), plus the
+/// document's scroll offset and size.
+const MEASURE_SCRIPT: &str = r#"((sel) => {
+    const doc = document.documentElement;
+    const measured = {
+        scroll_x: scrollX,
+        scroll_y: scrollY,
+        page_width: Math.max(doc.scrollWidth, doc.clientWidth),
+        page_height: Math.max(doc.scrollHeight, doc.clientHeight),
+    };
+    const el = sel === null ? null : deepQuery(sel);
+    if (el) {
+        const r = el.getBoundingClientRect();
+        measured.element = { x: r.left, y: r.top, width: r.width, height: r.height };
+    }
+    return measured;
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Measured {
+    #[serde(default)]
+    element: Option<ElementRect>,
+    scroll_x: f64,
+    scroll_y: f64,
+    page_width: f64,
+    page_height: f64,
+}
+
+/// Measure the element matching `css_selector` in `frame` (default: the
+/// page) for an element capture. Elements in open shadow roots are found,
+/// and a frame's elements are placed in the top-level document.
+///
+/// Returns None if the selector matched nothing.
+pub async fn element_region(
+    page: &Page,
+    frame: Option<&ChildFrame>,
+    css_selector: &str,
+    padding: f64,
+) -> Result<Option<ElementRegion>> {
+    let css = serde_json::to_string(css_selector)?;
+    let measured: Measured = frames::evaluate(page, frame, shadow::call(MEASURE_SCRIPT, &css))
+        .await
+        .context("Failed to measure element")?;
+    let Some(mut element) = measured.element else {
+        return Ok(None);
+    };
+
+    let document = match frame {
+        None => measured,
+        Some(frame) => {
+            let origin = frames::origin(page, frame).await?;
+            element.x += origin.x;
+            element.y += origin.y;
+            frames::evaluate(page, None, shadow::call(MEASURE_SCRIPT, "null"))
+                .await
+                .context("Failed to measure page")?
+        }
+    };
+    element.x += document.scroll_x;
+    element.y += document.scroll_y;
+
+    Ok(Some(ElementRegion {
+        element,
+        clip: padded_clip(&element, padding, document.page_width, document.page_height),
+    }))
+}
+
+/// Document rect of a `clip` given from `origin`, kept within the page.
+///
+/// Returns None if the clip lies outside the page.
+pub async fn clip_region(
+    page: &Page,
+    clip: ElementRect,
+    origin: ClipOrigin,
+) -> Result<Option<ElementRect>> {
+    let measured: Measured = frames::evaluate(page, None, shadow::call(MEASURE_SCRIPT, "null"))
+        .await
+        .context("Failed to measure page")?;
+    Ok(document_clip(clip, origin, &measured))
+}
+
+fn document_clip(
+    mut clip: ElementRect,
+    origin: ClipOrigin,
+    page: &Measured,
+) -> Option<ElementRect> {
+    if origin == ClipOrigin::Viewport {
+        clip.x += page.scroll_x;
+        clip.y += page.scroll_y;
+    }
+    let outside = clip.x >= page.page_width
+        || clip.y >= page.page_height
+        || clip.x + clip.width <= 0.0
+        || clip.y + clip.height <= 0.0;
+    (!outside).then(|| padded_clip(&clip, 0.0, page.page_width, page.page_height))
+}
+
+/// Grow `rect` by `padding` on every side, clamped to the page.
+fn padded_clip(rect: &ElementRect, padding: f64, page_width: f64, page_height: f64) -> ElementRect {
+    let left = (rect.x - padding).max(0.0);
+    let top = (rect.y - padding).max(0.0);
+    let right = (rect.x + rect.width + padding)
+        .min(page_width)
+        .max(left + 1.0);
+    let bottom = (rect.y + rect.height + padding)
+        .min(page_height)
+        .max(top + 1.0);
+    ElementRect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    }
+}
+
+/// Wait until `frames` new frames have been painted.
+///
+/// A `requestAnimationFrame` callback runs before its frame is painted, so a
+/// frame is known to be on screen once the callback of the frame after it
+/// runs. Returns false if the page stopped producing frames (e.g. it is
+/// hidden); the capture forces a frame of its own, so it can still go ahead.
+pub async fn wait_for_paint(page: &Page, frames: u32) -> Result<bool> {
+    let script = format!(
+        r#"new Promise((resolve) => {{
+            let left = {};
+            const timer = setTimeout(() => resolve(false), {});
+            const tick = () => {{
+                if (--left > 0) return requestAnimationFrame(tick);
+                clearTimeout(timer);
+                resolve(true);
+            }};
+            requestAnimationFrame(tick);
+        }})"#,
+        frames + 1,
+        PAINT_TIMEOUT_MS
+    );
+    let params = EvaluateParams::builder()
+        .expression(script)
+        .await_promise(true)
+        .return_by_value(true)
+        .build()
+        .map_err(anyhow::Error::msg)?;
+
+    page.evaluate_expression(params)
+        .await
+        .context("Failed to wait for paint")?
+        .into_value()
+        .context("Failed to parse paint wait")
+}
+
+/// Capture a screenshot and return Chrome's base64 payload untouched.
+///
+/// With a `scale`, the page is rendered at that device scale factor for this
+/// capture only; the CSS viewport size is left as it is, `zoom` included.
+pub async fn capture_base64(
+    page: &Page,
+    region: Region,
+    image: Image,
+    scale: Option<f64>,
+    zoom: Option<&Zoom>,
+) -> Result<String> {
+    let Some(scale) = scale else {
+        return capture(page, region, image).await;
+    };
+
+    let metrics = match zoom {
+        Some(zoom) => zoom.metrics(Some(scale))?,
+        // Width/height 0 keep the current window size and only override the DPR
+        None => SetDeviceMetricsOverrideParams::builder()
+            .width(0)
+            .height(0)
+            .device_scale_factor(scale)
+            .mobile(false)
+            .build()
+            .map_err(anyhow::Error::msg)?,
+    };
+    page.execute(metrics)
+        .await
+        .context("Failed to set device scale factor")?;
+
+    let result = capture(page, region, image).await;
+
+    zoom::restore(page, zoom)
+        .await
+        .context("Failed to restore device scale factor")?;
+
+    result
+}
+
+/// Width and height of the whole scrollable page in CSS pixels.
+pub async fn page_size(page: &Page) -> Result<(f64, f64)> {
+    let metrics = page
+        .execute(GetLayoutMetricsParams::default())
+        .await
+        .context("Failed to read layout metrics")?;
+    let size = &metrics.result.css_content_size;
+    Ok((size.width, size.height))
+}
+
+pub(super) async fn capture(page: &Page, region: Region, image: Image) -> Result<String> {
+    let clip = match region {
+        Region::Viewport => None,
+        Region::FullPage => {
+            let (width, height) = page_size(page).await?;
+            Some(Viewport {
+                x: 0.0,
+                y: 0.0,
+                width,
+                height,
+                scale: 1.0,
+            })
+        }
+        Region::Clip(rect) => Some(Viewport {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+            scale: 1.0,
+        }),
+    };
+
+    let format = match image.format {
+        ImageFormat::Png => CaptureScreenshotFormat::Png,
+        ImageFormat::Jpeg => CaptureScreenshotFormat::Jpeg,
+        ImageFormat::Webp => CaptureScreenshotFormat::Webp,
+    };
+    let mut params = CaptureScreenshotParams::builder().format(format);
+    if let Some(quality) = image.quality.filter(|_| image.format != ImageFormat::Png) {
+        params = params.quality(quality as i64);
+    }
+    if let Some(clip) = clip {
+        params = params.clip(clip).capture_beyond_viewport(true);
+    }
+    let params = params.build();
+
+    let response = page
+        .execute(params)
+        .await
+        .context("Failed to capture screenshot")?;
+
+    Ok(response.result.data.into())
+}
+
+/// Pixel size of a base64 image in `format`, read from its header.
+pub fn image_dimensions(encoded: &str, format: ImageFormat) -> Option<(u32, u32)> {
+    match format {
+        ImageFormat::Png => png_dimensions(encoded),
+        ImageFormat::Jpeg => jpeg_dimensions(encoded),
+        ImageFormat::Webp => webp_dimensions(encoded),
+    }
+}
+
+/// Pixel size of a base64 JPEG, read from its first start-of-frame segment.
+fn jpeg_dimensions(encoded: &str) -> Option<(u32, u32)> {
+    let prefix = &encoded[..encoded.len().min(JPEG_HEADER_CHARS)];
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, prefix).ok()?;
+    if bytes.get(..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+    let mut at = 2;
+    loop {
+        let segment = bytes.get(at..at + 9)?;
+        if segment[0] != 0xFF {
+            return None;
+        }
+        // SOF0-SOF15, except DHT (C4), JPG (C8) and DAC (CC)
+        if matches!(segment[1], 0xC0..=0xCF) && !matches!(segment[1], 0xC4 | 0xC8 | 0xCC) {
+            let height = u16::from_be_bytes([segment[5], segment[6]]);
+            let width = u16::from_be_bytes([segment[7], segment[8]]);
+            return Some((width as u32, height as u32));
+        }
+        at += 2 + u16::from_be_bytes([segment[2], segment[3]]) as usize;
+    }
+}
+
+/// Pixel size of a base64 WebP, from its VP8, VP8L or VP8X header.
+fn webp_dimensions(encoded: &str) -> Option<(u32, u32)> {
+    // RIFF header (12) + chunk header (8) + up to 10 bytes of frame header is
+    // 30 bytes, within the first 40 base64 characters
+    let header = base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD,
+        encoded.get(..40)?,
+    )
+    .ok()?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WEBP" {
+        return None;
+    }
+    let le16 = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]) as u32;
+    let le24 = |at: usize| le16(at) | ((header[at + 2] as u32) << 16);
+    match &header[12..16] {
+        b"VP8 " => Some((le16(26) & 0x3FFF, le16(28) & 0x3FFF)),
+        b"VP8L" => {
+            let bits = u32::from_le_bytes(header[21..25].try_into().ok()?);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => Some((le24(24) + 1, le24(27) + 1)),
+        _ => None,
+    }
+}
+
+/// Pixel size of a base64 PNG, read from its IHDR chunk.
+pub fn png_dimensions(encoded: &str) -> Option<(u32, u32)> {
+    // Signature (8) + chunk length (4) + "IHDR" (4) + width (4) + height (4)
+    // is 24 bytes, exactly the first 32 base64 characters
+    let header = base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD,
+        encoded.get(..32)?,
+    )
+    .ok()?;
+    if &header[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(header[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(header[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// Decode a base64 screenshot or PDF straight into `path`.
+///
+/// Decoding is streamed through a fixed-size buffer on a blocking thread, so
+/// long full-page captures never hold the decoded image in memory.
+pub async fn write_base64_to_file(data: String, path: PathBuf) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let mut decoder = base64::read::DecoderReader::new(
+            data.as_bytes(),
+            &base64::engine::general_purpose::STANDARD,
+        );
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = std::io::BufWriter::new(file);
+        std::io::copy(&mut decoder, &mut writer)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        writer.flush()?;
+        Ok(())
+    })
+    .await
+    .context("Screenshot writer task failed")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_base64_to_file_decodes_payload() {
+        let path =
+            std::env::temp_dir().join(format!("fgp-screenshot-test-{}.bin", uuid::Uuid::new_v4()));
+        let payload = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            b"\x89PNG\r\n\x1a\nfake-image-bytes",
+        );
+
+        write_base64_to_file(payload, path.clone()).await.unwrap();
+
+        let written = std::fs::read(&path).unwrap();
+        assert_eq!(written, b"\x89PNG\r\n\x1a\nfake-image-bytes");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> ElementRect {
+        ElementRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_padded_clip_adds_context_on_every_side() {
+        let clip = padded_clip(&rect(100.0, 200.0, 50.0, 20.0), 16.0, 1280.0, 4000.0);
+        assert_eq!(clip, rect(84.0, 184.0, 82.0, 52.0));
+    }
+
+    #[test]
+    fn test_padded_clip_stays_within_page() {
+        let clip = padded_clip(&rect(5.0, 3990.0, 1275.0, 10.0), 40.0, 1280.0, 4000.0);
+        assert_eq!(clip, rect(0.0, 3950.0, 1280.0, 50.0));
+    }
+
+    #[test]
+    fn test_padded_clip_never_empty() {
+        let clip = padded_clip(&rect(10.0, 10.0, 0.0, 0.0), 0.0, 1280.0, 800.0);
+        assert_eq!(clip, rect(10.0, 10.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_document_clip_from_viewport_adds_scroll() {
+        let page = Measured {
+            element: None,
+            scroll_x: 0.0,
+            scroll_y: 1200.0,
+            page_width: 1280.0,
+            page_height: 4000.0,
+        };
+        let clip = rect(100.0, 50.0, 300.0, 200.0);
+
+        assert_eq!(
+            document_clip(clip, ClipOrigin::Viewport, &page),
+            Some(rect(100.0, 1250.0, 300.0, 200.0))
+        );
+        assert_eq!(document_clip(clip, ClipOrigin::Document, &page), Some(clip));
+        assert_eq!(
+            document_clip(rect(1200.0, 0.0, 400.0, 100.0), ClipOrigin::Document, &page),
+            Some(rect(1200.0, 0.0, 80.0, 100.0))
+        );
+        assert_eq!(
+            document_clip(rect(0.0, 4000.0, 100.0, 100.0), ClipOrigin::Document, &page),
+            None
+        );
+    }
+
+    #[test]
+    fn test_png_dimensions_reads_ihdr() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&2560u32.to_be_bytes());
+        png.extend_from_slice(&1440u32.to_be_bytes());
+        png.extend_from_slice(b"\x08\x06\x00\x00\x00");
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &png);
+
+        assert_eq!(png_dimensions(&encoded), Some((2560, 1440)));
+    }
+
+    #[test]
+    fn test_png_dimensions_rejects_non_png() {
+        let encoded = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            b"definitely not a png image at all",
+        );
+
+        assert_eq!(png_dimensions(&encoded), None);
+        assert_eq!(png_dimensions("iVBORw0K"), None);
+    }
+
+    fn encode(bytes: &[u8]) -> String {
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+    }
+
+    #[test]
+    fn test_jpeg_dimensions_skip_to_frame_header() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        // APP0 (JFIF) segment, then a baseline frame header
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x10]);
+        jpeg.extend_from_slice(b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x11, 0x08]);
+        jpeg.extend_from_slice(&720u16.to_be_bytes());
+        jpeg.extend_from_slice(&1280u16.to_be_bytes());
+        jpeg.extend_from_slice(&[0x03, 0x01, 0x22, 0x00]);
+
+        assert_eq!(
+            image_dimensions(&encode(&jpeg), ImageFormat::Jpeg),
+            Some((1280, 720))
+        );
+        assert_eq!(
+            image_dimensions(&encode(b"\xFF\xD8"), ImageFormat::Jpeg),
+            None
+        );
+    }
+
+    #[test]
+    fn test_webp_dimensions_read_each_variant() {
+        let riff = |chunk: &[u8], frame: &[u8]| {
+            let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+            webp.extend_from_slice(chunk);
+            webp.extend_from_slice(&[0; 4]);
+            webp.extend_from_slice(frame);
+            webp.resize(40, 0);
+            encode(&webp)
+        };
+
+        // Lossy: frame tag (3) and start code (3), then 14-bit sizes
+        let mut lossy = vec![0x9D, 0x01, 0x2A, 0x9D, 0x01, 0x2A];
+        lossy.extend_from_slice(&1280u16.to_le_bytes());
+        lossy.extend_from_slice(&720u16.to_le_bytes());
+        assert_eq!(
+            image_dimensions(&riff(b"VP8 ", &lossy), ImageFormat::Webp),
+            Some((1280, 720))
+        );
+
+        // Lossless: signature byte, then 14-bit sizes minus one
+        let bits: u32 = (1280 - 1) | ((720 - 1) << 14);
+        let mut lossless = vec![0x2F];
+        lossless.extend_from_slice(&bits.to_le_bytes());
+        assert_eq!(
+            image_dimensions(&riff(b"VP8L", &lossless), ImageFormat::Webp),
+            Some((1280, 720))
+        );
+
+        // Extended: flags (4), then 24-bit canvas sizes minus one
+        let mut extended = vec![0; 4];
+        extended.extend_from_slice(&(1280u32 - 1).to_le_bytes()[..3]);
+        extended.extend_from_slice(&(720u32 - 1).to_le_bytes()[..3]);
+        assert_eq!(
+            image_dimensions(&riff(b"VP8X", &extended), ImageFormat::Webp),
+            Some((1280, 720))
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Scrolling, and scroll position capture and restore.
+//!
+//! Besides the window offset, any element covering a sizeable part of the
+//! viewport that is scrolled away from its origin (feeds, chat panes, app
+//! shells with their own scroller) is recorded with a CSS path so it can be
+//! found again after a reload or back-navigation.
+//!
+//! `by` and `to_bottom` move the window, or a scroll container given by
+//! selector, and report where it ended up. `to_bottom` keeps going while
+//! the content grows, so lazy-loaded feeds render past the first viewport.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+
+use super::client::resolve_selector;
+use crate::models::{ScrollBlock, ScrollRestoreResult, ScrollResult, ScrollState};
+
+/// Finds the scroller (`sel`, or the window's) and reads its position.
+const SCROLLER_JS: &str = r#"const scroller = (sel) => sel === null
+    ? document.scrollingElement || document.documentElement
+    : document.querySelector(sel);
+const position = (el) => ({
+    success: true,
+    x: el.scrollLeft,
+    y: el.scrollTop,
+    scroll_height: el.scrollHeight,
+    at_bottom: Math.ceil(el.scrollTop + el.clientHeight) >= el.scrollHeight - 1,
+});
+"#;
+
+const BY_SCRIPT: &str = r#"((sel, dx, dy) => {
+    const el = scroller(sel);
+    if (!el) return null;
+    el.scrollBy({ left: dx, top: dy, behavior: 'instant' });
+    return position(el);
+})"#;
+
+const BOTTOM_SCRIPT: &str = r#"((sel) => {
+    const el = scroller(sel);
+    if (!el) return null;
+    el.scrollTo({ top: el.scrollHeight, behavior: 'instant' });
+    return position(el);
+})"#;
+
+const ELEMENT_SCRIPT: &str = r#"((sel, block) => {
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    el.scrollIntoView({ behavior: 'instant', block, inline: 'nearest' });
+    const r = el.getBoundingClientRect();
+    const visible = r.width > 0 && r.height > 0 && r.bottom > 0 && r.right > 0 &&
+        r.top < innerHeight && r.left < innerWidth;
+    return { ...position(scroller(null)), visible };
+})"#;
+
+/// Maximum scroll containers recorded per capture.
+const MAX_CONTAINERS: usize = 20;
+
+/// Minimum share of the viewport a container must cover to be recorded.
+const MIN_VIEWPORT_SHARE: f64 = 0.1;
+
+/// Capture the current scroll position of the page and its major containers.
+pub async fn capture(page: &Page) -> Result<ScrollState> {
+    let script = format!(
+        r#"(() => {{
+            const cssPath = (el) => {{
+                const parts = [];
+                while (el && el.nodeType === 1 && el !== document.documentElement) {{
+                    if (el.id) {{
+                        parts.unshift('#' + CSS.escape(el.id));
+                        break;
+                    }}
+                    let part = el.tagName.toLowerCase();
+                    const parent = el.parentElement;
+                    if (parent) {{
+                        const same = Array.from(parent.children).filter(c => c.tagName === el.tagName);
+                        if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(el) + 1) + ')';
+                    }}
+                    parts.unshift(part);
+                    el = parent;
+                }}
+                return parts.join(' > ');
+            }};
+
+            const minArea = window.innerWidth * window.innerHeight * {share};
+            const containers = [];
+            for (const el of document.querySelectorAll('body *')) {{
+                if (containers.length >= {max}) break;
+                if (el.scrollTop === 0 && el.scrollLeft === 0) continue;
+                const rect = el.getBoundingClientRect();
+                if (rect.width * rect.height < minArea) continue;
+                containers.push({{ selector: cssPath(el), x: el.scrollLeft, y: el.scrollTop }});
+            }}
+
+            return {{ x: window.scrollX, y: window.scrollY, containers }};
+        }})()"#,
+        share = MIN_VIEWPORT_SHARE,
+        max = MAX_CONTAINERS,
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to capture scroll position")?
+        .into_value()
+        .context("Failed to parse scroll position")
+}
+
+/// Scroll the page and recorded containers back to `state`.
+pub async fn restore(page: &Page, state: &ScrollState) -> Result<ScrollRestoreResult> {
+    let state_json = serde_json::to_string(state)?;
+    let script = format!(
+        r#"(() => {{
+            const state = {};
+            const containers = [];
+            const missing = [];
+            for (const c of state.containers) {{
+                const el = document.querySelector(c.selector);
+                if (!el) {{
+                    missing.push(c.selector);
+                    continue;
+                }}
+                el.scrollTo({{ left: c.x, top: c.y, behavior: 'instant' }});
+                containers.push({{ selector: c.selector, x: el.scrollLeft, y: el.scrollTop }});
+            }}
+            window.scrollTo({{ left: state.x, top: state.y, behavior: 'instant' }});
+
+            return {{
+                success: missing.length === 0,
+                scroll: {{ x: window.scrollX, y: window.scrollY, containers }},
+                missing,
+            }};
+        }})()"#,
+        state_json
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to restore scroll position")?
+        .into_value()
+        .context("Failed to parse scroll restore result")
+}
+
+async fn run(page: &Page, script: String) -> Result<Option<ScrollResult>> {
+    page.evaluate(format!("(() => {{ {}return {}; }})()", SCROLLER_JS, script))
+        .await
+        .context("Failed to scroll")?
+        .into_value()
+        .context("Failed to parse scroll position")
+}
+
+/// Scroll the window (or the container at `selector`) by `dx`, `dy` pixels.
+pub async fn by(page: &Page, selector: Option<&str>, dx: f64, dy: f64) -> Result<ScrollResult> {
+    let sel = selector.map(resolve_selector);
+    let script = format!(
+        "{}({}, {}, {})",
+        BY_SCRIPT,
+        serde_json::to_string(&sel)?,
+        dx,
+        dy
+    );
+    run(page, script)
+        .await?
+        .with_context(|| format!("Element not found: {}", selector.unwrap_or_default()))
+}
+
+/// Scroll the element at `selector` into view, reporting whether it ended
+/// up (partly) inside the viewport.
+pub async fn to_element(page: &Page, selector: &str, block: ScrollBlock) -> Result<ScrollResult> {
+    let script = format!(
+        "{}({}, {})",
+        ELEMENT_SCRIPT,
+        serde_json::to_string(&resolve_selector(selector))?,
+        serde_json::to_string(&block)?
+    );
+    run(page, script)
+        .await?
+        .with_context(|| format!("Element not found: {}", selector))
+}
+
+/// Scroll the window (or the container at `selector`) to the bottom, again
+/// after each `settle` while the content keeps growing, up to `max_rounds`
+/// times.
+pub async fn to_bottom(
+    page: &Page,
+    selector: Option<&str>,
+    max_rounds: u32,
+    settle: Duration,
+) -> Result<ScrollResult> {
+    let sel = serde_json::to_string(&selector.map(resolve_selector))?;
+    let not_found = || format!("Element not found: {}", selector.unwrap_or_default());
+
+    let mut rounds = 0;
+    let mut result = loop {
+        rounds += 1;
+        let scrolled = run(page, format!("{}({})", BOTTOM_SCRIPT, sel))
+            .await?
+            .with_context(not_found)?;
+        tokio::time::sleep(settle).await;
+        // Lazy loading shows up as a taller scroller once the wait is over
+        let now = run(page, format!("{}({}, 0, 0)", BY_SCRIPT, sel))
+            .await?
+            .with_context(not_found)?;
+        if now.scroll_height <= scrolled.scroll_height || rounds >= max_rounds {
+            break now;
+        }
+    };
+    result.rounds = Some(rounds);
+
+    Ok(result)
+}
@@ -0,0 +1,234 @@
+//! Choosing options of a `<select>`.
+//!
+//! Options are matched by `value` attribute, by label, or by position. Labels
+//! match the option's visible text (its `label` attribute when set) exactly,
+//! then ignoring case and runs of whitespace, so `"United  states"` finds
+//! "United States". The selection is made through the DOM, with the `input`
+//! and `change` events a user's choice fires, and read back from
+//! `selectedOptions`.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use super::frames::{self, ChildFrame};
+use super::shadow;
+use crate::models::{OptionQuery, SelectOption, SelectResult};
+
+/// Options listed in a miss before the rest are elided.
+const MAX_LISTED_OPTIONS: usize = 20;
+
+const OPTIONS_SCRIPT: &str = r#"((sel) => {
+    const el = deepQuery(sel);
+    if (!el) return null;
+    if (el.tagName !== 'SELECT') return { is_select: false };
+    return {
+        is_select: true,
+        disabled: el.disabled,
+        multiple: el.multiple,
+        options: [...el.options].map((o, index) => ({
+            index,
+            value: o.value,
+            label: o.label.trim(),
+            disabled: o.disabled || (o.parentElement.tagName === 'OPTGROUP' && o.parentElement.disabled),
+        })),
+    };
+})"#;
+
+const SELECT_SCRIPT: &str = r#"((sel, indices) => {
+    const el = deepQuery(sel);
+    if (!el) return null;
+    el.focus();
+    [...el.options].forEach((o, i) => { o.selected = indices.includes(i); });
+    el.dispatchEvent(new Event('input', { bubbles: true }));
+    el.dispatchEvent(new Event('change', { bubbles: true }));
+    return {
+        multiple: el.multiple,
+        selected: [...el.selectedOptions].map((o) => ({
+            index: o.index,
+            value: o.value,
+            label: o.label.trim(),
+        })),
+    };
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Listing {
+    is_select: bool,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    multiple: bool,
+    #[serde(default)]
+    options: Vec<SelectOption>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Selected {
+    multiple: bool,
+    selected: Vec<SelectOption>,
+}
+
+/// Lowercase with whitespace runs collapsed.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// `"A" (a), "B" (b), ...` for error messages.
+fn list(options: &[SelectOption]) -> String {
+    let mut listed: Vec<String> = options
+        .iter()
+        .take(MAX_LISTED_OPTIONS)
+        .map(|o| format!("\"{}\" ({})", o.label, o.value))
+        .collect();
+    if options.len() > MAX_LISTED_OPTIONS {
+        listed.push(format!("and {} more", options.len() - MAX_LISTED_OPTIONS));
+    }
+    listed.join(", ")
+}
+
+/// Indices of the options `query` asks for.
+fn resolve(query: &OptionQuery, options: &[SelectOption]) -> Result<Vec<usize>> {
+    let found: Vec<(String, Option<&SelectOption>)> = match query {
+        OptionQuery::Value(values) => values
+            .iter()
+            .map(|v| {
+                let found = options.iter().find(|o| &o.value == v);
+                (format!("value '{}'", v), found)
+            })
+            .collect(),
+        OptionQuery::Label(labels) => labels
+            .iter()
+            .map(|l| {
+                let found = options.iter().find(|o| o.label == l.trim()).or_else(|| {
+                    let wanted = normalize(l);
+                    options.iter().find(|o| normalize(&o.label) == wanted)
+                });
+                (format!("label '{}'", l), found)
+            })
+            .collect(),
+        OptionQuery::Index(indices) => indices
+            .iter()
+            .map(|&i| (format!("index {}", i), options.get(i)))
+            .collect(),
+    };
+    if found.is_empty() {
+        anyhow::bail!("No option given to select");
+    }
+
+    let mut indices = Vec::new();
+    for (wanted, option) in found {
+        let option = option.with_context(|| {
+            if options.is_empty() {
+                format!("No option with {}; the select has no options", wanted)
+            } else {
+                format!("No option with {}; options: {}", wanted, list(options))
+            }
+        })?;
+        if option.disabled {
+            anyhow::bail!("Option \"{}\" is disabled", option.label);
+        }
+        if !indices.contains(&option.index) {
+            indices.push(option.index);
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Select the options of the `<select>` at `selector` in `frame` (default:
+/// the page) that `query` asks for, replacing the current selection.
+pub async fn select_options(
+    page: &Page,
+    frame: Option<&ChildFrame>,
+    selector: &str,
+    query: &OptionQuery,
+) -> Result<SelectResult> {
+    let css = serde_json::to_string(&resolve_selector(selector))?;
+    let listing: Option<Listing> =
+        frames::evaluate(page, frame, shadow::call(OPTIONS_SCRIPT, &css))
+            .await
+            .context("Failed to read select options")?;
+    let listing = listing.with_context(|| format!("Element not found: {}", selector))?;
+    if !listing.is_select {
+        anyhow::bail!("Element is not a <select>: {}", selector);
+    }
+    if listing.disabled {
+        anyhow::bail!("Element is disabled: {}", selector);
+    }
+
+    let indices = resolve(query, &listing.options)?;
+    if indices.len() > 1 && !listing.multiple {
+        anyhow::bail!(
+            "{} allows a single option, but {} were given",
+            selector,
+            indices.len()
+        );
+    }
+
+    let selected: Option<Selected> = frames::evaluate(
+        page,
+        frame,
+        shadow::call(
+            SELECT_SCRIPT,
+            &format!("{}, {}", css, serde_json::to_string(&indices)?),
+        ),
+    )
+    .await
+    .context("Failed to select option")?;
+    let selected = selected.with_context(|| format!("Element not found: {}", selector))?;
+
+    Ok(SelectResult {
+        success: indices
+            .iter()
+            .all(|i| selected.selected.iter().any(|o| o.index == *i)),
+        selector: selector.to_string(),
+        multiple: selected.multiple,
+        selected: selected.selected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(index: usize, value: &str, label: &str, disabled: bool) -> SelectOption {
+        SelectOption {
+            index,
+            value: value.to_string(),
+            label: label.to_string(),
+            disabled,
+        }
+    }
+
+    #[test]
+    fn test_resolve_options() {
+        let options = vec![
+            option(0, "", "Choose a country", true),
+            option(1, "us", "United States", false),
+            option(2, "gb", "United Kingdom", false),
+        ];
+
+        let by_value = OptionQuery::Value(vec!["gb".to_string()]);
+        assert_eq!(resolve(&by_value, &options).unwrap(), vec![2]);
+        let by_label = OptionQuery::Label(vec![" united  STATES ".to_string()]);
+        assert_eq!(resolve(&by_label, &options).unwrap(), vec![1]);
+        let by_index = OptionQuery::Index(vec![2, 1, 2]);
+        assert_eq!(resolve(&by_index, &options).unwrap(), vec![2, 1]);
+
+        let missing = OptionQuery::Value(vec!["fr".to_string()]);
+        let err = resolve(&missing, &options).unwrap_err().to_string();
+        assert!(err.contains("value 'fr'"));
+        assert!(err.contains("\"United Kingdom\" (gb)"));
+        let placeholder = OptionQuery::Index(vec![0]);
+        assert!(resolve(&placeholder, &options)
+            .unwrap_err()
+            .to_string()
+            .contains("disabled"));
+        assert!(resolve(&OptionQuery::Index(vec![]), &options).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Text selection and copying.
+//!
+//! A range is selected in an element's text by character offsets, or by
+//! matching text (whitespace-insensitive, so rendered text with its line
+//! breaks and indentation collapsed matches). Offsets count characters of
+//! the element's text nodes, the same offsets `get_selection` reports, so a
+//! selection read back can be selected again. In an input or textarea the
+//! range is set on its value with `setSelectionRange`.
+//!
+//! Copying runs `execCommand('copy')` as a user gesture, so the page's own
+//! `copy` handlers run as they would for Ctrl+C, and reports what they put
+//! on the clipboard when they replace the selection's content (attribution
+//! lines, share links). Elements in iframes aren't reached.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use super::shadow;
+use crate::models::{ClipboardCopy, TextRange, TextSelection};
+
+/// Reads the current selection; prefixed to every script here.
+const SELECTION_JS: &str = r#"
+    const pathOf = (el) => {
+        const parts = [];
+        while (el && el !== document.documentElement) {
+            if (el.id && document.querySelectorAll('#' + CSS.escape(el.id)).length === 1) {
+                parts.unshift('#' + CSS.escape(el.id));
+                return parts.join(' > ');
+            }
+            const parent = el.parentElement;
+            if (!parent) break;
+            parts.unshift(el.tagName.toLowerCase() + ':nth-child(' +
+                ([...parent.children].indexOf(el) + 1) + ')');
+            el = parent;
+        }
+        parts.unshift('html');
+        return parts.join(' > ');
+    };
+    const selectorOf = (el) => el.hasAttribute('data-fgp-ref')
+        ? '@' + el.getAttribute('data-fgp-ref') : pathOf(el);
+    const isField = (el) => (el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement)
+        && el.selectionStart !== null;
+    const current = () => {
+        const active = deepActiveElement();
+        if (active && isField(active)) {
+            const start = active.selectionStart, end = active.selectionEnd;
+            return { text: active.value.slice(start, end), html: null, collapsed: start === end,
+                element: selectorOf(active), start, end };
+        }
+        const selection = getSelection();
+        if (!selection || selection.rangeCount === 0) {
+            return { text: '', html: null, collapsed: true, element: null, start: null, end: null };
+        }
+        const range = selection.getRangeAt(0);
+        let container = range.commonAncestorContainer;
+        if (container.nodeType !== 1) container = container.parentElement;
+        const before = document.createRange();
+        before.selectNodeContents(container);
+        before.setEnd(range.startContainer, range.startOffset);
+        const start = before.toString().length;
+        const holder = document.createElement('div');
+        holder.appendChild(range.cloneContents());
+        return { text: selection.toString(), html: holder.innerHTML, collapsed: range.collapsed,
+            element: selectorOf(container), start, end: start + range.toString().length };
+    };
+"#;
+
+/// Select a range in the element at `sel` (default: the body): characters
+/// `start..end` of its text, or the `occurrence`-th match of `text`.
+const SELECT_SCRIPT: &str = r#"((sel, start, end, text, occurrence) => {
+    const root = sel === null ? document.body : deepQuery(sel);
+    if (!root) return { status: 'not_found' };
+
+    if (isField(root)) {
+        const value = root.value;
+        if (text !== null) {
+            let at = -1;
+            for (let i = 0; i < occurrence; i++) {
+                at = value.indexOf(text, at + 1);
+                if (at < 0) return { status: 'no_match' };
+            }
+            start = at;
+            end = at + text.length;
+        }
+        if (end === null) end = value.length;
+        if (start > value.length || end > value.length) {
+            return { status: 'out_of_range', length: value.length };
+        }
+        root.focus();
+        root.setSelectionRange(start, end);
+        return { status: 'selected', selection: current() };
+    }
+
+    const walker = document.createTreeWalker(root, NodeFilter.SHOW_TEXT);
+    const nodes = [];
+    while (walker.nextNode()) nodes.push(walker.currentNode);
+    let from, to;
+
+    if (text !== null) {
+        // Text with whitespace runs collapsed, and where each character came from
+        let flat = '';
+        const origin = [];
+        for (const node of nodes) {
+            const data = node.data;
+            for (let i = 0; i < data.length; i++) {
+                if (/\s/.test(data[i])) {
+                    if (flat === '' || flat.endsWith(' ')) continue;
+                    flat += ' ';
+                } else {
+                    flat += data[i];
+                }
+                origin.push([node, i]);
+            }
+        }
+        const query = text.replace(/\s+/g, ' ').trim();
+        if (!query) return { status: 'no_match' };
+        let at = -1;
+        for (let i = 0; i < occurrence; i++) {
+            at = flat.indexOf(query, at + 1);
+            if (at < 0) return { status: 'no_match' };
+        }
+        const last = origin[at + query.length - 1];
+        from = origin[at];
+        to = [last[0], last[1] + 1];
+    } else {
+        const length = nodes.reduce((n, node) => n + node.data.length, 0);
+        if (end === null) end = length;
+        if (start > length || end > length) return { status: 'out_of_range', length };
+        const locate = (offset) => {
+            let seen = 0;
+            for (const node of nodes) {
+                if (offset <= seen + node.data.length) return [node, offset - seen];
+                seen += node.data.length;
+            }
+            return [root, root.childNodes.length];
+        };
+        from = locate(start);
+        to = locate(end);
+    }
+
+    const range = document.createRange();
+    range.setStart(from[0], from[1]);
+    range.setEnd(to[0], to[1]);
+    const selection = getSelection();
+    selection.removeAllRanges();
+    selection.addRange(range);
+    return { status: 'selected', selection: current() };
+})"#;
+
+/// Copy the selection, watching the `copy` event for page handlers that
+/// replace what goes on the clipboard.
+const COPY_SCRIPT: &str = r#"(() => {
+    const selected = current();
+    let event = null;
+    const watch = (e) => { event = e; };
+    window.addEventListener('copy', watch);
+    const copied = document.execCommand('copy');
+    window.removeEventListener('copy', watch);
+    if (event && event.defaultPrevented) {
+        return { copied, text: event.clipboardData.getData('text/plain'),
+            html: event.clipboardData.getData('text/html') || null, modified: true };
+    }
+    return { copied, text: selected.text, html: selected.html, modified: false };
+})"#;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Selected {
+    Selected { selection: TextSelection },
+    NotFound,
+    NoMatch,
+    OutOfRange { length: usize },
+}
+
+/// `script` (a function expression) applied to `args`, with the selection
+/// and shadow DOM helpers in scope.
+fn call(script: &str, args: &str) -> String {
+    shadow::call(
+        &format!("(() => {{{}    return {};\n}})()", SELECTION_JS, script),
+        args,
+    )
+}
+
+async fn run<T: serde::de::DeserializeOwned>(page: &Page, expression: String) -> Result<T> {
+    let params = EvaluateParams::builder()
+        .expression(expression)
+        .user_gesture(true)
+        .return_by_value(true)
+        .build()
+        .map_err(anyhow::Error::msg)?;
+    page.evaluate_expression(params)
+        .await
+        .context("Failed to reach the selection")?
+        .into_value()
+        .context("Failed to parse selection")
+}
+
+/// Select `range` in the element at `selector`, or in the whole page
+/// without one.
+pub async fn select(
+    page: &Page,
+    selector: Option<&str>,
+    range: &TextRange,
+) -> Result<TextSelection> {
+    let css = serde_json::to_string(&selector.map(resolve_selector))?;
+    let args = match range {
+        TextRange::Offsets { start, end } => {
+            format!(
+                "{}, {}, {}, null, 1",
+                css,
+                start,
+                serde_json::to_string(end)?
+            )
+        }
+        TextRange::Text { text, occurrence } => format!(
+            "{}, 0, null, {}, {}",
+            css,
+            serde_json::to_string(text)?,
+            occurrence
+        ),
+    };
+    let outcome: Selected = run(page, call(SELECT_SCRIPT, &args)).await?;
+    let target = selector.unwrap_or("the page");
+    match outcome {
+        Selected::Selected { selection } => Ok(selection),
+        Selected::NotFound => anyhow::bail!("Element not found: {}", target),
+        Selected::NoMatch => match range {
+            TextRange::Text { text, occurrence } if *occurrence > 1 => {
+                anyhow::bail!(
+                    "Text {:?} occurs fewer than {} times in {}",
+                    text,
+                    occurrence,
+                    target
+                )
+            }
+            TextRange::Text { text, .. } => {
+                anyhow::bail!("Text not found in {}: {:?}", target, text)
+            }
+            TextRange::Offsets { .. } => anyhow::bail!("Nothing to select in {}", target),
+        },
+        Selected::OutOfRange { length } => {
+            anyhow::bail!("Offsets past the end of {} ({} characters)", target, length)
+        }
+    }
+}
+
+/// The current selection: in the focused input or textarea, or else in the
+/// document.
+pub async fn current(page: &Page) -> Result<TextSelection> {
+    run(page, call("current", "")).await
+}
+
+/// Copy the current selection to the clipboard.
+pub async fn copy(page: &Page) -> Result<ClipboardCopy> {
+    run(page, call(COPY_SCRIPT, "")).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_includes_helpers() {
+        let script = call("current", "");
+        assert!(script.contains("const deepActiveElement"));
+        assert!(script.contains("const current"));
+        assert!(script.trim_end().ends_with("})()"));
+    }
+}
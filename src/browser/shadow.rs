@@ -0,0 +1,209 @@
+//! Open shadow roots.
+//!
+//! CSS selectors stop at shadow boundaries, so an `@eN` ref (an attribute
+//! selector) on an element inside a web component is found by neither
+//! `document.querySelector` nor CDP's `DOM.querySelector`. Scripts that look
+//! up snapshot elements run with `deepQuery`/`deepQueryAll` in scope (see
+//! `call`), which search the document and then every open shadow root in it.
+//! Pointer actions go through `Target`: an element CDP finds is clicked as
+//! before, and one it can't find (or one in a child frame, see `frames`) is
+//! located by script and clicked at its center with CDP mouse events, as a
+//! user's click would be.
+//!
+//! Closed shadow roots can't be reached from the page and stay opaque.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchMouseEventParams, DispatchMouseEventType, MouseButton,
+};
+use chromiumoxide::element::Element;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use super::frames::{self, ChildFrame};
+
+/// Declares `shadowRoots(root)`, `deepQuery(sel)`, `deepQueryAll(sel)` and
+/// `deepActiveElement()`. Light DOM matches come before shadow ones, and
+/// outer roots before the roots nested in them.
+pub(super) const DEEP_QUERY: &str = r#"
+    const shadowRoots = (root) => {
+        const roots = [];
+        const walk = (r) => {
+            for (const el of r.querySelectorAll('*')) {
+                if (el.shadowRoot) {
+                    roots.push(el.shadowRoot);
+                    walk(el.shadowRoot);
+                }
+            }
+        };
+        walk(root);
+        return roots;
+    };
+    const deepQuery = (sel) => {
+        const hit = document.querySelector(sel);
+        if (hit) return hit;
+        for (const root of shadowRoots(document)) {
+            const found = root.querySelector(sel);
+            if (found) return found;
+        }
+        return null;
+    };
+    const deepQueryAll = (sel) => [document, ...shadowRoots(document)]
+        .flatMap((root) => [...root.querySelectorAll(sel)]);
+    const deepActiveElement = () => {
+        let el = document.activeElement;
+        while (el && el.shadowRoot && el.shadowRoot.activeElement) el = el.shadowRoot.activeElement;
+        return el;
+    };
+"#;
+
+/// Scrolls the element into view and returns its center in viewport pixels.
+const LOCATE_SCRIPT: &str = r#"((sel) => {
+    const el = deepQuery(sel);
+    if (!el) return null;
+    el.scrollIntoView({ block: 'center', inline: 'center' });
+    const r = el.getBoundingClientRect();
+    return { x: r.left + r.width / 2, y: r.top + r.height / 2 };
+})"#;
+
+/// `script` (a function expression) applied to `args`, with the
+/// `DEEP_QUERY` helpers in scope.
+pub(super) fn call(script: &str, args: &str) -> String {
+    format!(
+        "(() => {{{}    return ({})({});\n}})()",
+        DEEP_QUERY, script, args
+    )
+}
+
+/// Viewport CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// An element pointer actions land on.
+pub enum Target {
+    /// Found by CDP in the document
+    Element(Element),
+    /// Inside an open shadow root or a child frame: its center in the
+    /// top-level viewport, already scrolled into view
+    Located(Point),
+}
+
+impl Target {
+    /// Find the element at `selector` in `frame` (default: the page),
+    /// looking inside open shadow roots when it isn't in the document itself.
+    pub async fn find(page: &Page, frame: Option<&ChildFrame>, selector: &str) -> Result<Self> {
+        let css = resolve_selector(selector);
+        if let Some(frame) = frame {
+            return match frames::locate(page, frame, &css).await? {
+                Some(point) => Ok(Target::Located(point)),
+                None => anyhow::bail!("Element not found: {}", selector),
+            };
+        }
+        match page.find_element(&css).await {
+            Ok(element) => Ok(Target::Element(element)),
+            Err(e) => {
+                let point: Option<Point> = page
+                    .evaluate(call(LOCATE_SCRIPT, &serde_json::to_string(&css)?))
+                    .await
+                    .context("Failed to search shadow roots")?
+                    .into_value()
+                    .context("Failed to parse element position")?;
+                match point {
+                    Some(point) => Ok(Target::Located(point)),
+                    None => Err(e).context("Element not found"),
+                }
+            }
+        }
+    }
+
+    /// Scroll the element into view.
+    pub async fn scroll_into_view(&self) -> Result<()> {
+        if let Target::Element(element) = self {
+            element.scroll_into_view().await?;
+        }
+        Ok(())
+    }
+
+    /// Where a click on the element lands.
+    pub async fn clickable_point(&self) -> Result<Point> {
+        match self {
+            Target::Element(element) => {
+                let point = element.clickable_point().await?;
+                Ok(Point {
+                    x: point.x,
+                    y: point.y,
+                })
+            }
+            Target::Located(point) => Ok(*point),
+        }
+    }
+
+    /// Left-click the element once.
+    pub async fn click(&self, page: &Page) -> Result<()> {
+        match self {
+            Target::Element(element) => {
+                element.click().await?;
+            }
+            Target::Located(point) => {
+                move_to(page, *point).await?;
+                for (kind, buttons) in [
+                    (DispatchMouseEventType::MousePressed, 1),
+                    (DispatchMouseEventType::MouseReleased, 0),
+                ] {
+                    let event = DispatchMouseEventParams::builder()
+                        .r#type(kind)
+                        .x(point.x)
+                        .y(point.y)
+                        .button(MouseButton::Left)
+                        .buttons(buttons)
+                        .click_count(1)
+                        .build()
+                        .map_err(anyhow::Error::msg)?;
+                    page.execute(event).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the pointer over the element.
+    pub async fn hover(&self, page: &Page) -> Result<()> {
+        match self {
+            Target::Element(element) => {
+                element.hover().await?;
+            }
+            Target::Located(point) => move_to(page, *point).await?,
+        }
+        Ok(())
+    }
+}
+
+async fn move_to(page: &Page, point: Point) -> Result<()> {
+    let event = DispatchMouseEventParams::builder()
+        .r#type(DispatchMouseEventType::MouseMoved)
+        .x(point.x)
+        .y(point.y)
+        .button(MouseButton::None)
+        .build()
+        .map_err(anyhow::Error::msg)?;
+    page.execute(event).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_wraps_script_with_helpers() {
+        let script = call("((sel) => deepQuery(sel))", "\"[data-fgp-ref='e3']\"");
+        assert!(script.starts_with("(() => {"));
+        assert!(script.contains("const deepQuery = (sel) =>"));
+        assert!(script.contains("return (((sel) => deepQuery(sel)))(\"[data-fgp-ref='e3']\");"));
+        assert!(script.ends_with("})()"));
+    }
+}
@@ -0,0 +1,358 @@
+//! Diffs between consecutive snapshots.
+//!
+//! A ref lasts only as long as its DOM node, so an element the page
+//! re-rendered comes back under a new ref. Lines are compared without their
+//! `[@eN]` ref; unchanged lines with a new ref don't count as changes, and
+//! the lines shown carry the current refs.
+//!
+//! The node-level diff matches nodes the same way, by role and name in
+//! document order. A matched node whose value, focus or focusability moved
+//! is changed, and so is a removed node followed by an added one of the same
+//! role, which is how a renamed element ("Follow" to "Following") shows up.
+//! Changed nodes report both refs; a previous ref that differs no longer
+//! resolves.
+
+use crate::models::{AriaNode, AriaNodeChange, AriaSnapshotDiff};
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 2;
+
+/// Largest line-by-line comparison (old x new lines of the changed middle)
+/// done exactly; beyond it the middle is reported as replaced wholesale.
+const MAX_COMPARE_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    /// Old line, new line
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// A text snapshot line without its ` [@eN]` ref.
+fn without_ref(line: &str) -> String {
+    match line.find(" [@e") {
+        Some(start) => match line[start..].find(']') {
+            Some(end) => format!("{}{}", &line[..start], &line[start + end + 1..]),
+            None => line.to_string(),
+        },
+        None => line.to_string(),
+    }
+}
+
+/// Edit script turning `old` into `new` (longest common subsequence).
+fn edits<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    if a.len() * b.len() > MAX_COMPARE_CELLS {
+        ops.extend((0..a.len()).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..b.len()).map(|j| Op::Insert(prefix + j)));
+    } else {
+        // lcs[i][j]: common lines of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push(Op::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == b.len()
+                || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                ops.push(Op::Delete(prefix + i));
+                i += 1;
+            } else {
+                ops.push(Op::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    ops.extend((0..suffix).map(|k| Op::Equal(old_end + k, new_end + k)));
+    ops
+}
+
+/// Unified diff (hunks only, no file headers) from `old` to `new` text
+/// snapshots; empty when nothing changed.
+pub fn unified(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = edits(
+        &old_lines.iter().map(|l| without_ref(l)).collect::<Vec<_>>(),
+        &new_lines.iter().map(|l| without_ref(l)).collect::<Vec<_>>(),
+    );
+
+    // Group changes whose context would overlap into one hunk
+    let changed: Vec<usize> = (0..ops.len())
+        .filter(|&k| !matches!(ops[k], Op::Equal(..)))
+        .collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &k in &changed {
+        let start = k.saturating_sub(CONTEXT);
+        let end = (k + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::new();
+    for (start, end) in hunks {
+        // Positions before the hunk's first line, in each file
+        let (mut old_at, mut new_at) = ops[..start].iter().fold((0, 0), |(o, n), op| match op {
+            Op::Equal(..) => (o + 1, n + 1),
+            Op::Delete(_) => (o + 1, n),
+            Op::Insert(_) => (o, n + 1),
+        });
+        let mut body = String::new();
+        let (old_start, new_start) = (old_at, new_at);
+        for op in &ops[start..end] {
+            match *op {
+                Op::Equal(_, j) => {
+                    body.push_str(&format!(" {}\n", new_lines[j]));
+                    old_at += 1;
+                    new_at += 1;
+                }
+                Op::Delete(i) => {
+                    body.push_str(&format!("-{}\n", old_lines[i]));
+                    old_at += 1;
+                }
+                Op::Insert(j) => {
+                    body.push_str(&format!("+{}\n", new_lines[j]));
+                    new_at += 1;
+                }
+            }
+        }
+        let range = |from: usize, to: usize| {
+            let count = to - from;
+            // An empty range names the line before it
+            format!("{},{}", if count == 0 { from } else { from + 1 }, count)
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n{}",
+            range(old_start, old_at),
+            range(new_start, new_at),
+            body
+        ));
+    }
+    out
+}
+
+/// Nodes in document order, without their children.
+fn flatten(nodes: &[AriaNode], out: &mut Vec<AriaNode>) {
+    for node in nodes {
+        out.push(AriaNode {
+            children: Vec::new(),
+            ..node.clone()
+        });
+        flatten(&node.children, out);
+    }
+}
+
+/// Properties that differ between two matched nodes.
+fn changed_fields(before: &AriaNode, after: &AriaNode) -> Vec<String> {
+    let mut fields = Vec::new();
+    if before.name != after.name {
+        fields.push("name");
+    }
+    if before.value != after.value {
+        fields.push("value");
+    }
+    if before.focused != after.focused {
+        fields.push("focused");
+    }
+    if before.focusable != after.focusable {
+        fields.push("focusable");
+    }
+    fields.into_iter().map(str::to_string).collect()
+}
+
+/// Count `after` as unchanged or record how it changed from `before`.
+fn compare(before: &AriaNode, after: &AriaNode, diff: &mut AriaSnapshotDiff) {
+    let fields = changed_fields(before, after);
+    if fields.is_empty() {
+        diff.unchanged += 1;
+        return;
+    }
+    diff.changed.push(AriaNodeChange {
+        ref_id: after.ref_id.clone(),
+        previous_ref: before.ref_id.clone(),
+        role: after.role.clone(),
+        fields,
+        before: before.clone(),
+        after: after.clone(),
+    });
+}
+
+/// Settle a run of removed and added nodes between two matches: same-role
+/// pairs are changes, the rest stay removed or added.
+fn settle(removed: &mut Vec<&AriaNode>, added: &mut Vec<&AriaNode>, diff: &mut AriaSnapshotDiff) {
+    for before in removed.drain(..) {
+        match added.iter().position(|after| after.role == before.role) {
+            Some(k) => compare(before, added.remove(k), diff),
+            None => diff.removed.push(before.clone()),
+        }
+    }
+    diff.added.extend(added.drain(..).cloned());
+}
+
+/// Node-level diff from `old` to `new` snapshot nodes, for the page at `url`.
+pub fn nodes(url: &str, old: &[AriaNode], new: &[AriaNode]) -> AriaSnapshotDiff {
+    let (mut old_nodes, mut new_nodes) = (Vec::new(), Vec::new());
+    flatten(old, &mut old_nodes);
+    flatten(new, &mut new_nodes);
+    let key = |node: &AriaNode| (node.role.clone(), node.name.clone());
+    let ops = edits(
+        &old_nodes.iter().map(key).collect::<Vec<_>>(),
+        &new_nodes.iter().map(key).collect::<Vec<_>>(),
+    );
+
+    let mut diff = AriaSnapshotDiff {
+        url: url.to_string(),
+        compared: true,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    for op in ops {
+        match op {
+            Op::Equal(i, j) => {
+                settle(&mut removed, &mut added, &mut diff);
+                compare(&old_nodes[i], &new_nodes[j], &mut diff);
+            }
+            Op::Delete(i) => removed.push(&old_nodes[i]),
+            Op::Insert(j) => added.push(&new_nodes[j]),
+        }
+    }
+    settle(&mut removed, &mut added, &mut diff);
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_ignores_renumbered_refs() {
+        let old = "- heading \"Inbox\" [@e1]\n\
+                   - button \"Compose\" [@e2]\n\
+                   - link \"Starred\" [@e3]\n\
+                   - link \"Sent\" [@e4]\n\
+                   - link \"Drafts\" [@e5]\n\
+                   - link \"Archive\" [@e6]\n\
+                   - link \"Trash\" [@e7]\n\
+                   - link \"Spam\" [@e8]\n";
+        let new = "- heading \"Inbox\" [@e1]\n\
+                   - alert \"Message sent\" [@e2]\n\
+                   - button \"Compose\" [@e3]\n\
+                   - link \"Starred\" [@e4]\n\
+                   - link \"Sent\" [@e5]\n\
+                   - link \"Drafts\" [@e6]\n\
+                   - link \"Archive\" [@e7]\n\
+                   - link \"Trash\" [@e8]\n";
+
+        assert_eq!(
+            unified(old, new),
+            "@@ -1,3 +1,4 @@\n \
+             - heading \"Inbox\" [@e1]\n\
+             +- alert \"Message sent\" [@e2]\n \
+             - button \"Compose\" [@e3]\n \
+             - link \"Starred\" [@e4]\n\
+             @@ -6,3 +7,2 @@\n \
+             - link \"Archive\" [@e7]\n \
+             - link \"Trash\" [@e8]\n\
+             -- link \"Spam\" [@e8]\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_changed_value_and_no_change() {
+        let old = "- textbox \"Email\" [@e1]\n- button \"Next\" [@e2]\n";
+        let new = "- textbox \"Email\" [@e1] [focused]: ada@example.com\n- button \"Next\" [@e2]\n";
+
+        assert_eq!(
+            unified(old, new),
+            "@@ -1,2 +1,2 @@\n\
+             -- textbox \"Email\" [@e1]\n\
+             +- textbox \"Email\" [@e1] [focused]: ada@example.com\n \
+             - button \"Next\" [@e2]\n"
+        );
+        assert_eq!(unified(new, new), "");
+    }
+
+    #[test]
+    fn test_nodes_added_removed_changed() {
+        let node = |ref_id: &str, role: &str, name: &str| AriaNode {
+            ref_id: ref_id.to_string(),
+            role: role.to_string(),
+            name: Some(name.to_string()),
+            value: None,
+            focusable: true,
+            focused: false,
+            rect: None,
+            in_viewport: false,
+            children: vec![],
+        };
+        let old = vec![
+            node("@e1", "heading", "Profile"),
+            node("@e2", "button", "Follow"),
+            node("@e3", "textbox", "Comment"),
+            node("@e4", "link", "Report"),
+        ];
+        let mut comment = node("@e4", "textbox", "Comment");
+        comment.value = Some("Nice".to_string());
+        comment.focused = true;
+        let new = vec![
+            node("@e1", "heading", "Profile"),
+            node("@e2", "alert", "Now following"),
+            node("@e3", "button", "Following"),
+            comment,
+        ];
+
+        let diff = nodes("https://example.com/ada", &old, &new);
+        assert!(diff.compared);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].ref_id, "@e2");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].ref_id, "@e4");
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(
+            (
+                diff.changed[0].previous_ref.as_str(),
+                diff.changed[0].ref_id.as_str()
+            ),
+            ("@e2", "@e3")
+        );
+        assert_eq!(diff.changed[0].fields, vec!["name"]);
+        assert_eq!(diff.changed[1].ref_id, "@e4");
+        assert_eq!(diff.changed[1].fields, vec!["value", "focused"]);
+
+        let same = nodes("https://example.com/ada", &new, &new);
+        assert_eq!(same.unchanged, 4);
+        assert!(same.added.is_empty() && same.removed.is_empty() && same.changed.is_empty());
+    }
+}
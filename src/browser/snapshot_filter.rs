@@ -0,0 +1,219 @@
+//! Narrowing a snapshot to the nodes a caller cares about.
+//!
+//! Role filters work on the nodes alone. Visibility and subtree filters ask
+//! the page, through the `data-fgp-ref` attributes the snapshot injected, so
+//! they only keep nodes that carry a ref. The subtree root is marked before
+//! the snapshot is taken, while its ref still names the element the caller
+//! saw (a re-rendered element gets a new one). A node
+//! that's filtered out gives its place in the tree to whichever of its
+//! children are kept.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+
+use crate::models::{AriaNode, AriaSnapshot, SnapshotFilter};
+
+/// Roles a user acts on; focusable nodes of other roles count as well.
+const INTERACTIVE_ROLES: &[&str] = &[
+    "button",
+    "link",
+    "textbox",
+    "searchbox",
+    "checkbox",
+    "radio",
+    "switch",
+    "combobox",
+    "listbox",
+    "option",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "tab",
+    "treeitem",
+    "slider",
+    "spinbutton",
+];
+
+/// Attribute marking the subtree root between `mark_root` and `apply`.
+pub(super) const ROOT_ATTRIBUTE: &str = "data-fgp-root";
+
+/// Move the root mark to the element with the given ref; false when no
+/// element has it.
+const MARK_SCRIPT: &str = r#"((ref, attr) => {
+    document.querySelectorAll('[' + attr + ']').forEach((el) => el.removeAttribute(attr));
+    const el = document.querySelector('[data-fgp-ref="' + ref + '"]');
+    if (el) el.setAttribute(attr, '');
+    return !!el;
+})"#;
+
+/// Refs (without `@`) of the marked root and its descendants, or of the
+/// whole page without a root, optionally only rendered ones; null when the
+/// root has gone.
+const REFS_SCRIPT: &str = r#"((attr, rooted, visibleOnly) => {
+    const top = rooted ? document.querySelector('[' + attr + ']') : document;
+    if (!top) return null;
+    const all = [...top.querySelectorAll('[data-fgp-ref]')];
+    if (rooted) {
+        top.removeAttribute(attr);
+        if (top.hasAttribute('data-fgp-ref')) all.unshift(top);
+    }
+    const visible = (el) => {
+        const r = el.getBoundingClientRect();
+        const s = getComputedStyle(el);
+        return r.width > 0 && r.height > 0 && s.visibility !== 'hidden' && s.opacity !== '0';
+    };
+    return all.filter((el) => !visibleOnly || visible(el)).map((el) => el.getAttribute('data-fgp-ref'));
+})"#;
+
+fn matches(node: &AriaNode, filter: &SnapshotFilter, on_page: Option<&HashSet<String>>) -> bool {
+    let role = node.role.as_str();
+    let in_page = match on_page {
+        Some(refs) => refs.contains(node.ref_id.trim_start_matches('@')),
+        None => true,
+    };
+    (filter.roles.is_empty() || filter.roles.iter().any(|r| r == role))
+        && (!filter.interactive || node.focusable || INTERACTIVE_ROLES.contains(&role))
+        && in_page
+}
+
+/// `nodes` narrowed to those matching `filter` and, when given, in `on_page`.
+fn retain(
+    nodes: Vec<AriaNode>,
+    filter: &SnapshotFilter,
+    on_page: Option<&HashSet<String>>,
+) -> Vec<AriaNode> {
+    let mut kept = Vec::new();
+    for mut node in nodes {
+        let children = retain(std::mem::take(&mut node.children), filter, on_page);
+        if matches(&node, filter, on_page) {
+            node.children = children;
+            kept.push(node);
+        } else {
+            kept.extend(children);
+        }
+    }
+    kept
+}
+
+fn count(nodes: &[AriaNode]) -> usize {
+    nodes.iter().map(|node| 1 + count(&node.children)).sum()
+}
+
+/// Mark the element with `filter`'s root ref, if any, before a snapshot
+/// moves the refs.
+pub async fn mark_root(page: &Page, filter: &SnapshotFilter) -> Result<()> {
+    let Some(root) = &filter.root else {
+        return Ok(());
+    };
+    let found: bool = page
+        .evaluate(format!(
+            "{}({}, '{}')",
+            MARK_SCRIPT,
+            serde_json::to_string(root.trim_start_matches('@'))?,
+            ROOT_ATTRIBUTE
+        ))
+        .await
+        .context("Failed to find the snapshot root")?
+        .into_value()
+        .context("Failed to parse the snapshot root")?;
+    if !found {
+        anyhow::bail!("Element not found: {}", root);
+    }
+    Ok(())
+}
+
+/// `snapshot` narrowed to the nodes matching `filter`; the root must have
+/// been marked with `mark_root` first.
+pub async fn apply(
+    page: &Page,
+    mut snapshot: AriaSnapshot,
+    filter: &SnapshotFilter,
+) -> Result<AriaSnapshot> {
+    let on_page = if filter.visible || filter.root.is_some() {
+        let refs: Option<Vec<String>> = page
+            .evaluate(format!(
+                "{}('{}', {}, {})",
+                REFS_SCRIPT,
+                ROOT_ATTRIBUTE,
+                filter.root.is_some(),
+                filter.visible
+            ))
+            .await
+            .context("Failed to filter snapshot on the page")?
+            .into_value()
+            .context("Failed to parse snapshot filter")?;
+        let refs = refs.with_context(|| {
+            format!(
+                "Element {} was removed from the page",
+                filter.root.as_deref().unwrap_or_default()
+            )
+        })?;
+        Some(refs.into_iter().collect::<HashSet<String>>())
+    } else {
+        None
+    };
+
+    snapshot.nodes = retain(
+        std::mem::take(&mut snapshot.nodes),
+        filter,
+        on_page.as_ref(),
+    );
+    snapshot.element_count = count(&snapshot.nodes);
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(ref_id: &str, role: &str, children: Vec<AriaNode>) -> AriaNode {
+        AriaNode {
+            ref_id: ref_id.to_string(),
+            role: role.to_string(),
+            name: None,
+            value: None,
+            focusable: false,
+            focused: false,
+            rect: None,
+            in_viewport: false,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_retain_lifts_kept_children() {
+        let mut card = node("@e4", "generic", vec![]);
+        card.focusable = true;
+        let nodes = vec![
+            node("@e1", "heading", vec![]),
+            node("@e2", "navigation", vec![node("@e3", "link", vec![]), card]),
+            node("@e5", "button", vec![]),
+        ];
+        let refs = |nodes: &[AriaNode]| -> Vec<String> {
+            nodes.iter().map(|n| n.ref_id.clone()).collect()
+        };
+
+        let interactive = SnapshotFilter {
+            interactive: true,
+            ..SnapshotFilter::default()
+        };
+        let kept = retain(nodes.clone(), &interactive, None);
+        assert_eq!(refs(&kept), vec!["@e3", "@e4", "@e5"]);
+        assert_eq!(count(&kept), 3);
+
+        let links = SnapshotFilter {
+            roles: vec!["link".to_string(), "navigation".to_string()],
+            ..SnapshotFilter::default()
+        };
+        let kept = retain(nodes.clone(), &links, None);
+        assert_eq!(refs(&kept), vec!["@e2"]);
+        assert_eq!(refs(&kept[0].children), vec!["@e3"]);
+
+        let on_page: HashSet<String> = ["e2", "e3", "e4"].map(String::from).into();
+        let kept = retain(nodes, &SnapshotFilter::default(), Some(&on_page));
+        assert_eq!(count(&kept), 3);
+        assert_eq!(refs(&kept), vec!["@e2"]);
+    }
+}
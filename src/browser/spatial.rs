@@ -0,0 +1,393 @@
+//! Spatial queries over snapshot elements.
+//!
+//! Agents often know where something is relative to something else ("the
+//! Edit button in the same row as 'Invoice #123'") rather than its ref. These
+//! queries measure every element carrying a ref from the last snapshot and
+//! filter them by position: nearest to an anchor (optionally on one side of
+//! it) or inside a rectangle. All geometry is in document CSS pixels so
+//! off-screen elements take part too.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{
+    AriaNode, ElementRect, SpatialAnchor, SpatialDirection, SpatialElement, SpatialFilter,
+};
+
+/// Slack when comparing edges, so touching elements count as side by side.
+const EDGE_TOLERANCE: f64 = 1.0;
+
+const MEASURE_SCRIPT: &str = r#"((sel, text) => {
+    const rectOf = (el) => {
+        const r = el.getBoundingClientRect();
+        return { x: r.left + scrollX, y: r.top + scrollY, width: r.width, height: r.height };
+    };
+    const shown = (el) => {
+        const r = el.getBoundingClientRect();
+        return r.width > 0 && r.height > 0 && el.checkVisibility({ visibilityProperty: true });
+    };
+
+    let anchor = null;
+    if (sel !== null) {
+        anchor = document.querySelector(sel);
+    } else if (text !== null && document.body) {
+        // Innermost visible element containing the text
+        for (const el of document.body.querySelectorAll('*')) {
+            if (!(el.textContent || '').includes(text)) continue;
+            if ([...el.children].some((c) => (c.textContent || '').includes(text))) continue;
+            if (shown(el)) { anchor = el; break; }
+        }
+    }
+    if ((sel !== null || text !== null) && !anchor) return { anchor_found: false, elements: [] };
+
+    const elements = [];
+    for (const el of document.querySelectorAll('[data-fgp-ref]')) {
+        if (!shown(el)) continue;
+        elements.push({
+            ref_id: '@' + el.getAttribute('data-fgp-ref'),
+            rect: rectOf(el),
+            // The anchor itself and elements wrapping it
+            related: anchor !== null && el.contains(anchor),
+        });
+    }
+    return { anchor_found: true, anchor: anchor && rectOf(anchor), elements };
+})"#;
+
+/// A measured snapshot element.
+#[derive(Debug, Deserialize)]
+struct Placed {
+    ref_id: String,
+    rect: ElementRect,
+    #[serde(default)]
+    related: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Layout {
+    anchor_found: bool,
+    #[serde(default)]
+    anchor: Option<ElementRect>,
+    elements: Vec<Placed>,
+}
+
+/// A snapshot element with its position.
+#[derive(Debug)]
+pub struct Candidate {
+    element: SpatialElement,
+    /// Is, or contains, the anchor
+    related: bool,
+}
+
+/// Measure the anchor (if any) and every visible element with a ref, labelled
+/// with the role and name from `nodes` (the session's last snapshot).
+pub async fn measure(
+    page: &Page,
+    anchor: Option<&SpatialAnchor>,
+    nodes: &[AriaNode],
+) -> Result<(Option<ElementRect>, Vec<Candidate>)> {
+    let (sel, text) = match anchor {
+        Some(SpatialAnchor::Selector(selector)) => (Some(resolve_selector(selector)), None),
+        Some(SpatialAnchor::Text(text)) => (None, Some(text.clone())),
+        None => (None, None),
+    };
+    let script = format!(
+        "{}({}, {})",
+        MEASURE_SCRIPT,
+        serde_json::to_string(&sel)?,
+        serde_json::to_string(&text)?
+    );
+
+    let layout: Layout = page
+        .evaluate(script)
+        .await
+        .context("Failed to measure elements")?
+        .into_value()
+        .context("Failed to parse element layout")?;
+
+    if !layout.anchor_found {
+        match anchor {
+            Some(SpatialAnchor::Selector(selector)) => {
+                anyhow::bail!("Anchor not found: {}", selector)
+            }
+            _ => anyhow::bail!("No visible element contains the anchor text"),
+        }
+    }
+    if layout.elements.is_empty() {
+        anyhow::bail!("No element refs on the page; take a snapshot first");
+    }
+
+    let mut labels = HashMap::new();
+    collect_labels(nodes, &mut labels);
+
+    let candidates = layout
+        .elements
+        .into_iter()
+        .map(|placed| {
+            let (role, name) = labels
+                .get(placed.ref_id.as_str())
+                .map(|(role, name)| (role.to_string(), name.clone()))
+                .unwrap_or_else(|| ("unknown".to_string(), None));
+            Candidate {
+                element: SpatialElement {
+                    ref_id: placed.ref_id,
+                    role,
+                    name,
+                    rect: placed.rect,
+                    distance: None,
+                },
+                related: placed.related,
+            }
+        })
+        .collect();
+
+    Ok((layout.anchor, candidates))
+}
+
+fn collect_labels<'a>(
+    nodes: &'a [AriaNode],
+    labels: &mut HashMap<&'a str, (&'a str, Option<String>)>,
+) {
+    for node in nodes {
+        labels.insert(&node.ref_id, (&node.role, node.name.clone()));
+        collect_labels(&node.children, labels);
+    }
+}
+
+fn right(rect: &ElementRect) -> f64 {
+    rect.x + rect.width
+}
+
+fn bottom(rect: &ElementRect) -> f64 {
+    rect.y + rect.height
+}
+
+/// Edge-to-edge distance between two rects (0 when they overlap).
+fn gap(a: &ElementRect, b: &ElementRect) -> f64 {
+    let dx = (a.x - right(b)).max(b.x - right(a)).max(0.0);
+    let dy = (a.y - bottom(b)).max(b.y - bottom(a)).max(0.0);
+    dx.hypot(dy)
+}
+
+/// Whether `rect` lies in `direction` from `anchor`.
+fn in_direction(direction: SpatialDirection, anchor: &ElementRect, rect: &ElementRect) -> bool {
+    match direction {
+        SpatialDirection::Any => true,
+        SpatialDirection::Left => right(rect) <= anchor.x + EDGE_TOLERANCE,
+        SpatialDirection::Right => rect.x >= right(anchor) - EDGE_TOLERANCE,
+        SpatialDirection::Above => bottom(rect) <= anchor.y + EDGE_TOLERANCE,
+        SpatialDirection::Below => rect.y >= bottom(anchor) - EDGE_TOLERANCE,
+        SpatialDirection::SameRow => rect.y < bottom(anchor) && anchor.y < bottom(rect),
+        SpatialDirection::SameColumn => rect.x < right(anchor) && anchor.x < right(rect),
+    }
+}
+
+fn matches_filter(element: &SpatialElement, filter: &SpatialFilter) -> bool {
+    if let Some(role) = &filter.role {
+        if !element.role.eq_ignore_ascii_case(role) {
+            return false;
+        }
+    }
+    match &filter.name {
+        Some(name) => element
+            .name
+            .as_ref()
+            .is_some_and(|n| n.to_lowercase().contains(&name.to_lowercase())),
+        None => true,
+    }
+}
+
+/// Elements closest to `anchor`, nearest first.
+///
+/// The anchor and elements containing it are skipped; elements inside the
+/// anchor are kept (at distance 0).
+pub fn nearest(
+    anchor: &ElementRect,
+    candidates: Vec<Candidate>,
+    direction: SpatialDirection,
+    filter: &SpatialFilter,
+) -> Vec<SpatialElement> {
+    let mut matches: Vec<SpatialElement> = candidates
+        .into_iter()
+        .filter(|c| !c.related)
+        .map(|c| c.element)
+        .filter(|e| in_direction(direction, anchor, &e.rect) && matches_filter(e, filter))
+        .map(|mut e| {
+            e.distance = Some(gap(anchor, &e.rect));
+            e
+        })
+        .collect();
+    // Stable sort keeps document order between equally distant elements
+    matches.sort_by(|a, b| {
+        a.distance
+            .unwrap_or_default()
+            .total_cmp(&b.distance.unwrap_or_default())
+    });
+    matches.truncate(filter.limit);
+    matches
+}
+
+/// Elements inside `area` (or overlapping it, with `partial`), in reading
+/// order.
+pub fn within(
+    area: &ElementRect,
+    candidates: Vec<Candidate>,
+    partial: bool,
+    filter: &SpatialFilter,
+) -> Vec<SpatialElement> {
+    let mut matches: Vec<SpatialElement> = candidates
+        .into_iter()
+        .map(|c| c.element)
+        .filter(|e| {
+            let r = &e.rect;
+            let inside = if partial {
+                r.x < right(area) && area.x < right(r) && r.y < bottom(area) && area.y < bottom(r)
+            } else {
+                r.x >= area.x
+                    && r.y >= area.y
+                    && right(r) <= right(area)
+                    && bottom(r) <= bottom(area)
+            };
+            inside && matches_filter(e, filter)
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        a.rect
+            .y
+            .total_cmp(&b.rect.y)
+            .then(a.rect.x.total_cmp(&b.rect.x))
+    });
+    matches.truncate(filter.limit);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> ElementRect {
+        ElementRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn candidate(ref_id: &str, role: &str, name: &str, rect: ElementRect) -> Candidate {
+        Candidate {
+            element: SpatialElement {
+                ref_id: ref_id.to_string(),
+                role: role.to_string(),
+                name: Some(name.to_string()),
+                rect,
+                distance: None,
+            },
+            related: false,
+        }
+    }
+
+    /// Two table rows: "Invoice #123" and "Invoice #124", each with an Edit
+    /// and a Delete button.
+    fn rows() -> Vec<Candidate> {
+        vec![
+            candidate("@e1", "button", "Edit", rect(300.0, 100.0, 40.0, 20.0)),
+            candidate("@e2", "button", "Delete", rect(350.0, 100.0, 50.0, 20.0)),
+            candidate("@e3", "button", "Edit", rect(300.0, 130.0, 40.0, 20.0)),
+            candidate("@e4", "button", "Delete", rect(350.0, 130.0, 50.0, 20.0)),
+        ]
+    }
+
+    fn filter(role: Option<&str>, name: Option<&str>) -> SpatialFilter {
+        SpatialFilter {
+            role: role.map(str::to_string),
+            name: name.map(str::to_string),
+            limit: 10,
+        }
+    }
+
+    #[test]
+    fn test_gap() {
+        assert_eq!(
+            gap(&rect(0.0, 0.0, 10.0, 10.0), &rect(5.0, 5.0, 10.0, 10.0)),
+            0.0
+        );
+        assert_eq!(
+            gap(&rect(0.0, 0.0, 10.0, 10.0), &rect(20.0, 0.0, 10.0, 10.0)),
+            10.0
+        );
+        assert_eq!(
+            gap(&rect(0.0, 0.0, 10.0, 10.0), &rect(13.0, 14.0, 1.0, 1.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn test_nearest_in_same_row() {
+        let invoice = rect(20.0, 132.0, 100.0, 16.0);
+
+        let matches = nearest(
+            &invoice,
+            rows(),
+            SpatialDirection::SameRow,
+            &filter(Some("Button"), Some("edit")),
+        );
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].ref_id, "@e3");
+        assert_eq!(matches[0].distance, Some(180.0));
+    }
+
+    #[test]
+    fn test_nearest_skips_anchor_and_wrappers() {
+        let mut candidates = rows();
+        candidates[0].related = true;
+
+        let matches = nearest(
+            &rect(300.0, 100.0, 40.0, 20.0),
+            candidates,
+            SpatialDirection::Any,
+            &filter(None, None),
+        );
+
+        let refs: Vec<_> = matches.iter().map(|m| m.ref_id.as_str()).collect();
+        assert_eq!(refs, vec!["@e2", "@e3", "@e4"]);
+    }
+
+    #[test]
+    fn test_directions() {
+        let anchor = rect(100.0, 100.0, 50.0, 50.0);
+        let left = rect(40.0, 110.0, 60.0, 10.0);
+
+        assert!(in_direction(SpatialDirection::Left, &anchor, &left));
+        assert!(in_direction(SpatialDirection::SameRow, &anchor, &left));
+        assert!(!in_direction(SpatialDirection::Right, &anchor, &left));
+        assert!(!in_direction(SpatialDirection::SameColumn, &anchor, &left));
+        assert!(in_direction(
+            SpatialDirection::Below,
+            &anchor,
+            &rect(100.0, 150.0, 10.0, 10.0)
+        ));
+    }
+
+    #[test]
+    fn test_within() {
+        let area = rect(290.0, 95.0, 120.0, 30.0);
+
+        let inside = within(&area, rows(), false, &filter(None, None));
+        let refs: Vec<_> = inside.iter().map(|m| m.ref_id.as_str()).collect();
+        assert_eq!(refs, vec!["@e1", "@e2"]);
+
+        let overlapping = within(
+            &rect(320.0, 110.0, 10.0, 30.0),
+            rows(),
+            true,
+            &filter(None, None),
+        );
+        let refs: Vec<_> = overlapping.iter().map(|m| m.ref_id.as_str()).collect();
+        assert_eq!(refs, vec!["@e1", "@e3"]);
+    }
+}
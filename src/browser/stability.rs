@@ -0,0 +1,223 @@
+//! Page quiescence detection.
+//!
+//! A page counts as settled once three signals have all been quiet for the
+//! same window: no network requests in flight (tracked from CDP `Network`
+//! events), no layout shifts, and no DOM mutations (which also covers
+//! `requestAnimationFrame` loops that keep rewriting the page). The gateway's
+//! own mutations (refs, marked targets, overlays) are ignored so snapshots,
+//! highlights and the synthetic cursor don't keep a page "busy".
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::network::{
+    EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent, RequestId, ResourceType,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use serde::Deserialize;
+
+use super::own_mutations;
+use crate::models::StabilityResult;
+
+/// How often the in-page monitor is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Install (once per document) and read the in-page layout/DOM monitor.
+fn monitor_script() -> String {
+    format!(
+        r#"(() => {{
+        let s = window.__fgpStability;
+        if (!s) {{
+            s = {{ lastMutation: performance.now(), lastShift: performance.now() }};
+            {}
+            new MutationObserver((records) => {{
+                if (!records.every(ignored)) s.lastMutation = performance.now();
+            }}).observe(document, {{
+                subtree: true, childList: true, attributes: true, characterData: true
+            }});
+            try {{
+                new PerformanceObserver((list) => {{
+                    if (list.getEntries().some(e => !e.hadRecentInput)) {{
+                        s.lastShift = performance.now();
+                    }}
+                }}).observe({{ type: 'layout-shift' }});
+            }} catch (e) {{}}
+            window.__fgpStability = s;
+        }}
+        const now = performance.now();
+        return {{
+            ready: document.readyState === 'complete',
+            mutation_idle_ms: now - s.lastMutation,
+            shift_idle_ms: now - s.lastShift,
+        }};
+    }})()"#,
+        own_mutations::ignore_script()
+    )
+}
+
+/// Quiet times reported by the in-page monitor.
+#[derive(Debug, Deserialize)]
+struct MonitorSample {
+    ready: bool,
+    mutation_idle_ms: f64,
+    shift_idle_ms: f64,
+}
+
+/// Signals that have not yet been quiet for `quiet` (empty once settled).
+fn pending_signals(
+    sample: &MonitorSample,
+    inflight: usize,
+    network_idle: Duration,
+    quiet: Duration,
+) -> Vec<String> {
+    let quiet_ms = quiet.as_millis() as f64;
+    let mut pending = Vec::new();
+    if !sample.ready {
+        pending.push("load".to_string());
+    }
+    if inflight > 0 || network_idle < quiet {
+        pending.push("network".to_string());
+    }
+    if sample.shift_idle_ms < quiet_ms {
+        pending.push("layout".to_string());
+    }
+    if sample.mutation_idle_ms < quiet_ms {
+        pending.push("dom".to_string());
+    }
+    pending
+}
+
+/// Wait until the page has been quiet for `quiet`, giving up after `timeout`.
+///
+/// Requests already in flight when the wait starts aren't known to the
+/// tracker, so a fresh wait always observes the network for at least
+/// `quiet`. Event streams (`EventSource`) are long-lived by design and don't
+/// count as network activity.
+pub async fn wait_for_stable(
+    page: &Page,
+    quiet: Duration,
+    timeout: Duration,
+) -> Result<StabilityResult> {
+    let mut requests = page
+        .event_listener::<EventRequestWillBeSent>()
+        .await
+        .context("Failed to listen for network requests")?;
+    let mut finished = page
+        .event_listener::<EventLoadingFinished>()
+        .await
+        .context("Failed to listen for network requests")?;
+    let mut failed = page
+        .event_listener::<EventLoadingFailed>()
+        .await
+        .context("Failed to listen for network requests")?;
+
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut inflight: HashSet<RequestId> = HashSet::new();
+    let mut last_network = Instant::now();
+    let mut pending = vec!["network".to_string()];
+    let script = monitor_script();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            Some(event) = requests.next() => {
+                if event.r#type != Some(ResourceType::EventSource) {
+                    inflight.insert(event.request_id.clone());
+                    last_network = Instant::now();
+                }
+            }
+            Some(event) = finished.next() => {
+                if inflight.remove(&event.request_id) {
+                    last_network = Instant::now();
+                }
+            }
+            Some(event) = failed.next() => {
+                if inflight.remove(&event.request_id) {
+                    last_network = Instant::now();
+                }
+            }
+            _ = poll.tick() => {
+                // Evaluation fails while a navigation swaps documents; treat
+                // that as activity and sample again on the next tick
+                let sample: Option<MonitorSample> = match page.evaluate(script.as_str()).await {
+                    Ok(value) => value.into_value().ok(),
+                    Err(_) => None,
+                };
+                pending = match sample {
+                    Some(sample) => {
+                        pending_signals(&sample, inflight.len(), last_network.elapsed(), quiet)
+                    }
+                    None => vec!["load".to_string()],
+                };
+                if pending.is_empty() {
+                    return Ok(StabilityResult {
+                        stable: true,
+                        waited_ms: started.elapsed().as_millis() as u64,
+                        inflight_requests: 0,
+                        pending,
+                    });
+                }
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                return Ok(StabilityResult {
+                    stable: false,
+                    waited_ms: started.elapsed().as_millis() as u64,
+                    inflight_requests: inflight.len(),
+                    pending,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(ready: bool, mutation_idle_ms: f64, shift_idle_ms: f64) -> MonitorSample {
+        MonitorSample {
+            ready,
+            mutation_idle_ms,
+            shift_idle_ms,
+        }
+    }
+
+    #[test]
+    fn test_settled_page_has_no_pending_signals() {
+        let quiet = Duration::from_millis(500);
+        let pending = pending_signals(
+            &sample(true, 800.0, 2000.0),
+            0,
+            Duration::from_millis(600),
+            quiet,
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_each_busy_signal_is_reported() {
+        let quiet = Duration::from_millis(500);
+        let pending = pending_signals(
+            &sample(false, 10.0, 100.0),
+            2,
+            Duration::from_millis(900),
+            quiet,
+        );
+        assert_eq!(pending, vec!["load", "network", "layout", "dom"]);
+    }
+
+    #[test]
+    fn test_recent_network_activity_is_not_idle() {
+        let quiet = Duration::from_millis(500);
+        let pending = pending_signals(
+            &sample(true, 800.0, 800.0),
+            0,
+            Duration::from_millis(200),
+            quiet,
+        );
+        assert_eq!(pending, vec!["network"]);
+    }
+}
@@ -0,0 +1,385 @@
+//! Table cell targeting.
+//!
+//! Admin UIs are mostly grids; "the Actions cell of the row mentioning Acme"
+//! is how a person describes a target, not `@e417`. A cell is addressed by
+//! a row (text it contains, or index) and a column (header text, or index).
+//! Native `<table>`s and ARIA tables/grids (`role=row`, `columnheader`,
+//! `cell`/`gridcell`) are supported; `colspan`/`aria-colspan` shift column
+//! positions, `rowspan` is not followed.
+//!
+//! The page reports the header and row texts first and the match is made
+//! here, so a miss can list what was there instead.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{CellTarget, ElementRect, TableCell, TableColumnKey, TableRowKey};
+
+/// Row texts listed in a "no matching row" error.
+const MAX_LISTED_ROWS: usize = 10;
+
+/// `mode` is "layout" (headers and row texts) or "cell" (details of the cell
+/// at body row `row`, column position `column`).
+const TABLE_SCRIPT: &str = r#"((sel, mode, row, column, target) => {
+    const table = sel !== null ? document.querySelector(sel)
+        : document.querySelector('table, [role=table], [role=grid], [role=treegrid]');
+    if (!table) return { found: false };
+
+    const native = table.tagName === 'TABLE';
+    const text = (el) => (el.textContent || '').replace(/\s+/g, ' ').trim();
+    const rows = native ? [...table.rows] : [...table.querySelectorAll('[role=row]')].filter(
+        (r) => r.closest('table, [role=table], [role=grid], [role=treegrid]') === table);
+    const cellsOf = (r) => native ? [...r.cells] : [...r.querySelectorAll(
+        '[role=cell], [role=gridcell], [role=columnheader], [role=rowheader]')].filter(
+        (c) => c.closest('[role=row]') === r);
+    const span = (c) => native ? c.colSpan : (parseInt(c.getAttribute('aria-colspan'), 10) || 1);
+    const isHeader = (c) => native ? c.tagName === 'TH' : c.getAttribute('role') === 'columnheader';
+
+    const headerRow = rows.find((r) => (native && r.parentElement.tagName === 'THEAD') ||
+        (cellsOf(r).length > 0 && cellsOf(r).every(isHeader))) || null;
+    const body = rows.filter((r) => r !== headerRow &&
+        !(native && r.parentElement.tagName === 'THEAD'));
+
+    const positioned = (r) => {
+        let pos = 0;
+        return cellsOf(r).map((c) => {
+            const cell = { el: c, start: pos, span: span(c) };
+            pos += cell.span;
+            return cell;
+        });
+    };
+
+    if (mode === 'layout') {
+        return {
+            found: true,
+            headers: headerRow ? positioned(headerRow).map((c) => ({
+                text: text(c.el), start: c.start, span: c.span })) : [],
+            rows: body.map((r) => cellsOf(r).map(text).join(' | ')),
+        };
+    }
+
+    const cell = positioned(body[row]).find((c) => c.start <= column && column < c.start + c.span);
+    if (!cell) return { found: true, cell: null };
+
+    const pathOf = (el) => {
+        const parts = [];
+        while (el && el !== document.documentElement) {
+            if (el.id && document.querySelectorAll('#' + CSS.escape(el.id)).length === 1) {
+                parts.unshift('#' + CSS.escape(el.id));
+                return parts.join(' > ');
+            }
+            const parent = el.parentElement;
+            parts.unshift(el.tagName.toLowerCase() + ':nth-child(' +
+                ([...parent.children].indexOf(el) + 1) + ')');
+            el = parent;
+        }
+        parts.unshift('html');
+        return parts.join(' > ');
+    };
+    const labelOf = (el) => (el.getAttribute('aria-label') || text(el) || el.value ||
+        el.getAttribute('title') || '').trim();
+
+    const controls = [...cell.el.querySelectorAll(
+        'a[href], button, input, select, textarea, [role=button], [role=link], ' +
+        '[role=checkbox], [role=menuitem], [tabindex]:not([tabindex="-1"])')].filter((el) => {
+        const r = el.getBoundingClientRect();
+        return r.width > 0 && r.height > 0;
+    });
+    const wanted = target === null ? controls[0] : controls.find(
+        (el) => labelOf(el).toLowerCase().includes(target.toLowerCase()));
+
+    const r = cell.el.getBoundingClientRect();
+    return {
+        found: true,
+        cell: {
+            text: text(cell.el),
+            selector: pathOf(cell.el),
+            rect: { x: r.left, y: r.top, width: r.width, height: r.height },
+            target: wanted ? {
+                selector: pathOf(wanted),
+                tag: wanted.tagName.toLowerCase(),
+                label: labelOf(wanted) || null,
+            } : null,
+            target_missing: target !== null && !wanted,
+            refs: [...cell.el.querySelectorAll('[data-fgp-ref]'), cell.el]
+                .filter((el) => el.hasAttribute('data-fgp-ref'))
+                .map((el) => '@' + el.getAttribute('data-fgp-ref')),
+        },
+    };
+})"#;
+
+/// Header cell of a table.
+#[derive(Debug, Clone, Deserialize)]
+struct Header {
+    text: String,
+    /// Column position (counting colspans)
+    start: usize,
+    span: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Layout {
+    found: bool,
+    #[serde(default)]
+    headers: Vec<Header>,
+    #[serde(default)]
+    rows: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CellProbe {
+    text: String,
+    selector: String,
+    rect: ElementRect,
+    #[serde(default)]
+    target: Option<CellTarget>,
+    #[serde(default)]
+    target_missing: bool,
+    #[serde(default)]
+    refs: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CellLookup {
+    found: bool,
+    #[serde(default)]
+    cell: Option<CellProbe>,
+}
+
+/// Column position for `key`, and its header text.
+fn find_column(headers: &[Header], key: &TableColumnKey) -> Result<(usize, Option<String>)> {
+    let header_at = |position: usize| {
+        headers
+            .iter()
+            .find(|h| h.start <= position && position < h.start + h.span)
+            .map(|h| h.text.clone())
+    };
+
+    match key {
+        TableColumnKey::Index(position) => Ok((*position, header_at(*position))),
+        TableColumnKey::Header(name) => {
+            let wanted = name.trim().to_lowercase();
+            let header = headers
+                .iter()
+                .find(|h| h.text.to_lowercase() == wanted)
+                .or_else(|| {
+                    headers
+                        .iter()
+                        .find(|h| h.text.to_lowercase().contains(&wanted))
+                });
+            match header {
+                Some(h) => Ok((h.start, Some(h.text.clone()))),
+                None if headers.is_empty() => {
+                    anyhow::bail!("Table has no header row; address the column by index")
+                }
+                None => anyhow::bail!(
+                    "No column '{}'; columns: {}",
+                    name,
+                    headers
+                        .iter()
+                        .map(|h| h.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        }
+    }
+}
+
+/// Body row for `key`, and how many rows matched.
+fn find_row(rows: &[String], key: &TableRowKey) -> Result<(usize, usize)> {
+    match key {
+        TableRowKey::Index(index) if *index < rows.len() => Ok((*index, 1)),
+        TableRowKey::Index(index) => {
+            anyhow::bail!("Row {} out of range; table has {} rows", index, rows.len())
+        }
+        TableRowKey::Contains(needle) => {
+            let mut matches = rows
+                .iter()
+                .enumerate()
+                .filter(|(_, text)| text.contains(needle.as_str()));
+            let Some((first, _)) = matches.next() else {
+                let listed: Vec<&str> = rows
+                    .iter()
+                    .take(MAX_LISTED_ROWS)
+                    .map(|r| r.as_str())
+                    .collect();
+                anyhow::bail!(
+                    "No row contains '{}'; rows: {}{}",
+                    needle,
+                    listed.join(" / "),
+                    if rows.len() > MAX_LISTED_ROWS {
+                        " / ..."
+                    } else {
+                        ""
+                    }
+                );
+            };
+            Ok((first, 1 + matches.count()))
+        }
+    }
+}
+
+/// Locate a table cell.
+///
+/// `target` picks the control in the cell whose label contains it; without
+/// it the cell's first control (if any) is reported.
+pub async fn locate(
+    page: &Page,
+    table: Option<&str>,
+    row: &TableRowKey,
+    column: &TableColumnKey,
+    target: Option<&str>,
+) -> Result<TableCell> {
+    let sel = table.map(resolve_selector);
+    let run = |mode: &str, row: usize, column: usize| -> Result<String> {
+        Ok(format!(
+            "{}({}, {}, {}, {}, {})",
+            TABLE_SCRIPT,
+            serde_json::to_string(&sel)?,
+            serde_json::to_string(mode)?,
+            row,
+            column,
+            serde_json::to_string(&target)?
+        ))
+    };
+
+    let layout: Layout = page
+        .evaluate(run("layout", 0, 0)?)
+        .await
+        .context("Failed to read table")?
+        .into_value()
+        .context("Failed to parse table layout")?;
+    if !layout.found {
+        anyhow::bail!(
+            "Table not found: {}",
+            table.unwrap_or("(no table on the page)")
+        );
+    }
+
+    let (row_index, row_matches) = find_row(&layout.rows, row)?;
+    let (column_index, header) = find_column(&layout.headers, column)?;
+
+    let lookup: CellLookup = page
+        .evaluate(run("cell", row_index, column_index)?)
+        .await
+        .context("Failed to read table cell")?
+        .into_value()
+        .context("Failed to parse table cell")?;
+    let probe = match lookup {
+        CellLookup { found: false, .. } => anyhow::bail!("Table changed while reading it"),
+        CellLookup { cell: None, .. } => {
+            anyhow::bail!("Row {} has no cell in column {}", row_index, column_index)
+        }
+        CellLookup {
+            cell: Some(probe), ..
+        } => probe,
+    };
+    if probe.target_missing {
+        anyhow::bail!(
+            "No control labelled '{}' in the cell",
+            target.unwrap_or_default()
+        );
+    }
+
+    Ok(TableCell {
+        row: row_index,
+        row_matches,
+        column: column_index,
+        header,
+        text: probe.text,
+        selector: probe.selector,
+        rect: probe.rect,
+        target: probe.target,
+        refs: probe.refs,
+        clicked: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> Vec<Header> {
+        ["Customer", "Status", "Actions"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Header {
+                text: text.to_string(),
+                start: i,
+                span: 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_column_by_header() {
+        let headers = headers();
+
+        assert_eq!(
+            find_column(&headers, &TableColumnKey::Header("actions".to_string())).unwrap(),
+            (2, Some("Actions".to_string()))
+        );
+        // Substring match when nothing matches exactly
+        assert_eq!(
+            find_column(&headers, &TableColumnKey::Header("Stat".to_string())).unwrap(),
+            (1, Some("Status".to_string()))
+        );
+
+        let err = find_column(&headers, &TableColumnKey::Header("Owner".to_string())).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("columns: Customer, Status, Actions"));
+    }
+
+    #[test]
+    fn test_find_column_with_colspan() {
+        let headers = vec![
+            Header {
+                text: "Name".to_string(),
+                start: 0,
+                span: 2,
+            },
+            Header {
+                text: "Actions".to_string(),
+                start: 2,
+                span: 1,
+            },
+        ];
+
+        assert_eq!(
+            find_column(&headers, &TableColumnKey::Header("Actions".to_string())).unwrap(),
+            (2, Some("Actions".to_string()))
+        );
+        assert_eq!(
+            find_column(&headers, &TableColumnKey::Index(1)).unwrap(),
+            (1, Some("Name".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_find_row() {
+        let rows = vec![
+            "Acme | Active | Edit".to_string(),
+            "Globex | Paused | Edit".to_string(),
+            "Acme Europe | Active | Edit".to_string(),
+        ];
+
+        assert_eq!(
+            find_row(&rows, &TableRowKey::Contains("Acme".to_string())).unwrap(),
+            (0, 2)
+        );
+        assert_eq!(
+            find_row(&rows, &TableRowKey::Contains("Paused".to_string())).unwrap(),
+            (1, 1)
+        );
+        assert!(find_row(&rows, &TableRowKey::Index(3)).is_err());
+
+        let err = find_row(&rows, &TableRowKey::Contains("Initech".to_string())).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("rows: Acme | Active | Edit / Globex"));
+    }
+}
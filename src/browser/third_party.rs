@@ -0,0 +1,282 @@
+//! Third-party request breakdown of the current page.
+//!
+//! Requests are grouped by registrable domain (`cdn.example.co.uk` and
+//! `www.example.co.uk` are both `example.co.uk`), and every domain other than
+//! the page's own counts as third party. Counts and bytes come from the
+//! session's bandwidth meter. Blocking time is how long a domain's
+//! render-blocking resources (scripts and stylesheets the page had to wait for
+//! before first render) took to load, from the page's Resource Timing
+//! entries; it's 0 on browsers without `renderBlockingStatus`.
+//!
+//! There's no public suffix list here, only the common multi-label suffixes,
+//! so hosts under rarer ones (`example.gov.in`) group one label too wide.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::bandwidth::PageRequest;
+use crate::models::{ThirdPartyDomain, ThirdPartyReport};
+
+/// Public suffixes of more than one label, where registrable domains take
+/// three labels.
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "co.uk",
+    "org.uk",
+    "ac.uk",
+    "gov.uk",
+    "me.uk",
+    "net.uk",
+    "com.au",
+    "net.au",
+    "org.au",
+    "edu.au",
+    "gov.au",
+    "co.nz",
+    "org.nz",
+    "co.jp",
+    "ne.jp",
+    "or.jp",
+    "ac.jp",
+    "co.kr",
+    "or.kr",
+    "com.br",
+    "net.br",
+    "org.br",
+    "com.cn",
+    "net.cn",
+    "org.cn",
+    "com.hk",
+    "com.tw",
+    "com.sg",
+    "com.my",
+    "co.in",
+    "net.in",
+    "org.in",
+    "co.id",
+    "co.il",
+    "co.th",
+    "co.za",
+    "com.mx",
+    "com.ar",
+    "com.co",
+    "com.tr",
+    "com.ua",
+    "com.pl",
+    "com.es",
+    "com.pt",
+    "co.at",
+    "com.sa",
+    "com.eg",
+    "com.ng",
+    "com.pk",
+    "com.ph",
+    "com.vn",
+    "github.io",
+    "gitlab.io",
+    "herokuapp.com",
+    "appspot.com",
+    "azurewebsites.net",
+    "cloudfront.net",
+    "netlify.app",
+    "vercel.app",
+    "pages.dev",
+    "workers.dev",
+    "blogspot.com",
+];
+
+/// Render-blocking resource timings, by URL.
+const TIMING_SCRIPT: &str = r#"(() => performance.getEntriesByType('resource')
+    .filter((e) => e.renderBlockingStatus === 'blocking')
+    .map((e) => ({ url: e.name, duration_ms: e.duration })))()"#;
+
+#[derive(Debug, Deserialize)]
+struct Timing {
+    url: String,
+    duration_ms: f64,
+}
+
+/// Registrable domain of `url`'s host; IP addresses and single-label hosts
+/// are their own domain. None for URLs without a host (`data:`, `blob:`).
+fn registrable_domain(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = match parsed.host()? {
+        url::Host::Domain(domain) => domain.trim_end_matches('.').to_lowercase(),
+        ip => return Some(ip.to_string()),
+    };
+    let labels: Vec<&str> = host.split('.').collect();
+    let suffix_labels = if labels.len() > 2
+        && MULTI_LABEL_SUFFIXES.contains(&labels[labels.len() - 2..].join(".").as_str())
+    {
+        2
+    } else {
+        1
+    };
+    let keep = (suffix_labels + 1).min(labels.len());
+    Some(labels[labels.len() - keep..].join("."))
+}
+
+/// Stats of `domain`, created on first use.
+fn stats<'a>(
+    domains: &'a mut BTreeMap<String, ThirdPartyDomain>,
+    first_party: Option<&str>,
+    domain: String,
+) -> &'a mut ThirdPartyDomain {
+    let third_party = first_party != Some(domain.as_str());
+    domains
+        .entry(domain.clone())
+        .or_insert_with(|| ThirdPartyDomain {
+            domain,
+            third_party,
+            requests: 0,
+            downloaded_bytes: 0,
+            blocking_ms: 0.0,
+            resource_types: Vec::new(),
+        })
+}
+
+/// Group `requests` by registrable domain, adding the blocking time of
+/// `timings`; third-party domains first, heaviest first.
+fn group(page_url: &str, requests: &[PageRequest], timings: &[Timing]) -> ThirdPartyReport {
+    let first_party = registrable_domain(page_url);
+    let mut domains: BTreeMap<String, ThirdPartyDomain> = BTreeMap::new();
+
+    for request in requests {
+        let Some(domain) = registrable_domain(&request.url) else {
+            continue;
+        };
+        let stats = stats(&mut domains, first_party.as_deref(), domain);
+        stats.requests += 1;
+        stats.downloaded_bytes += request.downloaded_bytes;
+        if !stats.resource_types.contains(&request.resource_type) {
+            stats.resource_types.push(request.resource_type.clone());
+        }
+    }
+    for timing in timings {
+        if let Some(domain) = registrable_domain(&timing.url) {
+            stats(&mut domains, first_party.as_deref(), domain).blocking_ms +=
+                timing.duration_ms.max(0.0);
+        }
+    }
+
+    let mut domains: Vec<ThirdPartyDomain> = domains.into_values().collect();
+    domains.sort_by(|a, b| {
+        b.third_party
+            .cmp(&a.third_party)
+            .then(b.downloaded_bytes.cmp(&a.downloaded_bytes))
+            .then(a.domain.cmp(&b.domain))
+    });
+    let third: Vec<&ThirdPartyDomain> = domains.iter().filter(|d| d.third_party).collect();
+
+    ThirdPartyReport {
+        url: page_url.to_string(),
+        first_party,
+        third_party_domains: third.len(),
+        third_party_requests: third.iter().map(|d| d.requests).sum(),
+        third_party_bytes: third.iter().map(|d| d.downloaded_bytes).sum(),
+        third_party_blocking_ms: third.iter().map(|d| d.blocking_ms).sum(),
+        total_requests: domains.iter().map(|d| d.requests).sum(),
+        total_bytes: domains.iter().map(|d| d.downloaded_bytes).sum(),
+        domains,
+    }
+}
+
+/// Break the requests of the page (as recorded by the session's meter) down
+/// by domain.
+pub async fn report(page: &Page, requests: &[PageRequest]) -> Result<ThirdPartyReport> {
+    let page_url = page
+        .url()
+        .await
+        .context("Failed to read page URL")?
+        .unwrap_or_default();
+    let timings: Vec<Timing> = page
+        .evaluate(TIMING_SCRIPT)
+        .await
+        .context("Failed to read resource timings")?
+        .into_value()
+        .context("Failed to parse resource timings")?;
+
+    Ok(group(&page_url, requests, &timings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registrable_domain() {
+        let domain = |url| registrable_domain(url);
+        assert_eq!(
+            domain("https://www.example.com/a").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            domain("https://cdn.shop.example.co.uk/x.js").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            domain("https://user.github.io/").as_deref(),
+            Some("user.github.io")
+        );
+        assert_eq!(
+            domain("http://localhost:8080/").as_deref(),
+            Some("localhost")
+        );
+        assert_eq!(
+            domain("http://127.0.0.1:3000/").as_deref(),
+            Some("127.0.0.1")
+        );
+        assert_eq!(
+            domain("https://Example.COM./").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(domain("data:text/plain,hi"), None);
+    }
+
+    #[test]
+    fn test_group_by_domain() {
+        let request = |url: &str, resource_type: &str, bytes| PageRequest {
+            url: url.to_string(),
+            resource_type: resource_type.to_string(),
+            downloaded_bytes: bytes,
+        };
+        let requests = vec![
+            request("https://shop.example.com/", "document", 20_000),
+            request("https://static.example.com/app.js", "script", 80_000),
+            request("https://www.googletagmanager.com/gtm.js", "script", 90_000),
+            request("https://www.google-analytics.com/g/collect", "ping", 0),
+            request(
+                "https://www.google-analytics.com/analytics.js",
+                "script",
+                50_000,
+            ),
+            request("data:image/png;base64,AAAA", "image", 0),
+        ];
+        let timings = vec![Timing {
+            url: "https://www.googletagmanager.com/gtm.js".to_string(),
+            duration_ms: 120.0,
+        }];
+
+        let report = group("https://shop.example.com/", &requests, &timings);
+        assert_eq!(report.first_party.as_deref(), Some("example.com"));
+        assert_eq!(report.total_requests, 5);
+        assert_eq!(report.third_party_domains, 2);
+        assert_eq!(report.third_party_requests, 3);
+        assert_eq!(report.third_party_bytes, 140_000);
+        assert_eq!(report.third_party_blocking_ms, 120.0);
+
+        let names: Vec<&str> = report.domains.iter().map(|d| d.domain.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "googletagmanager.com",
+                "google-analytics.com",
+                "example.com"
+            ]
+        );
+        assert_eq!(report.domains[1].resource_types, vec!["ping", "script"]);
+        assert!(!report.domains[2].third_party);
+    }
+}
@@ -0,0 +1,147 @@
+//! Time limits for navigations, element actions and snapshots.
+//!
+//! Each kind of call has its own limit, so a slow intranet page can get
+//! minutes to load while a scraper fails fast on a stuck click. A limit
+//! comes from the call's `timeout_ms`, else from the session (`set_timeouts`),
+//! else from the daemon. A call that runs out fails with `TimedOut`
+//! ("TIMEOUT: ...") rather than hanging.
+//!
+//! A navigation that has committed but not reached its `wait_until`
+//! condition when its limit runs out still returns, reporting the last
+//! condition reached; only one that never committed times out.
+
+use std::time::Duration;
+
+use crate::models::TimeoutSettings;
+
+/// Limit of each kind unless the daemon is started with another.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest limit accepted.
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Calls that run under a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutKind {
+    Navigation,
+    Action,
+    Snapshot,
+}
+
+impl std::fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Navigation => "navigation",
+            Self::Action => "action",
+            Self::Snapshot => "snapshot",
+        })
+    }
+}
+
+/// Limit per kind of call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub navigation: Duration,
+    pub action: Duration,
+    pub snapshot: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            navigation: DEFAULT_TIMEOUT,
+            action: DEFAULT_TIMEOUT,
+            snapshot: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl Timeouts {
+    pub fn get(&self, kind: TimeoutKind) -> Duration {
+        match kind {
+            TimeoutKind::Navigation => self.navigation,
+            TimeoutKind::Action => self.action,
+            TimeoutKind::Snapshot => self.snapshot,
+        }
+    }
+
+    pub fn set(&mut self, kind: TimeoutKind, limit: Duration) {
+        match kind {
+            TimeoutKind::Navigation => self.navigation = limit,
+            TimeoutKind::Action => self.action = limit,
+            TimeoutKind::Snapshot => self.snapshot = limit,
+        }
+    }
+
+    /// The limits as reported to clients.
+    pub fn settings(&self) -> TimeoutSettings {
+        TimeoutSettings {
+            navigation_ms: self.navigation.as_millis() as u64,
+            action_ms: self.action.as_millis() as u64,
+            snapshot_ms: self.snapshot.as_millis() as u64,
+        }
+    }
+}
+
+/// Check a limit given in milliseconds.
+pub fn limit_from_ms(ms: u64) -> Result<Duration, String> {
+    let limit = Duration::from_millis(ms);
+    if ms == 0 || limit > MAX_TIMEOUT {
+        return Err(format!(
+            "timeouts must be between 1 and {}ms",
+            MAX_TIMEOUT.as_millis()
+        ));
+    }
+    Ok(limit)
+}
+
+/// A call that ran out of time.
+#[derive(Debug)]
+pub struct TimedOut {
+    pub kind: TimeoutKind,
+    pub limit: Duration,
+}
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TIMEOUT: {} did not complete within {}ms",
+            self.kind,
+            self.limit.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_per_kind() {
+        let mut timeouts = Timeouts::default();
+        timeouts.set(TimeoutKind::Navigation, Duration::from_secs(120));
+        assert_eq!(timeouts.get(TimeoutKind::Navigation).as_secs(), 120);
+        assert_eq!(timeouts.get(TimeoutKind::Action), DEFAULT_TIMEOUT);
+        assert_eq!(timeouts.settings().navigation_ms, 120_000);
+
+        assert!(limit_from_ms(0).is_err());
+        assert!(limit_from_ms(MAX_TIMEOUT.as_millis() as u64 + 1).is_err());
+        assert_eq!(limit_from_ms(500), Ok(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_timed_out_message() {
+        let error = anyhow::Error::new(TimedOut {
+            kind: TimeoutKind::Snapshot,
+            limit: Duration::from_millis(1500),
+        });
+        assert_eq!(
+            error.to_string(),
+            "TIMEOUT: snapshot did not complete within 1500ms"
+        );
+        assert!(error.downcast_ref::<TimedOut>().is_some());
+    }
+}
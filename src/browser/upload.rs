@@ -0,0 +1,171 @@
+//! Setting the files of a file input.
+//!
+//! Files go through `DOM.setFileInputFiles`, which fires the `input` and
+//! `change` events a file chooser would, and are read back from the input's
+//! `files` so the result shows what the page received. Chrome doesn't apply
+//! the input's `accept` filter to files set this way; files it would have
+//! hidden from the chooser are reported as `not_accepted` instead.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    GetDocumentParams, QuerySelectorParams, SetFileInputFilesParams,
+};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::client::resolve_selector;
+use crate::models::{UploadResult, UploadedFile};
+
+const PROBE_SCRIPT: &str = r#"((sel) => {
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    const is_file_input = el.tagName === 'INPUT' && el.type === 'file';
+    return { is_file_input, disabled: el.disabled, multiple: el.multiple, accept: el.accept || '' };
+})"#;
+
+const FILES_SCRIPT: &str = r#"((sel) => {
+    const el = document.querySelector(sel);
+    if (!el) return null;
+    return [...el.files].map((f) => ({ name: f.name, size: f.size, mime_type: f.type }));
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Probe {
+    is_file_input: bool,
+    disabled: bool,
+    multiple: bool,
+    accept: String,
+}
+
+/// `path` made absolute against the daemon's working directory, if it's a
+/// file.
+fn absolute(path: &str) -> Result<PathBuf> {
+    let path = Path::new(path);
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    if !absolute.is_file() {
+        anyhow::bail!("File not found: {}", absolute.display());
+    }
+    Ok(absolute)
+}
+
+/// Whether a file chooser filtered by `accept` (`.pdf`, `image/*`,
+/// `text/csv`, comma-separated) would offer the file.
+fn accepts(accept: &str, name: &str, mime_type: &str) -> bool {
+    let patterns: Vec<String> = accept
+        .split(',')
+        .map(|p| p.trim().to_lowercase())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        return true;
+    }
+    let name = name.to_lowercase();
+    let mime_type = mime_type.to_lowercase();
+    patterns.iter().any(|pattern| {
+        if pattern.starts_with('.') {
+            name.ends_with(pattern.as_str())
+        } else if let Some(major) = pattern.strip_suffix("/*") {
+            mime_type.split_once('/').is_some_and(|(m, _)| m == major)
+        } else {
+            mime_type == *pattern
+        }
+    })
+}
+
+/// Set the files of the file input at `selector` to `paths`, replacing any
+/// chosen before; no paths clears it.
+pub async fn set_files(page: &Page, selector: &str, paths: &[String]) -> Result<UploadResult> {
+    let files = paths
+        .iter()
+        .map(|p| absolute(p).map(|p| p.to_string_lossy().to_string()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let css_selector = resolve_selector(selector);
+    let css = serde_json::to_string(&css_selector)?;
+    let probe: Option<Probe> = page
+        .evaluate(format!("{}({})", PROBE_SCRIPT, css))
+        .await
+        .context("Failed to find file input")?
+        .into_value()
+        .context("Failed to parse file input")?;
+    let probe = probe.with_context(|| format!("Element not found: {}", selector))?;
+    if !probe.is_file_input {
+        anyhow::bail!("Element is not a file input: {}", selector);
+    }
+    if probe.disabled {
+        anyhow::bail!("Element is disabled: {}", selector);
+    }
+    if files.len() > 1 && !probe.multiple {
+        anyhow::bail!(
+            "{} takes a single file, but {} were given",
+            selector,
+            files.len()
+        );
+    }
+
+    let root = page
+        .execute(GetDocumentParams::default())
+        .await
+        .context("Failed to read document")?
+        .result
+        .root
+        .node_id;
+    let node_id = page
+        .execute(QuerySelectorParams::new(root, css_selector))
+        .await
+        .context("Failed to find file input")?
+        .result
+        .node_id;
+    page.execute(
+        SetFileInputFilesParams::builder()
+            .files(files)
+            .node_id(node_id)
+            .build()
+            .map_err(anyhow::Error::msg)?,
+    )
+    .await
+    .context("Failed to set files")?;
+
+    let files: Option<Vec<UploadedFile>> = page
+        .evaluate(format!("{}({})", FILES_SCRIPT, css))
+        .await
+        .context("Failed to read back files")?
+        .into_value()
+        .context("Failed to parse files")?;
+    let files = files.with_context(|| format!("Element not found: {}", selector))?;
+    tracing::debug!("Set {} file(s) on {}", files.len(), selector);
+
+    Ok(UploadResult {
+        success: files.len() == paths.len(),
+        selector: selector.to_string(),
+        not_accepted: files
+            .iter()
+            .filter(|f| !accepts(&probe.accept, &f.name, &f.mime_type))
+            .map(|f| f.name.clone())
+            .collect(),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_filter() {
+        assert!(accepts("", "notes.txt", "text/plain"));
+        assert!(accepts(".pdf, .docx", "Report.PDF", "application/pdf"));
+        assert!(!accepts(".pdf,.docx", "photo.jpg", "image/jpeg"));
+        assert!(accepts("image/*", "photo.jpg", "image/jpeg"));
+        assert!(!accepts("image/*", "clip.mp4", "video/mp4"));
+        assert!(accepts("text/csv", "data.csv", "text/csv"));
+        // Unknown type: only extension patterns can match
+        assert!(!accepts("image/*", "scan.heic", ""));
+    }
+}
@@ -0,0 +1,132 @@
+//! Loading a page as visitors from elsewhere see it.
+//!
+//! Each variant gets a fresh browser context, so cookies and cached
+//! redirects from one locale don't leak into the next, with its language
+//! (the `Accept-Language` header, `navigator.language` and `Intl`), timezone
+//! and position emulated before the page loads. The page is then captured as
+//! a text snapshot, which is compact enough to compare variants side by side.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    SetGeolocationOverrideParams, SetLocaleOverrideParams, SetTimezoneOverrideParams,
+    SetUserAgentOverrideParams,
+};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use super::aria::{extract_aria_tree, render_text};
+use super::oopif::Connection;
+use crate::models::{LocaleVariant, LocaleVariantSnapshot};
+
+const PAGE_SCRIPT: &str = r#"(() => ({
+    url: location.href,
+    title: document.title,
+    lang: document.documentElement.lang || null,
+}))()"#;
+
+#[derive(Debug, Deserialize)]
+struct PageInfo {
+    url: String,
+    title: String,
+    lang: Option<String>,
+}
+
+/// `Accept-Language` for `locale`, falling back to its bare language
+/// (`de-AT` -> `de-AT,de;q=0.9`).
+fn accept_language(locale: &str) -> String {
+    match locale.split_once(['-', '_']) {
+        Some((language, _)) if !language.is_empty() => {
+            format!("{},{};q=0.9", locale.replace('_', "-"), language)
+        }
+        _ => locale.to_string(),
+    }
+}
+
+/// Emulate `variant`'s language, timezone and position on `page`.
+pub async fn emulate(page: &Page, variant: &LocaleVariant) -> Result<()> {
+    let user_agent: String = page
+        .evaluate("navigator.userAgent")
+        .await
+        .context("Failed to read the user agent")?
+        .into_value()
+        .context("Failed to parse the user agent")?;
+    page.execute(
+        SetUserAgentOverrideParams::builder()
+            .user_agent(user_agent)
+            .accept_language(accept_language(&variant.locale))
+            .build()
+            .map_err(anyhow::Error::msg)?,
+    )
+    .await
+    .context("Failed to set the language")?;
+    page.execute(
+        SetLocaleOverrideParams::builder()
+            .locale(variant.locale.clone())
+            .build(),
+    )
+    .await
+    .with_context(|| format!("Unsupported locale: {}", variant.locale))?;
+
+    if let Some(timezone) = &variant.timezone {
+        page.execute(SetTimezoneOverrideParams::new(timezone.clone()))
+            .await
+            .with_context(|| format!("Unknown timezone: {}", timezone))?;
+    }
+    if let Some(position) = &variant.geolocation {
+        page.execute(
+            SetGeolocationOverrideParams::builder()
+                .latitude(position.latitude)
+                .longitude(position.longitude)
+                .accuracy(position.accuracy)
+                .build(),
+        )
+        .await
+        .context("Failed to set the position")?;
+    }
+    Ok(())
+}
+
+/// Load `url` and, after `settle`, capture it as a text snapshot.
+pub async fn capture(
+    page: &Page,
+    connection: Option<&Connection>,
+    url: &str,
+    variant: LocaleVariant,
+    settle: Duration,
+) -> Result<LocaleVariantSnapshot> {
+    page.goto(url).await.context("Navigation failed")?;
+    page.wait_for_navigation().await.ok();
+    tokio::time::sleep(settle).await;
+
+    let info: PageInfo = page
+        .evaluate(PAGE_SCRIPT)
+        .await
+        .context("Failed to read the page")?
+        .into_value()
+        .context("Failed to parse the page")?;
+    let nodes = extract_aria_tree(page, &Default::default(), connection).await?;
+
+    Ok(LocaleVariantSnapshot {
+        variant,
+        url: info.url,
+        title: info.title,
+        lang: info.lang,
+        element_count: nodes.len(),
+        text: render_text(&nodes),
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_language() {
+        assert_eq!(accept_language("de-AT"), "de-AT,de;q=0.9");
+        assert_eq!(accept_language("pt_BR"), "pt-BR,pt;q=0.9");
+        assert_eq!(accept_language("ja"), "ja");
+    }
+}
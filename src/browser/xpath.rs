@@ -0,0 +1,111 @@
+//! XPath selectors.
+//!
+//! Interactions find their elements by CSS selector. An XPath selector is
+//! resolved by the daemon before the interaction runs: every element it
+//! matches (the parent or owner element, for text and attribute nodes) gets a
+//! token in its `data-fgp-xpath` attribute, and the interaction is handed
+//! `[data-fgp-xpath~='N']` instead. It therefore sees the same matches in the
+//! same document order, and `rank` can choose between them. Tokens are
+//! cleared by the next resolution.
+//!
+//! A selector is XPath when it starts with `xpath=`, `/` or `(`, none of
+//! which can start a CSS selector or a ref.
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+/// Explicit XPath prefix, for expressions that don't start with `/` or `(`.
+const PREFIX: &str = "xpath=";
+
+/// Marks the matches of each expression with its index and reports how many
+/// elements each matched (or why it couldn't be evaluated).
+const MARK_SCRIPT: &str = r#"((exprs) => {
+    document.querySelectorAll('[data-fgp-xpath]').forEach((el) => el.removeAttribute('data-fgp-xpath'));
+    return exprs.map((expr, index) => {
+        let result;
+        try {
+            result = document.evaluate(expr, document, null, XPathResult.ORDERED_NODE_SNAPSHOT_TYPE, null);
+        } catch (e) {
+            return { count: 0, error: e.message };
+        }
+        const els = new Set();
+        for (let i = 0; i < result.snapshotLength; i++) {
+            const node = result.snapshotItem(i);
+            const el = node.nodeType === 1 ? node : node.nodeType === 2 ? node.ownerElement : node.parentElement;
+            if (el) els.add(el);
+        }
+        for (const el of els) {
+            const tokens = (el.getAttribute('data-fgp-xpath') || '').split(' ').filter(Boolean);
+            el.setAttribute('data-fgp-xpath', [...tokens, String(index)].join(' '));
+        }
+        return { count: els.size, error: null };
+    });
+})"#;
+
+#[derive(Debug, Deserialize)]
+struct Marked {
+    count: usize,
+    error: Option<String>,
+}
+
+/// Whether `selector` is an XPath expression rather than CSS or a ref.
+pub fn is_xpath(selector: &str) -> bool {
+    selector.starts_with(PREFIX) || selector.starts_with('/') || selector.starts_with('(')
+}
+
+/// The CSS selector standing in for the `index`th expression of a `mark`.
+fn marker(index: usize) -> String {
+    format!("[data-fgp-xpath~='{}']", index)
+}
+
+/// Resolve XPath `selectors` on the page, returning a CSS selector for the
+/// elements each one matches. Fails if any is invalid or matches nothing.
+pub async fn mark(page: &Page, selectors: &[&str]) -> Result<Vec<String>> {
+    let exprs: Vec<&str> = selectors
+        .iter()
+        .map(|s| s.strip_prefix(PREFIX).unwrap_or(s))
+        .collect();
+    let marked: Vec<Marked> = page
+        .evaluate(format!(
+            "{}({})",
+            MARK_SCRIPT,
+            serde_json::to_string(&exprs)?
+        ))
+        .await
+        .context("Failed to evaluate XPath")?
+        .into_value()
+        .context("Failed to parse XPath matches")?;
+
+    selectors
+        .iter()
+        .zip(marked)
+        .enumerate()
+        .map(|(index, (selector, marked))| {
+            if let Some(error) = marked.error {
+                anyhow::bail!("Invalid XPath {}: {}", selector, error);
+            }
+            if marked.count == 0 {
+                anyhow::bail!("Element not found: {}", selector);
+            }
+            Ok(marker(index))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_xpath() {
+        assert!(is_xpath("//button[text()='Save']"));
+        assert!(is_xpath("/html/body/form/input[2]"));
+        assert!(is_xpath("(//a[@href])[3]"));
+        assert!(is_xpath("xpath=id('main')//li"));
+        assert!(!is_xpath("@e12"));
+        assert!(!is_xpath("button.primary"));
+        assert!(!is_xpath("#search > input"));
+        assert!(!is_xpath("[data-test=save]"));
+    }
+}
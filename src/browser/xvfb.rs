@@ -0,0 +1,126 @@
+//! Virtual X display for headed Chrome on machines without one.
+//!
+//! A Linux server has no display for a headed browser's window, so an
+//! `Xvfb` server is started on the first free display number and Chrome is
+//! pointed at it. The server lives as long as the browser client that
+//! started it. `Xvfb` is looked up on `PATH`, or taken from `FGP_XVFB`.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tokio::process::{Child, Command};
+
+/// Environment variable naming the Xvfb binary.
+const XVFB_ENV: &str = "FGP_XVFB";
+
+/// Display numbers tried, in order.
+const DISPLAYS: std::ops::RangeInclusive<u32> = 99..=199;
+
+/// Screen of the virtual display (width x height x depth).
+const SCREEN: &str = "1920x1080x24";
+
+/// How long Xvfb gets to open its socket.
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether a headed browser needs a virtual display here.
+pub fn needed() -> bool {
+    cfg!(target_os = "linux")
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+fn socket_path(number: u32) -> String {
+    format!("/tmp/.X11-unix/X{}", number)
+}
+
+/// Whether an X server holds display `number`.
+fn in_use(number: u32) -> bool {
+    Path::new(&format!("/tmp/.X{}-lock", number)).exists()
+        || Path::new(&socket_path(number)).exists()
+}
+
+fn first_free(in_use: impl Fn(u32) -> bool) -> Option<u32> {
+    DISPLAYS.into_iter().find(|number| !in_use(*number))
+}
+
+/// A running Xvfb server.
+pub struct VirtualDisplay {
+    display: String,
+    /// Killed when the display is dropped
+    _server: Child,
+}
+
+impl VirtualDisplay {
+    /// Start Xvfb on a free display and wait until it accepts clients.
+    pub async fn start() -> Result<Self> {
+        let xvfb = std::env::var(XVFB_ENV).unwrap_or_else(|_| "Xvfb".to_string());
+        let number = first_free(in_use).with_context(|| {
+            format!(
+                "No free X display between :{} and :{}",
+                DISPLAYS.start(),
+                DISPLAYS.end()
+            )
+        })?;
+        let display = format!(":{}", number);
+
+        let mut server = match Command::new(&xvfb)
+            .args([display.as_str(), "-screen", "0", SCREEN, "-nolisten", "tcp"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(server) => server,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(
+                "XVFB_UNAVAILABLE: headed mode needs a display; install Xvfb, set {}, or set DISPLAY",
+                XVFB_ENV
+            ),
+            Err(e) => return Err(e).context("Failed to start Xvfb"),
+        };
+
+        let started = Instant::now();
+        while !Path::new(&socket_path(number)).exists() {
+            if let Some(status) = server.try_wait()? {
+                anyhow::bail!(
+                    "Xvfb exited ({}) before display {} was ready",
+                    status,
+                    display
+                );
+            }
+            if started.elapsed() >= START_TIMEOUT {
+                anyhow::bail!(
+                    "Xvfb did not open display {} within {}s",
+                    display,
+                    START_TIMEOUT.as_secs()
+                );
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        tracing::info!("Started Xvfb on display {}", display);
+
+        Ok(Self {
+            display,
+            _server: server,
+        })
+    }
+
+    /// X display name, e.g. `:99`.
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_free_display() {
+        assert_eq!(first_free(|_| false), Some(99));
+        assert_eq!(first_free(|n| n < 101), Some(101));
+        assert_eq!(first_free(|_| true), None);
+    }
+}
@@ -0,0 +1,135 @@
+//! Page zoom, as with the browser's zoom controls.
+//!
+//! Zooming to 200% doubles the size of a CSS pixel: the page lays out in a
+//! viewport half as wide and renders at twice the device pixel ratio, so
+//! media queries, reflow and text wrapping behave as they do for a user
+//! zoomed in (WCAG 1.4.4 and 1.4.10 checks). It is emulated with a device
+//! metrics override, which outlives navigations; captures that override the
+//! metrics themselves put the zoom back afterwards.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    ClearDeviceMetricsOverrideParams, SetDeviceMetricsOverrideParams,
+};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+/// Smallest zoom factor, as in Chrome's zoom menu.
+pub const MIN_ZOOM: f64 = 0.25;
+
+/// Largest zoom factor, as in Chrome's zoom menu.
+pub const MAX_ZOOM: f64 = 5.0;
+
+/// A zoom level and the window it was measured against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zoom {
+    pub factor: f64,
+    /// Window size in CSS pixels at 100%
+    width: f64,
+    height: f64,
+    /// Device pixel ratio at 100%
+    device_scale: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Window {
+    width: f64,
+    height: f64,
+    device_scale: f64,
+}
+
+impl Zoom {
+    /// CSS viewport width at this zoom.
+    pub fn viewport_width(&self) -> u32 {
+        (self.width / self.factor).round().max(1.0) as u32
+    }
+
+    /// CSS viewport height at this zoom.
+    pub fn viewport_height(&self) -> u32 {
+        (self.height / self.factor).round().max(1.0) as u32
+    }
+
+    /// Metrics rendering at this zoom, with the device pixel ratio scaled
+    /// to `scale` times the window's when given.
+    pub fn metrics(&self, scale: Option<f64>) -> Result<SetDeviceMetricsOverrideParams> {
+        SetDeviceMetricsOverrideParams::builder()
+            .width(self.viewport_width() as i64)
+            .height(self.viewport_height() as i64)
+            .device_scale_factor(scale.unwrap_or(self.device_scale) * self.factor)
+            .mobile(false)
+            .build()
+            .map_err(anyhow::Error::msg)
+    }
+}
+
+async fn window(page: &Page) -> Result<Window> {
+    page.evaluate("({ width: innerWidth, height: innerHeight, device_scale: devicePixelRatio })")
+        .await
+        .context("Failed to measure window")?
+        .into_value()
+        .context("Failed to parse window size")
+}
+
+/// Size of the page's CSS viewport, zoom included.
+pub async fn viewport(page: &Page) -> Result<(u32, u32)> {
+    let window = window(page).await?;
+    Ok((window.width.round() as u32, window.height.round() as u32))
+}
+
+/// Zoom `page` to `factor`; 1 clears the zoom and returns `None`.
+pub async fn apply(page: &Page, factor: f64) -> Result<Option<Zoom>> {
+    // Measure the window without any earlier zoom
+    page.execute(ClearDeviceMetricsOverrideParams::default())
+        .await
+        .context("Failed to reset zoom")?;
+    if factor == 1.0 {
+        return Ok(None);
+    }
+
+    let window = window(page).await?;
+    let zoom = Zoom {
+        factor,
+        width: window.width,
+        height: window.height,
+        device_scale: window.device_scale,
+    };
+    page.execute(zoom.metrics(None)?)
+        .await
+        .context("Failed to zoom page")?;
+    Ok(Some(zoom))
+}
+
+/// Put back `zoom` after a capture overrode the device metrics, or clear
+/// the override when the page isn't zoomed.
+pub async fn restore(page: &Page, zoom: Option<&Zoom>) -> Result<()> {
+    match zoom {
+        Some(zoom) => page.execute(zoom.metrics(None)?).await.map(drop),
+        None => page
+            .execute(ClearDeviceMetricsOverrideParams::default())
+            .await
+            .map(drop),
+    }
+    .context("Failed to restore viewport")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zoom(factor: f64) -> Zoom {
+        Zoom {
+            factor,
+            width: 1280.0,
+            height: 720.0,
+            device_scale: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_viewport_shrinks_as_zoom_grows() {
+        assert_eq!(zoom(2.0).viewport_width(), 640);
+        assert_eq!(zoom(2.0).viewport_height(), 360);
+        assert_eq!(zoom(4.0).viewport_width(), 320);
+        assert_eq!(zoom(0.5).viewport_width(), 2560);
+    }
+}
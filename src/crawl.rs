@@ -0,0 +1,518 @@
+//! Multi-page crawler built on the single-page primitives.
+//!
+//! A crawl runs as a background job on its own pages (outside every session).
+//! Workers pull URLs from a shared frontier seeded with the start page or the
+//! site's sitemap.xml, visit them through the same robots.txt check and
+//! per-origin throttle as `browser.open`, extract the requested fields, and
+//! queue the links they find. Results are appended as pages finish, so
+//! callers stream them by polling `browser.crawl.status` with `since`.
+//! Finished crawls are kept for `FINISHED_TTL`, and only the newest
+//! `MAX_FINISHED` of them.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use crate::browser::BrowserClient;
+use crate::models::{CrawlOptions, CrawlPage, CrawlState, CrawlStatus};
+use crate::robots::RobotsPolicy;
+use crate::throttle::{origin_of, OriginThrottle};
+
+/// Upper bound on parallel crawl pages.
+const MAX_CONCURRENCY: usize = 8;
+
+/// Sitemaps fetched per crawl, including nested sitemap indexes.
+const MAX_SITEMAPS: usize = 20;
+
+/// How long an idle worker waits for others to queue more links.
+const IDLE_POLL: Duration = Duration::from_millis(50);
+
+/// How long a finished crawl's results stay available.
+const FINISHED_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Finished crawls kept at most, newest first.
+const MAX_FINISHED: usize = 50;
+
+/// A running or finished crawl.
+pub struct CrawlJob {
+    pub id: String,
+    options: CrawlOptions,
+    pages: Mutex<Vec<CrawlPage>>,
+    outcome: Mutex<(CrawlState, Option<String>)>,
+    finished_at: Mutex<Option<Instant>>,
+    queued: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl CrawlJob {
+    pub fn new(options: CrawlOptions) -> Arc<Self> {
+        Arc::new(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            options,
+            pages: Mutex::new(Vec::new()),
+            outcome: Mutex::new((CrawlState::Running, None)),
+            finished_at: Mutex::new(None),
+            queued: AtomicUsize::new(0),
+            cancelled: AtomicBool::new(false),
+        })
+    }
+
+    /// Ask the workers to stop after their current page.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Progress plus every page recorded at or after `since`.
+    pub fn status(&self, since: usize) -> CrawlStatus {
+        let pages = lock(&self.pages);
+        let (state, error) = lock(&self.outcome).clone();
+        CrawlStatus {
+            crawl_id: self.id.clone(),
+            state,
+            error,
+            visited: pages.len(),
+            queued: self.queued.load(Ordering::Relaxed),
+            pages: pages.get(since..).unwrap_or_default().to_vec(),
+            next: pages.len(),
+        }
+    }
+
+    fn record(&self, page: CrawlPage) {
+        lock(&self.pages).push(page);
+    }
+
+    fn finish(&self, state: CrawlState, error: Option<String>) {
+        *lock(&self.outcome) = (state, error);
+        *lock(&self.finished_at) = Some(Instant::now());
+    }
+}
+
+/// Drop finished crawls older than `FINISHED_TTL`, then all but the newest
+/// `MAX_FINISHED` finished ones. Running crawls are kept.
+pub fn evict(crawls: &mut HashMap<String, Arc<CrawlJob>>, now: Instant) {
+    crawls.retain(|_, job| {
+        lock(&job.finished_at).map_or(true, |at| now.saturating_duration_since(at) < FINISHED_TTL)
+    });
+
+    let mut finished: Vec<(Instant, String)> = crawls
+        .iter()
+        .filter_map(|(id, job)| lock(&job.finished_at).map(|at| (at, id.clone())))
+        .collect();
+    if finished.len() > MAX_FINISHED {
+        finished.sort();
+        for (_, id) in &finished[..finished.len() - MAX_FINISHED] {
+            crawls.remove(id);
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Everything a worker needs, shared across workers.
+struct Crawl {
+    job: Arc<CrawlJob>,
+    client: Arc<BrowserClient>,
+    throttle: Arc<OriginThrottle>,
+    robots: Option<Arc<RobotsPolicy>>,
+    origin: Option<String>,
+    frontier: tokio::sync::Mutex<Frontier>,
+}
+
+#[derive(Default)]
+struct Frontier {
+    queue: VecDeque<(String, usize)>,
+    seen: HashSet<String>,
+    in_flight: usize,
+    started: usize,
+}
+
+impl Frontier {
+    fn push(&mut self, url: String, depth: usize) {
+        if self.seen.insert(url.clone()) {
+            self.queue.push_back((url, depth));
+        }
+    }
+}
+
+enum Step {
+    Visit(String, usize),
+    Wait,
+    Finish,
+}
+
+/// Run `job` to completion, recording its outcome on the job.
+pub async fn run(
+    job: Arc<CrawlJob>,
+    client: Arc<BrowserClient>,
+    throttle: Arc<OriginThrottle>,
+    robots: Option<Arc<RobotsPolicy>>,
+) {
+    let outcome = crawl(Arc::clone(&job), client, throttle, robots).await;
+    match outcome {
+        Ok(()) if job.cancelled.load(Ordering::Relaxed) => job.finish(CrawlState::Cancelled, None),
+        Ok(()) => job.finish(CrawlState::Done, None),
+        Err(e) => {
+            tracing::warn!("Crawl {} failed: {:#}", job.id, e);
+            job.finish(CrawlState::Failed, Some(format!("{:#}", e)));
+        }
+    }
+}
+
+async fn crawl(
+    job: Arc<CrawlJob>,
+    client: Arc<BrowserClient>,
+    throttle: Arc<OriginThrottle>,
+    robots: Option<Arc<RobotsPolicy>>,
+) -> Result<()> {
+    let start = url::Url::parse(&job.options.url).context("Invalid start URL")?;
+
+    let origin = origin_of(start.as_str());
+
+    let mut frontier = Frontier::default();
+    if job.options.sitemap {
+        let keep = |url: &str| !job.options.same_origin || origin_of(url) == origin;
+        match sitemap_urls(&start, keep, job.options.max_pages).await {
+            Ok(urls) if !urls.is_empty() => {
+                for url in urls {
+                    frontier.push(url, 0);
+                }
+            }
+            // No usable sitemap: crawl from the page instead (the site's
+            // root, when the start URL was the sitemap itself)
+            found => {
+                if let Err(e) = found {
+                    tracing::warn!("Crawl {}: {:#}; starting from the page", job.id, e);
+                }
+                let page = if start.path().ends_with(".xml") {
+                    start.join("/")?
+                } else {
+                    start.clone()
+                };
+                if let Some(url) = normalize_link(page.as_str()) {
+                    frontier.push(url, 0);
+                }
+            }
+        }
+    } else if let Some(url) = normalize_link(start.as_str()) {
+        frontier.push(url, 0);
+    }
+
+    let crawl = Arc::new(Crawl {
+        origin,
+        job: Arc::clone(&job),
+        client,
+        throttle,
+        robots,
+        frontier: tokio::sync::Mutex::new(frontier),
+    });
+
+    let workers = job.options.concurrency.clamp(1, MAX_CONCURRENCY);
+    let results = futures::future::join_all((0..workers).map(|_| worker(Arc::clone(&crawl)))).await;
+    results.into_iter().collect()
+}
+
+async fn worker(crawl: Arc<Crawl>) -> Result<()> {
+    let page = crawl.client.new_detached_page().await?;
+
+    loop {
+        if crawl.job.cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let step = {
+            let mut frontier = crawl.frontier.lock().await;
+            crawl
+                .job
+                .queued
+                .store(frontier.queue.len(), Ordering::Relaxed);
+            if frontier.started >= crawl.job.options.max_pages {
+                Step::Finish
+            } else if let Some((url, depth)) = frontier.queue.pop_front() {
+                frontier.started += 1;
+                frontier.in_flight += 1;
+                Step::Visit(url, depth)
+            } else if frontier.in_flight > 0 {
+                Step::Wait
+            } else {
+                Step::Finish
+            }
+        };
+
+        match step {
+            Step::Finish => break,
+            Step::Wait => tokio::time::sleep(IDLE_POLL).await,
+            Step::Visit(url, depth) => {
+                let (result, links) = visit(&crawl, &page, url, depth).await;
+                crawl.job.record(result);
+
+                let mut frontier = crawl.frontier.lock().await;
+                frontier.in_flight -= 1;
+                if depth < crawl.job.options.depth {
+                    for link in links {
+                        if !crawl.job.options.same_origin || origin_of(&link) == crawl.origin {
+                            frontier.push(link, depth + 1);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = page.close().await;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Extracted {
+    title: String,
+    links: Vec<String>,
+    data: BTreeMap<String, serde_json::Value>,
+}
+
+/// Visit one URL, returning its result and the crawlable links found on it.
+async fn visit(crawl: &Crawl, page: &Page, url: String, depth: usize) -> (CrawlPage, Vec<String>) {
+    let mut result = CrawlPage {
+        url,
+        depth,
+        title: None,
+        data: BTreeMap::new(),
+        links: 0,
+        error: None,
+    };
+
+    match load_and_extract(crawl, page, &result.url).await {
+        Ok(extracted) => {
+            let links: Vec<String> = extracted
+                .links
+                .into_iter()
+                .filter_map(|href| normalize_link(&href))
+                .collect();
+            result.title = Some(extracted.title);
+            result.data = extracted.data;
+            result.links = links.len();
+            (result, links)
+        }
+        Err(e) => {
+            result.error = Some(format!("{:#}", e));
+            (result, Vec::new())
+        }
+    }
+}
+
+async fn load_and_extract(crawl: &Crawl, page: &Page, url: &str) -> Result<Extracted> {
+    if let Some(robots) = &crawl.robots {
+        robots.check(url).await?;
+    }
+
+    {
+        let _permit = crawl.throttle.acquire(url).await;
+        page.goto(url).await.context("Navigation failed")?;
+        page.wait_for_navigation().await.ok();
+    }
+
+    let script = format!(
+        r#"((spec) => {{
+            const data = {{}};
+            for (const [name, sel] of Object.entries(spec)) {{
+                const el = document.querySelector(sel);
+                data[name] = el ? (el.innerText ?? el.textContent ?? '').trim() : null;
+            }}
+            return {{ title: document.title, links: Array.from(document.links, a => a.href), data }};
+        }})({})"#,
+        serde_json::to_string(&crawl.job.options.extract)?
+    );
+
+    page.evaluate(script)
+        .await
+        .context("Failed to extract page")?
+        .into_value()
+        .context("Failed to parse extracted page")
+}
+
+/// Canonical form of a crawlable link: http(s) only, fragment dropped.
+fn normalize_link(href: &str) -> Option<String> {
+    let mut url = url::Url::parse(href).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url.into())
+}
+
+/// Page URLs listed in the site's sitemap (following sitemap indexes) that
+/// `keep` accepts.
+///
+/// `start` itself is used if it points at an .xml file; otherwise
+/// `/sitemap.xml` on its origin.
+async fn sitemap_urls(
+    start: &url::Url,
+    keep: impl Fn(&str) -> bool,
+    max_urls: usize,
+) -> Result<Vec<String>> {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let root = if start.path().ends_with(".xml") {
+        start.clone()
+    } else {
+        start.join("/sitemap.xml")?
+    };
+
+    let mut urls = Vec::new();
+    let mut pending = vec![root.to_string()];
+    let mut fetched = 0;
+
+    while let Some(sitemap) = pending.pop() {
+        if fetched >= MAX_SITEMAPS || urls.len() >= max_urls {
+            break;
+        }
+        fetched += 1;
+
+        let body = http
+            .get(&sitemap)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to fetch sitemap {}", sitemap))?
+            .text()
+            .await?;
+
+        let locs = xml_locs(&body);
+        if body.contains("<sitemapindex") {
+            pending.extend(locs.into_iter().rev());
+        } else {
+            urls.extend(
+                locs.into_iter()
+                    .filter_map(|loc| normalize_link(&loc))
+                    .filter(|url| keep(url)),
+            );
+        }
+    }
+
+    urls.truncate(max_urls);
+    Ok(urls)
+}
+
+/// Contents of every `<loc>` element, entity-decoded.
+fn xml_locs(xml: &str) -> Vec<String> {
+    let mut locs = Vec::new();
+    let mut rest = xml;
+    while let Some(open) = rest.find("<loc>") {
+        rest = &rest[open + "<loc>".len()..];
+        let Some(close) = rest.find("</loc>") else {
+            break;
+        };
+        let loc = rest[..close]
+            .trim()
+            .trim_start_matches("<![CDATA[")
+            .trim_end_matches("]]>")
+            .replace("&amp;", "&")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'");
+        locs.push(loc);
+        rest = &rest[close..];
+    }
+    locs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_link() {
+        assert_eq!(
+            normalize_link("https://example.com/a#section").as_deref(),
+            Some("https://example.com/a")
+        );
+        assert_eq!(normalize_link("mailto:hi@example.com"), None);
+        assert_eq!(normalize_link("javascript:void(0)"), None);
+    }
+
+    #[test]
+    fn test_xml_locs() {
+        let xml = r#"<?xml version="1.0"?>
+            <urlset>
+              <url><loc>https://example.com/</loc></url>
+              <url><loc> https://example.com/search?a=1&amp;b=2 </loc></url>
+              <url><loc><![CDATA[https://example.com/c]]></loc></url>
+            </urlset>"#;
+        assert_eq!(
+            xml_locs(xml),
+            vec![
+                "https://example.com/",
+                "https://example.com/search?a=1&b=2",
+                "https://example.com/c",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frontier_dedups() {
+        let mut frontier = Frontier::default();
+        frontier.push("https://example.com/".to_string(), 0);
+        frontier.push("https://example.com/".to_string(), 1);
+        assert_eq!(frontier.queue.len(), 1);
+    }
+
+    #[test]
+    fn test_evict_finished_crawls() {
+        let job = || {
+            CrawlJob::new(
+                serde_json::from_value(serde_json::json!({"url": "https://example.com"})).unwrap(),
+            )
+        };
+        let mut crawls = HashMap::new();
+        let running = job();
+        crawls.insert(running.id.clone(), Arc::clone(&running));
+        for _ in 0..MAX_FINISHED + 5 {
+            let done = job();
+            done.finish(CrawlState::Done, None);
+            crawls.insert(done.id.clone(), done);
+        }
+
+        let now = Instant::now();
+        evict(&mut crawls, now);
+        assert_eq!(crawls.len(), MAX_FINISHED + 1);
+        assert!(crawls.contains_key(&running.id));
+
+        evict(&mut crawls, now + FINISHED_TTL);
+        assert_eq!(crawls.len(), 1);
+        assert!(crawls.contains_key(&running.id));
+    }
+
+    #[test]
+    fn test_status_pages_since() {
+        let job = CrawlJob::new(
+            serde_json::from_value(serde_json::json!({"url": "https://example.com"})).unwrap(),
+        );
+        for i in 0..3 {
+            job.record(CrawlPage {
+                url: format!("https://example.com/{}", i),
+                depth: 0,
+                title: None,
+                data: BTreeMap::new(),
+                links: 0,
+                error: None,
+            });
+        }
+
+        let status = job.status(1);
+        assert_eq!(status.state, CrawlState::Running);
+        assert_eq!(status.visited, 3);
+        assert_eq!(status.pages.len(), 2);
+        assert_eq!(status.next, 3);
+        assert!(job.status(10).pages.is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+//! Screencast frames streamed over the gateway.
+//!
+//! The live view serves MJPEG on its own HTTP port; a frame stream hands the
+//! same JPEG frames to gateway clients, for supervisors that only have the
+//! daemon socket. While a session's stream runs, its most recent
+//! `FRAME_BUFFER` frames are kept, numbered and timestamped. Callers poll
+//! with `since` and may wait for the next frame, so a watcher sees each
+//! frame about as soon as the page paints it. A stream nobody has polled for
+//! `IDLE_TIMEOUT` stops by itself, so an abandoned watcher costs nothing.
+//! Stopping a stream only leaves the session's screencast, which keeps
+//! running for a recording or live view still using it.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use chrono::Utc;
+use tokio::sync::{broadcast, oneshot, Notify};
+use tokio::task::JoinHandle;
+
+use crate::browser::{Frame, Screencast, ScreencastOptions};
+use crate::models::{StreamedFrame, StreamedFrames};
+
+/// Frames kept per stream before the oldest are evicted.
+pub const FRAME_BUFFER: usize = 30;
+
+/// A stream stops after this long without a poll.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Longest a poll may wait for a new frame.
+pub const MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// How often the stream checks whether it is still being polled.
+const IDLE_CHECK: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+struct Buffer {
+    frames: VecDeque<(u64, String, Frame)>,
+    next_seq: u64,
+    last_poll: Instant,
+    streaming: bool,
+}
+
+impl Buffer {
+    fn push(&mut self, jpeg: Frame) {
+        self.frames
+            .push_back((self.next_seq, Utc::now().to_rfc3339(), jpeg));
+        self.next_seq += 1;
+        while self.frames.len() > FRAME_BUFFER {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Up to `limit` of the newest frames numbered `since` or later.
+    fn frames(&self, session_id: &str, since: u64, limit: usize) -> StreamedFrames {
+        let newer: Vec<_> = self
+            .frames
+            .iter()
+            .filter(|(seq, _, _)| *seq >= since)
+            .collect();
+        let returned = &newer[newer.len().saturating_sub(limit)..];
+        StreamedFrames {
+            session_id: session_id.to_string(),
+            frames: returned
+                .iter()
+                .map(|(seq, at, jpeg)| StreamedFrame {
+                    seq: *seq,
+                    at: at.clone(),
+                    data: base64::engine::general_purpose::STANDARD.encode(jpeg.as_slice()),
+                    size: jpeg.len(),
+                })
+                .collect(),
+            next: self.next_seq,
+            skipped: self.next_seq.saturating_sub(since) - returned.len() as u64,
+            streaming: self.streaming,
+        }
+    }
+}
+
+/// Frames of a running stream, shared with the task filling them.
+#[derive(Debug, Clone)]
+pub struct Frames {
+    session_id: String,
+    buffer: Arc<Mutex<Buffer>>,
+    arrived: Arc<Notify>,
+}
+
+impl Frames {
+    fn new(session_id: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            buffer: Arc::new(Mutex::new(Buffer {
+                frames: VecDeque::new(),
+                next_seq: 0,
+                last_poll: Instant::now(),
+                streaming: true,
+            })),
+            arrived: Arc::new(Notify::new()),
+        }
+    }
+
+    fn push(&self, jpeg: Frame) {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(jpeg);
+        self.arrived.notify_waiters();
+    }
+
+    fn close(&self) {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .streaming = false;
+        self.arrived.notify_waiters();
+    }
+
+    fn idle(&self) -> Duration {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_poll
+            .elapsed()
+    }
+
+    /// Up to `limit` of the newest frames numbered `since` or later, waiting
+    /// up to `wait` for one if there are none yet.
+    pub async fn poll(&self, since: u64, limit: usize, wait: Duration) -> StreamedFrames {
+        let deadline = tokio::time::Instant::now() + wait.min(MAX_WAIT);
+        loop {
+            // Registered before checking, so a frame pushed in between still
+            // wakes us
+            let arrived = self.arrived.notified();
+            {
+                let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+                buffer.last_poll = Instant::now();
+                if buffer.next_seq > since
+                    || !buffer.streaming
+                    || tokio::time::Instant::now() >= deadline
+                {
+                    return buffer.frames(&self.session_id, since, limit);
+                }
+            }
+            let _ = tokio::time::timeout_at(deadline, arrived).await;
+        }
+    }
+
+    /// Whether the stream is still running.
+    pub fn streaming(&self) -> bool {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .streaming
+    }
+}
+
+/// Buffer frames from `screencast` until told to stop, the screencast ends,
+/// or nobody polls for `IDLE_TIMEOUT`; then leave the screencast.
+async fn pump(screencast: Screencast, frames: Frames, mut stop: oneshot::Receiver<()>) {
+    let (latest, mut receiver) = screencast.subscribe();
+    if let Some(jpeg) = latest {
+        frames.push(jpeg);
+    }
+    let mut idle_check = tokio::time::interval(IDLE_CHECK);
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            frame = receiver.recv() => match frame {
+                Ok(jpeg) => frames.push(jpeg),
+                // Lost frames show up as gaps in `seq`
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = idle_check.tick() => {
+                if frames.idle() >= IDLE_TIMEOUT {
+                    tracing::debug!("Frame stream of session {} idle, stopping", frames.session_id);
+                    break;
+                }
+            }
+        }
+    }
+    frames.close();
+    screencast.stop().await;
+}
+
+/// A session's frame stream.
+pub struct FrameStream {
+    frames: Frames,
+    /// Settings of the screencast the frames come from
+    options: ScreencastOptions,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl FrameStream {
+    /// Start buffering frames of `screencast`.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn start(session_id: &str, screencast: Screencast) -> Self {
+        let frames = Frames::new(session_id);
+        let options = screencast.options();
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(pump(screencast, frames.clone(), stopped));
+        Self {
+            frames,
+            options,
+            stop,
+            task,
+        }
+    }
+
+    /// Handle on the stream's frames.
+    pub fn frames(&self) -> Frames {
+        self.frames.clone()
+    }
+
+    pub fn options(&self) -> ScreencastOptions {
+        self.options
+    }
+
+    /// Stop the stream and leave its screencast.
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        if let Err(e) = self.task.await {
+            tracing::debug!("Frame stream task failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(byte: u8) -> Frame {
+        Arc::new(vec![byte; 4])
+    }
+
+    #[test]
+    fn test_newest_frames_within_limit() {
+        let frames = Frames::new("default");
+        for i in 0..FRAME_BUFFER as u8 + 5 {
+            frames.push(frame(i));
+        }
+        let buffer = frames.buffer.lock().unwrap();
+
+        let all = buffer.frames("default", 0, FRAME_BUFFER);
+        assert_eq!(all.frames.len(), FRAME_BUFFER);
+        assert_eq!(all.frames[0].seq, 5);
+        assert_eq!(all.skipped, 5);
+        assert_eq!(all.next, FRAME_BUFFER as u64 + 5);
+
+        let latest = buffer.frames("default", 10, 2);
+        assert_eq!(
+            latest.frames.iter().map(|f| f.seq).collect::<Vec<_>>(),
+            vec![33, 34]
+        );
+        assert_eq!(latest.skipped, 23);
+        assert_eq!(latest.frames[1].size, 4);
+
+        let none = buffer.frames("default", all.next, 2);
+        assert!(none.frames.is_empty());
+        assert_eq!(none.skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_poll_waits_for_next_frame() {
+        let frames = Frames::new("default");
+        frames.push(frame(1));
+
+        let waiting = {
+            let frames = frames.clone();
+            tokio::spawn(async move { frames.poll(1, 5, Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        frames.push(frame(2));
+        let polled = waiting.await.unwrap();
+        assert_eq!(polled.frames.len(), 1);
+        assert_eq!(polled.frames[0].seq, 1);
+
+        // Nothing newer: returns empty once the wait is up
+        let empty = frames.poll(2, 5, Duration::from_millis(10)).await;
+        assert!(empty.frames.is_empty());
+        assert!(empty.streaming);
+
+        frames.close();
+        assert!(!frames.poll(2, 5, Duration::from_secs(5)).await.streaming);
+    }
+}
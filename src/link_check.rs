@@ -0,0 +1,246 @@
+//! Broken link checking.
+//!
+//! Links are requested from the daemon, not the browser: a HEAD request per
+//! link, retried as GET when the server refuses HEAD or answers it with an
+//! error, since plenty of servers treat HEAD worse than GET. Redirects aren't
+//! followed, so a moved link is reported with where it points rather than
+//! wherever the chain ends. Requests carry the page's user agent and cookies
+//! and go through the per-origin throttle like navigations do.
+//!
+//! Answers are cached per URL and cookie set for a few minutes, so checking
+//! several pages of one site, which mostly share their navigation links,
+//! only requests each link once. Failed requests aren't cached.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use reqwest::header::{COOKIE, LOCATION, USER_AGENT};
+use reqwest::{Method, StatusCode};
+
+use crate::browser::PageLinks;
+use crate::models::{LinkCheck, LinkCheckReport};
+use crate::throttle::OriginThrottle;
+
+/// How long a link's answer is reused.
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Options for one `check` call.
+#[derive(Debug, Clone)]
+pub struct LinkCheckOptions {
+    /// Requests in flight at once
+    pub concurrency: usize,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Links checked, in document order; the rest are counted as unchecked
+    pub max_links: usize,
+    /// Reuse cached answers
+    pub use_cache: bool,
+}
+
+/// Requests links and caches their answers.
+pub struct LinkChecker {
+    http: reqwest::Client,
+    /// Answers by (cookie header, URL)
+    cache: Mutex<HashMap<(String, String), (Instant, LinkCheck)>>,
+}
+
+impl LinkChecker {
+    pub fn new() -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self {
+            http,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Check `page`'s links and sort them into ok, broken and redirected.
+    pub async fn check(
+        &self,
+        page: &PageLinks,
+        options: &LinkCheckOptions,
+        throttle: &OriginThrottle,
+    ) -> LinkCheckReport {
+        self.lock_cache()
+            .retain(|_, (fetched, _)| fetched.elapsed() < CACHE_TTL);
+
+        let checked = page.links.len().min(options.max_links);
+        let results: Vec<LinkCheck> = futures::stream::iter(&page.links[..checked])
+            .map(|url| self.check_one(page, url, options, throttle))
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await;
+
+        report(&page.url, results, page.links.len() - checked)
+    }
+
+    async fn check_one(
+        &self,
+        page: &PageLinks,
+        url: &str,
+        options: &LinkCheckOptions,
+        throttle: &OriginThrottle,
+    ) -> LinkCheck {
+        let key = (page.cookie.clone().unwrap_or_default(), url.to_string());
+        if options.use_cache {
+            if let Some((fetched, check)) = self.lock_cache().get(&key) {
+                if fetched.elapsed() < CACHE_TTL {
+                    return LinkCheck {
+                        cached: true,
+                        ..check.clone()
+                    };
+                }
+            }
+        }
+
+        let _permit = throttle.acquire(url).await;
+        let response = match self.request(Method::HEAD, page, url, options.timeout).await {
+            Ok((status, _)) if status.as_u16() >= 400 => {
+                self.request(Method::GET, page, url, options.timeout).await
+            }
+            other => other,
+        };
+
+        let check = match response {
+            Ok((status, location)) => LinkCheck {
+                url: url.to_string(),
+                status: Some(status.as_u16()),
+                location: location.and_then(|loc| resolve(url, &loc)),
+                error: None,
+                cached: false,
+            },
+            Err(e) => {
+                return LinkCheck {
+                    url: url.to_string(),
+                    status: None,
+                    location: None,
+                    error: Some(format!("{:#}", e)),
+                    cached: false,
+                }
+            }
+        };
+        self.lock_cache()
+            .insert(key, (Instant::now(), check.clone()));
+        check
+    }
+
+    /// Status and `Location` header of one request.
+    async fn request(
+        &self,
+        method: Method,
+        page: &PageLinks,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<(StatusCode, Option<String>)> {
+        let mut request = self
+            .http
+            .request(method, url)
+            .timeout(timeout)
+            .header(USER_AGENT, &page.user_agent);
+        if let Some(cookie) = &page.cookie {
+            request = request.header(COOKIE, cookie);
+        }
+        let response = request.send().await?;
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        Ok((response.status(), location))
+    }
+
+    fn lock_cache(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<(String, String), (Instant, LinkCheck)>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// `location` resolved against the URL that redirected to it.
+fn resolve(url: &str, location: &str) -> Option<String> {
+    let base = url::Url::parse(url).ok()?;
+    base.join(location).ok().map(String::from)
+}
+
+fn report(url: &str, results: Vec<LinkCheck>, unchecked: usize) -> LinkCheckReport {
+    let mut report = LinkCheckReport {
+        url: url.to_string(),
+        checked: results.len(),
+        ok: 0,
+        broken: Vec::new(),
+        redirected: Vec::new(),
+        unchecked,
+    };
+    for check in results {
+        match check.status {
+            Some(status) if status < 300 => report.ok += 1,
+            Some(status) if status < 400 => report.redirected.push(check),
+            _ => report.broken.push(check),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(url: &str, status: Option<u16>) -> LinkCheck {
+        LinkCheck {
+            url: url.to_string(),
+            status,
+            location: None,
+            error: status.is_none().then(|| "timed out".to_string()),
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_report_sorts_by_status() {
+        let report = report(
+            "https://example.com/",
+            vec![
+                check("https://example.com/a", Some(200)),
+                check("https://example.com/b", Some(301)),
+                check("https://example.com/c", Some(404)),
+                check("https://example.com/d", None),
+                check("https://example.com/e", Some(204)),
+                check("https://example.com/f", Some(503)),
+            ],
+            3,
+        );
+
+        assert_eq!(report.checked, 6);
+        assert_eq!(report.ok, 2);
+        assert_eq!(report.unchecked, 3);
+        let urls = |checks: &[LinkCheck]| -> Vec<String> {
+            checks.iter().map(|c| c.url.clone()).collect()
+        };
+        assert_eq!(urls(&report.redirected), vec!["https://example.com/b"]);
+        assert_eq!(
+            urls(&report.broken),
+            vec![
+                "https://example.com/c",
+                "https://example.com/d",
+                "https://example.com/f"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_location() {
+        assert_eq!(
+            resolve("https://example.com/docs/old", "new").as_deref(),
+            Some("https://example.com/docs/new")
+        );
+        assert_eq!(
+            resolve("https://example.com/a", "https://www.example.com/a").as_deref(),
+            Some("https://www.example.com/a")
+        );
+    }
+}
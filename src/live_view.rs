@@ -0,0 +1,445 @@
+//! Live view: sessions streamed as MJPEG over HTTP.
+//!
+//! Operators point a browser (or an `<img>` in a dashboard) at
+//! `/sessions/<id>/stream.mjpeg` to watch a headless session in real time;
+//! `/` lists every session. Each session's screencast starts with its first
+//! viewer and stops when the last one disconnects, so unwatched sessions
+//! cost nothing. Watching is read-only by construction: the endpoint only
+//! ever reads frames.
+//!
+//! The server binds to localhost by default and has no authentication of its
+//! own; put it behind a proxy before exposing it.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::browser::{BrowserClient, Frame, Screencast, ScreencastOptions};
+
+/// Multipart boundary between frames.
+const BOUNDARY: &str = "fgpframe";
+
+/// How long a viewer waits for a frame before re-sending the last one and
+/// checking that the session still exists.
+const IDLE_CHECK: Duration = Duration::from_secs(10);
+
+/// Upper bound on request line plus headers.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// How long a client has to send its request line and headers.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shared handle on the (lazily launched) browser.
+pub type ClientHandle = Arc<RwLock<Option<Arc<BrowserClient>>>>;
+
+/// Request routes.
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    Index,
+    Stream(String),
+    NotFound,
+}
+
+fn route(method: &str, path: &str) -> Route {
+    if method != "GET" {
+        return Route::NotFound;
+    }
+    let path = path.split('?').next().unwrap_or_default();
+    if path == "/" {
+        return Route::Index;
+    }
+    match path
+        .strip_prefix("/sessions/")
+        .and_then(|rest| rest.strip_suffix("/stream.mjpeg"))
+    {
+        Some(id) if !id.is_empty() && !id.contains('/') => Route::Stream(id.to_string()),
+        _ => Route::NotFound,
+    }
+}
+
+/// URL path of a session's stream.
+pub fn stream_path(session_id: &str) -> String {
+    format!("/sessions/{}/stream.mjpeg", session_id)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn index_html(sessions: &[String]) -> String {
+    let mut html = String::from(
+        "<!doctype html><meta charset=utf-8><title>fgp-browser live view</title>\
+         <style>body{font:14px sans-serif;margin:16px}figure{display:inline-block;margin:8px}\
+         img{max-width:640px;border:1px solid #ccc}</style>",
+    );
+    if sessions.is_empty() {
+        html.push_str("<p>No sessions</p>");
+    }
+    for id in sessions {
+        let id = escape_html(id);
+        html.push_str(&format!(
+            "<figure><img src=\"{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>",
+            stream_path(&id),
+            id,
+            id
+        ));
+    }
+    html
+}
+
+/// Read a request's line and headers, returning the request line; `None`
+/// when they run past `MAX_REQUEST_BYTES`.
+async fn read_head<R: AsyncBufRead + Unpin>(reader: R) -> Result<Option<String>> {
+    let mut reader = reader.take(MAX_REQUEST_BYTES as u64);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    loop {
+        let mut header = String::new();
+        let n = reader.read_line(&mut header).await?;
+        if header == "\r\n" || header == "\n" {
+            return Ok(Some(request_line));
+        }
+        if n == 0 {
+            // Out of budget, or the client stopped sending
+            return Ok((reader.limit() > 0).then_some(request_line));
+        }
+    }
+}
+
+/// Headers preceding each JPEG in the multipart stream.
+fn frame_header(len: usize) -> String {
+    format!(
+        "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+        BOUNDARY, len
+    )
+}
+
+/// Screencasts shared by every viewer of a session.
+struct Viewers {
+    screencast: Screencast,
+    count: usize,
+}
+
+/// Live view HTTP server.
+pub struct LiveView {
+    client: ClientHandle,
+    options: ScreencastOptions,
+    casts: Mutex<HashMap<String, Viewers>>,
+}
+
+impl LiveView {
+    pub fn new(client: ClientHandle, options: ScreencastOptions) -> Arc<Self> {
+        Arc::new(Self {
+            client,
+            options,
+            casts: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Accept connections until the runtime shuts down.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!("Live view accept failed: {}", e);
+                    continue;
+                }
+            };
+            let live_view = Arc::clone(&self);
+            tokio::spawn(async move {
+                if let Err(e) = live_view.handle(stream).await {
+                    tracing::debug!("Live view connection from {} ended: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    async fn browser(&self) -> Option<Arc<BrowserClient>> {
+        self.client.read().await.clone()
+    }
+
+    async fn handle(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream);
+        let head = tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut reader)).await;
+        let mut stream = reader.into_inner();
+        let request_line = match head {
+            Ok(Ok(Some(request_line))) => request_line,
+            Ok(Ok(None)) => {
+                return respond(
+                    &mut stream,
+                    "431 Request Header Fields Too Large",
+                    "text/plain",
+                    "Request headers too large",
+                )
+                .await;
+            }
+            Ok(Err(e)) => return Err(e),
+            Err(_) => {
+                return respond(
+                    &mut stream,
+                    "408 Request Timeout",
+                    "text/plain",
+                    "Request timed out",
+                )
+                .await;
+            }
+        };
+
+        let mut parts = request_line.split_whitespace();
+        let route = route(
+            parts.next().unwrap_or_default(),
+            parts.next().unwrap_or_default(),
+        );
+
+        let Some(browser) = self.browser().await else {
+            return respond(
+                &mut stream,
+                "503 Service Unavailable",
+                "text/plain",
+                "Browser not running",
+            )
+            .await;
+        };
+
+        match route {
+            Route::Index => {
+                let mut sessions = browser.list_sessions().await;
+                sessions.sort();
+                respond(
+                    &mut stream,
+                    "200 OK",
+                    "text/html; charset=utf-8",
+                    &index_html(&sessions),
+                )
+                .await
+            }
+            Route::Stream(id) => {
+                if !browser.list_sessions().await.contains(&id) {
+                    return respond(
+                        &mut stream,
+                        "404 Not Found",
+                        "text/plain",
+                        "Session not found",
+                    )
+                    .await;
+                }
+                self.stream(&mut stream, &browser, &id).await
+            }
+            Route::NotFound => {
+                respond(&mut stream, "404 Not Found", "text/plain", "Not found").await
+            }
+        }
+    }
+
+    /// Stream a session to one viewer until it disconnects or the session closes.
+    async fn stream(
+        &self,
+        stream: &mut TcpStream,
+        browser: &BrowserClient,
+        session_id: &str,
+    ) -> Result<()> {
+        let (mut latest, mut frames) = match self.join(browser, session_id).await {
+            Ok(joined) => joined,
+            Err(e) => {
+                let _ = respond(
+                    stream,
+                    "503 Service Unavailable",
+                    "text/plain",
+                    &format!("Failed to start the screencast: {}", e),
+                )
+                .await;
+                return Err(e);
+            }
+        };
+
+        let result = async {
+            stream
+                .write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\n\
+                         Content-Type: multipart/x-mixed-replace; boundary={}\r\n\
+                         Cache-Control: no-cache, no-store\r\n\
+                         Connection: close\r\n\r\n",
+                        BOUNDARY
+                    )
+                    .as_bytes(),
+                )
+                .await?;
+
+            loop {
+                if let Some(frame) = &latest {
+                    stream
+                        .write_all(frame_header(frame.len()).as_bytes())
+                        .await?;
+                    stream.write_all(frame).await?;
+                    stream.write_all(b"\r\n").await?;
+                    stream.flush().await?;
+                }
+
+                match tokio::time::timeout(IDLE_CHECK, frames.recv()).await {
+                    Ok(Ok(frame)) => latest = Some(frame),
+                    // Skip frames this viewer was too slow for
+                    Ok(Err(broadcast::error::RecvError::Lagged(_))) => {}
+                    Ok(Err(broadcast::error::RecvError::Closed)) => break,
+                    Err(_) => {
+                        if !browser
+                            .list_sessions()
+                            .await
+                            .iter()
+                            .any(|s| s == session_id)
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        }
+        .await;
+
+        self.leave(session_id).await;
+        result
+    }
+
+    /// Register a viewer, starting the session's screencast if needed.
+    async fn join(
+        &self,
+        browser: &BrowserClient,
+        session_id: &str,
+    ) -> Result<(Option<Frame>, broadcast::Receiver<Frame>)> {
+        let mut started: Option<Screencast> = None;
+        loop {
+            let mut casts = self.casts.lock().await;
+            if let Some(viewers) = casts.get_mut(session_id) {
+                viewers.count += 1;
+                let frames = viewers.screencast.subscribe();
+                drop(casts);
+                // Another viewer started the screencast meanwhile
+                if let Some(screencast) = started {
+                    screencast.stop().await;
+                }
+                return Ok(frames);
+            }
+            if let Some(screencast) = started.take() {
+                let frames = screencast.subscribe();
+                casts.insert(
+                    session_id.to_string(),
+                    Viewers {
+                        screencast,
+                        count: 1,
+                    },
+                );
+                return Ok(frames);
+            }
+            drop(casts);
+
+            // Started outside the lock, which every viewer takes
+            started = Some(
+                browser
+                    .join_screencast(self.options, false, Some(session_id))
+                    .await?,
+            );
+        }
+    }
+
+    /// Unregister a viewer, stopping the screencast after the last one.
+    async fn leave(&self, session_id: &str) {
+        let mut casts = self.casts.lock().await;
+        let last = match casts.get_mut(session_id) {
+            Some(viewers) => {
+                viewers.count -= 1;
+                viewers.count == 0
+            }
+            None => false,
+        };
+        let stopped = if last { casts.remove(session_id) } else { None };
+        drop(casts);
+        if let Some(viewers) = stopped {
+            viewers.screencast.stop().await;
+        }
+    }
+}
+
+/// Bind the live view server.
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind live view server to {}", addr))
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(route("GET", "/"), Route::Index);
+        assert_eq!(route("GET", "/?refresh=1"), Route::Index);
+        assert_eq!(
+            route("GET", "/sessions/agent-1/stream.mjpeg"),
+            Route::Stream("agent-1".to_string())
+        );
+        assert_eq!(route("GET", "/sessions//stream.mjpeg"), Route::NotFound);
+        assert_eq!(route("GET", "/sessions/a/b/stream.mjpeg"), Route::NotFound);
+        assert_eq!(route("POST", "/"), Route::NotFound);
+        assert_eq!(route("GET", "/favicon.ico"), Route::NotFound);
+    }
+
+    #[test]
+    fn test_index_escapes_session_ids() {
+        let html = index_html(&["default".to_string(), "<script>".to_string()]);
+
+        assert!(html.contains("/sessions/default/stream.mjpeg"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[tokio::test]
+    async fn test_request_heads_are_bounded() {
+        let request = "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(
+            read_head(request.as_bytes()).await.unwrap().as_deref(),
+            Some("GET / HTTP/1.1\r\n")
+        );
+
+        let padding = "X-Padding: ".to_string() + &"a".repeat(MAX_REQUEST_BYTES) + "\r\n";
+        let request = format!("GET / HTTP/1.1\r\n{}\r\n", padding);
+        assert_eq!(read_head(request.as_bytes()).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_frame_header() {
+        assert_eq!(
+            frame_header(1234),
+            "--fgpframe\r\nContent-Type: image/jpeg\r\nContent-Length: 1234\r\n\r\n"
+        );
+    }
+}
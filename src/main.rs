@@ -6,10 +6,23 @@
 //! 01/15/2026 - Added extension bridge WebSocket server (Claude)
 //! 01/15/2026 - Added connect mode for user's Chrome (Claude)
 
+mod artifacts;
 mod browser;
+mod crawl;
 mod extension_bridge;
+mod frame_stream;
+mod link_check;
+mod live_view;
 mod models;
+mod observer;
+mod recording;
+mod robots;
+mod schemas;
 mod service;
+mod state_diff;
+mod throttle;
+mod timeline;
+mod visual_diff;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
@@ -48,10 +61,15 @@ enum Commands {
         #[arg(short, long)]
         foreground: bool,
 
-        /// Run browser in headed mode (visible)
-        #[arg(long)]
+        /// Run browser in headed mode (visible; on Xvfb when there is no display)
+        #[arg(long, conflicts_with = "headless")]
         headed: bool,
 
+        /// Headless mode: old (fastest; no extensions or PDF viewer) or new
+        /// (the full browser without a window)
+        #[arg(long, default_value = "old")]
+        headless: String,
+
         /// Use system Chrome instead of bundled Chromium
         #[arg(long)]
         channel: Option<String>,
@@ -70,6 +88,96 @@ enum Commands {
         /// Extension bridge WebSocket port (default: 9223)
         #[arg(long, default_value = "9223")]
         extension_port: u16,
+
+        /// Launch Chrome at startup instead of on the first request
+        #[arg(long)]
+        warm: bool,
+
+        /// Artifact storage backend: "local" or "s3"
+        /// (s3 reads FGP_ARTIFACT_S3_BUCKET, AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY,
+        /// and optionally FGP_ARTIFACT_S3_ENDPOINT/_REGION/_PREFIX)
+        #[arg(long, default_value = "local")]
+        artifact_store: String,
+
+        /// Delete artifacts older than this many hours (0 = keep forever)
+        #[arg(long, default_value = "0")]
+        artifact_max_age_hours: u64,
+
+        /// Delete the oldest artifacts once the store exceeds this size (0 = unlimited)
+        #[arg(long, default_value = "0")]
+        artifact_max_mb: u64,
+
+        /// Keep at most this many artifacts per session (0 = unlimited)
+        #[arg(long, default_value = "0")]
+        artifact_max_per_session: usize,
+
+        /// Cap snapshot responses at about this size; larger trees are paginated
+        #[arg(long, default_value = "4096")]
+        max_response_kb: usize,
+
+        /// Snapshot rendering for calls without `format`: "json" or "text"
+        #[arg(long, default_value = "json")]
+        snapshot_format: String,
+
+        /// Max concurrent navigations per origin across all sessions (1 = serialize)
+        #[arg(long)]
+        per_origin_parallel: Option<usize>,
+
+        /// Minimum delay between navigations to the same origin, in ms
+        #[arg(long, default_value = "0")]
+        per_origin_delay_ms: u64,
+
+        /// Refuse navigations that the target's robots.txt disallows
+        #[arg(long)]
+        respect_robots: bool,
+
+        /// Product token matched against robots.txt User-agent lines
+        #[arg(long, default_value = robots::DEFAULT_USER_AGENT)]
+        robots_user_agent: String,
+
+        /// Serve sessions as MJPEG streams over HTTP on this port
+        #[arg(long)]
+        live_view_port: Option<u16>,
+
+        /// Address the live view server binds to
+        #[arg(long, default_value = "127.0.0.1")]
+        live_view_host: std::net::IpAddr,
+
+        /// Allow raw CDP calls through `cdp` for these domains ("*" for all)
+        #[arg(long, value_delimiter = ',')]
+        allow_cdp_domains: Vec<String>,
+
+        /// Reload and retry element actions whose target went missing (calls
+        /// can opt out with recover: false)
+        #[arg(long)]
+        recover: bool,
+
+        /// Bounded memory mode: periodically clear session caches, drop
+        /// sessions whose page closed, and trim event buffers
+        #[arg(long)]
+        bounded_memory: bool,
+
+        /// Seconds between maintenance passes in bounded memory mode
+        #[arg(long, default_value = "600", requires = "bounded_memory")]
+        maintenance_interval_secs: u64,
+
+        /// Give sessions a fresh page (and renderer process) once their page
+        /// is this many minutes old
+        #[arg(long, requires = "bounded_memory")]
+        recycle_after_mins: Option<u64>,
+
+        /// Time limit on navigations (open, back, forward, reload) for
+        /// sessions that don't set their own
+        #[arg(long, default_value = "30")]
+        navigation_timeout_secs: u64,
+
+        /// Time limit on element actions (click, fill, type_text, press, scroll, ...)
+        #[arg(long, default_value = "30")]
+        action_timeout_secs: u64,
+
+        /// Time limit on snapshots
+        #[arg(long, default_value = "30")]
+        snapshot_timeout_secs: u64,
     },
 
     /// Stop the browser daemon
@@ -84,9 +192,77 @@ enum Commands {
         socket: String,
     },
 
+    /// Detailed health check; exits non-zero when unhealthy (for probes)
+    Health {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+
+        /// Launch the browser if needed, open about:blank, time CDP, and check artifact disk
+        #[arg(long)]
+        self_test: bool,
+    },
+
+    /// Run a session maintenance pass now (see start --bounded-memory)
+    Maintain {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+
+    /// Print the JSON Schemas of all response models (no daemon needed)
+    Schemas {
+        /// Write them to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+
     /// Navigate to URL
     Open {
         url: String,
+        /// When navigation counts as done: commit, domcontentloaded, load, networkidle
+        #[arg(long)]
+        wait_until: Option<String>,
+        /// Time limit for this navigation, in ms (default: the session's)
+        #[arg(long)]
+        timeout_ms: Option<u64>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Go back one page in history
+    Back {
+        /// When navigation counts as done: commit, domcontentloaded, load, networkidle
+        #[arg(long)]
+        wait_until: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Go forward one page in history
+    Forward {
+        /// When navigation counts as done: commit, domcontentloaded, load, networkidle
+        #[arg(long)]
+        wait_until: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Reload the current page
+    Reload {
+        /// Bypass the cache (hard refresh)
+        #[arg(long)]
+        ignore_cache: bool,
+        /// When navigation counts as done: commit, domcontentloaded, load, networkidle
+        #[arg(long)]
+        wait_until: Option<String>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -96,6 +272,48 @@ enum Commands {
 
     /// Get ARIA accessibility tree snapshot
     Snapshot {
+        /// Always re-walk the tree instead of reusing a cached snapshot
+        #[arg(long)]
+        no_cache: bool,
+        /// Return at most this many nodes per page
+        #[arg(long)]
+        limit: Option<u64>,
+        /// Continue from a previous page's next_cursor
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Cap the response at about this many bytes (remaining nodes via next_cursor)
+        #[arg(long)]
+        max_bytes: Option<u64>,
+        /// Render as "json" (node tree) or "text" (indented `role "name" [@eN]` lines)
+        #[arg(long)]
+        format: Option<String>,
+        /// Also show a unified diff against the previous snapshot (text format)
+        #[arg(long)]
+        diff: bool,
+        /// Only nodes with these roles (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        roles: Vec<String>,
+        /// Only actionable nodes (buttons, links, fields, anything focusable)
+        #[arg(long)]
+        interactive: bool,
+        /// Only rendered nodes
+        #[arg(long)]
+        visible: bool,
+        /// Only this ref and its descendants
+        #[arg(long)]
+        root: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Take a snapshot and list the nodes added, removed and changed since the previous one
+    SnapshotDiff {
+        /// Always re-walk the tree instead of reusing a cached snapshot
+        #[arg(long)]
+        no_cache: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -105,8 +323,27 @@ enum Commands {
 
     /// Click an element
     Click {
-        /// Element selector (@e5 for ARIA ref, or CSS selector)
+        /// Element selector (@e5 for ARIA ref, CSS selector, or XPath)
         selector: String,
+        /// Mouse button: left, right (context menu), middle
+        #[arg(long, default_value = "left")]
+        button: String,
+        /// Clicks in a row (2 = double-click, 3 = triple-click)
+        #[arg(long, default_value = "1")]
+        count: u32,
+        /// Modifier keys to hold (Shift, Control, Alt, Meta)
+        #[arg(short, long, value_delimiter = ',')]
+        modifiers: Vec<String>,
+        /// Report what would happen without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Which match to use when several: in_viewport, largest, topmost,
+        /// reading_order (default: first in document order)
+        #[arg(long)]
+        rank: Option<String>,
+        /// Reload and retry if the target is missing or stale
+        #[arg(long)]
+        recover: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -120,6 +357,19 @@ enum Commands {
         selector: String,
         /// Value to fill
         value: String,
+        /// Add to the existing text instead of replacing it
+        #[arg(long)]
+        append: bool,
+        /// Report what would happen without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Which match to use when several: in_viewport, largest, topmost,
+        /// reading_order (default: first in document order)
+        #[arg(long)]
+        rank: Option<String>,
+        /// Reload and retry if the target is missing or stale
+        #[arg(long)]
+        recover: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -127,10 +377,10 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Press a key
-    Press {
-        /// Key to press (e.g., Enter, Tab, Escape)
-        key: String,
+    /// Focus an element without clicking it
+    Focus {
+        /// Element selector
+        selector: String,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -138,10 +388,10 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Take a screenshot
-    Screenshot {
-        /// Output file path (optional, returns base64 if not specified)
-        path: Option<String>,
+    /// Take focus away from an element (default: whatever has focus)
+    Blur {
+        /// Element selector
+        selector: Option<String>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -149,12 +399,10 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Select an option from a dropdown
-    Select {
+    /// Empty an input field
+    Clear {
         /// Element selector
         selector: String,
-        /// Option value to select
-        value: String,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -162,13 +410,19 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Set checkbox/radio state
-    Check {
-        /// Element selector
-        selector: String,
-        /// Whether to check (true) or uncheck (false)
-        #[arg(long, default_value = "true")]
-        checked: bool,
+    /// Type text key by key with human-like pauses
+    TypeText {
+        /// Text to type
+        text: String,
+        /// Element to click first (default: the focused element)
+        #[arg(long)]
+        selector: Option<String>,
+        /// Milliseconds between keystrokes (default 80)
+        #[arg(long)]
+        delay: Option<u64>,
+        /// Vary each pause randomly by up to this many milliseconds (default 40)
+        #[arg(long)]
+        jitter: Option<u64>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -176,10 +430,18 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Hover over an element
-    Hover {
-        /// Element selector
+    /// Fill a rich text editor (ProseMirror, Quill, Draft.js, contenteditable)
+    FillRichText {
+        /// Element selector (@e5 for ARIA ref, CSS selector, or XPath)
         selector: String,
+        /// Content to paste
+        content: String,
+        /// markdown, html, or text
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Add at the end instead of replacing the content
+        #[arg(long)]
+        append: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -187,16 +449,22 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Scroll to element or by amount
-    Scroll {
-        /// Element selector to scroll to (optional)
+    /// Select text in an element, by offsets or by matching text
+    SelectText {
+        /// Element selector (@e5 for ARIA ref, or CSS selector); whole page if omitted
         selector: Option<String>,
-        /// Horizontal scroll amount
-        #[arg(long, default_value = "0")]
-        x: i32,
-        /// Vertical scroll amount
-        #[arg(long, default_value = "0")]
-        y: i32,
+        /// Text to select, whitespace-insensitive
+        #[arg(long, conflicts_with_all = ["start", "end"])]
+        text: Option<String>,
+        /// Which match of --text to select, from 1
+        #[arg(long, default_value = "1")]
+        occurrence: u64,
+        /// First character to select
+        #[arg(long)]
+        start: Option<u64>,
+        /// Character after the last selected
+        #[arg(long)]
+        end: Option<u64>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -204,13 +472,8 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Press key with modifiers (Ctrl+A, Shift+Tab, etc.)
-    PressCombo {
-        /// Key to press
-        key: String,
-        /// Modifiers (Ctrl, Shift, Alt, Meta)
-        #[arg(short, long, value_delimiter = ',')]
-        modifiers: Vec<String>,
+    /// Show the selected text
+    Selection {
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -218,12 +481,8 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Upload a file to a file input
-    Upload {
-        /// Element selector
-        selector: String,
-        /// File path to upload
-        path: String,
+    /// Copy the selected text to the clipboard
+    CopySelection {
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
@@ -231,333 +490,2942 @@ enum Commands {
         session: Option<String>,
     },
 
-    /// Auth state management
-    State {
-        #[command(subcommand)]
-        action: StateAction,
+    /// Press a key or shortcut
+    Press {
+        /// Key to press (e.g., Enter, Tab, Escape, Control+A, Meta+Shift+P)
+        key: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
 
-    /// Session management for parallel requests
-    Session {
-        #[command(subcommand)]
-        action: SessionAction,
+    /// Set the keyboard layout used for fill/press (us, uk, de, fr)
+    KeyboardLayout {
+        /// Layout name
+        layout: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
 
-    /// Chrome extension features (tab groups, cookies, notifications)
-    Extension {
-        #[command(subcommand)]
-        action: ExtensionAction,
+    /// Set what happens to tabs and windows the page opens
+    PopupPolicy {
+        /// allow, same_tab, attach, or block
+        policy: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-}
 
-#[derive(Subcommand)]
-enum StateAction {
-    /// Save current auth state
-    Save {
-        name: String,
+    /// Zoom the page (2 for 200%, 1 to reset)
+    Zoom {
+        /// Zoom factor, 0.25 to 5
+        factor: f64,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
         #[arg(long)]
         session: Option<String>,
     },
-    /// Load saved auth state
-    Load {
-        name: String,
+
+    /// Set the session's time limits (prints them when none are given)
+    SetTimeouts {
+        /// Limit on navigations, in ms
+        #[arg(long)]
+        navigation_ms: Option<u64>,
+        /// Limit on element actions, in ms
+        #[arg(long)]
+        action_ms: Option<u64>,
+        /// Limit on snapshots, in ms
+        #[arg(long)]
+        snapshot_ms: Option<u64>,
+        /// Start from the daemon's limits instead of the session's
+        #[arg(long)]
+        reset: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
         /// Session ID (optional)
         #[arg(long)]
         session: Option<String>,
     },
-    /// List saved auth states
-    List {
+
+    /// Take a screenshot
+    Screenshot {
+        /// Output file path (optional, returns base64 if not specified)
+        path: Option<String>,
+        /// Capture the whole scrollable page instead of the viewport
+        #[arg(long)]
+        full_page: bool,
+        /// Crop to this element (@eN ref, CSS selector, or XPath)
+        #[arg(long)]
+        selector: Option<String>,
+        /// Pixels of surrounding context around --selector
+        #[arg(long, default_value = "0")]
+        padding: f64,
+        /// Outline the --selector element in the image
+        #[arg(long)]
+        marker: bool,
+        /// Capture this rect instead, as x,y,width,height in CSS pixels
+        #[arg(long)]
+        clip: Option<String>,
+        /// --clip coordinates are relative to the viewport, not the page
+        #[arg(long)]
+        clip_viewport: bool,
+        /// Device scale factor for this capture (e.g. 2 for a 2x image)
+        #[arg(long)]
+        scale: Option<f64>,
+        /// Image format: png, jpeg, webp
+        #[arg(long, default_value = "png")]
+        format: String,
+        /// Compression quality for jpeg and webp (0-100)
+        #[arg(long)]
+        quality: Option<u8>,
+        /// Wait for the next frame to be painted before capturing
+        #[arg(long)]
+        after_paint: bool,
+        /// Click this element first, then capture after paint
+        #[arg(long)]
+        click: Option<String>,
+        /// Painted frames to wait for (implies --after-paint)
+        #[arg(long)]
+        frames: Option<u32>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-}
 
-#[derive(Subcommand)]
-enum SessionAction {
-    /// Create a new isolated session
-    New {
-        /// Session ID
+    /// Capture one element, clipped to its box
+    ScreenshotElement {
+        /// Element selector (@e5 for ARIA ref, CSS selector, or XPath)
+        selector: String,
+        /// Output file path (optional, returns base64 if not specified)
+        path: Option<String>,
+        /// Pixels of surrounding context around the element
+        #[arg(long, default_value = "0")]
+        padding: f64,
+        /// Outline the element in the image
         #[arg(long)]
-        id: String,
+        marker: bool,
+        /// Device scale factor for this capture (e.g. 2 for a 2x image)
+        #[arg(long)]
+        scale: Option<f64>,
+        /// Image format: png, jpeg, webp
+        #[arg(long, default_value = "png")]
+        format: String,
+        /// Compression quality for jpeg and webp (0-100)
+        #[arg(long)]
+        quality: Option<u8>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-    /// List active sessions
-    List {
+
+    /// Compare a screenshot against a baseline image
+    CompareScreenshots {
+        /// Baseline image file (PNG, JPEG or WebP)
+        baseline: String,
+        /// Image to compare (optional, captures the page if not specified)
+        current: Option<String>,
+        /// BASELINE and CURRENT are artifact IDs, not file paths
+        #[arg(long)]
+        artifacts: bool,
+        /// Capture the whole scrollable page instead of the viewport
+        #[arg(long)]
+        full_page: bool,
+        /// Per-channel difference tolerated (0-255)
+        #[arg(long)]
+        threshold: Option<u8>,
+        /// Mismatch percentage allowed (reports passed/failed)
+        #[arg(long)]
+        max_mismatch: Option<f64>,
+        /// Save the diff image here (optional, returns base64 if not specified)
+        #[arg(long)]
+        diff: Option<String>,
+        /// Store the diff image as an artifact
+        #[arg(long)]
+        artifact: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-    /// Close a session
-    Close {
-        /// Session ID to close
+
+    /// Render the page with print media, one image per page
+    PrintPreview {
+        /// Paper size: letter, legal, a4, a3
+        #[arg(long, default_value = "letter")]
+        paper: String,
         #[arg(long)]
-        id: String,
+        landscape: bool,
+        /// Margin on every side, in inches
+        #[arg(long, default_value = "0.4")]
+        margin: f64,
+        /// Device scale factor of the page images
+        #[arg(long)]
+        scale: Option<f64>,
+        /// Also store a PDF (headless only)
+        #[arg(long)]
+        pdf: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-}
 
-#[derive(Subcommand)]
-enum ExtensionAction {
-    /// Group tabs together (requires extension)
-    Group {
-        /// Tab IDs to group (comma-separated)
-        #[arg(value_delimiter = ',')]
-        tab_ids: Vec<i32>,
-        /// Title for the tab group
-        #[arg(short, long)]
-        title: Option<String>,
-        /// Color for the tab group
-        #[arg(short, long)]
-        color: Option<String>,
+    /// Print the page to a PDF (headless only)
+    Pdf {
+        /// Output file path (optional, returns base64 if not specified)
+        path: Option<String>,
+        /// Paper size: letter, legal, a4, a3
+        #[arg(long, default_value = "letter")]
+        paper: String,
+        #[arg(long)]
+        landscape: bool,
+        /// Margin in inches, on every side or as top,right,bottom,left
+        #[arg(long, default_value = "0.4")]
+        margin: String,
+        /// HTML for the top margin (class pageNumber, totalPages, title, url, date)
+        #[arg(long)]
+        header: Option<String>,
+        /// HTML for the bottom margin
+        #[arg(long)]
+        footer: Option<String>,
+        /// Leave out background colors and images
+        #[arg(long)]
+        no_background: bool,
+        /// Store the PDF as an artifact instead of returning it
+        #[arg(long)]
+        artifact: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-    /// Ungroup tabs (requires extension)
-    Ungroup {
-        /// Tab IDs to ungroup (comma-separated)
-        #[arg(value_delimiter = ',')]
-        tab_ids: Vec<i32>,
+
+    /// Select an option from a dropdown
+    Select {
+        /// Element selector
+        selector: String,
+        /// Option value to select
+        value: String,
+        /// Report what would happen without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Which match to use when several: in_viewport, largest, topmost,
+        /// reading_order (default: first in document order)
+        #[arg(long)]
+        rank: Option<String>,
+        /// Reload and retry if the target is missing or stale
+        #[arg(long)]
+        recover: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-    /// List tab groups (requires extension)
-    ListGroups {
+
+    /// Select options of a dropdown by value, label, or index
+    SelectOption {
+        /// Element selector
+        selector: String,
+        /// Option value (repeatable for a multiple select)
+        #[arg(long, conflicts_with_all = ["label", "index"])]
+        value: Vec<String>,
+        /// Option text (repeatable)
+        #[arg(long, conflicts_with = "index")]
+        label: Vec<String>,
+        /// 0-based option position (repeatable)
+        #[arg(long)]
+        index: Vec<usize>,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-    /// Get all cookies for a domain (requires extension)
-    Cookies {
-        /// Domain to get cookies for (e.g., .x.com)
-        domain: String,
+
+    /// Set checkbox/radio state
+    Check {
+        /// Element selector
+        selector: String,
+        /// Whether to check (true) or uncheck (false)
+        #[arg(long, default_value = "true")]
+        checked: bool,
+        /// Report what would happen without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Which match to use when several: in_viewport, largest, topmost,
+        /// reading_order (default: first in document order)
+        #[arg(long)]
+        rank: Option<String>,
+        /// Reload and retry if the target is missing or stale
+        #[arg(long)]
+        recover: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-    /// Show a desktop notification (requires extension)
-    Notify {
-        /// Notification title
-        title: String,
-        /// Notification message
-        message: String,
+
+    /// Hover over an element
+    Hover {
+        /// Element selector
+        selector: String,
+        /// Report what would happen without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Which match to use when several: in_viewport, largest, topmost,
+        /// reading_order (default: first in document order)
+        #[arg(long)]
+        rank: Option<String>,
+        /// Reload and retry if the target is missing or stale
+        #[arg(long)]
+        recover: bool,
         #[arg(short, long, default_value = DEFAULT_SOCKET)]
         socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
     },
-}
 
-/// Build params with optional session_id
-fn with_session(mut params: serde_json::Value, session: Option<String>) -> serde_json::Value {
-    if let Some(sid) = session {
-        if let Some(obj) = params.as_object_mut() {
-            obj.insert("session_id".to_string(), serde_json::Value::String(sid));
-        } else {
-            params = serde_json::json!({ "session_id": sid });
-        }
-    }
-    params
-}
+    /// Drag one element onto another (kanban cards, sortable lists)
+    Drag {
+        /// Element to drag (@e5 for ARIA ref, CSS selector, or XPath)
+        source: String,
+        /// Element to drop it on
+        target: String,
+        /// Pointer moves between source and target (1-100)
+        #[arg(long, default_value = "10")]
+        steps: u32,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
 
-#[cfg(test)]
-mod tests {
-    use super::with_session;
-    use serde_json::json;
+    /// Set a date field (native date input or JS date picker)
+    SetDate {
+        /// Element selector (@e5 for ARIA ref, CSS selector, or XPath)
+        selector: String,
+        /// YYYY-MM-DD (or YYYY-MM-DDTHH:MM)
+        date: String,
+        /// Format to type the date in, e.g. DD/MM/YYYY (default: from the placeholder)
+        #[arg(long)]
+        format: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
 
-    #[test]
-    fn with_session_inserts_into_object() {
-        let params = json!({ "url": "https://example.com" });
-        let updated = with_session(params, Some("sess-1".to_string()));
-        assert_eq!(updated["session_id"], "sess-1");
-        assert_eq!(updated["url"], "https://example.com");
-    }
+    /// Find snapshot elements nearest to an anchor element
+    Nearest {
+        /// Anchor element (@e5 for ARIA ref, or CSS selector)
+        #[arg(long, conflicts_with = "text", required_unless_present = "text")]
+        anchor: Option<String>,
+        /// Anchor on the innermost element containing this text
+        #[arg(long)]
+        text: Option<String>,
+        /// any, left, right, above, below, same_row, same_column
+        #[arg(long, default_value = "any")]
+        direction: String,
+        /// Only elements with this ARIA role
+        #[arg(long)]
+        role: Option<String>,
+        /// Only elements whose name contains this
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
 
-    #[test]
-    fn with_session_replaces_non_object() {
-        let params = json!("not-an-object");
-        let updated = with_session(params, Some("sess-2".to_string()));
-        assert_eq!(updated, json!({ "session_id": "sess-2" }));
-    }
+    /// Find snapshot elements inside a rectangle (document pixels)
+    Within {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        /// Include elements that only overlap the rectangle
+        #[arg(long)]
+        partial: bool,
+        /// Only elements with this ARIA role
+        #[arg(long)]
+        role: Option<String>,
+        /// Only elements whose name contains this
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
 
-    #[test]
-    fn with_session_noop_when_missing() {
-        let params = json!({ "url": "https://example.com" });
-        let updated = with_session(params.clone(), None);
-        assert_eq!(updated, params);
-    }
-}
+    /// Find snapshot elements by role, accessible name or text
+    FindElements {
+        /// Only elements with this ARIA role
+        #[arg(long)]
+        role: Option<String>,
+        /// Only elements whose name contains this
+        #[arg(long)]
+        name: Option<String>,
+        /// Only the innermost elements whose text contains this
+        #[arg(long)]
+        text: Option<String>,
+        /// Match --name and --text whole instead of as substrings
+        #[arg(long)]
+        exact: bool,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// List the result cards of a search-result page (title, URL, snippet, ref)
+    SummarizeResults {
+        /// Only results inside this element (@e5 for ARIA ref, or CSS selector)
+        container: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
 
-    match cli.command {
-        Commands::Start {
-            socket,
-            foreground,
-            headed,
-            channel: _,
-            connect,
-            extension_bridge,
-            extension_port,
-        } => cmd_start(
-            socket,
-            foreground,
-            !headed,
-            connect,
-            extension_bridge,
-            extension_port,
-        ),
-        Commands::Stop { socket } => cmd_stop(socket),
-        Commands::Status { socket } => cmd_status(socket),
-        Commands::Open {
-            url,
-            socket,
-            session,
-        } => {
-            let params = with_session(serde_json::json!({"url": url}), session);
-            cmd_call_daemon(&socket, "browser.open", params, cli.json)
-        }
-        Commands::Snapshot { socket, session } => {
-            let params = with_session(serde_json::json!({}), session);
-            cmd_call_daemon(&socket, "browser.snapshot", params, cli.json)
-        }
-        Commands::Click {
-            selector,
+    /// List a form's fields with labels, types, values, constraints and options
+    DescribeForm {
+        /// Form or its container (@e5 for ARIA ref, or CSS selector; default: first form)
+        form: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Fill a form from a JSON object of values keyed by field name, autocomplete or label
+    AutofillForm {
+        /// JSON object of values (e.g. '{"email":"ada@example.com","terms":true}'),
+        /// or a path to a file containing one
+        data: String,
+        /// Form or its container (@e5 for ARIA ref, or CSS selector; default: first form)
+        #[arg(long)]
+        form: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// List the validation errors showing on a form, by field
+    ValidationErrors {
+        /// Form or its container (@e5 for ARIA ref, or CSS selector; default: first form)
+        form: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Locate a table cell by row text and column header
+    TableCell {
+        /// Table (@e5 for ARIA ref, or CSS selector; default: first table)
+        #[arg(long)]
+        table: Option<String>,
+        /// Row containing this text
+        #[arg(
+            long,
+            conflicts_with = "row_index",
+            required_unless_present = "row_index"
+        )]
+        row: Option<String>,
+        /// 0-based body row
+        #[arg(long)]
+        row_index: Option<usize>,
+        /// Column header text
+        #[arg(
+            long,
+            conflicts_with = "column_index",
+            required_unless_present = "column_index"
+        )]
+        column: Option<String>,
+        /// 0-based column
+        #[arg(long)]
+        column_index: Option<usize>,
+        /// Report the control in the cell whose label contains this
+        #[arg(long)]
+        target: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Click the control in a table cell (e.g. --row Acme --column Actions)
+    ClickCell {
+        /// Table (@e5 for ARIA ref, or CSS selector; default: first table)
+        #[arg(long)]
+        table: Option<String>,
+        /// Row containing this text
+        #[arg(
+            long,
+            conflicts_with = "row_index",
+            required_unless_present = "row_index"
+        )]
+        row: Option<String>,
+        /// 0-based body row
+        #[arg(long)]
+        row_index: Option<usize>,
+        /// Column header text
+        #[arg(
+            long,
+            conflicts_with = "column_index",
+            required_unless_present = "column_index"
+        )]
+        column: Option<String>,
+        /// 0-based column
+        #[arg(long)]
+        column_index: Option<usize>,
+        /// Click the control in the cell whose label contains this
+        #[arg(long)]
+        target: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Outline an element with a label (for headful runs and recordings)
+    Highlight {
+        /// Element selector (@e5 for ARIA ref, CSS selector, or XPath)
+        selector: String,
+        /// Text shown above the outline
+        #[arg(long)]
+        label: Option<String>,
+        /// How long the outline stays in ms (0 = until the next highlight)
+        #[arg(long, default_value = "3000")]
+        duration_ms: u64,
+        /// CSS color of the outline
+        #[arg(long)]
+        color: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Show a synthetic cursor and click ripples at interaction points
+    CursorOverlay {
+        /// Hide the cursor instead of showing it
+        #[arg(long)]
+        off: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Scroll to element or by amount
+    Scroll {
+        /// Element selector to scroll to (optional)
+        selector: Option<String>,
+        /// Horizontal scroll amount
+        #[arg(long, default_value = "0")]
+        x: i32,
+        /// Vertical scroll amount
+        #[arg(long, default_value = "0")]
+        y: i32,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Scroll to the bottom until lazy-loaded content stops appearing
+    ScrollToBottom {
+        /// Scroll container (@eN or CSS) instead of the window
+        #[arg(long)]
+        container: Option<String>,
+        /// Most scroll-and-wait rounds
+        #[arg(long, default_value = "10")]
+        max_rounds: u32,
+        /// Wait after each round for new content (ms)
+        #[arg(long, default_value = "500")]
+        settle_ms: u64,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Restore the scroll position last recorded for the current page
+    RestoreScroll {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Wait until the page has settled (network idle, no layout shifts, no DOM changes)
+    WaitStable {
+        /// How long every signal must stay quiet
+        #[arg(long, default_value = "500")]
+        quiet_ms: u64,
+        /// Give up after this long
+        #[arg(long, default_value = "10000")]
+        timeout_ms: u64,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Press key with modifiers (Ctrl+A, Shift+Tab, etc.)
+    PressCombo {
+        /// Key to press
+        key: String,
+        /// Modifiers (Ctrl, Shift, Alt, Meta)
+        #[arg(short, long, value_delimiter = ',')]
+        modifiers: Vec<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Upload a file to a file input
+    Upload {
+        /// Element selector
+        selector: String,
+        /// File path to upload
+        path: String,
+        /// Report what would happen without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Which match to use when several: in_viewport, largest, topmost,
+        /// reading_order (default: first in document order)
+        #[arg(long)]
+        rank: Option<String>,
+        /// Reload and retry if the target is missing or stale
+        #[arg(long)]
+        recover: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Set the files of a file input (several for a multiple input)
+    UploadFiles {
+        /// Element selector
+        selector: String,
+        /// Files to set; none clears the input
+        paths: Vec<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Assert element or page text; exits non-zero on failure
+    AssertText {
+        /// Expected text
+        expected: String,
+        /// Element selector (@e5 or CSS); omit to check the whole page
+        #[arg(long)]
+        selector: Option<String>,
+        /// Require equality instead of containment
+        #[arg(long)]
+        exact: bool,
+        /// Don't store a screenshot on failure
+        #[arg(long)]
+        no_screenshot: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Assert an element is visible; exits non-zero on failure
+    AssertVisible {
+        /// Element selector (@e5 for ARIA ref, or CSS selector)
+        selector: String,
+        /// Don't store a screenshot on failure
+        #[arg(long)]
+        no_screenshot: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Assert the page URL matches a pattern (* wildcards); exits non-zero on failure
+    AssertUrl {
+        /// URL pattern
+        pattern: String,
+        /// Don't store a screenshot on failure
+        #[arg(long)]
+        no_screenshot: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Assert how many elements match a selector; exits non-zero on failure
+    AssertCount {
+        /// CSS selector
+        selector: String,
+        /// Expected number of matches
+        count: usize,
+        /// Don't store a screenshot on failure
+        #[arg(long)]
+        no_screenshot: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Run a batch of assertions and report all failures; exits non-zero if any failed
+    Verify {
+        /// JSON array of assertions (e.g. '[{"type":"visible","selector":"nav"}]'),
+        /// or a path to a file containing one
+        assertions: String,
+        /// Don't store a screenshot on failure
+        #[arg(long)]
+        no_screenshot: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Crawl a site, printing one JSON line per page as it finishes
+    Crawl {
+        /// Start URL (or sitemap URL with --sitemap)
+        url: String,
+        /// Stop after this many pages
+        #[arg(long, default_value = "50")]
+        max_pages: usize,
+        /// Maximum link hops from the seed pages
+        #[arg(long, default_value = "2")]
+        depth: usize,
+        /// Follow links to other origins too
+        #[arg(long)]
+        any_origin: bool,
+        /// Seed from sitemap.xml instead of following links from the start page
+        #[arg(long)]
+        sitemap: bool,
+        /// Pages crawled in parallel
+        #[arg(long, default_value = "2")]
+        concurrency: usize,
+        /// Field to extract from each page, as name=selector (repeatable)
+        #[arg(long = "extract", value_name = "NAME=SELECTOR")]
+        extract: Vec<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+
+    /// Auth state management
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+
+    /// Print the MJPEG live view URL for a session (needs --live-view-port)
+    LiveView {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Stream a session's screencast frames over the daemon socket
+    Watch {
+        /// Write frames to this directory as <seq>.jpg instead of printing them
+        #[arg(long)]
+        dir: Option<String>,
+        /// Stop after this many frames
+        #[arg(long)]
+        count: Option<u64>,
+        /// JPEG quality (0-100)
+        #[arg(long)]
+        quality: Option<u8>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Draw on or capture canvas elements (signature pads, whiteboards)
+    Canvas {
+        #[command(subcommand)]
+        action: CanvasAction,
+    },
+
+    /// Play, pause, seek, or inspect video/audio elements
+    Media {
+        #[command(subcommand)]
+        action: MediaAction,
+    },
+
+    /// List Web Notifications the page has shown
+    Notifications {
+        /// Empty the list after reading
+        #[arg(long)]
+        clear: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Reload the page with a trial Content-Security-Policy and list what it blocks
+    CspTrial {
+        /// Policy to try, e.g. "default-src 'self'"
+        policy: String,
+        /// Page to load (default: reload the current page)
+        #[arg(long)]
+        url: Option<String>,
+        /// Enforce the policy (replacing the page's own) instead of report-only
+        #[arg(long)]
+        enforce: bool,
+        /// Milliseconds after load to keep collecting violations (default 1000)
+        #[arg(long)]
+        wait_ms: Option<u64>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Load a URL under several locales in parallel and snapshot each
+    LocaleVariants {
+        /// Page to load
+        url: String,
+        /// Variants as locale[@timezone][@latitude,longitude],
+        /// e.g. de-DE@Europe/Berlin@52.52,13.405
+        #[arg(required = true)]
+        variants: Vec<String>,
+        /// Milliseconds to let each page settle after load (default 500)
+        #[arg(long)]
+        wait_ms: Option<u64>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+
+    /// Browser build, platform, viewport, overrides, proxy, extensions and feature flags
+    Environment {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Send a raw CDP call to the page (daemon needs --allow-cdp-domains)
+    Cdp {
+        /// CDP method, e.g. Page.getLayoutMetrics
+        method: String,
+        /// Params as a JSON object
+        #[arg(long)]
+        params: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Break the current page's requests down by domain, third parties first
+    ThirdParty {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Say whether a login wall, paywall, CAPTCHA or interstitial blocks the page
+    DetectBlockers {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Check the page's same-origin links and list broken and redirected ones
+    CheckLinks {
+        /// Requests in flight at once
+        #[arg(long, default_value = "8")]
+        concurrency: u64,
+        /// Links checked, in document order
+        #[arg(long, default_value = "500")]
+        max_links: u64,
+        /// Per-link timeout in milliseconds
+        #[arg(long, default_value = "10000")]
+        timeout_ms: u64,
+        /// Request every link again instead of reusing recent answers
+        #[arg(long)]
+        no_cache: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Map snapshot refs to CDP node IDs for other CDP tools
+    ExportRefs {
+        /// Refs to export (e.g. @e3); all when omitted
+        refs: Vec<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Per-session screenshot timeline (storyboard)
+    Timeline {
+        #[command(subcommand)]
+        action: TimelineAction,
+    },
+
+    /// Record the session's page to a video file
+    Record {
+        #[command(subcommand)]
+        action: RecordAction,
+    },
+
+    /// Fake page clock for countdowns and expiry banners
+    Clock {
+        #[command(subcommand)]
+        action: ClockAction,
+    },
+
+    /// Session management for parallel requests
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
+
+    /// Chrome extension features (tab groups, cookies, notifications)
+    Extension {
+        #[command(subcommand)]
+        action: ExtensionAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum StateAction {
+    /// Save current auth state
+    Save {
+        name: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Load saved auth state
+    Load {
+        name: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// List saved auth states
+    List {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Show cookies and storage keys that differ between two saved states
+    Diff {
+        /// State to compare from
+        a: String,
+        /// State to compare to
+        b: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CanvasAction {
+    /// Draw strokes with pointer events
+    Draw {
+        /// Canvas (@e5 for ARIA ref, or CSS selector)
+        selector: String,
+        /// JSON list of strokes, each a list of [x, y] or [x, y, t_ms] points
+        #[arg(long)]
+        strokes: String,
+        /// Points are fractions (0-1) of the canvas size
+        #[arg(long)]
+        normalized: bool,
+        /// Delay between points without a time, in ms
+        #[arg(long)]
+        step_ms: Option<u64>,
+        /// Draw with a pen instead of the mouse
+        #[arg(long)]
+        pen: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Save the canvas bitmap as PNG
+    Capture {
+        /// Canvas (@e5 for ARIA ref, or CSS selector)
+        selector: String,
+        /// Output file path (optional, returns base64 if not specified)
+        path: Option<String>,
+        /// Store in the artifact backend
+        #[arg(long, conflicts_with = "path")]
+        artifact: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MediaAction {
+    /// Duration, position, and playback state
+    State {
+        /// Media element or player (@e5 for ARIA ref, or CSS selector; default: all on the page)
+        selector: Option<String>,
+        /// Wait up to this long (ms) for the media to become playable
+        #[arg(long)]
+        wait_ms: Option<u64>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Start playback
+    Play {
+        /// Media element or player (@e5 for ARIA ref, or CSS selector; default: first on the page)
+        selector: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Pause playback
+    Pause {
+        /// Media element or player (@e5 for ARIA ref, or CSS selector; default: first on the page)
+        selector: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Jump to a position
+    Seek {
+        /// Position in seconds
+        time: f64,
+        /// Media element or player (@e5 for ARIA ref, or CSS selector; default: first on the page)
+        #[arg(long)]
+        selector: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Mute all media on the page
+    Mute {
+        /// Unmute instead
+        #[arg(long)]
+        off: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClockAction {
+    /// Run the page clock from a given time
+    Set {
+        /// ISO 8601 time (local to the page without an offset) or epoch ms
+        start_time: String,
+        /// Stay at the start time instead of running
+        #[arg(long)]
+        frozen: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Skip ahead, running the page's timers that fall due
+    Forward {
+        /// Milliseconds to skip
+        ms: u64,
+        /// Stop after this many timer callbacks
+        #[arg(long)]
+        max_timers: Option<u64>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Put the real clock back
+    Clear {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TimelineAction {
+    /// Capture a frame after every navigation
+    Auto {
+        /// Turn auto-capture off
+        #[arg(long)]
+        off: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Add a screenshot of the current page
+    Capture {
+        /// Note shown with the frame
+        #[arg(long)]
+        label: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// List frames with timestamps, URLs, and artifact IDs
+    Index {
+        /// Only frames with this index or later
+        #[arg(long, default_value = "0")]
+        since: usize,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Drop all frames
+    Clear {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RecordAction {
+    /// Start recording (needs ffmpeg on PATH or FGP_FFMPEG)
+    Start {
+        /// Output file (default: the daemon's recordings directory)
+        path: Option<String>,
+        /// Video format: webm or mp4
+        #[arg(long, default_value = "webm")]
+        format: String,
+        /// Frames per second (1-30)
+        #[arg(long, default_value = "10")]
+        fps: u32,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Stop recording and finish the video
+    Stop {
+        /// Also store the video in the artifact store
+        #[arg(long)]
+        artifact: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+        /// Session ID (optional)
+        #[arg(long)]
+        session: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionAction {
+    /// Create a new isolated session
+    New {
+        /// Session ID
+        #[arg(long)]
+        id: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// List active sessions
+    List {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Close a session
+    Close {
+        /// Session ID to close
+        #[arg(long)]
+        id: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Create a read-only observer handle (use it as --session for snapshot/screenshot)
+    Observe {
+        /// Session to observe
+        #[arg(long, default_value = "default")]
+        id: String,
+        /// Start with the events the session recorded before now
+        #[arg(long)]
+        replay: bool,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Print events buffered for an observer, or recorded by a session
+    Events {
+        /// Observer handle from `session observe`
+        #[arg(long)]
+        observer: Option<String>,
+        /// Session whose recent events to print (without --observer)
+        #[arg(long, conflicts_with = "observer")]
+        id: Option<String>,
+        /// Only events with this sequence number or later
+        #[arg(long, default_value = "0")]
+        since: u64,
+        /// Only these event types, comma-separated (session events only)
+        #[arg(long, value_delimiter = ',', conflicts_with = "observer")]
+        types: Vec<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Release an observer handle
+    Unobserve {
+        /// Observer handle from `session observe`
+        #[arg(long)]
+        observer: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExtensionAction {
+    /// Group tabs together (requires extension)
+    Group {
+        /// Tab IDs to group (comma-separated)
+        #[arg(value_delimiter = ',')]
+        tab_ids: Vec<i32>,
+        /// Title for the tab group
+        #[arg(short, long)]
+        title: Option<String>,
+        /// Color for the tab group
+        #[arg(short, long)]
+        color: Option<String>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Ungroup tabs (requires extension)
+    Ungroup {
+        /// Tab IDs to ungroup (comma-separated)
+        #[arg(value_delimiter = ',')]
+        tab_ids: Vec<i32>,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// List tab groups (requires extension)
+    ListGroups {
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Get all cookies for a domain (requires extension)
+    Cookies {
+        /// Domain to get cookies for (e.g., .x.com)
+        domain: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Show a desktop notification (requires extension)
+    Notify {
+        /// Notification title
+        title: String,
+        /// Notification message
+        message: String,
+        #[arg(short, long, default_value = DEFAULT_SOCKET)]
+        socket: String,
+    },
+}
+
+/// Build params with optional session_id
+fn with_session(mut params: serde_json::Value, session: Option<String>) -> serde_json::Value {
+    if let Some(sid) = session {
+        if let Some(obj) = params.as_object_mut() {
+            obj.insert("session_id".to_string(), serde_json::Value::String(sid));
+        } else {
+            params = serde_json::json!({ "session_id": sid });
+        }
+    }
+    params
+}
+
+/// Parse a `locale[@timezone][@latitude,longitude]` variant spec
+fn locale_variant(spec: &str) -> Result<serde_json::Value> {
+    let mut parts = spec.split('@');
+    let locale = parts.next().unwrap_or_default();
+    if locale.is_empty() {
+        anyhow::bail!("Variant '{}' has no locale", spec);
+    }
+    let mut variant = serde_json::json!({ "locale": locale });
+    for part in parts {
+        match part.split_once(',') {
+            Some((latitude, longitude)) => {
+                let coordinate = |s: &str| {
+                    s.trim()
+                        .parse::<f64>()
+                        .with_context(|| format!("Bad coordinate in variant '{}'", spec))
+                };
+                variant["geolocation"] = serde_json::json!({
+                    "latitude": coordinate(latitude)?,
+                    "longitude": coordinate(longitude)?
+                });
+            }
+            None => variant["timezone"] = serde_json::json!(part),
+        }
+    }
+    Ok(variant)
+}
+
+/// Parse an `x,y,width,height` screenshot clip
+fn clip_rect(spec: &str) -> Result<serde_json::Value> {
+    let parts = spec
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Bad number in clip '{}'", spec))?;
+    let [x, y, width, height] = parts[..] else {
+        anyhow::bail!("Clip '{}' must be x,y,width,height", spec);
+    };
+    Ok(serde_json::json!({"x": x, "y": y, "width": width, "height": height}))
+}
+
+/// Parse a PDF margin: inches on every side, or `top,right,bottom,left`
+fn pdf_margin(spec: &str) -> Result<serde_json::Value> {
+    let parts = spec
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Bad number in margin '{}'", spec))?;
+    match parts[..] {
+        [inches] => Ok(serde_json::json!(inches)),
+        [top, right, bottom, left] => Ok(serde_json::json!({
+            "top": top, "right": right, "bottom": bottom, "left": left
+        })),
+        _ => anyhow::bail!(
+            "Margin '{}' must be one number or top,right,bottom,left",
+            spec
+        ),
+    }
+}
+
+/// Build table cell params (shared by table-cell and click-cell)
+fn table_params(
+    table: Option<String>,
+    row: Option<String>,
+    row_index: Option<usize>,
+    column: Option<String>,
+    column_index: Option<usize>,
+    target: Option<String>,
+) -> serde_json::Value {
+    let mut params = serde_json::json!({});
+    for (key, value) in [
+        ("table", table),
+        ("row_contains", row),
+        ("column", column),
+        ("target", target),
+    ] {
+        if let Some(value) = value {
+            params[key] = serde_json::json!(value);
+        }
+    }
+    for (key, value) in [("row_index", row_index), ("column_index", column_index)] {
+        if let Some(value) = value {
+            params[key] = serde_json::json!(value);
+        }
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clip_rect, locale_variant, pdf_margin, with_session};
+    use serde_json::json;
+
+    #[test]
+    fn locale_variant_parses_timezone_and_position() {
+        assert_eq!(
+            locale_variant("de-DE@Europe/Berlin@52.52,13.405").unwrap(),
+            json!({
+                "locale": "de-DE",
+                "timezone": "Europe/Berlin",
+                "geolocation": {"latitude": 52.52, "longitude": 13.405}
+            })
+        );
+        assert_eq!(locale_variant("ja-JP").unwrap(), json!({"locale": "ja-JP"}));
+        assert!(locale_variant("@Asia/Tokyo").is_err());
+        assert!(locale_variant("fr-FR@north,east").is_err());
+    }
+
+    #[test]
+    fn clip_rect_parses_four_numbers() {
+        assert_eq!(
+            clip_rect("640, 120,320.5,180").unwrap(),
+            json!({"x": 640.0, "y": 120.0, "width": 320.5, "height": 180.0})
+        );
+        assert!(clip_rect("640,120,320").is_err());
+        assert!(clip_rect("a,b,c,d").is_err());
+    }
+
+    #[test]
+    fn pdf_margin_is_uniform_or_per_side() {
+        assert_eq!(pdf_margin("0.5").unwrap(), json!(0.5));
+        assert_eq!(
+            pdf_margin("1, 0.5,1,0.5").unwrap(),
+            json!({"top": 1.0, "right": 0.5, "bottom": 1.0, "left": 0.5})
+        );
+        assert!(pdf_margin("1,0.5").is_err());
+        assert!(pdf_margin("wide").is_err());
+    }
+
+    #[test]
+    fn with_session_inserts_into_object() {
+        let params = json!({ "url": "https://example.com" });
+        let updated = with_session(params, Some("sess-1".to_string()));
+        assert_eq!(updated["session_id"], "sess-1");
+        assert_eq!(updated["url"], "https://example.com");
+    }
+
+    #[test]
+    fn with_session_replaces_non_object() {
+        let params = json!("not-an-object");
+        let updated = with_session(params, Some("sess-2".to_string()));
+        assert_eq!(updated, json!({ "session_id": "sess-2" }));
+    }
+
+    #[test]
+    fn with_session_noop_when_missing() {
+        let params = json!({ "url": "https://example.com" });
+        let updated = with_session(params.clone(), None);
+        assert_eq!(updated, params);
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Start {
+            socket,
+            foreground,
+            headed,
+            headless,
+            channel: _,
+            connect,
+            extension_bridge,
+            extension_port,
+            warm,
+            artifact_store,
+            artifact_max_age_hours,
+            artifact_max_mb,
+            artifact_max_per_session,
+            max_response_kb,
+            snapshot_format,
+            per_origin_parallel,
+            per_origin_delay_ms,
+            respect_robots,
+            robots_user_agent,
+            live_view_port,
+            live_view_host,
+            allow_cdp_domains,
+            recover,
+            bounded_memory,
+            maintenance_interval_secs,
+            recycle_after_mins,
+            navigation_timeout_secs,
+            action_timeout_secs,
+            snapshot_timeout_secs,
+        } => cmd_start(StartOptions {
+            socket,
+            foreground,
+            headless_mode: if headed {
+                "headed".to_string()
+            } else {
+                headless
+            },
+            connect,
+            extension_bridge,
+            extension_port,
+            warm,
+            artifact_store,
+            retention: artifacts::RetentionPolicy {
+                max_age: (artifact_max_age_hours > 0)
+                    .then(|| std::time::Duration::from_secs(artifact_max_age_hours * 3600)),
+                max_total_bytes: (artifact_max_mb > 0).then(|| artifact_max_mb * 1024 * 1024),
+                max_per_session: (artifact_max_per_session > 0).then_some(artifact_max_per_session),
+                ..Default::default()
+            },
+            max_response_bytes: max_response_kb * 1024,
+            snapshot_format,
+            origin_throttle: throttle::ThrottleConfig {
+                max_parallel: per_origin_parallel,
+                delay: std::time::Duration::from_millis(per_origin_delay_ms),
+            },
+            robots_user_agent: respect_robots.then_some(robots_user_agent),
+            live_view: live_view_port.map(|port| std::net::SocketAddr::new(live_view_host, port)),
+            allow_cdp_domains,
+            recover,
+            maintenance: bounded_memory.then(|| browser::MaintenancePolicy {
+                interval: std::time::Duration::from_secs(maintenance_interval_secs.max(1)),
+                recycle_after: recycle_after_mins
+                    .map(|mins| std::time::Duration::from_secs(mins * 60)),
+                ..Default::default()
+            }),
+            timeouts: browser::Timeouts {
+                navigation: std::time::Duration::from_secs(navigation_timeout_secs),
+                action: std::time::Duration::from_secs(action_timeout_secs),
+                snapshot: std::time::Duration::from_secs(snapshot_timeout_secs),
+            },
+        }),
+        Commands::Stop { socket } => cmd_stop(socket),
+        Commands::Status { socket } => cmd_status(socket),
+        Commands::Health { socket, self_test } => cmd_health(&socket, self_test, cli.json),
+        Commands::Maintain { socket } => {
+            cmd_call_daemon(&socket, "browser.maintain", serde_json::json!({}), cli.json)
+        }
+        Commands::Schemas { out } => cmd_schemas(out),
+        Commands::Open {
+            url,
+            wait_until,
+            timeout_ms,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"url": url});
+            if let Some(wait_until) = wait_until {
+                base["wait_until"] = serde_json::json!(wait_until);
+            }
+            if let Some(timeout_ms) = timeout_ms {
+                base["timeout_ms"] = serde_json::json!(timeout_ms);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.open", params, cli.json)
+        }
+        Commands::Back {
+            wait_until,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({});
+            if let Some(wait_until) = wait_until {
+                base["wait_until"] = serde_json::json!(wait_until);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.go_back", params, cli.json)
+        }
+        Commands::Forward {
+            wait_until,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({});
+            if let Some(wait_until) = wait_until {
+                base["wait_until"] = serde_json::json!(wait_until);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.go_forward", params, cli.json)
+        }
+        Commands::Reload {
+            ignore_cache,
+            wait_until,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"ignore_cache": ignore_cache});
+            if let Some(wait_until) = wait_until {
+                base["wait_until"] = serde_json::json!(wait_until);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.reload", params, cli.json)
+        }
+        Commands::Snapshot {
+            no_cache,
+            limit,
+            cursor,
+            max_bytes,
+            format,
+            diff,
+            roles,
+            interactive,
+            visible,
+            root,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"cache": !no_cache});
+            if !roles.is_empty() {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("roles".to_string(), serde_json::json!(roles));
+            }
+            if interactive {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("interactive".to_string(), serde_json::json!(true));
+            }
+            if visible {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("visible".to_string(), serde_json::json!(true));
+            }
+            if let Some(root) = root {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("root".to_string(), serde_json::Value::String(root));
+            }
+            if let Some(limit) = limit {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("limit".to_string(), serde_json::json!(limit));
+            }
+            if let Some(cursor) = cursor {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("cursor".to_string(), serde_json::Value::String(cursor));
+            }
+            if let Some(max_bytes) = max_bytes {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("max_bytes".to_string(), serde_json::json!(max_bytes));
+            }
+            // Diffs are of text snapshots
+            if let Some(format) = format.or_else(|| diff.then(|| "text".to_string())) {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("format".to_string(), serde_json::Value::String(format));
+            }
+            if diff {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("diff".to_string(), serde_json::json!(true));
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.snapshot", params, cli.json)
+        }
+        Commands::SnapshotDiff {
+            no_cache,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"cache": !no_cache}), session);
+            cmd_call_daemon(&socket, "browser.snapshot_diff", params, cli.json)
+        }
+        Commands::Click {
+            selector,
+            button,
+            count,
+            modifiers,
+            dry_run,
+            rank,
+            recover,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "button": button,
+                    "click_count": count,
+                    "modifiers": modifiers,
+                    "dry_run": dry_run,
+                    "rank": rank,
+                    "recover": recover.then_some(true)
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.click", params, cli.json)
+        }
+        Commands::Fill {
+            selector,
+            value,
+            append,
+            dry_run,
+            rank,
+            recover,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "value": value,
+                    "append": append,
+                    "dry_run": dry_run,
+                    "rank": rank,
+                    "recover": recover.then_some(true)
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.fill", params, cli.json)
+        }
+        Commands::Focus {
+            selector,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"selector": selector}), session);
+            cmd_call_daemon(&socket, "browser.focus", params, cli.json)
+        }
+        Commands::Blur {
+            selector,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"selector": selector}), session);
+            cmd_call_daemon(&socket, "browser.blur", params, cli.json)
+        }
+        Commands::Clear {
+            selector,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"selector": selector}), session);
+            cmd_call_daemon(&socket, "browser.clear", params, cli.json)
+        }
+        Commands::TypeText {
+            text,
+            selector,
+            delay,
+            jitter,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "text": text,
+                    "delay_ms": delay,
+                    "jitter_ms": jitter
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.type_text", params, cli.json)
+        }
+        Commands::FillRichText {
+            selector,
+            content,
+            format,
+            append,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "content": content,
+                    "format": format,
+                    "append": append,
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.fill_rich_text", params, cli.json)
+        }
+        Commands::SelectText {
+            selector,
+            text,
+            occurrence,
+            start,
+            end,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({});
+            if let Some(selector) = selector {
+                base["selector"] = serde_json::json!(selector);
+            }
+            if let Some(text) = text {
+                base["text"] = serde_json::json!(text);
+                base["occurrence"] = serde_json::json!(occurrence);
+            }
+            if let Some(start) = start {
+                base["start"] = serde_json::json!(start);
+            }
+            if let Some(end) = end {
+                base["end"] = serde_json::json!(end);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.select_text", params, cli.json)
+        }
+        Commands::Selection { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.get_selection", params, cli.json)
+        }
+        Commands::CopySelection { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.copy_selection", params, cli.json)
+        }
+        Commands::Press {
+            key,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"key": key}), session);
+            cmd_call_daemon(&socket, "browser.press", params, cli.json)
+        }
+        Commands::KeyboardLayout {
+            layout,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"layout": layout}), session);
+            cmd_call_daemon(&socket, "browser.keyboard_layout", params, cli.json)
+        }
+        Commands::PopupPolicy {
+            policy,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"policy": policy}), session);
+            cmd_call_daemon(&socket, "browser.popup_policy", params, cli.json)
+        }
+        Commands::Zoom {
+            factor,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"factor": factor}), session);
+            cmd_call_daemon(&socket, "browser.set_zoom", params, cli.json)
+        }
+        Commands::SetTimeouts {
+            navigation_ms,
+            action_ms,
+            snapshot_ms,
+            reset,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({});
+            if let Some(ms) = navigation_ms {
+                base["navigation_ms"] = serde_json::json!(ms);
+            }
+            if let Some(ms) = action_ms {
+                base["action_ms"] = serde_json::json!(ms);
+            }
+            if let Some(ms) = snapshot_ms {
+                base["snapshot_ms"] = serde_json::json!(ms);
+            }
+            if reset {
+                base["reset"] = serde_json::json!(true);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.set_timeouts", params, cli.json)
+        }
+        Commands::Screenshot {
+            path,
+            full_page,
+            selector,
+            padding,
+            marker,
+            clip,
+            clip_viewport,
+            scale,
+            format,
+            quality,
+            after_paint,
+            click,
+            frames,
+            socket,
+            session,
+        } => {
+            let mut base = match path {
+                Some(p) => serde_json::json!({"path": p}),
+                None => serde_json::json!({}),
+            };
+            if full_page {
+                base["full_page"] = serde_json::json!(true);
+            }
+            if let Some(selector) = selector {
+                base["selector"] = serde_json::json!(selector);
+                base["padding"] = serde_json::json!(padding);
+                base["marker"] = serde_json::json!(marker);
+            }
+            if let Some(clip) = clip {
+                base["clip"] = clip_rect(&clip)?;
+                if clip_viewport {
+                    base["clip_origin"] = serde_json::json!("viewport");
+                }
+            }
+            if let Some(scale) = scale {
+                base["scale"] = serde_json::json!(scale);
+            }
+            base["format"] = serde_json::json!(format);
+            if let Some(quality) = quality {
+                base["quality"] = serde_json::json!(quality);
+            }
+            let method = if after_paint || click.is_some() || frames.is_some() {
+                if let Some(click) = click {
+                    base["click"] = serde_json::json!(click);
+                }
+                if let Some(frames) = frames {
+                    base["frames"] = serde_json::json!(frames);
+                }
+                "browser.screenshot_after_paint"
+            } else {
+                "browser.screenshot"
+            };
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, method, params, cli.json)
+        }
+        Commands::ScreenshotElement {
+            selector,
+            path,
+            padding,
+            marker,
+            scale,
+            format,
+            quality,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({
+                "selector": selector,
+                "padding": padding,
+                "marker": marker,
+            });
+            if let Some(path) = path {
+                base["path"] = serde_json::json!(path);
+            }
+            if let Some(scale) = scale {
+                base["scale"] = serde_json::json!(scale);
+            }
+            base["format"] = serde_json::json!(format);
+            if let Some(quality) = quality {
+                base["quality"] = serde_json::json!(quality);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.screenshot_element", params, cli.json)
+        }
+        Commands::CompareScreenshots {
+            baseline,
+            current,
+            artifacts,
+            full_page,
+            threshold,
+            max_mismatch,
+            diff,
+            artifact,
+            socket,
+            session,
+        } => {
+            let (baseline_key, current_key) = if artifacts {
+                ("baseline_artifact", "current_artifact")
+            } else {
+                ("baseline", "current")
+            };
+            let mut base = serde_json::json!({
+                baseline_key: baseline,
+                "full_page": full_page,
+                "artifact": artifact,
+            });
+            if let Some(current) = current {
+                base[current_key] = serde_json::json!(current);
+            }
+            if let Some(threshold) = threshold {
+                base["threshold"] = serde_json::json!(threshold);
+            }
+            if let Some(max_mismatch) = max_mismatch {
+                base["max_mismatch"] = serde_json::json!(max_mismatch);
+            }
+            if let Some(diff) = diff {
+                base["diff_path"] = serde_json::json!(diff);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.compare_screenshots", params, cli.json)
+        }
+        Commands::PrintPreview {
+            paper,
+            landscape,
+            margin,
+            scale,
+            pdf,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({
+                "paper": paper,
+                "landscape": landscape,
+                "margin": margin,
+                "pdf": pdf,
+            });
+            if let Some(scale) = scale {
+                base["scale"] = serde_json::json!(scale);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.print_preview", params, cli.json)
+        }
+        Commands::Pdf {
+            path,
+            paper,
+            landscape,
+            margin,
+            header,
+            footer,
+            no_background,
+            artifact,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({
+                "paper": paper,
+                "landscape": landscape,
+                "margin": pdf_margin(&margin)?,
+                "background": !no_background,
+                "artifact": artifact,
+            });
+            if let Some(path) = path {
+                base["path"] = serde_json::json!(path);
+            }
+            if let Some(header) = header {
+                base["header"] = serde_json::json!(header);
+            }
+            if let Some(footer) = footer {
+                base["footer"] = serde_json::json!(footer);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.pdf", params, cli.json)
+        }
+        Commands::Select {
+            selector,
+            value,
+            dry_run,
+            rank,
+            recover,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "value": value,
+                    "dry_run": dry_run,
+                    "rank": rank,
+                    "recover": recover.then_some(true)
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.select", params, cli.json)
+        }
+        Commands::SelectOption {
+            selector,
+            value,
+            label,
+            index,
+            socket,
+            session,
+        } => {
+            let mut params = serde_json::json!({"selector": selector});
+            if !value.is_empty() {
+                params["value"] = serde_json::json!(value);
+            } else if !label.is_empty() {
+                params["label"] = serde_json::json!(label);
+            } else if !index.is_empty() {
+                params["index"] = serde_json::json!(index);
+            }
+            cmd_call_daemon(
+                &socket,
+                "browser.select_option",
+                with_session(params, session),
+                cli.json,
+            )
+        }
+        Commands::Check {
+            selector,
+            checked,
+            dry_run,
+            rank,
+            recover,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "checked": checked,
+                    "dry_run": dry_run,
+                    "rank": rank,
+                    "recover": recover.then_some(true)
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.check", params, cli.json)
+        }
+        Commands::Hover {
+            selector,
+            dry_run,
+            rank,
+            recover,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "dry_run": dry_run,
+                    "rank": rank,
+                    "recover": recover.then_some(true)
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.hover", params, cli.json)
+        }
+        Commands::SetDate {
+            selector,
+            date,
+            format,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"selector": selector, "date": date});
+            if let Some(format) = format {
+                base["format"] = serde_json::json!(format);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.set_date", params, cli.json)
+        }
+        Commands::Nearest {
+            anchor,
+            text,
+            direction,
+            role,
+            name,
+            limit,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"direction": direction});
+            for (key, value) in [
+                ("anchor", anchor),
+                ("text", text),
+                ("role", role),
+                ("name", name),
+            ] {
+                if let Some(value) = value {
+                    base[key] = serde_json::json!(value);
+                }
+            }
+            if let Some(limit) = limit {
+                base["limit"] = serde_json::json!(limit);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.nearest", params, cli.json)
+        }
+        Commands::Within {
+            x,
+            y,
+            width,
+            height,
+            partial,
+            role,
+            name,
+            limit,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({
+                "x": x,
+                "y": y,
+                "width": width,
+                "height": height,
+                "partial": partial,
+            });
+            for (key, value) in [("role", role), ("name", name)] {
+                if let Some(value) = value {
+                    base[key] = serde_json::json!(value);
+                }
+            }
+            if let Some(limit) = limit {
+                base["limit"] = serde_json::json!(limit);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.within", params, cli.json)
+        }
+        Commands::FindElements {
+            role,
+            name,
+            text,
+            exact,
+            limit,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({ "exact": exact });
+            for (key, value) in [("role", role), ("name", name), ("text", text)] {
+                if let Some(value) = value {
+                    base[key] = serde_json::json!(value);
+                }
+            }
+            if let Some(limit) = limit {
+                base["limit"] = serde_json::json!(limit);
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.find_elements", params, cli.json)
+        }
+        Commands::SummarizeResults {
+            container,
+            limit,
+            socket,
+            session,
+        } => {
+            let mut params = serde_json::json!({});
+            if let Some(container) = container {
+                params["container"] = serde_json::json!(container);
+            }
+            if let Some(limit) = limit {
+                params["limit"] = serde_json::json!(limit);
+            }
+            let params = with_session(params, session);
+            cmd_call_daemon(&socket, "browser.summarize_results", params, cli.json)
+        }
+        Commands::DescribeForm {
+            form,
+            socket,
+            session,
+        } => {
+            let mut params = serde_json::json!({});
+            if let Some(form) = form {
+                params["form"] = serde_json::json!(form);
+            }
+            let params = with_session(params, session);
+            cmd_call_daemon(&socket, "browser.describe_form", params, cli.json)
+        }
+        Commands::AutofillForm {
+            data,
+            form,
+            socket,
+            session,
+        } => {
+            let spec = if Path::new(&data).is_file() {
+                std::fs::read_to_string(&data)
+                    .with_context(|| format!("Failed to read {}", data))?
+            } else {
+                data
+            };
+            let data: serde_json::Value =
+                serde_json::from_str(&spec).context("Data must be a JSON object")?;
+            let mut params = serde_json::json!({ "data": data });
+            if let Some(form) = form {
+                params["form"] = serde_json::json!(form);
+            }
+            let params = with_session(params, session);
+            cmd_call_daemon(&socket, "browser.autofill_form", params, cli.json)
+        }
+        Commands::ValidationErrors {
+            form,
+            socket,
+            session,
+        } => {
+            let mut params = serde_json::json!({});
+            if let Some(form) = form {
+                params["form"] = serde_json::json!(form);
+            }
+            let params = with_session(params, session);
+            cmd_call_daemon(&socket, "browser.get_validation_errors", params, cli.json)
+        }
+        Commands::TableCell {
+            table,
+            row,
+            row_index,
+            column,
+            column_index,
+            target,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                table_params(table, row, row_index, column, column_index, target),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.table_cell", params, cli.json)
+        }
+        Commands::ClickCell {
+            table,
+            row,
+            row_index,
+            column,
+            column_index,
+            target,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                table_params(table, row, row_index, column, column_index, target),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.click_cell", params, cli.json)
+        }
+        Commands::Highlight {
+            selector,
+            label,
+            duration_ms,
+            color,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"selector": selector, "duration_ms": duration_ms});
+            if let Some(label) = label {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("label".to_string(), serde_json::Value::String(label));
+            }
+            if let Some(color) = color {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("color".to_string(), serde_json::Value::String(color));
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.highlight", params, cli.json)
+        }
+        Commands::CursorOverlay {
+            off,
+            socket,
+            session,
+        } => {
+            let params = with_session(serde_json::json!({"enabled": !off}), session);
+            cmd_call_daemon(&socket, "browser.cursor_overlay", params, cli.json)
+        }
+        Commands::Scroll {
+            selector,
+            x,
+            y,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({"x": x, "y": y});
+            if let Some(sel) = selector {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("selector".to_string(), serde_json::Value::String(sel));
+            }
+            let params = with_session(base, session);
+            cmd_call_daemon(&socket, "browser.scroll", params, cli.json)
+        }
+        Commands::ScrollToBottom {
+            container,
+            max_rounds,
+            settle_ms,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "container": container,
+                    "max_rounds": max_rounds,
+                    "settle_ms": settle_ms
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.scroll_to_bottom", params, cli.json)
+        }
+        Commands::RestoreScroll { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.restore_scroll", params, cli.json)
+        }
+        Commands::WaitStable {
+            quiet_ms,
+            timeout_ms,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"quiet_ms": quiet_ms, "timeout_ms": timeout_ms}),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.wait_for_stable", params, cli.json)
+        }
+        Commands::PressCombo {
+            key,
+            modifiers,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"key": key, "modifiers": modifiers}),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.press_combo", params, cli.json)
+        }
+        Commands::Drag {
+            source,
+            target,
+            steps,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"source": source, "target": target, "steps": steps}),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.drag_and_drop", params, cli.json)
+        }
+        Commands::Upload {
+            selector,
+            path,
+            dry_run,
+            rank,
+            recover,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({
+                    "selector": selector,
+                    "path": path,
+                    "dry_run": dry_run,
+                    "rank": rank,
+                    "recover": recover.then_some(true)
+                }),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.upload", params, cli.json)
+        }
+        Commands::UploadFiles {
+            selector,
+            paths,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"selector": selector, "paths": paths}),
+                session,
+            );
+            cmd_call_daemon(&socket, "browser.upload_file", params, cli.json)
+        }
+        Commands::AssertText {
+            expected,
+            selector,
+            exact,
+            no_screenshot,
+            socket,
+            session,
+        } => {
+            let mut base = serde_json::json!({
+                "expected": expected,
+                "exact": exact,
+                "screenshot": !no_screenshot
+            });
+            if let Some(selector) = selector {
+                base.as_object_mut()
+                    .unwrap()
+                    .insert("selector".to_string(), serde_json::Value::String(selector));
+            }
+            let params = with_session(base, session);
+            cmd_assert(&socket, "browser.assert_text", params, cli.json)
+        }
+        Commands::AssertVisible {
+            selector,
+            no_screenshot,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"selector": selector, "screenshot": !no_screenshot}),
+                session,
+            );
+            cmd_assert(&socket, "browser.assert_visible", params, cli.json)
+        }
+        Commands::AssertUrl {
+            pattern,
+            no_screenshot,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"pattern": pattern, "screenshot": !no_screenshot}),
+                session,
+            );
+            cmd_assert(&socket, "browser.assert_url", params, cli.json)
+        }
+        Commands::AssertCount {
+            selector,
+            count,
+            no_screenshot,
+            socket,
+            session,
+        } => {
+            let params = with_session(
+                serde_json::json!({"selector": selector, "count": count, "screenshot": !no_screenshot}),
+                session,
+            );
+            cmd_assert(&socket, "browser.assert_count", params, cli.json)
+        }
+        Commands::Verify {
+            assertions,
+            no_screenshot,
+            socket,
+            session,
+        } => {
+            let spec = if Path::new(&assertions).is_file() {
+                std::fs::read_to_string(&assertions)
+                    .with_context(|| format!("Failed to read {}", assertions))?
+            } else {
+                assertions
+            };
+            let assertions: serde_json::Value =
+                serde_json::from_str(&spec).context("Assertions must be a JSON array")?;
+            let params = with_session(
+                serde_json::json!({"assertions": assertions, "screenshot": !no_screenshot}),
+                session,
+            );
+            cmd_verify(&socket, params, cli.json)
+        }
+        Commands::Crawl {
+            url,
+            max_pages,
+            depth,
+            any_origin,
+            sitemap,
+            concurrency,
+            extract,
+            socket,
+        } => {
+            let mut fields = serde_json::Map::new();
+            for spec in extract {
+                let (name, selector) = spec
+                    .split_once('=')
+                    .with_context(|| format!("--extract expects name=selector, got '{}'", spec))?;
+                fields.insert(name.to_string(), serde_json::json!(selector));
+            }
+            let params = serde_json::json!({
+                "url": url,
+                "max_pages": max_pages,
+                "depth": depth,
+                "same_origin": !any_origin,
+                "sitemap": sitemap,
+                "concurrency": concurrency,
+                "extract": fields,
+            });
+            cmd_crawl(&socket, params)
+        }
+        Commands::State { action } => match action {
+            StateAction::Save {
+                name,
+                socket,
+                session,
+            } => {
+                let params = with_session(serde_json::json!({"name": name}), session);
+                cmd_call_daemon(&socket, "browser.state.save", params, cli.json)
+            }
+            StateAction::Load {
+                name,
+                socket,
+                session,
+            } => {
+                let params = with_session(serde_json::json!({"name": name}), session);
+                cmd_call_daemon(&socket, "browser.state.load", params, cli.json)
+            }
+            StateAction::List { socket } => cmd_call_daemon(
+                &socket,
+                "browser.state.list",
+                serde_json::json!({}),
+                cli.json,
+            ),
+            StateAction::Diff { a, b, socket } => cmd_call_daemon(
+                &socket,
+                "browser.state.diff",
+                serde_json::json!({"a": a, "b": b}),
+                cli.json,
+            ),
+        },
+        Commands::LiveView { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.live_view", params, cli.json)
+        }
+        Commands::Watch {
+            dir,
+            count,
+            quality,
+            socket,
+            session,
+        } => {
+            let mut params = serde_json::json!({});
+            if let Some(quality) = quality {
+                params["quality"] = serde_json::json!(quality);
+            }
+            cmd_watch(&socket, with_session(params, session), dir, count)
+        }
+        Commands::Canvas { action } => match action {
+            CanvasAction::Draw {
+                selector,
+                strokes,
+                normalized,
+                step_ms,
+                pen,
+                socket,
+                session,
+            } => {
+                let strokes: serde_json::Value =
+                    serde_json::from_str(&strokes).context("--strokes must be a JSON array")?;
+                let mut base = serde_json::json!({
+                    "selector": selector,
+                    "strokes": strokes,
+                    "normalized": normalized,
+                    "pointer": if pen { "pen" } else { "mouse" },
+                });
+                if let Some(step_ms) = step_ms {
+                    base["step_ms"] = serde_json::json!(step_ms);
+                }
+                let params = with_session(base, session);
+                cmd_call_daemon(&socket, "browser.canvas.draw", params, cli.json)
+            }
+            CanvasAction::Capture {
+                selector,
+                path,
+                artifact,
+                socket,
+                session,
+            } => {
+                let mut base = serde_json::json!({"selector": selector, "artifact": artifact});
+                if let Some(path) = path {
+                    base["path"] = serde_json::json!(path);
+                }
+                let params = with_session(base, session);
+                cmd_call_daemon(&socket, "browser.canvas.capture", params, cli.json)
+            }
+        },
+        Commands::Media { action } => match action {
+            MediaAction::State {
+                selector,
+                wait_ms,
+                socket,
+                session,
+            } => {
+                let mut base = serde_json::json!({});
+                if let Some(selector) = selector {
+                    base["selector"] = serde_json::json!(selector);
+                }
+                if let Some(wait_ms) = wait_ms {
+                    base["wait_ms"] = serde_json::json!(wait_ms);
+                }
+                let params = with_session(base, session);
+                cmd_call_daemon(&socket, "browser.media.state", params, cli.json)
+            }
+            MediaAction::Play {
+                selector,
+                socket,
+                session,
+            } => {
+                let params = with_session(serde_json::json!({"selector": selector}), session);
+                cmd_call_daemon(&socket, "browser.media.play", params, cli.json)
+            }
+            MediaAction::Pause {
+                selector,
+                socket,
+                session,
+            } => {
+                let params = with_session(serde_json::json!({"selector": selector}), session);
+                cmd_call_daemon(&socket, "browser.media.pause", params, cli.json)
+            }
+            MediaAction::Seek {
+                time,
+                selector,
+                socket,
+                session,
+            } => {
+                let params = with_session(
+                    serde_json::json!({"selector": selector, "time": time}),
+                    session,
+                );
+                cmd_call_daemon(&socket, "browser.media.seek", params, cli.json)
+            }
+            MediaAction::Mute {
+                off,
+                socket,
+                session,
+            } => {
+                let params = with_session(serde_json::json!({"muted": !off}), session);
+                cmd_call_daemon(&socket, "browser.media.mute", params, cli.json)
+            }
+        },
+        Commands::Notifications {
+            clear,
             socket,
             session,
         } => {
-            let params = with_session(serde_json::json!({"selector": selector}), session);
-            cmd_call_daemon(&socket, "browser.click", params, cli.json)
+            let params = with_session(serde_json::json!({"clear": clear}), session);
+            cmd_call_daemon(&socket, "browser.get_notifications", params, cli.json)
         }
-        Commands::Fill {
-            selector,
-            value,
+        Commands::CspTrial {
+            policy,
+            url,
+            enforce,
+            wait_ms,
             socket,
             session,
         } => {
+            let mode = if enforce { "enforce" } else { "report_only" };
             let params = with_session(
-                serde_json::json!({"selector": selector, "value": value}),
+                serde_json::json!({
+                    "policy": policy,
+                    "url": url,
+                    "mode": mode,
+                    "wait_ms": wait_ms
+                }),
                 session,
             );
-            cmd_call_daemon(&socket, "browser.fill", params, cli.json)
+            cmd_call_daemon(&socket, "browser.csp_trial", params, cli.json)
         }
-        Commands::Press {
-            key,
+        Commands::LocaleVariants {
+            url,
+            variants,
+            wait_ms,
             socket,
-            session,
         } => {
-            let params = with_session(serde_json::json!({"key": key}), session);
-            cmd_call_daemon(&socket, "browser.press", params, cli.json)
+            let variants = variants
+                .iter()
+                .map(|spec| locale_variant(spec))
+                .collect::<Result<Vec<_>>>()?;
+            let params = serde_json::json!({
+                "url": url,
+                "variants": variants,
+                "wait_ms": wait_ms
+            });
+            cmd_call_daemon(&socket, "browser.locale_variants", params, cli.json)
         }
-        Commands::Screenshot {
-            path,
-            socket,
-            session,
-        } => {
-            let base = match path {
-                Some(p) => serde_json::json!({"path": p}),
-                None => serde_json::json!({}),
-            };
-            let params = with_session(base, session);
-            cmd_call_daemon(&socket, "browser.screenshot", params, cli.json)
+        Commands::Environment { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.get_environment", params, cli.json)
         }
-        Commands::Select {
-            selector,
-            value,
+        Commands::Cdp {
+            method,
+            params,
             socket,
             session,
         } => {
-            let params = with_session(
-                serde_json::json!({"selector": selector, "value": value}),
-                session,
-            );
-            cmd_call_daemon(&socket, "browser.select", params, cli.json)
+            let mut body = serde_json::json!({ "method": method });
+            if let Some(params) = params {
+                let params: serde_json::Value =
+                    serde_json::from_str(&params).context("--params must be a JSON object")?;
+                body["params"] = params;
+            }
+            cmd_call_daemon(
+                &socket,
+                "browser.cdp",
+                with_session(body, session),
+                cli.json,
+            )
         }
-        Commands::Check {
-            selector,
-            checked,
+        Commands::ThirdParty { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.get_third_party_report", params, cli.json)
+        }
+        Commands::DetectBlockers { socket, session } => {
+            let params = with_session(serde_json::json!({}), session);
+            cmd_call_daemon(&socket, "browser.detect_blockers", params, cli.json)
+        }
+        Commands::CheckLinks {
+            concurrency,
+            max_links,
+            timeout_ms,
+            no_cache,
             socket,
             session,
         } => {
             let params = with_session(
-                serde_json::json!({"selector": selector, "checked": checked}),
+                serde_json::json!({
+                    "concurrency": concurrency,
+                    "max_links": max_links,
+                    "timeout_ms": timeout_ms,
+                    "cache": !no_cache,
+                }),
                 session,
             );
-            cmd_call_daemon(&socket, "browser.check", params, cli.json)
+            cmd_call_daemon(&socket, "browser.check_links", params, cli.json)
         }
-        Commands::Hover {
-            selector,
+        Commands::ExportRefs {
+            refs,
             socket,
             session,
         } => {
-            let params = with_session(serde_json::json!({"selector": selector}), session);
-            cmd_call_daemon(&socket, "browser.hover", params, cli.json)
+            let params = if refs.is_empty() {
+                serde_json::json!({})
+            } else {
+                serde_json::json!({"refs": refs})
+            };
+            cmd_call_daemon(
+                &socket,
+                "browser.export_refs",
+                with_session(params, session),
+                cli.json,
+            )
         }
-        Commands::Scroll {
-            selector,
-            x,
-            y,
-            socket,
-            session,
-        } => {
-            let mut base = serde_json::json!({"x": x, "y": y});
-            if let Some(sel) = selector {
-                base.as_object_mut()
-                    .unwrap()
-                    .insert("selector".to_string(), serde_json::Value::String(sel));
+        Commands::Clock { action } => match action {
+            ClockAction::Set {
+                start_time,
+                frozen,
+                socket,
+                session,
+            } => {
+                let params = with_session(
+                    serde_json::json!({
+                        "start_time": start_time,
+                        "tick_mode": if frozen { "frozen" } else { "realtime" }
+                    }),
+                    session,
+                );
+                cmd_call_daemon(&socket, "browser.set_fake_clock", params, cli.json)
             }
-            let params = with_session(base, session);
-            cmd_call_daemon(&socket, "browser.scroll", params, cli.json)
-        }
-        Commands::PressCombo {
-            key,
-            modifiers,
-            socket,
-            session,
-        } => {
-            let params = with_session(
-                serde_json::json!({"key": key, "modifiers": modifiers}),
+            ClockAction::Forward {
+                ms,
+                max_timers,
+                socket,
                 session,
-            );
-            cmd_call_daemon(&socket, "browser.press_combo", params, cli.json)
-        }
-        Commands::Upload {
-            selector,
-            path,
-            socket,
-            session,
-        } => {
-            let params = with_session(
-                serde_json::json!({"selector": selector, "path": path}),
+            } => {
+                let params = with_session(
+                    serde_json::json!({"ms": ms, "max_timers": max_timers}),
+                    session,
+                );
+                cmd_call_daemon(&socket, "browser.fast_forward", params, cli.json)
+            }
+            ClockAction::Clear { socket, session } => {
+                let params = with_session(serde_json::json!({}), session);
+                cmd_call_daemon(&socket, "browser.clear_fake_clock", params, cli.json)
+            }
+        },
+        Commands::Timeline { action } => match action {
+            TimelineAction::Auto {
+                off,
+                socket,
                 session,
-            );
-            cmd_call_daemon(&socket, "browser.upload", params, cli.json)
-        }
-        Commands::State { action } => match action {
-            StateAction::Save {
-                name,
+            } => {
+                let params = with_session(serde_json::json!({"enabled": !off}), session);
+                cmd_call_daemon(&socket, "browser.timeline.auto", params, cli.json)
+            }
+            TimelineAction::Capture {
+                label,
                 socket,
                 session,
             } => {
-                let params = with_session(serde_json::json!({"name": name}), session);
-                cmd_call_daemon(&socket, "browser.state.save", params, cli.json)
+                let params = with_session(serde_json::json!({"label": label}), session);
+                cmd_call_daemon(&socket, "browser.timeline.capture", params, cli.json)
             }
-            StateAction::Load {
-                name,
+            TimelineAction::Index {
+                since,
                 socket,
                 session,
             } => {
-                let params = with_session(serde_json::json!({"name": name}), session);
-                cmd_call_daemon(&socket, "browser.state.load", params, cli.json)
+                let params = with_session(serde_json::json!({"since": since}), session);
+                cmd_call_daemon(&socket, "browser.timeline.index", params, cli.json)
+            }
+            TimelineAction::Clear { socket, session } => {
+                let params = with_session(serde_json::json!({}), session);
+                cmd_call_daemon(&socket, "browser.timeline.clear", params, cli.json)
+            }
+        },
+        Commands::Record { action } => match action {
+            RecordAction::Start {
+                path,
+                format,
+                fps,
+                socket,
+                session,
+            } => {
+                let mut params = serde_json::json!({"format": format, "fps": fps});
+                if let Some(path) = path {
+                    params["path"] = serde_json::json!(path);
+                }
+                let params = with_session(params, session);
+                cmd_call_daemon(&socket, "browser.recording.start", params, cli.json)
+            }
+            RecordAction::Stop {
+                artifact,
+                socket,
+                session,
+            } => {
+                let params = with_session(serde_json::json!({"artifact": artifact}), session);
+                cmd_call_daemon(&socket, "browser.recording.stop", params, cli.json)
             }
-            StateAction::List { socket } => cmd_call_daemon(
-                &socket,
-                "browser.state.list",
-                serde_json::json!({}),
-                cli.json,
-            ),
         },
         Commands::Session { action } => match action {
             SessionAction::New { id, socket } => cmd_call_daemon(
@@ -578,6 +3446,42 @@ fn main() -> Result<()> {
                 serde_json::json!({"id": id}),
                 cli.json,
             ),
+            SessionAction::Observe { id, replay, socket } => cmd_call_daemon(
+                &socket,
+                "browser.session.observe",
+                serde_json::json!({"id": id, "replay": replay}),
+                cli.json,
+            ),
+            SessionAction::Events {
+                observer: Some(observer),
+                since,
+                socket,
+                ..
+            } => cmd_call_daemon(
+                &socket,
+                "browser.observer.events",
+                serde_json::json!({"observer_id": observer, "since": since}),
+                cli.json,
+            ),
+            SessionAction::Events {
+                observer: None,
+                id,
+                since,
+                types,
+                socket,
+            } => {
+                let mut params = with_session(serde_json::json!({"since": since}), id);
+                if !types.is_empty() {
+                    params["types"] = serde_json::json!(types);
+                }
+                cmd_call_daemon(&socket, "browser.session.events", params, cli.json)
+            }
+            SessionAction::Unobserve { observer, socket } => cmd_call_daemon(
+                &socket,
+                "browser.observer.close",
+                serde_json::json!({"observer_id": observer}),
+                cli.json,
+            ),
         },
         Commands::Extension { action } => match action {
             ExtensionAction::Group {
@@ -671,16 +3575,94 @@ fn main() -> Result<()> {
     }
 }
 
-fn cmd_start(
+/// `start` flags, resolved into the settings the daemon runs with.
+struct StartOptions {
     socket: String,
     foreground: bool,
-    headless: bool,
+    headless_mode: String,
     connect: Option<String>,
     extension_bridge: bool,
     extension_port: u16,
-) -> Result<()> {
+    warm: bool,
+    artifact_store: String,
+    retention: artifacts::RetentionPolicy,
+    max_response_bytes: usize,
+    snapshot_format: String,
+    origin_throttle: throttle::ThrottleConfig,
+    robots_user_agent: Option<String>,
+    live_view: Option<std::net::SocketAddr>,
+    allow_cdp_domains: Vec<String>,
+    recover: bool,
+    maintenance: Option<browser::MaintenancePolicy>,
+    timeouts: browser::Timeouts,
+}
+
+fn cmd_start(options: StartOptions) -> Result<()> {
+    let StartOptions {
+        socket,
+        foreground,
+        headless_mode,
+        connect,
+        extension_bridge,
+        extension_port,
+        warm,
+        artifact_store,
+        retention,
+        max_response_bytes,
+        snapshot_format,
+        origin_throttle,
+        robots_user_agent,
+        live_view,
+        allow_cdp_domains,
+        recover,
+        maintenance,
+        timeouts,
+    } = options;
     let socket_path = shellexpand::tilde(&socket).to_string();
 
+    if [timeouts.navigation, timeouts.action, timeouts.snapshot]
+        .iter()
+        .any(|limit| limit.is_zero() || *limit > browser::MAX_TIMEOUT)
+    {
+        anyhow::bail!(
+            "Timeouts must be between 1 and {}s",
+            browser::MAX_TIMEOUT.as_secs()
+        );
+    }
+
+    // Resolve the artifact backend up front so bad config fails before daemonizing
+    let s3_config = match artifact_store.as_str() {
+        "local" => None,
+        "s3" => Some(artifacts::S3Config::from_env()?),
+        other => anyhow::bail!("Unknown artifact store '{}' (expected local or s3)", other),
+    };
+    // Only the local store applies retention; lifecycle rules do that for a bucket
+    if s3_config.is_some() && retention.is_enabled() {
+        anyhow::bail!(
+            "--artifact-max-age-hours, --artifact-max-mb and --artifact-max-per-session \
+             only apply to the local artifact store; use bucket lifecycle rules with s3"
+        );
+    }
+    let snapshot_format = match snapshot_format.as_str() {
+        "json" => models::SnapshotFormat::Json,
+        "text" => models::SnapshotFormat::Text,
+        other => anyhow::bail!(
+            "Unknown snapshot format '{}' (expected json or text)",
+            other
+        ),
+    };
+    let headless_mode = match headless_mode.as_str() {
+        "old" => models::HeadlessMode::Old,
+        "new" => models::HeadlessMode::New,
+        "headed" => models::HeadlessMode::Headed,
+        other => anyhow::bail!("Unknown headless mode '{}' (expected old or new)", other),
+    };
+    let cdp_domains = if allow_cdp_domains.is_empty() {
+        None
+    } else {
+        Some(browser::CdpDomains::new(&allow_cdp_domains)?)
+    };
+
     // Create parent directory
     if let Some(parent) = Path::new(&socket_path).parent() {
         std::fs::create_dir_all(parent).context("Failed to create socket directory")?;
@@ -694,13 +3676,79 @@ fn cmd_start(
     if let Some(ref url) = connect {
         println!("Mode: CONNECT (attaching to user's Chrome at {})", url);
     } else {
-        println!("Mode: {}", if headless { "headless" } else { "headed" });
+        println!(
+            "Mode: {}",
+            match headless_mode {
+                models::HeadlessMode::Old => "headless (old)",
+                models::HeadlessMode::New => "headless (new)",
+                models::HeadlessMode::Headed => "headed",
+            }
+        );
     }
 
+    println!(
+        "Browser: {}",
+        if warm {
+            "pre-warmed at startup"
+        } else {
+            "launched on first request"
+        }
+    );
+
     if extension_bridge {
         println!("Extension bridge: ws://127.0.0.1:{}", extension_port);
     }
 
+    if let Some(ref config) = s3_config {
+        println!("Artifacts: s3://{}/{}", config.bucket, config.prefix);
+    }
+
+    if let Some(ref agent) = robots_user_agent {
+        println!("robots.txt: enforced for user-agent '{}'", agent);
+    }
+
+    if let Some(addr) = live_view {
+        println!("Live view: http://{}/", addr);
+    }
+
+    if let Some(ref domains) = cdp_domains {
+        println!("Raw CDP: allowed for {}", domains);
+    }
+
+    if recover {
+        println!("Recovery: element actions reload and retry");
+    }
+
+    if let Some(ref policy) = maintenance {
+        println!(
+            "Bounded memory: maintenance every {}s{}",
+            policy.interval.as_secs(),
+            policy.recycle_after.map_or(String::new(), |after| format!(
+                ", pages recycled after {} min",
+                after.as_secs() / 60
+            ))
+        );
+    }
+
+    if timeouts != browser::Timeouts::default() {
+        println!(
+            "Timeouts: navigation {}s, action {}s, snapshot {}s",
+            timeouts.navigation.as_secs(),
+            timeouts.action.as_secs(),
+            timeouts.snapshot.as_secs()
+        );
+    }
+
+    if origin_throttle.is_enabled() {
+        println!(
+            "Per-origin throttle: {} parallel, {}ms between navigations",
+            origin_throttle
+                .max_parallel
+                .map_or("unlimited".to_string(), |n| n.to_string()),
+            origin_throttle.delay.as_millis()
+        );
+    }
+
     // Create extension bridge if enabled (shared across threads)
     let bridge: Option<std::sync::Arc<extension_bridge::ExtensionBridge>> = if extension_bridge {
         Some(std::sync::Arc::new(extension_bridge::ExtensionBridge::new(
@@ -715,9 +3763,44 @@ fn cmd_start(
                           bridge: Option<std::sync::Arc<extension_bridge::ExtensionBridge>>|
      -> Result<BrowserService> {
         let service = if let Some(url) = connect_url {
-            BrowserService::new_connect(url)?
+            BrowserService::new_connect(url, warm)?
+        } else {
+            BrowserService::new(headless_mode, warm)?
+        };
+
+        let service = if let Some(config) = s3_config.clone() {
+            service.with_artifact_store(std::sync::Arc::new(artifacts::S3ArtifactStore::new(
+                config,
+            )?))
         } else {
-            BrowserService::new(headless)?
+            service
+        };
+        let service = service
+            .with_retention(retention.clone())
+            .with_max_response_bytes(max_response_bytes)
+            .with_snapshot_format(snapshot_format)
+            .with_origin_throttle(origin_throttle.clone());
+
+        let service = match robots_user_agent.as_deref() {
+            Some(agent) => {
+                service.with_robots(std::sync::Arc::new(robots::RobotsPolicy::new(agent)?))
+            }
+            None => service,
+        };
+
+        let service = match live_view {
+            Some(addr) => service.with_live_view(addr, browser::ScreencastOptions::default())?,
+            None => service,
+        };
+
+        let service = match cdp_domains.clone() {
+            Some(domains) => service.with_cdp_passthrough(domains),
+            None => service,
+        };
+        let service = service.with_recovery(recover).with_timeouts(timeouts);
+        let service = match maintenance.clone() {
+            Some(policy) => service.with_maintenance(policy),
+            None => service,
         };
 
         Ok(if let Some(b) = bridge {
@@ -877,6 +3960,184 @@ fn cmd_status(socket: String) -> Result<()> {
     Ok(())
 }
 
+fn cmd_schemas(out: Option<String>) -> Result<()> {
+    let schemas = serde_json::to_string_pretty(&schemas::export()?)?;
+    match out {
+        Some(out) => {
+            std::fs::write(&out, schemas + "\n")
+                .with_context(|| format!("Failed to write {}", out))?;
+            eprintln!(
+                "Wrote schemas for protocol version {} to {}",
+                schemas::PROTOCOL_VERSION,
+                out
+            );
+        }
+        None => println!("{}", schemas),
+    }
+    Ok(())
+}
+
+fn cmd_health(socket: &str, self_test: bool, json_output: bool) -> Result<()> {
+    let response = call_daemon_raw(
+        socket,
+        "health",
+        serde_json::json!({ "self_test": self_test }),
+    )?;
+    let result = response.get("result").cloned().unwrap_or_default();
+
+    if json_output {
+        println!("{}", response);
+    } else {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+
+    if result.get("healthy").and_then(|v| v.as_bool()) != Some(true) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run an assertion method, exiting non-zero if it failed.
+fn cmd_assert(
+    socket: &str,
+    method: &str,
+    params: serde_json::Value,
+    json_output: bool,
+) -> Result<()> {
+    let response = call_daemon_raw(socket, method, params)?;
+    let result = response.get("result").cloned().unwrap_or_default();
+
+    if json_output {
+        println!("{}", response);
+    } else {
+        let passed = result.get("passed").and_then(|v| v.as_bool()) == Some(true);
+        let message = result.get("message").and_then(|v| v.as_str()).unwrap_or("");
+        println!("{} {}", if passed { "PASS" } else { "FAIL" }, message);
+        if !passed {
+            println!("{}", serde_json::to_string_pretty(&result["evidence"])?);
+        }
+    }
+
+    if result.get("passed").and_then(|v| v.as_bool()) != Some(true) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run `browser.verify`, printing one line per assertion; exits non-zero if any failed.
+fn cmd_verify(socket: &str, params: serde_json::Value, json_output: bool) -> Result<()> {
+    let response = call_daemon_raw(socket, "browser.verify", params)?;
+    let report = response.get("result").cloned().unwrap_or_default();
+    let passed = report.get("passed").and_then(|v| v.as_bool()) == Some(true);
+
+    if json_output {
+        println!("{}", response);
+    } else {
+        for result in report["results"].as_array().into_iter().flatten() {
+            let ok = result.get("passed").and_then(|v| v.as_bool()) == Some(true);
+            let message = result.get("message").and_then(|v| v.as_str()).unwrap_or("");
+            println!("{} {}", if ok { "PASS" } else { "FAIL" }, message);
+        }
+        println!(
+            "{}/{} passed",
+            report["total"].as_u64().unwrap_or(0) - report["failed"].as_u64().unwrap_or(0),
+            report["total"].as_u64().unwrap_or(0)
+        );
+        if let Some(url) = report.pointer("/screenshot/url").and_then(|v| v.as_str()) {
+            println!("Screenshot: {}", url);
+        }
+    }
+
+    if !passed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Start a crawl and stream its pages as NDJSON until it finishes.
+fn cmd_crawl(socket: &str, params: serde_json::Value) -> Result<()> {
+    let started = call_daemon_raw(socket, "browser.crawl", params)?;
+    let crawl_id = started["result"]["crawl_id"]
+        .as_str()
+        .context("Daemon did not return a crawl_id")?
+        .to_string();
+
+    let mut since = 0;
+    loop {
+        let response = call_daemon_raw(
+            socket,
+            "browser.crawl.status",
+            serde_json::json!({"crawl_id": crawl_id, "since": since}),
+        )?;
+        let status = &response["result"];
+
+        for page in status["pages"].as_array().into_iter().flatten() {
+            println!("{}", page);
+        }
+        since = status["next"].as_u64().unwrap_or(since);
+
+        match status["state"].as_str() {
+            Some("running") => std::thread::sleep(std::time::Duration::from_millis(500)),
+            Some("failed") => anyhow::bail!(
+                "Crawl failed: {}",
+                status["error"].as_str().unwrap_or("unknown error")
+            ),
+            _ => {
+                eprintln!("Crawled {} page(s)", since);
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Poll a session's frame stream, printing each frame as a JSON line or
+/// writing it to `dir`
+fn cmd_watch(
+    socket: &str,
+    params: serde_json::Value,
+    dir: Option<String>,
+    count: Option<u64>,
+) -> Result<()> {
+    if let Some(dir) = &dir {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir))?;
+    }
+    call_daemon_raw(socket, "browser.screencast.start", params.clone())?;
+
+    let mut since = 0;
+    let mut seen = 0;
+    while count.map_or(true, |count| seen < count) {
+        let mut poll = params.clone();
+        poll["since"] = serde_json::json!(since);
+        poll["wait_ms"] = serde_json::json!(5000);
+        let response = call_daemon_raw(socket, "browser.screencast.frames", poll)?;
+        let result = &response["result"];
+
+        for mut frame in result["frames"].as_array().cloned().into_iter().flatten() {
+            if let Some(dir) = &dir {
+                let jpeg = base64::Engine::decode(
+                    &base64::engine::general_purpose::STANDARD,
+                    frame["data"].as_str().unwrap_or_default(),
+                )
+                .context("Daemon sent a bad frame")?;
+                let path = Path::new(dir).join(format!("{}.jpg", frame["seq"]));
+                std::fs::write(&path, jpeg)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                frame["data"] = serde_json::json!(path.display().to_string());
+            }
+            println!("{}", frame);
+            seen += 1;
+        }
+        since = result["next"].as_u64().unwrap_or(since);
+        if result["streaming"].as_bool() == Some(false) {
+            eprintln!("Stream stopped after {} frame(s)", seen);
+            return Ok(());
+        }
+    }
+
+    call_daemon_raw(socket, "browser.screencast.stop", params)?;
+    Ok(())
+}
+
 /// Call daemon and return parsed JSON response
 fn call_daemon_raw(
     socket: &str,
@@ -1,14 +1,18 @@
 //! Data models for browser automation.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use chromiumoxide::cdp::browser_protocol::network::CookieSameSite;
 
+use crate::browser::KeyboardLayout;
+
 /// ARIA tree node with @eN reference ID.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AriaNode {
-    /// Element reference ID (e.g., "@e1", "@e2")
+    /// Element reference ID (e.g., "@e1", "@e2"; "@f1e9" inside the first
+    /// child frame)
     pub ref_id: String,
     /// ARIA role (e.g., "button", "textbox", "link")
     pub role: String,
@@ -23,128 +27,2844 @@ pub struct AriaNode {
     pub focusable: bool,
     /// Whether the element is focused
     #[serde(default)]
-    pub focused: bool,
-    /// Child nodes
+    pub focused: bool,
+    /// Bounding box in viewport CSS pixels, when the element is rendered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rect: Option<ElementRect>,
+    /// Whether any of the element is inside the viewport
+    #[serde(default)]
+    pub in_viewport: bool,
+    /// Child nodes
+    #[serde(default)]
+    pub children: Vec<AriaNode>,
+}
+
+/// ARIA tree snapshot response.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AriaSnapshot {
+    /// Page URL
+    pub url: String,
+    /// Page title
+    pub title: String,
+    /// Root ARIA nodes
+    pub nodes: Vec<AriaNode>,
+    /// Total element count
+    pub element_count: usize,
+    /// Whether this snapshot was served from the per-session cache
+    #[serde(default)]
+    pub cached: bool,
+    /// Index of the first node in `nodes` when the snapshot is paginated
+    #[serde(default)]
+    pub offset: usize,
+    /// Cursor for the next page (None on the last or only page)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Whether the response size limit cut this page short (remaining nodes
+    /// follow via `next_cursor`) or shortened an oversized node's text
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Scroll position when the snapshot was taken
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<ScrollState>,
+    /// Ref of the focused element (None when focus is on the page itself or
+    /// on an element the tree leaves out)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focused: Option<String>,
+}
+
+/// Which nodes a snapshot returns; the default keeps them all.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotFilter {
+    /// Only these roles (any role when empty)
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Only widgets a user acts on, and other focusable nodes
+    #[serde(default)]
+    pub interactive: bool,
+    /// Only rendered nodes (with a size, not hidden or transparent)
+    #[serde(default)]
+    pub visible: bool,
+    /// Only the element with this ref and its descendants
+    #[serde(default)]
+    pub root: Option<String>,
+}
+
+impl SnapshotFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How `browser.snapshot` renders the tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotFormat {
+    /// `nodes` as JSON
+    #[default]
+    Json,
+    /// Indented `- role "name" [@eN]` lines in `text`
+    Text,
+}
+
+/// ARIA snapshot rendered as text (`format: "text"`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AriaTextSnapshot {
+    pub url: String,
+    pub title: String,
+    /// One `- role "name" [@eN]` line per node, children indented
+    pub text: String,
+    /// Unified diff against the previous snapshot (`diff: true`; absent for
+    /// a session's first snapshot)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    pub element_count: usize,
+    #[serde(default)]
+    pub cached: bool,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<ScrollState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focused: Option<String>,
+}
+
+/// Node-level changes between a session's last two snapshots
+/// (`browser.snapshot_diff`). Nodes are flattened, without children.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AriaSnapshotDiff {
+    pub url: String,
+    /// False for a session's first snapshot, which has nothing to compare
+    /// against (the lists are then empty)
+    pub compared: bool,
+    /// Nodes new in the latest snapshot, with their current refs
+    pub added: Vec<AriaNode>,
+    /// Nodes gone since the previous snapshot, with their old refs
+    pub removed: Vec<AriaNode>,
+    pub changed: Vec<AriaNodeChange>,
+    /// Nodes matched with nothing changed
+    pub unchanged: usize,
+}
+
+/// A node present in both snapshots whose properties changed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AriaNodeChange {
+    /// Current ref
+    pub ref_id: String,
+    /// Ref in the previous snapshot
+    pub previous_ref: String,
+    pub role: String,
+    /// Changed properties: name, value, focused, focusable
+    pub fields: Vec<String>,
+    pub before: AriaNode,
+    pub after: AriaNode,
+}
+
+/// Scroll position of the page and its scrolled containers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollState {
+    /// Window horizontal offset
+    pub x: f64,
+    /// Window vertical offset
+    pub y: f64,
+    /// Major scroll containers that are scrolled away from their origin
+    #[serde(default)]
+    pub containers: Vec<ScrollContainer>,
+}
+
+/// Where `scroll_to_element` puts the element vertically.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollBlock {
+    Start,
+    #[default]
+    Center,
+    End,
+    /// Only as far as needed to bring it into view
+    Nearest,
+}
+
+/// Position after a scroll.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollResult {
+    pub success: bool,
+    /// Horizontal offset of the window (or container) afterwards
+    pub x: f64,
+    /// Vertical offset of the window (or container) afterwards
+    pub y: f64,
+    /// Scrollable content height
+    pub scroll_height: f64,
+    /// Scrolled to the end of the content
+    pub at_bottom: bool,
+    /// Element is (partly) inside the viewport afterwards (`scroll_to_element`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visible: Option<bool>,
+    /// Scroll-and-wait rounds taken (`scroll_to_bottom`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounds: Option<u32>,
+}
+
+/// Scroll offset of a single scroll container.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollContainer {
+    /// CSS selector locating the container
+    pub selector: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Scroll restore result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollRestoreResult {
+    /// Whether the window and every container were restored
+    pub success: bool,
+    /// Scroll position after restoring (may be clamped by page size)
+    pub scroll: ScrollState,
+    /// Container selectors that no longer match an element
+    #[serde(default)]
+    pub missing: Vec<String>,
+}
+
+/// How a fake clock moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockTickMode {
+    /// Runs from the start time at normal speed
+    #[default]
+    Realtime,
+    /// Stays at the start time
+    Frozen,
+}
+
+/// Page zoom after `set_zoom`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ZoomResult {
+    /// Zoom factor (2 for 200%)
+    pub factor: f64,
+    /// CSS viewport the page lays out in at this zoom
+    pub viewport_width: u32,
+    pub viewport_height: u32,
+}
+
+/// Where and how a session runs, for bug reports and audit logs.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnvironmentSummary {
+    pub session_id: String,
+    pub browser: BrowserVersion,
+    pub platform: PlatformInfo,
+    pub viewport: ViewportInfo,
+    pub emulation: EmulationState,
+    /// `--proxy-server` Chrome runs with, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Extensions with a running background page or service worker
+    #[serde(default)]
+    pub extensions: Vec<ExtensionInfo>,
+    pub features: FeatureFlags,
+}
+
+/// Result of a raw CDP command, from `browser.cdp`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CdpResult {
+    pub method: String,
+    /// The command's result, as Chrome returned it
+    pub result: serde_json::Value,
+}
+
+/// Chrome build, from `Browser.getVersion`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BrowserVersion {
+    /// e.g. `HeadlessChrome/126.0.6478.126`
+    pub product: String,
+    pub revision: String,
+    pub protocol_version: String,
+    pub js_version: String,
+    /// User agent of the browser, before any per-page override
+    pub user_agent: String,
+    /// Whether Chrome runs headless (unknown when its command line isn't
+    /// available, as with some attached browsers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headless: Option<bool>,
+    /// `connect` when attached to a running Chrome, else `launch`
+    pub mode: String,
+}
+
+/// Operating system of the daemon and of the page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlatformInfo {
+    /// Daemon OS (`linux`, `macos`, `windows`)
+    pub os: String,
+    /// Daemon CPU architecture
+    pub arch: String,
+    /// `navigator.platform` as the page sees it
+    pub navigator_platform: String,
+}
+
+/// Size of the session's viewport and screen, in CSS pixels.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ViewportInfo {
+    pub width: u32,
+    pub height: u32,
+    pub device_pixel_ratio: f64,
+    pub screen_width: u32,
+    pub screen_height: u32,
+}
+
+/// Overrides in effect in a session, and what the page reads as a result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmulationState {
+    /// Page zoom, when not 100%
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom: Option<f64>,
+    /// Layout key events are generated for
+    pub keyboard_layout: String,
+    /// Mode of the fake clock, when one is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fake_clock: Option<ClockTickMode>,
+    pub popup_policy: PopupPolicy,
+    /// Whether the synthetic cursor is drawn
+    pub show_cursor: bool,
+    /// `navigator.userAgent` in the page
+    pub user_agent: String,
+    /// `navigator.language`
+    pub locale: String,
+    /// IANA timezone the page renders local times in
+    pub timezone: String,
+}
+
+/// A loaded Chrome extension.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtensionInfo {
+    pub id: String,
+    /// Title of its background page (often the extension's name)
+    pub title: String,
+    /// `background_page` or `service_worker`
+    pub kind: String,
+}
+
+/// Chrome features switched on or off on its command line.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureFlags {
+    /// `--enable-features`
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    /// `--disable-features`
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    /// Every switch Chrome was started with, when it reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub switches: Option<Vec<String>>,
+}
+
+/// Page time after setting or clearing a fake clock.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FakeClockResult {
+    pub success: bool,
+    /// Mode of the fake clock (absent once the real clock is back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick_mode: Option<ClockTickMode>,
+    /// `Date.now()` in the page
+    pub epoch_ms: f64,
+    /// Page time in UTC
+    pub iso: String,
+    /// Page time as the page renders it, in its timezone
+    pub local: String,
+}
+
+/// Result of moving a fake clock ahead.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FastForwardResult {
+    /// False when the timer limit stopped it short of `advanced_ms`
+    pub success: bool,
+    pub advanced_ms: u64,
+    /// Timer callbacks run on the way
+    pub timers_fired: usize,
+    /// Timers still waiting afterwards
+    pub timers_pending: usize,
+    /// Page time afterwards
+    pub clock: FakeClockResult,
+}
+
+/// Result of waiting for a page to settle.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StabilityResult {
+    /// Whether every signal was quiet before the timeout
+    pub stable: bool,
+    /// Time spent waiting
+    pub waited_ms: u64,
+    /// Requests still in flight when the wait ended
+    pub inflight_requests: usize,
+    /// Signals still busy at timeout: `load`, `network`, `layout`, `dom`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<String>,
+}
+
+/// Encoding of a captured image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ImageFormat {
+    /// File extension for images in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+/// Container and codec of a session recording.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoFormat {
+    /// VP9 in WebM
+    #[default]
+    Webm,
+    /// H.264 in MP4
+    Mp4,
+}
+
+impl VideoFormat {
+    /// File extension for videos in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Webm => "webm",
+            Self::Mp4 => "mp4",
+        }
+    }
+}
+
+/// A finished session recording.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordingResult {
+    pub session_id: String,
+    /// Video file
+    pub path: String,
+    pub format: VideoFormat,
+    /// When recording started (RFC 3339)
+    pub started_at: String,
+    /// Length of the video
+    pub duration_ms: u64,
+    /// Wall-clock time from start to stop
+    pub elapsed_ms: u64,
+    /// Frames written
+    pub frames: u64,
+    pub fps: u32,
+    /// Size of the video in bytes
+    pub size: u64,
+    /// Stored artifact (if artifact storage was requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+}
+
+/// A recording that has started, from `browser.recording.start`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordingStarted {
+    pub session_id: String,
+    /// Video file, written when the recording stops
+    pub path: String,
+    pub format: VideoFormat,
+    pub fps: u32,
+}
+
+/// Screenshot response.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenshotResult {
+    /// Base64-encoded image data (if no path specified)
+    #[serde(default)]
+    pub data: Option<String>,
+    /// File path (if path was specified)
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Stored artifact (if artifact storage was requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+    /// Image format
+    #[serde(default)]
+    pub format: ImageFormat,
+    /// Image dimensions
+    pub width: u32,
+    pub height: u32,
+    /// Captured region in document CSS pixels (element screenshots only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clip: Option<ElementRect>,
+    /// Size of the whole scrollable page in CSS pixels (page screenshots only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_width: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_height: Option<f64>,
+}
+
+/// Area of a screenshot comparison where pixels changed, in image pixels.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DiffRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Changed pixels within the region
+    pub pixels: u64,
+}
+
+/// Result of comparing a screenshot against a baseline.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenshotComparison {
+    /// Compared extent (the larger of both images)
+    pub width: u32,
+    pub height: u32,
+    /// Whether both images have the same dimensions
+    pub same_size: bool,
+    /// Per-channel difference tolerated (0-255)
+    pub threshold: u8,
+    pub changed_pixels: u64,
+    pub total_pixels: u64,
+    /// Changed pixels as a percentage of the compared extent
+    pub mismatch_percent: f64,
+    /// Changed areas, top to bottom
+    pub regions: Vec<DiffRegion>,
+    /// Whether the mismatch is within `max_mismatch` (if one was given)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passed: Option<bool>,
+    /// Base64-encoded diff image, PNG (if no path or artifact was requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Diff image file (if a path was specified)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_path: Option<String>,
+    /// Stored diff image (if artifact storage was requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+}
+
+/// Element to crop a screenshot to.
+#[derive(Debug, Clone)]
+pub struct ElementCapture {
+    /// @eN ref or CSS selector
+    pub selector: String,
+    /// CSS pixels of surrounding context to include on every side
+    pub padding: f64,
+    /// Outline the element in the captured image
+    pub marker: bool,
+}
+
+/// What the coordinates of a screenshot `clip` are relative to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipOrigin {
+    /// The top-left corner of the page, as in full-page captures and the
+    /// `clip` screenshots report
+    #[default]
+    Document,
+    /// The top-left corner of the viewport, as in viewport captures
+    Viewport,
+}
+
+/// What a screenshot covers and how it is encoded.
+#[derive(Debug, Clone, Default)]
+pub struct ScreenshotOptions {
+    /// Crop to this element
+    pub element: Option<ElementCapture>,
+    /// Crop to this rect, in CSS pixels from `clip_origin`
+    pub clip: Option<ElementRect>,
+    pub clip_origin: ClipOrigin,
+    /// Capture the whole scrollable page instead of the viewport
+    pub full_page: bool,
+    /// Device scale factor for this capture only
+    pub scale: Option<f64>,
+    pub format: ImageFormat,
+    /// JPEG or WebP quality, 0-100 (Chrome's default when unset)
+    pub quality: Option<u8>,
+}
+
+/// Kind of stored artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Screenshot,
+    Pdf,
+    Har,
+    Recording,
+    Download,
+}
+
+impl ArtifactKind {
+    /// Directory / key prefix for this kind.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Screenshot => "screenshot",
+            Self::Pdf => "pdf",
+            Self::Har => "har",
+            Self::Recording => "recording",
+            Self::Download => "download",
+        }
+    }
+
+    /// Parse a kind from its key prefix.
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        [
+            Self::Screenshot,
+            Self::Pdf,
+            Self::Har,
+            Self::Recording,
+            Self::Download,
+        ]
+        .into_iter()
+        .find(|kind| kind.as_str() == prefix)
+    }
+}
+
+/// Artifact stored by the configured artifact backend.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Artifact {
+    /// Stable ID (`<kind>/<uuid>.<ext>`), valid for `browser.artifact.get`
+    pub id: String,
+    pub kind: ArtifactKind,
+    pub content_type: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Backend URL of the artifact (file:// for local storage); S3 URLs are
+    /// unsigned, so read private objects with `browser.artifact.get`
+    pub url: String,
+    /// When the artifact was stored
+    pub created_at: String,
+}
+
+/// Contents of a stored artifact, from `browser.artifact.get`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArtifactData {
+    pub id: String,
+    pub content_type: String,
+    /// Size in bytes
+    pub size: usize,
+    /// Base64-encoded contents
+    pub data: String,
+}
+
+/// What caused a timeline frame to be captured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineTrigger {
+    /// Auto-capture after `browser.open`
+    Navigation,
+    /// `browser.timeline.capture`
+    Manual,
+}
+
+/// One screenshot in a session's timeline.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimelineFrame {
+    /// Position in the session's timeline (stable across dropped frames)
+    pub index: usize,
+    /// When the screenshot was taken
+    pub captured_at: String,
+    pub trigger: TimelineTrigger,
+    /// Page URL at capture time
+    pub url: String,
+    /// Page title at capture time
+    pub title: String,
+    /// Caller-supplied label for manual captures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Stored screenshot
+    pub artifact: Artifact,
+}
+
+/// Storyboard of a session's screenshots.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimelineIndex {
+    pub session_id: String,
+    /// Whether frames are captured after every navigation
+    pub auto: bool,
+    /// Earlier frames no longer held (past the per-session limit, or cleared)
+    pub dropped: usize,
+    pub frames: Vec<TimelineFrame>,
+}
+
+/// Outcome of `browser.timeline.auto`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimelineAuto {
+    pub session_id: String,
+    /// Whether frames are now captured after every navigation
+    pub auto: bool,
+}
+
+/// Outcome of `browser.timeline.clear`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimelineCleared {
+    pub session_id: String,
+    /// Frames dropped
+    pub cleared: usize,
+}
+
+/// Paper size for print previews.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PaperSize {
+    #[default]
+    Letter,
+    Legal,
+    A4,
+    A3,
+}
+
+impl PaperSize {
+    /// Portrait width and height in inches.
+    pub fn inches(self) -> (f64, f64) {
+        match self {
+            Self::Letter => (8.5, 11.0),
+            Self::Legal => (8.5, 14.0),
+            Self::A4 => (8.27, 11.69),
+            Self::A3 => (11.69, 16.54),
+        }
+    }
+}
+
+/// Layout of a print preview.
+#[derive(Debug, Clone, Copy)]
+pub struct PrintOptions {
+    pub paper: PaperSize,
+    pub landscape: bool,
+    /// Margin on every side, in inches
+    pub margin: f64,
+    /// Device scale factor of the page images
+    pub scale: Option<f64>,
+    /// Also render a PDF with Chrome's print pipeline
+    pub pdf: bool,
+}
+
+/// One rendered page of a print preview.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrintPage {
+    /// 1-based page number
+    pub number: usize,
+    /// Part of the document on this page, in CSS pixels
+    pub clip: ElementRect,
+    /// Stored page image
+    pub artifact: Artifact,
+}
+
+/// Page images of the document rendered with print media.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrintPreview {
+    pub paper: PaperSize,
+    pub landscape: bool,
+    /// Printable area of a page in CSS pixels (96 per inch)
+    pub page_width: f64,
+    pub page_height: f64,
+    /// Pages the document breaks into, including any not captured
+    pub total_pages: usize,
+    pub pages: Vec<PrintPage>,
+    /// Stored PDF (if requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdf: Option<Artifact>,
+}
+
+/// Page margins, in inches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    /// The same margin on every side.
+    pub fn uniform(inches: f64) -> Self {
+        Self {
+            top: inches,
+            right: inches,
+            bottom: inches,
+            left: inches,
+        }
+    }
+}
+
+/// Layout of a PDF export.
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    pub paper: PaperSize,
+    pub landscape: bool,
+    pub margins: Margins,
+    /// HTML templates drawn in the top and bottom margins
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    /// Print CSS backgrounds
+    pub background: bool,
+}
+
+/// PDF export response.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PdfResult {
+    /// Base64-encoded PDF (if no path or artifact was requested)
+    #[serde(default)]
+    pub data: Option<String>,
+    /// File path (if path was specified)
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Stored artifact (if artifact storage was requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+    pub paper: PaperSize,
+    pub landscape: bool,
+    pub margins: Margins,
+    /// Size of the PDF in bytes
+    pub size: usize,
+}
+
+/// Outcome of an artifact garbage-collection pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GcReport {
+    /// Artifacts deleted
+    pub deleted: usize,
+    /// Bytes freed
+    pub freed_bytes: u64,
+    /// Artifacts kept
+    pub remaining: usize,
+    /// Bytes still in use
+    pub remaining_bytes: u64,
+}
+
+/// Outcome of a session maintenance pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MaintenanceReport {
+    /// Sessions whose caches and buffers were cleared
+    pub sessions: usize,
+    /// Sessions removed because their page had closed
+    #[serde(default)]
+    pub pruned: Vec<String>,
+    /// Buffered events dropped from sessions and observers
+    pub trimmed_events: u64,
+    /// Sessions moved to a fresh page and renderer process
+    #[serde(default)]
+    pub recycled: Vec<String>,
+}
+
+/// Time limits a session's calls run under, after `set_timeouts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TimeoutSettings {
+    /// Limit on `open`, `go_back`, `go_forward` and `reload`
+    pub navigation_ms: u64,
+    /// Limit on element actions (`click`, `fill`, `type_text`, `press`, `scroll`, ...)
+    pub action_ms: u64,
+    /// Limit on `snapshot`
+    pub snapshot_ms: u64,
+}
+
+/// One check in the `health` self-test.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HealthCheck {
+    /// Check name: `launch`, `navigate`, `cdp_latency`, `capabilities`, or
+    /// `artifact_disk`
+    pub name: String,
+    /// Whether the check passed
+    pub ok: bool,
+    /// Time the check took, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+    /// Failure reason or extra context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl HealthCheck {
+    /// A check that passed.
+    pub fn pass(name: &str, duration_ms: Option<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: true,
+            duration_ms,
+            detail: None,
+        }
+    }
+
+    /// A check that failed with `detail`.
+    pub fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: false,
+            duration_ms: None,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Readiness report from `health` with `self_test: true`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SelfTestReport {
+    /// True when every check passed
+    pub ready: bool,
+    /// Individual checks, in the order they ran
+    pub checks: Vec<HealthCheck>,
+    /// Free space available to the artifact store, when it's on local disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_free_bytes: Option<u64>,
+    /// What the browser can do in its mode, when it launched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<BrowserCapabilities>,
+}
+
+/// Response of `health`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HealthStatus {
+    /// Whether the browser answers (and with `self_test`, every check passed)
+    pub healthy: bool,
+    /// Always `browser`
+    pub service: String,
+    /// Daemon version
+    pub version: String,
+    pub browser: BrowserStatus,
+    /// Artifact backend: `local` or `s3`
+    pub artifact_store: String,
+    /// Readiness checks, with `self_test: true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_test: Option<SelfTestReport>,
+}
+
+impl SelfTestReport {
+    /// Build a report, deriving `ready` from the checks.
+    pub fn new(checks: Vec<HealthCheck>, artifact_free_bytes: Option<u64>) -> Self {
+        Self {
+            ready: checks.iter().all(|c| c.ok),
+            checks,
+            artifact_free_bytes,
+            capabilities: None,
+        }
+    }
+}
+
+/// How a launched Chrome runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadlessMode {
+    /// Legacy headless (`--headless=old`): fastest, but no extensions or PDF viewer
+    #[default]
+    Old,
+    /// Chrome's new headless mode: the full browser without a window
+    New,
+    /// A visible window, on a virtual display (Xvfb) when there is none
+    Headed,
+}
+
+impl HeadlessMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Old => "old",
+            Self::New => "new",
+            Self::Headed => "headed",
+        }
+    }
+}
+
+/// Features available in the browser's mode, from the `health` self-test.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BrowserCapabilities {
+    pub mode: HeadlessMode,
+    /// Virtual X display Chrome runs on, if one was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+    /// Extensions can be loaded (not in old headless)
+    pub extensions: bool,
+    /// PDFs open in Chrome's viewer instead of downloading
+    pub pdf_viewer: bool,
+    /// `Page.printToPDF` works (`pdf`, `print_preview` with `pdf`)
+    pub print_to_pdf: bool,
+    /// Screencast frames arrive (live view, recordings, `screencast.*`)
+    pub screencast: bool,
+    /// Capabilities the mode should have that this browser lacks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+/// Navigation result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NavigationResult {
+    /// Final URL after navigation
+    pub url: String,
+    /// Page title
+    pub title: String,
+    /// HTTP status code
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// CAPTCHA widgets on the page, which need a human
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub captchas: Vec<CaptchaChallenge>,
+    /// Last load condition reached before returning (navigations only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_until: Option<WaitUntil>,
+    /// Time from starting the navigation to reaching `wait_until`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_time_ms: Option<u64>,
+}
+
+/// When a navigation counts as done, in the order pages reach them.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum WaitUntil {
+    /// The response arrived and the new document replaced the old one
+    Commit,
+    /// The HTML is parsed (`DOMContentLoaded`)
+    DomContentLoaded,
+    /// The page and its subresources loaded (`load`)
+    #[default]
+    Load,
+    /// No network requests for 500ms
+    NetworkIdle,
+}
+
+/// Service behind a CAPTCHA widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptchaProvider {
+    /// Google reCAPTCHA (v2 checkbox or Enterprise)
+    Recaptcha,
+    Hcaptcha,
+    /// Cloudflare Turnstile, including Cloudflare's managed challenge pages
+    Turnstile,
+}
+
+/// A CAPTCHA widget found on a page. Not solved; a cue to hand over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CaptchaChallenge {
+    pub provider: CaptchaProvider,
+    /// URL of the widget's iframe
+    pub frame_url: String,
+}
+
+/// Launch progress of the gateway's browser.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BrowserStatus {
+    /// Not launched yet (lazy mode, no request needed it so far)
+    Idle,
+    /// Launch (or connect) in progress
+    Launching { started_at: String },
+    /// Browser is up
+    Ready { launch_ms: u64 },
+    /// Last launch attempt failed; the next request retries
+    Failed { error: String },
+}
+
+/// Browser session info.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionInfo {
+    /// Session ID
+    pub id: String,
+    /// Current URL
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Whether this is the active session
+    pub active: bool,
+    /// Network traffic since the session was created
+    #[serde(default)]
+    pub bandwidth: BandwidthUsage,
+    /// Session whose page opened this one, for adopted popups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opener: Option<String>,
+}
+
+/// Network traffic of one resource type (or of a whole session).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceBandwidth {
+    /// Requests that finished loading
+    pub requests: u64,
+    /// Bytes received over the network, headers included; cache hits are 0
+    pub downloaded_bytes: u64,
+    /// Request body bytes sent
+    pub uploaded_bytes: u64,
+}
+
+/// Network traffic of a session, in total and by resource type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BandwidthUsage {
+    #[serde(flatten)]
+    pub total: ResourceBandwidth,
+    /// Keyed by CDP resource type (`document`, `script`, `image`, `xhr`, ...)
+    #[serde(default)]
+    pub by_type: std::collections::BTreeMap<String, ResourceBandwidth>,
+}
+
+/// A session created by `browser.session.new`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionCreated {
+    pub success: bool,
+    pub session_id: String,
+}
+
+/// Open sessions, from `browser.session.list`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionList {
+    pub count: usize,
+    pub sessions: Vec<SessionInfo>,
+}
+
+/// A session closed by `browser.session.close`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionClosed {
+    pub success: bool,
+    pub session_id: String,
+    /// Network traffic over the session's life (none if it wasn't open)
+    #[serde(default)]
+    pub bandwidth: Option<BandwidthUsage>,
+    /// Video of the session, if it was being recorded
+    #[serde(default)]
+    pub recording: Option<RecordingResult>,
+}
+
+/// How a trial Content-Security-Policy is applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CspMode {
+    /// Added as `Content-Security-Policy-Report-Only`; nothing is blocked
+    #[default]
+    ReportOnly,
+    /// Replaces the page's own `Content-Security-Policy` headers
+    Enforce,
+}
+
+/// A resource (or inline script/style) a trial policy blocks.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CspViolation {
+    /// Directive that blocked it (`script-src-elem`, `img-src`, ...)
+    pub directive: String,
+    /// URL, or `inline`/`eval` for inline code
+    pub blocked_uri: String,
+    /// Times it was reported
+    pub count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    /// Start of the inline code, when the policy has `'report-sample'`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<String>,
+}
+
+/// Result of loading a page under a trial Content-Security-Policy.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CspTrialResult {
+    /// Whether the policy was added to the main document
+    pub success: bool,
+    pub url: String,
+    pub policy: String,
+    pub mode: CspMode,
+    /// The page's own `Content-Security-Policy` header
+    #[serde(default)]
+    pub existing_policy: Option<String>,
+    /// Distinct resources blocked (or that would be)
+    pub blocked: usize,
+    pub violations: Vec<CspViolation>,
+}
+
+/// What a blocker standing between the agent and the page's content is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockerKind {
+    /// Content needs signing in
+    LoginWall,
+    /// Content needs a subscription
+    Paywall,
+    /// A human-verification challenge
+    Captcha,
+    /// A page in front of the real one: bot checks, age gates, exit notices,
+    /// browser error pages
+    Interstitial,
+}
+
+/// One detected blocker.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Blocker {
+    pub kind: BlockerKind,
+    /// Heuristic score, 0.5 to 1
+    pub confidence: f64,
+    /// Signals that point to this kind
+    pub evidence: Vec<String>,
+}
+
+/// Result of `browser.detect_blockers`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlockerReport {
+    pub url: String,
+    pub title: String,
+    pub blocked: bool,
+    /// Most confident blocker (None when nothing was detected)
+    pub primary: Option<BlockerKind>,
+    /// Detected blockers, most confident first
+    pub blockers: Vec<Blocker>,
+}
+
+/// Where and in what language a page is visited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LocaleVariant {
+    /// BCP 47 locale, e.g. "de-DE"
+    pub locale: String,
+    /// IANA timezone, e.g. "Europe/Berlin" (the browser's own when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geolocation: Option<GeoPosition>,
+}
+
+/// Emulated device position.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GeoPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Accuracy radius in meters
+    #[serde(default = "default_geo_accuracy")]
+    pub accuracy: f64,
+}
+
+fn default_geo_accuracy() -> f64 {
+    100.0
+}
+
+/// A page as loaded under one locale variant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LocaleVariantSnapshot {
+    #[serde(flatten)]
+    pub variant: LocaleVariant,
+    /// URL after redirects (locale redirects show here)
+    pub url: String,
+    pub title: String,
+    /// `lang` attribute of the document
+    #[serde(default)]
+    pub lang: Option<String>,
+    pub element_count: usize,
+    /// Text snapshot of the page
+    pub text: String,
+    /// Why this variant couldn't be captured; the other fields are empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of `browser.locale_variants`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LocaleVariantReport {
+    pub url: String,
+    /// In the order requested
+    pub variants: Vec<LocaleVariantSnapshot>,
+    /// Different text snapshots among the captured variants (1 when the
+    /// page doesn't change with locale)
+    pub distinct_snapshots: usize,
+}
+
+/// How one link answered `browser.check_links`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LinkCheck {
+    pub url: String,
+    /// HTTP status; absent when the request failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Redirect target, resolved against `url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Why the request failed (timeout, DNS, TLS, connection refused)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Answered from the link check cache
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+/// Result of `browser.check_links`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LinkCheckReport {
+    /// Page the links were collected from
+    pub url: String,
+    /// Same-origin links checked
+    pub checked: usize,
+    /// Links that answered 2xx
+    pub ok: usize,
+    /// Links that answered 4xx/5xx or failed
+    pub broken: Vec<LinkCheck>,
+    /// Links that answered 3xx
+    pub redirected: Vec<LinkCheck>,
+    /// Links past `max_links` that weren't checked
+    pub unchecked: usize,
+}
+
+/// Requests of one registrable domain on a page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ThirdPartyDomain {
+    /// Registrable domain (`example.co.uk`)
+    pub domain: String,
+    /// Whether it differs from the page's own domain
+    pub third_party: bool,
+    pub requests: u64,
+    /// Bytes received over the network
+    pub downloaded_bytes: u64,
+    /// Load time of its render-blocking resources
+    pub blocking_ms: f64,
+    /// Resource types requested, in order of first request
+    pub resource_types: Vec<String>,
+}
+
+/// Requests of the current page grouped by domain.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ThirdPartyReport {
+    pub url: String,
+    /// The page's registrable domain (None for pages without a host)
+    pub first_party: Option<String>,
+    pub third_party_domains: usize,
+    pub third_party_requests: u64,
+    pub third_party_bytes: u64,
+    pub third_party_blocking_ms: f64,
+    pub total_requests: u64,
+    pub total_bytes: u64,
+    /// Third-party domains first, heaviest first
+    pub domains: Vec<ThirdPartyDomain>,
+}
+
+/// Event emitted by a browser session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// Main-frame navigation committed
+    Navigation { url: String },
+    /// `console.*` call from the page
+    Console { level: String, text: String },
+    /// HTTP response received by the page
+    Response {
+        url: String,
+        status: i64,
+        resource_type: String,
+    },
+    /// Web Notification shown by the page
+    Notification(WebNotification),
+    /// CAPTCHA widget loaded in the page
+    Captcha(CaptchaChallenge),
+    /// The page opened a new tab or window, handled per the session's policy
+    Popup {
+        url: String,
+        policy: PopupPolicy,
+        /// Session the popup was adopted as (`attach`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+    },
+}
+
+impl SessionEvent {
+    /// The event's `type` on the wire.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Navigation { .. } => "navigation",
+            Self::Console { .. } => "console",
+            Self::Response { .. } => "response",
+            Self::Notification(_) => "notification",
+            Self::Captcha(_) => "captcha",
+            Self::Popup { .. } => "popup",
+        }
+    }
+}
+
+/// What a session does with the tabs and windows its page opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PopupPolicy {
+    /// Open them as Chrome does, outside every session
+    #[default]
+    Allow,
+    /// Open them in the session's own tab instead
+    SameTab,
+    /// Open them and adopt each as a new session
+    Attach,
+    /// Don't open them
+    Block,
+}
+
+/// Where a Web Notification was shown from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationSource {
+    /// `new Notification(...)`
+    Page,
+    /// `ServiceWorkerRegistration.showNotification(...)` called from the page
+    ServiceWorker,
+}
+
+/// A Web Notification captured from a page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebNotification {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Origin of the page that showed it
+    pub origin: String,
+    pub source: NotificationSource,
+    /// When it was shown (RFC 3339, page clock)
+    pub timestamp: String,
+}
+
+/// Notifications captured in a session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NotificationList {
+    /// Oldest first
+    pub notifications: Vec<WebNotification>,
+    /// Older notifications dropped to bound the buffer
+    #[serde(default)]
+    pub dropped: u64,
+}
+
+/// CDP handles for one snapshot ref.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RefBinding {
+    /// `@eN`
+    pub ref_id: String,
+    /// `DOM.BackendNodeId`; valid on any CDP session attached to the target
+    pub backend_node_id: i64,
+    /// `Runtime.RemoteObjectId`; valid on the daemon's CDP session only, and
+    /// until the next export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+    pub tag: String,
+    /// Structural CSS path (`#id` or `nth-child` steps)
+    pub selector: String,
+    /// Attribute selector matching the ref until the next snapshot
+    pub ref_selector: String,
+}
+
+/// Refs of a session's page, mapped to CDP handles.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RefExport {
+    /// CDP target to attach to
+    pub target_id: String,
+    pub url: String,
+    /// In document order
+    pub refs: Vec<RefBinding>,
+}
+
+/// Session event as buffered for an observer or the session's replay.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObservedEvent {
+    /// Sequence number within its buffer
+    pub seq: u64,
+    /// When the event was recorded
+    pub at: String,
+    #[serde(flatten)]
+    pub event: SessionEvent,
+}
+
+/// Events buffered for an observer.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObserverEvents {
+    pub observer_id: String,
+    /// Session being observed
+    pub session_id: String,
+    pub events: Vec<ObservedEvent>,
+    /// Pass as `since` to fetch only newer events
+    pub next: u64,
+    /// Events dropped because the observer fell behind
+    pub missed: u64,
+}
+
+/// An observer attached by `browser.session.observe`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObserverOpened {
+    /// Pass as `session_id` to call the read-only methods
+    pub observer_id: String,
+    /// Session being observed
+    pub session_id: String,
+    /// Methods the observer may call
+    pub allowed_methods: Vec<String>,
+}
+
+/// Outcome of `browser.observer.close`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObserverClosed {
+    pub observer_id: String,
+    /// False if there was no such observer
+    pub closed: bool,
+}
+
+/// Recent events of a session, kept for clients that ask after the fact.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionEvents {
+    pub session_id: String,
+    /// Oldest first
+    pub events: Vec<ObservedEvent>,
+    /// Pass as `since` to fetch only newer events
+    pub next: u64,
+    /// Older events evicted to bound the buffer
+    pub dropped: u64,
+    /// Events lost because recording fell behind
+    pub missed: u64,
+}
+
+/// A screencast frame streamed over the gateway.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StreamedFrame {
+    pub seq: u64,
+    /// When the daemon received the frame (RFC 3339)
+    pub at: String,
+    /// Base64-encoded JPEG
+    pub data: String,
+    /// Size of the JPEG in bytes
+    pub size: usize,
+}
+
+/// Frames of a session's screencast stream.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StreamedFrames {
+    pub session_id: String,
+    /// Oldest first
+    pub frames: Vec<StreamedFrame>,
+    /// Pass as `since` to fetch only newer frames
+    pub next: u64,
+    /// Frames numbered `since` or later that weren't returned, because they
+    /// were evicted or over the limit
+    pub skipped: u64,
+    /// False once the stream has stopped; no more frames will come
+    pub streaming: bool,
+}
+
+/// Outcome of `browser.screencast.start`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreencastStarted {
+    pub session_id: String,
+    /// False when the session was already streaming
+    pub started: bool,
+    /// The stream stops after this long without a poll
+    pub idle_timeout_ms: u64,
+}
+
+/// Outcome of `browser.screencast.stop`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreencastStopped {
+    pub session_id: String,
+    /// False when the session wasn't streaming
+    pub stopped: bool,
+}
+
+/// Where to watch a session, from `browser.live_view`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LiveViewUrls {
+    pub session_id: String,
+    /// MJPEG stream of the session
+    pub url: String,
+    /// Page linking every session's stream
+    pub index_url: String,
+}
+
+/// Saved auth state info.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SavedState {
+    /// State name
+    pub name: String,
+    /// Domain(s) the state applies to
+    pub domains: Vec<String>,
+    /// When the state was saved
+    pub saved_at: String,
+}
+
+/// Saved auth states, from `browser.state.list`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SavedStates {
+    pub count: usize,
+    pub states: Vec<SavedState>,
+}
+
+/// Outcome of `browser.state.save`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StateSaved {
+    pub success: bool,
+    /// File the state was written to
+    pub path: String,
+}
+
+/// Outcome of `browser.state.load`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StateLoaded {
+    pub success: bool,
+    pub name: String,
+}
+
+/// Serializable cookie for auth state.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SerializableCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    #[serde(default)]
+    pub expires: Option<f64>,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    pub same_site: Option<CookieSameSite>,
+}
+
+/// Local storage snapshot for a single origin.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct LocalStorageState {
+    #[serde(default)]
+    pub origin: String,
+    #[serde(default)]
+    pub items: HashMap<String, String>,
+}
+
+/// Auth state snapshot with cookies and localStorage.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuthState {
+    #[serde(default)]
+    pub cookies: Vec<SerializableCookie>,
+    #[serde(default)]
+    pub local_storage: LocalStorageState,
+    #[serde(default)]
+    pub saved_at: String,
+}
+
+/// Cookie identity: a cookie is replaced, not duplicated, when these match.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub struct CookieId {
+    pub domain: String,
+    pub path: String,
+    pub name: String,
+}
+
+/// Cookie present in both states with different attributes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CookieChange {
+    #[serde(flatten)]
+    pub cookie: CookieId,
+    /// Attributes that differ: `value`, `expires`, `secure`, `http_only`, `same_site`
+    pub fields: Vec<String>,
+}
+
+/// Cookie differences between two auth states.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CookieDiff {
+    pub added: Vec<CookieId>,
+    pub removed: Vec<CookieId>,
+    pub changed: Vec<CookieChange>,
+}
+
+/// localStorage differences between two auth states.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StorageDiff {
+    /// Origin of each state's storage, when they differ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<OriginChange>,
+    /// Keys only in the second state
+    pub added: Vec<String>,
+    /// Keys only in the first state
+    pub removed: Vec<String>,
+    /// Keys whose value differs
+    pub changed: Vec<String>,
+}
+
+/// Storage origin before and after.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OriginChange {
+    pub before: String,
+    pub after: String,
+}
+
+/// Structured diff of two auth states (`a` -> `b`).
+///
+/// Values are reported by key only, so a diff can be logged without leaking
+/// session tokens.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StateDiff {
+    /// Whether the states carry the same cookies and storage
+    pub identical: bool,
+    pub cookies: CookieDiff,
+    pub local_storage: StorageDiff,
+}
+
+/// Mouse button a click uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickButton {
+    #[default]
+    Left,
+    /// Opens the page's context menu
+    Right,
+    Middle,
+}
+
+/// How to click: which button, how many times, holding which modifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickOptions {
+    pub button: ClickButton,
+    /// 1 = click, 2 = double-click, 3 = triple-click
+    pub click_count: u32,
+    /// Modifier keys held (`Shift`, `Control`, `Alt`, `Meta`)
+    pub modifiers: Vec<String>,
+}
+
+impl Default for ClickOptions {
+    fn default() -> Self {
+        Self {
+            button: ClickButton::Left,
+            click_count: 1,
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+/// Click result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClickResult {
+    /// Whether click was successful
+    pub success: bool,
+    /// Element that was clicked (for debugging)
+    #[serde(default)]
+    pub element: Option<String>,
+    /// Button used
+    #[serde(default)]
+    pub button: ClickButton,
+    /// Clicks in the sequence (2 for a double-click)
+    #[serde(default = "default_click_count")]
+    pub click_count: u32,
+    /// Modifier keys held, as Chrome names them
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+}
+
+/// Key press result, from `browser.press` and `browser.press_combo`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyPressResult {
+    pub success: bool,
+    pub key: String,
+    /// Modifiers held during the press
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+}
+
+/// Outcome of `browser.keyboard_layout`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyboardLayoutResult {
+    pub success: bool,
+    pub layout: KeyboardLayout,
+}
+
+/// Outcome of `browser.popup_policy`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PopupPolicyResult {
+    pub success: bool,
+    pub policy: PopupPolicy,
+}
+
+/// Outcome of `browser.cursor_overlay`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CursorOverlayResult {
+    pub success: bool,
+    pub enabled: bool,
+}
+
+/// Select result, from `browser.select`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SelectValueResult {
+    pub success: bool,
+    /// Element that was changed
+    pub selector: String,
+    /// Value selected
+    pub value: String,
+}
+
+/// Check result, from `browser.check`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckResult {
+    pub success: bool,
+    /// Element that was changed
+    pub selector: String,
+    /// State the element was set to
+    pub checked: bool,
+}
+
+fn default_click_count() -> u32 {
+    1
+}
+
+/// Element bounding box in viewport CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ElementRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Highlight result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HighlightResult {
+    /// Whether the element was found and outlined
+    pub success: bool,
+    /// Element that was highlighted (for debugging)
+    pub element: String,
+    /// Element bounds at the time of highlighting
+    #[serde(default)]
+    pub rect: Option<ElementRect>,
+}
+
+/// How a table row is picked.
+#[derive(Debug, Clone)]
+pub enum TableRowKey {
+    /// First body row whose text contains this
+    Contains(String),
+    /// 0-based body row
+    Index(usize),
+}
+
+/// How a table column is picked.
+#[derive(Debug, Clone)]
+pub enum TableColumnKey {
+    /// Header text (exact, then substring; case-insensitive)
+    Header(String),
+    /// 0-based column
+    Index(usize),
+}
+
+/// A table cell to address.
+#[derive(Debug, Clone)]
+pub struct TableQuery {
+    /// @eN ref or CSS selector of the table (first table on the page if None)
+    pub table: Option<String>,
+    pub row: TableRowKey,
+    pub column: TableColumnKey,
+    /// Pick the control in the cell whose label contains this
+    pub target: Option<String>,
+}
+
+/// Control inside a table cell.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CellTarget {
+    /// CSS selector addressing the control
+    pub selector: String,
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A located table cell.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TableCell {
+    /// 0-based body row
+    pub row: usize,
+    /// Body rows matching `row_contains` (the first is used)
+    pub row_matches: usize,
+    /// 0-based column
+    pub column: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    pub text: String,
+    /// CSS selector addressing the cell
+    pub selector: String,
+    /// Bounds in viewport CSS pixels
+    pub rect: ElementRect,
+    /// First control in the cell (matching `target`, if given)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<CellTarget>,
+    /// Refs from the last snapshot inside the cell
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refs: Vec<String>,
+    /// Whether the target (or the cell itself) was clicked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub clicked: bool,
+}
+
+/// Element a spatial query is measured from.
+#[derive(Debug, Clone)]
+pub enum SpatialAnchor {
+    /// @eN ref or CSS selector
+    Selector(String),
+    /// Innermost element whose text contains this
+    Text(String),
+}
+
+/// Which side of the anchor a match must be on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SpatialDirection {
+    #[default]
+    Any,
+    Left,
+    Right,
+    Above,
+    Below,
+    /// Overlaps the anchor vertically
+    SameRow,
+    /// Overlaps the anchor horizontally
+    SameColumn,
+}
+
+/// Which snapshot elements a spatial query returns.
+#[derive(Debug, Clone, Default)]
+pub struct SpatialFilter {
+    /// Exact ARIA role (case-insensitive)
+    pub role: Option<String>,
+    /// Substring of the accessible name (case-insensitive)
+    pub name: Option<String>,
+    pub limit: usize,
+}
+
+/// Snapshot element matched by a spatial query.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SpatialElement {
+    /// Element reference ID from the last snapshot
+    pub ref_id: String,
+    pub role: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Bounds in document CSS pixels
+    pub rect: ElementRect,
+    /// Edge-to-edge distance from the anchor in CSS pixels (nearest only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>,
+}
+
+/// Result of `browser.nearest` or `browser.within`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SpatialResult {
+    /// Bounds of the anchor in document CSS pixels (nearest only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<ElementRect>,
+    pub matches: Vec<SpatialElement>,
+}
+
+/// Criteria for `browser.find_elements`; every criterion given must match.
+#[derive(Debug, Clone, Default)]
+pub struct ElementQuery {
+    /// Exact ARIA role (case-insensitive)
+    pub role: Option<String>,
+    /// Substring of the accessible name (case-insensitive)
+    pub name: Option<String>,
+    /// Substring of the element's rendered text (case-insensitive)
+    pub text: Option<String>,
+    /// Match `name` and `text` whole instead of as substrings
+    pub exact: bool,
+    pub limit: usize,
+}
+
+/// Result of `browser.find_elements`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FoundElements {
+    pub url: String,
+    /// Matching nodes in document order, without their children
+    pub matches: Vec<AriaNode>,
+    /// Matches before `limit` was applied
+    pub total: usize,
+}
+
+/// A result card of a search-result page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchResult {
+    /// 1-based, in page order
+    pub position: usize,
+    pub title: String,
+    /// Destination, with tracking redirects unwrapped
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Snapshot ref of the title link, to click it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<String>,
+}
+
+/// Result cards of a search-result page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchResults {
+    pub url: String,
+    /// Text in the page's search box
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    pub results: Vec<SearchResult>,
+    /// Results before `limit` was applied
+    pub total: usize,
+}
+
+/// What identifies a snapshot element across a reload, when its ref doesn't.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ElementFingerprint {
+    pub role: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Earlier nodes in the snapshot with the same role and name
+    pub occurrence: usize,
+}
+
+/// One step taken to recover a failed element action.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum RecoveryStep {
+    /// An attempt failed with `error`
+    Retry { attempt: u32, error: String },
+    /// The page was reloaded
+    Reload {
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// Reloading failed, so the page's URL was loaded again
+    Navigate { url: String },
+    /// A ref target was found again after the reload
+    Retarget {
+        param: String,
+        from: String,
+        to: String,
+    },
+}
+
+/// Steps taken before an element action succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecoveryReport {
+    /// Attempts made, including the one that succeeded
+    pub attempts: u32,
+    /// Whether the page was reloaded
+    pub reloaded: bool,
+    pub steps: Vec<RecoveryStep>,
+}
+
+/// Which element an interaction uses when its selector matches several.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementRanking {
+    /// First match in document order
+    #[default]
+    Document,
+    /// First visible match inside the viewport
+    InViewport,
+    /// Visible match with the largest area
+    Largest,
+    /// First visible match not covered by another element at its center
+    Topmost,
+    /// Visible match nearest the top, then the left
+    ReadingOrder,
+}
+
+/// How a ranked interaction chose its target.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RankedMatch {
+    pub strategy: ElementRanking,
+    /// Elements the selector matched
+    pub matches: usize,
+    /// Position of the chosen element among the matches, in document order
+    pub index: usize,
+}
+
+/// Interaction that can be rehearsed with `dry_run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DryRunAction {
+    Click,
+    Fill,
+    Select,
+    Check,
+    Hover,
+    Upload,
+}
+
+impl DryRunAction {
+    /// Action behind a method name (with or without the `browser.` prefix).
+    pub fn from_method(method: &str) -> Option<Self> {
+        match method.strip_prefix("browser.").unwrap_or(method) {
+            "click" => Some(Self::Click),
+            "fill" => Some(Self::Fill),
+            "select" => Some(Self::Select),
+            "check" => Some(Self::Check),
+            "hover" => Some(Self::Hover),
+            "upload" => Some(Self::Upload),
+            _ => None,
+        }
+    }
+
+    /// Whether the action goes through a real pointer event at the element's
+    /// center (so an element covering it would receive the event instead).
+    pub fn uses_pointer(self) -> bool {
+        matches!(self, Self::Click | Self::Fill | Self::Hover)
+    }
+}
+
+/// Element an interaction would target.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DryRunTarget {
+    /// Lowercase tag name
+    pub tag: String,
+    /// Explicit ARIA role or input type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Accessible label or trimmed text (first 80 characters)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Bounds in viewport CSS pixels
+    pub rect: ElementRect,
+}
+
+/// Navigation a click would trigger.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct NavigationIntent {
+    /// Absolute target URL
+    pub url: String,
+    /// GET for links, the form method for submits
+    pub method: String,
+    /// Opens in a new tab or window (`target` other than `_self`)
+    pub new_tab: bool,
+}
+
+/// What an interaction would do, without dispatching it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DryRunReport {
+    pub dry_run: bool,
+    pub action: DryRunAction,
+    pub selector: String,
+    /// Elements the selector matches; the first one is used unless `rank`
+    /// picks another
+    pub matches: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<DryRunTarget>,
+    pub visible: bool,
+    pub enabled: bool,
+    /// Element is (partly) inside the viewport; otherwise it would be
+    /// scrolled into view first
+    pub in_viewport: bool,
+    /// Element that would receive the pointer event instead, if covered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obscured_by: Option<String>,
+    /// Navigation a click would trigger
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub would_navigate: Option<NavigationIntent>,
+    /// Whether the selected value or checked state would change (select, check)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub would_change: Option<bool>,
+    /// All checks passed; the real command is expected to succeed
+    pub actionable: bool,
+    /// Why the command would fail or misbehave
+    pub problems: Vec<String>,
+}
+
+/// A check run by the `browser.assert_*` commands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    /// Element text (page text when `selector` is None) contains `expected`,
+    /// or equals it with `exact`; whitespace is collapsed on both sides
+    Text {
+        #[serde(default)]
+        selector: Option<String>,
+        expected: String,
+        #[serde(default)]
+        exact: bool,
+    },
+    /// Element exists, has a non-empty box, and isn't hidden by CSS
+    Visible { selector: String },
+    /// Page URL matches `pattern` (`*` matches any run of characters)
+    Url { pattern: String },
+    /// Exactly `expected` elements match `selector`
+    Count { selector: String, expected: usize },
+}
+
+/// What was observed when an assertion ran.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AssertionEvidence {
+    /// Observed value: text, visibility, URL, or count (null if the element was missing)
+    pub actual: serde_json::Value,
+    /// Page URL at the time of the check
+    pub url: String,
+    /// Screenshot taken when the assertion failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<Artifact>,
+}
+
+/// Assertion outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AssertionResult {
+    /// Whether the assertion held
+    pub passed: bool,
+    /// The assertion that was checked
+    pub assertion: Assertion,
+    /// Human-readable outcome
+    pub message: String,
+    /// Observed state backing the verdict
+    pub evidence: AssertionEvidence,
+}
+
+/// Aggregated outcome of `browser.verify`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyReport {
+    /// Whether every assertion held
+    pub passed: bool,
+    /// Number of assertions run
+    pub total: usize,
+    /// Number that failed
+    pub failed: usize,
+    /// Per-assertion results, in request order
+    pub results: Vec<AssertionResult>,
+    /// One screenshot of the page, taken when anything failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<Artifact>,
+}
+
+impl VerifyReport {
+    /// Summarize `results`.
+    pub fn new(results: Vec<AssertionResult>) -> Self {
+        let failed = results.iter().filter(|r| !r.passed).count();
+        Self {
+            passed: failed == 0,
+            total: results.len(),
+            failed,
+            results,
+            screenshot: None,
+        }
+    }
+}
+
+fn default_crawl_max_pages() -> usize {
+    50
+}
+
+fn default_crawl_depth() -> usize {
+    2
+}
+
+fn default_crawl_concurrency() -> usize {
+    2
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Options for `browser.crawl`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlOptions {
+    /// Start URL (or sitemap URL when `sitemap` is set and it ends in .xml)
+    pub url: String,
+    /// Stop after visiting this many pages
+    #[serde(default = "default_crawl_max_pages")]
+    pub max_pages: usize,
+    /// Only follow links on the start URL's origin
+    #[serde(default = "default_true")]
+    pub same_origin: bool,
+    /// Maximum link hops from the start page (or from sitemap entries)
+    #[serde(default = "default_crawl_depth")]
+    pub depth: usize,
+    /// Seed the crawl from sitemap.xml instead of the start page
+    #[serde(default)]
+    pub sitemap: bool,
+    /// Pages crawled in parallel
+    #[serde(default = "default_crawl_concurrency")]
+    pub concurrency: usize,
+    /// Fields to extract from every page: name -> CSS selector (first match's text)
     #[serde(default)]
-    pub children: Vec<AriaNode>,
+    pub extract: std::collections::BTreeMap<String, String>,
 }
 
-/// ARIA tree snapshot response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AriaSnapshot {
-    /// Page URL
+/// One visited page in a crawl.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlPage {
+    /// URL as queued
     pub url: String,
-    /// Page title
-    pub title: String,
-    /// Root ARIA nodes
-    pub nodes: Vec<AriaNode>,
-    /// Total element count
-    pub element_count: usize,
+    /// Link hops from the seed
+    pub depth: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Extracted fields (null when the selector matched nothing)
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub data: std::collections::BTreeMap<String, serde_json::Value>,
+    /// Links found on the page
+    #[serde(default)]
+    pub links: usize,
+    /// Why the page couldn't be crawled (navigation failure, robots.txt, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-/// Screenshot response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ScreenshotResult {
-    /// Base64-encoded PNG data (if no path specified)
+/// Lifecycle of a crawl job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CrawlState {
+    Running,
+    Done,
+    Cancelled,
+    Failed,
+}
+
+/// Progress report from `browser.crawl.status`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlStatus {
+    pub crawl_id: String,
+    pub state: CrawlState,
+    /// Set when the crawl failed as a whole (e.g. invalid start URL)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Pages visited so far
+    pub visited: usize,
+    /// URLs waiting in the frontier
+    pub queued: usize,
+    /// Pages since the requested offset
+    pub pages: Vec<CrawlPage>,
+    /// Offset to pass as `since` for the next poll
+    pub next: usize,
+}
+
+/// A crawl started by `browser.crawl`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlStarted {
+    /// Pass to `browser.crawl.status` and `browser.crawl.cancel`
+    pub crawl_id: String,
+}
+
+/// Outcome of `browser.crawl.cancel`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlCancelled {
+    pub crawl_id: String,
+    pub cancelled: bool,
+}
+
+/// Fill result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FillResult {
+    /// Whether fill was successful
+    pub success: bool,
+    /// Value that was filled
+    pub value: String,
+    /// Whether the value was added to the existing text rather than replacing it
     #[serde(default)]
-    pub data: Option<String>,
-    /// File path (if path was specified)
+    pub appended: bool,
+    /// Field text before filling
     #[serde(default)]
-    pub path: Option<String>,
-    /// Image dimensions
-    pub width: u32,
-    pub height: u32,
+    pub previous_value: String,
+    /// Field text afterwards, as the page left it (masks and formatters may
+    /// have changed it)
+    #[serde(default)]
+    pub field_value: String,
 }
 
-/// Navigation result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NavigationResult {
-    /// Final URL after navigation
-    pub url: String,
-    /// Page title
-    pub title: String,
-    /// HTTP status code
-    #[serde(default)]
-    pub status: Option<u16>,
+/// Result of emptying a field.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClearResult {
+    /// Whether the field is empty afterwards
+    pub success: bool,
+    pub selector: String,
+    /// Field text before clearing
+    pub previous_value: String,
 }
 
-/// Browser session info.
-#[allow(dead_code)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SessionInfo {
-    /// Session ID
-    pub id: String,
-    /// Current URL
+/// Result of typing text key by key.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TypeTextResult {
+    pub success: bool,
+    /// Characters typed
+    pub typed: usize,
+    /// Time spent typing, pauses included
+    pub elapsed_ms: u64,
+}
+
+/// Hover result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HoverResult {
+    /// Whether the pointer was moved over the element
+    pub success: bool,
+    /// Element that was hovered
+    pub selector: String,
+}
+
+/// Focus after a `focus` or `blur`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FocusResult {
+    /// Whether the element gained (focus) or lost (blur) focus
+    pub success: bool,
+    /// Element acted on (None for blurring whatever had focus)
     #[serde(default)]
-    pub url: Option<String>,
-    /// Whether this is the active session
-    pub active: bool,
+    pub selector: Option<String>,
+    /// Element with focus afterwards (`input#email`), None for the page
+    #[serde(default)]
+    pub active_element: Option<String>,
 }
 
-/// Saved auth state info.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SavedState {
-    /// State name
-    pub name: String,
-    /// Domain(s) the state applies to
-    pub domains: Vec<String>,
-    /// When the state was saved
-    pub saved_at: String,
+/// Options of a `<select>` to choose, by one of their attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionQuery {
+    /// `value` attribute
+    Value(Vec<String>),
+    /// Visible text (or `label` attribute)
+    Label(Vec<String>),
+    /// Position among the options, from 0
+    Index(Vec<usize>),
 }
 
-/// Serializable cookie for auth state.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SerializableCookie {
-    pub name: String,
+/// An option of a `<select>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SelectOption {
+    /// Position among the options, from 0
+    pub index: usize,
     pub value: String,
-    pub domain: String,
-    pub path: String,
+    pub label: String,
+    #[serde(default, skip_serializing)]
+    pub disabled: bool,
+}
+
+/// An option of a select or radio group in `describe_form`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormOption {
+    pub value: String,
+    pub label: String,
     #[serde(default)]
-    pub expires: Option<f64>,
+    pub selected: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+    /// Ref or CSS selector of the radio (radio groups only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+}
+
+/// A field of a form.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormField {
+    /// `name` attribute (or `id` without one)
     #[serde(default)]
-    pub secure: bool,
+    pub name: Option<String>,
+    /// Input type (`text`, `email`, `checkbox`, `radio`, ...), `select`,
+    /// `select-multiple` or `textarea`
+    #[serde(rename = "type")]
+    pub kind: String,
     #[serde(default)]
-    pub http_only: bool,
+    pub label: Option<String>,
+    /// Ref (`@eN`) or CSS selector to fill it with
+    pub selector: String,
     #[serde(default)]
-    pub same_site: Option<CookieSameSite>,
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub readonly: bool,
+    /// Current value; for checkboxes and radio groups, the checked value
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Checkboxes and radio groups only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autocomplete: Option<String>,
+    /// Validation attributes present: pattern, min, max, minlength,
+    /// maxlength, step, accept
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub constraints: std::collections::BTreeMap<String, String>,
+    /// Options of a select or radio group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<FormOption>,
+    /// Whether the current value passes the field's constraints
+    #[serde(default)]
+    pub valid: bool,
+    /// The browser's message when it doesn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_message: Option<String>,
 }
 
-/// Local storage snapshot for a single origin.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct LocalStorageState {
+/// A submit button of a form.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormButton {
     #[serde(default)]
-    pub origin: String,
+    pub label: Option<String>,
+    pub selector: String,
+}
+
+/// Result of `browser.describe_form`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormDescription {
+    /// Ref or CSS selector of the form
+    pub selector: String,
+    /// Submission URL (`<form>` elements only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    /// `get` or `post` (`<form>` elements only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// In document order, radio groups folded into one field
+    pub fields: Vec<FormField>,
+    pub submit: Vec<FormButton>,
+}
+
+/// A field `browser.get_validation_errors` found in error.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FieldError {
+    /// Ref or CSS selector of the field
+    pub selector: String,
     #[serde(default)]
-    pub items: HashMap<String, String>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Error text shown for the field, or the browser's validation message
+    pub messages: Vec<String>,
+    /// How it was flagged: `aria-invalid`, `invalid`, `aria-errormessage`
+    /// or `error-text`
+    pub sources: Vec<String>,
 }
 
-/// Auth state snapshot with cookies and localStorage.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AuthState {
+/// Result of `browser.get_validation_errors`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidationErrors {
+    /// Ref or CSS selector of the form
+    pub selector: String,
+    /// Whether nothing on the form is flagged
+    pub valid: bool,
+    /// In the order they were flagged, mostly document order
+    pub fields: Vec<FieldError>,
+    /// Error text not tied to a single field (e.g. "Please fix the errors
+    /// below")
+    pub form_errors: Vec<String>,
+}
+
+/// A field `browser.autofill_form` set.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutofilledField {
+    /// Data key that matched the field
+    pub key: String,
+    pub selector: String,
     #[serde(default)]
-    pub cookies: Vec<SerializableCookie>,
+    pub name: Option<String>,
     #[serde(default)]
-    pub local_storage: LocalStorageState,
+    pub label: Option<String>,
+    /// The value set: text, `true`/`false`, or option values
+    pub value: String,
+}
+
+/// A data key that matched a field but couldn't be applied to it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutofillFailure {
+    pub key: String,
+    pub selector: String,
+    pub error: String,
+}
+
+/// Result of `browser.autofill_form`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutofillResult {
+    /// Ref or CSS selector of the form
+    pub selector: String,
+    /// In document order
+    pub filled: Vec<AutofilledField>,
+    pub failed: Vec<AutofillFailure>,
+    /// Data keys no field matched
+    pub unmatched: Vec<String>,
+    /// Required fields no key matched that are still empty, by label (or
+    /// name, or selector)
+    pub unfilled_required: Vec<String>,
+}
+
+/// A file as a file input holds it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadedFile {
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// MIME type Chrome inferred from the name (empty when unknown)
+    pub mime_type: String,
+}
+
+/// File upload result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadResult {
+    /// Whether the input holds every file given
+    pub success: bool,
+    /// Element that was changed
+    pub selector: String,
+    /// Files the input holds afterwards
+    pub files: Vec<UploadedFile>,
+    /// Names of files outside the input's `accept` filter
+    pub not_accepted: Vec<String>,
+}
+
+/// Single-file upload result, from `browser.upload`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadPathResult {
+    pub success: bool,
+    /// Element that was changed
+    pub selector: String,
+    /// File given to the input
+    pub path: String,
+}
+
+/// Select option result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SelectResult {
+    /// Whether the options were selected
+    pub success: bool,
+    /// Element that was changed
+    pub selector: String,
+    /// Whether the element allows several selected options
+    pub multiple: bool,
+    /// Options selected afterwards
+    pub selected: Vec<SelectOption>,
+}
+
+/// Playback command for a media element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaAction {
+    Play,
+    Pause,
+    /// Jump to this many seconds
+    Seek(f64),
+}
+
+/// State of an `<audio>` or `<video>` element.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<String>,
+    /// audio or video
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src: Option<String>,
+    /// Seconds; None until metadata has loaded, and for live streams
     #[serde(default)]
-    pub saved_at: String,
+    pub duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub live: bool,
+    pub current_time: f64,
+    pub paused: bool,
+    pub ended: bool,
+    pub muted: bool,
+    pub volume: f64,
+    pub playback_rate: f64,
+    /// HTMLMediaElement.readyState (0 nothing - 4 enough data)
+    pub ready_state: u8,
+    /// Enough data to play (ready_state >= 3)
+    pub playable: bool,
+    /// Seconds buffered from the start of the last buffered range
+    pub buffered_until: f64,
+    /// Video size in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Load or decode failure (MediaError)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-/// Click result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ClickResult {
-    /// Whether click was successful
+/// Media elements on the page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaList {
+    pub media: Vec<MediaState>,
+}
+
+/// Result of a play/pause/seek.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaControlResult {
     pub success: bool,
-    /// Element that was clicked (for debugging)
+    /// Why the command didn't take (e.g. play() rejected)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    pub state: MediaState,
+}
+
+/// Page mute result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaMuteResult {
+    pub muted: bool,
+    /// Media elements on the page now
+    pub elements: usize,
+}
+
+/// Pointer used to draw on a canvas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerKind {
+    #[default]
+    Mouse,
+    /// Pen, with per-point pressure
+    Pen,
+}
+
+/// A point of a canvas stroke: `{x, y, t?, pressure?}`, `[x, y]`, or
+/// `[x, y, t]`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "CanvasPointInput")]
+pub struct CanvasPoint {
+    /// CSS pixels from the canvas's left edge (or a fraction of its width)
+    pub x: f64,
+    pub y: f64,
+    /// Milliseconds since the stroke started (default: evenly spaced)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub t: Option<f64>,
+    /// 0-1, pen only (default 0.5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CanvasPointInput {
+    Pair(f64, f64),
+    Timed(f64, f64, f64),
+    Object {
+        x: f64,
+        y: f64,
+        #[serde(default)]
+        t: Option<f64>,
+        #[serde(default)]
+        pressure: Option<f64>,
+    },
+}
+
+impl From<CanvasPointInput> for CanvasPoint {
+    fn from(input: CanvasPointInput) -> Self {
+        let (x, y, t, pressure) = match input {
+            CanvasPointInput::Pair(x, y) => (x, y, None, None),
+            CanvasPointInput::Timed(x, y, t) => (x, y, Some(t), None),
+            CanvasPointInput::Object { x, y, t, pressure } => (x, y, t, pressure),
+        };
+        CanvasPoint { x, y, t, pressure }
+    }
+}
+
+/// How canvas strokes are replayed.
+#[derive(Debug, Clone, Copy)]
+pub struct CanvasDrawOptions {
+    /// Points are fractions (0-1) of the canvas size rather than pixels
+    pub normalized: bool,
+    /// Milliseconds between points without `t`, and between strokes
+    pub step_ms: u64,
+    pub pointer: PointerKind,
+}
+
+/// Canvas draw result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CanvasDrawResult {
+    pub success: bool,
+    pub strokes: usize,
+    pub points: usize,
+    /// Canvas bounds in viewport CSS pixels while drawing
+    pub rect: ElementRect,
+    pub duration_ms: u64,
+}
+
+/// Result of `drag_and_drop`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DragResult {
+    pub success: bool,
+    pub source: String,
+    pub target: String,
+    /// Pointer moves between source and target
+    pub steps: u32,
+    /// Chrome started a native HTML5 drag, finished with drag events
+    pub native: bool,
+    pub duration_ms: u64,
+}
+
+/// What `fill_rich_text` content is written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RichTextFormat {
+    #[default]
+    Markdown,
+    Html,
+    /// Inserted as-is, without formatting
+    Text,
+}
+
+/// How rich text content got into the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RichTextMethod {
+    /// The editor handled a paste event
+    Paste,
+    /// `execCommand('insertHTML')`
+    InsertHtml,
+    /// `execCommand('insertText')`
+    InsertText,
+}
+
+/// Rich text fill result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RichTextResult {
+    pub success: bool,
+    /// prosemirror, quill, draft, lexical, ckeditor, or contenteditable
+    pub editor: String,
+    pub method: RichTextMethod,
+    /// Whether the editor text differs from before
+    pub changed: bool,
+    /// Editor text afterwards (truncated)
+    pub text: String,
+}
+
+/// Part of an element's text to select.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextRange {
+    /// Characters `start..end` of the element's text, to its end by default
+    Offsets { start: usize, end: Option<usize> },
+    /// The `occurrence`-th (1-based) match of `text`, whitespace-insensitive
+    Text { text: String, occurrence: usize },
+}
+
+/// Text selected on the page or in a focused input or textarea.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TextSelection {
+    /// Selected text (empty if nothing is selected)
+    pub text: String,
+    /// Selected markup (document selections only)
+    #[serde(default)]
+    pub html: Option<String>,
+    /// Whether the selection is empty, a caret at most
+    pub collapsed: bool,
+    /// Ref or CSS path of the element containing the selection
     #[serde(default)]
     pub element: Option<String>,
+    /// Character offsets of the selection in the element's text (or value)
+    #[serde(default)]
+    pub start: Option<usize>,
+    #[serde(default)]
+    pub end: Option<usize>,
 }
 
-/// Fill result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FillResult {
-    /// Whether fill was successful
+/// Outcome of copying the selection to the clipboard.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClipboardCopy {
+    /// Whether the browser ran the copy command
+    pub copied: bool,
+    /// Plain text put on the clipboard
+    pub text: String,
+    #[serde(default)]
+    pub html: Option<String>,
+    /// Whether a page `copy` handler replaced the selected content
+    pub modified: bool,
+}
+
+/// How `set_date` got the date into the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DateStrategy {
+    /// Value set on a native date/time input
+    Native,
+    /// Typed into a text field in the widget's format
+    Typed,
+    /// Picked from the widget's calendar popup
+    Calendar,
+}
+
+/// Date set result.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetDateResult {
     pub success: bool,
-    /// Value that was filled
+    /// Requested date (ISO 8601)
+    pub date: String,
+    pub strategy: DateStrategy,
+    /// strftime format the date was typed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Field value afterwards
     pub value: String,
 }
 
@@ -161,6 +2881,8 @@ mod tests {
             value: None,
             focusable: true,
             focused: false,
+            rect: None,
+            in_viewport: false,
             children: vec![],
         };
 
@@ -175,6 +2897,37 @@ mod tests {
         assert_eq!(parsed.name, Some("Submit".to_string()));
     }
 
+    #[test]
+    fn test_canvas_point_forms() {
+        let points: Vec<CanvasPoint> =
+            serde_json::from_str(r#"[[1, 2], [3, 4, 16], {"x": 5, "y": 6, "pressure": 0.8}]"#)
+                .unwrap();
+
+        assert_eq!(
+            points,
+            vec![
+                CanvasPoint {
+                    x: 1.0,
+                    y: 2.0,
+                    t: None,
+                    pressure: None
+                },
+                CanvasPoint {
+                    x: 3.0,
+                    y: 4.0,
+                    t: Some(16.0),
+                    pressure: None
+                },
+                CanvasPoint {
+                    x: 5.0,
+                    y: 6.0,
+                    t: None,
+                    pressure: Some(0.8)
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_aria_node_deserialization_with_defaults() {
         let json = r#"{"ref_id": "@e5", "role": "link"}"#;
@@ -189,20 +2942,79 @@ mod tests {
         assert!(node.children.is_empty());
     }
 
+    #[test]
+    fn test_aria_snapshot_cached_defaults_false() {
+        let json = r#"{"url": "https://example.com", "title": "Example", "nodes": [], "element_count": 0}"#;
+        let snapshot: AriaSnapshot = serde_json::from_str(json).unwrap();
+
+        assert!(!snapshot.cached);
+        assert_eq!(snapshot.element_count, 0);
+        assert_eq!(snapshot.offset, 0);
+        assert!(snapshot.next_cursor.is_none());
+        assert!(snapshot.scroll.is_none());
+    }
+
+    #[test]
+    fn test_highlight_result_serialization() {
+        let result = HighlightResult {
+            success: true,
+            element: "[data-fgp-ref='e5']".to_string(),
+            rect: Some(ElementRect {
+                x: 10.0,
+                y: 20.0,
+                width: 120.0,
+                height: 32.0,
+            }),
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["rect"]["width"], 120.0);
+
+        let missing: HighlightResult =
+            serde_json::from_str(r#"{"success": false, "element": ".gone"}"#).unwrap();
+        assert!(missing.rect.is_none());
+    }
+
+    #[test]
+    fn test_scroll_state_serialization() {
+        let state = ScrollState {
+            x: 0.0,
+            y: 1240.5,
+            containers: vec![ScrollContainer {
+                selector: "#feed".to_string(),
+                x: 0.0,
+                y: 800.0,
+            }],
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: ScrollState = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, state);
+
+        // Containers are optional when passed back in by callers
+        let window_only: ScrollState = serde_json::from_str(r#"{"x": 0, "y": 300}"#).unwrap();
+        assert!(window_only.containers.is_empty());
+    }
+
     #[test]
     fn test_navigation_result_serialization() {
         let result = NavigationResult {
             url: "https://example.com/page".to_string(),
             title: "Example Page".to_string(),
             status: Some(200),
+            captchas: vec![],
+            wait_until: Some(WaitUntil::DomContentLoaded),
+            load_time_ms: Some(120),
         };
 
         let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""wait_until":"domcontentloaded""#));
         let parsed: NavigationResult = serde_json::from_str(&json).unwrap();
 
         assert_eq!(parsed.url, "https://example.com/page");
         assert_eq!(parsed.title, "Example Page");
         assert_eq!(parsed.status, Some(200));
+        assert_eq!(parsed.wait_until, Some(WaitUntil::DomContentLoaded));
     }
 
     #[test]
@@ -210,8 +3022,13 @@ mod tests {
         let result = ScreenshotResult {
             data: None,
             path: Some("/tmp/screenshot.png".to_string()),
+            artifact: None,
+            format: ImageFormat::Png,
             width: 1920,
             height: 1080,
+            clip: None,
+            page_width: None,
+            page_height: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -225,8 +3042,13 @@ mod tests {
         let result = ScreenshotResult {
             data: Some("iVBORw0KGgo...".to_string()),
             path: None,
+            artifact: None,
+            format: ImageFormat::Jpeg,
             width: 800,
             height: 600,
+            clip: None,
+            page_width: Some(800.0),
+            page_height: Some(4200.0),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -234,6 +3056,99 @@ mod tests {
 
         assert!(parsed.data.is_some());
         assert!(parsed.path.is_none());
+        assert_eq!(parsed.page_height, Some(4200.0));
+        assert_eq!(parsed.format, ImageFormat::Jpeg);
+        assert!(json.contains(r#""format":"jpeg""#));
+    }
+
+    #[test]
+    fn test_browser_status_serialization() {
+        let json = serde_json::to_value(BrowserStatus::Ready { launch_ms: 412 }).unwrap();
+        assert_eq!(json["state"], "ready");
+        assert_eq!(json["launch_ms"], 412);
+
+        let idle = serde_json::to_value(BrowserStatus::Idle).unwrap();
+        assert_eq!(idle, serde_json::json!({"state": "idle"}));
+    }
+
+    #[test]
+    fn test_self_test_report_ready() {
+        let report = SelfTestReport::new(
+            vec![
+                HealthCheck::pass("launch", Some(412.0)),
+                HealthCheck::fail("artifact_disk", "12 MiB free"),
+            ],
+            Some(12 * 1024 * 1024),
+        );
+        assert!(!report.ready);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][0]["duration_ms"], 412.0);
+        assert!(json["checks"][0].get("detail").is_none());
+        assert_eq!(json["checks"][1]["detail"], "12 MiB free");
+
+        assert!(SelfTestReport::new(vec![HealthCheck::pass("launch", None)], None).ready);
+    }
+
+    #[test]
+    fn test_assertion_tagging() {
+        let assertion: Assertion = serde_json::from_value(serde_json::json!({
+            "type": "text",
+            "expected": "Welcome"
+        }))
+        .unwrap();
+        assert_eq!(
+            assertion,
+            Assertion::Text {
+                selector: None,
+                expected: "Welcome".to_string(),
+                exact: false,
+            }
+        );
+
+        let json = serde_json::to_value(Assertion::Count {
+            selector: "li".to_string(),
+            expected: 3,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "count", "selector": "li", "expected": 3})
+        );
+    }
+
+    #[test]
+    fn test_verify_report_counts_failures() {
+        let result = |passed| AssertionResult {
+            passed,
+            assertion: Assertion::Url {
+                pattern: "*".to_string(),
+            },
+            message: String::new(),
+            evidence: AssertionEvidence {
+                actual: serde_json::Value::Null,
+                url: "about:blank".to_string(),
+                screenshot: None,
+            },
+        };
+
+        let report = VerifyReport::new(vec![result(true), result(false), result(true)]);
+        assert!(!report.passed);
+        assert_eq!((report.total, report.failed), (3, 1));
+
+        assert!(VerifyReport::new(vec![result(true)]).passed);
+    }
+
+    #[test]
+    fn test_crawl_options_defaults() {
+        let options: CrawlOptions =
+            serde_json::from_value(serde_json::json!({"url": "https://example.com"})).unwrap();
+        assert_eq!(options.max_pages, 50);
+        assert_eq!(options.depth, 2);
+        assert_eq!(options.concurrency, 2);
+        assert!(options.same_origin);
+        assert!(!options.sitemap);
+        assert!(options.extract.is_empty());
     }
 
     #[test]
@@ -242,6 +3157,8 @@ mod tests {
             id: "session-abc".to_string(),
             url: Some("https://example.com".to_string()),
             active: true,
+            bandwidth: BandwidthUsage::default(),
+            opener: None,
         };
 
         let json = serde_json::to_string(&session).unwrap();
@@ -251,6 +3168,42 @@ mod tests {
         assert!(parsed.active);
     }
 
+    #[test]
+    fn test_session_event_tagging() {
+        let event = SessionEvent::Console {
+            level: "warning".to_string(),
+            text: "deprecated API".to_string(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "console");
+        assert_eq!(json["type"], event.kind());
+        assert_eq!(json["level"], "warning");
+
+        let event = SessionEvent::Response {
+            url: "https://example.com/api".to_string(),
+            status: 404,
+            resource_type: "fetch".to_string(),
+        };
+        assert_eq!(serde_json::to_value(&event).unwrap()["type"], event.kind());
+
+        let parsed: SessionEvent =
+            serde_json::from_str(r#"{"type": "navigation", "url": "https://example.com"}"#)
+                .unwrap();
+        assert!(matches!(parsed, SessionEvent::Navigation { url } if url == "https://example.com"));
+
+        let parsed: SessionEvent = serde_json::from_str(
+            r#"{"type": "notification", "title": "New message", "origin": "https://chat.example.com",
+                "source": "service_worker", "timestamp": "2024-03-15T10:00:00.000Z"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            parsed,
+            SessionEvent::Notification(WebNotification { title, body: None, source: NotificationSource::ServiceWorker, .. })
+                if title == "New message"
+        ));
+    }
+
     #[test]
     fn test_auth_state_serialization() {
         let state = AuthState {
@@ -291,6 +3244,9 @@ mod tests {
         let result = ClickResult {
             success: true,
             element: Some("button#submit".to_string()),
+            button: ClickButton::Right,
+            click_count: 1,
+            modifiers: vec!["Shift".to_string()],
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -298,19 +3254,32 @@ mod tests {
 
         assert!(parsed.success);
         assert_eq!(parsed.element, Some("button#submit".to_string()));
+        assert_eq!(parsed.button, ClickButton::Right);
+        assert_eq!(parsed.modifiers, vec!["Shift"]);
+
+        let parsed: ClickResult = serde_json::from_str(r#"{"success": true}"#).unwrap();
+        assert_eq!((parsed.button, parsed.click_count), (ClickButton::Left, 1));
     }
 
     #[test]
     fn test_fill_result() {
         let result = FillResult {
             success: true,
-            value: "test@example.com".to_string(),
+            value: "@example.com".to_string(),
+            appended: true,
+            previous_value: "test".to_string(),
+            field_value: "test@example.com".to_string(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
         let parsed: FillResult = serde_json::from_str(&json).unwrap();
 
         assert!(parsed.success);
-        assert_eq!(parsed.value, "test@example.com");
+        assert!(parsed.appended);
+        assert_eq!(parsed.field_value, "test@example.com");
+
+        let parsed: FillResult =
+            serde_json::from_str(r#"{"success": true, "value": "x"}"#).unwrap();
+        assert!(!parsed.appended);
     }
 }
@@ -0,0 +1,381 @@
+//! Read-only observers attached to a session.
+//!
+//! An observer handle lets a supervisor (a human watching in a dashboard, a
+//! second agent) follow a session without being able to drive it. The handle
+//! is passed as `session_id`; the service resolves it to the observed
+//! session, but only for the methods in `READ_ONLY_METHODS` and dry runs of
+//! interactions. Session events are buffered per observer so they can be
+//! polled with `since`, optionally starting with those the session recorded
+//! before the observer joined.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::models::{DryRunAction, ObservedEvent, ObserverEvents, SessionEvent};
+
+/// Prefix of observer handles, so they can't collide with session IDs.
+pub const OBSERVER_PREFIX: &str = "observer-";
+
+/// Events buffered per observer before the oldest are dropped.
+pub const EVENT_BUFFER: usize = 1000;
+
+/// Methods an observer may call (with or without the `browser.` prefix).
+pub const READ_ONLY_METHODS: &[&str] = &[
+    "snapshot",
+    "screenshot",
+    "screenshot_after_paint",
+    "compare_screenshots",
+    "pdf",
+    "canvas.capture",
+    "media.state",
+    "get_notifications",
+    "get_environment",
+    "session.events",
+    "wait_for_stable",
+    "timeline.index",
+    "nearest",
+    "summarize_results",
+    "within",
+    "table_cell",
+    "screencast.start",
+    "screencast.frames",
+    "assert_text",
+    "assert_visible",
+    "assert_url",
+    "assert_count",
+    "verify",
+];
+
+/// Screenshot options that would change what the observed page shows.
+const VISIBLE_SCREENSHOT_PARAMS: &[&str] = &["marker", "scale"];
+
+/// Screencast settings, which only the session's owner may choose.
+const SCREENCAST_PARAMS: &[&str] = &["quality", "max_width", "max_height"];
+
+/// Refused call from an observer handle.
+#[derive(Debug)]
+pub struct ObserverReadOnly {
+    pub method: String,
+}
+
+impl std::fmt::Display for ObserverReadOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "OBSERVER_READ_ONLY: observers can't call '{}'",
+            self.method
+        )
+    }
+}
+
+impl std::error::Error for ObserverReadOnly {}
+
+/// Whether an observer may make this call; `streaming` tells whether the
+/// observed session has a frame stream running.
+pub fn check_call(
+    method: &str,
+    params: &HashMap<String, Value>,
+    streaming: bool,
+) -> Result<(), ObserverReadOnly> {
+    let name = method.strip_prefix("browser.").unwrap_or(method);
+    let refused = || ObserverReadOnly {
+        method: method.to_string(),
+    };
+
+    // Dry runs only read the page
+    let dry_run = params.get("dry_run").and_then(|v| v.as_bool()) == Some(true);
+    if dry_run && DryRunAction::from_method(method).is_some() {
+        return Ok(());
+    }
+    if !READ_ONLY_METHODS.contains(&name) {
+        return Err(refused());
+    }
+    // Markers draw into the page and scale overrides the page's emulation
+    if (name == "screenshot" || name == "screenshot_after_paint")
+        && VISIBLE_SCREENSHOT_PARAMS.iter().any(|p| {
+            params
+                .get(*p)
+                .is_some_and(|v| !v.is_null() && v.as_bool() != Some(false))
+        })
+    {
+        return Err(refused());
+    }
+    // A subtree root is marked in the observed page
+    if name == "snapshot" && params.get("root").is_some_and(|v| !v.is_null()) {
+        return Err(refused());
+    }
+    // Clicking first drives the page
+    if name == "screenshot_after_paint" && params.get("click").is_some_and(|v| !v.is_null()) {
+        return Err(refused());
+    }
+    // Observers may join the session's frame stream, not start or tune one
+    if name == "screencast.start"
+        && (!streaming
+            || SCREENCAST_PARAMS
+                .iter()
+                .any(|p| params.get(*p).is_some_and(|v| !v.is_null())))
+    {
+        return Err(refused());
+    }
+    // Clearing would empty the inbox under the session's owner
+    if name == "get_notifications" && params.get("clear").and_then(|v| v.as_bool()) == Some(true) {
+        return Err(refused());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct EventLog {
+    events: VecDeque<ObservedEvent>,
+    next_seq: u64,
+    /// Events the broadcast channel dropped before we read them
+    missed: u64,
+}
+
+impl EventLog {
+    fn push(&mut self, event: SessionEvent) {
+        self.push_at(Utc::now().to_rfc3339(), event);
+    }
+
+    fn push_at(&mut self, at: String, event: SessionEvent) {
+        self.events.push_back(ObservedEvent {
+            seq: self.next_seq,
+            at,
+            event,
+        });
+        self.next_seq += 1;
+        while self.events.len() > EVENT_BUFFER {
+            self.events.pop_front();
+        }
+    }
+}
+
+/// A read-only handle on a session.
+pub struct Observer {
+    pub id: String,
+    pub session_id: String,
+    log: Arc<Mutex<EventLog>>,
+    task: JoinHandle<()>,
+}
+
+impl Observer {
+    /// Start buffering `events` from `session_id`, after the `backlog` the
+    /// session recorded earlier.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn spawn(
+        session_id: &str,
+        mut events: broadcast::Receiver<SessionEvent>,
+        backlog: Vec<ObservedEvent>,
+    ) -> Self {
+        let mut initial = EventLog::default();
+        for event in backlog {
+            initial.push_at(event.at, event.event);
+        }
+        let log = Arc::new(Mutex::new(initial));
+        let task = {
+            let log = Arc::clone(&log);
+            tokio::spawn(async move {
+                loop {
+                    match events.recv().await {
+                        Ok(event) => log.lock().unwrap_or_else(|e| e.into_inner()).push(event),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            log.lock().unwrap_or_else(|e| e.into_inner()).missed += n;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            })
+        };
+
+        Self {
+            id: format!("{}{}", OBSERVER_PREFIX, uuid::Uuid::new_v4().simple()),
+            session_id: session_id.to_string(),
+            log,
+            task,
+        }
+    }
+
+    /// Buffered events with a sequence number of `since` or later.
+    pub fn events(&self, since: u64) -> ObserverEvents {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        ObserverEvents {
+            observer_id: self.id.clone(),
+            session_id: self.session_id.clone(),
+            events: log
+                .events
+                .iter()
+                .filter(|e| e.seq >= since)
+                .cloned()
+                .collect(),
+            next: log.next_seq,
+            missed: log.missed,
+        }
+    }
+
+    /// Drop all but the newest `keep` buffered events; returns how many
+    /// went.
+    pub fn trim(&self, keep: usize) -> u64 {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        let excess = log.events.len().saturating_sub(keep);
+        log.events.drain(..excess);
+        excess as u64
+    }
+}
+
+impl Drop for Observer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_read_only_methods_are_allowed() {
+        assert!(check_call("browser.snapshot", &params(json!({})), false).is_ok());
+        assert!(check_call("screenshot", &params(json!({"artifact": true})), false).is_ok());
+        assert!(check_call("browser.timeline.index", &params(json!({})), false).is_ok());
+    }
+
+    #[test]
+    fn test_interactions_are_refused() {
+        for method in [
+            "browser.click",
+            "fill",
+            "browser.open",
+            "browser.session.close",
+            "browser.screencast.start",
+            "live_view",
+        ] {
+            let err = check_call(method, &params(json!({})), false).unwrap_err();
+            assert!(err.to_string().starts_with("OBSERVER_READ_ONLY"));
+        }
+    }
+
+    #[test]
+    fn test_observers_only_join_running_streams() {
+        let start = "browser.screencast.start";
+        assert!(check_call(start, &params(json!({})), true).is_ok());
+        assert!(check_call(start, &params(json!({})), false).is_err());
+        assert!(check_call(start, &params(json!({"quality": 90})), true).is_err());
+        assert!(check_call(start, &params(json!({"max_width": null})), true).is_ok());
+    }
+
+    #[test]
+    fn test_dry_runs_are_allowed() {
+        assert!(check_call("browser.click", &params(json!({"dry_run": true})), false).is_ok());
+        assert!(check_call("fill", &params(json!({"dry_run": true})), false).is_ok());
+        assert!(check_call("browser.click", &params(json!({"dry_run": false})), false).is_err());
+        assert!(check_call("browser.open", &params(json!({"dry_run": true})), false).is_err());
+    }
+
+    #[test]
+    fn test_visible_screenshot_options_are_refused() {
+        assert!(check_call(
+            "browser.screenshot",
+            &params(json!({"marker": true})),
+            false
+        )
+        .is_err());
+        assert!(check_call("browser.screenshot", &params(json!({"scale": 2})), false).is_err());
+        assert!(check_call(
+            "browser.screenshot",
+            &params(json!({"marker": false})),
+            false
+        )
+        .is_ok());
+        assert!(check_call(
+            "screenshot_after_paint",
+            &params(json!({"frames": 2})),
+            false
+        )
+        .is_ok());
+        assert!(check_call(
+            "screenshot_after_paint",
+            &params(json!({"click": "@e3"})),
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_notifications_are_read_but_not_cleared() {
+        assert!(check_call("browser.get_notifications", &params(json!({})), false).is_ok());
+        assert!(check_call("get_notifications", &params(json!({"clear": true})), false).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_roots_are_refused() {
+        assert!(check_call("snapshot", &params(json!({"interactive": true})), false).is_ok());
+        assert!(check_call("browser.snapshot", &params(json!({"root": "@e4"})), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_events_are_buffered_from_subscription() {
+        let (sender, receiver) = broadcast::channel(16);
+        let observer = Observer::spawn("default", receiver, Vec::new());
+        sender
+            .send(SessionEvent::Navigation {
+                url: "https://example.com/".to_string(),
+            })
+            .unwrap();
+        sender
+            .send(SessionEvent::Console {
+                level: "log".to_string(),
+                text: "hi".to_string(),
+            })
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        let all = observer.events(0);
+        assert_eq!(all.events.len(), 2);
+        assert_eq!(all.next, 2);
+
+        let newer = observer.events(1);
+        assert_eq!(newer.events.len(), 1);
+        assert!(matches!(
+            newer.events[0].event,
+            SessionEvent::Console { .. }
+        ));
+        assert!(observer.id.starts_with(OBSERVER_PREFIX));
+    }
+
+    #[tokio::test]
+    async fn test_backlog_comes_first() {
+        let (sender, receiver) = broadcast::channel(16);
+        let backlog = vec![ObservedEvent {
+            seq: 41,
+            at: "2024-03-15T10:00:00+00:00".to_string(),
+            event: SessionEvent::Navigation {
+                url: "https://example.com/".to_string(),
+            },
+        }];
+        let observer = Observer::spawn("default", receiver, backlog);
+        sender
+            .send(SessionEvent::Console {
+                level: "log".to_string(),
+                text: "hi".to_string(),
+            })
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        let all = observer.events(0);
+        assert_eq!(all.next, 2);
+        assert_eq!(all.events[0].seq, 0);
+        assert_eq!(all.events[0].at, "2024-03-15T10:00:00+00:00");
+        assert!(matches!(all.events[1].event, SessionEvent::Console { .. }));
+    }
+}
@@ -0,0 +1,257 @@
+//! Per-session video recordings.
+//!
+//! A recording runs the session's CDP screencast and feeds its JPEG frames
+//! to an `ffmpeg` child process, which encodes them to WebM (VP9) or MP4
+//! (H.264). Chrome only sends a frame when the page repaints, so frames are
+//! written at a fixed rate, repeating the latest one while the page is
+//! still; the video plays back in real time. Frames are scaled and padded to
+//! the screencast bounds so viewport changes don't break the encoder.
+//!
+//! `ffmpeg` is looked up on `PATH`, or taken from `FGP_FFMPEG`. The
+//! screencast is shared with the session's live view and frame stream, so
+//! the video takes the bounds it already runs at, and keeps recording when
+//! those stop.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::browser::{Frame, Screencast, ScreencastOptions};
+use crate::models::{RecordingResult, VideoFormat};
+
+/// Frames written per second unless a recording asks otherwise.
+pub const DEFAULT_FPS: u32 = 10;
+
+/// Highest frame rate a recording may ask for.
+pub const MAX_FPS: u32 = 30;
+
+/// Environment variable naming the ffmpeg binary.
+const FFMPEG_ENV: &str = "FGP_FFMPEG";
+
+/// A recording in progress.
+pub struct Recording {
+    session_id: String,
+    format: VideoFormat,
+    fps: u32,
+    path: PathBuf,
+    started_at: String,
+    started: Instant,
+    screencast: Screencast,
+    encoder: Child,
+    stop: oneshot::Sender<()>,
+    writer: JoinHandle<Result<u64>>,
+}
+
+/// Arguments for ffmpeg reading MJPEG on stdin and writing `path`.
+fn ffmpeg_args(
+    format: VideoFormat,
+    fps: u32,
+    options: &ScreencastOptions,
+    path: &Path,
+) -> Vec<String> {
+    // Encoders want even dimensions
+    let (width, height) = (options.max_width / 2 * 2, options.max_height / 2 * 2);
+    let mut args: Vec<String> = [
+        "-hide_banner",
+        "-loglevel",
+        "error",
+        "-y",
+        "-f",
+        "image2pipe",
+        "-c:v",
+        "mjpeg",
+        "-framerate",
+    ]
+    .map(str::to_string)
+    .to_vec();
+    args.push(fps.to_string());
+    args.extend(["-i", "-", "-vf"].map(str::to_string));
+    args.push(format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+        w = width,
+        h = height
+    ));
+    let codec: &[&str] = match format {
+        VideoFormat::Webm => &[
+            "-c:v",
+            "libvpx-vp9",
+            "-deadline",
+            "realtime",
+            "-b:v",
+            "0",
+            "-crf",
+            "40",
+        ],
+        VideoFormat::Mp4 => &[
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-pix_fmt",
+            "yuv420p",
+            "-movflags",
+            "+faststart",
+        ],
+    };
+    args.extend(codec.iter().map(|arg| arg.to_string()));
+    args.push(path.display().to_string());
+    args
+}
+
+/// Write the latest frame `fps` times a second until told to stop or the
+/// screencast ends; returns the number of frames written.
+async fn write_frames(
+    mut frames: broadcast::Receiver<Frame>,
+    mut latest: Option<Frame>,
+    mut stdin: ChildStdin,
+    fps: u32,
+    mut stop: oneshot::Receiver<()>,
+) -> Result<u64> {
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1) / fps);
+    let mut written = 0;
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            _ = ticker.tick() => {
+                if let Some(frame) = &latest {
+                    stdin
+                        .write_all(frame)
+                        .await
+                        .context("Video encoder stopped accepting frames")?;
+                    written += 1;
+                }
+            }
+            frame = frames.recv() => match frame {
+                Ok(frame) => latest = Some(frame),
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+    // Closing stdin lets ffmpeg finish the file
+    stdin.shutdown().await.ok();
+    Ok(written)
+}
+
+impl Recording {
+    /// Start encoding `screencast` to `path`.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn start(
+        session_id: &str,
+        screencast: Screencast,
+        format: VideoFormat,
+        fps: u32,
+        path: PathBuf,
+    ) -> Result<Self> {
+        let ffmpeg = std::env::var(FFMPEG_ENV).unwrap_or_else(|_| "ffmpeg".to_string());
+        let mut encoder = match Command::new(&ffmpeg)
+            .args(ffmpeg_args(format, fps, &screencast.options(), &path))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(encoder) => encoder,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(
+                "RECORDING_UNAVAILABLE: '{}' not found; install ffmpeg or set {}",
+                ffmpeg,
+                FFMPEG_ENV
+            ),
+            Err(e) => return Err(e).context("Failed to start video encoder"),
+        };
+        let stdin = encoder.stdin.take().context("Video encoder has no stdin")?;
+
+        let (latest, frames) = screencast.subscribe();
+        let (stop, stopped) = oneshot::channel();
+        let writer = tokio::spawn(write_frames(frames, latest, stdin, fps, stopped));
+
+        Ok(Self {
+            session_id: session_id.to_string(),
+            format,
+            fps,
+            path,
+            started_at: Utc::now().to_rfc3339(),
+            started: Instant::now(),
+            screencast,
+            encoder,
+            stop,
+            writer,
+        })
+    }
+
+    /// Stop recording and wait for the video to be written.
+    pub async fn finish(self) -> Result<RecordingResult> {
+        let _ = self.stop.send(());
+        let written = self.writer.await.context("Recording writer task failed")?;
+        self.screencast.stop().await;
+
+        let output = self
+            .encoder
+            .wait_with_output()
+            .await
+            .context("Video encoder failed")?;
+        // The encoder's own error explains a failed write best
+        if !output.status.success() {
+            anyhow::bail!(
+                "Video encoder failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let frames = written?;
+        if frames == 0 {
+            anyhow::bail!("Recording captured no frames");
+        }
+
+        let size = tokio::fs::metadata(&self.path)
+            .await
+            .with_context(|| format!("Failed to read {}", self.path.display()))?
+            .len();
+        Ok(RecordingResult {
+            session_id: self.session_id,
+            path: self.path.display().to_string(),
+            format: self.format,
+            started_at: self.started_at,
+            duration_ms: frames * 1000 / u64::from(self.fps),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            frames,
+            fps: self.fps,
+            size,
+            artifact: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffmpeg_args() {
+        let options = ScreencastOptions {
+            quality: 60,
+            max_width: 1279,
+            max_height: 800,
+        };
+        let args = ffmpeg_args(VideoFormat::Mp4, 10, &options, Path::new("/tmp/run.mp4"));
+        let position = |arg: &str| args.iter().position(|a| a == arg).unwrap();
+
+        assert_eq!(args[position("-framerate") + 1], "10");
+        assert_eq!(args[position("-c:v") + 1], "mjpeg");
+        assert!(args[position("-vf") + 1].starts_with("scale=1278:800:"));
+        assert!(args.iter().any(|a| a == "libx264"));
+        assert_eq!(args.last().map(String::as_str), Some("/tmp/run.mp4"));
+
+        let webm = ffmpeg_args(VideoFormat::Webm, 5, &options, Path::new("run.webm"));
+        assert!(webm.iter().any(|a| a == "libvpx-vp9"));
+    }
+}
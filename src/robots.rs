@@ -0,0 +1,373 @@
+//! robots.txt-aware navigation.
+//!
+//! When enabled, every navigation first consults the target origin's
+//! robots.txt (fetched once per origin and cached) and is refused with
+//! [`RobotsDisallowed`] if the path is disallowed for our user-agent token.
+//! Documents pages load by themselves are held to the same rules by
+//! `browser::gate`.
+//! Parsing and matching follow RFC 9309: the most specific (longest) rule
+//! wins, `Allow` wins ties, `*` matches any run of characters, and a trailing
+//! `$` anchors the end of the path.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::throttle::origin_of;
+
+/// How long a fetched robots.txt is trusted.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long an unreachable (or 5xx) robots.txt disallows its origin before
+/// it is fetched again.
+const FAILURE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Largest robots.txt we parse; RFC 9309 requires at least 500 KiB.
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+/// Default product token matched against `User-agent` lines.
+pub const DEFAULT_USER_AGENT: &str = "fgp-browser";
+
+/// Navigation refused because robots.txt disallows the path.
+#[derive(Debug, Clone)]
+pub struct RobotsDisallowed {
+    pub url: String,
+    pub user_agent: String,
+}
+
+impl fmt::Display for RobotsDisallowed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ROBOTS_DISALLOWED: robots.txt disallows {} for user-agent '{}'",
+            self.url, self.user_agent
+        )
+    }
+}
+
+impl std::error::Error for RobotsDisallowed {}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// Rules from one robots.txt that apply to our user-agent.
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+}
+
+impl RobotsRules {
+    /// Everything allowed (no robots.txt, or a 4xx fetching it).
+    fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Everything disallowed (robots.txt unreachable or 5xx).
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![Rule {
+                allow: false,
+                pattern: "/".to_string(),
+            }],
+        }
+    }
+
+    /// Parse `text`, keeping the groups for `user_agent` (or `*` if none match).
+    pub fn parse(text: &str, user_agent: &str) -> Self {
+        let user_agent = user_agent.to_ascii_lowercase();
+        let mut groups: Vec<(Vec<String>, Vec<Rule>)> = Vec::new();
+        let mut reading_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !reading_agents {
+                        groups.push((Vec::new(), Vec::new()));
+                        reading_agents = true;
+                    }
+                    if let Some((agents, _)) = groups.last_mut() {
+                        agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    reading_agents = false;
+                    // An empty Disallow means "nothing disallowed"
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some((_, rules)) = groups.last_mut() {
+                        rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Groups naming our token replace the `*` groups entirely
+        let rules_for = |agent: &str| -> Option<Vec<Rule>> {
+            let mut found = false;
+            let mut rules = Vec::new();
+            for (agents, group_rules) in &groups {
+                if agents.iter().any(|a| a == agent) {
+                    found = true;
+                    rules.extend(group_rules.iter().cloned());
+                }
+            }
+            found.then_some(rules)
+        };
+
+        Self {
+            rules: rules_for(&user_agent)
+                .or_else(|| rules_for("*"))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether `path` (path plus optional `?query`) may be fetched.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let decisive = self
+            .rules
+            .iter()
+            .filter(|rule| rule_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow));
+
+        match decisive {
+            Some(rule) => rule.allow,
+            None => true,
+        }
+    }
+}
+
+/// Match a robots.txt path pattern against `path`.
+fn rule_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    wildcard_match(pattern.as_bytes(), path.as_bytes(), anchored)
+}
+
+/// `*` matches any run of bytes; unanchored patterns only need to match a prefix.
+fn wildcard_match(pattern: &[u8], text: &[u8], anchored: bool) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    loop {
+        if p == pattern.len() && (!anchored || t == text.len()) {
+            return true;
+        }
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+            continue;
+        }
+        if p < pattern.len() && t < text.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+            continue;
+        }
+        match backtrack {
+            Some((star, matched)) if matched < text.len() => {
+                p = star + 1;
+                t = matched + 1;
+                backtrack = Some((star, t));
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Fetches, caches, and applies robots.txt per origin.
+pub struct RobotsPolicy {
+    user_agent: String,
+    http: reqwest::Client,
+    /// Rules by origin, with when they expire
+    cache: Mutex<HashMap<String, (Instant, Arc<RobotsRules>)>>,
+}
+
+impl RobotsPolicy {
+    pub fn new(user_agent: &str) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self {
+            user_agent: user_agent.to_string(),
+            http,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Refuse `url` with [`RobotsDisallowed`] if its robots.txt disallows it.
+    ///
+    /// URLs without a network origin (`about:`, `data:`, `file:`) always pass.
+    pub async fn check(&self, url: &str) -> Result<()> {
+        let Some(origin) = origin_of(url) else {
+            return Ok(());
+        };
+        let parsed = url::Url::parse(url).context("Invalid URL")?;
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+
+        if self.rules_for(&origin).await.is_allowed(&path) {
+            Ok(())
+        } else {
+            Err(RobotsDisallowed {
+                url: url.to_string(),
+                user_agent: self.user_agent.clone(),
+            }
+            .into())
+        }
+    }
+
+    async fn rules_for(&self, origin: &str) -> Arc<RobotsRules> {
+        if let Some((expires, rules)) = self.lock_cache().get(origin) {
+            if Instant::now() < *expires {
+                return Arc::clone(rules);
+            }
+        }
+
+        let (rules, ttl) = self.fetch(origin).await;
+        let rules = Arc::new(rules);
+        self.lock_cache().insert(
+            origin.to_string(),
+            (Instant::now() + ttl, Arc::clone(&rules)),
+        );
+        rules
+    }
+
+    /// The origin's rules, and how long to trust them: failures are retried
+    /// after `FAILURE_TTL` rather than shutting the origin out for a day.
+    async fn fetch(&self, origin: &str) -> (RobotsRules, Duration) {
+        let url = format!("{}/robots.txt", origin);
+        let failed = (RobotsRules::disallow_all(), FAILURE_TTL);
+        let response = match self.http.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Failed to fetch {}: {} - treating as disallow-all", url, e);
+                return failed;
+            }
+        };
+
+        let status = response.status();
+        if status.is_client_error() {
+            return (RobotsRules::allow_all(), CACHE_TTL);
+        }
+        if !status.is_success() {
+            tracing::warn!("{} returned {} - treating as disallow-all", url, status);
+            return failed;
+        }
+
+        match response.bytes().await {
+            Ok(body) => {
+                let body = &body[..body.len().min(MAX_ROBOTS_BYTES)];
+                (
+                    RobotsRules::parse(&String::from_utf8_lossy(body), &self.user_agent),
+                    CACHE_TTL,
+                )
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read {}: {} - treating as disallow-all", url, e);
+                failed
+            }
+        }
+    }
+
+    fn lock_cache(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, Arc<RobotsRules>)>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+# Example
+User-agent: *
+Disallow: /private/
+Allow: /private/press-kit
+Disallow: /*.pdf$
+Disallow: /search?
+
+User-agent: fgp-browser
+User-agent: otherbot
+Disallow: /admin
+";
+
+    #[test]
+    fn test_specific_agent_group_replaces_star() {
+        let rules = RobotsRules::parse(ROBOTS, "FGP-Browser");
+        assert!(!rules.is_allowed("/admin/users"));
+        // The * group doesn't apply once a specific group matches
+        assert!(rules.is_allowed("/private/notes"));
+    }
+
+    #[test]
+    fn test_star_group_longest_match_wins() {
+        let rules = RobotsRules::parse(ROBOTS, "somebot");
+        assert!(rules.is_allowed("/"));
+        assert!(!rules.is_allowed("/private/notes"));
+        assert!(rules.is_allowed("/private/press-kit/logo.png"));
+        assert!(!rules.is_allowed("/docs/report.pdf"));
+        assert!(rules.is_allowed("/docs/report.pdf.html"));
+        assert!(!rules.is_allowed("/search?q=shoes"));
+        assert!(rules.is_allowed("/admin"));
+    }
+
+    #[test]
+    fn test_allow_wins_ties_and_empty_disallow() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow: /a\nAllow: /a\n", "x");
+        assert!(rules.is_allowed("/a"));
+
+        let rules = RobotsRules::parse("User-agent: *\nDisallow:\n", "x");
+        assert!(rules.is_allowed("/anything"));
+    }
+
+    #[test]
+    fn test_fetch_failure_defaults() {
+        assert!(RobotsRules::allow_all().is_allowed("/x"));
+        assert!(!RobotsRules::disallow_all().is_allowed("/x"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_robots_is_retried_soon() {
+        let policy = RobotsPolicy::new(DEFAULT_USER_AGENT).unwrap();
+        // Nothing listens on port 1
+        let origin = "http://127.0.0.1:1";
+        assert!(!policy.rules_for(origin).await.is_allowed("/"));
+
+        let (expires, _) = policy.lock_cache()[origin].clone();
+        assert!(expires <= Instant::now() + FAILURE_TTL);
+    }
+
+    #[test]
+    fn test_disallowed_error_has_code() {
+        let err: anyhow::Error = RobotsDisallowed {
+            url: "https://example.com/admin".to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+        .into();
+        assert!(err.to_string().starts_with("ROBOTS_DISALLOWED"));
+        assert!(err.downcast_ref::<RobotsDisallowed>().is_some());
+    }
+}
@@ -0,0 +1,304 @@
+//! JSON Schemas of the daemon's responses, for clients in other languages.
+//!
+//! Every model a method returns is exported together with the models it
+//! contains, as draft-07 definitions keyed by type name, so a client can
+//! generate its types instead of tracking `models.rs` by hand; `methods`
+//! maps each method to the models it returns. Every response is an object
+//! and carries `protocol_version`; a client compares it with the
+//! version its types were generated from to notice a daemon that has moved
+//! on.
+//!
+//! Bump `PROTOCOL_VERSION` when a response changes incompatibly: a field
+//! removed, renamed or retyped, or an enum value dropped. New optional
+//! fields don't need a bump.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::models::*;
+
+/// Version of the response models.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Adds a response model to a generator's definitions; returns its name.
+type Model = fn(&mut SchemaGenerator) -> String;
+
+/// Add `T` (and what it contains) to `gen`'s definitions; returns its name.
+fn response<T: JsonSchema>(gen: &mut SchemaGenerator) -> String {
+    gen.subschema_for::<T>();
+    T::schema_name()
+}
+
+/// What each method returns; the element actions that take `dry_run` also
+/// list the `DryRunReport` they return with it. Extension methods aren't
+/// listed: they pass on whatever the Chrome extension answers.
+const METHODS: &[(&str, &[Model])] = &[
+    ("health", &[response::<HealthStatus>]),
+    ("browser.schemas", &[response::<ResponseSchemas>]),
+    ("browser.open", &[response::<NavigationResult>]),
+    ("browser.go_back", &[response::<NavigationResult>]),
+    ("browser.go_forward", &[response::<NavigationResult>]),
+    ("browser.reload", &[response::<NavigationResult>]),
+    (
+        "browser.snapshot",
+        &[response::<AriaSnapshot>, response::<AriaTextSnapshot>],
+    ),
+    ("browser.snapshot_diff", &[response::<AriaSnapshotDiff>]),
+    ("browser.screenshot", &[response::<ScreenshotResult>]),
+    (
+        "browser.screenshot_element",
+        &[response::<ScreenshotResult>],
+    ),
+    (
+        "browser.screenshot_after_paint",
+        &[response::<ScreenshotResult>],
+    ),
+    (
+        "browser.compare_screenshots",
+        &[response::<ScreenshotComparison>],
+    ),
+    ("browser.print_preview", &[response::<PrintPreview>]),
+    ("browser.pdf", &[response::<PdfResult>]),
+    ("browser.canvas.draw", &[response::<CanvasDrawResult>]),
+    ("browser.canvas.capture", &[response::<ScreenshotResult>]),
+    ("browser.media.state", &[response::<MediaList>]),
+    ("browser.media.play", &[response::<MediaControlResult>]),
+    ("browser.media.pause", &[response::<MediaControlResult>]),
+    ("browser.media.seek", &[response::<MediaControlResult>]),
+    ("browser.media.mute", &[response::<MediaMuteResult>]),
+    ("browser.csp_trial", &[response::<CspTrialResult>]),
+    (
+        "browser.get_third_party_report",
+        &[response::<ThirdPartyReport>],
+    ),
+    ("browser.get_environment", &[response::<EnvironmentSummary>]),
+    ("browser.cdp", &[response::<CdpResult>]),
+    (
+        "browser.locale_variants",
+        &[response::<LocaleVariantReport>],
+    ),
+    ("browser.check_links", &[response::<LinkCheckReport>]),
+    ("browser.detect_blockers", &[response::<BlockerReport>]),
+    ("browser.get_notifications", &[response::<NotificationList>]),
+    ("browser.export_refs", &[response::<RefExport>]),
+    ("browser.artifact.get", &[response::<ArtifactData>]),
+    ("browser.artifact.gc", &[response::<GcReport>]),
+    ("browser.maintain", &[response::<MaintenanceReport>]),
+    ("browser.crawl", &[response::<CrawlStarted>]),
+    ("browser.crawl.status", &[response::<CrawlStatus>]),
+    ("browser.crawl.cancel", &[response::<CrawlCancelled>]),
+    ("browser.timeline.auto", &[response::<TimelineAuto>]),
+    ("browser.timeline.capture", &[response::<TimelineFrame>]),
+    ("browser.timeline.index", &[response::<TimelineIndex>]),
+    ("browser.timeline.clear", &[response::<TimelineCleared>]),
+    ("browser.recording.start", &[response::<RecordingStarted>]),
+    ("browser.recording.stop", &[response::<RecordingResult>]),
+    ("browser.assert_text", &[response::<AssertionResult>]),
+    ("browser.assert_visible", &[response::<AssertionResult>]),
+    ("browser.assert_url", &[response::<AssertionResult>]),
+    ("browser.assert_count", &[response::<AssertionResult>]),
+    ("browser.verify", &[response::<VerifyReport>]),
+    (
+        "browser.click",
+        &[response::<ClickResult>, response::<DryRunReport>],
+    ),
+    (
+        "browser.fill",
+        &[response::<FillResult>, response::<DryRunReport>],
+    ),
+    ("browser.focus", &[response::<FocusResult>]),
+    ("browser.blur", &[response::<FocusResult>]),
+    ("browser.clear", &[response::<ClearResult>]),
+    ("browser.type_text", &[response::<TypeTextResult>]),
+    ("browser.fill_rich_text", &[response::<RichTextResult>]),
+    ("browser.select_text", &[response::<TextSelection>]),
+    ("browser.get_selection", &[response::<TextSelection>]),
+    ("browser.copy_selection", &[response::<ClipboardCopy>]),
+    ("browser.press", &[response::<KeyPressResult>]),
+    ("browser.press_key", &[response::<KeyPressResult>]),
+    (
+        "browser.keyboard_layout",
+        &[response::<KeyboardLayoutResult>],
+    ),
+    ("browser.popup_policy", &[response::<PopupPolicyResult>]),
+    ("browser.set_zoom", &[response::<ZoomResult>]),
+    ("browser.set_timeouts", &[response::<TimeoutSettings>]),
+    ("browser.set_fake_clock", &[response::<FakeClockResult>]),
+    ("browser.fast_forward", &[response::<FastForwardResult>]),
+    ("browser.clear_fake_clock", &[response::<FakeClockResult>]),
+    (
+        "browser.select",
+        &[response::<SelectValueResult>, response::<DryRunReport>],
+    ),
+    ("browser.select_option", &[response::<SelectResult>]),
+    (
+        "browser.check",
+        &[response::<CheckResult>, response::<DryRunReport>],
+    ),
+    (
+        "browser.hover",
+        &[response::<HoverResult>, response::<DryRunReport>],
+    ),
+    ("browser.drag_and_drop", &[response::<DragResult>]),
+    ("browser.set_date", &[response::<SetDateResult>]),
+    ("browser.nearest", &[response::<SpatialResult>]),
+    ("browser.within", &[response::<SpatialResult>]),
+    ("browser.find_elements", &[response::<FoundElements>]),
+    ("browser.summarize_results", &[response::<SearchResults>]),
+    ("browser.table_cell", &[response::<TableCell>]),
+    ("browser.click_cell", &[response::<TableCell>]),
+    ("browser.describe_form", &[response::<FormDescription>]),
+    ("browser.autofill_form", &[response::<AutofillResult>]),
+    (
+        "browser.get_validation_errors",
+        &[response::<ValidationErrors>],
+    ),
+    ("browser.highlight", &[response::<HighlightResult>]),
+    ("browser.cursor_overlay", &[response::<CursorOverlayResult>]),
+    ("browser.scroll", &[response::<ScrollResult>]),
+    ("browser.scroll_to_element", &[response::<ScrollResult>]),
+    ("browser.scroll_by", &[response::<ScrollResult>]),
+    ("browser.scroll_to_bottom", &[response::<ScrollResult>]),
+    ("browser.restore_scroll", &[response::<ScrollRestoreResult>]),
+    ("browser.wait_for_stable", &[response::<StabilityResult>]),
+    ("browser.press_combo", &[response::<KeyPressResult>]),
+    (
+        "browser.upload",
+        &[response::<UploadPathResult>, response::<DryRunReport>],
+    ),
+    ("browser.upload_file", &[response::<UploadResult>]),
+    ("browser.state.save", &[response::<StateSaved>]),
+    ("browser.state.load", &[response::<StateLoaded>]),
+    ("browser.state.list", &[response::<SavedStates>]),
+    ("browser.state.diff", &[response::<StateDiff>]),
+    ("browser.session.new", &[response::<SessionCreated>]),
+    ("browser.session.list", &[response::<SessionList>]),
+    ("browser.session.close", &[response::<SessionClosed>]),
+    ("browser.session.events", &[response::<SessionEvents>]),
+    ("browser.session.observe", &[response::<ObserverOpened>]),
+    ("browser.observer.events", &[response::<ObserverEvents>]),
+    ("browser.observer.close", &[response::<ObserverClosed>]),
+    ("browser.live_view", &[response::<LiveViewUrls>]),
+    ("browser.screencast.start", &[response::<ScreencastStarted>]),
+    ("browser.screencast.frames", &[response::<StreamedFrames>]),
+    ("browser.screencast.stop", &[response::<ScreencastStopped>]),
+];
+
+/// The document `browser.schemas` returns.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ResponseSchemas {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub title: String,
+    pub protocol_version: u32,
+    /// Models that methods return, in `definitions`
+    pub responses: Vec<String>,
+    /// Models each method returns, by method name
+    pub methods: BTreeMap<String, Vec<String>>,
+    /// Every model, by type name
+    pub definitions: Map<String, Value>,
+}
+
+/// Schemas of all response models, with the names of the top-level ones
+/// under `responses` and what each method returns under `methods`.
+pub fn export() -> Result<Value> {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    let mut responses = Vec::new();
+    let mut methods = BTreeMap::new();
+    for (method, models) in METHODS {
+        let names: Vec<String> = models.iter().map(|model| model(&mut gen)).collect();
+        for name in &names {
+            if !responses.contains(name) {
+                responses.push(name.clone());
+            }
+        }
+        methods.insert(method.to_string(), names);
+    }
+
+    let mut definitions: Map<String, Value> =
+        serde_json::from_value(serde_json::to_value(gen.take_definitions())?)?;
+    for name in &responses {
+        if let Some(properties) = definitions
+            .get_mut(name)
+            .and_then(|model| model.get_mut("properties"))
+            .and_then(Value::as_object_mut)
+        {
+            properties.insert(
+                "protocol_version".to_string(),
+                json!({"type": "integer", "const": PROTOCOL_VERSION}),
+            );
+        }
+    }
+
+    Ok(serde_json::to_value(ResponseSchemas {
+        schema: "http://json-schema.org/draft-07/schema#".to_string(),
+        title: "fgp-browser responses".to_string(),
+        protocol_version: PROTOCOL_VERSION,
+        responses,
+        methods,
+        definitions,
+    })?)
+}
+
+/// Add `protocol_version` to an object response.
+pub fn stamp(response: &mut Value) {
+    if let Some(obj) = response.as_object_mut() {
+        obj.insert("protocol_version".to_string(), json!(PROTOCOL_VERSION));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_includes_nested_models() {
+        let schemas = export().unwrap();
+        assert_eq!(schemas["protocol_version"], json!(PROTOCOL_VERSION));
+
+        let navigation = &schemas["definitions"]["NavigationResult"];
+        assert_eq!(
+            navigation["properties"]["protocol_version"]["const"],
+            json!(PROTOCOL_VERSION)
+        );
+        assert!(navigation["properties"]["url"].is_object());
+        // Contained models are defined, but aren't responses themselves
+        assert!(schemas["definitions"]["AriaNode"].is_object());
+        assert!(schemas["definitions"]["AriaNode"]["properties"]
+            .get("protocol_version")
+            .is_none());
+        let responses = schemas["responses"].as_array().unwrap();
+        assert!(!responses.contains(&json!("SessionInfo")));
+        assert!(schemas["definitions"]["SessionInfo"].is_object());
+    }
+
+    #[test]
+    fn test_every_response_is_an_object() {
+        // `stamp` can only version objects
+        let schemas = export().unwrap();
+        for name in schemas["responses"].as_array().unwrap() {
+            let model = &schemas["definitions"][name.as_str().unwrap()];
+            assert_eq!(model["type"], json!("object"), "{} isn't an object", name);
+        }
+        assert_eq!(
+            schemas["methods"]["browser.state.list"],
+            json!(["SavedStates"])
+        );
+    }
+
+    #[test]
+    fn test_stamp_only_objects() {
+        let mut object = json!({"success": true});
+        stamp(&mut object);
+        assert_eq!(object["protocol_version"], json!(PROTOCOL_VERSION));
+
+        let mut list = json!([1, 2]);
+        stamp(&mut list);
+        assert_eq!(list, json!([1, 2]));
+    }
+}
@@ -21,15 +21,114 @@ use fgp_daemon::service::MethodInfo;
 use fgp_daemon::FgpService;
 use serde_json::json;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use tokio::sync::RwLock;
 
-use crate::browser::BrowserClient;
+use crate::artifacts::{run_gc_loop, ArtifactStore, LocalArtifactStore, RetentionPolicy};
+use crate::browser::{
+    cdp_domain, is_chained, is_recoverable, is_xpath, limit_from_ms, render_aria_text,
+    BrowserClient, CdpDomains, KeyboardLayout, MaintenancePolicy, ScreencastConflict,
+    ScreencastOptions, TimedOut, TimeoutKind, Timeouts, DEFAULT_DRAG_STEPS, DEFAULT_PAGE_SIZE,
+    DEFAULT_PAINT_FRAMES, MAX_DRAG_STEPS, MAX_PAINT_FRAMES, MAX_SCREENSHOT_QUALITY,
+    MAX_SCREENSHOT_SCALE, MAX_ZOOM, MIN_ZOOM, PICKED_SELECTOR, RECOVERY_RETRIES, RETRY_DELAY_MS,
+};
+use crate::crawl::{self, CrawlJob};
 use crate::extension_bridge::{extension_method_name, is_extension_method, ExtensionBridge};
+use crate::frame_stream::{self, FrameStream};
+use crate::link_check::{LinkCheckOptions, LinkChecker};
+use crate::live_view::{self, LiveView};
 use crate::models::*;
+use crate::observer::{self, Observer, OBSERVER_PREFIX};
+use crate::recording::{self, Recording};
+use crate::robots::RobotsPolicy;
+use crate::schemas;
+use crate::state_diff::diff_states;
+use crate::throttle::{OriginThrottle, ThrottleConfig};
+use crate::timeline::Timeline;
+use crate::visual_diff;
+
+/// Default cap on snapshot response size, well under typical transport limits.
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Free space below which the artifact disk check fails.
+const MIN_ARTIFACT_FREE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Upper bound on the `health` self-test, so probes never hang on a wedged browser.
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long every signal must be quiet before `wait_for_stable` returns.
+const DEFAULT_STABLE_QUIET_MS: u64 = 500;
+
+/// How long `wait_for_stable` waits before reporting the page as unsettled.
+const DEFAULT_STABLE_TIMEOUT_MS: u64 = 10_000;
+
+/// Print preview margin in inches (Chrome's default print margin).
+const DEFAULT_PRINT_MARGIN_IN: f64 = 0.4;
+
+/// Milliseconds between canvas stroke points without their own timing.
+const DEFAULT_CANVAS_STEP_MS: u64 = 16;
+
+/// How long `media.state` waits for media to become playable by default.
+const DEFAULT_MEDIA_WAIT_MS: u64 = 0;
+
+/// Scroll-and-wait rounds `scroll_to_bottom` takes at most by default.
+const DEFAULT_SCROLL_ROUNDS: u64 = 10;
+
+/// How long `scroll_to_bottom` waits for lazy content after each round.
+const DEFAULT_SCROLL_SETTLE_MS: u64 = 500;
+
+/// Timer callbacks `fast_forward` runs at most by default, so a short
+/// interval can't keep it busy.
+const DEFAULT_FAST_FORWARD_MAX_TIMERS: u64 = 1000;
+
+/// How long `csp_trial` waits after load for late resources to be reported.
+const DEFAULT_CSP_SETTLE_MS: u64 = 1_000;
+
+/// Pause between keystrokes in `type_text`, about a brisk typist's pace.
+const DEFAULT_TYPE_DELAY_MS: u64 = 80;
+
+/// How far `type_text` varies each pause either way by default.
+const DEFAULT_TYPE_JITTER_MS: u64 = 40;
+
+/// Longest pause (delay plus jitter) `type_text` accepts between keystrokes.
+const MAX_TYPE_PAUSE_MS: u64 = 5_000;
+
+/// How long `locale_variants` lets each page settle after load, for text
+/// rendered client-side.
+const DEFAULT_VARIANT_SETTLE_MS: u64 = 500;
+
+/// Most variants one `locale_variants` call loads (each is a browser context).
+const MAX_LOCALE_VARIANTS: usize = 8;
+
+/// Link requests `check_links` keeps in flight at once by default.
+const DEFAULT_LINK_CONCURRENCY: usize = 8;
+
+/// Upper bound on `check_links` concurrency.
+const MAX_LINK_CONCURRENCY: usize = 32;
+
+/// Links `check_links` checks per page unless `max_links` is given.
+const DEFAULT_MAX_LINKS: usize = 500;
+
+/// How long `check_links` waits for each link to answer.
+const DEFAULT_LINK_TIMEOUT_MS: u64 = 10_000;
+
+/// Matches returned by `nearest` unless `limit` is given.
+const DEFAULT_NEAREST_LIMIT: usize = 5;
+
+/// Matches returned by `within` unless `limit` is given.
+const DEFAULT_WITHIN_LIMIT: usize = 50;
+
+/// Matches returned by `find_elements` unless `limit` is given.
+const DEFAULT_FIND_LIMIT: usize = 20;
+
+/// Results returned by `summarize_results` unless `limit` is given.
+const DEFAULT_RESULTS_LIMIT: usize = 10;
 
 /// Browser automation service.
 pub struct BrowserService {
@@ -37,16 +136,58 @@ pub struct BrowserService {
     client: Arc<RwLock<Option<Arc<BrowserClient>>>>,
     user_data_dir: PathBuf,
     auth_dir: PathBuf,
-    headless: bool,
+    mode: HeadlessMode,
     /// If Some, connect to existing Chrome instead of launching
     connect_url: Option<String>,
     /// Optional extension bridge for Chrome Extension API methods
     extension_bridge: Option<Arc<ExtensionBridge>>,
+    /// Launch progress of the (lazily started) browser, reported by `health`
+    launch_status: Arc<Mutex<BrowserStatus>>,
+    /// Backend for screenshots and other artifacts stored by ID
+    artifacts: Arc<dyn ArtifactStore>,
+    /// Limits enforced by `artifact.gc` and the background GC
+    retention: RetentionPolicy,
+    /// Upper bound on snapshot response size; larger trees are paginated
+    max_response_bytes: usize,
+    /// Snapshot rendering when a call doesn't pass `format`
+    snapshot_format: SnapshotFormat,
+    /// Per-origin navigation limits shared by all sessions
+    throttle: Arc<OriginThrottle>,
+    /// If Some, navigations disallowed by robots.txt are refused
+    robots: Option<Arc<RobotsPolicy>>,
+    /// HTTP client and answer cache for `check_links`
+    links: LinkChecker,
+    /// Background crawls by ID
+    crawls: Mutex<HashMap<String, Arc<CrawlJob>>>,
+    /// Screenshot timelines by session ID
+    timelines: Arc<Mutex<HashMap<String, Timeline>>>,
+    /// Video recordings in progress by session ID
+    recordings: Arc<Mutex<HashMap<String, Recording>>>,
+    /// Where recordings go unless a call passes `path`
+    recordings_dir: PathBuf,
+    /// Screencast frame streams by session ID
+    streams: Arc<Mutex<HashMap<String, FrameStream>>>,
+    /// Read-only observer handles by observer ID
+    observers: Arc<Mutex<HashMap<String, Observer>>>,
+    /// Address of the MJPEG live view server, if enabled
+    live_view: Option<SocketAddr>,
+    /// If Some, `cdp` forwards raw calls to these domains
+    cdp_domains: Option<CdpDomains>,
+    /// Reload and retry element actions whose target went missing, unless a
+    /// call passes `recover: false`
+    recovery: bool,
+    /// Applied by `maintain` and, in bounded memory mode, in the background
+    maintenance: MaintenancePolicy,
+    /// Time limits of calls in sessions that haven't set their own
+    timeouts: Timeouts,
 }
 
 impl BrowserService {
-    /// Create a new browser service with pre-warmed browser.
-    pub fn new(headless: bool) -> Result<Self> {
+    /// Create a new browser service.
+    ///
+    /// Chrome is launched on the first request that needs it unless `warm`
+    /// is set, in which case it is pre-warmed here for an instant first response.
+    pub fn new(mode: HeadlessMode, warm: bool) -> Result<Self> {
         let runtime = Runtime::new().context("Failed to create tokio runtime")?;
 
         let base_dir = dirs::home_dir()
@@ -61,24 +202,47 @@ impl BrowserService {
         // Create directories
         std::fs::create_dir_all(&user_data_dir)?;
         std::fs::create_dir_all(&auth_dir)?;
+        let artifacts = LocalArtifactStore::new(base_dir.join("artifacts"))?;
 
-        // Pre-warm browser for instant response on first request
-        let client = runtime.block_on(async {
-            tracing::info!("Pre-warming browser...");
-            BrowserClient::new(user_data_dir.clone(), headless).await
-        })?;
-
-        tracing::info!("Browser pre-warmed and ready");
-
-        Ok(Self {
+        let service = Self {
             runtime,
-            client: Arc::new(RwLock::new(Some(Arc::new(client)))),
+            client: Arc::new(RwLock::new(None)),
             user_data_dir,
             auth_dir,
-            headless,
+            mode,
             connect_url: None,
             extension_bridge: None,
-        })
+            launch_status: Arc::new(Mutex::new(BrowserStatus::Idle)),
+            artifacts: Arc::new(artifacts),
+            retention: RetentionPolicy::default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            snapshot_format: SnapshotFormat::default(),
+            throttle: Arc::new(OriginThrottle::new(ThrottleConfig::default())),
+            robots: None,
+            links: LinkChecker::new()?,
+            crawls: Mutex::new(HashMap::new()),
+            timelines: Arc::default(),
+            recordings: Arc::default(),
+            recordings_dir: base_dir.join("recordings"),
+            streams: Arc::default(),
+            observers: Arc::default(),
+            live_view: None,
+            cdp_domains: None,
+            recovery: false,
+            maintenance: MaintenancePolicy::default(),
+            timeouts: Timeouts::default(),
+        };
+
+        if warm {
+            // Pre-warm browser for instant response on first request
+            tracing::info!("Pre-warming browser...");
+            service.client()?;
+            tracing::info!("Browser pre-warmed and ready");
+        } else {
+            tracing::info!("Browser will launch on first request");
+        }
+
+        Ok(service)
     }
 
     /// Set the extension bridge for routing extension methods
@@ -87,6 +251,131 @@ impl BrowserService {
         self
     }
 
+    /// Store artifacts in `store` instead of the local artifact directory.
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>) -> Self {
+        self.artifacts = store;
+        self
+    }
+
+    /// Cap snapshot responses at `bytes`; per-call `max_bytes` can only lower it.
+    pub fn with_max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = bytes;
+        self
+    }
+
+    /// Render snapshots as `format` unless a call asks otherwise.
+    pub fn with_snapshot_format(mut self, format: SnapshotFormat) -> Self {
+        self.snapshot_format = format;
+        self
+    }
+
+    /// Limit concurrency and pacing of navigations per origin, across sessions.
+    pub fn with_origin_throttle(mut self, config: ThrottleConfig) -> Self {
+        self.throttle = Arc::new(OriginThrottle::new(config));
+        self
+    }
+
+    /// Refuse navigations that the target's robots.txt disallows, and
+    /// block the documents pages load by themselves that it disallows.
+    pub fn with_robots(mut self, robots: Arc<RobotsPolicy>) -> Self {
+        // A pre-warmed browser is already running
+        if let Some(client) = self.runtime.block_on(self.client.read()).clone() {
+            self.runtime
+                .block_on(client.enforce_robots(Arc::clone(&robots)));
+        }
+        self.robots = Some(robots);
+        self
+    }
+
+    /// Enforce `policy` on the artifact store, collecting garbage in the
+    /// background every `policy.interval` if it sets any limit. Call after
+    /// `with_artifact_store`.
+    pub fn with_retention(mut self, policy: RetentionPolicy) -> Self {
+        if policy.is_enabled() {
+            self.runtime
+                .spawn(run_gc_loop(self.artifacts.clone(), policy.clone()));
+        }
+        self.retention = policy;
+        self
+    }
+
+    /// Bounded memory mode: run a maintenance pass over the sessions every
+    /// `policy.interval` (see `browser::maintenance`). Set up after
+    /// `with_robots` and `with_origin_throttle`, which recycled sessions'
+    /// reloads follow.
+    pub fn with_maintenance(mut self, policy: MaintenancePolicy) -> Self {
+        let client = self.client.clone();
+        let timelines = self.timelines.clone();
+        let recordings = self.recordings.clone();
+        let streams = self.streams.clone();
+        let observers = self.observers.clone();
+        let robots = self.robots.clone();
+        let throttle = Arc::clone(&self.throttle);
+        let background = policy.clone();
+        self.runtime.spawn(async move {
+            let mut ticker = tokio::time::interval(background.interval);
+            // The first tick is immediate, before anything needs upkeep
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(client) = client.read().await.clone() else {
+                    continue;
+                };
+                match Self::run_maintenance(
+                    &client,
+                    &background,
+                    &timelines,
+                    &recordings,
+                    &streams,
+                    &observers,
+                    robots.as_deref(),
+                    &throttle,
+                )
+                .await
+                {
+                    Ok(report) => tracing::info!(
+                        "Maintenance: {} sessions, {} pruned, {} events trimmed, {} recycled",
+                        report.sessions,
+                        report.pruned.len(),
+                        report.trimmed_events,
+                        report.recycled.len()
+                    ),
+                    Err(e) => tracing::warn!("Maintenance failed: {}", e),
+                }
+            }
+        });
+        self.maintenance = policy;
+        self
+    }
+
+    /// Run sessions under `timeouts` unless they set their own.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Forward raw CDP calls from `cdp` for the given domains.
+    pub fn with_cdp_passthrough(mut self, domains: CdpDomains) -> Self {
+        self.cdp_domains = Some(domains);
+        self
+    }
+
+    /// Recover element actions by default (see `browser::recovery`).
+    pub fn with_recovery(mut self, enabled: bool) -> Self {
+        self.recovery = enabled;
+        self
+    }
+
+    /// Serve sessions as MJPEG streams over HTTP on `addr`.
+    pub fn with_live_view(mut self, addr: SocketAddr, options: ScreencastOptions) -> Result<Self> {
+        let listener = self.runtime.block_on(live_view::bind(addr))?;
+        let addr = listener.local_addr()?;
+        let server = LiveView::new(self.client.clone(), options);
+        self.runtime.spawn(server.serve(listener));
+        self.live_view = Some(addr);
+        Ok(self)
+    }
+
     /// Create a browser service that connects to user's existing Chrome.
     ///
     /// This mode attaches to a Chrome instance running with `--remote-debugging-port`.
@@ -94,7 +383,8 @@ impl BrowserService {
     ///
     /// # Arguments
     /// * `connect_url` - Chrome debugging URL (e.g., "http://localhost:9222")
-    pub fn new_connect(connect_url: &str) -> Result<Self> {
+    /// * `warm` - Connect immediately instead of on the first request
+    pub fn new_connect(connect_url: &str, warm: bool) -> Result<Self> {
         let runtime = Runtime::new().context("Failed to create tokio runtime")?;
 
         let base_dir = dirs::home_dir()
@@ -108,29 +398,45 @@ impl BrowserService {
 
         // Create directories (for auth state storage)
         std::fs::create_dir_all(&auth_dir)?;
+        let artifacts = LocalArtifactStore::new(base_dir.join("artifacts"))?;
 
-        // Connect to existing Chrome
-        let url = connect_url.to_string();
-        let client = runtime.block_on(async {
-            tracing::info!("Connecting to user's Chrome at: {}", url);
-            BrowserClient::connect(&url).await
-        })?;
-
-        tracing::info!("Connected to user's Chrome - sessions available!");
-
-        Ok(Self {
+        let service = Self {
             runtime,
-            client: Arc::new(RwLock::new(Some(Arc::new(client)))),
+            client: Arc::new(RwLock::new(None)),
             user_data_dir,
             auth_dir,
-            headless: false, // User's browser is always headed
+            mode: HeadlessMode::Headed, // User's browser is always headed
             connect_url: Some(connect_url.to_string()),
             extension_bridge: None,
-        })
-    }
+            launch_status: Arc::new(Mutex::new(BrowserStatus::Idle)),
+            artifacts: Arc::new(artifacts),
+            retention: RetentionPolicy::default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            snapshot_format: SnapshotFormat::default(),
+            throttle: Arc::new(OriginThrottle::new(ThrottleConfig::default())),
+            robots: None,
+            links: LinkChecker::new()?,
+            crawls: Mutex::new(HashMap::new()),
+            timelines: Arc::default(),
+            recordings: Arc::default(),
+            recordings_dir: base_dir.join("recordings"),
+            streams: Arc::default(),
+            observers: Arc::default(),
+            live_view: None,
+            cdp_domains: None,
+            recovery: false,
+            maintenance: MaintenancePolicy::default(),
+            timeouts: Timeouts::default(),
+        };
+
+        if warm {
+            tracing::info!("Connecting to user's Chrome at: {}", connect_url);
+            service.client()?;
+            tracing::info!("Connected to user's Chrome - sessions available!");
+        }
 
-    // Note: get_client() was removed - we now use session-based approach
-    // where each handler directly accesses the client via the RwLock.
+        Ok(service)
+    }
 
     /// Extract session_id from params (optional).
     fn get_session_id(params: &HashMap<String, Value>) -> Option<String> {
@@ -141,26 +447,125 @@ impl BrowserService {
             .map(|s| s.to_string())
     }
 
-    async fn get_or_init_client(
-        client: &Arc<RwLock<Option<Arc<BrowserClient>>>>,
-        user_data_dir: &Path,
-        headless: bool,
-        connect_url: Option<&str>,
-    ) -> Result<Arc<BrowserClient>> {
-        if let Some(existing) = client.read().await.as_ref() {
+    /// Extract wait_until from params (optional, defaults to load).
+    fn get_wait_until(params: &HashMap<String, Value>) -> Result<WaitUntil> {
+        match params.get("wait_until") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'wait_until' must be one of commit, domcontentloaded, load, networkidle"),
+            None => Ok(WaitUntil::default()),
+        }
+    }
+
+    /// Limit on a call of `kind`: its `timeout_ms`, else the session's
+    /// limit, else the daemon's.
+    fn get_timeout(
+        &self,
+        browser_client: &BrowserClient,
+        kind: TimeoutKind,
+        params: &HashMap<String, Value>,
+    ) -> Result<Duration> {
+        if let Some(v) = params.get("timeout_ms").filter(|v| !v.is_null()) {
+            let ms = v.as_u64().context("'timeout_ms' must be a number")?;
+            return limit_from_ms(ms).map_err(|e| anyhow::anyhow!("'timeout_ms': {}", e));
+        }
+        let session_id = Self::get_session_id(params);
+        let session = self
+            .runtime
+            .block_on(browser_client.timeouts(session_id.as_deref()))?;
+        Ok(session.unwrap_or(self.timeouts).get(kind))
+    }
+
+    /// Run `future` to completion, failing with `TimedOut` after `limit`.
+    fn block_on_within<T>(
+        &self,
+        kind: TimeoutKind,
+        limit: Duration,
+        future: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let result = self
+            .runtime
+            .block_on(async { tokio::time::timeout(limit, future).await });
+        match result {
+            Ok(result) => result,
+            Err(_) => Err(TimedOut { kind, limit }.into()),
+        }
+    }
+
+    /// Swap an observer handle passed as `session_id` for the session it
+    /// observes, refusing anything but read-only calls.
+    fn resolve_observer(
+        &self,
+        method: &str,
+        mut params: HashMap<String, Value>,
+    ) -> Result<HashMap<String, Value>> {
+        let Some(id) = Self::get_session_id(&params).filter(|id| id.starts_with(OBSERVER_PREFIX))
+        else {
+            return Ok(params);
+        };
+
+        let session_id = self
+            .observers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&id)
+            .map(|observer| observer.session_id.clone())
+            .with_context(|| format!("Observer not found: {}", id))?;
+        let streaming = self
+            .streams
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&session_id)
+            .is_some_and(|stream| stream.frames().streaming());
+        observer::check_call(method, &params, streaming)?;
+
+        params.remove("session");
+        params.insert("session_id".to_string(), json!(session_id));
+        Ok(params)
+    }
+
+    /// Get the browser client, launching (or connecting to) Chrome on first use.
+    fn client(&self) -> Result<Arc<BrowserClient>> {
+        self.runtime.block_on(self.get_or_init_client())
+    }
+
+    async fn get_or_init_client(&self) -> Result<Arc<BrowserClient>> {
+        if let Some(existing) = self.client.read().await.as_ref() {
             return Ok(Arc::clone(existing));
         }
 
-        let mut client_lock = client.write().await;
+        let mut client_lock = self.client.write().await;
         if client_lock.is_none() {
-            let new_client = if let Some(url) = connect_url {
+            self.set_launch_status(BrowserStatus::Launching {
+                started_at: Utc::now().to_rfc3339(),
+            });
+            let started = Instant::now();
+
+            let launched = if let Some(url) = self.connect_url.as_deref() {
                 // Connect mode: attach to existing Chrome
-                BrowserClient::connect(url).await?
+                BrowserClient::connect(url).await
             } else {
                 // Launch mode: spawn new Chrome
-                BrowserClient::new(user_data_dir.to_path_buf(), headless).await?
+                tracing::info!("Launching browser on first request...");
+                BrowserClient::new(self.user_data_dir.clone(), self.mode).await
             };
-            *client_lock = Some(Arc::new(new_client));
+
+            match launched {
+                Ok(new_client) => {
+                    if let Some(robots) = &self.robots {
+                        new_client.enforce_robots(Arc::clone(robots)).await;
+                    }
+                    let launch_ms = started.elapsed().as_millis() as u64;
+                    tracing::info!("Browser ready in {}ms", launch_ms);
+                    self.set_launch_status(BrowserStatus::Ready { launch_ms });
+                    *client_lock = Some(Arc::new(new_client));
+                }
+                Err(e) => {
+                    self.set_launch_status(BrowserStatus::Failed {
+                        error: e.to_string(),
+                    });
+                    return Err(e);
+                }
+            }
         }
 
         client_lock
@@ -169,886 +574,7663 @@ impl BrowserService {
             .ok_or_else(|| anyhow::anyhow!("Failed to get browser client"))
     }
 
+    fn set_launch_status(&self, status: BrowserStatus) {
+        if let Ok(mut current) = self.launch_status.lock() {
+            *current = status;
+        }
+    }
+
+    fn launch_status(&self) -> BrowserStatus {
+        self.launch_status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or(BrowserStatus::Idle)
+    }
+
     fn handle_open(&self, params: HashMap<String, Value>) -> Result<Value> {
         let url = params
             .get("url")
             .and_then(|v| v.as_str())
             .context("Missing 'url' parameter")?;
+        let wait_until = Self::get_wait_until(&params)?;
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Navigation, &params)?;
 
         let result = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.navigate(url, session_id.as_deref()).await
+            if let Some(robots) = &self.robots {
+                robots.check(url).await?;
+            }
+            let _permit = self.throttle.acquire(url).await;
+            browser_client
+                .navigate(url, wait_until, limit, session_id.as_deref())
+                .await
         })?;
 
+        if self.timeline_auto(session_id.as_deref()) {
+            // Best effort: a failed capture shouldn't fail the navigation
+            if let Err(e) = self.capture_frame(
+                &browser_client,
+                session_id.as_deref(),
+                TimelineTrigger::Navigation,
+                None,
+            ) {
+                tracing::warn!("Failed to capture timeline frame: {}", e);
+            }
+        }
+
         Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_snapshot(&self, params: HashMap<String, Value>) -> Result<Value> {
+    /// Move `delta` entries through the session's history (-1 is back).
+    fn handle_history(&self, params: HashMap<String, Value>, delta: i64) -> Result<Value> {
+        let wait_until = Self::get_wait_until(&params)?;
         let session_id = Self::get_session_id(&params);
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Navigation, &params)?;
 
         let result = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.snapshot(session_id.as_deref()).await
+            if delta < 0 {
+                browser_client
+                    .go_back(wait_until, limit, session_id.as_deref())
+                    .await
+            } else {
+                browser_client
+                    .go_forward(wait_until, limit, session_id.as_deref())
+                    .await
+            }
         })?;
 
+        if self.timeline_auto(session_id.as_deref()) {
+            if let Err(e) = self.capture_frame(
+                &browser_client,
+                session_id.as_deref(),
+                TimelineTrigger::Navigation,
+                None,
+            ) {
+                tracing::warn!("Failed to capture timeline frame: {}", e);
+            }
+        }
+
         Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_screenshot(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let path = params.get("path").and_then(|v| v.as_str());
+    fn handle_reload(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let ignore_cache = params
+            .get("ignore_cache")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let wait_until = Self::get_wait_until(&params)?;
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Navigation, &params)?;
 
-        let result = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.screenshot(path, session_id.as_deref()).await
-        })?;
+        let result = self.runtime.block_on(browser_client.reload(
+            ignore_cache,
+            wait_until,
+            limit,
+            session_id.as_deref(),
+        ))?;
+
+        if self.timeline_auto(session_id.as_deref()) {
+            if let Err(e) = self.capture_frame(
+                &browser_client,
+                session_id.as_deref(),
+                TimelineTrigger::Navigation,
+                None,
+            ) {
+                tracing::warn!("Failed to capture timeline frame: {}", e);
+            }
+        }
 
         Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_click(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .context("Missing 'selector' parameter")?;
+    /// Observers get the snapshot without changing the session's own
+    /// snapshot state (see `observe_snapshot_page`).
+    fn handle_snapshot(&self, params: HashMap<String, Value>, observing: bool) -> Result<Value> {
         let session_id = Self::get_session_id(&params);
+        let use_cache = params
+            .get("cache")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let limit = params
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize);
+        let cursor = params.get("cursor").and_then(|v| v.as_str());
+        let format: SnapshotFormat = match params.get("format") {
+            Some(v) => {
+                serde_json::from_value(v.clone()).context("'format' must be json or text")?
+            }
+            None => self.snapshot_format,
+        };
+        let diff = params
+            .get("diff")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if diff && (format != SnapshotFormat::Text || cursor.is_some()) {
+            anyhow::bail!("'diff' needs format: text and no cursor");
+        }
+        let filter = SnapshotFilter {
+            roles: match params.get("roles") {
+                Some(v) => serde_json::from_value(v.clone())
+                    .context("'roles' must be an array of role names")?,
+                None => Vec::new(),
+            },
+            interactive: params
+                .get("interactive")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            visible: params
+                .get("visible")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            root: params
+                .get("root")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        };
+        let max_bytes = params
+            .get("max_bytes")
+            .and_then(|v| v.as_u64())
+            .map_or(self.max_response_bytes, |n| {
+                (n as usize).min(self.max_response_bytes)
+            });
+        let browser_client = self.client()?;
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.to_string();
+        // Node-count pagination only when asked to, so plain calls keep the
+        // full tree unless it's over the size limit
+        let limit = match (limit, cursor) {
+            (Some(n), _) => n,
+            (None, Some(_)) => DEFAULT_PAGE_SIZE,
+            (None, None) => usize::MAX,
+        };
+        let timeout = self.get_timeout(&browser_client, TimeoutKind::Snapshot, &params)?;
+        let result = if observing {
+            self.block_on_within(
+                TimeoutKind::Snapshot,
+                timeout,
+                browser_client.observe_snapshot_page(
+                    session_id.as_deref(),
+                    use_cache,
+                    &filter,
+                    limit,
+                    max_bytes,
+                    cursor,
+                ),
+            )?
+        } else {
+            self.block_on_within(
+                TimeoutKind::Snapshot,
+                timeout,
+                browser_client.snapshot_page(
+                    session_id.as_deref(),
+                    use_cache,
+                    &filter,
+                    limit,
+                    max_bytes,
+                    cursor,
+                ),
+            )?
+        };
 
-        let result = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.click(&selector, session_id.as_deref()).await
-        })?;
+        let diff = if diff {
+            self.runtime
+                .block_on(browser_client.snapshot_diff(session_id.as_deref()))?
+        } else {
+            None
+        };
 
-        Ok(serde_json::to_value(result)?)
+        match format {
+            SnapshotFormat::Json => Ok(serde_json::to_value(result)?),
+            SnapshotFormat::Text => Ok(serde_json::to_value(AriaTextSnapshot {
+                text: render_aria_text(&result.nodes),
+                diff,
+                url: result.url,
+                title: result.title,
+                element_count: result.element_count,
+                cached: result.cached,
+                offset: result.offset,
+                next_cursor: result.next_cursor,
+                truncated: result.truncated,
+                scroll: result.scroll,
+                focused: result.focused,
+            })?),
+        }
     }
 
-    fn handle_fill(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .context("Missing 'selector' parameter")?;
-        let value = params
-            .get("value")
-            .and_then(|v| v.as_str())
-            .context("Missing 'value' parameter")?;
+    fn handle_snapshot_diff(&self, params: HashMap<String, Value>) -> Result<Value> {
         let session_id = Self::get_session_id(&params);
+        let use_cache = params
+            .get("cache")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let browser_client = self.client()?;
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.to_string();
-        let value = value.to_string();
-
-        let result = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client
-                .fill(&selector, &value, session_id.as_deref())
-                .await
-        })?;
+        let snapshot = self.runtime.block_on(browser_client.snapshot_page(
+            session_id.as_deref(),
+            use_cache,
+            &SnapshotFilter::default(),
+            usize::MAX,
+            self.max_response_bytes,
+            None,
+        ))?;
+        let diff = self
+            .runtime
+            .block_on(browser_client.snapshot_changes(&snapshot.url, session_id.as_deref()))?
+            .unwrap_or(AriaSnapshotDiff {
+                url: snapshot.url,
+                compared: false,
+                added: Vec::new(),
+                removed: Vec::new(),
+                changed: Vec::new(),
+                unchanged: 0,
+            });
 
-        Ok(serde_json::to_value(result)?)
+        Ok(serde_json::to_value(diff)?)
     }
 
-    fn handle_press(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let key = params
-            .get("key")
-            .and_then(|v| v.as_str())
-            .context("Missing 'key' parameter")?;
-        let session_id = Self::get_session_id(&params);
-
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let key = key.to_string();
+    fn handle_screenshot(&self, params: HashMap<String, Value>) -> Result<Value> {
+        self.take_screenshot(params, None)
+    }
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.press(&key, session_id.as_deref()).await
-        })?;
+    fn handle_screenshot_element(&self, params: HashMap<String, Value>) -> Result<Value> {
+        if !params.get("selector").is_some_and(Value::is_string) {
+            anyhow::bail!("Missing 'selector' parameter");
+        }
+        self.take_screenshot(params, None)
+    }
 
-        Ok(serde_json::json!({"success": true}))
+    fn handle_screenshot_after_paint(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let frames = params
+            .get("frames")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_PAINT_FRAMES as u64);
+        if !(1..=MAX_PAINT_FRAMES as u64).contains(&frames) {
+            anyhow::bail!("'frames' must be between 1 and {}", MAX_PAINT_FRAMES);
+        }
+        self.take_screenshot(params, Some(frames as u32))
     }
 
-    fn handle_state_save(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let name = params
-            .get("name")
-            .and_then(|v| v.as_str())
-            .context("Missing 'name' parameter")?;
+    /// Screenshot, or with `after_paint` frames, an optional `click` and then
+    /// a screenshot once that many frames have been painted.
+    fn take_screenshot(
+        &self,
+        params: HashMap<String, Value>,
+        after_paint: Option<u32>,
+    ) -> Result<Value> {
+        let path = params.get("path").and_then(|v| v.as_str());
+        let store = params
+            .get("artifact")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let format: ImageFormat = match params.get("format") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'format' must be one of png, jpeg, webp")?,
+            None => ImageFormat::default(),
+        };
+        let clip: Option<ElementRect> = params
+            .get("clip")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .context("'clip' must be {x, y, width, height}")?;
+        let clip_origin: ClipOrigin = match params.get("clip_origin") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'clip_origin' must be document or viewport")?,
+            None => ClipOrigin::default(),
+        };
+        let options = ScreenshotOptions {
+            element: params
+                .get("selector")
+                .and_then(|v| v.as_str())
+                .map(|selector| ElementCapture {
+                    selector: selector.to_string(),
+                    padding: params
+                        .get("padding")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0)
+                        .max(0.0),
+                    marker: params
+                        .get("marker")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                }),
+            clip,
+            clip_origin,
+            full_page: params
+                .get("full_page")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            scale: params.get("scale").and_then(|v| v.as_f64()),
+            format,
+            quality: params
+                .get("quality")
+                .and_then(|v| v.as_u64())
+                .map(|q| q.min(u8::MAX as u64) as u8),
+        };
         let session_id = Self::get_session_id(&params);
 
-        let state_path = self.auth_dir.join(format!("{}.json", name));
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
+        if store && path.is_some() {
+            anyhow::bail!("Pass either 'path' or 'artifact', not both");
+        }
+        if options.full_page && options.element.is_some() {
+            anyhow::bail!("Pass either 'selector' or 'full_page', not both");
+        }
+        if let Some(clip) = options.clip {
+            if options.element.is_some() || options.full_page {
+                anyhow::bail!("'clip' can't be combined with 'selector' or 'full_page'");
+            }
+            if !(clip.x.is_finite() && clip.y.is_finite())
+                || !(clip.width > 0.0 && clip.width.is_finite())
+                || !(clip.height > 0.0 && clip.height.is_finite())
+            {
+                anyhow::bail!("'clip' needs a positive width and height");
+            }
+        }
+        if let Some(scale) = options.scale {
+            if !(scale > 0.0 && scale <= MAX_SCREENSHOT_SCALE) {
+                anyhow::bail!(
+                    "'scale' must be greater than 0 and at most {}",
+                    MAX_SCREENSHOT_SCALE
+                );
+            }
+        }
+        if let Some(quality) = options.quality {
+            if format == ImageFormat::Png {
+                anyhow::bail!("'quality' applies to jpeg and webp, not png");
+            }
+            if quality > MAX_SCREENSHOT_QUALITY {
+                anyhow::bail!("'quality' must be at most {}", MAX_SCREENSHOT_QUALITY);
+            }
+        }
 
-        let state = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            let cookies = browser_client.get_cookies(session_id.as_deref()).await?;
-            let local_storage = browser_client
-                .get_local_storage(session_id.as_deref())
-                .await?;
-            Ok::<AuthState, anyhow::Error>(AuthState {
-                cookies,
-                local_storage,
-                saved_at: Utc::now().to_rfc3339(),
-            })
-        })?;
+        let browser_client = self.client()?;
 
-        let serialized = serde_json::to_vec_pretty(&state)?;
-        std::fs::write(&state_path, serialized)?;
+        let mut result = match after_paint {
+            None => self.runtime.block_on(browser_client.screenshot(
+                path,
+                &options,
+                session_id.as_deref(),
+            ))?,
+            Some(frames) => {
+                if let Some(selector) = params.get("click").and_then(|v| v.as_str()) {
+                    self.runtime
+                        .block_on(browser_client.click(selector, session_id.as_deref()))?;
+                }
+                self.runtime
+                    .block_on(browser_client.screenshot_after_paint(
+                        path,
+                        &options,
+                        frames,
+                        session_id.as_deref(),
+                    ))?
+            }
+        };
+
+        if store {
+            let encoded = result.data.take().unwrap_or_default();
+            result.artifact =
+                Some(self.store_screenshot(encoded, format, session_id.as_deref())?);
+        }
 
-        Ok(serde_json::json!({
-            "success": true,
-            "path": state_path.to_string_lossy()
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_state_load(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let name = params
-            .get("name")
-            .and_then(|v| v.as_str())
-            .context("Missing 'name' parameter")?;
+    /// Save a base64 image from Chrome as a screenshot artifact.
+    fn store_screenshot(
+        &self,
+        encoded: String,
+        format: ImageFormat,
+        session_id: Option<&str>,
+    ) -> Result<Artifact> {
+        let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+            .context("Failed to decode screenshot")?;
+        self.runtime.block_on(self.artifacts.put(
+            ArtifactKind::Screenshot,
+            session_id.unwrap_or("default"),
+            format.extension(),
+            bytes,
+        ))
+    }
+
+    /// Image bytes from the `<name>` file path or the `<name>_artifact` ID.
+    fn comparison_image(
+        &self,
+        params: &HashMap<String, Value>,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        let artifact_param = format!("{}_artifact", name);
+        let path = params.get(name).and_then(|v| v.as_str());
+        let artifact = params.get(&artifact_param).and_then(|v| v.as_str());
+        match (path, artifact) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Pass either '{}' or '{}', not both", name, artifact_param)
+            }
+            (Some(path), None) => std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path))
+                .map(Some),
+            (None, Some(id)) => self.runtime.block_on(self.artifacts.get(id)).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn handle_compare_screenshots(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let threshold = params
+            .get("threshold")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(visual_diff::DEFAULT_THRESHOLD as u64);
+        if threshold > u8::MAX as u64 {
+            anyhow::bail!("'threshold' must be at most {}", u8::MAX);
+        }
+        let max_mismatch = params.get("max_mismatch").and_then(|v| v.as_f64());
+        if max_mismatch.is_some_and(|max| !(0.0..=100.0).contains(&max)) {
+            anyhow::bail!("'max_mismatch' must be between 0 and 100");
+        }
+        let diff_path = params.get("diff_path").and_then(|v| v.as_str());
+        let store = params
+            .get("artifact")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if store && diff_path.is_some() {
+            anyhow::bail!("Pass either 'diff_path' or 'artifact', not both");
+        }
         let session_id = Self::get_session_id(&params);
 
-        let state_path = self.auth_dir.join(format!("{}.json", name));
+        let baseline = self
+            .comparison_image(&params, "baseline")?
+            .context("Missing 'baseline' or 'baseline_artifact' parameter")?;
+        let current = match self.comparison_image(&params, "current")? {
+            Some(bytes) => bytes,
+            None => {
+                let options = ScreenshotOptions {
+                    full_page: params
+                        .get("full_page")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                    ..Default::default()
+                };
+                let browser_client = self.client()?;
+                let capture = self.runtime.block_on(browser_client.screenshot(
+                    None,
+                    &options,
+                    session_id.as_deref(),
+                ))?;
+                base64::Engine::decode(
+                    &base64::engine::general_purpose::STANDARD,
+                    capture.data.unwrap_or_default(),
+                )
+                .context("Failed to decode screenshot")?
+            }
+        };
 
-        if !state_path.exists() {
-            anyhow::bail!("State '{}' not found", name);
+        let (mut comparison, diff) = visual_diff::compare(&baseline, &current, threshold as u8)?;
+        comparison.passed = max_mismatch.map(|max| comparison.mismatch_percent <= max);
+        if store {
+            comparison.artifact = Some(self.runtime.block_on(self.artifacts.put(
+                ArtifactKind::Screenshot,
+                session_id.as_deref().unwrap_or("default"),
+                "png",
+                diff,
+            ))?);
+        } else if let Some(path) = diff_path {
+            std::fs::write(path, &diff).with_context(|| format!("Failed to write {}", path))?;
+            comparison.diff_path = Some(path.to_string());
+        } else {
+            comparison.diff = Some(base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                &diff,
+            ));
         }
 
-        let state_bytes = std::fs::read(&state_path)?;
-        let state: AuthState = serde_json::from_slice(&state_bytes)?;
+        Ok(serde_json::to_value(comparison)?)
+    }
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
+    fn handle_print_preview(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let paper: PaperSize = match params.get("paper") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'paper' must be one of letter, legal, a4, a3")?,
+            None => PaperSize::default(),
+        };
+        let options = PrintOptions {
+            paper,
+            landscape: params
+                .get("landscape")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            margin: params
+                .get("margin")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(DEFAULT_PRINT_MARGIN_IN),
+            scale: params.get("scale").and_then(|v| v.as_f64()),
+            pdf: params.get("pdf").and_then(|v| v.as_bool()).unwrap_or(false),
+        };
+        let session_id = Self::get_session_id(&params);
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client
-                .set_cookies(&state.cookies, session_id.as_deref())
-                .await?;
-            browser_client
-                .set_local_storage(&state.local_storage, session_id.as_deref())
-                .await?;
-            Ok::<(), anyhow::Error>(())
-        })?;
-
-        Ok(serde_json::json!({
-            "success": true,
-            "name": name
-        }))
-    }
+        let (width, height) = paper.inches();
+        if !(options.margin >= 0.0 && 2.0 * options.margin < width.min(height)) {
+            anyhow::bail!("'margin' must be at least 0 and leave room on the paper");
+        }
+        if let Some(scale) = options.scale {
+            if !(scale > 0.0 && scale <= MAX_SCREENSHOT_SCALE) {
+                anyhow::bail!(
+                    "'scale' must be greater than 0 and at most {}",
+                    MAX_SCREENSHOT_SCALE
+                );
+            }
+        }
 
-    fn handle_state_list(&self, _params: HashMap<String, Value>) -> Result<Value> {
-        let mut states = Vec::new();
+        let browser_client = self.client()?;
+        let capture = self
+            .runtime
+            .block_on(browser_client.print_preview(&options, session_id.as_deref()))?;
 
-        if self.auth_dir.exists() {
-            for entry in std::fs::read_dir(&self.auth_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.extension().map(|e| e == "json").unwrap_or(false) {
-                    if let Some(stem) = path.file_stem() {
-                        let name = stem.to_string_lossy().to_string();
-                        let mut domains = Vec::new();
-                        let mut saved_at = String::new();
+        let mut pages = Vec::with_capacity(capture.pages.len());
+        for (index, (clip, encoded)) in capture.pages.into_iter().enumerate() {
+            pages.push(PrintPage {
+                number: index + 1,
+                clip,
+                artifact: self.store_screenshot(
+                    encoded,
+                    ImageFormat::Png,
+                    session_id.as_deref(),
+                )?,
+            });
+        }
+        let pdf = match capture.pdf {
+            Some(encoded) => Some(self.store_pdf(encoded, session_id.as_deref())?),
+            None => None,
+        };
 
-                        if let Ok(contents) = std::fs::read(&path) {
-                            if let Ok(state) = serde_json::from_slice::<AuthState>(&contents) {
-                                let mut seen = std::collections::HashSet::new();
-                                for cookie in state.cookies {
-                                    if seen.insert(cookie.domain.clone()) {
-                                        domains.push(cookie.domain);
-                                    }
-                                }
-                                saved_at = state.saved_at;
-                            }
-                        }
+        Ok(serde_json::to_value(PrintPreview {
+            paper,
+            landscape: options.landscape,
+            page_width: capture.page_width,
+            page_height: capture.page_height,
+            total_pages: capture.total_pages,
+            pages,
+            pdf,
+        })?)
+    }
 
-                        states.push(SavedState {
-                            name,
-                            domains,
-                            saved_at,
-                        });
+    fn handle_pdf(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let paper: PaperSize = match params.get("paper") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'paper' must be one of letter, legal, a4, a3")?,
+            None => PaperSize::default(),
+        };
+        let landscape = params
+            .get("landscape")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let margins = match params.get("margin") {
+            None => Margins::uniform(DEFAULT_PRINT_MARGIN_IN),
+            Some(Value::Object(sides)) => {
+                let mut margins = Margins::uniform(DEFAULT_PRINT_MARGIN_IN);
+                for (side, inches) in sides {
+                    let inches = inches
+                        .as_f64()
+                        .with_context(|| format!("Margin '{}' must be a number", side))?;
+                    match side.as_str() {
+                        "top" => margins.top = inches,
+                        "right" => margins.right = inches,
+                        "bottom" => margins.bottom = inches,
+                        "left" => margins.left = inches,
+                        other => anyhow::bail!(
+                            "Unknown margin '{}' (expected top, right, bottom, left)",
+                            other
+                        ),
                     }
                 }
+                margins
             }
+            Some(v) => Margins::uniform(v.as_f64().context(
+                "'margin' must be inches, or an object of top, right, bottom, left in inches",
+            )?),
+        };
+        let options = PdfOptions {
+            paper,
+            landscape,
+            margins,
+            header: params
+                .get("header")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            footer: params
+                .get("footer")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            background: params
+                .get("background")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+        };
+        let path = params.get("path").and_then(|v| v.as_str());
+        let store = params
+            .get("artifact")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let session_id = Self::get_session_id(&params);
+
+        if store && path.is_some() {
+            anyhow::bail!("Pass either 'path' or 'artifact', not both");
+        }
+        let (width, height) = paper.inches();
+        let (width, height) = if landscape {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let sides = [margins.top, margins.right, margins.bottom, margins.left];
+        let fits = sides.iter().all(|side| *side >= 0.0)
+            && margins.left + margins.right < width
+            && margins.top + margins.bottom < height;
+        if !fits {
+            anyhow::bail!("'margin' must be at least 0 and leave room on the paper");
         }
 
-        Ok(serde_json::to_value(states)?)
-    }
+        let browser_client = self.client()?;
+        let mut result =
+            self.runtime
+                .block_on(browser_client.pdf(path, &options, session_id.as_deref()))?;
 
-    fn handle_health(&self, _params: HashMap<String, Value>) -> Result<Value> {
-        let client = self.client.clone();
+        if store {
+            let encoded = result.data.take().unwrap_or_default();
+            result.artifact = Some(self.store_pdf(encoded, session_id.as_deref())?);
+        }
 
-        let healthy = self.runtime.block_on(async {
-            let client_lock = client.read().await;
-            if let Some(ref browser_client) = *client_lock {
-                browser_client.health_check().await.unwrap_or(false)
-            } else {
-                true // No browser yet is OK
-            }
-        });
+        Ok(serde_json::to_value(result)?)
+    }
 
-        Ok(serde_json::json!({
-            "healthy": healthy,
-            "service": "browser",
-            "version": env!("CARGO_PKG_VERSION")
-        }))
+    /// Save a base64 PDF from Chrome as an artifact.
+    fn store_pdf(&self, encoded: String, session_id: Option<&str>) -> Result<Artifact> {
+        let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+            .context("Failed to decode PDF")?;
+        self.runtime.block_on(self.artifacts.put(
+            ArtifactKind::Pdf,
+            session_id.unwrap_or("default"),
+            "pdf",
+            bytes,
+        ))
     }
 
     // =========================================================================
-    // SESSION MANAGEMENT HANDLERS
+    // CANVAS
     // =========================================================================
 
-    fn handle_session_new(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let session_id = params
-            .get("id")
-            .or_else(|| params.get("session_id"))
+    fn handle_canvas_draw(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
             .and_then(|v| v.as_str())
-            .context("Missing 'id' parameter")?;
+            .context("Missing 'selector' parameter")?;
+        let strokes: Vec<Vec<CanvasPoint>> = serde_json::from_value(
+            params
+                .get("strokes")
+                .cloned()
+                .context("Missing 'strokes' parameter")?,
+        )
+        .context("'strokes' must be a list of strokes, each a list of [x, y], [x, y, t], or {x, y, t?, pressure?} points")?;
+        if strokes.is_empty() {
+            anyhow::bail!("'strokes' is empty");
+        }
+        let pointer: PointerKind = match params.get("pointer") {
+            Some(v) => {
+                serde_json::from_value(v.clone()).context("'pointer' must be mouse or pen")?
+            }
+            None => PointerKind::default(),
+        };
+        let options = CanvasDrawOptions {
+            normalized: params
+                .get("normalized")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            step_ms: params
+                .get("step_ms")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_CANVAS_STEP_MS),
+            pointer,
+        };
+        let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-
-        let id = self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.create_session(session_id).await
-        })?;
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "session_id": id
-        }))
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.canvas_draw(selector, &strokes, &options, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_session_list(&self, _params: HashMap<String, Value>) -> Result<Value> {
-        let client = self.client.clone();
+    fn handle_drag_and_drop(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let source = params
+            .get("source")
+            .and_then(|v| v.as_str())
+            .context("Missing 'source' parameter")?;
+        let target = params
+            .get("target")
+            .and_then(|v| v.as_str())
+            .context("Missing 'target' parameter")?;
+        let steps = params
+            .get("steps")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_DRAG_STEPS as u64);
+        if !(1..=MAX_DRAG_STEPS as u64).contains(&steps) {
+            anyhow::bail!("'steps' must be between 1 and {}", MAX_DRAG_STEPS);
+        }
+        let session_id = Self::get_session_id(&params);
 
-        let sessions = self.runtime.block_on(async {
-            let client_lock = client.read().await;
-            if let Some(ref browser_client) = *client_lock {
-                browser_client.list_sessions().await
-            } else {
-                vec![]
-            }
-        });
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.drag_and_drop(source, target, steps as u32, session_id.as_deref()),
+        )?;
 
-        Ok(serde_json::json!({
-            "sessions": sessions
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_session_close(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let session_id = params
-            .get("id")
-            .or_else(|| params.get("session_id"))
+    fn handle_canvas_capture(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
             .and_then(|v| v.as_str())
-            .context("Missing 'id' parameter")?;
+            .context("Missing 'selector' parameter")?;
+        let path = params.get("path").and_then(|v| v.as_str());
+        let store = params
+            .get("artifact")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
+        if store && path.is_some() {
+            anyhow::bail!("Pass either 'path' or 'artifact', not both");
+        }
 
-        self.runtime.block_on(async {
-            let client_lock = client.read().await;
-            if let Some(ref browser_client) = *client_lock {
-                browser_client.close_session(session_id).await
-            } else {
-                Ok(())
-            }
-        })?;
+        let browser_client = self.client()?;
+
+        let mut result = self.runtime.block_on(browser_client.canvas_capture(
+            selector,
+            path,
+            session_id.as_deref(),
+        ))?;
+
+        if store {
+            let encoded = result.data.take().unwrap_or_default();
+            result.artifact =
+                Some(self.store_screenshot(encoded, result.format, session_id.as_deref())?);
+        }
 
-        Ok(serde_json::json!({
-            "success": true,
-            "session_id": session_id
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
     // =========================================================================
-    // FEATURE PARITY HANDLERS
+    // MEDIA
     // =========================================================================
 
-    fn handle_select(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .context("Missing 'selector' parameter")?;
-        let value = params
-            .get("value")
-            .and_then(|v| v.as_str())
-            .context("Missing 'value' parameter")?;
+    fn handle_media_state(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params.get("selector").and_then(|v| v.as_str());
+        let wait_ms = params
+            .get("wait_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MEDIA_WAIT_MS);
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.to_string();
-        let value = value.to_string();
+        let browser_client = self.client()?;
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client
-                .select(&selector, &value, session_id.as_deref())
-                .await
-        })?;
+        let result = self.runtime.block_on(browser_client.media_state(
+            selector,
+            wait_ms,
+            session_id.as_deref(),
+        ))?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "selector": selector,
-            "value": value
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_check(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .context("Missing 'selector' parameter")?;
-        let checked = params
-            .get("checked")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true); // Default to checking
+    fn handle_media_control(
+        &self,
+        params: HashMap<String, Value>,
+        action: MediaAction,
+    ) -> Result<Value> {
+        let selector = params.get("selector").and_then(|v| v.as_str());
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.to_string();
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client
-                .check(&selector, checked, session_id.as_deref())
-                .await
-        })?;
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.media_control(selector, action, session_id.as_deref()),
+        )?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "selector": selector,
-            "checked": checked
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_hover(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .context("Missing 'selector' parameter")?;
+    fn handle_media_seek(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let time = params
+            .get("time")
+            .and_then(|v| v.as_f64())
+            .context("Missing 'time' parameter (seconds)")?;
+        if !time.is_finite() || time < 0.0 {
+            anyhow::bail!("'time' must be a non-negative number of seconds");
+        }
+        self.handle_media_control(params, MediaAction::Seek(time))
+    }
+
+    fn handle_media_mute(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let muted = params
+            .get("muted")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.to_string();
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client.hover(&selector, session_id.as_deref()).await
-        })?;
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.mute_media(muted, session_id.as_deref()),
+        )?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "selector": selector
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_scroll(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params.get("selector").and_then(|v| v.as_str());
-        let x = params.get("x").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-        let y = params.get("y").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+    // =========================================================================
+    // NOTIFICATIONS
+    // =========================================================================
+
+    fn handle_get_notifications(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let clear = params
+            .get("clear")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.map(|s| s.to_string());
+        let browser_client = self.client()?;
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client
-                .scroll(selector.as_deref(), x, y, session_id.as_deref())
-                .await
-        })?;
+        let result = self
+            .runtime
+            .block_on(browser_client.get_notifications(clear, session_id.as_deref()))?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "x": x,
-            "y": y
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_press_combo(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let key = params
-            .get("key")
+    fn handle_csp_trial(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let policy = params
+            .get("policy")
             .and_then(|v| v.as_str())
-            .context("Missing 'key' parameter")?;
-        let modifiers: Vec<String> = params
-            .get("modifiers")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect()
-            })
-            .unwrap_or_default();
+            .context("Missing 'policy' parameter")?;
+        let url = params.get("url").and_then(|v| v.as_str());
+        let mode: CspMode = serde_json::from_value(params.get("mode").cloned().unwrap_or_default())
+            .context("'mode' must be one of report_only, enforce")?;
+        let settle_ms = params
+            .get("wait_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_CSP_SETTLE_MS);
         let session_id = Self::get_session_id(&params);
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let key = key.to_string();
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Navigation, &params)?;
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            let mod_refs: Vec<&str> = modifiers.iter().map(|s| s.as_str()).collect();
+        let result = self.runtime.block_on(async {
+            let url = match url {
+                Some(url) => url.to_string(),
+                None => browser_client.reload_url(session_id.as_deref()).await?,
+            };
+            if let Some(robots) = &self.robots {
+                robots.check(&url).await?;
+            }
+            let _permit = self.throttle.acquire(&url).await;
             browser_client
-                .press_combo(&mod_refs, &key, session_id.as_deref())
+                .csp_trial(
+                    &url,
+                    policy,
+                    mode,
+                    Duration::from_millis(settle_ms),
+                    limit,
+                    session_id.as_deref(),
+                )
                 .await
         })?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "key": key,
-            "modifiers": modifiers
-        }))
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn handle_upload(&self, params: HashMap<String, Value>) -> Result<Value> {
-        let selector = params
-            .get("selector")
-            .and_then(|v| v.as_str())
-            .context("Missing 'selector' parameter")?;
-        let path = params
-            .get("path")
+    fn handle_locale_variants(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let url = params
+            .get("url")
             .and_then(|v| v.as_str())
-            .context("Missing 'path' parameter")?;
-        let session_id = Self::get_session_id(&params);
+            .context("Missing 'url' parameter")?;
+        let variants: Vec<LocaleVariant> =
+            serde_json::from_value(params.get("variants").cloned().unwrap_or_default())
+                .context("'variants' must be an array of {locale, timezone?, geolocation?}")?;
+        if variants.is_empty() || variants.len() > MAX_LOCALE_VARIANTS {
+            anyhow::bail!("'variants' must have 1 to {} entries", MAX_LOCALE_VARIANTS);
+        }
+        for variant in &variants {
+            if variant.locale.trim().is_empty() {
+                anyhow::bail!("Every variant needs a 'locale'");
+            }
+            if let Some(position) = &variant.geolocation {
+                if !(-90.0..=90.0).contains(&position.latitude)
+                    || !(-180.0..=180.0).contains(&position.longitude)
+                {
+                    anyhow::bail!(
+                        "'geolocation' must have latitude -90..90 and longitude -180..180"
+                    );
+                }
+            }
+        }
+        let settle = Duration::from_millis(
+            params
+                .get("wait_ms")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_VARIANT_SETTLE_MS),
+        );
 
-        let client = self.client.clone();
-        let user_data_dir = self.user_data_dir.clone();
-        let headless = self.headless;
-        let connect_url = self.connect_url.clone();
-        let selector = selector.to_string();
-        let path = path.to_string();
+        let browser_client = self.client()?;
 
-        self.runtime.block_on(async {
-            let browser_client =
-                Self::get_or_init_client(&client, &user_data_dir, headless, connect_url.as_deref())
-                    .await?;
-            browser_client
-                .upload(&selector, &path, session_id.as_deref())
-                .await
+        let snapshots = self.runtime.block_on(async {
+            if let Some(robots) = &self.robots {
+                robots.check(url).await?;
+            }
+            let loads = variants.into_iter().map(|variant| {
+                let browser_client = &browser_client;
+                async move {
+                    let _permit = self.throttle.acquire(url).await;
+                    let requested = variant.clone();
+                    // One variant failing doesn't lose the others
+                    browser_client
+                        .locale_variant(url, variant, settle)
+                        .await
+                        .unwrap_or_else(|e| LocaleVariantSnapshot {
+                            variant: requested,
+                            error: Some(format!("{:#}", e)),
+                            ..LocaleVariantSnapshot::default()
+                        })
+                }
+            });
+            Ok::<_, anyhow::Error>(futures::future::join_all(loads).await)
         })?;
 
-        Ok(serde_json::json!({
-            "success": true,
-            "selector": selector,
-            "path": path
-        }))
+        let distinct: std::collections::HashSet<&str> = snapshots
+            .iter()
+            .filter(|s| s.error.is_none())
+            .map(|s| s.text.as_str())
+            .collect();
+        let report = LocaleVariantReport {
+            url: url.to_string(),
+            distinct_snapshots: distinct.len(),
+            variants: snapshots,
+        };
+
+        Ok(serde_json::to_value(report)?)
     }
 
-    // =========================================================================
-    // EXTENSION BRIDGE ROUTING
-    // =========================================================================
+    fn handle_detect_blockers(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
 
-    /// Route extension-specific methods to the Chrome extension via WebSocket.
-    /// Called by dispatch() when the method is an extension-only method.
-    fn dispatch_to_extension(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
-        let bridge = self.extension_bridge.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "Extension method '{}' requires Chrome extension. \
-                 Start daemon with --extension-bridge and install the FGP extension.",
-                method
-            )
-        })?;
+        let browser_client = self.client()?;
 
-        if !bridge.is_connected_blocking() {
-            anyhow::bail!(
-                "Extension not connected. Install the FGP Browser Bridge extension \
-                 from chrome://extensions and ensure it's enabled."
-            );
+        let result = self
+            .runtime
+            .block_on(browser_client.detect_blockers(session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_check_links(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+        let concurrency = params
+            .get("concurrency")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_LINK_CONCURRENCY);
+        if concurrency == 0 || concurrency > MAX_LINK_CONCURRENCY {
+            anyhow::bail!("'concurrency' must be 1 to {}", MAX_LINK_CONCURRENCY);
         }
+        let options = LinkCheckOptions {
+            concurrency,
+            timeout: Duration::from_millis(
+                params
+                    .get("timeout_ms")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_LINK_TIMEOUT_MS),
+            ),
+            max_links: params
+                .get("max_links")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(DEFAULT_MAX_LINKS),
+            use_cache: params
+                .get("cache")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+        };
 
-        // Strip 'browser.' prefix for the actual Chrome Extension API call
-        let ext_method = extension_method_name(method);
-        tracing::debug!(
-            "Routing '{}' to Chrome extension as '{}'",
-            method,
-            ext_method
-        );
-        let response = bridge.call_blocking(ext_method, params)?;
-        ExtensionBridge::response_to_value(response)
+        let browser_client = self.client()?;
+
+        let report = self.runtime.block_on(async {
+            let page = browser_client.page_links(session_id.as_deref()).await?;
+            Ok::<_, anyhow::Error>(self.links.check(&page, &options, &self.throttle).await)
+        })?;
+
+        Ok(serde_json::to_value(report)?)
     }
-}
 
-impl FgpService for BrowserService {
-    fn name(&self) -> &str {
-        "browser"
+    fn handle_third_party_report(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.third_party_report(session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn version(&self) -> &str {
-        env!("CARGO_PKG_VERSION")
+    fn handle_get_environment(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.environment(session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn dispatch(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
-        // Check if this is an extension-only method that should be routed to the Chrome extension
-        if is_extension_method(method) {
-            return self.dispatch_to_extension(method, params);
+    fn handle_cdp(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let method = params
+            .get("method")
+            .and_then(|v| v.as_str())
+            .context("Missing 'method' parameter")?;
+        let Some(allowed) = &self.cdp_domains else {
+            anyhow::bail!(
+                "CDP_NOT_ALLOWED: start the daemon with --allow-cdp-domains to enable raw CDP calls"
+            );
+        };
+        let domain = cdp_domain(method)?;
+        if !allowed.allows(domain) {
+            anyhow::bail!(
+                "CDP_NOT_ALLOWED: domain '{}' isn't enabled (allowed: {})",
+                domain,
+                allowed
+            );
         }
+        let cdp_params = params.get("params").cloned().unwrap_or(Value::Null);
+        let session_id = Self::get_session_id(&params);
 
-        match method {
-            "health" => self.handle_health(params),
-            // Navigation and state
-            "browser.open" | "open" => self.handle_open(params),
-            "browser.snapshot" | "snapshot" => self.handle_snapshot(params),
-            "browser.screenshot" | "screenshot" => self.handle_screenshot(params),
-            // Interaction
-            "browser.click" | "click" => self.handle_click(params),
-            "browser.fill" | "fill" => self.handle_fill(params),
-            "browser.press" | "press" => self.handle_press(params),
-            "browser.select" | "select" => self.handle_select(params),
-            "browser.check" | "check" => self.handle_check(params),
-            "browser.hover" | "hover" => self.handle_hover(params),
-            "browser.scroll" | "scroll" => self.handle_scroll(params),
-            "browser.press_combo" | "press_combo" => self.handle_press_combo(params),
-            "browser.upload" | "upload" => self.handle_upload(params),
-            // Auth state
-            "browser.state.save" | "state.save" => self.handle_state_save(params),
-            "browser.state.load" | "state.load" => self.handle_state_load(params),
-            "browser.state.list" | "state.list" => self.handle_state_list(params),
-            // Session management
-            "browser.session.new" | "session.new" => self.handle_session_new(params),
-            "browser.session.list" | "session.list" => self.handle_session_list(params),
-            "browser.session.close" | "session.close" => self.handle_session_close(params),
-            _ => Err(anyhow::anyhow!("Unknown method: {}", method)),
+        let browser_client = self.client()?;
+
+        let result =
+            self.runtime
+                .block_on(browser_client.cdp(method, cdp_params, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(CdpResult {
+            method: method.to_string(),
+            result,
+        })?)
+    }
+
+    // =========================================================================
+    // REF EXPORT
+    // =========================================================================
+
+    fn handle_export_refs(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let refs: Option<Vec<String>> = params.get("refs").and_then(|v| v.as_array()).map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect()
+        });
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.export_refs(refs.as_deref(), session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    // =========================================================================
+    // TIMELINE HANDLERS
+    // =========================================================================
+
+    fn timeline_auto(&self, session_id: Option<&str>) -> bool {
+        self.timelines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session_id.unwrap_or("default"))
+            .is_some_and(|timeline| timeline.auto)
+    }
+
+    /// Screenshot the session into the artifact store and append it to the
+    /// session's timeline.
+    fn capture_frame(
+        &self,
+        browser_client: &BrowserClient,
+        session_id: Option<&str>,
+        trigger: TimelineTrigger,
+        label: Option<String>,
+    ) -> Result<TimelineFrame> {
+        let options = ScreenshotOptions {
+            full_page: true,
+            ..Default::default()
+        };
+        let (shot, page) = self.runtime.block_on(async {
+            futures::try_join!(
+                browser_client.screenshot(None, &options, session_id),
+                browser_client.page_info(session_id),
+            )
+        })?;
+        let artifact =
+            self.store_screenshot(shot.data.unwrap_or_default(), shot.format, session_id)?;
+
+        Ok(self
+            .timelines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(session_id.unwrap_or("default").to_string())
+            .or_default()
+            .push(trigger, page.url, page.title, label, artifact))
+    }
+
+    fn handle_timeline_auto(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let enabled = params
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.unwrap_or_else(|| "default".to_string());
+
+        self.timelines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(session.clone())
+            .or_default()
+            .auto = enabled;
+
+        Ok(serde_json::to_value(TimelineAuto {
+            session_id: session,
+            auto: enabled,
+        })?)
+    }
+
+    fn handle_timeline_capture(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let label = params
+            .get("label")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let frame = self.capture_frame(
+            &browser_client,
+            session_id.as_deref(),
+            TimelineTrigger::Manual,
+            label,
+        )?;
+
+        Ok(serde_json::to_value(frame)?)
+    }
+
+    fn handle_timeline_index(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let since = params.get("since").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.as_deref().unwrap_or("default");
+
+        let timelines = self.timelines.lock().unwrap_or_else(|e| e.into_inner());
+        let index = match timelines.get(session) {
+            Some(timeline) => timeline.index(session, since),
+            None => Timeline::default().index(session, since),
+        };
+
+        Ok(serde_json::to_value(index)?)
+    }
+
+    fn handle_timeline_clear(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.unwrap_or_else(|| "default".to_string());
+
+        let cleared = self
+            .timelines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(&session)
+            .map_or(0, Timeline::clear);
+
+        Ok(serde_json::to_value(TimelineCleared {
+            session_id: session,
+            cleared,
+        })?)
+    }
+
+    // =========================================================================
+    // RECORDING HANDLERS
+    // =========================================================================
+
+    fn handle_recording_start(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let format: VideoFormat = match params.get("format") {
+            Some(v) => serde_json::from_value(v.clone()).context("'format' must be webm or mp4")?,
+            None => VideoFormat::default(),
+        };
+        let fps = params
+            .get("fps")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(recording::DEFAULT_FPS as u64);
+        if !(1..=recording::MAX_FPS as u64).contains(&fps) {
+            anyhow::bail!("'fps' must be between 1 and {}", recording::MAX_FPS);
+        }
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.as_deref().unwrap_or("default");
+        if self
+            .recordings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(session)
+        {
+            anyhow::bail!("Session {} is already being recorded", session);
+        }
+        let path = match params.get("path").and_then(|v| v.as_str()) {
+            Some(path) => PathBuf::from(path),
+            None => {
+                std::fs::create_dir_all(&self.recordings_dir)
+                    .context("Failed to create recordings directory")?;
+                self.recordings_dir.join(format!(
+                    "{}-{}.{}",
+                    session,
+                    Utc::now().format("%Y%m%dT%H%M%S"),
+                    format.extension()
+                ))
+            }
+        };
+
+        let browser_client = self.client()?;
+        let recording = self.runtime.block_on(async {
+            let screencast = browser_client
+                .join_screencast(ScreencastOptions::default(), false, session_id.as_deref())
+                .await?;
+            Recording::start(session, screencast, format, fps as u32, path.clone())
+        })?;
+        self.recordings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session.to_string(), recording);
+
+        Ok(serde_json::to_value(RecordingStarted {
+            session_id: session.to_string(),
+            path: path.display().to_string(),
+            format,
+            fps: fps as u32,
+        })?)
+    }
+
+    fn handle_recording_stop(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let store = params
+            .get("artifact")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.as_deref().unwrap_or("default");
+
+        let recording = self
+            .recordings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session)
+            .with_context(|| format!("Session {} is not being recorded", session))?;
+        let mut result = self.runtime.block_on(recording.finish())?;
+
+        if store {
+            let bytes = std::fs::read(&result.path)
+                .with_context(|| format!("Failed to read {}", result.path))?;
+            result.artifact = Some(self.runtime.block_on(self.artifacts.put(
+                ArtifactKind::Recording,
+                session,
+                result.format.extension(),
+                bytes,
+            ))?);
         }
+
+        Ok(serde_json::to_value(result)?)
     }
 
-    fn method_list(&self) -> Vec<MethodInfo> {
-        // Common session parameter schema
-        let session_param = || {
-            SchemaBuilder::string()
-                .description("Session ID for isolated browser context (optional)")
+    // =========================================================================
+    // CRAWL HANDLERS
+    // =========================================================================
+
+    fn handle_crawl(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let options: CrawlOptions = serde_json::from_value(serde_json::to_value(&params)?)
+            .context("Invalid crawl options")?;
+        if options.max_pages == 0 {
+            anyhow::bail!("'max_pages' must be at least 1");
+        }
+
+        let browser_client = self.client()?;
+        let job = CrawlJob::new(options);
+        {
+            let mut crawls = self.crawls.lock().unwrap_or_else(|e| e.into_inner());
+            crawl::evict(&mut crawls, Instant::now());
+            crawls.insert(job.id.clone(), Arc::clone(&job));
+        }
+
+        self.runtime.spawn(crawl::run(
+            Arc::clone(&job),
+            browser_client,
+            Arc::clone(&self.throttle),
+            self.robots.clone(),
+        ));
+
+        Ok(serde_json::to_value(CrawlStarted {
+            crawl_id: job.id.clone(),
+        })?)
+    }
+
+    fn crawl_job(&self, params: &HashMap<String, Value>) -> Result<Arc<CrawlJob>> {
+        let id = params
+            .get("crawl_id")
+            .and_then(|v| v.as_str())
+            .context("Missing 'crawl_id' parameter")?;
+        self.crawls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+            .with_context(|| format!("Crawl not found: {}", id))
+    }
+
+    fn handle_crawl_status(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let job = self.crawl_job(&params)?;
+        let since = params.get("since").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        Ok(serde_json::to_value(job.status(since))?)
+    }
+
+    fn handle_crawl_cancel(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let job = self.crawl_job(&params)?;
+        job.cancel();
+        Ok(serde_json::to_value(CrawlCancelled {
+            crawl_id: job.id.clone(),
+            cancelled: true,
+        })?)
+    }
+
+    // =========================================================================
+    // ASSERTION HANDLERS
+    // =========================================================================
+
+    fn handle_assert_text(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let expected = params
+            .get("expected")
+            .and_then(|v| v.as_str())
+            .context("Missing 'expected' parameter")?;
+        let assertion = Assertion::Text {
+            selector: params
+                .get("selector")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            expected: expected.to_string(),
+            exact: params
+                .get("exact")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
         };
+        self.handle_assert(assertion, &params)
+    }
 
-        vec![
-            // ================================================================
-            // Navigation and State
-            // ================================================================
-            MethodInfo::new("browser.open", "Navigate to a URL")
+    fn handle_assert_visible(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let assertion = Assertion::Visible {
+            selector: selector.to_string(),
+        };
+        self.handle_assert(assertion, &params)
+    }
+
+    fn handle_assert_url(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let pattern = params
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .context("Missing 'pattern' parameter")?;
+        let assertion = Assertion::Url {
+            pattern: pattern.to_string(),
+        };
+        self.handle_assert(assertion, &params)
+    }
+
+    fn handle_assert_count(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let count = params
+            .get("count")
+            .and_then(|v| v.as_u64())
+            .context("Missing 'count' parameter")?;
+        let assertion = Assertion::Count {
+            selector: selector.to_string(),
+            expected: count as usize,
+        };
+        self.handle_assert(assertion, &params)
+    }
+
+    fn handle_verify(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let checks: Vec<Assertion> = serde_json::from_value(
+            params
+                .get("assertions")
+                .cloned()
+                .context("Missing 'assertions' parameter")?,
+        )
+        .context("Invalid 'assertions' parameter")?;
+        if checks.is_empty() {
+            anyhow::bail!("'assertions' must not be empty");
+        }
+        let session_id = Self::get_session_id(&params);
+        let screenshot = params
+            .get("screenshot")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let browser_client = self.client()?;
+        let mut report = self
+            .runtime
+            .block_on(browser_client.verify(&checks, session_id.as_deref()))?;
+
+        if !report.passed && screenshot {
+            report.screenshot = self.capture_evidence(&browser_client, session_id.as_deref());
+        }
+
+        Ok(serde_json::to_value(report)?)
+    }
+
+    /// Run one assertion, attaching a screenshot artifact if it failed
+    /// (unless `screenshot: false`).
+    fn handle_assert(
+        &self,
+        assertion: Assertion,
+        params: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let session_id = Self::get_session_id(params);
+        let screenshot = params
+            .get("screenshot")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let browser_client = self.client()?;
+        let mut result = self
+            .runtime
+            .block_on(browser_client.assert(&assertion, session_id.as_deref()))?;
+
+        if !result.passed && screenshot {
+            result.evidence.screenshot =
+                self.capture_evidence(&browser_client, session_id.as_deref());
+        }
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Screenshot the session into the artifact store. Evidence is best
+    /// effort: a failure here shouldn't mask the assertion result.
+    fn capture_evidence(
+        &self,
+        browser_client: &BrowserClient,
+        session_id: Option<&str>,
+    ) -> Option<Artifact> {
+        let options = ScreenshotOptions {
+            full_page: true,
+            ..Default::default()
+        };
+        let captured = self
+            .runtime
+            .block_on(browser_client.screenshot(None, &options, session_id))
+            .and_then(|shot| {
+                self.store_screenshot(shot.data.unwrap_or_default(), shot.format, session_id)
+            });
+
+        match captured {
+            Ok(artifact) => Some(artifact),
+            Err(e) => {
+                tracing::warn!("Failed to capture assertion evidence: {}", e);
+                None
+            }
+        }
+    }
+
+    fn handle_artifact_get(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let id = params
+            .get("id")
+            .and_then(|v| v.as_str())
+            .context("Missing 'id' parameter")?;
+
+        let data = self.runtime.block_on(self.artifacts.get(id))?;
+
+        Ok(serde_json::to_value(ArtifactData {
+            id: id.to_string(),
+            content_type: crate::artifacts::content_type_for(id).to_string(),
+            size: data.len(),
+            data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data),
+        })?)
+    }
+
+    fn handle_artifact_gc(&self) -> Result<Value> {
+        let report = self
+            .runtime
+            .block_on(self.artifacts.collect_garbage(&self.retention))?;
+        Ok(serde_json::to_value(report)?)
+    }
+
+    fn handle_maintain(&self) -> Result<Value> {
+        // Nothing to maintain before the browser is launched
+        let Some(browser_client) = self.runtime.block_on(self.client.read()).clone() else {
+            return Ok(serde_json::to_value(MaintenanceReport::default())?);
+        };
+        let report = self.runtime.block_on(Self::run_maintenance(
+            &browser_client,
+            &self.maintenance,
+            &self.timelines,
+            &self.recordings,
+            &self.streams,
+            &self.observers,
+            self.robots.as_deref(),
+            &self.throttle,
+        ))?;
+        Ok(serde_json::to_value(report)?)
+    }
+
+    /// A maintenance pass: the client's, then the service's own state for
+    /// the sessions it removed, and the observers' buffers. Sessions being
+    /// recorded or streamed keep their page.
+    async fn run_maintenance(
+        client: &BrowserClient,
+        policy: &MaintenancePolicy,
+        timelines: &Mutex<HashMap<String, Timeline>>,
+        recordings: &Mutex<HashMap<String, Recording>>,
+        streams: &Mutex<HashMap<String, FrameStream>>,
+        observers: &Mutex<HashMap<String, Observer>>,
+        robots: Option<&RobotsPolicy>,
+        throttle: &OriginThrottle,
+    ) -> Result<MaintenanceReport> {
+        let busy: HashSet<String> = {
+            let recordings = recordings.lock().unwrap_or_else(|e| e.into_inner());
+            let streams = streams.lock().unwrap_or_else(|e| e.into_inner());
+            recordings.keys().chain(streams.keys()).cloned().collect()
+        };
+        let mut report = client.maintain(policy, &busy, robots, throttle).await?;
+
+        for session_id in &report.pruned {
+            let recording = recordings
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(session_id);
+            if let Some(recording) = recording {
+                if let Err(e) = recording.finish().await {
+                    tracing::warn!("Recording of session {} failed: {}", session_id, e);
+                }
+            }
+            let stream = streams
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(session_id);
+            if let Some(stream) = stream {
+                stream.stop().await;
+            }
+            timelines
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(session_id);
+        }
+
+        let mut observers = observers.lock().unwrap_or_else(|e| e.into_inner());
+        observers.retain(|_, observer| !report.pruned.contains(&observer.session_id));
+        for observer in observers.values() {
+            report.trimmed_events += observer.trim(policy.keep_events);
+        }
+
+        Ok(report)
+    }
+
+    /// The interaction to rehearse, if this is a `dry_run` call.
+    fn dry_run_action(method: &str, params: &HashMap<String, Value>) -> Option<DryRunAction> {
+        let dry_run = params
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        DryRunAction::from_method(method).filter(|_| dry_run)
+    }
+
+    fn handle_dry_run(
+        &self,
+        action: DryRunAction,
+        params: HashMap<String, Value>,
+    ) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let value = match action {
+            DryRunAction::Fill | DryRunAction::Select => Some(
+                params
+                    .get("value")
+                    .and_then(|v| v.as_str())
+                    .context("Missing 'value' parameter")?,
+            ),
+            DryRunAction::Upload => Some(
+                params
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .context("Missing 'path' parameter")?,
+            ),
+            DryRunAction::Click | DryRunAction::Check | DryRunAction::Hover => None,
+        };
+        let checked = params
+            .get("checked")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let report = self.runtime.block_on(browser_client.dry_run(
+            action,
+            selector,
+            value,
+            checked,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(report)?)
+    }
+
+    /// Run an interaction (or its dry run) on the match `rank` picks, and
+    /// report the choice as `ranking`.
+    fn handle_ranked(&self, method: &str, mut params: HashMap<String, Value>) -> Result<Value> {
+        let strategy: ElementRanking = serde_json::from_value(
+            params.remove("rank").unwrap_or_default(),
+        )
+        .context("'rank' must be one of document, in_viewport, largest, topmost, reading_order")?;
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?
+            .to_string();
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let ranking = self.runtime.block_on(browser_client.rank_matches(
+            &selector,
+            strategy,
+            session_id.as_deref(),
+        ))?;
+
+        params.insert("selector".to_string(), json!(PICKED_SELECTOR));
+        let mut result = self.dispatch(method, params)?;
+        if let Some(obj) = result.as_object_mut() {
+            // Echo the caller's selector rather than the marker
+            for value in obj.values_mut() {
+                if value.as_str() == Some(PICKED_SELECTOR) {
+                    *value = json!(selector);
+                }
+            }
+            if obj.contains_key("dry_run") {
+                obj.insert("matches".to_string(), json!(ranking.matches));
+            }
+            obj.insert("ranking".to_string(), serde_json::to_value(&ranking)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Selectors among the element params of an element action that `pick`
+    /// accepts, by param. Waits and assertions aren't included: their
+    /// elements may not exist yet, and resolving them up front would fail
+    /// them.
+    fn element_selectors(
+        method: &str,
+        params: &HashMap<String, Value>,
+        pick: fn(&str) -> bool,
+    ) -> Vec<(&'static str, String)> {
+        let keys: &[&'static str] = match method.strip_prefix("browser.").unwrap_or(method) {
+            "drag_and_drop" => &["source", "target"],
+            "click" | "fill" | "clear" | "focus" | "blur" | "type_text" | "fill_rich_text"
+            | "select" | "select_option" | "check" | "hover" | "set_date" | "upload"
+            | "upload_file" | "highlight" | "scroll_to_element" | "screenshot"
+            | "screenshot_element" => &["selector"],
+            _ => &[],
+        };
+        keys.iter()
+            .filter_map(|&key| {
+                let selector = params.get(key)?.as_str()?;
+                pick(selector).then(|| (key, selector.to_string()))
+            })
+            .collect()
+    }
+
+    /// Run a call with its XPath selectors swapped for CSS selectors of the
+    /// elements they match.
+    fn handle_xpath(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        xpaths: Vec<(&'static str, String)>,
+    ) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+        let selectors: Vec<&str> = xpaths.iter().map(|(_, s)| s.as_str()).collect();
+
+        let browser_client = self.client()?;
+
+        let markers = self
+            .runtime
+            .block_on(browser_client.mark_xpaths(&selectors, session_id.as_deref()))?;
+        self.dispatch_resolved(method, params, &xpaths, markers)
+    }
+
+    /// Run a call with its chained selectors swapped for refs of the
+    /// elements they end on.
+    fn handle_chained(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        chains: Vec<(&'static str, String)>,
+    ) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+        let selectors: Vec<&str> = chains.iter().map(|(_, s)| s.as_str()).collect();
+
+        let browser_client = self.client()?;
+
+        let refs = self
+            .runtime
+            .block_on(browser_client.resolve_chains(&selectors, session_id.as_deref()))?;
+        self.dispatch_resolved(method, params, &chains, refs)
+    }
+
+    /// Dispatch with each of `selectors` replaced by its `resolved` form,
+    /// echoing the caller's selectors in the result.
+    fn dispatch_resolved(
+        &self,
+        method: &str,
+        mut params: HashMap<String, Value>,
+        selectors: &[(&'static str, String)],
+        resolved: Vec<String>,
+    ) -> Result<Value> {
+        for ((key, _), replacement) in selectors.iter().zip(&resolved) {
+            params.insert(key.to_string(), json!(replacement));
+        }
+        let mut result = self.dispatch(method, params)?;
+        if let Some(obj) = result.as_object_mut() {
+            // Echo the caller's selectors rather than the replacements
+            for value in obj.values_mut() {
+                if let Some(i) = resolved.iter().position(|r| value.as_str() == Some(r)) {
+                    *value = json!(selectors[i].1);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Whether a call should go through `handle_recovering`: element actions
+    /// (not dry runs) with `recover`, or by default if the daemon recovers.
+    fn wants_recovery(&self, method: &str, params: &HashMap<String, Value>) -> bool {
+        let recover = params
+            .get("recover")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(self.recovery);
+        let dry_run = params.get("dry_run").and_then(|v| v.as_bool()) == Some(true);
+        recover && !dry_run && !Self::element_selectors(method, params, |_| true).is_empty()
+    }
+
+    /// Run an element action, retrying it while its target is missing or
+    /// stale and then once more after reloading the page, with ref targets
+    /// found again by fingerprint. The steps taken are reported as `recovery`.
+    fn handle_recovering(&self, method: &str, mut params: HashMap<String, Value>) -> Result<Value> {
+        // The attempts themselves don't recover
+        params.insert("recover".to_string(), json!(false));
+        let selectors = Self::element_selectors(method, &params, |_| true);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let fingerprints: Vec<_> = selectors
+            .iter()
+            .filter(|(_, selector)| selector.starts_with('@') && !is_chained(selector))
+            .map(|(key, selector)| {
+                let fingerprint = self
+                    .runtime
+                    .block_on(browser_client.fingerprint(selector, session_id.as_deref()));
+                (*key, selector.clone(), fingerprint)
+            })
+            .collect();
+
+        let mut steps = Vec::new();
+        let mut attempt = 1;
+        let mut result = self.dispatch(method, params.clone());
+        let mut reloaded = false;
+        while let Err(e) = &result {
+            if !is_recoverable(e) {
+                break;
+            }
+            steps.push(RecoveryStep::Retry {
+                attempt,
+                error: format!("{:#}", e),
+            });
+            if attempt > RECOVERY_RETRIES {
+                reloaded = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(RETRY_DELAY_MS << (attempt - 1)));
+            attempt += 1;
+            result = self.dispatch(method, params.clone());
+        }
+
+        if let (true, Err(e)) = (reloaded, &result) {
+            let step = self
+                .runtime
+                .block_on(browser_client.reload_for_recovery(session_id.as_deref()))
+                .with_context(|| format!("RECOVERY_FAILED: {:#}", e))?;
+            steps.push(step);
+
+            for (key, selector, fingerprint) in &fingerprints {
+                let Some(fingerprint) = fingerprint else {
+                    anyhow::bail!(
+                        "RECOVERY_FAILED: {} is not in the last snapshot, so it can't be found again after reloading ({:#})",
+                        selector,
+                        e
+                    );
+                };
+                let found = self
+                    .runtime
+                    .block_on(browser_client.refind(fingerprint, session_id.as_deref()))?
+                    .with_context(|| {
+                        format!(
+                            "RECOVERY_FAILED: no {} '{}' after reloading ({:#})",
+                            fingerprint.role,
+                            fingerprint.name.as_deref().unwrap_or_default(),
+                            e
+                        )
+                    })?;
+                params.insert(key.to_string(), json!(found));
+                steps.push(RecoveryStep::Retarget {
+                    param: key.to_string(),
+                    from: selector.clone(),
+                    to: found,
+                });
+            }
+
+            attempt += 1;
+            result = self.dispatch(method, params).map_err(|e| {
+                anyhow::anyhow!("RECOVERY_FAILED: still failing after reloading: {:#}", e)
+            });
+        }
+
+        let mut result = result?;
+        if !steps.is_empty() {
+            if let Some(obj) = result.as_object_mut() {
+                // Echo the caller's refs rather than the ones found again
+                for step in &steps {
+                    if let RecoveryStep::Retarget { from, to, .. } = step {
+                        for value in obj.values_mut() {
+                            if value.as_str() == Some(to.as_str()) {
+                                *value = json!(from);
+                            }
+                        }
+                    }
+                }
+                let report = RecoveryReport {
+                    attempts: attempt,
+                    reloaded,
+                    steps,
+                };
+                obj.insert("recovery".to_string(), serde_json::to_value(report)?);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn handle_click(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let button: ClickButton =
+            serde_json::from_value(params.get("button").cloned().unwrap_or_default())
+                .context("'button' must be one of left, right, middle")?;
+        let click_count = params
+            .get("click_count")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1);
+        if !(1..=3).contains(&click_count) {
+            anyhow::bail!("'click_count' must be between 1 and 3");
+        }
+        let modifiers: Vec<String> = match params.get("modifiers") {
+            None | Some(Value::Null) => Vec::new(),
+            // "Shift+Control" as well as ["Shift", "Control"]
+            Some(Value::String(s)) => s.split('+').map(|m| m.to_string()).collect(),
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'modifiers' must be a list of modifier keys")?,
+        };
+        let options = ClickOptions {
+            button,
+            click_count: click_count as u32,
+            modifiers,
+        };
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let selector = selector.to_string();
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.click_with(&selector, &options, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_fill(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let value = params
+            .get("value")
+            .and_then(|v| v.as_str())
+            .context("Missing 'value' parameter")?;
+        let append = params
+            .get("append")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let selector = selector.to_string();
+        let value = value.to_string();
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.fill(&selector, &value, append, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_focus(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.focus(selector, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_blur(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params.get("selector").and_then(|v| v.as_str());
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.blur(selector, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_clear(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.clear(selector, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_type_text(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params.get("selector").and_then(|v| v.as_str());
+        let text = params
+            .get("text")
+            .and_then(|v| v.as_str())
+            .context("Missing 'text' parameter")?;
+        let delay_ms = params
+            .get("delay_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_TYPE_DELAY_MS);
+        let jitter_ms = params
+            .get("jitter_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_TYPE_JITTER_MS);
+        if delay_ms.saturating_add(jitter_ms) > MAX_TYPE_PAUSE_MS {
+            anyhow::bail!(
+                "'delay_ms' plus 'jitter_ms' must be at most {}",
+                MAX_TYPE_PAUSE_MS
+            );
+        }
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.type_text(selector, text, delay_ms, jitter_ms, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_fill_rich_text(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let content = params
+            .get("content")
+            .and_then(|v| v.as_str())
+            .context("Missing 'content' parameter")?;
+        let format: RichTextFormat = match params.get("format") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'format' must be one of markdown, html, text")?,
+            None => RichTextFormat::default(),
+        };
+        let append = params
+            .get("append")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.fill_rich_text(selector, content, format, append, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_select_text(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params.get("selector").and_then(|v| v.as_str());
+        let start = params.get("start").and_then(|v| v.as_u64());
+        let end = params.get("end").and_then(|v| v.as_u64());
+        let session_id = Self::get_session_id(&params);
+
+        let range = match params.get("text").and_then(|v| v.as_str()) {
+            Some(_) if start.is_some() || end.is_some() => {
+                anyhow::bail!("Pass either 'text' or 'start'/'end', not both")
+            }
+            Some(text) => {
+                let occurrence = params
+                    .get("occurrence")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(1);
+                if occurrence == 0 {
+                    anyhow::bail!("'occurrence' counts from 1");
+                }
+                TextRange::Text {
+                    text: text.to_string(),
+                    occurrence: occurrence as usize,
+                }
+            }
+            None => {
+                let start = start.unwrap_or(0);
+                if end.is_some_and(|end| end < start) {
+                    anyhow::bail!("'end' must not be before 'start'");
+                }
+                TextRange::Offsets {
+                    start: start as usize,
+                    end: end.map(|end| end as usize),
+                }
+            }
+        };
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let selection = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.select_text(selector, &range, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(selection)?)
+    }
+
+    fn handle_get_selection(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let selection = self
+            .runtime
+            .block_on(browser_client.get_selection(session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(selection)?)
+    }
+
+    fn handle_copy_selection(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let copy = self
+            .runtime
+            .block_on(browser_client.copy_selection(session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(copy)?)
+    }
+
+    fn handle_press(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let key = params
+            .get("key")
+            .and_then(|v| v.as_str())
+            .context("Missing 'key' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+        let key = key.to_string();
+
+        self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.press(&key, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(KeyPressResult {
+            success: true,
+            key,
+            modifiers: Vec::new(),
+        })?)
+    }
+
+    fn handle_set_zoom(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let factor = params
+            .get("factor")
+            .and_then(|v| v.as_f64())
+            .context("Missing 'factor' parameter")?;
+        if !(MIN_ZOOM..=MAX_ZOOM).contains(&factor) {
+            anyhow::bail!("'factor' must be between {} and {}", MIN_ZOOM, MAX_ZOOM);
+        }
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.set_zoom(factor, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Set a session's time limits; the ones not given stay as they were.
+    fn handle_set_timeouts(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let reset = params
+            .get("reset")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let current = self
+            .runtime
+            .block_on(browser_client.timeouts(session_id.as_deref()))?;
+        let mut timeouts = match (reset, current) {
+            (false, Some(timeouts)) => timeouts,
+            _ => self.timeouts,
+        };
+        let mut changed = false;
+        for (key, kind) in [
+            ("navigation_ms", TimeoutKind::Navigation),
+            ("action_ms", TimeoutKind::Action),
+            ("snapshot_ms", TimeoutKind::Snapshot),
+        ] {
+            if let Some(v) = params.get(key).filter(|v| !v.is_null()) {
+                let ms = v
+                    .as_u64()
+                    .with_context(|| format!("'{}' must be a number", key))?;
+                let limit = limit_from_ms(ms).map_err(|e| anyhow::anyhow!("'{}': {}", key, e))?;
+                timeouts.set(kind, limit);
+                changed = true;
+            }
+        }
+
+        // Without limits it only reports them, or with `reset` hands the
+        // session back to the daemon's
+        if changed || reset {
+            self.runtime.block_on(
+                browser_client.set_timeouts(changed.then_some(timeouts), session_id.as_deref()),
+            )?;
+        }
+
+        Ok(serde_json::to_value(timeouts.settings())?)
+    }
+
+    fn handle_keyboard_layout(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let layout = params
+            .get("layout")
+            .and_then(|v| v.as_str())
+            .context("Missing 'layout' parameter")?;
+        let layout = KeyboardLayout::from_name(layout)?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        self.runtime
+            .block_on(browser_client.set_keyboard_layout(layout, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(KeyboardLayoutResult {
+            success: true,
+            layout,
+        })?)
+    }
+
+    fn handle_popup_policy(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let policy: PopupPolicy = serde_json::from_value(
+            params
+                .get("policy")
+                .cloned()
+                .context("Missing 'policy' parameter")?,
+        )
+        .context("'policy' must be one of allow, same_tab, attach, block")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        self.runtime
+            .block_on(browser_client.set_popup_policy(policy, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(PopupPolicyResult {
+            success: true,
+            policy,
+        })?)
+    }
+
+    fn handle_set_fake_clock(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let start_time = match params.get("start_time") {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Number(ms)) => ms.to_string(),
+            _ => anyhow::bail!("Missing 'start_time' parameter"),
+        };
+        let tick_mode: ClockTickMode =
+            serde_json::from_value(params.get("tick_mode").cloned().unwrap_or_default())
+                .context("'tick_mode' must be realtime or frozen")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.set_fake_clock(
+            &start_time,
+            tick_mode,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_fast_forward(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let ms = params
+            .get("ms")
+            .and_then(|v| v.as_u64())
+            .context("Missing 'ms' parameter")?;
+        let max_timers = params
+            .get("max_timers")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_FAST_FORWARD_MAX_TIMERS);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.fast_forward(
+            ms,
+            max_timers as usize,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_clear_fake_clock(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.clear_fake_clock(session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_state_save(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .context("Missing 'name' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let state_path = self.auth_dir.join(format!("{}.json", name));
+        let browser_client = self.client()?;
+
+        let state = self.runtime.block_on(async {
+            let (cookies, local_storage) = futures::try_join!(
+                browser_client.get_cookies(session_id.as_deref()),
+                browser_client.get_local_storage(session_id.as_deref())
+            )?;
+            Ok::<AuthState, anyhow::Error>(AuthState {
+                cookies,
+                local_storage,
+                saved_at: Utc::now().to_rfc3339(),
+            })
+        })?;
+
+        let serialized = serde_json::to_vec_pretty(&state)?;
+        std::fs::write(&state_path, serialized)?;
+
+        Ok(serde_json::to_value(StateSaved {
+            success: true,
+            path: state_path.to_string_lossy().to_string(),
+        })?)
+    }
+
+    fn handle_state_load(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .context("Missing 'name' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let state = self.read_state(name)?;
+
+        let browser_client = self.client()?;
+
+        self.runtime.block_on(async {
+            futures::try_join!(
+                browser_client.set_cookies(&state.cookies, session_id.as_deref()),
+                browser_client.set_local_storage(&state.local_storage, session_id.as_deref())
+            )?;
+            Ok::<(), anyhow::Error>(())
+        })?;
+
+        Ok(serde_json::to_value(StateLoaded {
+            success: true,
+            name: name.to_string(),
+        })?)
+    }
+
+    /// Read a saved auth state by name.
+    fn read_state(&self, name: &str) -> Result<AuthState> {
+        let state_path = self.auth_dir.join(format!("{}.json", name));
+
+        if !state_path.exists() {
+            anyhow::bail!("State '{}' not found", name);
+        }
+
+        let state_bytes = std::fs::read(&state_path)?;
+        Ok(serde_json::from_slice(&state_bytes)?)
+    }
+
+    fn handle_state_diff(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let a = params
+            .get("a")
+            .and_then(|v| v.as_str())
+            .context("Missing 'a' parameter")?;
+        let b = params
+            .get("b")
+            .and_then(|v| v.as_str())
+            .context("Missing 'b' parameter")?;
+
+        let diff = diff_states(&self.read_state(a)?, &self.read_state(b)?);
+
+        Ok(serde_json::to_value(diff)?)
+    }
+
+    fn handle_state_list(&self, _params: HashMap<String, Value>) -> Result<Value> {
+        let mut states = Vec::new();
+
+        if self.auth_dir.exists() {
+            for entry in std::fs::read_dir(&self.auth_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if path.extension().map(|e| e == "json").unwrap_or(false) {
+                    if let Some(stem) = path.file_stem() {
+                        let name = stem.to_string_lossy().to_string();
+                        let mut domains = Vec::new();
+                        let mut saved_at = String::new();
+
+                        if let Ok(contents) = std::fs::read(&path) {
+                            if let Ok(state) = serde_json::from_slice::<AuthState>(&contents) {
+                                let mut seen = std::collections::HashSet::new();
+                                for cookie in state.cookies {
+                                    if seen.insert(cookie.domain.clone()) {
+                                        domains.push(cookie.domain);
+                                    }
+                                }
+                                saved_at = state.saved_at;
+                            }
+                        }
+
+                        states.push(SavedState {
+                            name,
+                            domains,
+                            saved_at,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(serde_json::to_value(SavedStates {
+            count: states.len(),
+            states,
+        })?)
+    }
+
+    fn handle_health(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let self_test = params
+            .get("self_test")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let client = self.client.clone();
+
+        let mut healthy = self.runtime.block_on(async {
+            let client_lock = client.read().await;
+            if let Some(ref browser_client) = *client_lock {
+                browser_client.health_check().await.unwrap_or(false)
+            } else {
+                true // No browser yet is OK
+            }
+        });
+
+        let report = self_test.then(|| self.run_self_test());
+        if let Some(report) = &report {
+            healthy = healthy && report.ready;
+        }
+
+        Ok(serde_json::to_value(HealthStatus {
+            healthy,
+            service: "browser".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            // After the self-test, which may have launched the browser
+            browser: self.launch_status(),
+            artifact_store: self.artifacts.backend().to_string(),
+            self_test: report,
+        })?)
+    }
+
+    /// Launch (if needed), open about:blank, time a CDP round-trip, probe the
+    /// browser's capabilities, and check artifact disk space.
+    fn run_self_test(&self) -> SelfTestReport {
+        let mut checks = Vec::new();
+        let mut capabilities = None;
+
+        let started = Instant::now();
+        match self.client() {
+            Ok(browser_client) => {
+                checks.push(HealthCheck::pass(
+                    "launch",
+                    Some(started.elapsed().as_secs_f64() * 1000.0),
+                ));
+
+                let probe = self.runtime.block_on(async {
+                    tokio::time::timeout(SELF_TEST_TIMEOUT, browser_client.self_test()).await
+                });
+                match probe {
+                    Ok(Ok((navigate_ms, latency_ms))) => {
+                        checks.push(HealthCheck::pass("navigate", Some(navigate_ms)));
+                        checks.push(HealthCheck::pass("cdp_latency", Some(latency_ms)));
+                    }
+                    Ok(Err(e)) => checks.push(HealthCheck::fail("navigate", format!("{:#}", e))),
+                    Err(_) => checks.push(HealthCheck::fail(
+                        "navigate",
+                        format!("Timed out after {}s", SELF_TEST_TIMEOUT.as_secs()),
+                    )),
+                }
+
+                let started = Instant::now();
+                let probe = self.runtime.block_on(async {
+                    tokio::time::timeout(SELF_TEST_TIMEOUT, browser_client.capabilities()).await
+                });
+                match probe {
+                    Ok(Ok(found)) => {
+                        checks.push(if found.missing.is_empty() {
+                            HealthCheck::pass(
+                                "capabilities",
+                                Some(started.elapsed().as_secs_f64() * 1000.0),
+                            )
+                        } else {
+                            HealthCheck::fail(
+                                "capabilities",
+                                format!(
+                                    "{} mode is missing {}",
+                                    found.mode.as_str(),
+                                    found.missing.join(", ")
+                                ),
+                            )
+                        });
+                        capabilities = Some(found);
+                    }
+                    Ok(Err(e)) => {
+                        checks.push(HealthCheck::fail("capabilities", format!("{:#}", e)))
+                    }
+                    Err(_) => checks.push(HealthCheck::fail(
+                        "capabilities",
+                        format!("Timed out after {}s", SELF_TEST_TIMEOUT.as_secs()),
+                    )),
+                }
+            }
+            Err(e) => checks.push(HealthCheck::fail("launch", format!("{:#}", e))),
+        }
+
+        let free = self.artifacts.free_bytes();
+        match free {
+            Some(bytes) if bytes < MIN_ARTIFACT_FREE_BYTES => checks.push(HealthCheck::fail(
+                "artifact_disk",
+                format!(
+                    "{} MiB free, need at least {} MiB",
+                    bytes / (1024 * 1024),
+                    MIN_ARTIFACT_FREE_BYTES / (1024 * 1024)
+                ),
+            )),
+            _ => checks.push(HealthCheck::pass("artifact_disk", None)),
+        }
+
+        let mut report = SelfTestReport::new(checks, free);
+        report.capabilities = capabilities;
+        report
+    }
+
+    // =========================================================================
+    // SESSION MANAGEMENT HANDLERS
+    // =========================================================================
+
+    fn handle_session_new(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = params
+            .get("id")
+            .or_else(|| params.get("session_id"))
+            .and_then(|v| v.as_str())
+            .context("Missing 'id' parameter")?;
+
+        let browser_client = self.client()?;
+
+        let id = self
+            .runtime
+            .block_on(browser_client.create_session(session_id))?;
+
+        Ok(serde_json::to_value(SessionCreated {
+            success: true,
+            session_id: id,
+        })?)
+    }
+
+    fn handle_session_list(&self, _params: HashMap<String, Value>) -> Result<Value> {
+        let client = self.client.clone();
+
+        let sessions = self.runtime.block_on(async {
+            let client_lock = client.read().await;
+            if let Some(ref browser_client) = *client_lock {
+                browser_client.session_infos().await
+            } else {
+                vec![]
+            }
+        });
+
+        Ok(serde_json::to_value(SessionList {
+            count: sessions.len(),
+            sessions,
+        })?)
+    }
+
+    fn handle_session_close(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = params
+            .get("id")
+            .or_else(|| params.get("session_id"))
+            .and_then(|v| v.as_str())
+            .context("Missing 'id' parameter")?;
+
+        let client = self.client.clone();
+
+        // Finish the video while the page is still there
+        let recording = self
+            .recordings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id);
+        let recording = match recording {
+            Some(recording) => match self.runtime.block_on(recording.finish()) {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    tracing::warn!("Recording of session {} failed: {}", session_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let stream = self
+            .streams
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id);
+        if let Some(stream) = stream {
+            self.runtime.block_on(stream.stop());
+        }
+
+        let bandwidth = self.runtime.block_on(async {
+            let client_lock = client.read().await;
+            if let Some(ref browser_client) = *client_lock {
+                browser_client.close_session(session_id).await
+            } else {
+                Ok(None)
+            }
+        })?;
+        self.timelines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id);
+        self.observers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, observer| observer.session_id != session_id);
+
+        Ok(serde_json::to_value(SessionClosed {
+            success: true,
+            session_id: session_id.to_string(),
+            bandwidth,
+            recording,
+        })?)
+    }
+
+    fn handle_live_view(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let addr = self
+            .live_view
+            .context("Live view is disabled; start the daemon with --live-view-port")?;
+        let session_id = Self::get_session_id(&params).unwrap_or_else(|| "default".to_string());
+
+        Ok(serde_json::to_value(LiveViewUrls {
+            url: format!("http://{}{}", addr, live_view::stream_path(&session_id)),
+            index_url: format!("http://{}/", addr),
+            session_id,
+        })?)
+    }
+
+    fn handle_screencast_start(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let defaults = ScreencastOptions::default();
+        // Without settings the session's screencast is shared as it runs
+        let exact = ["quality", "max_width", "max_height"]
+            .iter()
+            .any(|p| params.get(*p).is_some_and(|v| !v.is_null()));
+        let options = ScreencastOptions {
+            quality: params
+                .get("quality")
+                .and_then(|v| v.as_u64())
+                .map_or(defaults.quality, |q| q.min(100) as u8),
+            max_width: params
+                .get("max_width")
+                .and_then(|v| v.as_u64())
+                .map_or(defaults.max_width, |w| w as u32),
+            max_height: params
+                .get("max_height")
+                .and_then(|v| v.as_u64())
+                .map_or(defaults.max_height, |h| h as u32),
+        };
+        if options.max_width == 0 || options.max_height == 0 {
+            anyhow::bail!("'max_width' and 'max_height' must be positive");
+        }
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.as_deref().unwrap_or("default");
+
+        // Checked under the lock but started outside it: launching Chrome
+        // and joining the screencast take a while, and every observer call
+        // reads the streams
+        let started = |started: bool| {
+            serde_json::to_value(ScreencastStarted {
+                session_id: session.to_string(),
+                started,
+                idle_timeout_ms: frame_stream::IDLE_TIMEOUT.as_millis() as u64,
+            })
+        };
+        let running = {
+            let streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+            Self::stream_running(&streams, session, options, exact)?
+        };
+        if running {
+            return Ok(started(false)?);
+        }
+
+        let browser_client = self.client()?;
+        let stream = self.runtime.block_on(async {
+            let screencast = browser_client
+                .join_screencast(options, exact, session_id.as_deref())
+                .await?;
+            Ok::<FrameStream, anyhow::Error>(FrameStream::start(session, screencast))
+        })?;
+
+        let mut streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        match Self::stream_running(&streams, session, options, exact) {
+            Ok(false) => {
+                streams.insert(session.to_string(), stream);
+                Ok(started(true)?)
+            }
+            // A concurrent start got there first: keep its stream
+            won => {
+                drop(streams);
+                self.runtime.block_on(stream.stop());
+                won.and_then(|_| Ok(started(false)?))
+            }
+        }
+    }
+
+    /// Whether `session` has a frame stream running that a start with
+    /// `options` can share; a conflict when `exact` options differ from it.
+    /// A stream that stopped by itself doesn't count, and is replaced.
+    fn stream_running(
+        streams: &HashMap<String, FrameStream>,
+        session: &str,
+        options: ScreencastOptions,
+        exact: bool,
+    ) -> Result<bool> {
+        let running = streams
+            .get(session)
+            .filter(|stream| stream.frames().streaming());
+        if let Some(stream) = running.filter(|stream| exact && stream.options() != options) {
+            return Err(ScreencastConflict {
+                running: stream.options(),
+            }
+            .into());
+        }
+        Ok(running.is_some())
+    }
+
+    fn handle_screencast_frames(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let since = params.get("since").and_then(|v| v.as_u64()).unwrap_or(0);
+        let limit = params
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(1, |n| n as usize)
+            .clamp(1, frame_stream::FRAME_BUFFER);
+        let wait =
+            Duration::from_millis(params.get("wait_ms").and_then(|v| v.as_u64()).unwrap_or(0));
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.as_deref().unwrap_or("default");
+
+        let frames = self
+            .streams
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session)
+            .map(|stream| stream.frames())
+            .with_context(|| {
+                format!(
+                    "Session {} is not streaming; call browser.screencast.start first",
+                    session
+                )
+            })?;
+        let result = self.runtime.block_on(frames.poll(since, limit, wait));
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_screencast_stop(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = Self::get_session_id(&params);
+        let session = session_id.as_deref().unwrap_or("default");
+
+        let stream = self
+            .streams
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session);
+        let stopped = stream.is_some();
+        if let Some(stream) = stream {
+            self.runtime.block_on(stream.stop());
+        }
+
+        Ok(serde_json::to_value(ScreencastStopped {
+            session_id: session.to_string(),
+            stopped,
+        })?)
+    }
+
+    fn handle_session_observe(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let session_id = params
+            .get("id")
+            .or_else(|| params.get("session_id"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("default")
+            .to_string();
+        let replay = params
+            .get("replay")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let browser_client = self.client()?;
+
+        let observer = self.runtime.block_on(async {
+            let (events, backlog) = browser_client
+                .subscribe_events(replay, Some(&session_id))
+                .await?;
+            Ok::<Observer, anyhow::Error>(Observer::spawn(&session_id, events, backlog))
+        })?;
+        let observer_id = observer.id.clone();
+        self.observers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(observer_id.clone(), observer);
+
+        Ok(serde_json::to_value(ObserverOpened {
+            observer_id,
+            session_id,
+            allowed_methods: observer::READ_ONLY_METHODS
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+        })?)
+    }
+
+    fn handle_session_events(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let since = params.get("since").and_then(|v| v.as_u64()).unwrap_or(0);
+        let types: Vec<String> = match params.get("types") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'types' must be an array of event types")?,
+            None => Vec::new(),
+        };
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.session_events(
+            since,
+            &types,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_observer_events(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let id = params
+            .get("observer_id")
+            .and_then(|v| v.as_str())
+            .context("Missing 'observer_id' parameter")?;
+        let since = params.get("since").and_then(|v| v.as_u64()).unwrap_or(0);
+
+        let observers = self.observers.lock().unwrap_or_else(|e| e.into_inner());
+        let observer = observers
+            .get(id)
+            .with_context(|| format!("Observer not found: {}", id))?;
+
+        Ok(serde_json::to_value(observer.events(since))?)
+    }
+
+    fn handle_observer_close(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let id = params
+            .get("observer_id")
+            .and_then(|v| v.as_str())
+            .context("Missing 'observer_id' parameter")?;
+
+        let closed = self
+            .observers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id)
+            .is_some();
+
+        Ok(serde_json::to_value(ObserverClosed {
+            observer_id: id.to_string(),
+            closed,
+        })?)
+    }
+
+    // =========================================================================
+    // FEATURE PARITY HANDLERS
+    // =========================================================================
+
+    fn handle_select(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let value = params
+            .get("value")
+            .and_then(|v| v.as_str())
+            .context("Missing 'value' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let selector = selector.to_string();
+        let value = value.to_string();
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.select(&selector, &value, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(SelectValueResult {
+            success: true,
+            selector,
+            value,
+        })?)
+    }
+
+    fn handle_check(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let checked = params
+            .get("checked")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true); // Default to checking
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let selector = selector.to_string();
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.check(&selector, checked, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(CheckResult {
+            success: true,
+            selector,
+            checked,
+        })?)
+    }
+
+    fn handle_hover(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let selector = selector.to_string();
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.hover(&selector, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// The options `browser.select_option` asks for; each of `value`,
+    /// `label`, and `index` takes one or a list.
+    fn option_query(params: &HashMap<String, Value>) -> Result<OptionQuery> {
+        let list = |name: &str| -> Option<Vec<Value>> {
+            params.get(name).filter(|v| !v.is_null()).map(|v| match v {
+                Value::Array(items) => items.clone(),
+                other => vec![other.clone()],
+            })
+        };
+        let strings = |name: &str, items: Vec<Value>| -> Result<Vec<String>> {
+            items
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_string()))
+                .collect::<Option<_>>()
+                .with_context(|| format!("'{}' must be a string or a list of strings", name))
+        };
+
+        match (list("value"), list("label"), list("index")) {
+            (Some(values), None, None) => Ok(OptionQuery::Value(strings("value", values)?)),
+            (None, Some(labels), None) => Ok(OptionQuery::Label(strings("label", labels)?)),
+            (None, None, Some(indices)) => Ok(OptionQuery::Index(
+                indices
+                    .iter()
+                    .map(|v| v.as_u64().map(|i| i as usize))
+                    .collect::<Option<_>>()
+                    .context("'index' must be a number or a list of numbers")?,
+            )),
+            (None, None, None) => anyhow::bail!("Missing 'value', 'label', or 'index' parameter"),
+            _ => anyhow::bail!("Pass only one of 'value', 'label', or 'index'"),
+        }
+    }
+
+    fn handle_select_option(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let query = Self::option_query(&params)?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.select_option(selector, &query, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_set_date(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let date = params
+            .get("date")
+            .and_then(|v| v.as_str())
+            .context("Missing 'date' parameter")?;
+        let format = params.get("format").and_then(|v| v.as_str());
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.set_date(selector, date, format, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_highlight(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let label = params.get("label").and_then(|v| v.as_str());
+        let duration_ms = params
+            .get("duration_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(3000);
+        let color = params.get("color").and_then(|v| v.as_str());
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.highlight(
+            selector,
+            label,
+            duration_ms,
+            color,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_cursor_overlay(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let enabled = params
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        self.runtime
+            .block_on(browser_client.set_cursor_overlay(enabled, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(CursorOverlayResult {
+            success: true,
+            enabled,
+        })?)
+    }
+
+    fn handle_scroll(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params.get("selector").and_then(|v| v.as_str());
+        let x = params.get("x").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+        let y = params.get("y").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+        let selector = selector.map(|s| s.to_string());
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.scroll(selector.as_deref(), x, y, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_scroll_to_element(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let block: ScrollBlock = match params.get("block") {
+            Some(v) => serde_json::from_value(v.clone())
+                .context("'block' must be start, center, end, or nearest")?,
+            None => ScrollBlock::default(),
+        };
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.scroll_to_element(selector, block, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_scroll_by(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let container = params.get("container").and_then(|v| v.as_str());
+        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.scroll_by(container, x, y, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_scroll_to_bottom(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let container = params.get("container").and_then(|v| v.as_str());
+        let max_rounds = params
+            .get("max_rounds")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_SCROLL_ROUNDS)
+            .max(1) as u32;
+        let settle_ms = params
+            .get("settle_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_SCROLL_SETTLE_MS);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.scroll_to_bottom(
+                container,
+                max_rounds,
+                Duration::from_millis(settle_ms),
+                session_id.as_deref(),
+            ),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_restore_scroll(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let state: Option<ScrollState> = params
+            .get("scroll")
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .context("Invalid 'scroll' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.restore_scroll(state, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_wait_for_stable(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let quiet_ms = params
+            .get("quiet_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_STABLE_QUIET_MS);
+        let timeout_ms = params
+            .get("timeout_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_STABLE_TIMEOUT_MS);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.wait_for_stable(
+            Duration::from_millis(quiet_ms),
+            Duration::from_millis(timeout_ms),
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_press_combo(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let key = params
+            .get("key")
+            .and_then(|v| v.as_str())
+            .context("Missing 'key' parameter")?;
+        let modifiers: Vec<String> = params
+            .get("modifiers")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+        let key = key.to_string();
+
+        self.block_on_within(TimeoutKind::Action, limit, async {
+            let mod_refs: Vec<&str> = modifiers.iter().map(|s| s.as_str()).collect();
+            browser_client
+                .press_combo(&mod_refs, &key, session_id.as_deref())
+                .await
+        })?;
+
+        Ok(serde_json::to_value(KeyPressResult {
+            success: true,
+            key,
+            modifiers,
+        })?)
+    }
+
+    fn handle_upload(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .context("Missing 'path' parameter")?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let selector = selector.to_string();
+        let path = path.to_string();
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.upload(&selector, &path, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(UploadPathResult {
+            success: true,
+            selector,
+            path,
+        })?)
+    }
+
+    fn handle_upload_file(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let selector = params
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .context("Missing 'selector' parameter")?;
+        let paths: Vec<String> = match params.get("paths").or_else(|| params.get("path")) {
+            Some(Value::String(path)) => vec![path.clone()],
+            Some(Value::Array(paths)) => paths
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_string()))
+                .collect::<Option<_>>()
+                .context("'paths' must be a list of file paths")?,
+            _ => anyhow::bail!("Missing 'paths' parameter"),
+        };
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.upload_files(selector, &paths, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    // =========================================================================
+    // SPATIAL QUERIES
+    // =========================================================================
+
+    fn spatial_filter(params: &HashMap<String, Value>, default_limit: usize) -> SpatialFilter {
+        SpatialFilter {
+            role: params
+                .get("role")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            name: params
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            limit: params
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(default_limit),
+        }
+    }
+
+    fn handle_nearest(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let anchor = match (
+            params.get("anchor").and_then(|v| v.as_str()),
+            params.get("text").and_then(|v| v.as_str()),
+        ) {
+            (Some(selector), None) => SpatialAnchor::Selector(selector.to_string()),
+            (None, Some(text)) => SpatialAnchor::Text(text.to_string()),
+            (Some(_), Some(_)) => anyhow::bail!("Pass either 'anchor' or 'text', not both"),
+            (None, None) => anyhow::bail!("Missing 'anchor' or 'text' parameter"),
+        };
+        let direction: SpatialDirection = match params.get("direction") {
+            Some(v) => serde_json::from_value(v.clone()).context(
+                "'direction' must be one of any, left, right, above, below, same_row, same_column",
+            )?,
+            None => SpatialDirection::default(),
+        };
+        let filter = Self::spatial_filter(&params, DEFAULT_NEAREST_LIMIT);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.nearest(
+            &anchor,
+            direction,
+            &filter,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_within(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let coord = |name: &str| {
+            params
+                .get(name)
+                .and_then(|v| v.as_f64())
+                .with_context(|| format!("Missing '{}' parameter", name))
+        };
+        let area = ElementRect {
+            x: coord("x")?,
+            y: coord("y")?,
+            width: coord("width")?,
+            height: coord("height")?,
+        };
+        let partial = params
+            .get("partial")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let filter = Self::spatial_filter(&params, DEFAULT_WITHIN_LIMIT);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.within(
+            &area,
+            partial,
+            &filter,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_find_elements(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let text = |name: &str| {
+            params
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        let query = ElementQuery {
+            role: text("role"),
+            name: text("name"),
+            text: text("text"),
+            exact: params
+                .get("exact")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            limit: params
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(DEFAULT_FIND_LIMIT),
+        };
+        if query.role.is_none() && query.name.is_none() && query.text.is_none() {
+            anyhow::bail!("Pass at least one of 'role', 'name' or 'text'");
+        }
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.find_elements(&query, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_summarize_results(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let container = params.get("container").and_then(|v| v.as_str());
+        let limit = params
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_RESULTS_LIMIT);
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self.runtime.block_on(browser_client.summarize_results(
+            container,
+            limit,
+            session_id.as_deref(),
+        ))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    // =========================================================================
+    // TABLES
+    // =========================================================================
+
+    fn table_query(params: &HashMap<String, Value>) -> Result<TableQuery> {
+        let row = match (
+            params.get("row_contains").and_then(|v| v.as_str()),
+            params.get("row_index").and_then(|v| v.as_u64()),
+        ) {
+            (Some(text), None) => TableRowKey::Contains(text.to_string()),
+            (None, Some(index)) => TableRowKey::Index(index as usize),
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Pass either 'row_contains' or 'row_index', not both")
+            }
+            (None, None) => anyhow::bail!("Missing 'row_contains' or 'row_index' parameter"),
+        };
+        let column = match (
+            params.get("column").and_then(|v| v.as_str()),
+            params.get("column_index").and_then(|v| v.as_u64()),
+        ) {
+            (Some(header), None) => TableColumnKey::Header(header.to_string()),
+            (None, Some(index)) => TableColumnKey::Index(index as usize),
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Pass either 'column' or 'column_index', not both")
+            }
+            (None, None) => anyhow::bail!("Missing 'column' or 'column_index' parameter"),
+        };
+
+        Ok(TableQuery {
+            table: params
+                .get("table")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            row,
+            column,
+            target: params
+                .get("target")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        })
+    }
+
+    fn handle_table_cell(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let query = Self::table_query(&params)?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.table_cell(&query, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_describe_form(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let form = params.get("form").and_then(|v| v.as_str());
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.describe_form(form, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_get_validation_errors(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let form = params.get("form").and_then(|v| v.as_str());
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+
+        let result = self
+            .runtime
+            .block_on(browser_client.validation_errors(form, session_id.as_deref()))?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_autofill_form(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let form = params.get("form").and_then(|v| v.as_str());
+        let data: Vec<(String, Value)> = params
+            .get("data")
+            .and_then(|v| v.as_object())
+            .context("Missing 'data' parameter (an object of field keys to values)")?
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.autofill_form(form, &data, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn handle_click_cell(&self, params: HashMap<String, Value>) -> Result<Value> {
+        let query = Self::table_query(&params)?;
+        let session_id = Self::get_session_id(&params);
+
+        let browser_client = self.client()?;
+        let limit = self.get_timeout(&browser_client, TimeoutKind::Action, &params)?;
+
+        let result = self.block_on_within(
+            TimeoutKind::Action,
+            limit,
+            browser_client.table_click(&query, session_id.as_deref()),
+        )?;
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    // =========================================================================
+    // EXTENSION BRIDGE ROUTING
+    // =========================================================================
+
+    /// Route extension-specific methods to the Chrome extension via WebSocket.
+    /// Called by dispatch() when the method is an extension-only method.
+    fn dispatch_to_extension(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        let bridge = self.extension_bridge.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Extension method '{}' requires Chrome extension. \
+                 Start daemon with --extension-bridge and install the FGP extension.",
+                method
+            )
+        })?;
+
+        if !bridge.is_connected_blocking() {
+            anyhow::bail!(
+                "Extension not connected. Install the FGP Browser Bridge extension \
+                 from chrome://extensions and ensure it's enabled."
+            );
+        }
+
+        // Strip 'browser.' prefix for the actual Chrome Extension API call
+        let ext_method = extension_method_name(method);
+        tracing::debug!(
+            "Routing '{}' to Chrome extension as '{}'",
+            method,
+            ext_method
+        );
+        let response = bridge.call_blocking(ext_method, params)?;
+        ExtensionBridge::response_to_value(response)
+    }
+
+    /// Run `method`; `dispatch` adds the protocol version to its response.
+    fn route(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        let observing =
+            Self::get_session_id(&params).is_some_and(|id| id.starts_with(OBSERVER_PREFIX));
+        let params = self.resolve_observer(method, params)?;
+
+        if self.wants_recovery(method, &params) {
+            return self.handle_recovering(method, params);
+        }
+
+        let chains = Self::element_selectors(method, &params, is_chained);
+        if !chains.is_empty() {
+            return self.handle_chained(method, params, chains);
+        }
+
+        let xpaths = Self::element_selectors(method, &params, is_xpath);
+        if !xpaths.is_empty() {
+            return self.handle_xpath(method, params, xpaths);
+        }
+
+        if DryRunAction::from_method(method).is_some()
+            && params.get("rank").is_some_and(|v| !v.is_null())
+        {
+            return self.handle_ranked(method, params);
+        }
+
+        if let Some(action) = Self::dry_run_action(method, &params) {
+            return self.handle_dry_run(action, params);
+        }
+
+        // Check if this is an extension-only method that should be routed to the Chrome extension
+        if is_extension_method(method) {
+            return self.dispatch_to_extension(method, params);
+        }
+
+        match method {
+            "health" => self.handle_health(params),
+            // Navigation and state
+            "browser.schemas" | "schemas" => schemas::export(),
+            "browser.open" | "open" => self.handle_open(params),
+            "browser.go_back" | "go_back" => self.handle_history(params, -1),
+            "browser.go_forward" | "go_forward" => self.handle_history(params, 1),
+            "browser.reload" | "reload" => self.handle_reload(params),
+            "browser.snapshot" | "snapshot" => self.handle_snapshot(params, observing),
+            "browser.snapshot_diff" | "snapshot_diff" => self.handle_snapshot_diff(params),
+            "browser.screenshot" | "screenshot" => self.handle_screenshot(params),
+            "browser.screenshot_element" | "screenshot_element" => {
+                self.handle_screenshot_element(params)
+            }
+            "browser.screenshot_after_paint" | "screenshot_after_paint" => {
+                self.handle_screenshot_after_paint(params)
+            }
+            "browser.compare_screenshots" | "compare_screenshots" => {
+                self.handle_compare_screenshots(params)
+            }
+            "browser.print_preview" | "print_preview" => self.handle_print_preview(params),
+            "browser.pdf" | "pdf" => self.handle_pdf(params),
+            "browser.canvas.draw" | "canvas.draw" => self.handle_canvas_draw(params),
+            "browser.canvas.capture" | "canvas.capture" => self.handle_canvas_capture(params),
+            "browser.media.state" | "media.state" => self.handle_media_state(params),
+            "browser.media.play" | "media.play" => {
+                self.handle_media_control(params, MediaAction::Play)
+            }
+            "browser.media.pause" | "media.pause" => {
+                self.handle_media_control(params, MediaAction::Pause)
+            }
+            "browser.media.seek" | "media.seek" => self.handle_media_seek(params),
+            "browser.media.mute" | "media.mute" => self.handle_media_mute(params),
+            "browser.get_notifications" | "get_notifications" => {
+                self.handle_get_notifications(params)
+            }
+            "browser.get_third_party_report" | "get_third_party_report" => {
+                self.handle_third_party_report(params)
+            }
+            "browser.get_environment" | "get_environment" => self.handle_get_environment(params),
+            "browser.cdp" | "cdp" => self.handle_cdp(params),
+            "browser.csp_trial" | "csp_trial" => self.handle_csp_trial(params),
+            "browser.detect_blockers" | "detect_blockers" => self.handle_detect_blockers(params),
+            "browser.locale_variants" | "locale_variants" => self.handle_locale_variants(params),
+            "browser.check_links" | "check_links" => self.handle_check_links(params),
+            "browser.export_refs" | "export_refs" => self.handle_export_refs(params),
+            "browser.artifact.get" | "artifact.get" => self.handle_artifact_get(params),
+            "browser.artifact.gc" | "artifact.gc" => self.handle_artifact_gc(),
+            "browser.maintain" | "maintain" => self.handle_maintain(),
+            // Crawling
+            "browser.crawl" | "crawl" => self.handle_crawl(params),
+            "browser.crawl.status" | "crawl.status" => self.handle_crawl_status(params),
+            "browser.crawl.cancel" | "crawl.cancel" => self.handle_crawl_cancel(params),
+            // Screenshot timeline
+            "browser.timeline.auto" | "timeline.auto" => self.handle_timeline_auto(params),
+            "browser.timeline.capture" | "timeline.capture" => self.handle_timeline_capture(params),
+            "browser.timeline.index" | "timeline.index" => self.handle_timeline_index(params),
+            "browser.timeline.clear" | "timeline.clear" => self.handle_timeline_clear(params),
+            // Recording
+            "browser.recording.start" | "recording.start" => self.handle_recording_start(params),
+            "browser.recording.stop" | "recording.stop" => self.handle_recording_stop(params),
+            // Assertions
+            "browser.assert_text" | "assert_text" => self.handle_assert_text(params),
+            "browser.assert_visible" | "assert_visible" => self.handle_assert_visible(params),
+            "browser.assert_url" | "assert_url" => self.handle_assert_url(params),
+            "browser.assert_count" | "assert_count" => self.handle_assert_count(params),
+            "browser.verify" | "verify" => self.handle_verify(params),
+            // Interaction
+            "browser.click" | "click" => self.handle_click(params),
+            "browser.fill" | "fill" => self.handle_fill(params),
+            "browser.clear" | "clear" => self.handle_clear(params),
+            "browser.focus" | "focus" => self.handle_focus(params),
+            "browser.blur" | "blur" => self.handle_blur(params),
+            "browser.type_text" | "type_text" => self.handle_type_text(params),
+            "browser.fill_rich_text" | "fill_rich_text" => self.handle_fill_rich_text(params),
+            "browser.select_text" | "select_text" => self.handle_select_text(params),
+            "browser.get_selection" | "get_selection" => self.handle_get_selection(params),
+            "browser.copy_selection" | "copy_selection" => self.handle_copy_selection(params),
+            "browser.press" | "press" | "browser.press_key" | "press_key" => {
+                self.handle_press(params)
+            }
+            "browser.keyboard_layout" | "keyboard_layout" => self.handle_keyboard_layout(params),
+            "browser.popup_policy" | "popup_policy" => self.handle_popup_policy(params),
+            "browser.set_zoom" | "set_zoom" => self.handle_set_zoom(params),
+            "browser.set_timeouts" | "set_timeouts" => self.handle_set_timeouts(params),
+            "browser.set_fake_clock" | "set_fake_clock" => self.handle_set_fake_clock(params),
+            "browser.fast_forward" | "fast_forward" => self.handle_fast_forward(params),
+            "browser.clear_fake_clock" | "clear_fake_clock" => self.handle_clear_fake_clock(params),
+            "browser.select" | "select" => self.handle_select(params),
+            "browser.select_option" | "select_option" => self.handle_select_option(params),
+            "browser.check" | "check" => self.handle_check(params),
+            "browser.hover" | "hover" => self.handle_hover(params),
+            "browser.drag_and_drop" | "drag_and_drop" => self.handle_drag_and_drop(params),
+            "browser.set_date" | "set_date" => self.handle_set_date(params),
+            "browser.nearest" | "nearest" => self.handle_nearest(params),
+            "browser.within" | "within" => self.handle_within(params),
+            "browser.find_elements" | "find_elements" => self.handle_find_elements(params),
+            "browser.summarize_results" | "summarize_results" => {
+                self.handle_summarize_results(params)
+            }
+            "browser.table_cell" | "table_cell" => self.handle_table_cell(params),
+            "browser.click_cell" | "click_cell" => self.handle_click_cell(params),
+            "browser.describe_form" | "describe_form" => self.handle_describe_form(params),
+            "browser.autofill_form" | "autofill_form" => self.handle_autofill_form(params),
+            "browser.get_validation_errors" | "get_validation_errors" => {
+                self.handle_get_validation_errors(params)
+            }
+            "browser.highlight" | "highlight" => self.handle_highlight(params),
+            "browser.cursor_overlay" | "cursor_overlay" => self.handle_cursor_overlay(params),
+            "browser.scroll" | "scroll" => self.handle_scroll(params),
+            "browser.scroll_to_element" | "scroll_to_element" => {
+                self.handle_scroll_to_element(params)
+            }
+            "browser.scroll_by" | "scroll_by" => self.handle_scroll_by(params),
+            "browser.scroll_to_bottom" | "scroll_to_bottom" => self.handle_scroll_to_bottom(params),
+            "browser.restore_scroll" | "restore_scroll" => self.handle_restore_scroll(params),
+            "browser.wait_for_stable" | "wait_for_stable" => self.handle_wait_for_stable(params),
+            "browser.press_combo" | "press_combo" => self.handle_press_combo(params),
+            "browser.upload" | "upload" => self.handle_upload(params),
+            "browser.upload_file" | "upload_file" => self.handle_upload_file(params),
+            // Auth state
+            "browser.state.save" | "state.save" => self.handle_state_save(params),
+            "browser.state.load" | "state.load" => self.handle_state_load(params),
+            "browser.state.list" | "state.list" => self.handle_state_list(params),
+            "browser.state.diff" | "state.diff" => self.handle_state_diff(params),
+            // Session management
+            "browser.session.new" | "session.new" => self.handle_session_new(params),
+            "browser.session.list" | "session.list" => self.handle_session_list(params),
+            "browser.session.close" | "session.close" => self.handle_session_close(params),
+            "browser.session.observe" | "session.observe" => self.handle_session_observe(params),
+            "browser.live_view" | "live_view" => self.handle_live_view(params),
+            "browser.screencast.start" | "screencast.start" => self.handle_screencast_start(params),
+            "browser.screencast.frames" | "screencast.frames" => {
+                self.handle_screencast_frames(params)
+            }
+            "browser.screencast.stop" | "screencast.stop" => self.handle_screencast_stop(params),
+            "browser.session.events" | "session.events" => self.handle_session_events(params),
+            "browser.observer.events" | "observer.events" => self.handle_observer_events(params),
+            "browser.observer.close" | "observer.close" => self.handle_observer_close(params),
+            _ => Err(anyhow::anyhow!("Unknown method: {}", method)),
+        }
+    }
+}
+
+impl FgpService for BrowserService {
+    fn name(&self) -> &str {
+        "browser"
+    }
+
+    fn version(&self) -> &str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    fn dispatch(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        let mut response = self.route(method, params)?;
+        schemas::stamp(&mut response);
+        Ok(response)
+    }
+
+    fn method_list(&self) -> Vec<MethodInfo> {
+        // Shared by the timeline methods
+        let timeline_frame = || {
+            SchemaBuilder::object()
+                .property("index", SchemaBuilder::integer())
+                .property("captured_at", SchemaBuilder::string())
+                .property(
+                    "trigger",
+                    SchemaBuilder::string().enum_values(&["navigation", "manual"]),
+                )
+                .property("url", SchemaBuilder::string())
+                .property("title", SchemaBuilder::string())
+                .property("label", SchemaBuilder::string())
+                .property(
+                    "artifact",
+                    SchemaBuilder::object()
+                        .property("id", SchemaBuilder::string())
+                        .property("url", SchemaBuilder::string()),
+                )
+        };
+        // Shared by focus and blur
+        let focus_returns = || {
+            SchemaBuilder::object()
+                .property("success", SchemaBuilder::boolean())
+                .property("selector", SchemaBuilder::string())
+                .property(
+                    "active_element",
+                    SchemaBuilder::string()
+                        .description("Element with focus afterwards, e.g. input#email"),
+                )
+        };
+        // Shared by the session list and close methods
+        let bandwidth_returns = || {
+            SchemaBuilder::object()
+                .property("requests", SchemaBuilder::integer())
+                .property(
+                    "downloaded_bytes",
+                    SchemaBuilder::integer()
+                        .description("Bytes received over the network, headers included"),
+                )
+                .property(
+                    "uploaded_bytes",
+                    SchemaBuilder::integer().description("Request body bytes sent"),
+                )
+                .property(
+                    "by_type",
+                    SchemaBuilder::object().description(
+                        "The same counts keyed by resource type (document, script, image, xhr, ...)",
+                    ),
+                )
+        };
+        // Shared by the fake clock methods
+        let fake_clock_returns = || {
+            SchemaBuilder::object()
+                .property("success", SchemaBuilder::boolean())
+                .property(
+                    "tick_mode",
+                    SchemaBuilder::string().enum_values(&["realtime", "frozen"]),
+                )
+                .property("epoch_ms", SchemaBuilder::number())
+                .property("iso", SchemaBuilder::string())
+                .property(
+                    "local",
+                    SchemaBuilder::string().description("Page time in the page's timezone"),
+                )
+        };
+        // Shared by the assert_* methods
+        let screenshot_param = || {
+            SchemaBuilder::boolean()
+                .default_value(json!(true))
+                .description("Store a screenshot artifact as evidence when the assertion fails")
+        };
+        let assertion_returns = || {
+            SchemaBuilder::object()
+                .property("passed", SchemaBuilder::boolean())
+                .property("assertion", SchemaBuilder::object())
+                .property("message", SchemaBuilder::string())
+                .property(
+                    "evidence",
+                    SchemaBuilder::object()
+                        .property(
+                            "actual",
+                            SchemaBuilder::string()
+                                .description("Observed text, visibility, URL, or count"),
+                        )
+                        .property("url", SchemaBuilder::string().format("uri"))
+                        .property(
+                            "screenshot",
+                            SchemaBuilder::object().description("Artifact, on failure"),
+                        ),
+                )
+                .build()
+        };
+
+        // Common session parameter schema
+        let session_param = || {
+            SchemaBuilder::string()
+                .description("Session ID for isolated browser context (optional)")
+        };
+
+        // Per-call time limit, over the session's and the daemon's
+        let timeout_param = || {
+            SchemaBuilder::integer().minimum(1).maximum(600000).description(
+                "Time limit for this call in milliseconds (default: the session's, see set_timeouts)",
+            )
+        };
+
+        // Spatial query filters and results
+        let spatial_role_param =
+            || SchemaBuilder::string().description("Only elements with this ARIA role");
+        let spatial_name_param = || {
+            SchemaBuilder::string()
+                .description("Only elements whose accessible name contains this (case-insensitive)")
+        };
+        let spatial_returns = || {
+            SchemaBuilder::object()
+                .property(
+                    "anchor",
+                    SchemaBuilder::object().description("Anchor bounds (nearest only)"),
+                )
+                .property(
+                    "matches",
+                    SchemaBuilder::array().items(
+                        SchemaBuilder::object()
+                            .property("ref_id", SchemaBuilder::string())
+                            .property("role", SchemaBuilder::string())
+                            .property("name", SchemaBuilder::string())
+                            .property("rect", SchemaBuilder::object())
+                            .property(
+                                "distance",
+                                SchemaBuilder::number()
+                                    .description("Edge-to-edge pixels from the anchor"),
+                            ),
+                    ),
+                )
+                .build()
+        };
+
+        // A text selection
+        let selection_returns = || {
+            SchemaBuilder::object()
+                .property("text", SchemaBuilder::string())
+                .property(
+                    "html",
+                    SchemaBuilder::string().description("Selected markup (document selections)"),
+                )
+                .property("collapsed", SchemaBuilder::boolean())
+                .property(
+                    "element",
+                    SchemaBuilder::string()
+                        .description("Ref or CSS path of the element containing the selection"),
+                )
+                .property(
+                    "start",
+                    SchemaBuilder::integer()
+                        .description("Offset of the selection in the element's text"),
+                )
+                .property("end", SchemaBuilder::integer())
+                .build()
+        };
+
+        // One link in check_links results
+        let link_check_schema = || {
+            SchemaBuilder::object()
+                .property("url", SchemaBuilder::string())
+                .property(
+                    "status",
+                    SchemaBuilder::integer().description("Absent when the request failed"),
+                )
+                .property(
+                    "location",
+                    SchemaBuilder::string().description("Redirect target"),
+                )
+                .property("error", SchemaBuilder::string())
+                .property(
+                    "cached",
+                    SchemaBuilder::boolean().description("Present when answered from the cache"),
+                )
+        };
+
+        // Shared by the table methods
+        let table_params = || {
+            SchemaBuilder::object()
+                .property(
+                    "table",
+                    SchemaBuilder::string()
+                        .description("@eN ref or CSS selector of the table (default: first table)"),
+                )
+                .property(
+                    "row_contains",
+                    SchemaBuilder::string().description("Text in the row (first match is used)"),
+                )
+                .property(
+                    "row_index",
+                    SchemaBuilder::integer().description("0-based body row"),
+                )
+                .property(
+                    "column",
+                    SchemaBuilder::string().description("Header text (case-insensitive)"),
+                )
+                .property(
+                    "column_index",
+                    SchemaBuilder::integer().description("0-based column, counting colspans"),
+                )
+                .property(
+                    "target",
+                    SchemaBuilder::string()
+                        .description("Pick the control in the cell whose label contains this"),
+                )
+                .property("session_id", session_param())
+        };
+        let table_cell_returns = || {
+            SchemaBuilder::object()
+                .property("row", SchemaBuilder::integer())
+                .property(
+                    "row_matches",
+                    SchemaBuilder::integer().description("Rows containing row_contains"),
+                )
+                .property("column", SchemaBuilder::integer())
+                .property("header", SchemaBuilder::string())
+                .property("text", SchemaBuilder::string())
+                .property("selector", SchemaBuilder::string())
+                .property("rect", SchemaBuilder::object())
+                .property(
+                    "target",
+                    SchemaBuilder::object()
+                        .property("selector", SchemaBuilder::string())
+                        .property("tag", SchemaBuilder::string())
+                        .property("label", SchemaBuilder::string()),
+                )
+                .property(
+                    "refs",
+                    SchemaBuilder::array().items(SchemaBuilder::string()),
+                )
+                .property("clicked", SchemaBuilder::boolean())
+                .build()
+        };
+
+        let media_selector_param = || {
+            SchemaBuilder::string().description(
+                "@eN ref or CSS selector of a <video>/<audio> or its player container; \
+                 default: the first media element on the page",
+            )
+        };
+        let media_state = || {
+            SchemaBuilder::object()
+                .property("ref_id", SchemaBuilder::string())
+                .property(
+                    "tag",
+                    SchemaBuilder::string().enum_values(&["video", "audio"]),
+                )
+                .property("src", SchemaBuilder::string())
+                .property(
+                    "duration",
+                    SchemaBuilder::number().description("Seconds; null until known or when live"),
+                )
+                .property("live", SchemaBuilder::boolean())
+                .property("current_time", SchemaBuilder::number())
+                .property("paused", SchemaBuilder::boolean())
+                .property("ended", SchemaBuilder::boolean())
+                .property("muted", SchemaBuilder::boolean())
+                .property("volume", SchemaBuilder::number())
+                .property("playback_rate", SchemaBuilder::number())
+                .property(
+                    "ready_state",
+                    SchemaBuilder::integer().description("HTMLMediaElement.readyState (0-4)"),
+                )
+                .property(
+                    "playable",
+                    SchemaBuilder::boolean().description("Enough data to play (readyState >= 3)"),
+                )
+                .property(
+                    "buffered_until",
+                    SchemaBuilder::number().description("End of the last buffered range, seconds"),
+                )
+                .property("width", SchemaBuilder::integer())
+                .property("height", SchemaBuilder::integer())
+                .property(
+                    "error",
+                    SchemaBuilder::string().description("Why the media failed to load"),
+                )
+        };
+        let media_control_returns = || {
+            SchemaBuilder::object()
+                .property("success", SchemaBuilder::boolean())
+                .property(
+                    "problem",
+                    SchemaBuilder::string().description(
+                        "Why the action didn't take (autoplay blocked, load error, ...)",
+                    ),
+                )
+                .property("state", media_state())
+                .build()
+        };
+
+        // Rehearse an interaction instead of performing it
+        // Shared by the scroll methods
+        let scroll_returns = || {
+            SchemaBuilder::object()
+                .property("success", SchemaBuilder::boolean())
+                .property("x", SchemaBuilder::number())
+                .property(
+                    "y",
+                    SchemaBuilder::number().description("Scroll offset afterwards"),
+                )
+                .property("scroll_height", SchemaBuilder::number())
+                .property("at_bottom", SchemaBuilder::boolean())
+                .property(
+                    "visible",
+                    SchemaBuilder::boolean()
+                        .description("Element is in the viewport (scroll_to_element)"),
+                )
+                .property(
+                    "rounds",
+                    SchemaBuilder::integer().description("Rounds taken (scroll_to_bottom)"),
+                )
+                .build()
+        };
+        let dry_run_param = || {
+            SchemaBuilder::boolean()
+                .default_value(json!(false))
+                .description(
+                    "Resolve the target and run actionability checks without dispatching events; \
+                     returns a dry-run report (actionable, problems, would_navigate, would_change)",
+                )
+        };
+        let rank_param =
+            || {
+                SchemaBuilder::string()
+                .enum_values(&["document", "in_viewport", "largest", "topmost", "reading_order"])
+                .description(
+                    "Which element to use when the selector matches several (default: first in \
+                     document order); the result reports the choice as ranking",
+                )
+            };
+
+        let recover_param = || {
+            SchemaBuilder::boolean().description(
+                "If the target is missing or stale, retry, then reload the page and retry once \
+                 more, finding a ref target again by role and name; the result reports the steps \
+                 as recovery (default: the daemon's --recover setting)",
+            )
+        };
+
+        // Screenshot region by coordinates
+        let clip_param = || {
+            SchemaBuilder::object()
+                .property("x", SchemaBuilder::number())
+                .property("y", SchemaBuilder::number())
+                .property("width", SchemaBuilder::number())
+                .property("height", SchemaBuilder::number())
+                .required(&["x", "y", "width", "height"])
+                .description(
+                    "Rect to capture in CSS pixels, e.g. a region a vision model picked out; combine with scale for more detail",
+                )
+        };
+
+        // Screenshot image format
+        let format_param = || {
+            SchemaBuilder::string()
+                .enum_values(&["png", "jpeg", "webp"])
+                .default_value(json!("png"))
+                .description(
+                    "Image format; jpeg and webp are much smaller for photos and video frames",
+                )
+        };
+        let quality_param = || {
+            SchemaBuilder::integer().description(
+                "Compression quality for jpeg and webp (0-100, Chrome's default if omitted)",
+            )
+        };
+
+        vec![
+            // ================================================================
+            // Navigation and State
+            // ================================================================
+            MethodInfo::new("browser.open", "Navigate to a URL")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "url",
+                            SchemaBuilder::string()
+                                .format("uri")
+                                .description("URL to navigate to"),
+                        )
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"])
+                                .default_value(json!("load"))
+                                .description("When to consider navigation complete; networkidle waits for no requests for 500ms (up to the time limit)"),
+                        )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["url"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("url", SchemaBuilder::string().format("uri"))
+                        .property("title", SchemaBuilder::string())
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"])
+                                .description("Last condition reached; earlier than requested if the wait timed out"),
+                        )
+                        .property("load_time_ms", SchemaBuilder::number())
+                        .property(
+                            "captchas",
+                            SchemaBuilder::array()
+                                .items(
+                                    SchemaBuilder::object()
+                                        .property(
+                                            "provider",
+                                            SchemaBuilder::string().enum_values(&[
+                                                "recaptcha",
+                                                "hcaptcha",
+                                                "turnstile",
+                                            ]),
+                                        )
+                                        .property("frame_url", SchemaBuilder::string()),
+                                )
+                                .description(
+                                    "CAPTCHA widgets on the page, for handing over to a human; absent when there are none",
+                                ),
+                        )
+                        .build(),
+                )
+                .example("Navigate to Google", json!({"url": "https://google.com"}))
+                .example(
+                    "Wait for network idle",
+                    json!({"url": "https://example.com", "wait_until": "networkidle"}),
+                )
+                .example(
+                    "Give a slow intranet page two minutes",
+                    json!({"url": "https://intranet.example.com", "timeout_ms": 120000}),
+                )
+                .errors(&["NAVIGATION_FAILED", "TIMEOUT", "ROBOTS_DISALLOWED"]),
+            MethodInfo::new("browser.go_back", "Go back one page in history")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"])
+                                .default_value(json!("load"))
+                                .description("When to consider navigation complete"),
+                        )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("url", SchemaBuilder::string().format("uri"))
+                        .property("title", SchemaBuilder::string())
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"]),
+                        )
+                        .property("load_time_ms", SchemaBuilder::number())
+                        .build(),
+                )
+                .example("Go back", json!({}))
+                .errors(&["NAVIGATION_FAILED", "TIMEOUT"]),
+            MethodInfo::new("browser.go_forward", "Go forward one page in history")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"])
+                                .default_value(json!("load"))
+                                .description("When to consider navigation complete"),
+                        )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("url", SchemaBuilder::string().format("uri"))
+                        .property("title", SchemaBuilder::string())
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"]),
+                        )
+                        .property("load_time_ms", SchemaBuilder::number())
+                        .build(),
+                )
+                .example("Go forward", json!({}))
+                .errors(&["NAVIGATION_FAILED", "TIMEOUT"]),
+            MethodInfo::new("browser.reload", "Reload the current page")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "ignore_cache",
+                            SchemaBuilder::boolean()
+                                .default_value(json!(false))
+                                .description("Bypass the cache, like a hard refresh"),
+                        )
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"])
+                                .default_value(json!("load"))
+                                .description("When to consider navigation complete"),
+                        )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("url", SchemaBuilder::string().format("uri"))
+                        .property("title", SchemaBuilder::string())
+                        .property(
+                            "wait_until",
+                            SchemaBuilder::string()
+                                .enum_values(&["commit", "domcontentloaded", "load", "networkidle"]),
+                        )
+                        .property("load_time_ms", SchemaBuilder::number())
+                        .build(),
+                )
+                .example("Hard refresh", json!({"ignore_cache": true}))
+                .errors(&["NAVIGATION_FAILED", "TIMEOUT"]),
+            MethodInfo::new(
+                "browser.snapshot",
+                "Get ARIA accessibility tree with @eN refs for element targeting",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "cache",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Reuse the last snapshot if the page hasn't changed"),
+                    )
+                    .property(
+                        "limit",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .description("Maximum nodes per page (enables pagination)"),
+                    )
+                    .property(
+                        "cursor",
+                        SchemaBuilder::string()
+                            .description("next_cursor from a previous page of the same snapshot"),
+                    )
+                    .property(
+                        "max_bytes",
+                        SchemaBuilder::integer().minimum(1024).description(
+                            "Approximate response size cap; can only lower the daemon's --max-response-kb",
+                        ),
+                    )
+                    .property(
+                        "format",
+                        SchemaBuilder::string().enum_values(&["json", "text"]).description(
+                            "json: nodes as a tree; text: indented `- role \"name\" [@eN]` lines, \
+                             a fraction of the tokens (default: the daemon's --snapshot-format)",
+                        ),
+                    )
+                    .property(
+                        "diff",
+                        SchemaBuilder::boolean().default_value(json!(false)).description(
+                            "With format text: also return a unified diff against the previous snapshot",
+                        ),
+                    )
+                    .property(
+                        "roles",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Only nodes with these roles"),
+                    )
+                    .property(
+                        "interactive",
+                        SchemaBuilder::boolean().default_value(json!(false)).description(
+                            "Only actionable nodes: buttons, links, form fields, tabs, menu items, and anything focusable",
+                        ),
+                    )
+                    .property(
+                        "visible",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Only rendered nodes, not hidden, collapsed or transparent"),
+                    )
+                    .property(
+                        "root",
+                        SchemaBuilder::string().description(
+                            "Only this @eN ref (from an earlier snapshot) and its descendants",
+                        ),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "snapshot",
+                        SchemaBuilder::string()
+                            .description("ARIA tree with @eN refs for clicking/filling"),
+                    )
+                    .property(
+                        "text",
+                        SchemaBuilder::string().description("The tree as text (format: text)"),
+                    )
+                    .property(
+                        "diff",
+                        SchemaBuilder::string().description(
+                            "Changed lines with context, refs ignored when comparing; absent on the first snapshot",
+                        ),
+                    )
+                    .property("url", SchemaBuilder::string().format("uri"))
+                    .property("title", SchemaBuilder::string())
+                    .property(
+                        "cached",
+                        SchemaBuilder::boolean()
+                            .description("True if served from the per-session cache"),
+                    )
+                    .property(
+                        "element_count",
+                        SchemaBuilder::integer().description("Total nodes across all pages"),
+                    )
+                    .property("offset", SchemaBuilder::integer())
+                    .property(
+                        "next_cursor",
+                        SchemaBuilder::string().description(
+                            "Pass as cursor to fetch the next page; absent on the last page",
+                        ),
+                    )
+                    .property(
+                        "truncated",
+                        SchemaBuilder::boolean().description(
+                            "Present when the size limit ended the page early or shortened node text",
+                        ),
+                    )
+                    .property(
+                        "focused",
+                        SchemaBuilder::string()
+                            .description("Ref of the focused element; absent when nothing in the tree has focus"),
+                    )
+                    .build(),
+            )
+            .example("Get page snapshot", json!({}))
+            .example("Force a fresh snapshot", json!({"cache": false}))
+            .example("First page of a large tree", json!({"limit": 500}))
+            .example(
+                "Next page",
+                json!({"limit": 500, "cursor": "<next_cursor from previous page>"}),
+            )
+            .example("Stay under 256 KB per response", json!({"max_bytes": 262144}))
+            .example("Compact text tree", json!({"format": "text"}))
+            .example(
+                "Only what can be clicked or typed into, on screen",
+                json!({"interactive": true, "visible": true}),
+            )
+            .example("One dialog's contents", json!({"root": "@e42"}))
+            .example(
+                "What changed since the last snapshot",
+                json!({"format": "text", "diff": true}),
+            )
+            .errors(&["SNAPSHOT_CURSOR_EXPIRED", "TIMEOUT"]),
+            MethodInfo::new(
+                "browser.snapshot_diff",
+                "Take a snapshot and report the nodes added, removed and changed since the previous one",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "cache",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Reuse the cached snapshot when the page hasn't changed"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string().format("uri"))
+                    .property(
+                        "compared",
+                        SchemaBuilder::boolean().description(
+                            "False on the session's first snapshot, with nothing to compare against",
+                        ),
+                    )
+                    .property(
+                        "added",
+                        SchemaBuilder::array().description("New nodes, with their current refs"),
+                    )
+                    .property(
+                        "removed",
+                        SchemaBuilder::array()
+                            .description("Nodes gone since the previous snapshot, with their old refs"),
+                    )
+                    .property(
+                        "changed",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property("ref_id", SchemaBuilder::string())
+                                    .property("previous_ref", SchemaBuilder::string())
+                                    .property("role", SchemaBuilder::string())
+                                    .property(
+                                        "fields",
+                                        SchemaBuilder::array()
+                                            .items(SchemaBuilder::string())
+                                            .description("name, value, focused and/or focusable"),
+                                    )
+                                    .property("before", SchemaBuilder::object())
+                                    .property("after", SchemaBuilder::object()),
+                            )
+                            .description(
+                                "Nodes matched by role and name (or same-role replacements) whose properties changed",
+                            ),
+                    )
+                    .property("unchanged", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example("What changed since the last snapshot", json!({}))
+            .example("Force a fresh snapshot", json!({"cache": false})),
+            MethodInfo::new(
+                "browser.screenshot",
+                "Capture screenshot as base64 or save to file",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "path",
+                        SchemaBuilder::string().description(
+                            "File path to save screenshot (optional, returns base64 if omitted)",
+                        ),
+                    )
+                    .property(
+                        "full_page",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Capture the whole scrollable page instead of the viewport"),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Store in the artifact backend and return its ID/URL"),
+                    )
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref, CSS selector or XPath to crop the capture to"),
+                    )
+                    .property(
+                        "padding",
+                        SchemaBuilder::number()
+                            .default_value(json!(0))
+                            .description("CSS pixels of surrounding context around the element"),
+                    )
+                    .property(
+                        "marker",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Outline the element in the captured image"),
+                    )
+                    .property("clip", clip_param())
+                    .property(
+                        "clip_origin",
+                        SchemaBuilder::string()
+                            .enum_values(&["document", "viewport"])
+                            .default_value(json!("document"))
+                            .description("What clip coordinates are relative to"),
+                    )
+                    .property(
+                        "scale",
+                        SchemaBuilder::number().description(
+                            "Device scale factor for this capture only, e.g. 2 for a crisp 2x image (0 < scale <= 4)",
+                        ),
+                    )
+                    .property("format", format_param())
+                    .property("quality", quality_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "base64",
+                        SchemaBuilder::string()
+                            .description("Base64-encoded image (if no path specified)"),
+                    )
+                    .property(
+                        "path",
+                        SchemaBuilder::string()
+                            .description("Saved file path (if path was specified)"),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::object()
+                            .property("id", SchemaBuilder::string())
+                            .property("url", SchemaBuilder::string())
+                            .property("size", SchemaBuilder::integer())
+                            .description("Stored artifact (if artifact was requested)"),
+                    )
+                    .property("format", SchemaBuilder::string())
+                    .property("width", SchemaBuilder::integer())
+                    .property("height", SchemaBuilder::integer())
+                    .property(
+                        "clip",
+                        SchemaBuilder::object()
+                            .property("x", SchemaBuilder::number())
+                            .property("y", SchemaBuilder::number())
+                            .property("width", SchemaBuilder::number())
+                            .property("height", SchemaBuilder::number())
+                            .description("Captured region in document pixels (element and clip captures)"),
+                    )
+                    .property(
+                        "page_width",
+                        SchemaBuilder::number()
+                            .description("Width of the whole page in CSS pixels (page captures)"),
+                    )
+                    .property(
+                        "page_height",
+                        SchemaBuilder::number()
+                            .description("Height of the whole page in CSS pixels (page captures)"),
+                    )
+                    .build(),
+            )
+            .example("Get base64 screenshot", json!({}))
+            .example("Store as artifact", json!({"artifact": true}))
+            .example("2x image for documentation", json!({"scale": 2}))
+            .example(
+                "Re-capture a region of the viewport at 3x",
+                json!({"clip": {"x": 640, "y": 120, "width": 320, "height": 180}, "clip_origin": "viewport", "scale": 3}),
+            )
+            .example(
+                "Compact JPEG of the whole page",
+                json!({"full_page": true, "format": "jpeg", "quality": 70}),
+            )
+            .example(
+                "Element in context, outlined",
+                json!({"selector": "@e12", "padding": 40, "marker": true}),
+            )
+            .example(
+                "Save to file",
+                json!({"path": "/tmp/screenshot.png", "full_page": true}),
+            ),
+            MethodInfo::new(
+                "browser.screenshot_element",
+                "Capture one element, clipped to its box plus optional padding",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description(
+                            "@eN ref (frame refs too), CSS selector or XPath of the element",
+                        ),
+                    )
+                    .property(
+                        "padding",
+                        SchemaBuilder::number()
+                            .default_value(json!(0))
+                            .description("CSS pixels of surrounding context around the element"),
+                    )
+                    .property(
+                        "marker",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Outline the element in the captured image"),
+                    )
+                    .property(
+                        "path",
+                        SchemaBuilder::string().description(
+                            "File path to save screenshot (optional, returns base64 if omitted)",
+                        ),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Store in the artifact backend and return its ID/URL"),
+                    )
+                    .property(
+                        "scale",
+                        SchemaBuilder::number()
+                            .description("Device scale factor for this capture only (0 < scale <= 4)"),
+                    )
+                    .property("format", format_param())
+                    .property("quality", quality_param())
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("base64", SchemaBuilder::string())
+                    .property("path", SchemaBuilder::string())
+                    .property("artifact", SchemaBuilder::object())
+                    .property("format", SchemaBuilder::string())
+                    .property("width", SchemaBuilder::integer())
+                    .property("height", SchemaBuilder::integer())
+                    .property(
+                        "clip",
+                        SchemaBuilder::object()
+                            .description("Captured region in document pixels"),
+                    )
+                    .build(),
+            )
+            .example("Capture a widget", json!({"selector": "@e12"}))
+            .example(
+                "Widget with 16px of context, saved to a file",
+                json!({"selector": "#pricing-table", "padding": 16, "path": "/tmp/pricing.png"}),
+            )
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.screenshot_after_paint",
+                "Screenshot once the next frame is painted, optionally right after a click",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "click",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector to click first"),
+                    )
+                    .property(
+                        "frames",
+                        SchemaBuilder::integer()
+                            .default_value(json!(DEFAULT_PAINT_FRAMES))
+                            .description(
+                                "Painted frames to wait for (1-10); more for changes made in animation callbacks",
+                            ),
+                    )
+                    .property(
+                        "path",
+                        SchemaBuilder::string().description(
+                            "File path to save screenshot (optional, returns base64 if omitted)",
+                        ),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Store in the artifact backend and return its ID/URL"),
+                    )
+                    .property(
+                        "full_page",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Capture the whole scrollable page instead of the viewport"),
+                    )
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector to crop the capture to"),
+                    )
+                    .property(
+                        "padding",
+                        SchemaBuilder::number()
+                            .default_value(json!(0))
+                            .description("CSS pixels of surrounding context around the element"),
+                    )
+                    .property(
+                        "marker",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Outline the element in the captured image"),
+                    )
+                    .property("clip", clip_param())
+                    .property(
+                        "clip_origin",
+                        SchemaBuilder::string()
+                            .enum_values(&["document", "viewport"])
+                            .default_value(json!("document"))
+                            .description("What clip coordinates are relative to"),
+                    )
+                    .property(
+                        "scale",
+                        SchemaBuilder::number()
+                            .description("Device scale factor for this capture only (0 < scale <= 4)"),
+                    )
+                    .property("format", format_param())
+                    .property("quality", quality_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("base64", SchemaBuilder::string())
+                    .property("path", SchemaBuilder::string())
+                    .property("artifact", SchemaBuilder::object())
+                    .property("format", SchemaBuilder::string())
+                    .property("width", SchemaBuilder::integer())
+                    .property("height", SchemaBuilder::integer())
+                    .property("clip", SchemaBuilder::object())
+                    .build(),
+            )
+            .example(
+                "Capture the dropdown a click opens",
+                json!({"click": "@e12", "artifact": true}),
+            ),
+            MethodInfo::new(
+                "browser.compare_screenshots",
+                "Compare a screenshot against a baseline; returns mismatch, changed regions and a diff image",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "baseline",
+                        SchemaBuilder::string().description("Baseline image file (PNG, JPEG or WebP)"),
+                    )
+                    .property(
+                        "baseline_artifact",
+                        SchemaBuilder::string().description("Artifact ID of the baseline image"),
+                    )
+                    .property(
+                        "current",
+                        SchemaBuilder::string()
+                            .description("Image file to compare (optional, captures the page if omitted)"),
+                    )
+                    .property(
+                        "current_artifact",
+                        SchemaBuilder::string().description("Artifact ID of the image to compare"),
+                    )
+                    .property(
+                        "full_page",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Capture the whole scrollable page instead of the viewport"),
+                    )
+                    .property(
+                        "threshold",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .maximum(255)
+                            .default_value(json!(visual_diff::DEFAULT_THRESHOLD))
+                            .description("Per-channel difference tolerated before a pixel counts as changed"),
+                    )
+                    .property(
+                        "max_mismatch",
+                        SchemaBuilder::number()
+                            .description("Mismatch percentage allowed; sets 'passed' when given"),
+                    )
+                    .property(
+                        "diff_path",
+                        SchemaBuilder::string().description(
+                            "File path to save the diff PNG (optional, returns base64 if omitted)",
+                        ),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Store the diff image in the artifact backend"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("width", SchemaBuilder::integer())
+                    .property("height", SchemaBuilder::integer())
+                    .property("same_size", SchemaBuilder::boolean())
+                    .property("threshold", SchemaBuilder::integer())
+                    .property("changed_pixels", SchemaBuilder::integer())
+                    .property("total_pixels", SchemaBuilder::integer())
+                    .property("mismatch_percent", SchemaBuilder::number())
+                    .property(
+                        "regions",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("x", SchemaBuilder::integer())
+                                .property("y", SchemaBuilder::integer())
+                                .property("width", SchemaBuilder::integer())
+                                .property("height", SchemaBuilder::integer())
+                                .property("pixels", SchemaBuilder::integer()),
+                        ),
+                    )
+                    .property("passed", SchemaBuilder::boolean())
+                    .property("diff", SchemaBuilder::string())
+                    .property("diff_path", SchemaBuilder::string())
+                    .property("artifact", SchemaBuilder::object())
+                    .build(),
+            )
+            .example(
+                "Check the page against a stored baseline",
+                json!({"baseline_artifact": "screenshot/default/1b4e28ba.png", "max_mismatch": 0.5, "artifact": true}),
+            ),
+            MethodInfo::new(
+                "browser.print_preview",
+                "Render the page with print media and store one image per page",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "paper",
+                        SchemaBuilder::string()
+                            .enum_values(&["letter", "legal", "a4", "a3"])
+                            .default_value(json!("letter")),
+                    )
+                    .property(
+                        "landscape",
+                        SchemaBuilder::boolean().default_value(json!(false)),
+                    )
+                    .property(
+                        "margin",
+                        SchemaBuilder::number()
+                            .default_value(json!(DEFAULT_PRINT_MARGIN_IN))
+                            .description("Margin on every side, in inches"),
+                    )
+                    .property(
+                        "scale",
+                        SchemaBuilder::number()
+                            .description("Device scale factor of the page images (0 < scale <= 4)"),
+                    )
+                    .property(
+                        "pdf",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Also store a PDF from Chrome's print pipeline (headless only)"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("paper", SchemaBuilder::string())
+                    .property("landscape", SchemaBuilder::boolean())
+                    .property(
+                        "page_width",
+                        SchemaBuilder::number().description("Printable width in CSS pixels"),
+                    )
+                    .property(
+                        "page_height",
+                        SchemaBuilder::number().description("Printable height in CSS pixels"),
+                    )
+                    .property("total_pages", SchemaBuilder::integer())
+                    .property(
+                        "pages",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property("number", SchemaBuilder::integer())
+                                    .property("clip", SchemaBuilder::object())
+                                    .property("artifact", SchemaBuilder::object()),
+                            )
+                            .description("Captured pages (at most 50)"),
+                    )
+                    .property(
+                        "pdf",
+                        SchemaBuilder::object().description("Stored PDF (if requested)"),
+                    )
+                    .build(),
+            )
+            .example("Letter pages", json!({}))
+            .example(
+                "A4 landscape report with a PDF",
+                json!({"paper": "a4", "landscape": true, "pdf": true}),
+            ),
+            MethodInfo::new(
+                "browser.pdf",
+                "Print the page to a PDF with Chrome's print pipeline (headless only)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "paper",
+                        SchemaBuilder::string()
+                            .enum_values(&["letter", "legal", "a4", "a3"])
+                            .default_value(json!("letter")),
+                    )
+                    .property(
+                        "landscape",
+                        SchemaBuilder::boolean().default_value(json!(false)),
+                    )
+                    .property(
+                        "margin",
+                        SchemaBuilder::number()
+                            .default_value(json!(DEFAULT_PRINT_MARGIN_IN))
+                            .description("Margin on every side in inches, or {top, right, bottom, left}"),
+                    )
+                    .property(
+                        "header",
+                        SchemaBuilder::string().description(
+                            "HTML drawn in the top margin; elements with class pageNumber, totalPages, title, url or date get those values",
+                        ),
+                    )
+                    .property(
+                        "footer",
+                        SchemaBuilder::string().description("HTML drawn in the bottom margin, like header"),
+                    )
+                    .property(
+                        "background",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Print background colors and images"),
+                    )
+                    .property(
+                        "path",
+                        SchemaBuilder::string().description("Write the PDF to this file"),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Store the PDF as an artifact"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "data",
+                        SchemaBuilder::string()
+                            .description("Base64 PDF (without path or artifact)"),
+                    )
+                    .property("path", SchemaBuilder::string())
+                    .property("artifact", SchemaBuilder::object())
+                    .property("paper", SchemaBuilder::string())
+                    .property("landscape", SchemaBuilder::boolean())
+                    .property(
+                        "margins",
+                        SchemaBuilder::object()
+                            .property("top", SchemaBuilder::number())
+                            .property("right", SchemaBuilder::number())
+                            .property("bottom", SchemaBuilder::number())
+                            .property("left", SchemaBuilder::number()),
+                    )
+                    .property(
+                        "size",
+                        SchemaBuilder::integer().description("PDF size in bytes"),
+                    )
+                    .build(),
+            )
+            .example("Letter PDF to a file", json!({"path": "/tmp/page.pdf"}))
+            .example(
+                "A4 report with page numbers, stored",
+                json!({
+                    "paper": "a4",
+                    "margin": {"top": 0.8, "bottom": 0.8},
+                    "footer": "<div style=\"font-size:9px;width:100%;text-align:center\"><span class=\"pageNumber\"></span> / <span class=\"totalPages\"></span></div>",
+                    "artifact": true
+                }),
+            ),
+            MethodInfo::new(
+                "browser.canvas.draw",
+                "Draw strokes on a canvas with pointer events (signature pads, whiteboards)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector of the canvas or its container"),
+                    )
+                    .property(
+                        "strokes",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::array().items(SchemaBuilder::object()))
+                            .description(
+                                "Strokes of points relative to the canvas's top-left: [x, y], \
+                                 [x, y, t], or {x, y, t?, pressure?} with t in ms since the stroke started",
+                            ),
+                    )
+                    .property(
+                        "normalized",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Points are fractions (0-1) of the canvas size"),
+                    )
+                    .property(
+                        "step_ms",
+                        SchemaBuilder::integer()
+                            .default_value(json!(DEFAULT_CANVAS_STEP_MS))
+                            .description("Delay between points without t, and between strokes"),
+                    )
+                    .property(
+                        "pointer",
+                        SchemaBuilder::string()
+                            .enum_values(&["mouse", "pen"])
+                            .default_value(json!("mouse")),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["selector", "strokes"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("strokes", SchemaBuilder::integer())
+                    .property("points", SchemaBuilder::integer())
+                    .property(
+                        "rect",
+                        SchemaBuilder::object().description("Canvas bounds in viewport pixels"),
+                    )
+                    .property("duration_ms", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example(
+                "Sign with a zigzag",
+                json!({"selector": "@e8", "normalized": true, "strokes": [[[0.1, 0.6], [0.3, 0.3], [0.5, 0.7], [0.7, 0.3], [0.9, 0.6]]]}),
+            ),
+            MethodInfo::new(
+                "browser.canvas.capture",
+                "Read a canvas bitmap as PNG (toDataURL)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector of the canvas or its container"),
+                    )
+                    .property(
+                        "path",
+                        SchemaBuilder::string().description("Save to this file instead of returning data"),
+                    )
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Store in the artifact backend instead of returning data"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("data", SchemaBuilder::string().description("Base64 PNG"))
+                    .property("path", SchemaBuilder::string())
+                    .property("artifact", SchemaBuilder::object())
+                    .property(
+                        "width",
+                        SchemaBuilder::integer().description("Bitmap width (not CSS width)"),
+                    )
+                    .property("height", SchemaBuilder::integer())
+                    .property(
+                        "clip",
+                        SchemaBuilder::object().description("Canvas bounds in document pixels"),
+                    )
+                    .build(),
+            )
+            .example("Signature as PNG", json!({"selector": "@e8", "artifact": true})),
+            MethodInfo::new(
+                "browser.media.state",
+                "Duration, position, and playback state of video/audio elements",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description(
+                            "@eN ref or CSS selector of a <video>/<audio> or its player container; \
+                             default: all media on the page",
+                        ),
+                    )
+                    .property(
+                        "wait_ms",
+                        SchemaBuilder::integer()
+                            .default_value(json!(DEFAULT_MEDIA_WAIT_MS))
+                            .description("Wait up to this long for the media to become playable"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("media", SchemaBuilder::array().items(media_state()))
+                    .build(),
+            )
+            .example(
+                "Check the hero video loads",
+                json!({"selector": "@e4", "wait_ms": 5000}),
+            ),
+            MethodInfo::new("browser.media.play", "Play a video/audio element")
+                .schema(
+                    SchemaBuilder::object()
+                        .property("selector", media_selector_param())
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .build(),
+                )
+                .returns(media_control_returns()),
+            MethodInfo::new("browser.media.pause", "Pause a video/audio element")
+                .schema(
+                    SchemaBuilder::object()
+                        .property("selector", media_selector_param())
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .build(),
+                )
+                .returns(media_control_returns()),
+            MethodInfo::new("browser.media.seek", "Seek a video/audio element")
+                .schema(
+                    SchemaBuilder::object()
+                        .property("selector", media_selector_param())
+                        .property(
+                            "time",
+                            SchemaBuilder::number().description("Position in seconds"),
+                        )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["time"])
+                        .build(),
+                )
+                .returns(media_control_returns())
+                .example("Skip to 1:30", json!({"selector": "@e4", "time": 90})),
+            MethodInfo::new(
+                "browser.media.mute",
+                "Mute (or unmute) all media on the page, including media that starts later",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "muted",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("false to unmute"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("muted", SchemaBuilder::boolean())
+                    .property(
+                        "elements",
+                        SchemaBuilder::integer().description("Media elements currently on the page"),
+                    )
+                    .build(),
+            ),
+            MethodInfo::new(
+                "browser.csp_trial",
+                "Reload the page with a trial Content-Security-Policy and report what it blocks",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "policy",
+                        SchemaBuilder::string().description("Policy to try, as in the header"),
+                    )
+                    .property(
+                        "url",
+                        SchemaBuilder::string()
+                            .format("uri")
+                            .description("Page to load (default: reload the current page)"),
+                    )
+                    .property(
+                        "mode",
+                        SchemaBuilder::string()
+                            .enum_values(&["report_only", "enforce"])
+                            .default_value(json!("report_only"))
+                            .description(
+                                "report_only adds a Report-Only header and blocks nothing; enforce replaces the page's own CSP headers",
+                            ),
+                    )
+                    .property(
+                        "wait_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(DEFAULT_CSP_SETTLE_MS))
+                            .description("Time after load to keep collecting violations"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["policy"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "success",
+                        SchemaBuilder::boolean()
+                            .description("Whether the policy was added to the main document"),
+                    )
+                    .property("url", SchemaBuilder::string())
+                    .property("policy", SchemaBuilder::string())
+                    .property("mode", SchemaBuilder::string())
+                    .property("existing_policy", SchemaBuilder::string())
+                    .property("blocked", SchemaBuilder::integer())
+                    .property(
+                        "violations",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("directive", SchemaBuilder::string())
+                                .property("blocked_uri", SchemaBuilder::string())
+                                .property("count", SchemaBuilder::integer())
+                                .property("source_file", SchemaBuilder::string())
+                                .property("line", SchemaBuilder::integer())
+                                .property("sample", SchemaBuilder::string()),
+                        ),
+                    )
+                    .build(),
+            )
+            .example(
+                "Trial a strict script policy",
+                json!({"policy": "default-src 'self'; script-src 'self'"}),
+            )
+            .example(
+                "Enforce on another page",
+                json!({"url": "https://shop.example.com/", "policy": "img-src 'self' data:", "mode": "enforce"}),
+            )
+            .errors(&["TIMEOUT", "ROBOTS_DISALLOWED"]),
+            MethodInfo::new(
+                "browser.get_third_party_report",
+                "Requests of the current page grouped by registrable domain, with bytes and blocking time",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string())
+                    .property("first_party", SchemaBuilder::string())
+                    .property("third_party_domains", SchemaBuilder::integer())
+                    .property("third_party_requests", SchemaBuilder::integer())
+                    .property("third_party_bytes", SchemaBuilder::integer())
+                    .property("third_party_blocking_ms", SchemaBuilder::number())
+                    .property("total_requests", SchemaBuilder::integer())
+                    .property("total_bytes", SchemaBuilder::integer())
+                    .property(
+                        "domains",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("domain", SchemaBuilder::string())
+                                .property("third_party", SchemaBuilder::boolean())
+                                .property("requests", SchemaBuilder::integer())
+                                .property("downloaded_bytes", SchemaBuilder::integer())
+                                .property(
+                                    "blocking_ms",
+                                    SchemaBuilder::number().description(
+                                        "Load time of the domain's render-blocking resources",
+                                    ),
+                                )
+                                .property(
+                                    "resource_types",
+                                    SchemaBuilder::array().items(SchemaBuilder::string()),
+                                ),
+                        ),
+                    )
+                    .build(),
+            )
+            .example("Audit the current page", json!({})),
+            MethodInfo::new(
+                "browser.get_environment",
+                "Browser build, platform, viewport, overrides, proxy, extensions and feature flags of a session",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "browser",
+                        SchemaBuilder::object()
+                            .property("product", SchemaBuilder::string())
+                            .property("revision", SchemaBuilder::string())
+                            .property("protocol_version", SchemaBuilder::string())
+                            .property("js_version", SchemaBuilder::string())
+                            .property("user_agent", SchemaBuilder::string())
+                            .property("headless", SchemaBuilder::boolean())
+                            .property(
+                                "mode",
+                                SchemaBuilder::string().enum_values(&["launch", "connect"]),
+                            ),
+                    )
+                    .property(
+                        "platform",
+                        SchemaBuilder::object()
+                            .property("os", SchemaBuilder::string())
+                            .property("arch", SchemaBuilder::string())
+                            .property("navigator_platform", SchemaBuilder::string()),
+                    )
+                    .property(
+                        "viewport",
+                        SchemaBuilder::object()
+                            .property("width", SchemaBuilder::integer())
+                            .property("height", SchemaBuilder::integer())
+                            .property("device_pixel_ratio", SchemaBuilder::number())
+                            .property("screen_width", SchemaBuilder::integer())
+                            .property("screen_height", SchemaBuilder::integer()),
+                    )
+                    .property(
+                        "emulation",
+                        SchemaBuilder::object()
+                            .property("zoom", SchemaBuilder::number())
+                            .property("keyboard_layout", SchemaBuilder::string())
+                            .property("fake_clock", SchemaBuilder::string())
+                            .property("popup_policy", SchemaBuilder::string())
+                            .property("show_cursor", SchemaBuilder::boolean())
+                            .property("user_agent", SchemaBuilder::string())
+                            .property("locale", SchemaBuilder::string())
+                            .property("timezone", SchemaBuilder::string())
+                            .description("Session overrides, and what the page reads"),
+                    )
+                    .property(
+                        "proxy",
+                        SchemaBuilder::string().description("Chrome's --proxy-server, if any"),
+                    )
+                    .property(
+                        "extensions",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("id", SchemaBuilder::string())
+                                .property("title", SchemaBuilder::string())
+                                .property("kind", SchemaBuilder::string()),
+                        ),
+                    )
+                    .property(
+                        "features",
+                        SchemaBuilder::object()
+                            .property("enabled", SchemaBuilder::array().items(SchemaBuilder::string()))
+                            .property("disabled", SchemaBuilder::array().items(SchemaBuilder::string()))
+                            .property(
+                                "switches",
+                                SchemaBuilder::array()
+                                    .items(SchemaBuilder::string())
+                                    .description("Chrome's command line, when it reports it"),
+                            ),
+                    )
+                    .build(),
+            )
+            .example("Attach to a bug report", json!({}))
+            .example("A named session", json!({"session_id": "checkout"})),
+            MethodInfo::new(
+                "browser.cdp",
+                "Send a raw Chrome DevTools Protocol call to the session's page and return the raw result (daemon must allow the domain)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "method",
+                        SchemaBuilder::string()
+                            .description("CDP method as Domain.method, e.g. Page.getLayoutMetrics"),
+                    )
+                    .property(
+                        "params",
+                        SchemaBuilder::object().description("CDP params, passed as given"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["method"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("method", SchemaBuilder::string())
+                    .property(
+                        "result",
+                        SchemaBuilder::object().description("Chrome's result, unchanged"),
+                    )
+                    .build(),
+            )
+            .example(
+                "Layout metrics",
+                json!({"method": "Page.getLayoutMetrics"}),
+            )
+            .example(
+                "Emulate a slow CPU",
+                json!({"method": "Emulation.setCPUThrottlingRate", "params": {"rate": 4}}),
+            )
+            .errors(&["CDP_NOT_ALLOWED"]),
+            MethodInfo::new(
+                "browser.locale_variants",
+                "Load a URL under several locale, timezone and geolocation combinations in parallel and snapshot each",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string().format("uri"))
+                    .property(
+                        "variants",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property(
+                                        "locale",
+                                        SchemaBuilder::string()
+                                            .description("BCP 47 locale, e.g. de-DE"),
+                                    )
+                                    .property(
+                                        "timezone",
+                                        SchemaBuilder::string()
+                                            .description("IANA timezone, e.g. Europe/Berlin"),
+                                    )
+                                    .property(
+                                        "geolocation",
+                                        SchemaBuilder::object()
+                                            .property("latitude", SchemaBuilder::number())
+                                            .property("longitude", SchemaBuilder::number())
+                                            .property(
+                                                "accuracy",
+                                                SchemaBuilder::number()
+                                                    .default_value(json!(100))
+                                                    .description("Meters"),
+                                            )
+                                            .required(&["latitude", "longitude"]),
+                                    )
+                                    .required(&["locale"]),
+                            )
+                            .description("1 to 8 variants, each loaded in its own browser context"),
+                    )
+                    .property(
+                        "wait_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(500))
+                            .description("Time to let each page settle after load"),
+                    )
+                    .required(&["url", "variants"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string())
+                    .property(
+                        "variants",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("locale", SchemaBuilder::string())
+                                .property("timezone", SchemaBuilder::string())
+                                .property("geolocation", SchemaBuilder::object())
+                                .property(
+                                    "url",
+                                    SchemaBuilder::string().description("URL after redirects"),
+                                )
+                                .property("title", SchemaBuilder::string())
+                                .property(
+                                    "lang",
+                                    SchemaBuilder::string()
+                                        .description("The document's lang attribute"),
+                                )
+                                .property("element_count", SchemaBuilder::integer())
+                                .property(
+                                    "text",
+                                    SchemaBuilder::string().description("Text snapshot"),
+                                )
+                                .property(
+                                    "error",
+                                    SchemaBuilder::string()
+                                        .description("Present when this variant failed to load"),
+                                ),
+                        ),
+                    )
+                    .property(
+                        "distinct_snapshots",
+                        SchemaBuilder::integer()
+                            .description("Different text snapshots among the variants; 1 means the page didn't localize"),
+                    )
+                    .build(),
+            )
+            .example(
+                "German, Japanese and US English",
+                json!({
+                    "url": "https://shop.example.com/",
+                    "variants": [
+                        {"locale": "de-DE", "timezone": "Europe/Berlin", "geolocation": {"latitude": 52.52, "longitude": 13.405}},
+                        {"locale": "ja-JP", "timezone": "Asia/Tokyo"},
+                        {"locale": "en-US", "timezone": "America/New_York"}
+                    ]
+                }),
+            )
+            .errors(&["ROBOTS_DISALLOWED"]),
+            MethodInfo::new(
+                "browser.check_links",
+                "Check the current page's same-origin links and report broken and redirected ones",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .property(
+                        "concurrency",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .maximum(32)
+                            .default_value(json!(8))
+                            .description("Requests in flight at once; per-origin throttle still applies"),
+                    )
+                    .property(
+                        "max_links",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(500))
+                            .description("Links checked, in document order"),
+                    )
+                    .property(
+                        "timeout_ms",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .default_value(json!(10000))
+                            .description("Per-link timeout"),
+                    )
+                    .property(
+                        "cache",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Reuse answers from the last 5 minutes"),
+                    )
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string())
+                    .property("checked", SchemaBuilder::integer())
+                    .property("ok", SchemaBuilder::integer())
+                    .property(
+                        "broken",
+                        SchemaBuilder::array()
+                            .items(link_check_schema())
+                            .description("4xx/5xx answers and failed requests"),
+                    )
+                    .property(
+                        "redirected",
+                        SchemaBuilder::array()
+                            .items(link_check_schema())
+                            .description("3xx answers; redirects aren't followed"),
+                    )
+                    .property(
+                        "unchecked",
+                        SchemaBuilder::integer().description("Links past max_links"),
+                    )
+                    .build(),
+            )
+            .example("Check every link on the page", json!({}))
+            .example("Gentler on the site", json!({"concurrency": 2, "max_links": 100})),
+            MethodInfo::new(
+                "browser.detect_blockers",
+                "Classify whether a login wall, paywall, CAPTCHA or interstitial blocks the current page",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string())
+                    .property("title", SchemaBuilder::string())
+                    .property("blocked", SchemaBuilder::boolean())
+                    .property(
+                        "primary",
+                        SchemaBuilder::string()
+                            .enum_values(&["login_wall", "paywall", "captcha", "interstitial"])
+                            .description("Most confident blocker; null when nothing was detected"),
+                    )
+                    .property(
+                        "blockers",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property(
+                                    "kind",
+                                    SchemaBuilder::string().enum_values(&[
+                                        "login_wall",
+                                        "paywall",
+                                        "captcha",
+                                        "interstitial",
+                                    ]),
+                                )
+                                .property(
+                                    "confidence",
+                                    SchemaBuilder::number()
+                                        .description("Heuristic score from 0.5 (detected) to 1"),
+                                )
+                                .property(
+                                    "evidence",
+                                    SchemaBuilder::array().items(SchemaBuilder::string()),
+                                ),
+                        ),
+                    )
+                    .build(),
+            )
+            .example("Check the current page", json!({})),
+            MethodInfo::new(
+                "browser.get_notifications",
+                "Web Notifications the page has shown (captured instead of displayed)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "clear",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Empty the session's notification list after reading"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "notifications",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("title", SchemaBuilder::string())
+                                .property("body", SchemaBuilder::string())
+                                .property("tag", SchemaBuilder::string())
+                                .property("icon", SchemaBuilder::string())
+                                .property("origin", SchemaBuilder::string())
+                                .property(
+                                    "source",
+                                    SchemaBuilder::string().enum_values(&["page", "service_worker"]),
+                                )
+                                .property(
+                                    "timestamp",
+                                    SchemaBuilder::string().description("RFC 3339, page clock"),
+                                ),
+                        ),
+                    )
+                    .property(
+                        "dropped",
+                        SchemaBuilder::integer().description("Older notifications dropped (100 are kept)"),
+                    )
+                    .build(),
+            ),
+            MethodInfo::new(
+                "browser.export_refs",
+                "Map snapshot refs to CDP node handles for other CDP clients on the same browser",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "refs",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Refs to export (e.g. [\"@e3\"]); all when omitted"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "target_id",
+                        SchemaBuilder::string().description("CDP target to attach to"),
+                    )
+                    .property("url", SchemaBuilder::string())
+                    .property(
+                        "refs",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("ref_id", SchemaBuilder::string())
+                                .property(
+                                    "backend_node_id",
+                                    SchemaBuilder::integer()
+                                        .description("DOM.BackendNodeId, usable from any CDP session"),
+                                )
+                                .property(
+                                    "object_id",
+                                    SchemaBuilder::string().description(
+                                        "Runtime.RemoteObjectId on the daemon's CDP session, until the next export",
+                                    ),
+                                )
+                                .property("tag", SchemaBuilder::string())
+                                .property(
+                                    "selector",
+                                    SchemaBuilder::string().description("Structural CSS path"),
+                                )
+                                .property(
+                                    "ref_selector",
+                                    SchemaBuilder::string()
+                                        .description("[data-fgp-ref] selector, valid until the next snapshot"),
+                                ),
+                        ),
+                    )
+                    .build(),
+            ),
+            MethodInfo::new(
+                "browser.artifact.get",
+                "Fetch a stored artifact by ID from the configured backend",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "id",
+                        SchemaBuilder::string()
+                            .description("Artifact ID, e.g. screenshot/<session>/<uuid>.png"),
+                    )
+                    .required(&["id"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("id", SchemaBuilder::string())
+                    .property("content_type", SchemaBuilder::string())
+                    .property("size", SchemaBuilder::integer())
+                    .property(
+                        "data",
+                        SchemaBuilder::string().description("Base64-encoded artifact bytes"),
+                    )
+                    .build(),
+            )
+            .example(
+                "Fetch a screenshot",
+                json!({"id": "screenshot/default/6f1c2a4e-0d7b-4a53-9a0e-2c9d1b7f3e10.png"}),
+            )
+            .errors(&["ARTIFACT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.artifact.gc",
+                "Apply the retention policy now instead of waiting for the background GC",
+            )
+            .schema(SchemaBuilder::object().build())
+            .returns(
+                SchemaBuilder::object()
+                    .property("deleted", SchemaBuilder::integer())
+                    .property("freed_bytes", SchemaBuilder::integer())
+                    .property("remaining", SchemaBuilder::integer())
+                    .property("remaining_bytes", SchemaBuilder::integer())
+                    .build(),
+            ),
+            MethodInfo::new(
+                "browser.maintain",
+                "Run a session maintenance pass now: clear caches, drop sessions whose page closed, trim event buffers, and recycle pages due for it",
+            )
+            .schema(SchemaBuilder::object().build())
+            .returns(
+                SchemaBuilder::object()
+                    .property("sessions", SchemaBuilder::integer())
+                    .property(
+                        "pruned",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Sessions removed because their page had closed"),
+                    )
+                    .property("trimmed_events", SchemaBuilder::integer())
+                    .property(
+                        "recycled",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Sessions moved to a fresh page and renderer process"),
+                    )
+                    .build(),
+            ),
+            // ================================================================
+            // Crawling
+            // ================================================================
+            MethodInfo::new(
+                "browser.crawl",
+                "Start a background crawl from a URL or sitemap.xml; poll browser.crawl.status for results",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string().format("uri"))
+                    .property(
+                        "max_pages",
+                        SchemaBuilder::integer().minimum(1).default_value(json!(50)),
+                    )
+                    .property(
+                        "same_origin",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Only follow links on the start URL's origin"),
+                    )
+                    .property(
+                        "depth",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(2))
+                            .description("Maximum link hops from the seed pages"),
+                    )
+                    .property(
+                        "sitemap",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description(
+                                "Seed from /sitemap.xml (or url, if it ends in .xml); \
+                                 from the page when there is no sitemap",
+                            ),
+                    )
+                    .property(
+                        "concurrency",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .default_value(json!(2))
+                            .description("Pages crawled in parallel (max 8); per-origin throttle still applies"),
+                    )
+                    .property(
+                        "extract",
+                        SchemaBuilder::object().description(
+                            "Field name -> CSS selector; each page reports the first match's text",
+                        ),
+                    )
+                    .required(&["url"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("crawl_id", SchemaBuilder::string())
+                    .build(),
+            )
+            .example(
+                "Crawl product pages",
+                json!({
+                    "url": "https://shop.example.com/",
+                    "max_pages": 100,
+                    "extract": {"name": "h1", "price": ".price"}
+                }),
+            )
+            .example(
+                "From sitemap",
+                json!({"url": "https://example.com", "sitemap": true, "depth": 0}),
+            ),
+            MethodInfo::new(
+                "browser.crawl.status",
+                "Crawl progress and pages finished since an offset",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("crawl_id", SchemaBuilder::string())
+                    .property(
+                        "since",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(0))
+                            .description("Return pages from this offset (the previous response's next)"),
+                    )
+                    .required(&["crawl_id"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("crawl_id", SchemaBuilder::string())
+                    .property(
+                        "state",
+                        SchemaBuilder::string()
+                            .enum_values(&["running", "done", "cancelled", "failed"]),
+                    )
+                    .property("error", SchemaBuilder::string())
+                    .property("visited", SchemaBuilder::integer())
+                    .property("queued", SchemaBuilder::integer())
+                    .property(
+                        "pages",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("url", SchemaBuilder::string())
+                                .property("depth", SchemaBuilder::integer())
+                                .property("title", SchemaBuilder::string())
+                                .property("data", SchemaBuilder::object())
+                                .property("links", SchemaBuilder::integer())
+                                .property("error", SchemaBuilder::string()),
+                        ),
+                    )
+                    .property("next", SchemaBuilder::integer())
+                    .build(),
+            )
+            .errors(&["CRAWL_NOT_FOUND"]),
+            MethodInfo::new("browser.crawl.cancel", "Stop a crawl after its in-flight pages")
+                .schema(
+                    SchemaBuilder::object()
+                        .property("crawl_id", SchemaBuilder::string())
+                        .required(&["crawl_id"])
+                        .build(),
+                )
+                .errors(&["CRAWL_NOT_FOUND"]),
+            // ================================================================
+            // Screenshot timeline
+            // ================================================================
+            MethodInfo::new(
+                "browser.timeline.auto",
+                "Capture a timeline screenshot after every browser.open",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "enabled",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Turn auto-capture on or off"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property("auto", SchemaBuilder::boolean())
+                    .build(),
+            )
+            .example("Record a storyboard", json!({}))
+            .example("Stop recording", json!({"enabled": false})),
+            MethodInfo::new(
+                "browser.timeline.capture",
+                "Add a screenshot of the current page to the session timeline",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "label",
+                        SchemaBuilder::string().description("Note shown with the frame"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(timeline_frame().build())
+            .example("Mark a step", json!({"label": "after checkout"})),
+            MethodInfo::new(
+                "browser.timeline.index",
+                "List the session's timeline frames in capture order",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "since",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(0))
+                            .description("Only frames with this index or later"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property("auto", SchemaBuilder::boolean())
+                    .property("dropped", SchemaBuilder::integer())
+                    .property("frames", SchemaBuilder::array().items(timeline_frame()))
+                    .build(),
+            )
+            .example("Whole storyboard", json!({}))
+            .example("Frames since the last poll", json!({"since": 12})),
+            MethodInfo::new("browser.timeline.clear", "Drop the session's timeline frames")
+                .schema(
+                    SchemaBuilder::object()
+                        .property("session_id", session_param())
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("session_id", SchemaBuilder::string())
+                        .property("cleared", SchemaBuilder::integer())
+                        .build(),
+                ),
+            // ================================================================
+            // Recording
+            // ================================================================
+            MethodInfo::new(
+                "browser.recording.start",
+                "Start recording the session's page to a WebM or MP4 video (needs ffmpeg)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "format",
+                        SchemaBuilder::string()
+                            .enum_values(&["webm", "mp4"])
+                            .default_value(json!("webm")),
+                    )
+                    .property(
+                        "fps",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .maximum(30)
+                            .default_value(json!(10))
+                            .description("Frames written per second"),
+                    )
+                    .property(
+                        "path",
+                        SchemaBuilder::string()
+                            .description("Output file (default: the daemon's recordings directory)"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property("path", SchemaBuilder::string())
+                    .property("format", SchemaBuilder::string())
+                    .property("fps", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example("Record as MP4", json!({"format": "mp4"}))
+            .errors(&["RECORDING_UNAVAILABLE"]),
+            MethodInfo::new(
+                "browser.recording.stop",
+                "Stop the session's recording and finish the video file",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "artifact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Also store the video in the artifact store"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property("path", SchemaBuilder::string())
+                    .property("format", SchemaBuilder::string())
+                    .property("started_at", SchemaBuilder::string())
+                    .property("duration_ms", SchemaBuilder::integer())
+                    .property("elapsed_ms", SchemaBuilder::integer())
+                    .property("frames", SchemaBuilder::integer())
+                    .property("fps", SchemaBuilder::integer())
+                    .property("size", SchemaBuilder::integer())
+                    .property("artifact", SchemaBuilder::object())
+                    .build(),
+            ),
+            // ================================================================
+            // Assertions
+            // ================================================================
+            MethodInfo::new(
+                "browser.assert_text",
+                "Assert that an element's text (or the page's) contains or equals a value",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector; omit to check the whole page"),
+                    )
+                    .property("expected", SchemaBuilder::string())
+                    .property(
+                        "exact",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Require equality instead of containment"),
+                    )
+                    .property("screenshot", screenshot_param())
+                    .property("session_id", session_param())
+                    .required(&["expected"])
+                    .build(),
+            )
+            .returns(assertion_returns())
+            .example("Page shows a greeting", json!({"expected": "Welcome back"}))
+            .example(
+                "Exact button label",
+                json!({"selector": "@e5", "expected": "Place order", "exact": true}),
+            ),
+            MethodInfo::new(
+                "browser.assert_visible",
+                "Assert that an element exists and is visible",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description("@eN ref or CSS selector"),
+                    )
+                    .property("screenshot", screenshot_param())
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(assertion_returns())
+            .example("Confirmation banner", json!({"selector": ".toast-success"})),
+            MethodInfo::new("browser.assert_url", "Assert that the page URL matches a pattern")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "pattern",
+                            SchemaBuilder::string()
+                                .description("Full URL; * matches any run of characters"),
+                        )
+                        .property("screenshot", screenshot_param())
+                        .property("session_id", session_param())
+                        .required(&["pattern"])
+                        .build(),
+                )
+                .returns(assertion_returns())
+                .example("Reached checkout", json!({"pattern": "*/checkout*"})),
+            MethodInfo::new(
+                "browser.assert_count",
+                "Assert how many elements match a selector",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("selector", SchemaBuilder::string().description("CSS selector"))
+                    .property("count", SchemaBuilder::integer().minimum(0))
+                    .property("screenshot", screenshot_param())
+                    .property("session_id", session_param())
+                    .required(&["selector", "count"])
+                    .build(),
+            )
+            .returns(assertion_returns())
+            .example("Three cart items", json!({"selector": ".cart-item", "count": 3})),
+            MethodInfo::new(
+                "browser.verify",
+                "Run several assertions without stopping at the first failure and report them together",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "assertions",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property(
+                                    "type",
+                                    SchemaBuilder::string()
+                                        .enum_values(&["text", "visible", "url", "count"]),
+                                )
+                                .property("selector", SchemaBuilder::string())
+                                .property(
+                                    "expected",
+                                    SchemaBuilder::string()
+                                        .description("Text for type=text, number for type=count"),
+                                )
+                                .property("exact", SchemaBuilder::boolean())
+                                .property("pattern", SchemaBuilder::string())
+                                .required(&["type"]),
+                        ),
+                    )
+                    .property(
+                        "screenshot",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Store one screenshot artifact if any assertion fails"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["assertions"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("passed", SchemaBuilder::boolean())
+                    .property("total", SchemaBuilder::integer())
+                    .property("failed", SchemaBuilder::integer())
+                    .property(
+                        "results",
+                        SchemaBuilder::array().items(SchemaBuilder::object()),
+                    )
+                    .property("screenshot", SchemaBuilder::object())
+                    .build(),
+            )
+            .example(
+                "Post-deploy smoke check",
+                json!({"assertions": [
+                    {"type": "url", "pattern": "https://shop.example.com/*"},
+                    {"type": "visible", "selector": "nav"},
+                    {"type": "text", "selector": "h1", "expected": "Spring sale"},
+                    {"type": "count", "selector": ".product-card", "expected": 12}
+                ]}),
+            ),
+            // ================================================================
+            // Interaction
+            // ================================================================
+            MethodInfo::new("browser.click", "Click element by @eN ref, CSS selector or XPath")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "selector",
+                            SchemaBuilder::string()
+                                .description("@eN ref from snapshot, CSS selector or XPath"),
+                        )
+                        .property(
+                            "button",
+                            SchemaBuilder::string()
+                                .enum_values(&["left", "right", "middle"])
+                                .default_value(json!("left")),
+                        )
+                        .property(
+                            "click_count",
+                            SchemaBuilder::integer()
+                                .minimum(1)
+                                .maximum(3)
+                                .default_value(json!(1))
+                                .description("1=click, 2=double-click, 3=triple-click"),
+                        )
+                        .property(
+                            "modifiers",
+                            SchemaBuilder::array()
+                                .items(SchemaBuilder::string())
+                                .description(
+                                    "Modifier keys to hold: Shift, Control, Alt, Meta (or \
+                                     \"Shift+Control\")",
+                                ),
+                        )
+                        .property("dry_run", dry_run_param())
+                        .property("rank", rank_param())
+                        .property("recover", recover_param())
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["selector"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("success", SchemaBuilder::boolean())
+                        .property("element", SchemaBuilder::string())
+                        .property(
+                            "button",
+                            SchemaBuilder::string().enum_values(&["left", "right", "middle"]),
+                        )
+                        .property("click_count", SchemaBuilder::integer())
+                        .property(
+                            "modifiers",
+                            SchemaBuilder::array().items(SchemaBuilder::string()),
+                        )
+                        .build(),
+                )
+                .example("Click by ref", json!({"selector": "@e15"}))
+                .example("Open the context menu", json!({"selector": "@e15", "button": "right"}))
+                .example("Double-click", json!({"selector": "@e15", "click_count": 2}))
+                .example(
+                    "Shift-click to extend a selection",
+                    json!({"selector": "@e22", "modifiers": ["Shift"]}),
+                )
+                .example(
+                    "Check what a click would do",
+                    json!({"selector": "@e15", "dry_run": true}),
+                )
+                .example(
+                    "Click the on-screen one of several matches",
+                    json!({"selector": "button.save", "rank": "in_viewport"}),
+                )
+                .example(
+                    "Double-click",
+                    json!({"selector": "@e20", "click_count": 2}),
+                )
+                .errors(&["ELEMENT_NOT_FOUND", "ELEMENT_NOT_VISIBLE", "TIMEOUT"]),
+            MethodInfo::new("browser.fill", "Fill input field with value")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "selector",
+                            SchemaBuilder::string()
+                                .description("@eN ref from snapshot, CSS selector or XPath"),
+                        )
+                        .property("value", SchemaBuilder::string().description("Text to fill"))
+                        .property(
+                            "append",
+                            SchemaBuilder::boolean()
+                                .default_value(json!(false))
+                                .description("Add to the existing text instead of replacing it"),
+                        )
+                        .property("dry_run", dry_run_param())
+                        .property("rank", rank_param())
+                        .property("recover", recover_param())
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["selector", "value"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("success", SchemaBuilder::boolean())
+                        .property("value", SchemaBuilder::string())
+                        .property("appended", SchemaBuilder::boolean())
+                        .property("previous_value", SchemaBuilder::string())
+                        .property(
+                            "field_value",
+                            SchemaBuilder::string()
+                                .description("Field text afterwards, as the page left it"),
+                        )
+                        .build(),
+                )
+                .example(
+                    "Fill search box",
+                    json!({"selector": "@e5", "value": "search query"}),
+                )
+                .example(
+                    "Add to a prefilled field",
+                    json!({"selector": "@e7", "value": ", Apt 4", "append": true}),
+                )
+                .errors(&["ELEMENT_NOT_FOUND", "ELEMENT_NOT_EDITABLE", "TIMEOUT"]),
+            MethodInfo::new(
+                "browser.focus",
+                "Focus an element without clicking it, firing focus/focusin",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description("@eN ref from snapshot, CSS selector or XPath"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(focus_returns().build())
+            .example("Focus a field", json!({"selector": "@e7"}))
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.blur",
+                "Take focus away from an element, firing blur/focusout (validation, autosave)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("Element to blur (default: whatever has focus)"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(focus_returns().build())
+            .example("Leave a field to trigger validation", json!({"selector": "@e7"}))
+            .example("Blur whatever has focus", json!({}))
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new("browser.clear", "Empty an input, textarea, or contenteditable")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "selector",
+                            SchemaBuilder::string()
+                                .description("@eN ref from snapshot, CSS selector or XPath"),
+                        )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["selector"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property(
+                            "success",
+                            SchemaBuilder::boolean().description("Whether the field is now empty"),
+                        )
+                        .property("selector", SchemaBuilder::string())
+                        .property("previous_value", SchemaBuilder::string())
+                        .build(),
+                )
+                .example("Empty a prefilled field", json!({"selector": "@e7"}))
+                .errors(&["ELEMENT_NOT_FOUND", "ELEMENT_NOT_EDITABLE"]),
+            MethodInfo::new(
+                "browser.type_text",
+                "Type text one key at a time with human-like pauses, for autocomplete and controlled inputs",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description(
+                            "@eN ref, CSS selector or XPath to click first (default: the focused element)",
+                        ),
+                    )
+                    .property("text", SchemaBuilder::string().description("Text to type"))
+                    .property(
+                        "delay_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(DEFAULT_TYPE_DELAY_MS))
+                            .description("Pause between keystrokes"),
+                    )
+                    .property(
+                        "jitter_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(DEFAULT_TYPE_JITTER_MS))
+                            .description("Vary each pause randomly by up to this much either way"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["text"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("typed", SchemaBuilder::integer())
+                    .property("elapsed_ms", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example(
+                "Type into an autocomplete",
+                json!({"selector": "#city", "text": "San Fran"}),
+            )
+            .example(
+                "Slow, steady typing",
+                json!({"selector": "@e4", "text": "hello", "delay_ms": 200, "jitter_ms": 0}),
+            )
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.fill_rich_text",
+                "Fill a rich text editor (ProseMirror, Quill, Draft.js, contenteditable) by pasting",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref, CSS selector or XPath of the editor or its container"),
+                    )
+                    .property("content", SchemaBuilder::string())
+                    .property(
+                        "format",
+                        SchemaBuilder::string()
+                            .enum_values(&["markdown", "html", "text"])
+                            .default_value(json!("markdown")),
+                    )
+                    .property(
+                        "append",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Add at the end instead of replacing the content"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["selector", "content"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("editor", SchemaBuilder::string())
+                    .property(
+                        "method",
+                        SchemaBuilder::string().enum_values(&[
+                            "paste",
+                            "insert_html",
+                            "insert_text",
+                        ]),
+                    )
+                    .property("changed", SchemaBuilder::boolean())
+                    .property(
+                        "text",
+                        SchemaBuilder::string().description("Editor text afterwards (truncated)"),
+                    )
+                    .build(),
+            )
+            .example(
+                "Write a formatted comment",
+                json!({"selector": "@e21", "content": "Looks good, **ship it**.\n\n- tests pass"}),
+            )
+            .errors(&["ELEMENT_NOT_FOUND", "ELEMENT_NOT_EDITABLE"]),
+            MethodInfo::new(
+                "browser.select_text",
+                "Select text in an element by character offsets or by matching text",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description(
+                            "@eN ref or CSS selector of the element (default: the whole page)",
+                        ),
+                    )
+                    .property(
+                        "start",
+                        SchemaBuilder::integer()
+                            .default_value(json!(0))
+                            .description("First character to select in the element's text"),
+                    )
+                    .property(
+                        "end",
+                        SchemaBuilder::integer()
+                            .description("Character after the last selected (default: the end)"),
+                    )
+                    .property(
+                        "text",
+                        SchemaBuilder::string().description(
+                            "Text to select instead of offsets, whitespace-insensitive",
+                        ),
+                    )
+                    .property(
+                        "occurrence",
+                        SchemaBuilder::integer()
+                            .default_value(json!(1))
+                            .description("Which match of 'text' to select, from 1"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(selection_returns())
+            .example(
+                "Select a sentence",
+                json!({"selector": "@e31", "text": "Shipping is free over $50."}),
+            )
+            .example(
+                "Select characters 10-25 of a paragraph",
+                json!({"selector": "article p:nth-of-type(2)", "start": 10, "end": 25}),
+            )
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.get_selection",
+                "Read the selected text, in the focused field or the page",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(selection_returns())
+            .example("Read the selection", json!({})),
+            MethodInfo::new(
+                "browser.copy_selection",
+                "Copy the selection to the clipboard, running the page's copy handlers",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("copied", SchemaBuilder::boolean())
+                    .property(
+                        "text",
+                        SchemaBuilder::string().description("Plain text put on the clipboard"),
+                    )
+                    .property("html", SchemaBuilder::string())
+                    .property(
+                        "modified",
+                        SchemaBuilder::boolean()
+                            .description("Whether a page copy handler replaced the selected content"),
+                    )
+                    .build(),
+            )
+            .example("Copy what is selected", json!({})),
+            MethodInfo::new("browser.press", "Press a keyboard key")
                 .schema(
                     SchemaBuilder::object()
                         .property(
-                            "url",
-                            SchemaBuilder::string()
-                                .format("uri")
-                                .description("URL to navigate to"),
+                            "key",
+                            SchemaBuilder::string().description(
+                                "Key name (Enter, Tab, Escape, ArrowDown, etc.), a single character, \
+                                 or a combo like Control+A or Meta+Shift+P",
+                            ),
                         )
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["key"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("success", SchemaBuilder::boolean())
+                        .property("key", SchemaBuilder::string())
+                        .build(),
+                )
+                .example("Press Enter", json!({"key": "Enter"}))
+                .example("Press Escape", json!({"key": "Escape"}))
+                .example("Select all", json!({"key": "Control+A"})),
+            MethodInfo::new(
+                "browser.press_key",
+                "Press a key or shortcut (alias of browser.press)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "key",
+                        SchemaBuilder::string().description(
+                            "Key name (Enter, Tab, Escape, ArrowDown, etc.), a single character, \
+                             or a combo like Control+A or Meta+Shift+P",
+                        ),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["key"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("key", SchemaBuilder::string())
+                    .build(),
+            )
+            .example("Press Enter", json!({"key": "Enter"}))
+            .example("Press Escape", json!({"key": "Escape"}))
+            .example("Select all", json!({"key": "Control+A"})),
+            MethodInfo::new(
+                "browser.keyboard_layout",
+                "Set the keyboard layout used for fill/press key events",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "layout",
+                        SchemaBuilder::string()
+                            .enum_values(&["us", "uk", "de", "fr"])
+                            .description("Layout whose physical keys and keyCodes are emulated"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["layout"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("layout", SchemaBuilder::string())
+                    .build(),
+            )
+            .example("Type on a German keyboard", json!({"layout": "de"}))
+            .example("AZERTY", json!({"layout": "fr"})),
+            MethodInfo::new(
+                "browser.popup_policy",
+                "Set what happens to tabs and windows the page opens",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "policy",
+                        SchemaBuilder::string()
+                            .enum_values(&["allow", "same_tab", "attach", "block"])
+                            .description(
+                                "allow: open as Chrome does; same_tab: open in this tab; attach: adopt each as a session (<session>-popup-N); block: don't open",
+                            ),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["policy"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("policy", SchemaBuilder::string())
+                    .build(),
+            )
+            .example("Keep target=_blank links in this tab", json!({"policy": "same_tab"}))
+            .example("Drive popups as their own sessions", json!({"policy": "attach"}))
+            .example("No popups", json!({"policy": "block"})),
+            MethodInfo::new(
+                "browser.set_zoom",
+                "Zoom the page like the browser's zoom controls, reflowing the layout",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "factor",
+                        SchemaBuilder::number().description(
+                            "Zoom factor, 0.25 to 5: 2 for 200%, 0.5 for 50%, 1 to reset",
+                        ),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["factor"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("factor", SchemaBuilder::number())
+                    .property(
+                        "viewport_width",
+                        SchemaBuilder::integer()
+                            .description("CSS viewport width the page lays out in"),
+                    )
+                    .property("viewport_height", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example("Check reflow at 200%", json!({"factor": 2}))
+            .example("Back to 100%", json!({"factor": 1})),
+            MethodInfo::new(
+                "browser.set_timeouts",
+                "Set the session's time limits on navigations, element actions and snapshots",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "navigation_ms",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .maximum(600000)
+                            .description("Limit on open, go_back, go_forward and reload"),
+                    )
+                    .property(
+                        "action_ms",
+                        SchemaBuilder::integer().minimum(1).maximum(600000).description(
+                            "Limit on click, fill, select, check, hover and upload",
+                        ),
+                    )
+                    .property(
+                        "snapshot_ms",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .maximum(600000)
+                            .description("Limit on snapshot"),
+                    )
+                    .property(
+                        "reset",
+                        SchemaBuilder::boolean().default_value(json!(false)).description(
+                            "Start from the daemon's limits instead of the session's",
+                        ),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("navigation_ms", SchemaBuilder::integer())
+                    .property("action_ms", SchemaBuilder::integer())
+                    .property("snapshot_ms", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example("Slow intranet", json!({"navigation_ms": 120000}))
+            .example(
+                "Fail fast while scraping",
+                json!({"navigation_ms": 10000, "action_ms": 2000, "snapshot_ms": 5000}),
+            )
+            .example("Current limits", json!({}))
+            .example("Back to the daemon's limits", json!({"reset": true})),
+            MethodInfo::new(
+                "browser.set_fake_clock",
+                "Run the page clock (Date) from a given time, on this and later pages",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "start_time",
+                        SchemaBuilder::string().description(
+                            "ISO 8601 time (without an offset: in the page's timezone) or \
+                             epoch milliseconds",
+                        ),
+                    )
+                    .property(
+                        "tick_mode",
+                        SchemaBuilder::string()
+                            .enum_values(&["realtime", "frozen"])
+                            .default_value(json!("realtime"))
+                            .description("Run at normal speed from start_time, or stay at it"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["start_time"])
+                    .build(),
+            )
+            .returns(fake_clock_returns().build())
+            .example(
+                "Five minutes before a token expires",
+                json!({"start_time": "2024-03-15T09:55:00Z"}),
+            )
+            .example(
+                "Freeze at local midnight",
+                json!({"start_time": "2024-12-31T23:59:59", "tick_mode": "frozen"}),
+            ),
+            MethodInfo::new(
+                "browser.fast_forward",
+                "Move the fake clock ahead, running the page's timers that fall due",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .description("Milliseconds to skip"),
+                    )
+                    .property(
+                        "max_timers",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .default_value(json!(DEFAULT_FAST_FORWARD_MAX_TIMERS))
+                            .description("Stop after this many timer callbacks"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["ms"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "success",
+                        SchemaBuilder::boolean()
+                            .description("False when max_timers stopped it short"),
+                    )
+                    .property("advanced_ms", SchemaBuilder::integer())
+                    .property("timers_fired", SchemaBuilder::integer())
+                    .property("timers_pending", SchemaBuilder::integer())
+                    .property("clock", fake_clock_returns())
+                    .build(),
+            )
+            .example("Skip a 10 second redirect countdown", json!({"ms": 10000}))
+            .errors(&["NO_FAKE_CLOCK"]),
+            MethodInfo::new(
+                "browser.clear_fake_clock",
+                "Put the real clock back after set_fake_clock",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(fake_clock_returns().build()),
+            MethodInfo::new("browser.select", "Select an option from a dropdown")
+                .schema(
+                    SchemaBuilder::object()
                         .property(
-                            "wait_until",
+                            "selector",
                             SchemaBuilder::string()
-                                .enum_values(&["load", "domcontentloaded", "networkidle"])
-                                .default_value(json!("load"))
-                                .description("When to consider navigation complete"),
+                                .description("@eN ref, CSS selector or XPath for <select> element"),
+                        )
+                        .property(
+                            "value",
+                            SchemaBuilder::string().description("Option value to select"),
                         )
+                        .property("dry_run", dry_run_param())
+                        .property("rank", rank_param())
+                        .property("recover", recover_param())
+                        .property("timeout_ms", timeout_param())
                         .property("session_id", session_param())
-                        .required(&["url"])
+                        .required(&["selector", "value"])
                         .build(),
                 )
                 .returns(
                     SchemaBuilder::object()
-                        .property("url", SchemaBuilder::string().format("uri"))
-                        .property("title", SchemaBuilder::string())
-                        .property("load_time_ms", SchemaBuilder::number())
+                        .property("selected", SchemaBuilder::boolean())
+                        .property("value", SchemaBuilder::string())
                         .build(),
                 )
-                .example("Navigate to Google", json!({"url": "https://google.com"}))
                 .example(
-                    "Wait for network idle",
-                    json!({"url": "https://example.com", "wait_until": "networkidle"}),
+                    "Select option",
+                    json!({"selector": "@e10", "value": "option2"}),
                 )
-                .errors(&["NAVIGATION_FAILED", "TIMEOUT"]),
+                .errors(&["ELEMENT_NOT_FOUND", "OPTION_NOT_FOUND", "TIMEOUT"]),
+            MethodInfo::new(
+                "browser.select_option",
+                "Select options of a <select> by value, label, or index",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref, CSS selector or XPath for <select> element"),
+                    )
+                    .property(
+                        "value",
+                        SchemaBuilder::string().description(
+                            "Option value attribute, or a list of them for a multiple select",
+                        ),
+                    )
+                    .property(
+                        "label",
+                        SchemaBuilder::string().description(
+                            "Option text, exact or ignoring case and spacing, or a list of them",
+                        ),
+                    )
+                    .property(
+                        "index",
+                        SchemaBuilder::integer()
+                            .description("0-based option position, or a list of them"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("selector", SchemaBuilder::string())
+                    .property("multiple", SchemaBuilder::boolean())
+                    .property(
+                        "selected",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("index", SchemaBuilder::integer())
+                                .property("value", SchemaBuilder::string())
+                                .property("label", SchemaBuilder::string()),
+                        ),
+                    )
+                    .build(),
+            )
+            .example(
+                "Select by label",
+                json!({"selector": "@e10", "label": "United Kingdom"}),
+            )
+            .example(
+                "Select several",
+                json!({"selector": "#toppings", "value": ["cheese", "olives"]}),
+            )
+            .example("Select the third option", json!({"selector": "@e10", "index": 2}))
+            .errors(&["ELEMENT_NOT_FOUND", "OPTION_NOT_FOUND"]),
+            MethodInfo::new("browser.check", "Set checkbox or radio button state")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "selector",
+                            SchemaBuilder::string().description("@eN ref, CSS selector or XPath"),
+                        )
+                        .property(
+                            "checked",
+                            SchemaBuilder::boolean()
+                                .default_value(json!(true))
+                                .description("Desired checked state"),
+                        )
+                        .property("dry_run", dry_run_param())
+                        .property("rank", rank_param())
+                        .property("recover", recover_param())
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["selector"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("checked", SchemaBuilder::boolean())
+                        .build(),
+                )
+                .example("Check checkbox", json!({"selector": "@e8"}))
+                .example("Uncheck", json!({"selector": "@e8", "checked": false}))
+                .errors(&["TIMEOUT"]),
+            MethodInfo::new("browser.hover", "Hover over an element")
+                .schema(
+                    SchemaBuilder::object()
+                        .property(
+                            "selector",
+                            SchemaBuilder::string().description("@eN ref, CSS selector or XPath"),
+                        )
+                        .property("dry_run", dry_run_param())
+                        .property("rank", rank_param())
+                        .property("recover", recover_param())
+                        .property("timeout_ms", timeout_param())
+                        .property("session_id", session_param())
+                        .required(&["selector"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("success", SchemaBuilder::boolean())
+                        .property("selector", SchemaBuilder::string())
+                        .build(),
+                )
+                .example("Hover over menu", json!({"selector": "@e12"}))
+                .errors(&["ELEMENT_NOT_FOUND", "TIMEOUT"]),
+            MethodInfo::new(
+                "browser.drag_and_drop",
+                "Drag one element onto another with mouse events (kanban boards, sortable lists)",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "source",
+                        SchemaBuilder::string().description("@eN ref, CSS selector or XPath to drag"),
+                    )
+                    .property(
+                        "target",
+                        SchemaBuilder::string().description("@eN ref, CSS selector or XPath to drop on"),
+                    )
+                    .property(
+                        "steps",
+                        SchemaBuilder::integer()
+                            .default_value(json!(DEFAULT_DRAG_STEPS))
+                            .description("Pointer moves between source and target (1-100)"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["source", "target"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("source", SchemaBuilder::string())
+                    .property("target", SchemaBuilder::string())
+                    .property("steps", SchemaBuilder::integer())
+                    .property(
+                        "native",
+                        SchemaBuilder::boolean()
+                            .description("HTML5 drag and drop, finished with CDP drag events"),
+                    )
+                    .property("duration_ms", SchemaBuilder::integer())
+                    .build(),
+            )
+            .example(
+                "Move a card to another column",
+                json!({"source": "@e14", "target": "@e31"}),
+            )
+            .errors(&["ELEMENT_NOT_FOUND"]),
             MethodInfo::new(
-                "browser.snapshot",
-                "Get ARIA accessibility tree with @eN refs for element targeting",
+                "browser.set_date",
+                "Set a date field: native date inputs or JS date pickers (typed or via the calendar)",
             )
             .schema(
                 SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref, CSS selector or XPath of the input or picker"),
+                    )
+                    .property(
+                        "date",
+                        SchemaBuilder::string()
+                            .description("YYYY-MM-DD (or YYYY-MM-DDTHH:MM for datetime-local)"),
+                    )
+                    .property(
+                        "format",
+                        SchemaBuilder::string().description(
+                            "Format to type the date in (DD/MM/YYYY or strftime); \
+                             default from the placeholder, else ISO",
+                        ),
+                    )
+                    .property("timeout_ms", timeout_param())
                     .property("session_id", session_param())
+                    .required(&["selector", "date"])
                     .build(),
             )
             .returns(
                 SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("date", SchemaBuilder::string())
                     .property(
-                        "snapshot",
+                        "strategy",
+                        SchemaBuilder::string().enum_values(&["native", "typed", "calendar"]),
+                    )
+                    .property("format", SchemaBuilder::string())
+                    .property("value", SchemaBuilder::string().description("Field value afterwards"))
+                    .build(),
+            )
+            .example(
+                "Pick a check-in date",
+                json!({"selector": "@e9", "date": "2024-03-15"}),
+            )
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.nearest",
+                "Snapshot elements nearest to an anchor element, optionally on one side of it",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "anchor",
+                        SchemaBuilder::string().description("@eN ref or CSS selector to measure from"),
+                    )
+                    .property(
+                        "text",
                         SchemaBuilder::string()
-                            .description("ARIA tree with @eN refs for clicking/filling"),
+                            .description("Measure from the innermost element containing this text"),
                     )
-                    .property("url", SchemaBuilder::string().format("uri"))
-                    .property("title", SchemaBuilder::string())
+                    .property(
+                        "direction",
+                        SchemaBuilder::string()
+                            .enum_values(&[
+                                "any",
+                                "left",
+                                "right",
+                                "above",
+                                "below",
+                                "same_row",
+                                "same_column",
+                            ])
+                            .default_value(json!("any")),
+                    )
+                    .property("role", spatial_role_param())
+                    .property("name", spatial_name_param())
+                    .property(
+                        "limit",
+                        SchemaBuilder::integer().default_value(json!(DEFAULT_NEAREST_LIMIT)),
+                    )
+                    .property("session_id", session_param())
                     .build(),
             )
-            .example("Get page snapshot", json!({})),
+            .returns(spatial_returns())
+            .example(
+                "Edit button in the same row as an invoice",
+                json!({"text": "Invoice #123", "direction": "same_row", "role": "button", "name": "Edit"}),
+            )
+            .example(
+                "Input right of a label",
+                json!({"anchor": "@e7", "direction": "right", "role": "textbox", "limit": 1}),
+            ),
             MethodInfo::new(
-                "browser.screenshot",
-                "Capture screenshot as base64 or save to file",
+                "browser.within",
+                "Snapshot elements inside a rectangle of the page",
             )
             .schema(
                 SchemaBuilder::object()
+                    .property("x", SchemaBuilder::number().description("Document CSS pixels"))
+                    .property("y", SchemaBuilder::number().description("Document CSS pixels"))
+                    .property("width", SchemaBuilder::number())
+                    .property("height", SchemaBuilder::number())
                     .property(
-                        "path",
+                        "partial",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Include elements that only overlap the rectangle"),
+                    )
+                    .property("role", spatial_role_param())
+                    .property("name", spatial_name_param())
+                    .property(
+                        "limit",
+                        SchemaBuilder::integer().default_value(json!(DEFAULT_WITHIN_LIMIT)),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["x", "y", "width", "height"])
+                    .build(),
+            )
+            .returns(spatial_returns())
+            .example(
+                "Links in the page header",
+                json!({"x": 0, "y": 0, "width": 1280, "height": 80, "role": "link"}),
+            ),
+            MethodInfo::new(
+                "browser.find_elements",
+                "Snapshot elements matching a role, accessible name or text, without the whole tree",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("role", spatial_role_param())
+                    .property("name", spatial_name_param())
+                    .property(
+                        "text",
                         SchemaBuilder::string().description(
-                            "File path to save screenshot (optional, returns base64 if omitted)",
+                            "Only the innermost elements whose rendered text contains this (case-insensitive)",
                         ),
                     )
                     .property(
-                        "full_page",
+                        "exact",
                         SchemaBuilder::boolean()
                             .default_value(json!(false))
-                            .description("Capture full scrollable page"),
+                            .description("Match 'name' and 'text' whole instead of as substrings"),
+                    )
+                    .property(
+                        "limit",
+                        SchemaBuilder::integer().default_value(json!(DEFAULT_FIND_LIMIT)),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string())
+                    .property(
+                        "matches",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::object())
+                            .description("Matching snapshot nodes in document order, without children"),
+                    )
+                    .property(
+                        "total",
+                        SchemaBuilder::integer().description("Matches before 'limit' was applied"),
+                    )
+                    .build(),
+            )
+            .example(
+                "Submit buttons",
+                json!({"role": "button", "name": "submit"}),
+            )
+            .example(
+                "Element showing an error message",
+                json!({"text": "Invalid email address"}),
+            ),
+            MethodInfo::new(
+                "browser.summarize_results",
+                "Title, URL and snippet of each result card on a search-result page, with refs",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "container",
+                        SchemaBuilder::string()
+                            .description("Only results inside this element (@eN ref or CSS selector)"),
+                    )
+                    .property(
+                        "limit",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .default_value(json!(DEFAULT_RESULTS_LIMIT)),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("url", SchemaBuilder::string())
+                    .property(
+                        "query",
+                        SchemaBuilder::string().description("Text in the page's search box"),
+                    )
+                    .property(
+                        "results",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("position", SchemaBuilder::integer())
+                                .property("title", SchemaBuilder::string())
+                                .property(
+                                    "url",
+                                    SchemaBuilder::string()
+                                        .description("Destination, tracking redirects unwrapped"),
+                                )
+                                .property("snippet", SchemaBuilder::string())
+                                .property(
+                                    "ref_id",
+                                    SchemaBuilder::string().description("Ref of the title link"),
+                                ),
+                        ),
+                    )
+                    .property(
+                        "total",
+                        SchemaBuilder::integer().description("Results before 'limit' was applied"),
+                    )
+                    .build(),
+            )
+            .example("Top five results", json!({"limit": 5}))
+            .example("Organic results only", json!({"container": "#search"})),
+            MethodInfo::new(
+                "browser.table_cell",
+                "Locate a table cell by row text and column header",
+            )
+            .schema(table_params().build())
+            .returns(table_cell_returns())
+            .example(
+                "Actions cell of the Acme row",
+                json!({"row_contains": "Acme", "column": "Actions"}),
+            ),
+            MethodInfo::new(
+                "browser.click_cell",
+                "Click the control in a table cell (or the cell itself)",
+            )
+            .schema(
+                table_params()
+                    .property("timeout_ms", timeout_param())
+                    .build(),
+            )
+            .returns(table_cell_returns())
+            .example(
+                "Delete button of the Acme row",
+                json!({"table": "@e12", "row_contains": "Acme", "column": "Actions", "target": "Delete"}),
+            ),
+            MethodInfo::new(
+                "browser.describe_form",
+                "List a form's fields with their labels, types, values, constraints and options",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "form",
+                        SchemaBuilder::string().description(
+                            "@eN ref or CSS selector of the form or its container (default: first form)",
+                        ),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("selector", SchemaBuilder::string())
+                    .property("action", SchemaBuilder::string().format("uri"))
+                    .property("method", SchemaBuilder::string())
+                    .property(
+                        "fields",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property("name", SchemaBuilder::string())
+                                    .property(
+                                        "type",
+                                        SchemaBuilder::string().description(
+                                            "Input type, select, select-multiple or textarea",
+                                        ),
+                                    )
+                                    .property("label", SchemaBuilder::string())
+                                    .property(
+                                        "selector",
+                                        SchemaBuilder::string()
+                                            .description("Ref or CSS selector to fill it with"),
+                                    )
+                                    .property("required", SchemaBuilder::boolean())
+                                    .property("disabled", SchemaBuilder::boolean())
+                                    .property("readonly", SchemaBuilder::boolean())
+                                    .property("value", SchemaBuilder::string())
+                                    .property("checked", SchemaBuilder::boolean())
+                                    .property("placeholder", SchemaBuilder::string())
+                                    .property("autocomplete", SchemaBuilder::string())
+                                    .property(
+                                        "constraints",
+                                        SchemaBuilder::object().description(
+                                            "pattern, min, max, minlength, maxlength, step, accept",
+                                        ),
+                                    )
+                                    .property(
+                                        "options",
+                                        SchemaBuilder::array()
+                                            .items(
+                                                SchemaBuilder::object()
+                                                    .property("value", SchemaBuilder::string())
+                                                    .property("label", SchemaBuilder::string())
+                                                    .property("selected", SchemaBuilder::boolean())
+                                                    .property("disabled", SchemaBuilder::boolean())
+                                                    .property("selector", SchemaBuilder::string()),
+                                            )
+                                            .description("Select options, or a radio group's radios"),
+                                    )
+                                    .property("valid", SchemaBuilder::boolean())
+                                    .property("validation_message", SchemaBuilder::string()),
+                            )
+                            .description("In document order; radios sharing a name are one field"),
+                    )
+                    .property(
+                        "submit",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("label", SchemaBuilder::string())
+                                .property("selector", SchemaBuilder::string()),
+                        ),
+                    )
+                    .build(),
+            )
+            .example("First form on the page", json!({}))
+            .example("A checkout form by ref", json!({"form": "@e31"})),
+            MethodInfo::new(
+                "browser.autofill_form",
+                "Fill a form from a key/value map, matching keys to fields by name, autocomplete or label",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "form",
+                        SchemaBuilder::string().description(
+                            "@eN ref or CSS selector of the form or its container (default: first form)",
+                        ),
+                    )
+                    .property(
+                        "data",
+                        SchemaBuilder::object().description(
+                            "Values by field name, autocomplete token or label; booleans for checkboxes, option values or labels for selects and radios, lists for multi-selects and file inputs",
+                        ),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["data"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("selector", SchemaBuilder::string())
+                    .property(
+                        "filled",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property("key", SchemaBuilder::string())
+                                    .property("selector", SchemaBuilder::string())
+                                    .property("name", SchemaBuilder::string())
+                                    .property("label", SchemaBuilder::string())
+                                    .property("value", SchemaBuilder::string()),
+                            )
+                            .description("Fields set, in document order"),
+                    )
+                    .property(
+                        "failed",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property("key", SchemaBuilder::string())
+                                    .property("selector", SchemaBuilder::string())
+                                    .property("error", SchemaBuilder::string()),
+                            )
+                            .description("Keys matched to a field that couldn't take the value"),
+                    )
+                    .property(
+                        "unmatched",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Keys no field matched"),
+                    )
+                    .property(
+                        "unfilled_required",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Required fields still empty that no key matched"),
+                    )
+                    .build(),
+            )
+            .example(
+                "Sign-up form",
+                json!({"data": {"email": "ada@example.com", "given-name": "Ada", "country": "United Kingdom", "terms": true}}),
+            ),
+            MethodInfo::new(
+                "browser.get_validation_errors",
+                "List the validation errors showing on a form, by field",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "form",
+                        SchemaBuilder::string().description(
+                            "@eN ref or CSS selector of the form or its container (default: first form)",
+                        ),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("selector", SchemaBuilder::string())
+                    .property("valid", SchemaBuilder::boolean())
+                    .property(
+                        "fields",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property(
+                                        "selector",
+                                        SchemaBuilder::string()
+                                            .description("Ref or CSS selector of the field"),
+                                    )
+                                    .property("name", SchemaBuilder::string())
+                                    .property("label", SchemaBuilder::string())
+                                    .property(
+                                        "messages",
+                                        SchemaBuilder::array().items(SchemaBuilder::string()),
+                                    )
+                                    .property(
+                                        "sources",
+                                        SchemaBuilder::array()
+                                            .items(SchemaBuilder::string().enum_values(&[
+                                                "aria-invalid",
+                                                "invalid",
+                                                "aria-errormessage",
+                                                "error-text",
+                                            ]))
+                                            .description("How the field was flagged"),
+                                    ),
+                            )
+                            .description("Fields in error"),
+                    )
+                    .property(
+                        "form_errors",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Error text not tied to a single field"),
+                    )
+                    .build(),
+            )
+            .example("After a rejected submit", json!({}))
+            .example("A checkout form by ref", json!({"form": "@e31"})),
+            MethodInfo::new(
+                "browser.highlight",
+                "Outline an element with a label for headful runs and recordings",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description("@eN ref, CSS selector or XPath"),
+                    )
+                    .property(
+                        "label",
+                        SchemaBuilder::string().description("Text shown above the outline"),
+                    )
+                    .property(
+                        "duration_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(3000))
+                            .description("How long the outline stays (0 = until the next highlight)"),
+                    )
+                    .property(
+                        "color",
+                        SchemaBuilder::string()
+                            .default_value(json!("#ff3366"))
+                            .description("CSS color of the outline and label"),
+                    )
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("element", SchemaBuilder::string())
+                    .property(
+                        "rect",
+                        SchemaBuilder::object()
+                            .property("x", SchemaBuilder::number())
+                            .property("y", SchemaBuilder::number())
+                            .property("width", SchemaBuilder::number())
+                            .property("height", SchemaBuilder::number()),
+                    )
+                    .build(),
+            )
+            .example(
+                "Show what the agent is about to click",
+                json!({"selector": "@e5", "label": "click: Submit"}),
+            ),
+            MethodInfo::new(
+                "browser.cursor_overlay",
+                "Render a synthetic cursor and click ripples at interaction points",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "enabled",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(true))
+                            .description("Show (true) or hide (false) the cursor for this session"),
                     )
                     .property("session_id", session_param())
                     .build(),
             )
             .returns(
                 SchemaBuilder::object()
-                    .property(
-                        "base64",
-                        SchemaBuilder::string()
-                            .description("Base64-encoded PNG (if no path specified)"),
-                    )
-                    .property(
-                        "path",
-                        SchemaBuilder::string()
-                            .description("Saved file path (if path was specified)"),
-                    )
-                    .property("width", SchemaBuilder::integer())
-                    .property("height", SchemaBuilder::integer())
+                    .property("success", SchemaBuilder::boolean())
+                    .property("enabled", SchemaBuilder::boolean())
                     .build(),
             )
-            .example("Get base64 screenshot", json!({}))
-            .example(
-                "Save to file",
-                json!({"path": "/tmp/screenshot.png", "full_page": true}),
-            ),
-            // ================================================================
-            // Interaction
-            // ================================================================
-            MethodInfo::new("browser.click", "Click element by @eN ref or CSS selector")
-                .schema(
-                    SchemaBuilder::object()
-                        .property(
-                            "selector",
-                            SchemaBuilder::string()
-                                .description("@eN ref from snapshot or CSS selector"),
-                        )
-                        .property(
-                            "button",
-                            SchemaBuilder::string()
-                                .enum_values(&["left", "right", "middle"])
-                                .default_value(json!("left")),
-                        )
-                        .property(
-                            "click_count",
-                            SchemaBuilder::integer()
-                                .minimum(1)
-                                .maximum(3)
-                                .default_value(json!(1))
-                                .description("1=click, 2=double-click, 3=triple-click"),
-                        )
-                        .property("session_id", session_param())
-                        .required(&["selector"])
-                        .build(),
-                )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("clicked", SchemaBuilder::boolean())
-                        .property("selector", SchemaBuilder::string())
-                        .build(),
-                )
-                .example("Click by ref", json!({"selector": "@e15"}))
-                .example(
-                    "Double-click",
-                    json!({"selector": "@e20", "click_count": 2}),
-                )
-                .errors(&["ELEMENT_NOT_FOUND", "ELEMENT_NOT_VISIBLE"]),
-            MethodInfo::new("browser.fill", "Fill input field with value")
-                .schema(
-                    SchemaBuilder::object()
-                        .property(
-                            "selector",
-                            SchemaBuilder::string()
-                                .description("@eN ref from snapshot or CSS selector"),
-                        )
-                        .property("value", SchemaBuilder::string().description("Text to fill"))
-                        .property(
-                            "clear",
-                            SchemaBuilder::boolean()
-                                .default_value(json!(true))
-                                .description("Clear existing content before filling"),
-                        )
-                        .property("session_id", session_param())
-                        .required(&["selector", "value"])
-                        .build(),
-                )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("filled", SchemaBuilder::boolean())
-                        .property("selector", SchemaBuilder::string())
-                        .build(),
-                )
-                .example(
-                    "Fill search box",
-                    json!({"selector": "@e5", "value": "search query"}),
-                )
-                .errors(&["ELEMENT_NOT_FOUND", "ELEMENT_NOT_EDITABLE"]),
-            MethodInfo::new("browser.press", "Press a keyboard key")
-                .schema(
-                    SchemaBuilder::object()
-                        .property(
-                            "key",
-                            SchemaBuilder::string()
-                                .description("Key name: Enter, Tab, Escape, ArrowDown, etc."),
-                        )
-                        .property("session_id", session_param())
-                        .required(&["key"])
-                        .build(),
-                )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("pressed", SchemaBuilder::boolean())
-                        .property("key", SchemaBuilder::string())
-                        .build(),
-                )
-                .example("Press Enter", json!({"key": "Enter"}))
-                .example("Press Escape", json!({"key": "Escape"})),
-            MethodInfo::new("browser.select", "Select an option from a dropdown")
-                .schema(
-                    SchemaBuilder::object()
-                        .property(
-                            "selector",
-                            SchemaBuilder::string()
-                                .description("@eN ref or CSS selector for <select> element"),
-                        )
-                        .property(
-                            "value",
-                            SchemaBuilder::string().description("Option value to select"),
-                        )
-                        .property("session_id", session_param())
-                        .required(&["selector", "value"])
-                        .build(),
-                )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("selected", SchemaBuilder::boolean())
-                        .property("value", SchemaBuilder::string())
-                        .build(),
-                )
-                .example(
-                    "Select option",
-                    json!({"selector": "@e10", "value": "option2"}),
-                )
-                .errors(&["ELEMENT_NOT_FOUND", "OPTION_NOT_FOUND"]),
-            MethodInfo::new("browser.check", "Set checkbox or radio button state")
-                .schema(
-                    SchemaBuilder::object()
-                        .property(
-                            "selector",
-                            SchemaBuilder::string().description("@eN ref or CSS selector"),
-                        )
-                        .property(
-                            "checked",
-                            SchemaBuilder::boolean()
-                                .default_value(json!(true))
-                                .description("Desired checked state"),
-                        )
-                        .property("session_id", session_param())
-                        .required(&["selector"])
-                        .build(),
-                )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("checked", SchemaBuilder::boolean())
-                        .build(),
-                )
-                .example("Check checkbox", json!({"selector": "@e8"}))
-                .example("Uncheck", json!({"selector": "@e8", "checked": false})),
-            MethodInfo::new("browser.hover", "Hover over an element")
-                .schema(
-                    SchemaBuilder::object()
-                        .property(
-                            "selector",
-                            SchemaBuilder::string().description("@eN ref or CSS selector"),
-                        )
-                        .property("session_id", session_param())
-                        .required(&["selector"])
-                        .build(),
-                )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("hovered", SchemaBuilder::boolean())
-                        .build(),
-                )
-                .example("Hover over menu", json!({"selector": "@e12"}))
-                .errors(&["ELEMENT_NOT_FOUND"]),
+            .example("Show cursor for a recording", json!({}))
+            .example("Hide cursor", json!({"enabled": false})),
             MethodInfo::new("browser.scroll", "Scroll page or element")
                 .schema(
                     SchemaBuilder::object()
@@ -1070,16 +8252,185 @@ impl FgpService for BrowserService {
                                 .default_value(json!(500))
                                 .description("Pixels to scroll (if using direction)"),
                         )
+                        .property("timeout_ms", timeout_param())
                         .property("session_id", session_param())
                         .build(),
                 )
-                .returns(
-                    SchemaBuilder::object()
-                        .property("scrolled", SchemaBuilder::boolean())
-                        .build(),
-                )
+                .returns(scroll_returns())
                 .example("Scroll to element", json!({"selector": "@e50"}))
                 .example("Scroll down", json!({"direction": "down", "amount": 1000})),
+            MethodInfo::new(
+                "browser.scroll_to_element",
+                "Scroll an element into view and report whether it is visible",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string().description("@eN ref, CSS selector or XPath"),
+                    )
+                    .property(
+                        "block",
+                        SchemaBuilder::string()
+                            .enum_values(&["start", "center", "end", "nearest"])
+                            .default_value(json!("center"))
+                            .description("Where the element ends up vertically"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["selector"])
+                    .build(),
+            )
+            .returns(scroll_returns())
+            .example("Bring a row into view", json!({"selector": "@e50"}))
+            .errors(&["ELEMENT_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.scroll_by",
+                "Scroll the window or a scroll container by pixels",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("x", SchemaBuilder::number().default_value(json!(0)))
+                    .property(
+                        "y",
+                        SchemaBuilder::number()
+                            .default_value(json!(0))
+                            .description("Pixels down (negative scrolls up)"),
+                    )
+                    .property(
+                        "container",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector of a scroll container (default: the window)"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(scroll_returns())
+            .example("Page down", json!({"y": 800})),
+            MethodInfo::new(
+                "browser.scroll_to_bottom",
+                "Scroll to the bottom repeatedly until lazy-loaded content stops appearing",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "container",
+                        SchemaBuilder::string()
+                            .description("@eN ref or CSS selector of a scroll container (default: the window)"),
+                    )
+                    .property(
+                        "max_rounds",
+                        SchemaBuilder::integer()
+                            .default_value(json!(DEFAULT_SCROLL_ROUNDS))
+                            .description("Most scroll-and-wait rounds (infinite feeds never end)"),
+                    )
+                    .property(
+                        "settle_ms",
+                        SchemaBuilder::integer()
+                            .default_value(json!(DEFAULT_SCROLL_SETTLE_MS))
+                            .description("Wait after each round for new content"),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(scroll_returns())
+            .example("Load a whole feed", json!({"max_rounds": 20})),
+            MethodInfo::new(
+                "browser.restore_scroll",
+                "Restore page and container scroll positions",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "scroll",
+                        SchemaBuilder::object()
+                            .property("x", SchemaBuilder::number())
+                            .property("y", SchemaBuilder::number())
+                            .property(
+                                "containers",
+                                SchemaBuilder::array().items(
+                                    SchemaBuilder::object()
+                                        .property("selector", SchemaBuilder::string())
+                                        .property("x", SchemaBuilder::number())
+                                        .property("y", SchemaBuilder::number()),
+                                ),
+                            )
+                            .description(
+                                "Scroll state from a snapshot; defaults to the last position recorded for the current URL",
+                            ),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property(
+                        "scroll",
+                        SchemaBuilder::object()
+                            .property("x", SchemaBuilder::number())
+                            .property("y", SchemaBuilder::number()),
+                    )
+                    .property(
+                        "missing",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Container selectors that no longer match"),
+                    )
+                    .build(),
+            )
+            .example("Restore after reload", json!({}))
+            .example(
+                "Restore an explicit position",
+                json!({"scroll": {"x": 0, "y": 1200, "containers": [{"selector": "#feed", "x": 0, "y": 800}]}}),
+            ),
+            MethodInfo::new(
+                "browser.wait_for_stable",
+                "Wait until network, layout and DOM have all been quiet",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "quiet_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(DEFAULT_STABLE_QUIET_MS))
+                            .description("How long every signal must stay quiet"),
+                    )
+                    .property(
+                        "timeout_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(DEFAULT_STABLE_TIMEOUT_MS))
+                            .description("Give up and report stable=false after this long"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("stable", SchemaBuilder::boolean())
+                    .property("waited_ms", SchemaBuilder::integer())
+                    .property("inflight_requests", SchemaBuilder::integer())
+                    .property(
+                        "pending",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::string()
+                                    .enum_values(&["load", "network", "layout", "dom"]),
+                            )
+                            .description("Signals still busy at timeout"),
+                    )
+                    .build(),
+            )
+            .example("Wait after a click", json!({}))
+            .example(
+                "Stricter settle window",
+                json!({"quiet_ms": 1000, "timeout_ms": 30000}),
+            ),
             MethodInfo::new(
                 "browser.press_combo",
                 "Press key with modifiers (Ctrl, Shift, Alt, Meta)",
@@ -1099,6 +8450,7 @@ impl FgpService for BrowserService {
                             )
                             .description("Modifier keys to hold"),
                     )
+                    .property("timeout_ms", timeout_param())
                     .property("session_id", session_param())
                     .required(&["key", "modifiers"])
                     .build(),
@@ -1122,12 +8474,16 @@ impl FgpService for BrowserService {
                         .property(
                             "selector",
                             SchemaBuilder::string()
-                                .description("@eN ref or CSS selector for file input"),
+                                .description("@eN ref, CSS selector or XPath for file input"),
                         )
                         .property(
                             "path",
                             SchemaBuilder::string().description("Absolute path to file to upload"),
                         )
+                        .property("dry_run", dry_run_param())
+                        .property("rank", rank_param())
+                        .property("recover", recover_param())
+                        .property("timeout_ms", timeout_param())
                         .property("session_id", session_param())
                         .required(&["selector", "path"])
                         .build(),
@@ -1142,7 +8498,60 @@ impl FgpService for BrowserService {
                     "Upload file",
                     json!({"selector": "@e30", "path": "/tmp/document.pdf"}),
                 )
-                .errors(&["ELEMENT_NOT_FOUND", "FILE_NOT_FOUND"]),
+                .errors(&["ELEMENT_NOT_FOUND", "FILE_NOT_FOUND", "TIMEOUT"]),
+            MethodInfo::new(
+                "browser.upload_file",
+                "Set the files of a file input and report what it accepted",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "selector",
+                        SchemaBuilder::string()
+                            .description("@eN ref, CSS selector or XPath for file input"),
+                    )
+                    .property(
+                        "paths",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description(
+                                "Files to set, replacing any chosen before (relative to the \
+                                 daemon's working directory; more than one needs a multiple \
+                                 input; empty clears the input)",
+                            ),
+                    )
+                    .property("timeout_ms", timeout_param())
+                    .property("session_id", session_param())
+                    .required(&["selector", "paths"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("success", SchemaBuilder::boolean())
+                    .property("selector", SchemaBuilder::string())
+                    .property(
+                        "files",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("name", SchemaBuilder::string())
+                                .property("size", SchemaBuilder::integer())
+                                .property("mime_type", SchemaBuilder::string()),
+                        ),
+                    )
+                    .property(
+                        "not_accepted",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Files outside the input's accept filter"),
+                    )
+                    .build(),
+            )
+            .example(
+                "Attach two files",
+                json!({"selector": "@e30", "paths": ["/tmp/invoice.pdf", "/tmp/receipt.png"]}),
+            )
+            .example("Clear the input", json!({"selector": "@e30", "paths": []}))
+            .errors(&["ELEMENT_NOT_FOUND", "FILE_NOT_FOUND"]),
             // ================================================================
             // Auth State Management
             // ================================================================
@@ -1201,16 +8610,62 @@ impl FgpService for BrowserService {
                                 SchemaBuilder::object()
                                     .property("name", SchemaBuilder::string())
                                     .property(
-                                        "created_at",
-                                        SchemaBuilder::string().format("date-time"),
+                                        "domains",
+                                        SchemaBuilder::array().items(SchemaBuilder::string()),
                                     )
-                                    .property("size_bytes", SchemaBuilder::integer()),
+                                    .property(
+                                        "saved_at",
+                                        SchemaBuilder::string().format("date-time"),
+                                    ),
                             ),
                         )
                         .property("count", SchemaBuilder::integer())
                         .build(),
                 )
                 .example("List auth states", json!({})),
+            MethodInfo::new(
+                "browser.state.diff",
+                "Compare two saved auth states: added/removed/changed cookies and storage keys",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "a",
+                        SchemaBuilder::string().description("Saved state to compare from"),
+                    )
+                    .property(
+                        "b",
+                        SchemaBuilder::string().description("Saved state to compare to"),
+                    )
+                    .required(&["a", "b"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("identical", SchemaBuilder::boolean())
+                    .property(
+                        "cookies",
+                        SchemaBuilder::object()
+                            .property("added", SchemaBuilder::array())
+                            .property("removed", SchemaBuilder::array())
+                            .property("changed", SchemaBuilder::array())
+                            .description("Cookies keyed by domain, path, and name"),
+                    )
+                    .property(
+                        "local_storage",
+                        SchemaBuilder::object()
+                            .property("origin", SchemaBuilder::object())
+                            .property("added", SchemaBuilder::array())
+                            .property("removed", SchemaBuilder::array())
+                            .property("changed", SchemaBuilder::array())
+                            .description("Storage keys; values are never included"),
+                    )
+                    .build(),
+            )
+            .example(
+                "What did login persist?",
+                json!({"a": "logged-out", "b": "logged-in"}),
+            ),
             // ================================================================
             // Session Management
             // ================================================================
@@ -1243,19 +8698,109 @@ impl FgpService for BrowserService {
                             "sessions",
                             SchemaBuilder::array().items(
                                 SchemaBuilder::object()
-                                    .property("session_id", SchemaBuilder::string())
-                                    .property("name", SchemaBuilder::string())
+                                    .property("id", SchemaBuilder::string())
+                                    .property("url", SchemaBuilder::string().format("uri"))
                                     .property(
-                                        "created_at",
-                                        SchemaBuilder::string().format("date-time"),
+                                        "active",
+                                        SchemaBuilder::boolean()
+                                            .description("Whether this is the default session"),
                                     )
-                                    .property("current_url", SchemaBuilder::string().format("uri")),
+                                    .property("bandwidth", bandwidth_returns())
+                                    .property(
+                                        "opener",
+                                        SchemaBuilder::string()
+                                            .description("Session that opened this one (adopted popups)"),
+                                    ),
                             ),
                         )
                         .property("count", SchemaBuilder::integer())
                         .build(),
                 )
                 .example("List sessions", json!({})),
+            MethodInfo::new(
+                "health",
+                "Service health; with self_test, a readiness report for load balancers and probes",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "self_test",
+                        SchemaBuilder::boolean().default_value(json!(false)).description(
+                            "Launch the browser if needed, open about:blank, time a CDP round-trip, probe the headless mode's capabilities, and check artifact disk space",
+                        ),
+                    )
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("healthy", SchemaBuilder::boolean())
+                    .property("browser", SchemaBuilder::object())
+                    .property("artifact_store", SchemaBuilder::string())
+                    .property(
+                        "self_test",
+                        SchemaBuilder::object()
+                            .property("ready", SchemaBuilder::boolean())
+                            .property(
+                                "checks",
+                                SchemaBuilder::array().items(
+                                    SchemaBuilder::object()
+                                        .property("name", SchemaBuilder::string())
+                                        .property("ok", SchemaBuilder::boolean())
+                                        .property("duration_ms", SchemaBuilder::number())
+                                        .property("detail", SchemaBuilder::string()),
+                                ),
+                            )
+                            .property("artifact_free_bytes", SchemaBuilder::integer())
+                            .property(
+                                "capabilities",
+                                SchemaBuilder::object()
+                                    .property(
+                                        "mode",
+                                        SchemaBuilder::string()
+                                            .enum_values(&["old", "new", "headed"]),
+                                    )
+                                    .property("display", SchemaBuilder::string())
+                                    .property("extensions", SchemaBuilder::boolean())
+                                    .property("pdf_viewer", SchemaBuilder::boolean())
+                                    .property("print_to_pdf", SchemaBuilder::boolean())
+                                    .property("screencast", SchemaBuilder::boolean())
+                                    .property(
+                                        "missing",
+                                        SchemaBuilder::array().items(SchemaBuilder::string()),
+                                    ),
+                            ),
+                    )
+                    .build(),
+            )
+            .example("Liveness", json!({}))
+            .example("Readiness probe", json!({"self_test": true})),
+            MethodInfo::new(
+                "browser.schemas",
+                "JSON Schemas of all response models, for generating client types",
+            )
+            .schema(SchemaBuilder::object().build())
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "protocol_version",
+                        SchemaBuilder::integer().description(
+                            "Also in every object response; changes when a response changes incompatibly",
+                        ),
+                    )
+                    .property(
+                        "responses",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string())
+                            .description("Models methods return, by name"),
+                    )
+                    .property(
+                        "definitions",
+                        SchemaBuilder::object()
+                            .description("Draft-07 schema of every response model and the models they contain"),
+                    )
+                    .build(),
+            )
+            .example("Export schemas", json!({})),
             MethodInfo::new(
                 "browser.session.close",
                 "Close and dispose a browser session",
@@ -1271,12 +8816,328 @@ impl FgpService for BrowserService {
             )
             .returns(
                 SchemaBuilder::object()
-                    .property("closed", SchemaBuilder::boolean())
+                    .property("success", SchemaBuilder::boolean())
                     .property("session_id", SchemaBuilder::string())
+                    .property("bandwidth", bandwidth_returns())
+                    .property(
+                        "recording",
+                        SchemaBuilder::object()
+                            .description("The session's recording, finished on close"),
+                    )
                     .build(),
             )
             .example("Close session", json!({"session_id": "abc123"}))
             .errors(&["SESSION_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.session.events",
+                "Recent events of a session, recorded whether or not anyone was subscribed",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "since",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(0))
+                            .description("Only events with this sequence number or later"),
+                    )
+                    .property(
+                        "types",
+                        SchemaBuilder::array()
+                            .items(SchemaBuilder::string().enum_values(&[
+                                "navigation",
+                                "console",
+                                "response",
+                                "notification",
+                                "captcha",
+                                "popup",
+                            ]))
+                            .description("Only events of these types"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "events",
+                        SchemaBuilder::array()
+                            .items(
+                                SchemaBuilder::object()
+                                    .property("seq", SchemaBuilder::integer())
+                                    .property("at", SchemaBuilder::string())
+                                    .property("type", SchemaBuilder::string()),
+                            )
+                            .description("Oldest first; the session keeps its last 500"),
+                    )
+                    .property(
+                        "next",
+                        SchemaBuilder::integer()
+                            .description("Note before acting, pass as since afterwards"),
+                    )
+                    .property(
+                        "dropped",
+                        SchemaBuilder::integer().description("Older events evicted"),
+                    )
+                    .property(
+                        "missed",
+                        SchemaBuilder::integer()
+                            .description("Events lost because recording fell behind"),
+                    )
+                    .build(),
+            )
+            .example("Everything recorded", json!({}))
+            .example(
+                "Responses and console messages since an action",
+                json!({"since": 42, "types": ["response", "console"]}),
+            )
+            .errors(&["SESSION_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.session.observe",
+                "Create a read-only observer handle on a session",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "session_id",
+                        SchemaBuilder::string()
+                            .default_value(json!("default"))
+                            .description("Session to observe"),
+                    )
+                    .property(
+                        "replay",
+                        SchemaBuilder::boolean()
+                            .default_value(json!(false))
+                            .description("Start with the events the session recorded before now"),
+                    )
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property(
+                        "observer_id",
+                        SchemaBuilder::string()
+                            .description("Pass as session_id to read-only methods"),
+                    )
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "allowed_methods",
+                        SchemaBuilder::array().items(SchemaBuilder::string()),
+                    )
+                    .build(),
+            )
+            .example("Watch an agent's session", json!({"session_id": "agent-1"}))
+            .errors(&["SESSION_NOT_FOUND"]),
+            MethodInfo::new(
+                "browser.observer.events",
+                "Session events buffered for an observer",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("observer_id", SchemaBuilder::string())
+                    .property(
+                        "since",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(0))
+                            .description("Only events with this sequence number or later"),
+                    )
+                    .required(&["observer_id"])
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("observer_id", SchemaBuilder::string())
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "events",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("seq", SchemaBuilder::integer())
+                                .property("at", SchemaBuilder::string())
+                                .property(
+                                    "type",
+                                    SchemaBuilder::string()
+                                        .enum_values(&["navigation", "console", "response", "notification", "captcha", "popup"]),
+                                ),
+                        ),
+                    )
+                    .property(
+                        "next",
+                        SchemaBuilder::integer().description("Pass as since on the next poll"),
+                    )
+                    .property(
+                        "missed",
+                        SchemaBuilder::integer()
+                            .description("Events dropped because the observer fell behind"),
+                    )
+                    .build(),
+            )
+            .example("Poll for new events", json!({"observer_id": "observer-3f2a...", "since": 42}))
+            .errors(&["OBSERVER_NOT_FOUND"]),
+            MethodInfo::new("browser.observer.close", "Release an observer handle")
+                .schema(
+                    SchemaBuilder::object()
+                        .property("observer_id", SchemaBuilder::string())
+                        .required(&["observer_id"])
+                        .build(),
+                )
+                .returns(
+                    SchemaBuilder::object()
+                        .property("observer_id", SchemaBuilder::string())
+                        .property("closed", SchemaBuilder::boolean())
+                        .build(),
+                ),
+            MethodInfo::new(
+                "browser.live_view",
+                "MJPEG stream URL for watching a session live",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "url",
+                        SchemaBuilder::string()
+                            .format("uri")
+                            .description("multipart/x-mixed-replace JPEG stream"),
+                    )
+                    .property(
+                        "index_url",
+                        SchemaBuilder::string()
+                            .format("uri")
+                            .description("Page listing every session"),
+                    )
+                    .build(),
+            )
+            .example("Stream URL for a session", json!({"session_id": "agent-1"})),
+            MethodInfo::new(
+                "browser.screencast.start",
+                "Start streaming the session's screencast frames over the gateway",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "quality",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .maximum(100)
+                            .default_value(json!(60))
+                            .description(
+                                "JPEG quality. Setting this or the size fails with \
+                                 SCREENCAST_CONFLICT if the session's screencast (shared \
+                                 with recordings and live view) already runs otherwise",
+                            ),
+                    )
+                    .property(
+                        "max_width",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .default_value(json!(1280)),
+                    )
+                    .property(
+                        "max_height",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .default_value(json!(800)),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "started",
+                        SchemaBuilder::boolean()
+                            .description("False if the session was already streaming"),
+                    )
+                    .property(
+                        "idle_timeout_ms",
+                        SchemaBuilder::integer()
+                            .description("The stream stops after this long without a poll"),
+                    )
+                    .build(),
+            )
+            .example("Stream a session", json!({"session_id": "agent-1"}))
+            .errors(&["SCREENCAST_CONFLICT"]),
+            MethodInfo::new(
+                "browser.screencast.frames",
+                "Poll the session's streamed frames, waiting for the next one if asked",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property(
+                        "since",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .default_value(json!(0))
+                            .description("Only frames numbered this or later (pass the last `next`)"),
+                    )
+                    .property(
+                        "limit",
+                        SchemaBuilder::integer()
+                            .minimum(1)
+                            .maximum(30)
+                            .default_value(json!(1))
+                            .description("Newest frames to return"),
+                    )
+                    .property(
+                        "wait_ms",
+                        SchemaBuilder::integer()
+                            .minimum(0)
+                            .maximum(30000)
+                            .default_value(json!(0))
+                            .description("Wait this long for a frame if there is none yet"),
+                    )
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property(
+                        "frames",
+                        SchemaBuilder::array().items(
+                            SchemaBuilder::object()
+                                .property("seq", SchemaBuilder::integer())
+                                .property("at", SchemaBuilder::string())
+                                .property(
+                                    "data",
+                                    SchemaBuilder::string().description("Base64-encoded JPEG"),
+                                )
+                                .property("size", SchemaBuilder::integer()),
+                        ),
+                    )
+                    .property("next", SchemaBuilder::integer())
+                    .property("skipped", SchemaBuilder::integer())
+                    .property("streaming", SchemaBuilder::boolean())
+                    .build(),
+            )
+            .example(
+                "Wait for the next frame",
+                json!({"session_id": "agent-1", "since": 42, "wait_ms": 5000}),
+            ),
+            MethodInfo::new(
+                "browser.screencast.stop",
+                "Stop streaming the session's screencast frames",
+            )
+            .schema(
+                SchemaBuilder::object()
+                    .property("session_id", session_param())
+                    .build(),
+            )
+            .returns(
+                SchemaBuilder::object()
+                    .property("session_id", SchemaBuilder::string())
+                    .property("stopped", SchemaBuilder::boolean())
+                    .build(),
+            ),
             // ================================================================
             // Extension Methods (requires Chrome extension)
             // ================================================================
@@ -1456,4 +9317,25 @@ mod tests {
         let session_id = BrowserService::get_session_id(&params);
         assert_eq!(session_id, None);
     }
+
+    #[test]
+    fn test_every_method_returns_an_exported_model() {
+        let service = BrowserService::new(HeadlessMode::Old, false).unwrap();
+        let schemas = schemas::export().unwrap();
+        let responses = schemas["responses"].as_array().unwrap();
+
+        for method in service.method_list() {
+            // Answered by the Chrome extension, not by a model of ours
+            if is_extension_method(&method.name) {
+                continue;
+            }
+            let models = schemas["methods"][method.name.as_str()]
+                .as_array()
+                .unwrap_or_else(|| panic!("{} has no response model", method.name));
+            assert!(!models.is_empty(), "{} has no response model", method.name);
+            for model in models {
+                assert!(responses.contains(model), "{} isn't exported", model);
+            }
+        }
+    }
 }
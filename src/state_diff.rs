@@ -0,0 +1,231 @@
+//! Structured comparison of saved auth states.
+//!
+//! Answers "what did this login flow actually persist?" by comparing two
+//! `AuthState`s cookie by cookie (keyed by domain, path, and name, the way
+//! the browser identifies them) and localStorage key by key.
+
+use std::collections::BTreeMap;
+
+use crate::models::{
+    AuthState, CookieChange, CookieDiff, CookieId, OriginChange, SerializableCookie, StateDiff,
+    StorageDiff,
+};
+
+/// Report what changed going from state `a` to state `b`.
+pub fn diff_states(a: &AuthState, b: &AuthState) -> StateDiff {
+    let cookies = diff_cookies(&a.cookies, &b.cookies);
+    let local_storage = diff_storage(a, b);
+    let identical = cookies == CookieDiff::default() && local_storage == StorageDiff::default();
+
+    StateDiff {
+        identical,
+        cookies,
+        local_storage,
+    }
+}
+
+fn cookie_id(cookie: &SerializableCookie) -> CookieId {
+    CookieId {
+        domain: cookie.domain.clone(),
+        path: cookie.path.clone(),
+        name: cookie.name.clone(),
+    }
+}
+
+/// Attributes of `b` that differ from `a`.
+fn changed_fields(a: &SerializableCookie, b: &SerializableCookie) -> Vec<String> {
+    let mut fields = Vec::new();
+    if a.value != b.value {
+        fields.push("value");
+    }
+    if a.expires != b.expires {
+        fields.push("expires");
+    }
+    if a.secure != b.secure {
+        fields.push("secure");
+    }
+    if a.http_only != b.http_only {
+        fields.push("http_only");
+    }
+    if a.same_site != b.same_site {
+        fields.push("same_site");
+    }
+    fields.into_iter().map(String::from).collect()
+}
+
+fn diff_cookies(a: &[SerializableCookie], b: &[SerializableCookie]) -> CookieDiff {
+    let before: BTreeMap<CookieId, &SerializableCookie> =
+        a.iter().map(|c| (cookie_id(c), c)).collect();
+    let after: BTreeMap<CookieId, &SerializableCookie> =
+        b.iter().map(|c| (cookie_id(c), c)).collect();
+
+    let mut diff = CookieDiff::default();
+    for (id, old) in &before {
+        match after.get(id) {
+            None => diff.removed.push(id.clone()),
+            Some(new) => {
+                let fields = changed_fields(old, new);
+                if !fields.is_empty() {
+                    diff.changed.push(CookieChange {
+                        cookie: id.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    diff.added = after
+        .keys()
+        .filter(|id| !before.contains_key(id))
+        .cloned()
+        .collect();
+    diff
+}
+
+fn diff_storage(a: &AuthState, b: &AuthState) -> StorageDiff {
+    let before = &a.local_storage;
+    let after = &b.local_storage;
+
+    let mut added: Vec<String> = after
+        .items
+        .keys()
+        .filter(|key| !before.items.contains_key(*key))
+        .cloned()
+        .collect();
+    let mut removed: Vec<String> = before
+        .items
+        .keys()
+        .filter(|key| !after.items.contains_key(*key))
+        .cloned()
+        .collect();
+    let mut changed: Vec<String> = before
+        .items
+        .iter()
+        .filter(|(key, value)| after.items.get(*key).is_some_and(|v| v != *value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    added.sort();
+    removed.sort();
+    changed.sort();
+
+    StorageDiff {
+        origin: (before.origin != after.origin).then(|| OriginChange {
+            before: before.origin.clone(),
+            after: after.origin.clone(),
+        }),
+        added,
+        removed,
+        changed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LocalStorageState;
+    use std::collections::HashMap;
+
+    fn cookie(name: &str, value: &str) -> SerializableCookie {
+        SerializableCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: ".example.com".to_string(),
+            path: "/".to_string(),
+            expires: None,
+            secure: true,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    fn state(cookies: Vec<SerializableCookie>, items: &[(&str, &str)]) -> AuthState {
+        AuthState {
+            cookies,
+            local_storage: LocalStorageState {
+                origin: "https://example.com".to_string(),
+                items: items
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            },
+            saved_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_identical_states() {
+        let a = state(vec![cookie("sid", "1")], &[("theme", "dark")]);
+
+        let diff = diff_states(&a, &a.clone());
+
+        assert!(diff.identical);
+        assert_eq!(diff.cookies, CookieDiff::default());
+    }
+
+    #[test]
+    fn test_login_adds_and_changes_cookies() {
+        let before = state(vec![cookie("csrf", "a"), cookie("tracking", "x")], &[]);
+        let mut session = cookie("session", "secret");
+        session.http_only = true;
+        let mut csrf = cookie("csrf", "b");
+        csrf.expires = Some(1_900_000_000.0);
+        let after = state(vec![csrf, session], &[]);
+
+        let diff = diff_states(&before, &after);
+
+        assert!(!diff.identical);
+        assert_eq!(diff.cookies.added.len(), 1);
+        assert_eq!(diff.cookies.added[0].name, "session");
+        assert_eq!(diff.cookies.removed[0].name, "tracking");
+        assert_eq!(diff.cookies.changed[0].cookie.name, "csrf");
+        assert_eq!(diff.cookies.changed[0].fields, vec!["value", "expires"]);
+    }
+
+    #[test]
+    fn test_same_name_on_other_path_is_a_different_cookie() {
+        let before = state(vec![cookie("sid", "1")], &[]);
+        let mut moved = cookie("sid", "1");
+        moved.path = "/app".to_string();
+        let after = state(vec![moved], &[]);
+
+        let diff = diff_states(&before, &after);
+
+        assert_eq!(diff.cookies.added[0].path, "/app");
+        assert_eq!(diff.cookies.removed[0].path, "/");
+        assert!(diff.cookies.changed.is_empty());
+    }
+
+    #[test]
+    fn test_storage_keys_and_origin() {
+        let before = state(vec![], &[("theme", "dark"), ("cart", "[]"), ("old", "1")]);
+        let mut after = state(
+            vec![],
+            &[("theme", "dark"), ("cart", "[1]"), ("token", "t")],
+        );
+        after.local_storage.origin = "https://app.example.com".to_string();
+
+        let diff = diff_states(&before, &after).local_storage;
+
+        assert_eq!(diff.added, vec!["token"]);
+        assert_eq!(diff.removed, vec!["old"]);
+        assert_eq!(diff.changed, vec!["cart"]);
+        assert_eq!(
+            diff.origin,
+            Some(OriginChange {
+                before: "https://example.com".to_string(),
+                after: "https://app.example.com".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_diff_does_not_serialize_values() {
+        let before = state(vec![cookie("session", "old-secret")], &[("token", "abc")]);
+        let after = state(vec![cookie("session", "new-secret")], &[("token", "xyz")]);
+
+        let json = serde_json::to_string(&diff_states(&before, &after)).unwrap();
+
+        assert!(!json.contains("secret"));
+        assert!(!json.contains("xyz"));
+    }
+}
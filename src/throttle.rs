@@ -0,0 +1,161 @@
+//! Per-origin politeness for navigations.
+//!
+//! Large scraping fleets often drive many sessions through one gateway. The
+//! throttle bounds how many navigations to the same origin run at once and
+//! spaces their starts, across all sessions, so a target site sees a polite
+//! client rather than the fleet's full parallelism.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// Per-origin limits. The default disables throttling.
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleConfig {
+    /// Maximum concurrent navigations per origin (1 serializes them)
+    pub max_parallel: Option<usize>,
+    /// Minimum time between the starts of navigations to the same origin
+    pub delay: Duration,
+}
+
+impl ThrottleConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.max_parallel.is_some() || !self.delay.is_zero()
+    }
+}
+
+struct OriginSlot {
+    permits: Option<Arc<Semaphore>>,
+    /// Earliest time the next navigation may start
+    next_start: tokio::sync::Mutex<Instant>,
+}
+
+/// Held for the duration of a navigation; releases the origin slot on drop.
+pub struct OriginPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Shared per-origin throttle.
+pub struct OriginThrottle {
+    config: ThrottleConfig,
+    origins: Mutex<HashMap<String, Arc<OriginSlot>>>,
+}
+
+impl OriginThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            origins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a slot to navigate to `url`.
+    ///
+    /// URLs without a network origin (`about:`, `data:`, `file:`) are never
+    /// throttled.
+    pub async fn acquire(&self, url: &str) -> OriginPermit {
+        let unthrottled = OriginPermit { _permit: None };
+        if !self.config.is_enabled() {
+            return unthrottled;
+        }
+        let Some(origin) = origin_of(url) else {
+            return unthrottled;
+        };
+
+        let slot = self.slot(&origin);
+        let permit = match &slot.permits {
+            Some(semaphore) => Some(
+                Arc::clone(semaphore)
+                    .acquire_owned()
+                    .await
+                    .expect("origin semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        if !self.config.delay.is_zero() {
+            // Holding the lock while sleeping queues later callers behind us
+            let mut next_start = slot.next_start.lock().await;
+            tokio::time::sleep_until(*next_start).await;
+            *next_start = Instant::now() + self.config.delay;
+        }
+
+        OriginPermit { _permit: permit }
+    }
+
+    fn slot(&self, origin: &str) -> Arc<OriginSlot> {
+        let mut origins = self.origins.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(origins.entry(origin.to_string()).or_insert_with(|| {
+            Arc::new(OriginSlot {
+                permits: self
+                    .config
+                    .max_parallel
+                    .map(|n| Arc::new(Semaphore::new(n.max(1)))),
+                next_start: tokio::sync::Mutex::new(Instant::now()),
+            })
+        }))
+    }
+}
+
+/// `scheme://host[:port]` for network URLs, None otherwise.
+pub fn origin_of(url: &str) -> Option<String> {
+    let origin = url::Url::parse(url).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_of() {
+        assert_eq!(
+            origin_of("https://Example.com:443/a?b#c").as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            origin_of("http://localhost:8080/x").as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(origin_of("about:blank"), None);
+        assert_eq!(origin_of("not a url"), None);
+    }
+
+    #[tokio::test]
+    async fn test_max_parallel_serializes_same_origin() {
+        let throttle = OriginThrottle::new(ThrottleConfig {
+            max_parallel: Some(1),
+            delay: Duration::ZERO,
+        });
+
+        let first = throttle.acquire("https://example.com/a").await;
+        let blocked = tokio::time::timeout(
+            Duration::from_millis(50),
+            throttle.acquire("https://example.com/b"),
+        )
+        .await;
+        assert!(blocked.is_err(), "second navigation should wait");
+
+        // Other origins are independent
+        let _other = throttle.acquire("https://example.org/").await;
+
+        drop(first);
+        let _second = throttle.acquire("https://example.com/b").await;
+    }
+
+    #[tokio::test]
+    async fn test_delay_spaces_starts() {
+        let throttle = OriginThrottle::new(ThrottleConfig {
+            max_parallel: None,
+            delay: Duration::from_millis(100),
+        });
+
+        let started = Instant::now();
+        let _a = throttle.acquire("https://example.com/1").await;
+        let _b = throttle.acquire("https://example.com/2").await;
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,158 @@
+//! Per-session screenshot timelines.
+//!
+//! A timeline is an ordered list of screenshot artifacts with the URL and
+//! title they were taken at, so a run can be reviewed as a storyboard
+//! afterwards. Frames are added on demand or, with auto-capture on, after
+//! every `browser.open`. Only the artifact ID is kept here; the image itself
+//! lives in the artifact store and is subject to its retention policy.
+
+use std::collections::VecDeque;
+
+use chrono::Utc;
+
+use crate::models::{Artifact, TimelineFrame, TimelineIndex, TimelineTrigger};
+
+/// Frames kept per session before the oldest are dropped.
+pub const MAX_FRAMES: usize = 500;
+
+/// Screenshot history of one session.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    /// Capture a frame after every navigation
+    pub auto: bool,
+    frames: VecDeque<TimelineFrame>,
+    /// Index assigned to the next frame; indexes survive dropped frames
+    next_index: usize,
+}
+
+impl Timeline {
+    /// Append a frame, dropping the oldest past `MAX_FRAMES`.
+    pub fn push(
+        &mut self,
+        trigger: TimelineTrigger,
+        url: String,
+        title: String,
+        label: Option<String>,
+        artifact: Artifact,
+    ) -> TimelineFrame {
+        let frame = TimelineFrame {
+            index: self.next_index,
+            captured_at: Utc::now().to_rfc3339(),
+            trigger,
+            url,
+            title,
+            label,
+            artifact,
+        };
+        self.next_index += 1;
+
+        self.frames.push_back(frame.clone());
+        while self.frames.len() > MAX_FRAMES {
+            self.frames.pop_front();
+        }
+        frame
+    }
+
+    /// Drop every frame, keeping the auto-capture setting and the index
+    /// sequence. Returns the number of frames dropped.
+    pub fn clear(&mut self) -> usize {
+        let cleared = self.frames.len();
+        self.frames.clear();
+        cleared
+    }
+
+    /// Frames from `since` (a frame index) onwards.
+    pub fn index(&self, session_id: &str, since: usize) -> TimelineIndex {
+        TimelineIndex {
+            session_id: session_id.to_string(),
+            auto: self.auto,
+            dropped: self.next_index - self.frames.len(),
+            frames: self
+                .frames
+                .iter()
+                .filter(|frame| frame.index >= since)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ArtifactKind;
+
+    fn artifact(n: usize) -> Artifact {
+        Artifact {
+            id: format!("screenshot/default/{}.png", n),
+            kind: ArtifactKind::Screenshot,
+            content_type: "image/png".to_string(),
+            size: 1,
+            url: String::new(),
+            created_at: String::new(),
+        }
+    }
+
+    fn push(timeline: &mut Timeline, n: usize) -> TimelineFrame {
+        timeline.push(
+            TimelineTrigger::Navigation,
+            format!("https://example.com/{}", n),
+            format!("Page {}", n),
+            None,
+            artifact(n),
+        )
+    }
+
+    #[test]
+    fn test_frames_are_indexed_in_order() {
+        let mut timeline = Timeline::default();
+        push(&mut timeline, 0);
+        let second = push(&mut timeline, 1);
+
+        assert_eq!(second.index, 1);
+        let index = timeline.index("default", 0);
+        assert_eq!(index.frames.len(), 2);
+        assert_eq!(index.frames[0].url, "https://example.com/0");
+        assert_eq!(index.dropped, 0);
+    }
+
+    #[test]
+    fn test_index_since_skips_seen_frames() {
+        let mut timeline = Timeline::default();
+        for n in 0..3 {
+            push(&mut timeline, n);
+        }
+
+        let index = timeline.index("default", 2);
+        assert_eq!(index.frames.len(), 1);
+        assert_eq!(index.frames[0].index, 2);
+    }
+
+    #[test]
+    fn test_clear_keeps_index_sequence() {
+        let mut timeline = Timeline {
+            auto: true,
+            ..Timeline::default()
+        };
+        push(&mut timeline, 0);
+        push(&mut timeline, 1);
+
+        assert_eq!(timeline.clear(), 2);
+        let next = push(&mut timeline, 2);
+        assert_eq!(next.index, 2);
+        assert!(timeline.auto);
+    }
+
+    #[test]
+    fn test_oldest_frames_are_dropped() {
+        let mut timeline = Timeline::default();
+        for n in 0..MAX_FRAMES + 3 {
+            push(&mut timeline, n);
+        }
+
+        let index = timeline.index("default", 0);
+        assert_eq!(index.frames.len(), MAX_FRAMES);
+        assert_eq!(index.frames[0].index, 3);
+        assert_eq!(index.dropped, 3);
+    }
+}
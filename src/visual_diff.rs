@@ -0,0 +1,295 @@
+//! Pixel comparison of screenshots for visual regression checks.
+//!
+//! Two captures (PNG, JPEG or WebP) are compared pixel by pixel; a pixel
+//! counts as changed when any channel differs by more than the threshold,
+//! which absorbs JPEG noise and anti-aliasing. Changed pixels are grouped
+//! into regions on a grid of `REGION_CELL` cells, neighbouring cells joining
+//! the same region, so a caller gets a few boxes instead of thousands of
+//! pixels. Captures of different sizes are compared over the larger
+//! extent, and pixels only one of them has count as changed.
+//!
+//! The diff image shows the current capture washed out, with changed pixels
+//! in red.
+
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+
+use crate::models::{DiffRegion, ScreenshotComparison};
+
+/// Per-channel difference (0-255) tolerated unless the caller asks otherwise.
+pub const DEFAULT_THRESHOLD: u8 = 10;
+
+/// Side of the grid cells changed pixels are grouped by.
+const REGION_CELL: u32 = 16;
+
+/// Regions reported, largest first.
+const MAX_REGIONS: usize = 50;
+
+const CHANGED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+fn decode(bytes: &[u8], which: &str) -> Result<RgbaImage> {
+    Ok(image::load_from_memory(bytes)
+        .with_context(|| format!("Failed to decode {} image", which))?
+        .to_rgba8())
+}
+
+fn pixel(image: &RgbaImage, x: u32, y: u32) -> Option<[u8; 4]> {
+    (x < image.width() && y < image.height()).then(|| image.get_pixel(x, y).0)
+}
+
+fn differs(a: [u8; 4], b: [u8; 4], threshold: u8) -> bool {
+    a.iter().zip(b).any(|(a, b)| a.abs_diff(b) > threshold)
+}
+
+/// `rgba` in light grey, so red changes stand out.
+fn washed_out([r, g, b, _]: [u8; 4]) -> Rgba<u8> {
+    let luma = (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000;
+    let v = (170 + luma / 3) as u8;
+    Rgba([v, v, v, 255])
+}
+
+/// Changed pixels of one grid cell: count and bounds.
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    pixels: u64,
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+}
+
+impl Cell {
+    fn at(x: u32, y: u32) -> Self {
+        Self {
+            pixels: 1,
+            left: x,
+            top: y,
+            right: x,
+            bottom: y,
+        }
+    }
+
+    fn add(&mut self, other: &Cell) {
+        self.pixels += other.pixels;
+        self.left = self.left.min(other.left);
+        self.top = self.top.min(other.top);
+        self.right = self.right.max(other.right);
+        self.bottom = self.bottom.max(other.bottom);
+    }
+}
+
+/// Regions of touching changed cells (diagonals included), largest first
+/// and then top to bottom.
+fn regions(mut cells: Vec<Option<Cell>>, columns: usize) -> Vec<DiffRegion> {
+    let rows = cells.len() / columns.max(1);
+    let mut found: Vec<Cell> = Vec::new();
+    for start in 0..cells.len() {
+        let Some(mut region) = cells[start].take() else {
+            continue;
+        };
+        let mut pending = vec![start];
+        while let Some(index) = pending.pop() {
+            let (column, row) = (index % columns, index / columns);
+            for dy in -1i64..=1 {
+                for dx in -1i64..=1 {
+                    let (c, r) = (column as i64 + dx, row as i64 + dy);
+                    if c < 0 || r < 0 || c >= columns as i64 || r >= rows as i64 {
+                        continue;
+                    }
+                    let neighbour = r as usize * columns + c as usize;
+                    if let Some(cell) = cells[neighbour].take() {
+                        region.add(&cell);
+                        pending.push(neighbour);
+                    }
+                }
+            }
+        }
+        found.push(region);
+    }
+
+    found.sort_by(|a, b| b.pixels.cmp(&a.pixels));
+    found.truncate(MAX_REGIONS);
+    found.sort_by_key(|cell| (cell.top, cell.left));
+    found
+        .into_iter()
+        .map(|cell| DiffRegion {
+            x: cell.left,
+            y: cell.top,
+            width: cell.right - cell.left + 1,
+            height: cell.bottom - cell.top + 1,
+            pixels: cell.pixels,
+        })
+        .collect()
+}
+
+/// Compare two encoded images; returns the report and the diff image (PNG).
+pub fn compare(
+    baseline: &[u8],
+    current: &[u8],
+    threshold: u8,
+) -> Result<(ScreenshotComparison, Vec<u8>)> {
+    let baseline = decode(baseline, "baseline")?;
+    let current = decode(current, "current")?;
+    let (comparison, diff) = compare_images(&baseline, &current, threshold);
+
+    let mut png = Vec::new();
+    diff.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .context("Failed to encode diff image")?;
+    Ok((comparison, png))
+}
+
+fn compare_images(
+    baseline: &RgbaImage,
+    current: &RgbaImage,
+    threshold: u8,
+) -> (ScreenshotComparison, RgbaImage) {
+    let width = baseline.width().max(current.width());
+    let height = baseline.height().max(current.height());
+    let columns = width.div_ceil(REGION_CELL) as usize;
+    let rows = height.div_ceil(REGION_CELL) as usize;
+    let mut cells: Vec<Option<Cell>> = vec![None; columns * rows];
+    let mut diff = RgbaImage::new(width, height);
+    let mut changed = 0u64;
+
+    for y in 0..height {
+        for x in 0..width {
+            let (before, after) = (pixel(baseline, x, y), pixel(current, x, y));
+            let is_changed = match (before, after) {
+                (Some(a), Some(b)) => differs(a, b, threshold),
+                _ => true,
+            };
+            if !is_changed {
+                diff.put_pixel(x, y, washed_out(after.or(before).unwrap_or_default()));
+                continue;
+            }
+            diff.put_pixel(x, y, CHANGED);
+            changed += 1;
+            let index = (y / REGION_CELL) as usize * columns + (x / REGION_CELL) as usize;
+            match &mut cells[index] {
+                Some(cell) => cell.add(&Cell::at(x, y)),
+                empty => *empty = Some(Cell::at(x, y)),
+            }
+        }
+    }
+
+    let total = u64::from(width) * u64::from(height);
+    let mismatch = if total == 0 {
+        0.0
+    } else {
+        changed as f64 * 100.0 / total as f64
+    };
+    let comparison = ScreenshotComparison {
+        width,
+        height,
+        same_size: baseline.dimensions() == current.dimensions(),
+        threshold,
+        changed_pixels: changed,
+        total_pixels: total,
+        mismatch_percent: (mismatch * 1000.0).round() / 1000.0,
+        regions: regions(cells, columns),
+        passed: None,
+        diff: None,
+        diff_path: None,
+        artifact: None,
+    };
+    (comparison, diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]))
+    }
+
+    fn paint(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32) {
+        for py in y..y + height {
+            for px in x..x + width {
+                image.put_pixel(px, py, Rgba([0, 0, 0, 255]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_within_threshold() {
+        let baseline = blank(40, 30);
+        let mut current = blank(40, 30);
+        current.put_pixel(3, 3, Rgba([250, 250, 250, 255]));
+
+        let (comparison, diff) = compare_images(&baseline, &current, DEFAULT_THRESHOLD);
+        assert_eq!(comparison.changed_pixels, 0);
+        assert_eq!(comparison.mismatch_percent, 0.0);
+        assert!(comparison.regions.is_empty());
+        assert!(comparison.same_size);
+        assert_ne!(*diff.get_pixel(3, 3), CHANGED);
+
+        let (strict, _) = compare_images(&baseline, &current, 0);
+        assert_eq!(strict.changed_pixels, 1);
+    }
+
+    #[test]
+    fn test_changed_regions() {
+        let baseline = blank(100, 100);
+        let mut current = blank(100, 100);
+        // Two touching blocks across a cell border make one region
+        paint(&mut current, 10, 10, 10, 5);
+        paint(&mut current, 20, 12, 4, 4);
+        // A far one is separate
+        paint(&mut current, 80, 90, 5, 5);
+
+        let (comparison, diff) = compare_images(&baseline, &current, DEFAULT_THRESHOLD);
+        assert_eq!(comparison.changed_pixels, 50 + 16 + 25);
+        assert_eq!(comparison.total_pixels, 10_000);
+        assert_eq!(comparison.mismatch_percent, 0.91);
+        assert_eq!(
+            comparison.regions,
+            vec![
+                DiffRegion {
+                    x: 10,
+                    y: 10,
+                    width: 14,
+                    height: 6,
+                    pixels: 66,
+                },
+                DiffRegion {
+                    x: 80,
+                    y: 90,
+                    width: 5,
+                    height: 5,
+                    pixels: 25,
+                },
+            ]
+        );
+        assert_eq!(*diff.get_pixel(12, 12), CHANGED);
+        assert_ne!(*diff.get_pixel(50, 50), CHANGED);
+    }
+
+    #[test]
+    fn test_size_mismatch_counts_extra_area() {
+        let baseline = blank(20, 20);
+        let current = blank(20, 25);
+
+        let (comparison, diff) = compare_images(&baseline, &current, DEFAULT_THRESHOLD);
+        assert!(!comparison.same_size);
+        assert_eq!((comparison.width, comparison.height), (20, 25));
+        assert_eq!(comparison.changed_pixels, 100);
+        assert_eq!(comparison.regions.len(), 1);
+        assert_eq!(comparison.regions[0].y, 20);
+        assert_eq!(*diff.get_pixel(0, 24), CHANGED);
+    }
+
+    #[test]
+    fn test_compare_encoded() {
+        let mut png = Vec::new();
+        blank(8, 8)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let (comparison, diff) = compare(&png, &png, DEFAULT_THRESHOLD).unwrap();
+        assert_eq!(comparison.changed_pixels, 0);
+        assert!(diff.starts_with(b"\x89PNG"));
+        assert!(compare(b"not an image", &png, DEFAULT_THRESHOLD).is_err());
+    }
+}